@@ -9,6 +9,7 @@ use imgal::threshold::global::otsu_value;
 const SIZE: usize = 32;
 const THREADS: Option<usize> = Some(0);
 
+#[allow(clippy::needless_late_init)]
 fn sim_coloc_data(size: usize, n_dims: usize) -> Result<(ArrayD<f64>, ArrayD<f64>), ImgalError> {
     let a_pos = (size / 2) as u16;
     let b_pos = a_pos + 5;
@@ -54,12 +55,12 @@ fn bench_saca_2d(c: &mut Criterion) {
     let mut group = c.benchmark_group("saca_2d");
     group.bench_function("Parallel", |b| {
         b.iter(|| {
-            let _ = saca_2d(&ch_a, &ch_b, ta, tb, THREADS).unwrap();
+            let _ = saca_2d(&ch_a, &ch_b, ta, tb, None, THREADS).unwrap();
         });
     });
     group.bench_function("Sequential", |b| {
         b.iter(|| {
-            let _ = saca_2d(&ch_a, &ch_b, ta, tb, Some(1)).unwrap();
+            let _ = saca_2d(&ch_a, &ch_b, ta, tb, None, Some(1)).unwrap();
         });
     });
     group.finish();
@@ -75,12 +76,12 @@ fn bench_saca_3d(c: &mut Criterion) {
     group.sample_size(10);
     group.bench_function("Parallel", |b| {
         b.iter(|| {
-            let _ = saca_3d(&ch_a, &ch_b, ta, tb, THREADS).unwrap();
+            let _ = saca_3d(&ch_a, &ch_b, ta, tb, None, THREADS).unwrap();
         });
     });
     group.bench_function("Sequential", |b| {
         b.iter(|| {
-            let _ = saca_3d(&ch_a, &ch_b, ta, tb, Some(1)).unwrap();
+            let _ = saca_3d(&ch_a, &ch_b, ta, tb, None, Some(1)).unwrap();
         });
     });
     group.finish();
@@ -92,7 +93,7 @@ fn bench_saca_significance_mask(c: &mut Criterion) {
     let ch_b = ch_b.into_dimensionality::<Ix2>().unwrap();
     let ta = otsu_value(&ch_a, None, None).unwrap();
     let tb = otsu_value(&ch_b, None, None).unwrap();
-    let z = saca_2d(&ch_a, &ch_b, ta, tb, Some(0)).unwrap();
+    let z = saca_2d(&ch_a, &ch_b, ta, tb, None, Some(0)).unwrap();
     let mut group = c.benchmark_group("saca_significance_mask");
     group.bench_function("Parallel", |b| {
         b.iter(|| {
@@ -0,0 +1,9 @@
+//! Pixel classification functions.
+//!
+//! This module provides functions for post-processing the outputs of pixel
+//! classifiers, such as converting multi-class probability maps into label
+//! images for label-based measurements.
+
+mod postprocess;
+
+pub use postprocess::postprocess;
@@ -1,9 +1,28 @@
-//! Colocalization analysis functions (2D and 3D).
+//! Colocalization analysis functions (2D, 3D and n-dimensional).
 
+mod object_coloc;
+mod pairwise;
+mod report;
 mod roi_coloc;
 mod saca;
+mod spearman;
 
+pub use object_coloc::ObjectPair;
+pub use object_coloc::object_coloc;
+pub use pairwise::ColocCoefficient;
+pub use pairwise::pairwise_matrix;
+pub use pairwise::pairwise_roi_matrix;
+pub use report::ColocReport;
+pub use report::coloc_report;
+pub use report::coloc_roi_report;
 pub use roi_coloc::pearson_roi_coloc;
+pub use saca::SacaOutput;
 pub use saca::saca_2d;
+pub use saca::saca_2d_full;
 pub use saca::saca_3d;
+pub use saca::saca_3d_full;
+pub use saca::saca_nd;
+pub use saca::saca_nd_full;
 pub use saca::saca_significance_mask;
+pub use spearman::spearman_coloc;
+pub use spearman::spearman_roi_coloc;
@@ -0,0 +1,161 @@
+use std::collections::HashMap;
+
+use ndarray::{Array2, ArrayBase, AsArray, Axis, Dimension, ViewRepr, Zip};
+use rayon::prelude::*;
+
+use crate::prelude::*;
+use crate::spatial::KDTree;
+use crate::spatial::roi::roi_cloud_map;
+use crate::validate::check_shapes;
+
+/// The pairing result of an object in the first label image with its nearest
+/// object in the second label image.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ObjectPair {
+    /// The label ID of the object in the first label image.
+    pub label_a: u64,
+    /// The label ID of the paired object in the second label image. `None` if
+    /// no object centroid is within the maximum distance.
+    pub label_b: Option<u64>,
+    /// The Euclidean distance between the object centroids. `NaN` if the
+    /// object is unpaired.
+    pub distance: f64,
+    /// The fraction of the first object's pixels that overlap the paired
+    /// object.
+    pub overlap_a: f64,
+    /// The fraction of the paired object's pixels that overlap the first
+    /// object.
+    pub overlap_b: f64,
+}
+
+/// Compute object-based colocalization between two n-dimensional label images.
+///
+/// # Description
+///
+/// Computes object-based colocalization between the objects (*e.g.* puncta or
+/// vesicles) of two channels, given as label images. The centroid of each
+/// object is computed and every object in `labels_a` is paired with the
+/// object in `labels_b` with the nearest centroid, searched with a `KDTree`
+/// within `max_distance`. For each pair the centroid distance and the overlap
+/// fractions are reported:
+///
+/// ```text
+/// overlap_a = |A ∩ B| / |A|
+/// overlap_b = |A ∩ B| / |B|
+/// ```
+///
+/// Where `|A|` and `|B|` are the pixel counts of the paired objects. The
+/// pairing is not one-to-one, multiple objects in `labels_a` can be paired
+/// with the same object in `labels_b`. Swap the label images to pair the
+/// objects of `labels_b`. The label `0` is the background.
+///
+/// # Arguments
+///
+/// * `labels_a`: The first n-dimensional label image.
+/// * `labels_b`: The second n-dimensional label image.
+/// * `max_distance`: The maximum centroid distance in pixels for objects to be
+///   paired.
+/// * `threads`: The requested number of threads to use for parallel execution.
+///   If `None` or `Some(1)` sequential execution is used. If `Some(0)`, then
+///   the maximum available parallelism is used. Thread counts are clamped to
+///   the systems maximum.
+///
+/// # Returns
+///
+/// * `Ok(HashMap<u64, ObjectPair>)`: A `HashMap` where the keys are the label
+///   IDs of `labels_a` and the values are the pairing results for each object
+///   respectively.
+/// * `Err(ImgalError)`: If the shapes of `labels_a` and `labels_b` do not
+///   match. If `max_distance < 0.0`.
+pub fn object_coloc<'a, A, D>(
+    labels_a: A,
+    labels_b: A,
+    max_distance: f64,
+    threads: Option<usize>,
+) -> Result<HashMap<u64, ObjectPair>, ImgalError>
+where
+    A: AsArray<'a, u64, D>,
+    D: Dimension,
+{
+    let labels_a: ArrayBase<ViewRepr<&'a u64>, D> = labels_a.into();
+    let labels_b: ArrayBase<ViewRepr<&'a u64>, D> = labels_b.into();
+    check_shapes("labels_a", labels_a.shape(), "labels_b", labels_b.shape())?;
+    if max_distance < 0.0 {
+        return Err(ImgalError::InvalidParameterValueOutsideRange {
+            param_name: "max_distance",
+            value: max_distance,
+            min: 0.0,
+            max: f64::INFINITY,
+        });
+    }
+    let rois_a = roi_cloud_map(labels_a.view(), threads);
+    let rois_b = roi_cloud_map(labels_b.view(), threads);
+    // the overlapping pixel counts of all object pairs
+    let mut overlaps: HashMap<(u64, u64), usize> = HashMap::new();
+    Zip::from(&labels_a).and(&labels_b).for_each(|&a, &b| {
+        if a != 0 && b != 0 {
+            *overlaps.entry((a, b)).or_insert(0) += 1;
+        }
+    });
+    let ndim = labels_a.ndim();
+    let centroid = |cloud: &Array2<usize>| -> Vec<f64> {
+        let n = cloud.dim().0 as f64;
+        cloud
+            .axis_iter(Axis(1))
+            .map(|c| c.iter().sum::<usize>() as f64 / n)
+            .collect()
+    };
+    let b_labels: Vec<u64> = rois_b.keys().cloned().collect();
+    let mut b_centroids = Array2::<f64>::zeros((b_labels.len(), ndim));
+    b_centroids
+        .axis_iter_mut(Axis(0))
+        .zip(b_labels.iter())
+        .for_each(|(mut row, k)| {
+            row.iter_mut()
+                .zip(centroid(&rois_b[k]))
+                .for_each(|(r, c)| *r = c);
+        });
+    let tree = KDTree::build(&b_centroids);
+    let pair_object = |k: u64, cloud: &Array2<usize>| -> Result<(u64, ObjectPair), ImgalError> {
+        let c_a = centroid(cloud);
+        let candidates = tree.search_for_indices(&c_a, max_distance)?;
+        let nearest = candidates
+            .iter()
+            .map(|&i| {
+                let dist_sq = b_centroids
+                    .row(i)
+                    .iter()
+                    .zip(c_a.iter())
+                    .map(|(b, a)| (b - a).powi(2))
+                    .sum::<f64>();
+                (i, dist_sq)
+            })
+            .min_by(|x, y| x.1.total_cmp(&y.1).then(b_labels[x.0].cmp(&b_labels[y.0])));
+        let pair = match nearest {
+            Some((i, dist_sq)) => {
+                let label_b = b_labels[i];
+                let shared = overlaps.get(&(k, label_b)).cloned().unwrap_or(0) as f64;
+                ObjectPair {
+                    label_a: k,
+                    label_b: Some(label_b),
+                    distance: dist_sq.sqrt(),
+                    overlap_a: shared / cloud.dim().0 as f64,
+                    overlap_b: shared / rois_b[&label_b].dim().0 as f64,
+                }
+            }
+            None => ObjectPair {
+                label_a: k,
+                label_b: None,
+                distance: f64::NAN,
+                overlap_a: 0.0,
+                overlap_b: 0.0,
+            },
+        };
+        Ok((k, pair))
+    };
+    par!(threads,
+        seq_exp: rois_a.iter().map(|(&k, v)| pair_object(k, v))
+            .collect::<Result<HashMap<u64, ObjectPair>, ImgalError>>(),
+        par_exp: rois_a.par_iter().map(|(&k, v)| pair_object(k, v))
+            .collect::<Result<HashMap<u64, ObjectPair>, ImgalError>>())
+}
@@ -0,0 +1,254 @@
+use std::collections::HashMap;
+
+use ndarray::{Array2, ArrayBase, AsArray, Axis, Dimension, IxDyn, RemoveAxis, ViewRepr};
+use rayon::prelude::*;
+
+use crate::colocalization::report::centered_correlation;
+use crate::prelude::*;
+use crate::statistics::average_ranks;
+use crate::statistics::weighted_kendall_tau_b;
+use crate::validate::{check_axis, check_roi_bounds};
+
+/// The colocalization coefficient computed by `pairwise_matrix`.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum ColocCoefficient {
+    /// The Pearson correlation coefficient.
+    #[default]
+    Pearson,
+    /// The Spearman rank correlation coefficient.
+    Spearman,
+    /// The Manders' coefficient M1, entry `(i, j)` is the fraction of the
+    /// channel `i` intensity in pixels where channel `j` is above its
+    /// threshold. The Manders' M2 of the pair is entry `(j, i)`.
+    Manders,
+    /// Li's intensity correlation quotient (ICQ), ranging from `-0.5` to `0.5`.
+    LiIcq,
+    /// The Kendall's Tau-b rank correlation coefficient, entry `(i, j)` is the
+    /// coefficient of channel `i` with channel `j`.
+    KendallTau,
+}
+
+/// Compute a colocalization coefficient for all channel pairs of a
+/// multi-channel n-dimensional image.
+///
+/// # Description
+///
+/// Computes the chosen colocalization coefficient (see `coloc_report`) of
+/// every pair of channels along the channel `axis` and returns a `(ch, ch)`
+/// matrix, so that an `N` channel panel does not require `N²` separate calls.
+/// Each channel is converted once and the ranks used by the Spearman
+/// coefficient are computed once per channel. The Pearson, Spearman and Li's
+/// ICQ matrices are symmetric, so only the upper triangle is computed. The
+/// Kendall's Tau-b entries are computed for each ordered channel pair with
+/// `statistics::weighted_kendall_tau_b`, as in `coloc_report`. The Manders'
+/// matrix is asymmetric:
+///
+/// ```text
+/// M[i, j] = Σ(aᵢ, where aⱼ > thresholdⱼ) / Σaᵢ
+/// ```
+///
+/// Channel pairs are computed in parallel. Coefficients that are undefined
+/// for the data (*e.g.* the correlation of a constant channel) are `NaN`.
+///
+/// # Arguments
+///
+/// * `data`: The n-dimensional multi-channel image.
+/// * `coefficient`: The colocalization coefficient. If `None`, then
+///   `coefficient = ColocCoefficient::Pearson`.
+/// * `thresholds`: The intensity threshold of each channel for the Manders'
+///   coefficients. If `None`, then all thresholds are `0.0`.
+/// * `axis`: The channel axis. If `None`, then `axis = 0`.
+/// * `threads`: The requested number of threads to use for parallel execution.
+///   If `None` or `Some(1)` sequential execution is used. If `Some(0)`, then
+///   the maximum available parallelism is used. Thread counts are clamped to
+///   the systems maximum.
+///
+/// # Returns
+///
+/// * `Ok(Array2<f64>)`: The coefficient matrix with shape `(ch, ch)`.
+/// * `Err(ImgalError)`: If `axis >= data.ndim()`. If the length of
+///   `thresholds` does not match the number of channels. If a channel has
+///   fewer than `3` pixels.
+pub fn pairwise_matrix<'a, T, A, D>(
+    data: A,
+    coefficient: Option<ColocCoefficient>,
+    thresholds: Option<&[f64]>,
+    axis: Option<usize>,
+    threads: Option<usize>,
+) -> Result<Array2<f64>, ImgalError>
+where
+    A: AsArray<'a, T, D>,
+    D: Dimension + RemoveAxis,
+    T: 'a + AsNumeric,
+{
+    let data: ArrayBase<ViewRepr<&'a T>, D> = data.into();
+    let axis = axis.unwrap_or(0);
+    check_axis(axis, data.ndim())?;
+    let channels: Vec<Vec<f64>> = data
+        .axis_iter(Axis(axis))
+        .map(|ch| ch.iter().map(|v| v.to_f64()).collect())
+        .collect();
+    let thresholds = channel_thresholds(thresholds, channels.len())?;
+    matrix_values(
+        &channels,
+        coefficient.unwrap_or_default(),
+        &thresholds,
+        threads,
+    )
+}
+
+/// Compute a colocalization coefficient for all channel pairs of a
+/// multi-channel n-dimensional image and a ROI map.
+///
+/// # Description
+///
+/// Computes the coefficient matrix of `pairwise_matrix` for each ROI in the
+/// map, using only the pixels within the ROI. The ROIs are computed in
+/// parallel.
+///
+/// # Arguments
+///
+/// * `data`: The n-dimensional multi-channel image.
+/// * `rois`: A map of point clouds representing Regions of Interest (ROIs).
+///   The individual ROIs must have the dimensionality of `data` without the
+///   channel axis.
+/// * `coefficient`: The colocalization coefficient. If `None`, then
+///   `coefficient = ColocCoefficient::Pearson`.
+/// * `thresholds`: The intensity threshold of each channel for the Manders'
+///   coefficients. If `None`, then all thresholds are `0.0`.
+/// * `axis`: The channel axis. If `None`, then `axis = 0`.
+/// * `threads`: The requested number of threads to use for parallel execution.
+///   If `None` or `Some(1)` sequential execution is used. If `Some(0)`, then
+///   the maximum available parallelism is used. Thread counts are clamped to
+///   the systems maximum.
+///
+/// # Returns
+///
+/// * `Ok(HashMap<u64, Array2<f64>>)`: A `HashMap` where the keys are the ROI
+///   label IDs and values are the `(ch, ch)` coefficient matrices of each ROI.
+/// * `Err(ImgalError)`: If `axis >= data.ndim()`. If the length of
+///   `thresholds` does not match the number of channels. If a ROI point is out
+///   of bounds of `data` without the channel axis. If a ROI contains fewer
+///   than `3` points.
+pub fn pairwise_roi_matrix<'a, T, A, D>(
+    data: A,
+    rois: &HashMap<u64, Array2<usize>>,
+    coefficient: Option<ColocCoefficient>,
+    thresholds: Option<&[f64]>,
+    axis: Option<usize>,
+    threads: Option<usize>,
+) -> Result<HashMap<u64, Array2<f64>>, ImgalError>
+where
+    A: AsArray<'a, T, D>,
+    D: Dimension,
+    T: 'a + AsNumeric,
+{
+    let data: ArrayBase<ViewRepr<&'a T>, IxDyn> = data.into().into_dyn();
+    let axis = axis.unwrap_or(0);
+    check_axis(axis, data.ndim())?;
+    let mut shape = data.shape().to_vec();
+    let n_ch = shape.remove(axis);
+    check_roi_bounds(rois, &shape)?;
+    let thresholds = channel_thresholds(thresholds, n_ch)?;
+    let coefficient = coefficient.unwrap_or_default();
+    let per_roi_matrix = |k: u64, v: &Array2<usize>| -> Result<(u64, Array2<f64>), ImgalError> {
+        let channels: Vec<Vec<f64>> = data
+            .axis_iter(Axis(axis))
+            .map(|ch| {
+                v.lanes(Axis(1))
+                    .into_iter()
+                    .map(|p| ch[IxDyn(&p.to_vec())].to_f64())
+                    .collect()
+            })
+            .collect();
+        Ok((k, matrix_values(&channels, coefficient, &thresholds, None)?))
+    };
+    par!(threads,
+        seq_exp: rois.iter().map(|(&k, v)| per_roi_matrix(k, v))
+            .collect::<Result<HashMap<u64, Array2<f64>>, ImgalError>>(),
+        par_exp: rois.into_par_iter().map(|(&k, v)| per_roi_matrix(k, v))
+            .collect::<Result<HashMap<u64, Array2<f64>>, ImgalError>>())
+}
+
+/// Validate the per-channel thresholds, all `0.0` if `None`.
+fn channel_thresholds(thresholds: Option<&[f64]>, n_ch: usize) -> Result<Vec<f64>, ImgalError> {
+    match thresholds {
+        Some(t) if t.len() != n_ch => Err(ImgalError::MismatchedArrayLengths {
+            a_arr_name: "thresholds",
+            a_arr_len: t.len(),
+            b_arr_name: "channels",
+            b_arr_len: n_ch,
+        }),
+        Some(t) => Ok(t.to_vec()),
+        None => Ok(vec![0.0; n_ch]),
+    }
+}
+
+/// Compute the coefficient matrix of the channel value buffers.
+fn matrix_values(
+    channels: &[Vec<f64>],
+    coefficient: ColocCoefficient,
+    thresholds: &[f64],
+    threads: Option<usize>,
+) -> Result<Array2<f64>, ImgalError> {
+    let n_ch = channels.len();
+    let n = channels.first().map_or(0, |c| c.len());
+    if n <= 2 {
+        return Err(ImgalError::InvalidArrayLengthMinimum {
+            arr_name: "data",
+            arr_len: n,
+            min_len: 3,
+        });
+    }
+    let ranks: Vec<Vec<f64>> = match coefficient {
+        ColocCoefficient::Spearman => channels.iter().map(|c| average_ranks(c)).collect(),
+        _ => Vec::new(),
+    };
+    let ones = match coefficient {
+        ColocCoefficient::KendallTau => vec![1.0; n],
+        _ => Vec::new(),
+    };
+    let symmetric = matches!(
+        coefficient,
+        ColocCoefficient::Pearson | ColocCoefficient::Spearman | ColocCoefficient::LiIcq
+    );
+    let pairs: Vec<(usize, usize)> = (0..n_ch)
+        .flat_map(|i| (0..n_ch).map(move |j| (i, j)))
+        .filter(|&(i, j)| !symmetric || i <= j)
+        .collect();
+    let pair_value = |&(i, j): &(usize, usize)| -> Result<f64, ImgalError> {
+        let (a, b) = (&channels[i], &channels[j]);
+        Ok(match coefficient {
+            ColocCoefficient::Pearson => centered_correlation(a, b),
+            ColocCoefficient::Spearman => centered_correlation(&ranks[i], &ranks[j]),
+            ColocCoefficient::Manders => {
+                let (num, denom) = a.iter().zip(b.iter()).fold((0.0, 0.0), |acc, (&x, &y)| {
+                    (acc.0 + if y > thresholds[j] { x } else { 0.0 }, acc.1 + x)
+                });
+                if denom != 0.0 { num / denom } else { f64::NAN }
+            }
+            ColocCoefficient::LiIcq => {
+                let mean_a = a.iter().sum::<f64>() / n as f64;
+                let mean_b = b.iter().sum::<f64>() / n as f64;
+                let n_pos = a
+                    .iter()
+                    .zip(b.iter())
+                    .filter(|&(&x, &y)| (x - mean_a) * (y - mean_b) > 0.0)
+                    .count();
+                n_pos as f64 / n as f64 - 0.5
+            }
+            ColocCoefficient::KendallTau => weighted_kendall_tau_b(a, b, &ones)?,
+        })
+    };
+    let values: Vec<f64> = par!(threads,
+        seq_exp: pairs.iter().map(pair_value).collect::<Result<_, ImgalError>>(),
+        par_exp: pairs.par_iter().map(pair_value).collect::<Result<_, ImgalError>>())?;
+    let mut matrix = Array2::<f64>::zeros((n_ch, n_ch));
+    pairs.iter().zip(values).for_each(|(&(i, j), v)| {
+        matrix[[i, j]] = v;
+        if symmetric {
+            matrix[[j, i]] = v;
+        }
+    });
+    Ok(matrix)
+}
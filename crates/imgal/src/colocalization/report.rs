@@ -0,0 +1,253 @@
+use std::collections::HashMap;
+
+use ndarray::{Array2, ArrayBase, AsArray, Axis, Dimension, IxDyn, ViewRepr};
+use rayon::prelude::*;
+
+use crate::prelude::*;
+use crate::statistics::average_ranks;
+use crate::statistics::weighted_kendall_tau_b;
+use crate::validate::{check_roi_bounds, check_shapes};
+
+/// The colocalization coefficients of an image pair.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ColocReport {
+    /// The Pearson correlation coefficient.
+    pub pearson: f64,
+    /// The Spearman rank correlation coefficient.
+    pub spearman: f64,
+    /// The Manders' coefficient M1, the fraction of the first channel
+    /// intensity in pixels where the second channel is above its threshold.
+    pub manders_m1: f64,
+    /// The Manders' coefficient M2, the fraction of the second channel
+    /// intensity in pixels where the first channel is above its threshold.
+    pub manders_m2: f64,
+    /// Li's intensity correlation quotient (ICQ), ranging from `-0.5` to
+    /// `0.5`.
+    pub li_icq: f64,
+    /// The Kendall's Tau-b rank correlation coefficient.
+    pub kendall_tau: f64,
+}
+
+/// Compute a report of common colocalization coefficients between two
+/// n-dimensional images.
+///
+/// # Description
+///
+/// Computes the Pearson and Spearman correlation coefficients, the Manders'
+/// coefficients M1 and M2, Li's intensity correlation quotient (ICQ) and the
+/// Kendall's Tau-b rank correlation coefficient of an image pair. The
+/// intensity sums are shared between the coefficients in two passes over the
+/// data, and the ranks are computed once for both channels:
+///
+/// ```text
+/// M1 = Σ(aᵢ, where bᵢ > threshold_b) / Σaᵢ
+/// M2 = Σ(bᵢ, where aᵢ > threshold_a) / Σbᵢ
+/// ICQ = |{i : (aᵢ - mean(a)) × (bᵢ - mean(b)) > 0}| / n - 0.5
+/// ```
+///
+/// Coefficients that are undefined for the data (*e.g.* the correlation of a
+/// constant image) are `NaN`.
+///
+/// # Arguments
+///
+/// * `data_a`: The first n-dimensional image.
+/// * `data_b`: The second n-dimensional image.
+/// * `threshold_a`: The intensity threshold of `data_a` for the Manders'
+///   coefficient M2. If `None`, then `threshold_a = 0.0`.
+/// * `threshold_b`: The intensity threshold of `data_b` for the Manders'
+///   coefficient M1. If `None`, then `threshold_b = 0.0`.
+/// * `threads`: The requested number of threads to use for parallel execution.
+///   If `None` or `Some(1)` sequential execution is used. If `Some(0)`, then
+///   the maximum available parallelism is used. Thread counts are clamped to
+///   the systems maximum.
+///
+/// # Returns
+///
+/// * `Ok(ColocReport)`: The colocalization coefficients of the image pair.
+/// * `Err(ImgalError)`: If the shapes of `data_a` and `data_b` do not match. If
+///   `data_a.len()` is <= 2.
+///
+/// # Reference
+///
+/// <https://doi.org/10.1111/j.1365-2818.1993.tb03313.x>
+/// <https://doi.org/10.1523/JNEUROSCI.3573-03.2004>
+pub fn coloc_report<'a, T, A, D>(
+    data_a: A,
+    data_b: A,
+    threshold_a: Option<f64>,
+    threshold_b: Option<f64>,
+    threads: Option<usize>,
+) -> Result<ColocReport, ImgalError>
+where
+    A: AsArray<'a, T, D>,
+    D: Dimension,
+    T: 'a + AsNumeric,
+{
+    let data_a: ArrayBase<ViewRepr<&'a T>, D> = data_a.into();
+    let data_b: ArrayBase<ViewRepr<&'a T>, D> = data_b.into();
+    check_shapes("data_a", data_a.shape(), "data_b", data_b.shape())?;
+    let vals_a: Vec<f64> = data_a.iter().map(|v| v.to_f64()).collect();
+    let vals_b: Vec<f64> = data_b.iter().map(|v| v.to_f64()).collect();
+    report_values(
+        &vals_a,
+        &vals_b,
+        threshold_a.unwrap_or(0.0),
+        threshold_b.unwrap_or(0.0),
+        threads,
+    )
+}
+
+/// Compute a report of common colocalization coefficients between two
+/// n-dimensional images and a ROI map.
+///
+/// # Description
+///
+/// Computes the colocalization coefficients of `coloc_report` for each ROI in
+/// the map, using only the pixels within the ROI. Returning a `HashMap` of
+/// colocalization reports and ROI label IDs.
+///
+/// # Arguments
+///
+/// * `data_a`: The first n-dimensional image.
+/// * `data_b`: The second n-dimensional image.
+/// * `rois`: A map of point clouds representing Regions of Interest (ROIs).
+///   The individual ROIs must have the same dimensionality as the input data.
+/// * `threshold_a`: The intensity threshold of `data_a` for the Manders'
+///   coefficient M2. If `None`, then `threshold_a = 0.0`.
+/// * `threshold_b`: The intensity threshold of `data_b` for the Manders'
+///   coefficient M1. If `None`, then `threshold_b = 0.0`.
+/// * `threads`: The requested number of threads to use for parallel execution.
+///   If `None` or `Some(1)` sequential execution is used. If `Some(0)`, then
+///   the maximum available parallelism is used. Thread counts are clamped to
+///   the systems maximum.
+///
+/// # Returns
+///
+/// * `Ok(HashMap<u64, ColocReport>)`: A `HashMap` where the keys are the ROI
+///   label IDs and values are the colocalization reports for each ROI
+///   respectively.
+/// * `Err(ImgalError)`: If the shapes of `data_a` and `data_b` do not match. If
+///   a ROI point is out of bounds of `data_a`. If a ROI contains <= 2 points.
+pub fn coloc_roi_report<'a, T, A, D>(
+    data_a: A,
+    data_b: A,
+    rois: &HashMap<u64, Array2<usize>>,
+    threshold_a: Option<f64>,
+    threshold_b: Option<f64>,
+    threads: Option<usize>,
+) -> Result<HashMap<u64, ColocReport>, ImgalError>
+where
+    A: AsArray<'a, T, D>,
+    D: Dimension,
+    T: 'a + AsNumeric,
+{
+    let data_a: ArrayBase<ViewRepr<&'a T>, IxDyn> = data_a.into().into_dyn();
+    let data_b: ArrayBase<ViewRepr<&'a T>, IxDyn> = data_b.into().into_dyn();
+    check_shapes("data_a", data_a.shape(), "data_b", data_b.shape())?;
+    check_roi_bounds(rois, data_a.shape())?;
+    let threshold_a = threshold_a.unwrap_or(0.0);
+    let threshold_b = threshold_b.unwrap_or(0.0);
+    let per_roi_report = |k: u64, v: &Array2<usize>| -> Result<(u64, ColocReport), ImgalError> {
+        let n = v.dim().0;
+        let mut buf_a: Vec<f64> = Vec::with_capacity(n);
+        let mut buf_b: Vec<f64> = Vec::with_capacity(n);
+        v.lanes(Axis(1)).into_iter().for_each(|p| {
+            let pos = p.to_vec();
+            buf_a.push(data_a[IxDyn(&pos)].to_f64());
+            buf_b.push(data_b[IxDyn(&pos)].to_f64());
+        });
+        let report = report_values(&buf_a, &buf_b, threshold_a, threshold_b, None)?;
+        Ok((k, report))
+    };
+    par!(threads,
+        seq_exp: rois.iter().map(|(&k, v)| per_roi_report(k, v))
+            .collect::<Result<HashMap<u64, ColocReport>, ImgalError>>(),
+        par_exp: rois.into_par_iter().map(|(&k, v)| per_roi_report(k, v))
+            .collect::<Result<HashMap<u64, ColocReport>, ImgalError>>())
+}
+
+/// Compute the colocalization coefficients of two value buffers.
+fn report_values(
+    a: &[f64],
+    b: &[f64],
+    threshold_a: f64,
+    threshold_b: f64,
+    threads: Option<usize>,
+) -> Result<ColocReport, ImgalError> {
+    let n = a.len();
+    if n <= 2 {
+        return Err(ImgalError::InvalidArrayLengthMinimum {
+            arr_name: "data_a",
+            arr_len: n,
+            min_len: 3,
+        });
+    }
+    // first pass, the intensity sums and the Manders' numerators
+    let zero = || [0.0; 4];
+    let pass_1 = |mut acc: [f64; 4], (&a, &b): (&f64, &f64)| {
+        acc[0] += a;
+        acc[1] += b;
+        if b > threshold_b {
+            acc[2] += a;
+        }
+        if a > threshold_a {
+            acc[3] += b;
+        }
+        acc
+    };
+    let add = |x: [f64; 4], y: [f64; 4]| [x[0] + y[0], x[1] + y[1], x[2] + y[2], x[3] + y[3]];
+    let [sum_a, sum_b, m1_num, m2_num] = par!(threads,
+        seq_exp: a.iter().zip(b.iter()).fold(zero(), pass_1),
+        par_exp: a.par_iter().zip(b.par_iter()).fold(zero, pass_1).reduce(zero, add));
+    let mean_a = sum_a / n as f64;
+    let mean_b = sum_b / n as f64;
+    // second pass, the centered sums for Pearson and Li's ICQ
+    let pass_2 = |mut acc: [f64; 4], (&a, &b): (&f64, &f64)| {
+        let (da, db) = (a - mean_a, b - mean_b);
+        acc[0] += da * da;
+        acc[1] += db * db;
+        acc[2] += da * db;
+        if da * db > 0.0 {
+            acc[3] += 1.0;
+        }
+        acc
+    };
+    let [sq_a, sq_b, cross, n_pos] = par!(threads,
+        seq_exp: a.iter().zip(b.iter()).fold(zero(), pass_2),
+        par_exp: a.par_iter().zip(b.par_iter()).fold(zero, pass_2).reduce(zero, add));
+    let ratio = |num: f64, denom: f64| if denom != 0.0 { num / denom } else { f64::NAN };
+    let (rank_a, rank_b) = par!(threads,
+        seq_exp: (average_ranks(a), average_ranks(b)),
+        par_exp: rayon::join(|| average_ranks(a), || average_ranks(b)));
+    let ones = vec![1.0; n];
+    let kendall_tau = weighted_kendall_tau_b(a, b, &ones)?;
+    Ok(ColocReport {
+        pearson: ratio(cross, (sq_a * sq_b).sqrt()),
+        spearman: centered_correlation(&rank_a, &rank_b),
+        manders_m1: ratio(m1_num, sum_a),
+        manders_m2: ratio(m2_num, sum_b),
+        li_icq: n_pos / n as f64 - 0.5,
+        kendall_tau,
+    })
+}
+
+/// Compute the Pearson correlation coefficient of two buffers, `NaN` if either
+/// buffer is constant.
+pub(super) fn centered_correlation(a: &[f64], b: &[f64]) -> f64 {
+    let n = a.len() as f64;
+    let mean_a = a.iter().sum::<f64>() / n;
+    let mean_b = b.iter().sum::<f64>() / n;
+    let (sq_a, sq_b, cross) = a
+        .iter()
+        .zip(b.iter())
+        .fold((0.0, 0.0, 0.0), |acc, (&a, &b)| {
+            let (da, db) = (a - mean_a, b - mean_b);
+            (acc.0 + da * da, acc.1 + db * db, acc.2 + da * db)
+        });
+    let denom = (sq_a * sq_b).sqrt();
+    if denom != 0.0 {
+        cross / denom
+    } else {
+        f64::NAN
+    }
+}
@@ -5,6 +5,7 @@ use rayon::prelude::*;
 
 use crate::prelude::*;
 use crate::statistics::pearson;
+use crate::validate::{check_roi_bounds, check_shapes};
 
 /// Compute the Pearson correlation coefficient between two n-dimensional images
 /// and a ROI map.
@@ -35,8 +36,8 @@ use crate::statistics::pearson;
 /// * `Ok(HashMap<u64, f64>)`: A `HashMap` where the keys are the ROI label IDs
 ///   and values are the Pearson correlation coefficients for each ROI
 ///   respectively.
-/// * `Err(ImgalError)`: If `data_a.len() != data_b.len()`. If `data_a.len()` or
-///   `data_b.len()` is <= 2.
+/// * `Err(ImgalError)`: If the shapes of `data_a` and `data_b` do not match. If
+///   a ROI point is out of bounds of `data_a`. If a ROI contains <= 2 points.
 #[inline]
 pub fn pearson_roi_coloc<'a, T, A, D>(
     data_a: A,
@@ -51,6 +52,8 @@ where
 {
     let data_a: ArrayBase<ViewRepr<&'a T>, IxDyn> = data_a.into().into_dyn();
     let data_b: ArrayBase<ViewRepr<&'a T>, IxDyn> = data_b.into().into_dyn();
+    check_shapes("data_a", data_a.shape(), "data_b", data_b.shape())?;
+    check_roi_bounds(rois, data_a.shape())?;
     let per_roi_pearson_corr = |k: u64, v: &Array2<usize>| -> Result<(u64, f64), ImgalError> {
         let n = v.dim().0;
         let mut buf_a: Vec<T> = Vec::with_capacity(n);
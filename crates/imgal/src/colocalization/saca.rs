@@ -1,8 +1,9 @@
 use std::mem;
 
 use ndarray::{
-    Array, Array2, Array3, Array4, ArrayBase, ArrayView2, ArrayView3, ArrayViewMut1, ArrayViewMut2,
-    ArrayViewMut3, ArrayViewMut4, AsArray, Axis, Dimension, Ix2, Ix3, ViewRepr, Zip,
+    Array, Array2, Array3, Array4, ArrayBase, ArrayD, ArrayView, ArrayView2, ArrayView3,
+    ArrayViewMut1, ArrayViewMut2, ArrayViewMut3, ArrayViewMut4, AsArray, Axis, Dimension, Ix2, Ix3,
+    IxDyn, ViewRepr, Zip,
 };
 use rayon::prelude::*;
 
@@ -11,6 +12,23 @@ use crate::kernel::neighborhood::{weighted_circle_kernel, weighted_sphere_kernel
 use crate::prelude::*;
 use crate::statistics::{effective_sample_size, weighted_kendall_tau_b};
 use crate::threshold::manual::manual_mask;
+use crate::validate::check_shapes;
+
+/// The Spatially Adaptive Colocalization Analysis (SACA) output with the
+/// intermediate per-pixel maps.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SacaOutput<D: Dimension> {
+    /// The pixel-wise *z-score*, see `saca_2d`. Pixels outside of the mask are
+    /// `NaN`.
+    pub z_score: Array<f64, D>,
+    /// The weighted Kendall Tau-b of the final adaptive neighborhood. Pixels
+    /// outside of the mask are `NaN`.
+    pub tau: Array<f64, D>,
+    /// The radius of the final adaptive neighborhood in pixels (in units of
+    /// the smallest pixel spacing for `saca_nd_full`). Pixels outside of the
+    /// mask are `0`.
+    pub radius: Array<usize, D>,
+}
 
 /// Compute 2D colocalization strength with Spatially Adaptive Colocalization
 /// Analysis (SACA).
@@ -25,6 +43,8 @@ use crate::threshold::manual::manual_mask;
 /// The pixels within the neighborhood are assigned weights based on their
 /// distance from the center pixel (decreasing with distance), ranked and their
 /// colocalization coefficient computed using Kendall's Tau-b rank correlation.
+/// If a `mask` is given, the expensive adaptive kernel expansion is skipped for
+/// background pixels outside of the mask.
 ///
 /// # Arguments
 ///
@@ -36,6 +56,10 @@ use crate::threshold::manual::manual_mask;
 /// * `threshold_b`: Pixel intensity threshold value for `data_b`. Pixels below
 ///   this value are given a weight of `0.0` if the pixel is in the circular
 ///   neighborhood.
+/// * `mask`: An optional 2D boolean mask (*e.g.* of a cell or ROI) with the
+///   same shape as the input images. If `Some`, the *z-score* is only computed
+///   for pixels where the mask is `true`, and pixels where the mask is `false`
+///   are excluded from the adaptive neighborhoods.
 /// * `threads`: The requested number of threads to use for parallel execution.
 ///   If `None` or `Some(1)` sequential execution is used. If `Some(0)`, then
 ///   the maximum available parallelism is used. Thread counts are clamped to
@@ -45,8 +69,10 @@ use crate::threshold::manual::manual_mask;
 ///
 /// * `OK(Array2<f64>)`: The pixel-wise *z-score* indicating colocalization or
 ///   anti-colocalization by its sign and the degree or strength of the
-///   relationship through its absolute values.
-/// * `Err(ImgalError)`: If `data_a.shape() != data_b.shape()`.
+///   relationship through its absolute values. Pixels outside of the `mask`
+///   are `NaN`.
+/// * `Err(ImgalError)`: If `data_a.shape() != data_b.shape()`. If
+///   `mask.shape() != data_a.shape()`.
 ///
 /// # Reference
 ///
@@ -56,8 +82,58 @@ pub fn saca_2d<'a, T, A>(
     data_b: A,
     threshold_a: T,
     threshold_b: T,
+    mask: Option<ArrayView2<bool>>,
     threads: Option<usize>,
 ) -> Result<Array2<f64>, ImgalError>
+where
+    A: AsArray<'a, T, Ix2>,
+    T: 'a + AsNumeric,
+{
+    saca_2d_full(data_a, data_b, threshold_a, threshold_b, mask, threads).map(|o| o.z_score)
+}
+
+/// Compute 2D colocalization strength and the intermediate maps with
+/// Spatially Adaptive Colocalization Analysis (SACA).
+///
+/// # Description
+///
+/// Computes the pixel-wise *z-score* (see `saca_2d`) together with the
+/// weighted Kendall Tau-b and the radius of the final adaptive neighborhood of
+/// each pixel. The radius is the neighborhood size at which the kernel
+/// expansion of the pixel stopped, which is useful for debugging and
+/// visualizing the adaptive neighborhoods.
+///
+/// # Arguments
+///
+/// * `data_a`: The 2D input image corresponding to the first channel.
+/// * `data_b`: The 2D input image corresponding to the second channel.
+/// * `threshold_a`: Pixel intensity threshold value for `data_a`.
+/// * `threshold_b`: Pixel intensity threshold value for `data_b`.
+/// * `mask`: An optional 2D boolean mask with the same shape as the
+///   input images.
+/// * `threads`: The requested number of threads to use for parallel execution.
+///   If `None` or `Some(1)` sequential execution is used. If `Some(0)`, then
+///   the maximum available parallelism is used. Thread counts are clamped to
+///   the systems maximum.
+///
+/// # Returns
+///
+/// * `Ok(SacaOutput<Ix2>)`: The *z-score*, weighted Kendall Tau-b and
+///   final neighborhood radius maps.
+/// * `Err(ImgalError)`: If `data_a.shape() != data_b.shape()`. If
+///   `mask.shape() != data_a.shape()`.
+///
+/// # Reference
+///
+/// <https://doi.org/10.1109/TIP.2019.2909194>
+pub fn saca_2d_full<'a, T, A>(
+    data_a: A,
+    data_b: A,
+    threshold_a: T,
+    threshold_b: T,
+    mask: Option<ArrayView2<bool>>,
+    threads: Option<usize>,
+) -> Result<SacaOutput<Ix2>, ImgalError>
 where
     A: AsArray<'a, T, Ix2>,
     T: 'a + AsNumeric,
@@ -65,14 +141,9 @@ where
     let data_a: ArrayBase<ViewRepr<&'a T>, Ix2> = data_a.into();
     let data_b: ArrayBase<ViewRepr<&'a T>, Ix2> = data_b.into();
     let dims_a = data_a.dim();
-    let dims_b = data_b.dim();
-    if dims_a != dims_b {
-        return Err(ImgalError::MismatchedArrayShapes {
-            a_arr_name: "data_a",
-            a_shape: data_a.shape().to_vec(),
-            b_arr_name: "data_b",
-            b_shape: data_b.shape().to_vec(),
-        });
+    check_shapes("data_a", data_a.shape(), "data_b", data_b.shape())?;
+    if let Some(m) = mask {
+        check_shapes("data_a", data_a.shape(), "mask", m.shape())?;
     }
     // create kendall tau b working buffers and output container
     let mut result = Array2::<f64>::zeros(dims_a);
@@ -81,6 +152,7 @@ where
     let mut old_tau = Array2::<f64>::zeros(dims_a);
     let mut old_sqrt_n = Array2::<f64>::ones(dims_a);
     let mut stop = Array3::<f64>::zeros((dims_a.0, dims_a.1, 3));
+    let mut radius_map = Array2::<usize>::zeros(dims_a);
     // set up saca parameters, see reference on "dn" value selection for lambda
     let dn = ((dims_a.0 * dims_a.1) as f64).ln().sqrt() * 2.0;
     let lambda = dn * 1.0;
@@ -97,6 +169,7 @@ where
             data_b,
             threshold_a,
             threshold_b,
+            mask,
             result.view_mut(),
             new_tau.view_mut(),
             new_sqrt_n.view_mut(),
@@ -111,6 +184,14 @@ where
         );
         mem::swap(&mut old_tau, &mut new_tau);
         mem::swap(&mut old_sqrt_n, &mut new_sqrt_n);
+        // stopped pixels keep the radius of their accepted neighborhood
+        Zip::from(&mut radius_map)
+            .and(stop.lanes(Axis(2)))
+            .for_each(|r, ln| {
+                if ln[0] == 0.0 {
+                    *r = radius;
+                }
+            });
         size_f *= step_size;
         if s == tl {
             lower_bound_check = true;
@@ -131,7 +212,7 @@ where
             );
         }
     });
-    Ok(result)
+    Ok(masked_output(result, old_tau, radius_map, mask))
 }
 
 /// Compute 3D colocalization strength with Spatially Adaptive Colocalization
@@ -147,7 +228,8 @@ where
 /// The pixels within the neighborhood are assigned weights based on their
 /// distance from the center pixel (decreasing with distance), ranked and
 /// their colocalization coefficient computed using Kendall's Tau-b rank
-/// correlation.
+/// correlation. If a `mask` is given, the expensive adaptive kernel expansion
+/// is skipped for background pixels outside of the mask.
 ///
 /// # Arguments
 ///
@@ -159,6 +241,10 @@ where
 /// * `threshold_b`: Pixel intensity threshold value for `data_b`. Pixels below
 ///   this value are given a weight of `0.0` if the pixel is in the circular
 ///   neighborhood.
+/// * `mask`: An optional 3D boolean mask (*e.g.* of a cell or ROI) with the
+///   same shape as the input images. If `Some`, the *z-score* is only computed
+///   for pixels where the mask is `true`, and pixels where the mask is `false`
+///   are excluded from the adaptive neighborhoods.
 /// * `threads`: The requested number of threads to use for parallel execution.
 ///   If `None` or `Some(1)` sequential execution is used. If `Some(0)`, then
 ///   the maximum available parallelism is used. Thread counts are clamped to
@@ -168,8 +254,10 @@ where
 ///
 /// * `OK(Array3<f64>)`: The pixel-wise *z-score* indicating colocalization or
 ///   anti-colocalization by its sign and the degree or strength of the
-///   relationship through its absolute values.
-/// * `Err(ImgalError)`: If `data_a.shape() != data_b.shape()`.
+///   relationship through its absolute values. Pixels outside of the `mask`
+///   are `NaN`.
+/// * `Err(ImgalError)`: If `data_a.shape() != data_b.shape()`. If
+///   `mask.shape() != data_a.shape()`.
 ///
 /// # Reference
 ///
@@ -179,8 +267,58 @@ pub fn saca_3d<'a, T, A>(
     data_b: A,
     threshold_a: T,
     threshold_b: T,
+    mask: Option<ArrayView3<bool>>,
     threads: Option<usize>,
 ) -> Result<Array3<f64>, ImgalError>
+where
+    A: AsArray<'a, T, Ix3>,
+    T: 'a + AsNumeric,
+{
+    saca_3d_full(data_a, data_b, threshold_a, threshold_b, mask, threads).map(|o| o.z_score)
+}
+
+/// Compute 3D colocalization strength and the intermediate maps with
+/// Spatially Adaptive Colocalization Analysis (SACA).
+///
+/// # Description
+///
+/// Computes the pixel-wise *z-score* (see `saca_3d`) together with the
+/// weighted Kendall Tau-b and the radius of the final adaptive neighborhood of
+/// each pixel. The radius is the neighborhood size at which the kernel
+/// expansion of the pixel stopped, which is useful for debugging and
+/// visualizing the adaptive neighborhoods.
+///
+/// # Arguments
+///
+/// * `data_a`: The 3D input image corresponding to the first channel.
+/// * `data_b`: The 3D input image corresponding to the second channel.
+/// * `threshold_a`: Pixel intensity threshold value for `data_a`.
+/// * `threshold_b`: Pixel intensity threshold value for `data_b`.
+/// * `mask`: An optional 3D boolean mask with the same shape as the
+///   input images.
+/// * `threads`: The requested number of threads to use for parallel execution.
+///   If `None` or `Some(1)` sequential execution is used. If `Some(0)`, then
+///   the maximum available parallelism is used. Thread counts are clamped to
+///   the systems maximum.
+///
+/// # Returns
+///
+/// * `Ok(SacaOutput<Ix3>)`: The *z-score*, weighted Kendall Tau-b and
+///   final neighborhood radius maps.
+/// * `Err(ImgalError)`: If `data_a.shape() != data_b.shape()`. If
+///   `mask.shape() != data_a.shape()`.
+///
+/// # Reference
+///
+/// <https://doi.org/10.1109/TIP.2019.2909194>
+pub fn saca_3d_full<'a, T, A>(
+    data_a: A,
+    data_b: A,
+    threshold_a: T,
+    threshold_b: T,
+    mask: Option<ArrayView3<bool>>,
+    threads: Option<usize>,
+) -> Result<SacaOutput<Ix3>, ImgalError>
 where
     A: AsArray<'a, T, Ix3>,
     T: 'a + AsNumeric,
@@ -188,14 +326,9 @@ where
     let data_a: ArrayBase<ViewRepr<&'a T>, Ix3> = data_a.into();
     let data_b: ArrayBase<ViewRepr<&'a T>, Ix3> = data_b.into();
     let dims_a = data_a.dim();
-    let dims_b = data_b.dim();
-    if dims_a != dims_b {
-        return Err(ImgalError::MismatchedArrayShapes {
-            a_arr_name: "data_a",
-            a_shape: data_a.shape().to_vec(),
-            b_arr_name: "data_b",
-            b_shape: data_b.shape().to_vec(),
-        });
+    check_shapes("data_a", data_a.shape(), "data_b", data_b.shape())?;
+    if let Some(m) = mask {
+        check_shapes("data_a", data_a.shape(), "mask", m.shape())?;
     }
     // create kendall tau b working buffers and output container
     let mut result = Array3::<f64>::zeros(dims_a);
@@ -204,6 +337,7 @@ where
     let mut old_tau = Array3::<f64>::zeros(dims_a);
     let mut old_sqrt_n = Array3::<f64>::ones(dims_a);
     let mut stop = Array4::<f64>::zeros((dims_a.0, dims_a.1, dims_a.2, 3));
+    let mut radius_map = Array3::<usize>::zeros(dims_a);
     // set up saca parameters, see reference on "dn" value selection for lambda
     let dn = ((dims_a.0 * dims_a.1 * dims_a.2) as f64).ln().sqrt() * 2.0;
     let lambda = dn * 1.0;
@@ -220,6 +354,7 @@ where
             data_b,
             threshold_a,
             threshold_b,
+            mask,
             result.view_mut(),
             new_tau.view_mut(),
             new_sqrt_n.view_mut(),
@@ -234,6 +369,14 @@ where
         );
         mem::swap(&mut old_tau, &mut new_tau);
         mem::swap(&mut old_sqrt_n, &mut new_sqrt_n);
+        // stopped pixels keep the radius of their accepted neighborhood
+        Zip::from(&mut radius_map)
+            .and(stop.lanes(Axis(3)))
+            .for_each(|r, ln| {
+                if ln[0] == 0.0 {
+                    *r = radius;
+                }
+            });
         size_f *= step_size;
         if s == tl {
             lower_bound_check = true;
@@ -253,7 +396,250 @@ where
                 }));
         }
     });
-    Ok(result)
+    Ok(masked_output(result, old_tau, radius_map, mask))
+}
+
+/// Compute n-dimensional colocalization strength with Spatially Adaptive
+/// Colocalization Analysis (SACA) on anisotropic data.
+///
+/// # Description
+///
+/// Computes a pixel-wise *z-score* indicating colocalization and
+/// anti-colocalization strength on n-dimensional input images using the
+/// Spatially Adaptive Colocalization Analysis (SACA) framework (see `saca_2d`
+/// and `saca_3d`). Unlike `saca_2d` and `saca_3d`, which assume isotropic
+/// pixels, the adaptive neighborhood is an ellipsoid defined in physical
+/// units. The neighborhood radius `r` is measured in units of the smallest
+/// pixel spacing, and a neighbor at the pixel offset `o` is weighted by its
+/// physical distance:
+///
+/// ```text
+/// d = √(Σₖ (oₖ × spacingₖ / min(spacing))²)
+/// w = 1 - d / (r × √2.5), if d <= r
+/// ```
+///
+/// On anisotropic z-stacks (*e.g.* a z-step larger than the lateral pixel
+/// size) the neighborhood therefore spans fewer planes than rows and columns.
+/// With isotropic spacing the results are equal to `saca_2d` and `saca_3d`.
+///
+/// # Arguments
+///
+/// * `data_a`: The n-dimensional input image corresponding to the first
+///   channel.
+/// * `data_b`: The n-dimensional input image corresponding to the second
+///   channel.
+/// * `threshold_a`: Pixel intensity threshold value for `data_a`. Pixels below
+///   this value are given a weight of `0.0` if the pixel is in the ellipsoidal
+///   neighborhood.
+/// * `threshold_b`: Pixel intensity threshold value for `data_b`. Pixels below
+///   this value are given a weight of `0.0` if the pixel is in the ellipsoidal
+///   neighborhood.
+/// * `mask`: An optional n-dimensional boolean mask (*e.g.* of a cell or ROI)
+///   with the same shape as the input images. If `Some`, the *z-score* is only
+///   computed for pixels where the mask is `true`, and pixels where the mask is
+///   `false` are excluded from the adaptive neighborhoods.
+/// * `spacing`: The physical pixel spacing of each axis. If `None`, then the
+///   spacing is `1.0` for all axes (*i.e.* isotropic).
+/// * `threads`: The requested number of threads to use for parallel execution.
+///   If `None` or `Some(1)` sequential execution is used. If `Some(0)`, then
+///   the maximum available parallelism is used. Thread counts are clamped to
+///   the systems maximum.
+///
+/// # Returns
+///
+/// * `Ok(ArrayD<f64>)`: The pixel-wise *z-score* indicating colocalization or
+///   anti-colocalization by its sign and the degree or strength of the
+///   relationship through its absolute values. Pixels outside of the `mask`
+///   are `NaN`.
+/// * `Err(ImgalError)`: If `data_a.shape() != data_b.shape()`. If
+///   `mask.shape() != data_a.shape()`. If `spacing.len() != data_a.ndim()`.
+///   If `spacing` contains values `<= 0.0`.
+///
+/// # Reference
+///
+/// <https://doi.org/10.1109/TIP.2019.2909194>
+pub fn saca_nd<'a, T, A, D>(
+    data_a: A,
+    data_b: A,
+    threshold_a: T,
+    threshold_b: T,
+    mask: Option<ArrayView<bool, D>>,
+    spacing: Option<&[f64]>,
+    threads: Option<usize>,
+) -> Result<ArrayD<f64>, ImgalError>
+where
+    A: AsArray<'a, T, D>,
+    D: Dimension,
+    T: 'a + AsNumeric,
+{
+    saca_nd_full(
+        data_a,
+        data_b,
+        threshold_a,
+        threshold_b,
+        mask,
+        spacing,
+        threads,
+    )
+    .map(|o| o.z_score)
+}
+
+/// Compute n-dimensional colocalization strength and the intermediate maps
+/// with Spatially Adaptive Colocalization Analysis (SACA) on anisotropic data.
+///
+/// # Description
+///
+/// Computes the pixel-wise *z-score* (see `saca_nd`) together with the
+/// weighted Kendall Tau-b and the radius of the final adaptive neighborhood of
+/// each pixel. The radius is in units of the smallest pixel spacing.
+///
+/// # Arguments
+///
+/// * `data_a`: The n-dimensional input image corresponding to the first
+///   channel.
+/// * `data_b`: The n-dimensional input image corresponding to the second
+///   channel.
+/// * `threshold_a`: Pixel intensity threshold value for `data_a`.
+/// * `threshold_b`: Pixel intensity threshold value for `data_b`.
+/// * `mask`: An optional n-dimensional boolean mask with the same shape as the
+///   input images.
+/// * `spacing`: The physical pixel spacing of each axis. If `None`, then the
+///   spacing is `1.0` for all axes (*i.e.* isotropic).
+/// * `threads`: The requested number of threads to use for parallel execution.
+///   If `None` or `Some(1)` sequential execution is used. If `Some(0)`, then
+///   the maximum available parallelism is used. Thread counts are clamped to
+///   the systems maximum.
+///
+/// # Returns
+///
+/// * `Ok(SacaOutput<IxDyn>)`: The *z-score*, weighted Kendall Tau-b and final
+///   neighborhood radius maps.
+/// * `Err(ImgalError)`: If `data_a.shape() != data_b.shape()`. If
+///   `mask.shape() != data_a.shape()`. If `spacing.len() != data_a.ndim()`.
+///   If `spacing` contains values `<= 0.0`.
+///
+/// # Reference
+///
+/// <https://doi.org/10.1109/TIP.2019.2909194>
+pub fn saca_nd_full<'a, T, A, D>(
+    data_a: A,
+    data_b: A,
+    threshold_a: T,
+    threshold_b: T,
+    mask: Option<ArrayView<bool, D>>,
+    spacing: Option<&[f64]>,
+    threads: Option<usize>,
+) -> Result<SacaOutput<IxDyn>, ImgalError>
+where
+    A: AsArray<'a, T, D>,
+    D: Dimension,
+    T: 'a + AsNumeric,
+{
+    let data_a: ArrayBase<ViewRepr<&'a T>, D> = data_a.into();
+    let data_b: ArrayBase<ViewRepr<&'a T>, D> = data_b.into();
+    check_shapes("data_a", data_a.shape(), "data_b", data_b.shape())?;
+    if let Some(m) = &mask {
+        check_shapes("data_a", data_a.shape(), "mask", m.shape())?;
+    }
+    let shape = data_a.shape().to_vec();
+    let n_dims = shape.len();
+    let spacing = spacing.map_or(vec![1.0; n_dims], |s| s.to_vec());
+    if spacing.len() != n_dims {
+        return Err(ImgalError::MismatchedArrayLengths {
+            a_arr_name: "spacing",
+            a_arr_len: spacing.len(),
+            b_arr_name: "shape",
+            b_arr_len: n_dims,
+        });
+    }
+    if let Some(&s) = spacing.iter().find(|&&s| s <= 0.0) {
+        return Err(ImgalError::InvalidParameterValueOutsideRange {
+            param_name: "spacing",
+            value: s,
+            min: 0.0,
+            max: f64::INFINITY,
+        });
+    }
+    // scale the spacing to units of the smallest spacing
+    let min_spacing = spacing.iter().cloned().fold(f64::INFINITY, f64::min);
+    let spacing: Vec<f64> = spacing.iter().map(|s| s / min_spacing).collect();
+    let data_a = data_a.as_standard_layout();
+    let data_b = data_b.as_standard_layout();
+    let buf_a = data_a.as_slice().unwrap();
+    let buf_b = data_b.as_slice().unwrap();
+    let buf_mask: Option<Vec<bool>> = mask.as_ref().map(|m| m.iter().cloned().collect());
+    // create kendall tau b working buffers and output container
+    let n_pixels = data_a.len();
+    let mut state = SacaState {
+        result: vec![0.0; n_pixels],
+        new_tau: vec![0.0; n_pixels],
+        new_sqrt_n: vec![0.0; n_pixels],
+        old_tau: vec![0.0; n_pixels],
+        old_sqrt_n: vec![1.0; n_pixels],
+        stop: vec![[0.0; 3]; n_pixels],
+    };
+    let mut radius_map = vec![0usize; n_pixels];
+    // set up saca parameters, see reference on "dn" value selection for lambda
+    let dn = (n_pixels as f64).ln().sqrt() * 2.0;
+    let lambda = dn * 1.0;
+    let tu: usize = 15;
+    let tl: usize = 8;
+    let mut size_f: f64 = 1.0;
+    let step_size: f64 = 1.15;
+    let mut lower_bound_check = false;
+    (0..tu).for_each(|s| {
+        let radius = size_f.floor() as usize;
+        let kernel = ellipsoid_kernel(&shape, &spacing, radius);
+        single_iteration_nd(
+            buf_a,
+            buf_b,
+            threshold_a,
+            threshold_b,
+            buf_mask.as_deref(),
+            &shape,
+            &kernel,
+            &mut state,
+            dn,
+            lambda,
+            lower_bound_check,
+            threads,
+        );
+        mem::swap(&mut state.old_tau, &mut state.new_tau);
+        mem::swap(&mut state.old_sqrt_n, &mut state.new_sqrt_n);
+        // stopped pixels keep the radius of their accepted neighborhood
+        radius_map
+            .iter_mut()
+            .zip(state.stop.iter())
+            .for_each(|(r, st)| {
+                if st[0] == 0.0 {
+                    *r = radius;
+                }
+            });
+        size_f *= step_size;
+        if s == tl {
+            lower_bound_check = true;
+            let SacaState {
+                stop,
+                new_tau,
+                new_sqrt_n,
+                ..
+            } = &mut state;
+            stop.iter_mut()
+                .zip(new_tau.iter())
+                .zip(new_sqrt_n.iter())
+                .for_each(|((st, nt), ns)| {
+                    st[1] = *nt;
+                    st[2] = *ns;
+                });
+        }
+    });
+    let to_array = |v: Vec<f64>| ArrayD::from_shape_vec(IxDyn(&shape), v).unwrap();
+    Ok(masked_output(
+        to_array(state.result),
+        to_array(state.old_tau),
+        ArrayD::from_shape_vec(IxDyn(&shape), radius_map).unwrap(),
+        mask.map(|m| m.into_dyn()),
+    ))
 }
 
 /// Create a significant pixel mask from a pixel-wise *z-score* array.
@@ -298,11 +684,38 @@ where
     manual_mask(&view, q, threads)
 }
 
+/// Assemble the SACA output, the intermediate maps of pixels outside of the
+/// mask are set to `NaN` and `0`.
+fn masked_output<D: Dimension>(
+    z_score: Array<f64, D>,
+    mut tau: Array<f64, D>,
+    mut radius: Array<usize, D>,
+    mask: Option<ArrayView<bool, D>>,
+) -> SacaOutput<D> {
+    if let Some(m) = mask {
+        Zip::from(&mut tau)
+            .and(&mut radius)
+            .and(m)
+            .for_each(|t, r, &inside| {
+                if !inside {
+                    *t = f64::NAN;
+                    *r = 0;
+                }
+            });
+    }
+    SacaOutput {
+        z_score,
+        tau,
+        radius,
+    }
+}
+
 /// Fill working buffers from 2-dimensional data.
 fn fill_buffers_2d<T>(
     data_a: ArrayView2<T>,
     data_b: ArrayView2<T>,
     kernel: ArrayView2<f64>,
+    mask: Option<ArrayView2<bool>>,
     old_tau: ArrayView2<f64>,
     old_sqrt_n: ArrayView2<f64>,
     buf_a: &mut [T],
@@ -341,7 +754,8 @@ fn fill_buffers_2d<T>(
             buf_b[i] = data_b[[r, c]];
             let tau_diff_abs = (old_tau[[r, c]] - ot).abs() * on_dn;
             let w = kernel[[kr, kc]];
-            buf_w[i] = if tau_diff_abs < 1.0 {
+            let in_mask = mask.is_none_or(|m| m[[r, c]]);
+            buf_w[i] = if tau_diff_abs < 1.0 && in_mask {
                 w * (1.0 - tau_diff_abs) * (1.0 - tau_diff_abs)
             } else {
                 0.0
@@ -359,6 +773,7 @@ fn fill_buffers_3d<T>(
     data_a: ArrayView3<T>,
     data_b: ArrayView3<T>,
     kernel: ArrayView3<f64>,
+    mask: Option<ArrayView3<bool>>,
     old_tau: ArrayView3<f64>,
     old_sqrt_n: ArrayView3<f64>,
     buf_a: &mut [T],
@@ -406,7 +821,8 @@ fn fill_buffers_3d<T>(
             buf_b[i] = data_b[[p, r, c]];
             let tau_diff_abs = (old_tau[[p, r, c]] - ot).abs() * on_dn;
             let w = kernel[[kp, kr, kc]];
-            buf_w[i] = if tau_diff_abs < 1.0 {
+            let in_mask = mask.is_none_or(|m| m[[p, r, c]]);
+            buf_w[i] = if tau_diff_abs < 1.0 && in_mask {
                 w * (1.0 - tau_diff_abs) * (1.0 - tau_diff_abs)
             } else {
                 0.0
@@ -425,12 +841,77 @@ fn get_end_position(location: usize, radius: usize, boundary: usize) -> usize {
     if end >= boundary { boundary - 1 } else { end }
 }
 
+/// The n-dimensional SACA working buffers in row-major order.
+struct SacaState {
+    result: Vec<f64>,
+    new_tau: Vec<f64>,
+    new_sqrt_n: Vec<f64>,
+    old_tau: Vec<f64>,
+    old_sqrt_n: Vec<f64>,
+    stop: Vec<[f64; 3]>,
+}
+
+/// A neighborhood offset of an n-dimensional kernel, with its per axis offset,
+/// row-major flat offset and weight.
+struct KernelOffset {
+    offset: Vec<isize>,
+    flat_offset: isize,
+    weight: f64,
+}
+
+/// Create the weighted ellipsoidal kernel offsets in row-major order. The
+/// spacing is in units of the smallest spacing.
+fn ellipsoid_kernel(shape: &[usize], spacing: &[f64], radius: usize) -> Vec<KernelOffset> {
+    let n_dims = shape.len();
+    let falloff = radius as f64 * (2.5_f64).sqrt();
+    let half: Vec<isize> = spacing
+        .iter()
+        .map(|s| (radius as f64 / s).floor() as isize)
+        .collect();
+    let mut strides = vec![1isize; n_dims];
+    (0..n_dims.saturating_sub(1)).rev().for_each(|k| {
+        strides[k] = strides[k + 1] * shape[k + 1] as isize;
+    });
+    let n_offsets: usize = half.iter().map(|&h| (2 * h + 1) as usize).product();
+    (0..n_offsets)
+        .map(|i| {
+            // unravel the offset index over the kernel bounding box
+            let mut remaining = i;
+            let mut offset = vec![0isize; n_dims];
+            (0..n_dims).rev().for_each(|k| {
+                let len = (2 * half[k] + 1) as usize;
+                offset[k] = (remaining % len) as isize - half[k];
+                remaining /= len;
+            });
+            let dist = offset
+                .iter()
+                .zip(spacing.iter())
+                .map(|(&o, s)| (o as f64 * s).powi(2))
+                .sum::<f64>()
+                .sqrt();
+            let norm_dist = dist / falloff;
+            let weight = if dist <= radius as f64 && norm_dist < 1.0 {
+                1.0 - norm_dist
+            } else {
+                0.0
+            };
+            let flat_offset = offset.iter().zip(strides.iter()).map(|(o, s)| o * s).sum();
+            KernelOffset {
+                offset,
+                flat_offset,
+                weight,
+            }
+        })
+        .collect()
+}
+
 /// Single 2-dimensional SACA iteration.
 fn single_iteration_2d<T>(
     data_a: ArrayView2<T>,
     data_b: ArrayView2<T>,
     threshold_a: T,
     threshold_b: T,
+    mask: Option<ArrayView2<bool>>,
     mut result: ArrayViewMut2<f64>,
     mut new_tau: ArrayViewMut2<f64>,
     mut new_sqrt_n: ArrayViewMut2<f64>,
@@ -458,6 +939,13 @@ fn single_iteration_2d<T>(
                      nt: &mut f64,
                      nn: &mut f64,
                      mut ln: ArrayViewMut1<f64>| {
+        // skip pixels outside of the mask
+        if let Some(m) = mask
+            && !m[[row, col]]
+        {
+            *re = f64::NAN;
+            return;
+        }
         // check stop condition and skip loop if true
         if bound_check && ln[0] != 0.0 {
             return;
@@ -476,6 +964,7 @@ fn single_iteration_2d<T>(
             data_a,
             data_b,
             kernel.view(),
+            mask,
             old_tau.view(),
             old_sqrt_n.view(),
             &mut buf_a,
@@ -542,6 +1031,7 @@ fn single_iteration_3d<T>(
     data_b: ArrayView3<T>,
     threshold_a: T,
     threshold_b: T,
+    mask: Option<ArrayView3<bool>>,
     mut result: ArrayViewMut3<f64>,
     mut new_tau: ArrayViewMut3<f64>,
     mut new_sqrt_n: ArrayViewMut3<f64>,
@@ -570,6 +1060,13 @@ fn single_iteration_3d<T>(
                      nt: &mut f64,
                      nn: &mut f64,
                      mut ln: ArrayViewMut1<f64>| {
+        // skip pixels outside of the mask
+        if let Some(m) = mask
+            && !m[[pln, row, col]]
+        {
+            *re = f64::NAN;
+            return;
+        }
         // check stop condition and skip loop if true
         if bound_check && ln[0] != 0.0 {
             return;
@@ -590,6 +1087,7 @@ fn single_iteration_3d<T>(
             data_a,
             data_b,
             kernel.view(),
+            mask,
             old_tau.view(),
             old_sqrt_n.view(),
             &mut buf_a,
@@ -651,3 +1149,118 @@ fn single_iteration_3d<T>(
             saca_iter(pln, row, col, re, nt, nn, ln);
         }));
 }
+
+/// Single n-dimensional SACA iteration.
+fn single_iteration_nd<T>(
+    data_a: &[T],
+    data_b: &[T],
+    threshold_a: T,
+    threshold_b: T,
+    mask: Option<&[bool]>,
+    shape: &[usize],
+    kernel: &[KernelOffset],
+    state: &mut SacaState,
+    dn: f64,
+    lambda: f64,
+    bound_check: bool,
+    threads: Option<usize>,
+) where
+    T: AsNumeric,
+{
+    let SacaState {
+        result,
+        new_tau,
+        new_sqrt_n,
+        old_tau,
+        old_sqrt_n,
+        stop,
+    } = state;
+    let old_tau: &[f64] = old_tau;
+    let old_sqrt_n: &[f64] = old_sqrt_n;
+    let buf_size = kernel.len();
+    let saca_iter = |p: usize, re: &mut f64, nt: &mut f64, nn: &mut f64, st: &mut [f64; 3]| {
+        // skip pixels outside of the mask
+        if mask.is_some_and(|m| !m[p]) {
+            *re = f64::NAN;
+            return;
+        }
+        // check stop condition and skip loop if true
+        if bound_check && st[0] != 0.0 {
+            return;
+        }
+        // unravel the flat position into the pixel position
+        let mut pos = vec![0usize; shape.len()];
+        let mut remaining = p;
+        (0..shape.len()).rev().for_each(|k| {
+            pos[k] = remaining % shape[k];
+            remaining /= shape[k];
+        });
+        // create buffers for the current local neighborhood, only neighbors
+        // inside the image are loaded
+        let mut buf_a: Vec<T> = Vec::with_capacity(buf_size);
+        let mut buf_b: Vec<T> = Vec::with_capacity(buf_size);
+        let mut buf_w: Vec<f64> = Vec::with_capacity(buf_size);
+        let ot = old_tau[p];
+        let on_dn = old_sqrt_n[p] / dn;
+        kernel
+            .iter()
+            .filter(|ko| {
+                ko.offset
+                    .iter()
+                    .zip(pos.iter())
+                    .zip(shape.iter())
+                    .all(|((&o, &x), &len)| {
+                        let q = x as isize + o;
+                        q >= 0 && q < len as isize
+                    })
+            })
+            .for_each(|ko| {
+                let q = (p as isize + ko.flat_offset) as usize;
+                let (a, b) = (data_a[q], data_b[q]);
+                let tau_diff_abs = (old_tau[q] - ot).abs() * on_dn;
+                let in_mask = mask.is_none_or(|m| m[q]);
+                let w = if tau_diff_abs >= 1.0 || !in_mask || a < threshold_a || b < threshold_b {
+                    0.0
+                } else {
+                    ko.weight * (1.0 - tau_diff_abs) * (1.0 - tau_diff_abs)
+                };
+                buf_a.push(a);
+                buf_b.push(b);
+                buf_w.push(w);
+            });
+        *nn = effective_sample_size(&buf_w).sqrt();
+        if *nn <= 0.0 {
+            *nt = 0.0;
+            *re = 0.0;
+        } else {
+            let tau = weighted_kendall_tau_b(&buf_a, &buf_b, &buf_w).unwrap_or(0.0);
+            *nt = tau;
+            *re = tau * *nn * 1.5;
+        }
+        if bound_check {
+            let tau_diff = (st[1] - *nt).abs() * st[2];
+            if tau_diff > lambda {
+                st[0] = 1.0;
+                *nt = old_tau[p];
+                *nn = old_sqrt_n[p];
+            }
+        }
+    };
+    par!(threads,
+    seq_exp: result.iter_mut()
+        .zip(new_tau.iter_mut())
+        .zip(new_sqrt_n.iter_mut())
+        .zip(stop.iter_mut())
+        .enumerate()
+        .for_each(|(p, (((re, nt), nn), st))| {
+            saca_iter(p, re, nt, nn, st);
+        }),
+    par_exp: result.par_iter_mut()
+        .zip(new_tau.par_iter_mut())
+        .zip(new_sqrt_n.par_iter_mut())
+        .zip(stop.par_iter_mut())
+        .enumerate()
+        .for_each(|(p, (((re, nt), nn), st))| {
+            saca_iter(p, re, nt, nn, st);
+        }));
+}
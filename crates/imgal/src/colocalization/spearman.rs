@@ -0,0 +1,130 @@
+use std::collections::HashMap;
+
+use ndarray::{Array2, ArrayBase, AsArray, Axis, Dimension, IxDyn, ViewRepr};
+use rayon::prelude::*;
+
+use crate::prelude::*;
+use crate::statistics::{average_ranks, pearson};
+use crate::validate::{check_roi_bounds, check_shapes};
+
+/// Compute the Spearman rank correlation coefficient between two n-dimensional
+/// images.
+///
+/// # Description
+///
+/// Computes the Spearman rank correlation coefficient, a measure of monotonic
+/// (but possibly nonlinear) correlation between two n-dimensional images. The
+/// pixel values of each image are replaced by their ranks, and the Pearson
+/// correlation coefficient of the ranks is computed:
+///
+/// ```text
+/// ρ = pearson(rank(a), rank(b))
+/// ```
+///
+/// Tied values are assigned the average of the ranks they span.
+///
+/// # Arguments
+///
+/// * `data_a`: The first n-dimensional image for Spearman colocalization
+///   analysis.
+/// * `data_b`: The second n-dimensional image for Spearman colocalization
+///   analysis.
+/// * `threads`: The requested number of threads to use for parallel execution.
+///   If `None` or `Some(1)` sequential execution is used. If `Some(0)`, then
+///   the maximum available parallelism is used. Thread counts are clamped to
+///   the systems maximum.
+///
+/// # Returns
+///
+/// * `Ok(f64)`: Spearman's rank correlation coefficient ranging between `-1.0`
+///   (perfect negative monotonic correlation), `0.0` (no correlation), and
+///   `1.0` (perfect positive monotonic correlation).
+/// * `Err(ImgalError)`: If the shapes of `data_a` and `data_b` do not match. If
+///   `data_a.len()` is <= 2.
+pub fn spearman_coloc<'a, T, A, D>(
+    data_a: A,
+    data_b: A,
+    threads: Option<usize>,
+) -> Result<f64, ImgalError>
+where
+    A: AsArray<'a, T, D>,
+    D: Dimension,
+    T: 'a + AsNumeric,
+{
+    let data_a: ArrayBase<ViewRepr<&'a T>, D> = data_a.into();
+    let data_b: ArrayBase<ViewRepr<&'a T>, D> = data_b.into();
+    check_shapes("data_a", data_a.shape(), "data_b", data_b.shape())?;
+    let vals_a: Vec<f64> = data_a.iter().map(|v| v.to_f64()).collect();
+    let vals_b: Vec<f64> = data_b.iter().map(|v| v.to_f64()).collect();
+    let (rank_a, rank_b) = par!(threads,
+        seq_exp: (average_ranks(&vals_a), average_ranks(&vals_b)),
+        par_exp: rayon::join(|| average_ranks(&vals_a), || average_ranks(&vals_b)));
+    pearson(&rank_a, &rank_b, threads)
+}
+
+/// Compute the Spearman rank correlation coefficient between two n-dimensional
+/// images and a ROI map.
+///
+/// # Description
+///
+/// Computes the Spearman rank correlation coefficient, a measure of monotonic
+/// (but possibly nonlinear) correlation between two sets of n-dimensional
+/// images and a ROI map. This function iterates through each ROI in the map,
+/// ranks the pixel values of each image within the ROI and computes the Pearson
+/// correlation coefficient of the ranks. Tied values are assigned the average
+/// of the ranks they span. Returning a `HashMap` of Spearman rank correlation
+/// coefficient values and ROI label IDs.
+///
+/// # Arguments
+///
+/// * `data_a`: The first n-dimensional image for Spearman colocalization
+///   analysis.
+/// * `data_b`: The second n-dimensional image for Spearman colocalization
+///   analysis.
+/// * `rois`: A map of point clouds representing Regions of Interest (ROIs).
+///   The individual ROIs must have the same dimensionality as the input data.
+/// * `threads`: The requested number of threads to use for parallel execution.
+///   If `None` or `Some(1)` sequential execution is used. If `Some(0)`, then
+///   the maximum available parallelism is used. Thread counts are clamped to
+///   the systems maximum.
+///
+/// # Returns
+///
+/// * `Ok(HashMap<u64, f64>)`: A `HashMap` where the keys are the ROI label IDs
+///   and values are the Spearman rank correlation coefficients for each ROI
+///   respectively.
+/// * `Err(ImgalError)`: If the shapes of `data_a` and `data_b` do not match. If
+///   a ROI point is out of bounds of `data_a`. If a ROI contains <= 2 points.
+pub fn spearman_roi_coloc<'a, T, A, D>(
+    data_a: A,
+    data_b: A,
+    rois: &HashMap<u64, Array2<usize>>,
+    threads: Option<usize>,
+) -> Result<HashMap<u64, f64>, ImgalError>
+where
+    A: AsArray<'a, T, D>,
+    D: Dimension,
+    T: 'a + AsNumeric,
+{
+    let data_a: ArrayBase<ViewRepr<&'a T>, IxDyn> = data_a.into().into_dyn();
+    let data_b: ArrayBase<ViewRepr<&'a T>, IxDyn> = data_b.into().into_dyn();
+    check_shapes("data_a", data_a.shape(), "data_b", data_b.shape())?;
+    check_roi_bounds(rois, data_a.shape())?;
+    let per_roi_spearman_corr = |k: u64, v: &Array2<usize>| -> Result<(u64, f64), ImgalError> {
+        let n = v.dim().0;
+        let mut buf_a: Vec<f64> = Vec::with_capacity(n);
+        let mut buf_b: Vec<f64> = Vec::with_capacity(n);
+        v.lanes(Axis(1)).into_iter().for_each(|p| {
+            let pos = p.to_vec();
+            buf_a.push(data_a[IxDyn(&pos)].to_f64());
+            buf_b.push(data_b[IxDyn(&pos)].to_f64());
+        });
+        let corr = pearson(&average_ranks(&buf_a), &average_ranks(&buf_b), None)?;
+        Ok((k, corr))
+    };
+    par!(threads,
+        seq_exp: rois.iter().map(|(&k, v)| per_roi_spearman_corr(k, v))
+            .collect::<Result<HashMap<u64, f64>, ImgalError>>(),
+        par_exp: rois.into_par_iter().map(|(&k, v)| per_roi_spearman_corr(k, v))
+            .collect::<Result<HashMap<u64, f64>, ImgalError>>())
+}
@@ -3,6 +3,7 @@ use std::mem::MaybeUninit;
 use ndarray::{Array, Array1, ArrayBase, ArrayViewMut, AsArray, Dimension, ViewRepr, Zip};
 
 use crate::prelude::*;
+use crate::validate::check_shapes;
 
 /// Copy n-dimensional image data into an exisiting array.
 ///
@@ -34,14 +35,7 @@ where
     T: 'a + AsNumeric,
 {
     let data_a: ArrayBase<ViewRepr<&'a T>, D> = data_a.into();
-    if data_a.shape() != data_b.shape() {
-        return Err(ImgalError::MismatchedArrayShapes {
-            a_arr_name: "data_a",
-            a_shape: data_a.shape().to_vec(),
-            b_arr_name: "data_b",
-            b_shape: data_b.shape().to_vec(),
-        });
-    }
+    check_shapes("data_a", data_a.shape(), "data_b", data_b.shape())?;
     par!(threads,
         seq_exp: data_b.assign(&data_a),
         par_exp: Zip::from(data_a).and(data_b)
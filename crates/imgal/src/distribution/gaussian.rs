@@ -1,7 +1,7 @@
 use ndarray::Array1;
 use rayon::prelude::*;
 
-use crate::statistics::sum;
+use crate::statistics::sum_f64;
 
 /// Create a normalized Gaussian distribution over a specified range.
 ///
@@ -55,7 +55,7 @@ pub fn normalized_gaussian(
     par!(threads,
         seq_exp: gauss_arr.iter_mut().enumerate().for_each(gauss_calc),
         par_exp: gauss_arr.par_iter_mut().enumerate().for_each(gauss_calc));
-    let gauss_sum = sum(&gauss_arr, threads);
+    let gauss_sum = sum_f64(&gauss_arr, threads);
     par!(threads,
         seq_exp: gauss_arr.iter_mut().for_each(|v| *v /= gauss_sum),
         par_exp: gauss_arr.par_iter_mut().for_each(|v| *v /= gauss_sum));
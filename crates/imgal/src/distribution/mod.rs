@@ -6,5 +6,9 @@
 mod cdf;
 mod gaussian;
 
+pub use cdf::f_cdf;
 pub use cdf::inverse_normal_cdf;
+pub use cdf::normal_cdf;
+pub use cdf::regularized_incomplete_beta;
+pub use cdf::t_cdf;
 pub use gaussian::normalized_gaussian;
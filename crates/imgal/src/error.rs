@@ -42,9 +42,16 @@ pub enum ImgalError {
         axis_idx: usize,
         multiple: usize,
     },
+    InvalidDecayIntegral {
+        n_pixels: usize,
+    },
     InvalidGeneric {
         msg: &'static str,
     },
+    InvalidNumericOverflow {
+        op: &'static str,
+        type_name: &'static str,
+    },
     InvalidParameterEmptyArray {
         param_name: &'static str,
     },
@@ -74,6 +81,11 @@ pub enum ImgalError {
         start: usize,
         end: usize,
     },
+    InvalidRoiCoordinates {
+        roi_label: u64,
+        coords: Vec<usize>,
+        shape: Vec<usize>,
+    },
     InvalidSum {
         expected: f64,
         got: f64,
@@ -175,9 +187,23 @@ impl fmt::Display for ImgalError {
                     axis_idx, arr_name, multiple
                 )
             }
+            ImgalError::InvalidDecayIntegral { n_pixels } => {
+                write!(
+                    f,
+                    "Invalid decay integral, {} pixels have a decay integral of zero.",
+                    n_pixels
+                )
+            }
             ImgalError::InvalidGeneric { msg } => {
                 write!(f, "{}", msg)
             }
+            ImgalError::InvalidNumericOverflow { op, type_name } => {
+                write!(
+                    f,
+                    "Invalid numeric result, the {} overflows the range of type \"{}\".",
+                    op, type_name
+                )
+            }
             ImgalError::InvalidParameterEmptyArray { param_name } => {
                 write!(
                     f,
@@ -235,6 +261,17 @@ impl fmt::Display for ImgalError {
                     start, end
                 )
             }
+            ImgalError::InvalidRoiCoordinates {
+                roi_label,
+                coords,
+                shape,
+            } => {
+                write!(
+                    f,
+                    "Invalid ROI coordinates, point {:?} of ROI {} is out of bounds for array shape {:?}.",
+                    coords, roi_label, shape
+                )
+            }
             ImgalError::InvalidSum { expected, got } => {
                 write!(f, "Invalid sum, expected {} but got {}.", expected, got)
             }
@@ -0,0 +1,219 @@
+use ndarray::{Array1, Array2, ArrayBase, AsArray, Ix2, ViewRepr, Zip};
+
+use crate::prelude::*;
+
+/// The shape of a fiducial marker.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum FiducialShape {
+    /// A cross (*i.e.* "+") of two perpendicular bars.
+    #[default]
+    Cross,
+    /// A filled square (*e.g.* the outline of ArUco-like markers).
+    Square,
+}
+
+/// Detect fiducial markers in a 2D image.
+///
+/// # Description
+///
+/// Detects high-contrast fiducial markers (*e.g.* crosses or squares printed or
+/// etched on slides) and returns their subpixel coordinates, for the
+/// registration of coordinate systems across modalities. A binary template of
+/// the marker is matched with the zero-normalized cross-correlation (ZNCC):
+///
+/// ```text
+/// ZNCC(x) = Σ (I(x + u) - mean(I)) × (M(u) - mean(M)) / (n × σ(I) × σ(M))
+/// ```
+///
+/// Where `I` is the image window around `x`, `M` is the template and `n` is
+/// the number of template pixels. The ZNCC ranges from `-1.0` to `1.0` and is
+/// invariant to the marker brightness and contrast. Local maxima of the ZNCC
+/// above `min_score` are markers, and their positions are refined to subpixel
+/// precision by fitting a parabola through the maximum and its neighbors along
+/// each axis. Markers closer than the template size are suppressed, keeping
+/// the best match.
+///
+/// The cross template spans `size` pixels with bars of `line_width` pixels.
+/// The square template is a square of side `size` surrounded by a background
+/// border of `size / 4` pixels.
+///
+/// # Arguments
+///
+/// * `data`: The input 2D image.
+/// * `shape`: The shape of the fiducial markers.
+/// * `size`: The size of the markers in pixels, *i.e.* the length of the cross
+///   bars or the side length of the square.
+/// * `line_width`: The width of the cross bars in pixels. If `None`, then
+///   `line_width = max(size / 5, 1)`. Ignored for squares.
+/// * `min_score`: The minimum ZNCC score of a marker. If `None`, then
+///   `min_score = 0.5`.
+/// * `dark`: If `true`, the markers are darker than the background.
+/// * `threads`: The requested number of threads to use for parallel execution.
+///   If `None` or `Some(1)` sequential execution is used. If `Some(0)`, then
+///   the maximum available parallelism is used. Thread counts are clamped to
+///   the systems maximum.
+///
+/// # Returns
+///
+/// * `Ok((Array2<f64>, Array1<f64>))`: A tuple containing the subpixel marker
+///   coordinates with shape `(p, 2)`, where each row is `(row, col)`, and the
+///   ZNCC score of each marker, sorted by descending score, *i.e.*
+///   `(coords, scores)`.
+/// * `Err(ImgalError)`: If `size < 3`. If `line_width == 0` or
+///   `line_width >= size`. If `min_score` is outside the range `-1.0` to
+///   `1.0`.
+pub fn detect_fiducials<'a, T, A>(
+    data: A,
+    shape: FiducialShape,
+    size: usize,
+    line_width: Option<usize>,
+    min_score: Option<f64>,
+    dark: bool,
+    threads: Option<usize>,
+) -> Result<(Array2<f64>, Array1<f64>), ImgalError>
+where
+    A: AsArray<'a, T, Ix2>,
+    T: 'a + AsNumeric,
+{
+    let data: ArrayBase<ViewRepr<&'a T>, Ix2> = data.into();
+    if size < 3 {
+        return Err(ImgalError::InvalidParameterValueLess {
+            param_name: "size",
+            value: 3,
+        });
+    }
+    let line_width = line_width.unwrap_or((size / 5).max(1));
+    if line_width == 0 || line_width >= size {
+        return Err(ImgalError::InvalidParameterValueOutsideRange {
+            param_name: "line_width",
+            value: line_width as f64,
+            min: 1.0,
+            max: (size - 1) as f64,
+        });
+    }
+    let min_score = min_score.unwrap_or(0.5);
+    if !(-1.0..=1.0).contains(&min_score) {
+        return Err(ImgalError::InvalidParameterValueOutsideRange {
+            param_name: "min_score",
+            value: min_score,
+            min: -1.0,
+            max: 1.0,
+        });
+    }
+    let template = marker_template(shape, size, line_width, dark);
+    let (t_rows, t_cols) = template.dim();
+    let (rows, cols) = data.dim();
+    // the zero-mean and unit-norm template
+    let n = template.len() as f64;
+    let t_mean = template.sum() / n;
+    let t_centered = template.mapv(|v| v - t_mean);
+    let t_norm = t_centered.iter().map(|v| v * v).sum::<f64>().sqrt();
+    let img = data.mapv(|v| v.to_f64());
+    let (h_r, h_c) = (t_rows / 2, t_cols / 2);
+    let mut score = Array2::<f64>::from_elem((rows, cols), f64::NAN);
+    let zncc = |(r, c): (usize, usize), s: &mut f64| {
+        if r < h_r || c < h_c || r + h_r >= rows || c + h_c >= cols {
+            return;
+        }
+        let mut sum = 0.0;
+        let mut sum_sq = 0.0;
+        let mut cross = 0.0;
+        for i in 0..t_rows {
+            for j in 0..t_cols {
+                let v = img[[r + i - h_r, c + j - h_c]];
+                sum += v;
+                sum_sq += v * v;
+                cross += v * t_centered[[i, j]];
+            }
+        }
+        let var = sum_sq - sum * sum / n;
+        *s = if var > 0.0 {
+            cross / (var.sqrt() * t_norm)
+        } else {
+            0.0
+        };
+    };
+    par!(threads,
+        seq_exp: Zip::indexed(&mut score).for_each(zncc),
+        par_exp: Zip::indexed(&mut score).par_for_each(zncc));
+    // local maxima of the score above the minimum score
+    let mut candidates: Vec<(f64, usize, usize)> = Vec::new();
+    for r in 1..rows.saturating_sub(1) {
+        for c in 1..cols.saturating_sub(1) {
+            let s = score[[r, c]];
+            if s.is_nan() || s < min_score {
+                continue;
+            }
+            let is_max = (r - 1..=r + 1)
+                .flat_map(|i| (c - 1..=c + 1).map(move |j| (i, j)))
+                .all(|(i, j)| score[[i, j]].is_nan() || score[[i, j]] <= s);
+            if is_max {
+                candidates.push((s, r, c));
+            }
+        }
+    }
+    // suppress markers closer than the template size, best matches first
+    candidates.sort_by(|a, b| b.0.total_cmp(&a.0));
+    let mut markers: Vec<(f64, f64, f64)> = Vec::new();
+    for (s, r, c) in candidates {
+        let (rf, cf) = (r as f64, c as f64);
+        let too_close = markers
+            .iter()
+            .any(|&(_, mr, mc)| (mr - rf).abs() < t_rows as f64 && (mc - cf).abs() < t_cols as f64);
+        if too_close {
+            continue;
+        }
+        let refine = |lo: f64, hi: f64| -> f64 {
+            let denom = lo - 2.0 * s + hi;
+            if lo.is_nan() || hi.is_nan() || denom.abs() <= f64::EPSILON {
+                0.0
+            } else {
+                (0.5 * (lo - hi) / denom).clamp(-0.5, 0.5)
+            }
+        };
+        let dr = refine(score[[r - 1, c]], score[[r + 1, c]]);
+        let dc = refine(score[[r, c - 1]], score[[r, c + 1]]);
+        markers.push((s, rf + dr, cf + dc));
+    }
+    let mut coords = Array2::<f64>::zeros((markers.len(), 2));
+    let mut scores = Array1::<f64>::zeros(markers.len());
+    markers.iter().enumerate().for_each(|(i, &(s, r, c))| {
+        coords[[i, 0]] = r;
+        coords[[i, 1]] = c;
+        scores[i] = s;
+    });
+    Ok((coords, scores))
+}
+
+/// Create a binary marker template with odd side lengths.
+fn marker_template(
+    shape: FiducialShape,
+    size: usize,
+    line_width: usize,
+    dark: bool,
+) -> Array2<f64> {
+    let (fg, bg) = if dark { (0.0, 1.0) } else { (1.0, 0.0) };
+    match shape {
+        FiducialShape::Cross => {
+            let side = size | 1;
+            let center = (side / 2) as f64;
+            let half_width = line_width as f64 / 2.0;
+            Array2::from_shape_fn((side, side), |(r, c)| {
+                let on_bar = (r as f64 - center).abs() < half_width
+                    || (c as f64 - center).abs() < half_width;
+                if on_bar { fg } else { bg }
+            })
+        }
+        FiducialShape::Square => {
+            let border = (size / 4).max(1);
+            let side = (size + 2 * border) | 1;
+            let center = (side / 2) as f64;
+            let half_size = size as f64 / 2.0;
+            Array2::from_shape_fn((side, side), |(r, c)| {
+                let inside =
+                    (r as f64 - center).abs() < half_size && (c as f64 - center).abs() < half_size;
+                if inside { fg } else { bg }
+            })
+        }
+    }
+}
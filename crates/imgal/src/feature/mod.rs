@@ -0,0 +1,12 @@
+//! Image feature detection functions.
+//!
+//! This module provides functions for detecting features in images, such as
+//! fiducial markers for the registration of coordinate systems across
+//! modalities and fluorescent spots.
+
+mod fiducials;
+mod wavelet;
+
+pub use fiducials::FiducialShape;
+pub use fiducials::detect_fiducials;
+pub use wavelet::wavelet_spots;
@@ -0,0 +1,198 @@
+use ndarray::{Array, Array2, ArrayBase, ArrayD, AsArray, Dimension, ViewRepr, Zip};
+
+use crate::filter::{BorderMode, correlate_axis};
+use crate::prelude::*;
+use crate::statistics::mad;
+
+/// Detect spots in an n-dimensional image with the à trous wavelet product.
+///
+/// # Description
+///
+/// Detects bright spots (*e.g.* fluorescent puncta or vesicles) with the
+/// undecimated ("à trous") wavelet transform of Olivo-Marin. The image is
+/// smoothed at increasing scales with the separable B3-spline kernel
+/// `[1, 4, 6, 4, 1] / 16`, dilated with `2ʲ⁻¹ - 1` zeros between its taps at
+/// level `j`, and the wavelet planes are the differences of successive
+/// smoothings:
+///
+/// ```text
+/// A₀ = I
+/// Aⱼ = Aⱼ₋₁ ⋆ hⱼ
+/// Wⱼ = Aⱼ₋₁ - Aⱼ
+/// ```
+///
+/// Each wavelet plane is denoised with a hard threshold of `k` robust
+/// standard deviations of its coefficients (estimated with the median
+/// absolute deviation), keeping only positive coefficients. Noise is
+/// uncorrelated across scales while spots persist, so the spots are the
+/// pixels where the product of all thresholded planes is positive:
+///
+/// ```text
+/// Wⱼ' = Wⱼ if Wⱼ ≥ k × 1.4826 × MAD(Wⱼ), else 0
+/// P = Π Wⱼ'
+/// ```
+///
+/// The spots are the connected regions (face connectivity) of the mask
+/// `P > 0` with at least `min_size` pixels, and their coordinates are the
+/// centroids weighted by `P`. Compared to a difference of Gaussians, the
+/// product detector is robust to noise and to spots of variable size. The
+/// image is extended at its borders by reflection.
+///
+/// # Arguments
+///
+/// * `data`: The input n-dimensional image.
+/// * `levels`: The number of wavelet levels `J`, larger values detect larger
+///   spots. If `None`, then `levels = 3`.
+/// * `k`: The threshold of each wavelet plane in robust standard deviations.
+///   If `None`, then `k = 3.0`.
+/// * `min_size`: The minimum number of pixels of a spot. If `None`, then
+///   `min_size = 1`.
+/// * `threads`: The requested number of threads to use for parallel execution.
+///   If `None` or `Some(1)` sequential execution is used. If `Some(0)`, then
+///   the maximum available parallelism is used. Thread counts are clamped to
+///   the systems maximum.
+///
+/// # Returns
+///
+/// * `Ok((Array<bool, D>, Array2<f64>))`: A tuple containing the spot mask and
+///   the spot coordinates with shape `(p, D)` in the scan order of the spots,
+///   *i.e.* `(mask, coords)`.
+/// * `Err(ImgalError)`: If `data` is empty. If `levels == 0`. If `k < 0.0`.
+///   If the kernel radius `2ʲ` of the largest level is greater than or equal
+///   to an axis length.
+///
+/// # Reference
+///
+/// <https://doi.org/10.1016/S0031-3203(01)00127-3>
+pub fn wavelet_spots<'a, T, A, D>(
+    data: A,
+    levels: Option<usize>,
+    k: Option<f64>,
+    min_size: Option<usize>,
+    threads: Option<usize>,
+) -> Result<(Array<bool, D>, Array2<f64>), ImgalError>
+where
+    A: AsArray<'a, T, D>,
+    D: Dimension,
+    T: 'a + AsNumeric,
+{
+    let data: ArrayBase<ViewRepr<&'a T>, D> = data.into();
+    if data.is_empty() {
+        return Err(ImgalError::InvalidParameterEmptyArray { param_name: "data" });
+    }
+    let levels = levels.unwrap_or(3);
+    if levels == 0 {
+        return Err(ImgalError::InvalidParameterValueEqual {
+            param_name: "levels",
+            value: 0,
+        });
+    }
+    // the largest level kernel radius 2ʲ must be shorter than each axis
+    let radius = u32::try_from(levels)
+        .ok()
+        .and_then(|l| 1usize.checked_shl(l))
+        .unwrap_or(usize::MAX);
+    if let Some(axis_idx) = data.shape().iter().position(|&l| l <= radius) {
+        return Err(ImgalError::InvalidAxisLengthLess {
+            arr_name: "data",
+            axis_idx,
+            value: radius.saturating_add(1),
+        });
+    }
+    let k = k.unwrap_or(3.0);
+    if k.is_nan() || k < 0.0 {
+        return Err(ImgalError::InvalidParameterValueOutsideRange {
+            param_name: "k",
+            value: k,
+            min: 0.0,
+            max: f64::INFINITY,
+        });
+    }
+    let min_size = min_size.unwrap_or(1);
+    let mut smooth: ArrayD<f64> = data.mapv(|v| v.to_f64()).into_dyn();
+    let mut product = ArrayD::<f64>::ones(smooth.raw_dim());
+    for j in 0..levels {
+        // the B3-spline kernel with 2ʲ - 1 zeros between its taps
+        let step = 1 << j;
+        let mut kernel = vec![0.0; 4 * step + 1];
+        [1.0, 4.0, 6.0, 4.0, 1.0]
+            .iter()
+            .enumerate()
+            .for_each(|(i, w)| kernel[i * step] = w / 16.0);
+        let mut next = smooth.clone();
+        for ax in 0..next.ndim() {
+            correlate_axis(&mut next, ax, &kernel, BorderMode::Reflect, threads)?;
+        }
+        let plane = &smooth - &next;
+        let t = k * mad(&plane, None, Some(1.4826), threads)?[0];
+        Zip::from(&mut product)
+            .and(&plane)
+            .for_each(|p, &w| *p *= if w >= t && w > 0.0 { w } else { 0.0 });
+        smooth = next;
+    }
+    let (mask, coords) = spot_regions(&product, min_size);
+    Ok((
+        mask.into_dimensionality::<D>()
+            .expect("Failed to convert the spot mask to the input dimensionality."),
+        coords,
+    ))
+}
+
+/// Find the face connected regions of positive values with at least
+/// `min_size` pixels, returning their mask and weighted centroids.
+fn spot_regions(product: &ArrayD<f64>, min_size: usize) -> (ArrayD<bool>, Array2<f64>) {
+    let shape = product.shape().to_vec();
+    let ndim = shape.len();
+    let values: Vec<f64> = product.iter().copied().collect();
+    let mut strides = vec![1; ndim];
+    for ax in (0..ndim.saturating_sub(1)).rev() {
+        strides[ax] = strides[ax + 1] * shape[ax + 1];
+    }
+    let mut mask = vec![false; values.len()];
+    let mut visited = vec![false; values.len()];
+    let mut centroids: Vec<Vec<f64>> = Vec::new();
+    for start in 0..values.len() {
+        if visited[start] || values[start] <= 0.0 {
+            continue;
+        }
+        // collect the region with a flood fill
+        visited[start] = true;
+        let mut region = vec![start];
+        let mut i = 0;
+        while i < region.len() {
+            let p = region[i];
+            for ax in 0..ndim {
+                let pos = p / strides[ax] % shape[ax];
+                let mut push = |q: usize| {
+                    if !visited[q] && values[q] > 0.0 {
+                        visited[q] = true;
+                        region.push(q);
+                    }
+                };
+                if pos > 0 {
+                    push(p - strides[ax]);
+                }
+                if pos + 1 < shape[ax] {
+                    push(p + strides[ax]);
+                }
+            }
+            i += 1;
+        }
+        if region.len() < min_size {
+            continue;
+        }
+        let mut centroid = vec![0.0; ndim];
+        let mut weight = 0.0;
+        region.iter().for_each(|&p| {
+            mask[p] = true;
+            weight += values[p];
+            for ax in 0..ndim {
+                centroid[ax] += values[p] * (p / strides[ax] % shape[ax]) as f64;
+            }
+        });
+        centroid.iter_mut().for_each(|c| *c /= weight);
+        centroids.push(centroid);
+    }
+    let coords = Array2::from_shape_fn((centroids.len(), ndim), |(i, ax)| centroids[i][ax]);
+    (ArrayD::from_shape_vec(shape, mask).unwrap(), coords)
+}
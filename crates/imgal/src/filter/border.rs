@@ -0,0 +1,84 @@
+use ndarray::{ArrayD, ArrayViewD, IxDyn};
+
+use crate::prelude::*;
+use crate::transform::pad::{constant_pad, reflect_pad};
+
+/// The border handling of neighborhood filters.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum BorderMode {
+    /// Extend the image by reflection, mirroring without repeating the edge
+    /// pixel (*i.e.* `d c b | a b c d | c b a`). Borders wider than the image
+    /// are mirrored repeatedly, and an axis of length `1` is extended with its
+    /// value.
+    #[default]
+    Reflect,
+    /// Extend the image with a constant value.
+    Constant(f64),
+}
+
+/// Pad an n-dimensional image symmetrically by `radius` along a single axis
+/// with the given border mode.
+pub(crate) fn pad_axis(
+    data: ArrayViewD<f64>,
+    axis: usize,
+    radius: usize,
+    border: BorderMode,
+    threads: Option<usize>,
+) -> Result<ArrayD<f64>, ImgalError> {
+    let mut pad_config = vec![0; data.ndim()];
+    pad_config[axis] = radius;
+    pad_border(data, &pad_config, border, threads)
+}
+
+/// Pad an n-dimensional image symmetrically by `pad_config` along each axis
+/// with the given border mode.
+///
+/// `transform::reflect_pad` requires each pad to be shorter than its axis, so
+/// reflected pads as wide as or wider than a (non-empty) axis fall back to
+/// mirroring the image repeatedly.
+pub(crate) fn pad_border<T>(
+    data: ArrayViewD<T>,
+    pad_config: &[usize],
+    border: BorderMode,
+    threads: Option<usize>,
+) -> Result<ArrayD<T>, ImgalError>
+where
+    T: AsNumeric,
+{
+    match border {
+        BorderMode::Reflect => {
+            let shape = data.shape();
+            let wide = pad_config.len() == shape.len()
+                && shape.iter().all(|&l| l > 0)
+                && pad_config.iter().zip(shape).any(|(&p, &l)| p >= l);
+            if !wide {
+                return reflect_pad(&data, pad_config, None, threads);
+            }
+            let pad_shape: Vec<usize> = shape
+                .iter()
+                .zip(pad_config)
+                .map(|(&l, &p)| l + 2 * p)
+                .collect();
+            Ok(ArrayD::from_shape_fn(IxDyn(&pad_shape), |idx| {
+                let src: Vec<usize> = (0..shape.len())
+                    .map(|ax| reflect_index(idx[ax] as isize - pad_config[ax] as isize, shape[ax]))
+                    .collect();
+                data[IxDyn(&src)]
+            }))
+        }
+        BorderMode::Constant(value) => {
+            constant_pad(&data, T::from_f64(value), pad_config, None, threads)
+        }
+    }
+}
+
+/// Map a (possibly out of bounds) index onto an axis of length `n` by
+/// mirroring without repeating the edge pixel.
+fn reflect_index(i: isize, n: usize) -> usize {
+    if n == 1 {
+        return 0;
+    }
+    let period = 2 * (n - 1);
+    let m = i.rem_euclid(period as isize) as usize;
+    if m < n { m } else { period - m }
+}
@@ -0,0 +1,204 @@
+use ndarray::{Array, ArrayBase, ArrayD, ArrayView, AsArray, Dimension, IntoDimension, ViewRepr};
+use rayon::prelude::*;
+
+use crate::filter::BorderMode;
+use crate::filter::border::pad_border;
+use crate::prelude::*;
+
+/// Compute the rank transform of an n-dimensional image.
+///
+/// # Description
+///
+/// Replaces each pixel with the number of pixels in its neighborhood, given by
+/// a boolean `footprint` centered on the pixel, with a value strictly less
+/// than the center pixel:
+///
+/// ```text
+/// R(x) = |{y ∈ N(x) : I(y) < I(x)}|
+/// ```
+///
+/// The rank transform only depends on the ordering of the intensities, it is
+/// invariant to any monotonic change of the intensities (*e.g.* illumination
+/// changes, gain and offset). Correlation measures computed on rank
+/// transformed images (*e.g.* Kendall's Tau-b) and block matching for
+/// registration or stereo become robust to such changes. The center pixel of
+/// the footprint is never counted. The image is extended at its borders with
+/// `transform::pad` according to the `border` mode.
+///
+/// # Arguments
+///
+/// * `data`: The input n-dimensional image.
+/// * `footprint`: The boolean neighborhood with the same dimensionality as
+///   `data` and an odd length along each axis, centered on the transformed
+///   pixel.
+/// * `border`: The border handling of the image. If `None`, then
+///   `border = BorderMode::Reflect`.
+/// * `threads`: The requested number of threads to use for parallel execution.
+///   If `None` or `Some(1)` sequential execution is used. If `Some(0)`, then
+///   the maximum available parallelism is used. Thread counts are clamped to
+///   the systems maximum.
+///
+/// # Returns
+///
+/// * `Ok(Array<u64, D>)`: The rank transformed image.
+/// * `Err(ImgalError)`: If an axis length of `footprint` is even.
+///
+/// # Reference
+///
+/// <https://doi.org/10.1007/BFb0028345>
+pub fn rank_transform<'a, T, A, D>(
+    data: A,
+    footprint: ArrayView<bool, D>,
+    border: Option<BorderMode>,
+    threads: Option<usize>,
+) -> Result<Array<u64, D>, ImgalError>
+where
+    A: AsArray<'a, T, D>,
+    D: Dimension,
+    T: 'a + AsNumeric,
+{
+    let data: ArrayBase<ViewRepr<&'a T>, D> = data.into();
+    local_codes(data, footprint, border, threads, |center, neighbors| {
+        neighbors.filter(|&v| v < center).count() as u64
+    })
+}
+
+/// Compute the census transform of an n-dimensional image.
+///
+/// # Description
+///
+/// Replaces each pixel with a bit string encoding which pixels of its
+/// neighborhood, given by a boolean `footprint` centered on the pixel, have a
+/// value strictly less than the center pixel:
+///
+/// ```text
+/// C(x) = Σ 2ᵏ × [I(yₖ) < I(x)],  for yₖ ∈ N(x)
+/// ```
+///
+/// Where `yₖ` is the `k`-th `true` pixel of the footprint in row-major order,
+/// skipping the center pixel. Unlike the rank transform, the census transform
+/// also encodes the spatial layout of the neighborhood. Census codes are
+/// compared with the Hamming distance (*i.e.* `(a ^ b).count_ones()`), which
+/// is invariant to monotonic intensity changes and robust to outliers, for
+/// stereo and registration matching. The image is extended at its borders
+/// with `transform::pad` according to the `border` mode.
+///
+/// # Arguments
+///
+/// * `data`: The input n-dimensional image.
+/// * `footprint`: The boolean neighborhood with the same dimensionality as
+///   `data` and an odd length along each axis, centered on the transformed
+///   pixel. At most `64` pixels of the footprint, excluding the center, can
+///   be `true`.
+/// * `border`: The border handling of the image. If `None`, then
+///   `border = BorderMode::Reflect`.
+/// * `threads`: The requested number of threads to use for parallel execution.
+///   If `None` or `Some(1)` sequential execution is used. If `Some(0)`, then
+///   the maximum available parallelism is used. Thread counts are clamped to
+///   the systems maximum.
+///
+/// # Returns
+///
+/// * `Ok(Array<u64, D>)`: The census transformed image.
+/// * `Err(ImgalError)`: If an axis length of `footprint` is even. If the
+///   footprint has more than `64` `true` pixels excluding the center.
+///
+/// # Reference
+///
+/// <https://doi.org/10.1007/BFb0028345>
+pub fn census_transform<'a, T, A, D>(
+    data: A,
+    footprint: ArrayView<bool, D>,
+    border: Option<BorderMode>,
+    threads: Option<usize>,
+) -> Result<Array<u64, D>, ImgalError>
+where
+    A: AsArray<'a, T, D>,
+    D: Dimension,
+    T: 'a + AsNumeric,
+{
+    let data: ArrayBase<ViewRepr<&'a T>, D> = data.into();
+    let center = footprint
+        .shape()
+        .iter()
+        .map(|l| l / 2)
+        .collect::<Vec<usize>>();
+    let n_neighbors = footprint
+        .indexed_iter()
+        .filter(|(p, v)| **v && p.clone().into_dimension().slice() != center.as_slice())
+        .count();
+    if n_neighbors > 64 {
+        return Err(ImgalError::InvalidGeneric {
+            msg: "Invalid census transform footprint, the footprint has more than 64 true pixels excluding the center.",
+        });
+    }
+    local_codes(data, footprint, border, threads, |center, neighbors| {
+        neighbors
+            .enumerate()
+            .fold(0u64, |code, (k, v)| code | ((v < center) as u64) << k)
+    })
+}
+
+/// Compute a code for each pixel of an n-dimensional image from the center
+/// pixel and its footprint neighbors, in row-major footprint order.
+fn local_codes<T, D, F>(
+    data: ArrayView<T, D>,
+    footprint: ArrayView<bool, D>,
+    border: Option<BorderMode>,
+    threads: Option<usize>,
+    code: F,
+) -> Result<Array<u64, D>, ImgalError>
+where
+    D: Dimension,
+    T: AsNumeric,
+    F: Fn(T, &mut dyn Iterator<Item = T>) -> u64 + Sync,
+{
+    if let Some(ax) = footprint.shape().iter().position(|l| l.is_multiple_of(2)) {
+        return Err(ImgalError::InvalidAxisValueNotAMultipleOf {
+            arr_name: "footprint",
+            axis_idx: ax,
+            multiple: 2,
+        });
+    }
+    let radii: Vec<usize> = footprint.shape().iter().map(|l| l / 2).collect();
+    let padded: ArrayD<T> = pad_border(
+        data.view().into_dyn(),
+        &radii,
+        border.unwrap_or_default(),
+        threads,
+    )?
+    .as_standard_layout()
+    .into_owned();
+    // the flat offsets of the footprint and its center in the padded image
+    let p_strides: Vec<isize> = padded.strides().to_vec();
+    let flat = |p: &[usize]| -> isize {
+        p.iter()
+            .zip(p_strides.iter())
+            .map(|(&i, &s)| i as isize * s)
+            .sum()
+    };
+    let center = flat(&radii);
+    let offsets: Vec<isize> = footprint
+        .indexed_iter()
+        .filter(|(_, v)| **v)
+        .map(|(p, _)| flat(p.into_dimension().slice()))
+        .filter(|&o| o != center)
+        .collect();
+    let src = padded.as_slice().unwrap();
+    let shape = data.shape().to_vec();
+    let pixel_code = |j: usize| -> u64 {
+        // unravel the output index into its position in the padded image
+        let mut rem = j;
+        let mut pos = 0;
+        for ax in (0..shape.len()).rev() {
+            pos += (rem % shape[ax]) as isize * p_strides[ax];
+            rem /= shape[ax];
+        }
+        let mut neighbors = offsets.iter().map(|&o| src[(pos + o) as usize]);
+        code(src[(pos + center) as usize], &mut neighbors)
+    };
+    let codes: Vec<u64> = par!(threads,
+        seq_exp: (0..data.len()).map(pixel_code).collect(),
+        par_exp: (0..data.len()).into_par_iter().map(pixel_code).collect());
+    Ok(Array::from_shape_vec(data.raw_dim(), codes).unwrap())
+}
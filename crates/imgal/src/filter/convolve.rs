@@ -1,18 +1,53 @@
-use ndarray::{Array1, ArrayBase, AsArray, Ix1, ViewRepr, Zip};
+use std::cell::RefCell;
+use std::sync::Arc;
+
+use ndarray::{
+    Array, Array1, ArrayBase, ArrayView1, ArrayViewMut1, AsArray, Axis, Dimension, Ix1, Slice,
+    ViewRepr, Zip,
+};
 use rayon::prelude::*;
+use realfft::{ComplexToReal, RealFftPlanner, RealToComplex};
 use rustfft::{FftPlanner, num_complex::Complex, num_traits::Zero};
 
 use crate::prelude::*;
 
+thread_local! {
+    /// The real FFT planner of the current thread, which caches the plans of
+    /// every FFT length it has planned.
+    static REAL_PLANNER: RefCell<RealFftPlanner<f64>> = RefCell::new(RealFftPlanner::new());
+    /// The complex FFT planner of the current thread, which caches the plans
+    /// of every FFT length it has planned.
+    static COMPLEX_PLANNER: RefCell<FftPlanner<f64>> = RefCell::new(FftPlanner::new());
+}
+
+/// The output size of an n-dimensional convolution.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum ConvolveMode {
+    /// The full convolution, the length of each axis is
+    /// `data_len + kernel_len - 1`.
+    Full,
+    /// The central part of the full convolution with the same shape as the
+    /// data, the kernel is centered at index `(kernel_len - 1) / 2`.
+    #[default]
+    Same,
+    /// Only the part of the convolution where the kernel fully overlaps the
+    /// data, the length of each axis is `data_len - kernel_len + 1`.
+    Valid,
+}
+
 /// Convolve two 1D signals using the Fast Fourier Transform (FFT).
 ///
 /// # Description
 ///
 /// Computes the convolution of two discrete signals (`data_a` and `data_b`) by
-/// transforming them into the frequency domain, multiplying them, and then
-/// transforming the result back into a signal. This function uses "same-length"
+/// transforming them into the frequency domain with real-to-complex FFTs,
+/// multiplying them, and then transforming the result back into a signal. Only
+/// the non-negative frequencies of the real signals are computed, which halves
+/// the time and memory of a complex FFT. This function uses "same-length"
 /// trimming with the first parameter `data_a`. This means that the returned
-/// convolution's array length will have the same length as `data_a`.
+/// convolution's array length will have the same length as `data_a`. The FFT
+/// plans are cached per thread, so repeated convolutions of the same length
+/// (*e.g.* per pixel or per tile) are not re-planned.
 ///
 /// # Arguments
 ///
@@ -36,49 +71,21 @@ where
 {
     let data_a: ArrayBase<ViewRepr<&'a T>, Ix1> = data_a.into();
     let data_b: ArrayBase<ViewRepr<&'a T>, Ix1> = data_b.into();
-    let n_a = data_a.len();
-    let n_b = data_b.len();
-    let n_fft = n_a + n_b - 1;
-    let fft_size = n_fft.next_power_of_two();
-    let mut a_fft_buf = vec![Complex::zero(); fft_size];
-    let mut b_fft_buf = vec![Complex::zero(); fft_size];
-    let load_buffers = |a_buf: &mut Complex<f64>, b_buf: &mut Complex<f64>, a: &T, b: &T| {
-        *a_buf = Complex::new(a.to_f64(), 0.0);
-        *b_buf = Complex::new(b.to_f64(), 0.0);
-    };
-    par!(threads,
-        seq_exp: Zip::from(&mut a_fft_buf[..n_a]).and(&mut b_fft_buf[..n_b])
-            .and(data_a.view())
-            .and(data_b.view())
-            .for_each(&load_buffers),
-        par_exp: Zip::from(&mut a_fft_buf[..n_a]).and(&mut b_fft_buf[..n_b])
-            .and(data_a.view())
-            .and(data_b.view())
-            .par_for_each(&load_buffers));
-    let mut planner = FftPlanner::new();
-    let fft = planner.plan_fft_forward(fft_size);
-    let ifft = planner.plan_fft_inverse(fft_size);
-    fft.process(&mut a_fft_buf);
-    fft.process(&mut b_fft_buf);
-    // multiply in the frequency domain and extract the real component (scaled
-    // and input length trimmed)
+    let fft_size = (data_a.len() + data_b.len() - 1).next_power_of_two();
+    let (r2c, c2r) = cached_real_fft_plans(fft_size);
+    let mut a_spec = real_spectrum(data_a.view(), r2c.as_ref(), threads);
+    let b_spec = real_spectrum(data_b.view(), r2c.as_ref(), threads);
+    // multiply in the frequency domain, only the non-negative frequencies of
+    // the real signals are stored
     let mul_calc = |a: &mut Complex<f64>, b: &Complex<f64>| {
         *a *= b;
     };
     par!(threads,
-        seq_exp: a_fft_buf.iter_mut().zip(b_fft_buf.iter())
+        seq_exp: a_spec.iter_mut().zip(b_spec.iter())
             .for_each(|(a, b)| mul_calc(a, b)),
-        par_exp: a_fft_buf.par_iter_mut().zip(b_fft_buf.par_iter())
+        par_exp: a_spec.par_iter_mut().zip(b_spec.par_iter())
             .for_each(|(a, b)| mul_calc(a, b)));
-    ifft.process(&mut a_fft_buf);
-    let scale = 1.0 / fft_size as f64;
-    par!(threads,
-        seq_exp: (0..n_a).zip(a_fft_buf.iter()).map(|(_, v)| v.re * scale)
-            .collect::<Array1<f64>>(),
-        par_exp: Array1::from_vec((0..n_a).into_par_iter()
-            .zip(a_fft_buf.par_iter())
-            .map(|(_, v)| v.re * scale)
-            .collect::<Vec<f64>>()))
+    inverse_real_spectrum(a_spec, c2r.as_ref(), data_a.len(), threads)
 }
 
 /// Deconvolve two 1D signals using the Fast Fourier Transform (FFT).
@@ -86,10 +93,12 @@ where
 /// # Description
 ///
 /// Computes the deconvolution of two discrete signals (`data_a` and `data_b`)
-/// by transforming them into the frequency domain, dividing them, and then
-/// transforming the result back into a signal. This function uses "same-length"
-/// trimming with the first parameter `data_a`. This means that the returned
-/// deconvolution's array length will have the same length as `data_a`.
+/// by transforming them into the frequency domain with real-to-complex FFTs,
+/// dividing them, and then transforming the result back into a signal. This
+/// function uses "same-length" trimming with the first parameter `data_a`.
+/// This means that the returned deconvolution's array length will have the
+/// same length as `data_a`. The FFT plans are cached per thread, as with
+/// `fft_convolve_1d`.
 ///
 /// # Arguments
 ///
@@ -121,32 +130,11 @@ where
     let data_a: ArrayBase<ViewRepr<&'a T>, Ix1> = data_a.into();
     let data_b: ArrayBase<ViewRepr<&'a T>, Ix1> = data_b.into();
     let epsilon = epsilon.unwrap_or(1e-8);
-    let n_a = data_a.len();
-    let n_b = data_b.len();
-    let n_fft = n_a + n_b - 1;
-    let fft_size = n_fft.next_power_of_two();
-    let mut a_fft_buf = vec![Complex::zero(); fft_size];
-    let mut b_fft_buf = vec![Complex::zero(); fft_size];
-    let load_buffers = |a_buf: &mut Complex<f64>, b_buf: &mut Complex<f64>, a: &T, b: &T| {
-        *a_buf = Complex::new(a.to_f64(), 0.0);
-        *b_buf = Complex::new(b.to_f64(), 0.0);
-    };
-    par!(threads,
-        seq_exp: Zip::from(&mut a_fft_buf[..n_a]).and(&mut b_fft_buf[..n_b])
-            .and(data_a.view())
-            .and(data_b.view())
-            .for_each(&load_buffers),
-        par_exp: Zip::from(&mut a_fft_buf[..n_a]).and(&mut b_fft_buf[..n_b])
-            .and(data_a.view())
-            .and(data_b.view())
-            .par_for_each(&load_buffers));
-    let mut planner = FftPlanner::new();
-    let fft = planner.plan_fft_forward(fft_size);
-    let ifft = planner.plan_fft_inverse(fft_size);
-    fft.process(&mut a_fft_buf);
-    fft.process(&mut b_fft_buf);
-    // divide in the frequency domain with epsilon value and extract the real
-    // component (scaled and input length trimmed)
+    let fft_size = (data_a.len() + data_b.len() - 1).next_power_of_two();
+    let (r2c, c2r) = cached_real_fft_plans(fft_size);
+    let mut a_spec = real_spectrum(data_a.view(), r2c.as_ref(), threads);
+    let b_spec = real_spectrum(data_b.view(), r2c.as_ref(), threads);
+    // divide in the frequency domain with epsilon value
     let div_calc = |a: &mut Complex<f64>, b: &Complex<f64>| {
         if a.norm_sqr() > epsilon {
             *a /= b;
@@ -155,17 +143,208 @@ where
         }
     };
     par!(threads,
-        seq_exp: a_fft_buf.iter_mut().zip(b_fft_buf.iter())
+        seq_exp: a_spec.iter_mut().zip(b_spec.iter())
             .for_each(|(a, b)| div_calc(a, b)),
-        par_exp: a_fft_buf.par_iter_mut().zip(b_fft_buf.par_iter())
+        par_exp: a_spec.par_iter_mut().zip(b_spec.par_iter())
             .for_each(|(a, b)| div_calc(a, b)));
-    ifft.process(&mut a_fft_buf);
-    let scale = 1.0 / fft_size as f64;
+    inverse_real_spectrum(a_spec, c2r.as_ref(), data_a.len(), threads)
+}
+
+/// Convolve an n-dimensional image with a kernel using the Fast Fourier
+/// Transform (FFT).
+///
+/// # Description
+///
+/// Computes the linear convolution of an n-dimensional image and kernel (*e.g.*
+/// a 2D image and a PSF) by zero-padding both to the full convolution shape,
+/// transforming them into the frequency domain with 1D FFTs along each axis,
+/// multiplying them and transforming the product back:
+///
+/// ```text
+/// data * kernel = IFFT(FFT(data) × FFT(kernel))
+/// ```
+///
+/// The cost is independent of the kernel size, which is faster than direct
+/// convolution for large kernels. The output is cropped according to `mode`,
+/// note that the `ConvolveMode::Same` output is centered on the kernel, unlike
+/// the "same-length" trimming of `fft_convolve_1d`. The lanes along each axis
+/// are transformed in parallel.
+///
+/// # Arguments
+///
+/// * `data`: The input n-dimensional image.
+/// * `kernel`: The n-dimensional convolution kernel with the same
+///   dimensionality as `data`.
+/// * `mode`: The output size of the convolution. If `None`, then
+///   `mode = ConvolveMode::Same`.
+/// * `threads`: The requested number of threads to use for parallel execution.
+///   If `None` or `Some(1)` sequential execution is used. If `Some(0)`, then
+///   the maximum available parallelism is used. Thread counts are clamped to
+///   the systems maximum.
+///
+/// # Returns
+///
+/// * `Ok(Array<f64, D>)`: The convolved image.
+/// * `Err(ImgalError)`: If `data` or `kernel` is empty. If the dimensionality
+///   of `data` and `kernel` do not match. If a `kernel` axis is longer than
+///   the `data` axis with `ConvolveMode::Valid`.
+pub fn fft_convolve<'a, T, A, D>(
+    data: A,
+    kernel: A,
+    mode: Option<ConvolveMode>,
+    threads: Option<usize>,
+) -> Result<Array<f64, D>, ImgalError>
+where
+    A: AsArray<'a, T, D>,
+    D: Dimension,
+    T: 'a + AsNumeric,
+{
+    let data: ArrayBase<ViewRepr<&'a T>, D> = data.into();
+    let kernel: ArrayBase<ViewRepr<&'a T>, D> = kernel.into();
+    if data.is_empty() {
+        return Err(ImgalError::InvalidParameterEmptyArray { param_name: "data" });
+    }
+    if kernel.is_empty() {
+        return Err(ImgalError::InvalidParameterEmptyArray {
+            param_name: "kernel",
+        });
+    }
+    if data.ndim() != kernel.ndim() {
+        return Err(ImgalError::MismatchedArrayLengths {
+            a_arr_name: "data shape",
+            a_arr_len: data.ndim(),
+            b_arr_name: "kernel shape",
+            b_arr_len: kernel.ndim(),
+        });
+    }
+    let mode = mode.unwrap_or_default();
+    if mode == ConvolveMode::Valid
+        && let Some(ax) =
+            (0..data.ndim()).find(|&ax| kernel.len_of(Axis(ax)) > data.len_of(Axis(ax)))
+    {
+        return Err(ImgalError::InvalidAxisValueGreaterEqual {
+            arr_name: "kernel",
+            axis_idx: ax,
+            value: data.len_of(Axis(ax)) + 1,
+        });
+    }
+    let mut full_shape = data.raw_dim();
+    full_shape
+        .slice_mut()
+        .iter_mut()
+        .zip(kernel.shape())
+        .for_each(|(f, &k)| *f += k - 1);
+    let mut data_buf = Array::from_elem(full_shape.clone(), Complex::<f64>::zero());
+    let mut kernel_buf = Array::from_elem(full_shape.clone(), Complex::<f64>::zero());
+    let load = |b: &mut Complex<f64>, v: &T| *b = Complex::new(v.to_f64(), 0.0);
+    data_buf
+        .slice_each_axis_mut(|ad| Slice::from(..data.len_of(ad.axis)))
+        .zip_mut_with(&data, load);
+    kernel_buf
+        .slice_each_axis_mut(|ad| Slice::from(..kernel.len_of(ad.axis)))
+        .zip_mut_with(&kernel, load);
+    fft_nd(&mut data_buf, false, threads);
+    fft_nd(&mut kernel_buf, false, threads);
+    let mul_calc = |a: &mut Complex<f64>, b: &Complex<f64>| *a *= b;
+    par!(threads,
+        seq_exp: Zip::from(&mut data_buf).and(&kernel_buf).for_each(mul_calc),
+        par_exp: Zip::from(&mut data_buf).and(&kernel_buf).par_for_each(mul_calc));
+    fft_nd(&mut data_buf, true, threads);
+    let scale = 1.0 / data_buf.len() as f64;
+    let cropped = data_buf.slice_each_axis(|ad| {
+        let (n_d, n_k) = (data.len_of(ad.axis), kernel.len_of(ad.axis));
+        match mode {
+            ConvolveMode::Full => Slice::from(..),
+            ConvolveMode::Same => {
+                let start = (n_k - 1) / 2;
+                Slice::from(start..start + n_d)
+            }
+            ConvolveMode::Valid => Slice::from(n_k - 1..n_d),
+        }
+    });
+    Ok(cropped.mapv(|v| v.re * scale))
+}
+
+/// Get the real-to-complex and complex-to-real FFT plans of a length from the
+/// planner cache of the current thread. The planner is only borrowed while
+/// planning, so the plans can be used in parallel code that reenters this
+/// function.
+fn cached_real_fft_plans(len: usize) -> (Arc<dyn RealToComplex<f64>>, Arc<dyn ComplexToReal<f64>>) {
+    REAL_PLANNER
+        .with_borrow_mut(|planner| (planner.plan_fft_forward(len), planner.plan_fft_inverse(len)))
+}
+
+/// Compute the non-negative frequency spectrum of a zero-padded real signal.
+fn real_spectrum<T>(
+    data: ArrayView1<T>,
+    r2c: &dyn RealToComplex<f64>,
+    threads: Option<usize>,
+) -> Vec<Complex<f64>>
+where
+    T: AsNumeric,
+{
+    let mut buf = r2c.make_input_vec();
+    let load = |b: &mut f64, v: &T| *b = v.to_f64();
+    par!(threads,
+        seq_exp: Zip::from(&mut buf[..data.len()]).and(&data).for_each(load),
+        par_exp: Zip::from(&mut buf[..data.len()]).and(&data).par_for_each(load));
+    let mut spec = r2c.make_output_vec();
+    r2c.process(&mut buf, &mut spec)
+        .expect("Failed to compute the real FFT, invalid buffer lengths.");
+    spec
+}
+
+/// Transform a non-negative frequency spectrum back into a real signal,
+/// scaled and trimmed to the first `len` samples.
+fn inverse_real_spectrum(
+    mut spec: Vec<Complex<f64>>,
+    c2r: &dyn ComplexToReal<f64>,
+    len: usize,
+    threads: Option<usize>,
+) -> Array1<f64> {
+    // the zero and Nyquist frequencies of a real signal are real
+    let last = spec.len() - 1;
+    spec[0].im = 0.0;
+    spec[last].im = 0.0;
+    let mut buf = c2r.make_output_vec();
+    c2r.process(&mut spec, &mut buf)
+        .expect("Failed to compute the inverse real FFT, invalid buffer lengths.");
+    let scale = 1.0 / buf.len() as f64;
+    buf.truncate(len);
+    let mut out = Array1::from_vec(buf);
     par!(threads,
-        seq_exp: (0..n_a).zip(a_fft_buf.iter()).map(|(_, v)| v.re * scale)
-            .collect::<Array1<f64>>(),
-        par_exp: Array1::from_vec((0..n_a).into_par_iter()
-            .zip(a_fft_buf.par_iter())
-            .map(|(_, v)| v.re * scale)
-            .collect::<Vec<f64>>()))
+        seq_exp: out.iter_mut().for_each(|v| *v *= scale),
+        par_exp: out.par_iter_mut().for_each(|v| *v *= scale));
+    out
+}
+
+/// Transform an n-dimensional complex array in place with 1D FFTs along each
+/// axis. The inverse transform is unnormalized. The FFT plans are cached per
+/// thread, so repeated transforms of the same shape (*e.g.* per tile) are not
+/// re-planned.
+pub(crate) fn fft_nd<D>(data: &mut Array<Complex<f64>, D>, inverse: bool, threads: Option<usize>)
+where
+    D: Dimension,
+{
+    for ax in 0..data.ndim() {
+        let n = data.len_of(Axis(ax));
+        let fft = COMPLEX_PLANNER.with_borrow_mut(|planner| {
+            if inverse {
+                planner.plan_fft_inverse(n)
+            } else {
+                planner.plan_fft_forward(n)
+            }
+        });
+        let fft_lane = |mut lane: ArrayViewMut1<Complex<f64>>| match lane.as_slice_mut() {
+            Some(s) => fft.process(s),
+            None => {
+                let mut buf = lane.to_vec();
+                fft.process(&mut buf);
+                lane.iter_mut().zip(buf).for_each(|(l, b)| *l = b);
+            }
+        };
+        par!(threads,
+            seq_exp: Zip::from(data.lanes_mut(Axis(ax))).for_each(fft_lane),
+            par_exp: Zip::from(data.lanes_mut(Axis(ax))).par_for_each(fft_lane));
+    }
 }
@@ -0,0 +1,140 @@
+use ndarray::{Array, ArrayBase, ArrayD, ArrayView, Dimension, IntoDimension, IxDyn, ViewRepr};
+use rayon::prelude::*;
+
+use crate::filter::border::{BorderMode, pad_border};
+use crate::prelude::*;
+
+/// A padded n-dimensional image and the flat offsets of the `true` values of a
+/// footprint in it, to scan the footprint neighborhood of each pixel lane by
+/// lane along the last axis.
+pub(crate) struct FootprintScan<T> {
+    /// The padded image in standard layout.
+    padded: ArrayD<T>,
+    /// The flat offsets of the footprint values in the padded image.
+    offsets: Vec<isize>,
+    /// The shape of the unpadded image.
+    shape: Vec<usize>,
+}
+
+impl<T: AsNumeric> FootprintScan<T> {
+    /// Pad `data` by the radii of `footprint` with the `border` mode and
+    /// compute the flat offsets of the `footprint` values.
+    ///
+    /// Returns an error if `footprint` and `data` do not have the same number
+    /// of dimensions, if an axis length of `footprint` is even or if
+    /// `footprint` has no `true` values.
+    pub(crate) fn new<D: Dimension>(
+        data: ArrayBase<ViewRepr<&T>, D>,
+        footprint: ArrayView<bool, D>,
+        border: BorderMode,
+        threads: Option<usize>,
+    ) -> Result<Self, ImgalError> {
+        if footprint.ndim() != data.ndim() {
+            return Err(ImgalError::MismatchedDimensionLengths {
+                a_name: "footprint",
+                a_dim_len: footprint.ndim(),
+                b_name: "data",
+                b_dim_len: data.ndim(),
+            });
+        }
+        if let Some(ax) = footprint.shape().iter().position(|l| l.is_multiple_of(2)) {
+            return Err(ImgalError::InvalidAxisValueNotAMultipleOf {
+                arr_name: "footprint",
+                axis_idx: ax,
+                multiple: 2,
+            });
+        }
+        let radii: Vec<usize> = footprint.shape().iter().map(|l| l / 2).collect();
+        let padded: ArrayD<T> = pad_border(data.view().into_dyn(), &radii, border, threads)?
+            .as_standard_layout()
+            .into_owned();
+        let p_strides = padded.strides();
+        let offsets: Vec<isize> = footprint
+            .indexed_iter()
+            .filter(|(_, v)| **v)
+            .map(|(p, _)| {
+                p.into_dimension()
+                    .slice()
+                    .iter()
+                    .zip(p_strides.iter())
+                    .map(|(&i, &s)| i as isize * s)
+                    .sum()
+            })
+            .collect();
+        if offsets.is_empty() {
+            return Err(ImgalError::InvalidGeneric {
+                msg: "Invalid footprint, the footprint has no true values.",
+            });
+        }
+        Ok(Self {
+            padded,
+            offsets,
+            shape: data.shape().to_vec(),
+        })
+    }
+
+    /// The padded image as a flat slice.
+    pub(crate) fn src(&self) -> &[T] {
+        self.padded.as_slice().unwrap()
+    }
+
+    /// The flat offsets of the footprint values in the padded image.
+    pub(crate) fn offsets(&self) -> &[isize] {
+        &self.offsets
+    }
+
+    /// The flat step between neighboring pixels along the last axis.
+    pub(crate) fn step(&self) -> isize {
+        self.padded.strides()[self.shape.len() - 1]
+    }
+
+    /// Compute an output image with the shape of the unpadded image, one lane
+    /// along the last axis at a time. `row_calc` receives the per-thread state
+    /// created by `init`, the flat index in the padded image of the footprint
+    /// origin of the first pixel of the lane and the output lane.
+    pub(crate) fn scan<U, D, S, I, R>(
+        &self,
+        threads: Option<usize>,
+        init: I,
+        row_calc: R,
+    ) -> Array<U, D>
+    where
+        U: Clone + Default + Send,
+        D: Dimension,
+        I: Fn() -> S + Send + Sync,
+        R: Fn(&mut S, isize, &mut [U]) + Send + Sync,
+    {
+        let mut out = Array::<U, IxDyn>::from_elem(self.shape.as_slice(), U::default());
+        if out.is_empty() {
+            return out.into_dimensionality::<D>().unwrap();
+        }
+        let last = self.shape.len() - 1;
+        let row_len = self.shape[last];
+        let p_strides = self.padded.strides();
+        // the start of each output row (i.e. lane along the last axis) in the
+        // padded image
+        let row_base = |j: usize| -> isize {
+            let mut rem = j;
+            let mut base = 0;
+            for ax in (0..last).rev() {
+                let len = self.shape[ax];
+                base += (rem % len) as isize * p_strides[ax];
+                rem /= len;
+            }
+            base
+        };
+        let rows = out.as_slice_mut().unwrap();
+        par!(threads,
+        seq_exp: {
+            let mut state = init();
+            rows.chunks_mut(row_len)
+                .enumerate()
+                .for_each(|(j, lane)| row_calc(&mut state, row_base(j), lane));
+        },
+        par_exp: rows.par_chunks_mut(row_len)
+            .enumerate()
+            .for_each_init(&init, |state, (j, lane)| row_calc(state, row_base(j), lane)));
+        out.into_dimensionality::<D>()
+            .expect("Failed to convert the footprint scan to the input dimensionality.")
+    }
+}
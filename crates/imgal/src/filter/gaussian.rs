@@ -0,0 +1,210 @@
+use ndarray::{
+    Array, ArrayBase, ArrayD, ArrayView1, ArrayViewMut1, AsArray, Axis, Dimension, ViewRepr, Zip,
+};
+
+use crate::filter::border::{BorderMode, pad_axis};
+use crate::prelude::*;
+
+/// Blur an n-dimensional image with a separable Gaussian filter.
+///
+/// # Description
+///
+/// Convolves an n-dimensional image with a Gaussian kernel with an independent
+/// standard deviation per axis (*e.g.* for anisotropic 3D voxels). The
+/// Gaussian is separable, so the image is convolved with a normalized 1D
+/// kernel along each axis in turn:
+///
+/// ```text
+/// G(x) = exp(-x² / 2σ²) / Σ exp(-x² / 2σ²),  |x| ≤ ⌈3σ⌉
+/// ```
+///
+/// The image is extended at its borders along each axis with
+/// `transform::pad` according to the `border` mode. With `BorderMode::Reflect`
+/// a kernel radius `⌈3σ⌉` greater than or equal to the axis length is
+/// supported by mirroring the image repeatedly. The lanes along each axis are
+/// convolved in parallel.
+///
+/// # Arguments
+///
+/// * `data`: The input n-dimensional image.
+/// * `sigma`: The standard deviation of the Gaussian in pixels of each axis.
+///   An axis with a standard deviation of `0.0` is not blurred.
+/// * `border`: The border handling of the image. If `None`, then
+///   `border = BorderMode::Reflect`.
+/// * `threads`: The requested number of threads to use for parallel execution.
+///   If `None` or `Some(1)` sequential execution is used. If `Some(0)`, then
+///   the maximum available parallelism is used. Thread counts are clamped to
+///   the systems maximum.
+///
+/// # Returns
+///
+/// * `Ok(Array<f64, D>)`: The Gaussian blurred image.
+/// * `Err(ImgalError)`: If `sigma.len() != data.ndim()`. If a `sigma` value is
+///   negative or not finite.
+pub fn gaussian_blur<'a, T, A, D>(
+    data: A,
+    sigma: &[f64],
+    border: Option<BorderMode>,
+    threads: Option<usize>,
+) -> Result<Array<f64, D>, ImgalError>
+where
+    A: AsArray<'a, T, D>,
+    D: Dimension,
+    T: 'a + AsNumeric,
+{
+    let data: ArrayBase<ViewRepr<&'a T>, D> = data.into();
+    if sigma.len() != data.ndim() {
+        return Err(ImgalError::InvalidArrayLengthExpected {
+            arr_name: "sigma",
+            expected: data.ndim(),
+            got: sigma.len(),
+        });
+    }
+    if let Some(&s) = sigma.iter().find(|s| !(s.is_finite() && **s >= 0.0)) {
+        return Err(ImgalError::InvalidParameterValueOutsideRange {
+            param_name: "sigma",
+            value: s,
+            min: 0.0,
+            max: f64::INFINITY,
+        });
+    }
+    let border = border.unwrap_or_default();
+    let mut blur: ArrayD<f64> = data.mapv(|v| v.to_f64()).into_dyn();
+    for (ax, &s) in sigma.iter().enumerate() {
+        if s == 0.0 {
+            continue;
+        }
+        correlate_axis(&mut blur, ax, &gaussian_kernel_1d(s), border, threads)?;
+    }
+    Ok(blur
+        .into_dimensionality::<D>()
+        .expect("Failed to convert the blurred image to the input dimensionality."))
+}
+
+/// Filter an n-dimensional image with a difference of Gaussians band-pass.
+///
+/// # Description
+///
+/// Subtracts a wide Gaussian blur from a narrow Gaussian blur of an
+/// n-dimensional image (see `gaussian_blur`), a cheap band-pass filter that
+/// enhances structures between the two scales (*e.g.* spots with a radius of
+/// about `√2 × σ_low`) while removing noise and the smooth background:
+///
+/// ```text
+/// DoG = G(σ_low) ∗ I - G(σ_high) ∗ I
+/// σ_high = ratio × σ_low
+/// ```
+///
+/// The wide standard deviations are either given explicitly per axis or as a
+/// `ratio` of the narrow standard deviations. A ratio of `1.6` approximates
+/// the Laplacian of Gaussian.
+///
+/// # Arguments
+///
+/// * `data`: The input n-dimensional image.
+/// * `sigma_low`: The standard deviation of the narrow Gaussian in pixels of
+///   each axis.
+/// * `sigma_high`: The standard deviation of the wide Gaussian in pixels of
+///   each axis. If `None`, then `sigma_high = ratio × sigma_low`.
+/// * `ratio`: The ratio of the wide to the narrow standard deviations, only
+///   used if `sigma_high` is `None`. If `None`, then `ratio = 1.6`.
+/// * `border`: The border handling of the image. If `None`, then
+///   `border = BorderMode::Reflect`.
+/// * `threads`: The requested number of threads to use for parallel execution.
+///   If `None` or `Some(1)` sequential execution is used. If `Some(0)`, then
+///   the maximum available parallelism is used. Thread counts are clamped to
+///   the systems maximum.
+///
+/// # Returns
+///
+/// * `Ok(Array<f64, D>)`: The difference of Gaussians filtered image.
+/// * `Err(ImgalError)`: If `sigma_low.len() != data.ndim()` or
+///   `sigma_high.len() != data.ndim()`. If a standard deviation is negative or
+///   not finite. If `ratio <= 1.0`. If a `sigma_high` value is less than its
+///   `sigma_low` value.
+pub fn difference_of_gaussians<'a, T, A, D>(
+    data: A,
+    sigma_low: &[f64],
+    sigma_high: Option<&[f64]>,
+    ratio: Option<f64>,
+    border: Option<BorderMode>,
+    threads: Option<usize>,
+) -> Result<Array<f64, D>, ImgalError>
+where
+    A: AsArray<'a, T, D>,
+    D: Dimension,
+    T: 'a + AsNumeric,
+{
+    let data: ArrayBase<ViewRepr<&'a T>, D> = data.into();
+    let sigma_high: Vec<f64> = match sigma_high {
+        Some(s) => {
+            if let Some(i) = s.iter().zip(sigma_low.iter()).position(|(h, l)| h < l) {
+                return Err(ImgalError::InvalidParameterValueOutsideRange {
+                    param_name: "sigma_high",
+                    value: s[i],
+                    min: sigma_low[i],
+                    max: f64::INFINITY,
+                });
+            }
+            s.to_vec()
+        }
+        None => {
+            let ratio = ratio.unwrap_or(1.6);
+            if ratio.is_nan() || ratio <= 1.0 {
+                return Err(ImgalError::InvalidParameterValueOutsideRange {
+                    param_name: "ratio",
+                    value: ratio,
+                    min: 1.0,
+                    max: f64::INFINITY,
+                });
+            }
+            sigma_low.iter().map(|s| s * ratio).collect()
+        }
+    };
+    let mut low = gaussian_blur(data.view(), sigma_low, border, threads)?;
+    let high = gaussian_blur(data.view(), &sigma_high, border, threads)?;
+    par!(threads,
+        seq_exp: Zip::from(&mut low).and(&high).for_each(|l, &h| *l -= h),
+        par_exp: Zip::from(&mut low).and(&high).par_for_each(|l, &h| *l -= h));
+    Ok(low)
+}
+
+/// Create a normalized 1D Gaussian kernel with a radius of `⌈3σ⌉`.
+pub(crate) fn gaussian_kernel_1d(sigma: f64) -> Vec<f64> {
+    let radius = (3.0 * sigma).ceil() as isize;
+    let kernel: Vec<f64> = (-radius..=radius)
+        .map(|x| (-0.5 * (x as f64 / sigma).powi(2)).exp())
+        .collect();
+    let sum: f64 = kernel.iter().sum();
+    kernel.iter().map(|k| k / sum).collect()
+}
+
+/// Correlate an n-dimensional image in place with an odd length 1D kernel along
+/// a single axis, with the image extended at its borders by `border`.
+pub(crate) fn correlate_axis(
+    data: &mut ArrayD<f64>,
+    axis: usize,
+    kernel: &[f64],
+    border: BorderMode,
+    threads: Option<usize>,
+) -> Result<(), ImgalError> {
+    let radius = kernel.len() / 2;
+    let padded = pad_axis(data.view(), axis, radius, border, threads)?;
+    let correlate = |src: ArrayView1<f64>, mut dst: ArrayViewMut1<f64>| {
+        dst.iter_mut().enumerate().for_each(|(i, d)| {
+            *d = kernel
+                .iter()
+                .zip(src.iter().skip(i))
+                .map(|(k, v)| k * v)
+                .sum();
+        });
+    };
+    par!(threads,
+        seq_exp: Zip::from(padded.lanes(Axis(axis)))
+            .and(data.lanes_mut(Axis(axis)))
+            .for_each(correlate),
+        par_exp: Zip::from(padded.lanes(Axis(axis)))
+            .and(data.lanes_mut(Axis(axis)))
+            .par_for_each(correlate));
+    Ok(())
+}
@@ -0,0 +1,104 @@
+use ndarray::{Array, ArrayBase, ArrayView, AsArray, Dimension, ViewRepr, Zip};
+
+use crate::filter::clipped_uniform_filter;
+use crate::prelude::*;
+use crate::validate::check_shapes;
+
+/// Smooth an n-dimensional image with the edge-preserving guided filter.
+///
+/// # Description
+///
+/// Smooths an n-dimensional image `p` with the guided filter, a local linear
+/// model of a guidance image `I` (*e.g.* the raw intensity image when refining
+/// a probability or SACA *z-score* map). In each window the output is a linear
+/// transform of the guide, so edges of the guide are preserved:
+///
+/// ```text
+/// a = cov(I, p) / (var(I) + ε)
+/// b = mean(p) - a × mean(I)
+/// q = mean(a) × I + mean(b)
+/// ```
+///
+/// Where the means, variances and covariances are computed in hypercube
+/// windows of side `2 × radius + 1`, clipped at the image borders. The window
+/// means are computed from integral images (see `filter::uniform_filter`), so
+/// the cost per pixel is independent of the `radius` (*i.e.* O(1)). Without a guide the image is
+/// its own guide, an edge-preserving smoothing filter. The regularization `ε`
+/// is in squared intensity units of the guide, regions with a guide variance
+/// much smaller than `ε` are smoothed while edges with a larger variance are
+/// preserved.
+///
+/// # Arguments
+///
+/// * `data`: The input n-dimensional image to filter.
+/// * `guide`: The optional guidance image with the same shape as `data`. If
+///   `None`, then `data` is used as the guide.
+/// * `radius`: The window radius in pixels. If `None`, then `radius = 2`.
+/// * `eps`: The regularization `ε`, larger values smooth more across edges.
+///   If `None`, then `eps = 0.01`.
+/// * `threads`: The requested number of threads to use for parallel execution.
+///   If `None` or `Some(1)` sequential execution is used. If `Some(0)`, then
+///   the maximum available parallelism is used. Thread counts are clamped to
+///   the systems maximum.
+///
+/// # Returns
+///
+/// * `Ok(Array<f64, D>)`: The guided filtered image.
+/// * `Err(ImgalError)`: If the `guide` shape does not match the `data` shape.
+///   If `eps <= 0.0`.
+///
+/// # Reference
+///
+/// <https://doi.org/10.1109/TPAMI.2012.213>
+pub fn guided<'a, T, A, D>(
+    data: A,
+    guide: Option<ArrayView<f64, D>>,
+    radius: Option<usize>,
+    eps: Option<f64>,
+    threads: Option<usize>,
+) -> Result<Array<f64, D>, ImgalError>
+where
+    A: AsArray<'a, T, D>,
+    D: Dimension,
+    T: 'a + AsNumeric,
+{
+    let data: ArrayBase<ViewRepr<&'a T>, D> = data.into();
+    let radius = radius.unwrap_or(2);
+    let eps = eps.unwrap_or(0.01);
+    if eps.is_nan() || eps <= 0.0 {
+        return Err(ImgalError::InvalidParameterValueOutsideRange {
+            param_name: "eps",
+            value: eps,
+            min: 0.0,
+            max: f64::INFINITY,
+        });
+    }
+    let p = data.mapv(|v| v.to_f64());
+    let guide = match guide {
+        Some(g) => {
+            check_shapes("guide", g.shape(), "data", data.shape())?;
+            g.to_owned()
+        }
+        None => p.clone(),
+    };
+    // the window means are clipped at the image borders
+    let radius = vec![radius; data.ndim()];
+    let box_mean = |arr: &Array<f64, D>| clipped_uniform_filter(arr, &radius, threads);
+    let mean_i = box_mean(&guide)?;
+    let mean_p = box_mean(&p)?;
+    let mean_ii = box_mean(&(&guide * &guide))?;
+    let mean_ip = box_mean(&(&guide * &p))?;
+    let mut a = mean_ip - &mean_i * &mean_p;
+    Zip::from(&mut a)
+        .and(&mean_ii)
+        .and(&mean_i)
+        .for_each(|a, &ii, &i| *a /= ii - i * i + eps);
+    let b = mean_p - &a * &mean_i;
+    let mean_a = box_mean(&a)?;
+    let mut q = box_mean(&b)?;
+    Zip::from(&mut q)
+        .and(&guide)
+        .and(&mean_a)
+        .for_each(|q, &i, &ma| *q += ma * i);
+    Ok(q)
+}
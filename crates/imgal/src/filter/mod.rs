@@ -3,6 +3,34 @@
 //! This module provides *n*-dimensional image filtering functions using various
 //! techniques like convolution.
 
+mod border;
+mod census;
 mod convolve;
+mod diffusion;
+mod footprint;
+mod gaussian;
+mod guided;
+mod rank;
+mod sobel;
+mod structure_tensor;
+mod uniform;
 
-pub use convolve::{fft_convolve_1d, fft_deconvolve_1d};
+pub use border::BorderMode;
+pub(crate) use border::pad_border;
+pub use census::{census_transform, rank_transform};
+pub use convolve::ConvolveMode;
+pub(crate) use convolve::fft_nd;
+pub use convolve::{fft_convolve, fft_convolve_1d, fft_deconvolve_1d};
+pub use diffusion::Conductance;
+pub use diffusion::anisotropic_diffusion;
+pub(crate) use footprint::FootprintScan;
+pub(crate) use gaussian::{correlate_axis, gaussian_kernel_1d};
+pub use gaussian::{difference_of_gaussians, gaussian_blur};
+pub use guided::guided;
+pub use rank::{maximum_filter, median_filter, minimum_filter, percentile_filter};
+pub use sobel::SobelOutput;
+pub use sobel::sobel;
+pub use structure_tensor::StructureTensorOutput;
+pub use structure_tensor::structure_tensor;
+pub(crate) use uniform::clipped_uniform_filter;
+pub use uniform::uniform_filter;
@@ -0,0 +1,336 @@
+use std::cmp::Ordering;
+
+use ndarray::{Array, ArrayBase, ArrayView, AsArray, Dimension, ViewRepr};
+
+use crate::filter::BorderMode;
+use crate::filter::footprint::FootprintScan;
+use crate::prelude::*;
+
+/// Filter an n-dimensional image with a median filter.
+///
+/// # Description
+///
+/// Replaces each pixel with the median of its neighborhood, given by a boolean
+/// `footprint` (*e.g.* a rectangle of `true` values or a circle or sphere from
+/// `kernel::neighborhood`) centered on the pixel. The median filter removes
+/// speckle and shot noise while preserving edges, a common cleanup before
+/// thresholding. For an even number of neighborhood values the lower median
+/// is returned, so the output has the input type.
+///
+/// Integer types with at most `65536` distinct values (*e.g.* `u8` and `u16`)
+/// use a sliding window histogram along the last axis, only the pixels
+/// entering and leaving the footprint are updated between neighboring pixels
+/// and the median is found with a two-level histogram. Other types (*e.g.*
+/// floats) select the median of each window with a partial sort. The image is
+/// extended at its borders with `transform::pad` according to the `border`
+/// mode.
+///
+/// # Arguments
+///
+/// * `data`: The input n-dimensional image.
+/// * `footprint`: The boolean neighborhood with the same dimensionality as
+///   `data` and an odd length along each axis, centered on the filtered pixel.
+/// * `border`: The border handling of the image. If `None`, then
+///   `border = BorderMode::Reflect`.
+/// * `threads`: The requested number of threads to use for parallel execution.
+///   If `None` or `Some(1)` sequential execution is used. If `Some(0)`, then
+///   the maximum available parallelism is used. Thread counts are clamped to
+///   the systems maximum.
+///
+/// # Returns
+///
+/// * `Ok(Array<T, D>)`: The median filtered image.
+/// * `Err(ImgalError)`: If `footprint` and `data` do not have the same number
+///   of dimensions. If an axis length of `footprint` is even. If `footprint`
+///   has no `true` values.
+///
+/// # Reference
+///
+/// <https://doi.org/10.1109/TIP.2007.902329>
+pub fn median_filter<'a, T, A, D>(
+    data: A,
+    footprint: ArrayView<bool, D>,
+    border: Option<BorderMode>,
+    threads: Option<usize>,
+) -> Result<Array<T, D>, ImgalError>
+where
+    A: AsArray<'a, T, D>,
+    D: Dimension,
+    T: 'a + AsNumeric,
+{
+    rank_filter(data.into(), footprint, border, threads, |n| (n - 1) / 2)
+}
+
+/// Filter an n-dimensional image with a minimum filter.
+///
+/// # Description
+///
+/// Replaces each pixel with the minimum of its neighborhood, given by a
+/// boolean `footprint` (*e.g.* a circle or sphere from `kernel::neighborhood`)
+/// centered on the pixel. The minimum filter with a flat footprint is the
+/// grayscale erosion, it shrinks bright objects and removes bright details
+/// smaller than the footprint. The minimum is found as for `median_filter`.
+///
+/// # Arguments
+///
+/// * `data`: The input n-dimensional image.
+/// * `footprint`: The boolean neighborhood with the same dimensionality as
+///   `data` and an odd length along each axis, centered on the filtered pixel.
+/// * `border`: The border handling of the image. If `None`, then
+///   `border = BorderMode::Reflect`.
+/// * `threads`: The requested number of threads to use for parallel execution.
+///   If `None` or `Some(1)` sequential execution is used. If `Some(0)`, then
+///   the maximum available parallelism is used. Thread counts are clamped to
+///   the systems maximum.
+///
+/// # Returns
+///
+/// * `Ok(Array<T, D>)`: The minimum filtered image.
+/// * `Err(ImgalError)`: If `footprint` and `data` do not have the same number
+///   of dimensions. If an axis length of `footprint` is even. If `footprint`
+///   has no `true` values.
+pub fn minimum_filter<'a, T, A, D>(
+    data: A,
+    footprint: ArrayView<bool, D>,
+    border: Option<BorderMode>,
+    threads: Option<usize>,
+) -> Result<Array<T, D>, ImgalError>
+where
+    A: AsArray<'a, T, D>,
+    D: Dimension,
+    T: 'a + AsNumeric,
+{
+    rank_filter(data.into(), footprint, border, threads, |_| 0)
+}
+
+/// Filter an n-dimensional image with a maximum filter.
+///
+/// # Description
+///
+/// Replaces each pixel with the maximum of its neighborhood, given by a
+/// boolean `footprint` (*e.g.* a circle or sphere from `kernel::neighborhood`)
+/// centered on the pixel. The maximum filter with a flat footprint is the
+/// grayscale dilation, it grows bright objects and fills dark details smaller
+/// than the footprint. The maximum is found as for `median_filter`.
+///
+/// # Arguments
+///
+/// * `data`: The input n-dimensional image.
+/// * `footprint`: The boolean neighborhood with the same dimensionality as
+///   `data` and an odd length along each axis, centered on the filtered pixel.
+/// * `border`: The border handling of the image. If `None`, then
+///   `border = BorderMode::Reflect`.
+/// * `threads`: The requested number of threads to use for parallel execution.
+///   If `None` or `Some(1)` sequential execution is used. If `Some(0)`, then
+///   the maximum available parallelism is used. Thread counts are clamped to
+///   the systems maximum.
+///
+/// # Returns
+///
+/// * `Ok(Array<T, D>)`: The maximum filtered image.
+/// * `Err(ImgalError)`: If `footprint` and `data` do not have the same number
+///   of dimensions. If an axis length of `footprint` is even. If `footprint`
+///   has no `true` values.
+pub fn maximum_filter<'a, T, A, D>(
+    data: A,
+    footprint: ArrayView<bool, D>,
+    border: Option<BorderMode>,
+    threads: Option<usize>,
+) -> Result<Array<T, D>, ImgalError>
+where
+    A: AsArray<'a, T, D>,
+    D: Dimension,
+    T: 'a + AsNumeric,
+{
+    rank_filter(data.into(), footprint, border, threads, |n| n - 1)
+}
+
+/// Filter an n-dimensional image with a percentile filter.
+///
+/// # Description
+///
+/// Replaces each pixel with a percentile of its neighborhood, given by a
+/// boolean `footprint` (*e.g.* a circle or sphere from `kernel::neighborhood`)
+/// centered on the pixel. The percentile is the neighborhood value with the
+/// nearest rank, so the output has the input type:
+///
+/// ```text
+/// k = round(p / 100 × (n - 1))
+/// ```
+///
+/// Where `k` is the zero-based rank in ascending order and `n` is the number
+/// of footprint values. The `0.0`, `50.0` and `100.0` percentiles are the
+/// minimum, (lower) median and maximum filters. Low percentiles are robust
+/// alternatives to the grayscale erosion (*e.g.* for background estimation).
+/// The percentile is found as for `median_filter`.
+///
+/// # Arguments
+///
+/// * `data`: The input n-dimensional image.
+/// * `footprint`: The boolean neighborhood with the same dimensionality as
+///   `data` and an odd length along each axis, centered on the filtered pixel.
+/// * `percentile`: The percentile in the range `0.0` to `100.0`.
+/// * `border`: The border handling of the image. If `None`, then
+///   `border = BorderMode::Reflect`.
+/// * `threads`: The requested number of threads to use for parallel execution.
+///   If `None` or `Some(1)` sequential execution is used. If `Some(0)`, then
+///   the maximum available parallelism is used. Thread counts are clamped to
+///   the systems maximum.
+///
+/// # Returns
+///
+/// * `Ok(Array<T, D>)`: The percentile filtered image.
+/// * `Err(ImgalError)`: If `percentile` is outside the range `0.0` to
+///   `100.0`. If `footprint` and `data` do not have the same number of
+///   dimensions. If an axis length of `footprint` is even. If `footprint` has
+///   no `true` values.
+pub fn percentile_filter<'a, T, A, D>(
+    data: A,
+    footprint: ArrayView<bool, D>,
+    percentile: f64,
+    border: Option<BorderMode>,
+    threads: Option<usize>,
+) -> Result<Array<T, D>, ImgalError>
+where
+    A: AsArray<'a, T, D>,
+    D: Dimension,
+    T: 'a + AsNumeric,
+{
+    if !(0.0..=100.0).contains(&percentile) {
+        return Err(ImgalError::InvalidParameterValueOutsideRange {
+            param_name: "percentile",
+            value: percentile,
+            min: 0.0,
+            max: 100.0,
+        });
+    }
+    rank_filter(data.into(), footprint, border, threads, |n| {
+        (percentile / 100.0 * (n - 1) as f64).round() as usize
+    })
+}
+
+/// Replace each pixel with the value of rank `rank(n)` (zero-based, ascending)
+/// of the `n` footprint values of its neighborhood.
+fn rank_filter<T, D, F>(
+    data: ArrayBase<ViewRepr<&T>, D>,
+    footprint: ArrayView<bool, D>,
+    border: Option<BorderMode>,
+    threads: Option<usize>,
+    rank: F,
+) -> Result<Array<T, D>, ImgalError>
+where
+    D: Dimension,
+    T: AsNumeric,
+    F: Fn(usize) -> usize,
+{
+    let scan = FootprintScan::new(data, footprint, border.unwrap_or_default(), threads)?;
+    let src = scan.src();
+    let offsets = scan.offsets();
+    let step = scan.step();
+    let rank = rank(offsets.len());
+    let hist_bins = T::MIN
+        .to_i128()
+        .zip(T::MAX.to_i128())
+        .filter(|(lo, hi)| hi - lo < 1 << 16)
+        .map(|(lo, hi)| ((hi - lo + 1) as usize, lo));
+    let filtered = match hist_bins {
+        Some((n_bins, lo)) => {
+            // the footprint pixels leaving and entering the window when it
+            // slides by one pixel along the last axis
+            let contains = |o: isize| offsets.contains(&o);
+            let leaving: Vec<isize> = offsets
+                .iter()
+                .copied()
+                .filter(|&o| !contains(o - step))
+                .collect();
+            let entering: Vec<isize> = offsets
+                .iter()
+                .copied()
+                .filter(|&o| !contains(o + step))
+                .collect();
+            let bin = |v: T| (v.to_i128().unwrap() - lo) as usize;
+            scan.scan(
+                threads,
+                || Histogram::new(n_bins),
+                |hist: &mut Histogram, base: isize, lane: &mut [T]| {
+                    offsets
+                        .iter()
+                        .for_each(|&o| hist.add(bin(src[(base + o) as usize]), 1));
+                    let n = lane.len();
+                    lane.iter_mut().enumerate().for_each(|(x, v)| {
+                        *v = T::from_i128(hist.kth(rank) as i128 + lo).unwrap();
+                        let pos = base + x as isize * step;
+                        if x + 1 < n {
+                            leaving
+                                .iter()
+                                .for_each(|&o| hist.add(bin(src[(pos + o) as usize]), -1));
+                            entering
+                                .iter()
+                                .for_each(|&o| hist.add(bin(src[(pos + step + o) as usize]), 1));
+                        } else {
+                            // clear the histogram for the next lane
+                            offsets
+                                .iter()
+                                .for_each(|&o| hist.add(bin(src[(pos + o) as usize]), -1));
+                        }
+                    });
+                },
+            )
+        }
+        None => scan.scan(
+            threads,
+            || Vec::with_capacity(offsets.len()),
+            |buf: &mut Vec<T>, base: isize, lane: &mut [T]| {
+                lane.iter_mut().enumerate().for_each(|(x, v)| {
+                    let pos = base + x as isize * step;
+                    buf.clear();
+                    buf.extend(offsets.iter().map(|&o| src[(pos + o) as usize]));
+                    let (_, kth, _) = buf.select_nth_unstable_by(rank, |a, b| {
+                        a.partial_cmp(b).unwrap_or(Ordering::Less)
+                    });
+                    *v = *kth;
+                });
+            },
+        ),
+    };
+    Ok(filtered)
+}
+
+/// A two-level histogram of integer bins with 256 fine bins per coarse bin.
+struct Histogram {
+    fine: Vec<u32>,
+    coarse: Vec<u32>,
+}
+
+impl Histogram {
+    /// Create an empty histogram with `n_bins` fine bins.
+    fn new(n_bins: usize) -> Self {
+        Self {
+            fine: vec![0; n_bins],
+            coarse: vec![0; n_bins.div_ceil(256)],
+        }
+    }
+
+    /// Add `count` (or remove if negative) to the bin `b`.
+    #[inline]
+    fn add(&mut self, b: usize, count: i32) {
+        self.fine[b] = self.fine[b].wrapping_add_signed(count);
+        self.coarse[b >> 8] = self.coarse[b >> 8].wrapping_add_signed(count);
+    }
+
+    /// Find the bin of the value with the zero-based rank `k`.
+    fn kth(&self, k: usize) -> usize {
+        let mut k = k as u32;
+        let mut c = 0;
+        while self.coarse[c] <= k {
+            k -= self.coarse[c];
+            c += 1;
+        }
+        let mut b = c << 8;
+        while self.fine[b] <= k {
+            k -= self.fine[b];
+            b += 1;
+        }
+        b
+    }
+}
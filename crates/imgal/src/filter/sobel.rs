@@ -0,0 +1,98 @@
+use ndarray::{Array, ArrayBase, ArrayD, AsArray, Dimension, ViewRepr, Zip};
+
+use crate::filter::border::BorderMode;
+use crate::filter::gaussian::correlate_axis;
+use crate::prelude::*;
+
+/// The per-axis derivatives and the gradient magnitude output of `sobel`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SobelOutput<D: Dimension> {
+    /// The derivative image along each axis, in axis order.
+    pub gradients: Vec<Array<f64, D>>,
+    /// The gradient magnitude image.
+    pub magnitude: Array<f64, D>,
+}
+
+/// Compute the Sobel gradients of an n-dimensional image.
+///
+/// # Description
+///
+/// Computes the derivative of an n-dimensional image (*e.g.* 2D or 3D) along
+/// each axis with the separable Sobel operator, a central difference along the
+/// derivative axis and a triangular smoothing along all other axes:
+///
+/// ```text
+/// Gₖ = I ⋆ (d on axis k) ⋆ (s on all other axes)
+/// d = [-1, 0, 1]
+/// s = [1, 2, 1]
+/// |G| = √(Σ Gₖ²)
+/// ```
+///
+/// Where `⋆` is the correlation, such that a positive derivative is an
+/// increase of intensity along the axis. The kernels are not normalized, so a
+/// 2D unit step has a maximum derivative of `4.0`. The image is extended at
+/// its borders with `transform::pad` according to the `border` mode. The
+/// gradient magnitude is used for edge-based quality control and as the input
+/// of edge detectors (*e.g.* Canny).
+///
+/// # Arguments
+///
+/// * `data`: The input n-dimensional image.
+/// * `border`: The border handling of the image. If `None`, then
+///   `border = BorderMode::Reflect`.
+/// * `threads`: The requested number of threads to use for parallel execution.
+///   If `None` or `Some(1)` sequential execution is used. If `Some(0)`, then
+///   the maximum available parallelism is used. Thread counts are clamped to
+///   the systems maximum.
+///
+/// # Returns
+///
+/// * `Ok(SobelOutput<D>)`: The derivative images along each axis and the
+///   gradient magnitude image.
+/// * `Err(ImgalError)`: If `data` has `0` dimensions.
+pub fn sobel<'a, T, A, D>(
+    data: A,
+    border: Option<BorderMode>,
+    threads: Option<usize>,
+) -> Result<SobelOutput<D>, ImgalError>
+where
+    A: AsArray<'a, T, D>,
+    D: Dimension,
+    T: 'a + AsNumeric,
+{
+    let data: ArrayBase<ViewRepr<&'a T>, D> = data.into();
+    if data.ndim() == 0 {
+        return Err(ImgalError::InvalidGeneric {
+            msg: "Invalid Sobel operator, the input data has 0 dimensions.",
+        });
+    }
+    let border = border.unwrap_or_default();
+    let input: ArrayD<f64> = data.mapv(|v| v.to_f64()).into_dyn();
+    let mut magnitude = ArrayD::<f64>::zeros(input.raw_dim());
+    let mut gradients = Vec::with_capacity(data.ndim());
+    for k in 0..data.ndim() {
+        let mut grad = input.clone();
+        for ax in 0..data.ndim() {
+            let kernel = if ax == k {
+                [-1.0, 0.0, 1.0]
+            } else {
+                [1.0, 2.0, 1.0]
+            };
+            correlate_axis(&mut grad, ax, &kernel, border, threads)?;
+        }
+        Zip::from(&mut magnitude)
+            .and(&grad)
+            .for_each(|m, &g| *m += g * g);
+        gradients.push(
+            grad.into_dimensionality::<D>()
+                .expect("Failed to convert the gradient to the input dimensionality."),
+        );
+    }
+    magnitude.mapv_inplace(f64::sqrt);
+    Ok(SobelOutput {
+        gradients,
+        magnitude: magnitude
+            .into_dimensionality::<D>()
+            .expect("Failed to convert the magnitude to the input dimensionality."),
+    })
+}
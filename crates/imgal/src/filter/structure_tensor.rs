@@ -0,0 +1,138 @@
+use ndarray::{Array2, ArrayBase, ArrayD, AsArray, Ix2, ViewRepr, Zip};
+
+use crate::filter::border::BorderMode;
+use crate::filter::gaussian::{correlate_axis, gaussian_blur};
+use crate::prelude::*;
+
+/// The tensor components and the derived orientation maps of
+/// `structure_tensor`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StructureTensorOutput {
+    /// The smoothed squared row derivative `Jrr`.
+    pub jrr: Array2<f64>,
+    /// The smoothed product of the row and col derivatives `Jrc`.
+    pub jrc: Array2<f64>,
+    /// The smoothed squared col derivative `Jcc`.
+    pub jcc: Array2<f64>,
+    /// The dominant local structure orientation in radians, in the range
+    /// `-π/2` to `π/2` from the col axis towards the row axis.
+    pub orientation: Array2<f64>,
+    /// The coherence in the range `0.0` (isotropic) to `1.0` (perfectly
+    /// oriented).
+    pub coherence: Array2<f64>,
+    /// The energy, the trace of the tensor `Jrr + Jcc`.
+    pub energy: Array2<f64>,
+}
+
+/// Compute the structure tensor and the orientation and coherence maps of a 2D
+/// image.
+///
+/// # Description
+///
+/// Computes the local orientation of a 2D image (*e.g.* of fibers, filaments
+/// or tissue textures) from the gradient structure tensor, the outer product
+/// of the image gradient averaged over a Gaussian window:
+///
+/// ```text
+/// J = G_ρ ∗ | ∂r∂r  ∂r∂c |
+///           | ∂r∂c  ∂c∂c |
+/// λ₁,₂ = (Jrr + Jcc) / 2 ± √(((Jrr - Jcc) / 2)² + Jrc²)
+/// θ = ½ × atan2(-2 × Jrc, Jrr - Jcc)
+/// coherence = (λ₁ - λ₂) / (λ₁ + λ₂)
+/// ```
+///
+/// Where the derivatives `∂r` and `∂c` are central differences of the image
+/// blurred with a Gaussian of `derivative_sigma` (the noise scale) and `G_ρ`
+/// is a Gaussian of `sigma` (the integration scale, about the size of the
+/// oriented structures). The orientation `θ` is the direction along the
+/// structures, perpendicular to the dominant gradient, and the coherence is
+/// `0.0` where the energy `λ₁ + λ₂` is `0.0`. The image is extended at its
+/// borders according to the `border` mode.
+///
+/// # Arguments
+///
+/// * `data`: The input 2D image.
+/// * `sigma`: The standard deviation of the integration Gaussian in pixels.
+/// * `derivative_sigma`: The standard deviation of the Gaussian blur before
+///   differentiation in pixels, `0.0` disables the blur. If `None`, then
+///   `derivative_sigma = 1.0`.
+/// * `border`: The border handling of the image. If `None`, then
+///   `border = BorderMode::Reflect`.
+/// * `threads`: The requested number of threads to use for parallel execution.
+///   If `None` or `Some(1)` sequential execution is used. If `Some(0)`, then
+///   the maximum available parallelism is used. Thread counts are clamped to
+///   the systems maximum.
+///
+/// # Returns
+///
+/// * `Ok(StructureTensorOutput)`: The tensor components and the orientation,
+///   coherence and energy images.
+/// * `Err(ImgalError)`: If `sigma` or `derivative_sigma` is negative or not
+///   finite. If a Gaussian kernel radius `⌈3σ⌉` is greater than or equal to an
+///   axis length, or an axis length is `1`, with `BorderMode::Reflect`.
+///
+/// # Reference
+///
+/// <https://doi.org/10.1016/0734-189X(87)90043-0>
+pub fn structure_tensor<'a, T, A>(
+    data: A,
+    sigma: f64,
+    derivative_sigma: Option<f64>,
+    border: Option<BorderMode>,
+    threads: Option<usize>,
+) -> Result<StructureTensorOutput, ImgalError>
+where
+    A: AsArray<'a, T, Ix2>,
+    T: 'a + AsNumeric,
+{
+    let data: ArrayBase<ViewRepr<&'a T>, Ix2> = data.into();
+    let derivative_sigma = derivative_sigma.unwrap_or(1.0);
+    for (name, s) in [("sigma", sigma), ("derivative_sigma", derivative_sigma)] {
+        if !(s.is_finite() && s >= 0.0) {
+            return Err(ImgalError::InvalidParameterValueOutsideRange {
+                param_name: name,
+                value: s,
+                min: 0.0,
+                max: f64::INFINITY,
+            });
+        }
+    }
+    let border = border.unwrap_or_default();
+    let smooth: ArrayD<f64> =
+        gaussian_blur(&data, &[derivative_sigma; 2], Some(border), threads)?.into_dyn();
+    let mut grads = [smooth.clone(), smooth];
+    for (ax, grad) in grads.iter_mut().enumerate() {
+        correlate_axis(grad, ax, &[-0.5, 0.0, 0.5], border, threads)?;
+    }
+    let [dr, dc] = grads;
+    let product = |a: &ArrayD<f64>, b: &ArrayD<f64>| -> Result<Array2<f64>, ImgalError> {
+        Ok(gaussian_blur(&(a * b), &[sigma; 2], Some(border), threads)?
+            .into_dimensionality::<Ix2>()
+            .expect("Failed to convert the tensor component to the input dimensionality."))
+    };
+    let jrr = product(&dr, &dr)?;
+    let jrc = product(&dr, &dc)?;
+    let jcc = product(&dc, &dc)?;
+    let mut orientation = Array2::<f64>::zeros(jrr.raw_dim());
+    let mut coherence = Array2::<f64>::zeros(jrr.raw_dim());
+    let energy = &jrr + &jcc;
+    Zip::from(&mut orientation)
+        .and(&mut coherence)
+        .and(&jrr)
+        .and(&jrc)
+        .and(&jcc)
+        .and(&energy)
+        .for_each(|o, c, &rr, &rc, &cc, &e| {
+            *o = 0.5 * (-2.0 * rc).atan2(rr - cc);
+            let diff = ((rr - cc) * (rr - cc) + 4.0 * rc * rc).sqrt();
+            *c = if e > 0.0 { (diff / e).min(1.0) } else { 0.0 };
+        });
+    Ok(StructureTensorOutput {
+        jrr,
+        jrc,
+        jcc,
+        orientation,
+        coherence,
+        energy,
+    })
+}
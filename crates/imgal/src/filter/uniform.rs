@@ -0,0 +1,138 @@
+use ndarray::{Array, Array1, ArrayBase, ArrayD, AsArray, Axis, Dimension, Slice, ViewRepr};
+use rayon::prelude::*;
+
+use crate::filter::border::{BorderMode, pad_border};
+use crate::image::integral_image;
+use crate::prelude::*;
+
+/// Filter an n-dimensional image with a uniform (box) mean filter.
+///
+/// # Description
+///
+/// Computes the mean of an n-dimensional image in hyperrectangle windows of
+/// side `2 × rₖ + 1` along each axis `k` centered on each pixel. The window
+/// sums are computed from the integral image (see `image::integral_image`) of
+/// the padded image as the inclusion-exclusion sum of its `2ᴰ` window corners:
+///
+/// ```text
+/// mean(x) = Σ (-1)ᴰ⁻ᶜ S(corner) / Π (2rₖ + 1)
+/// ```
+///
+/// Where `c` is the number of upper corner coordinates, so the cost per pixel
+/// is independent of the window size (*i.e.* O(1)), unlike a direct or
+/// separable convolution. The image is extended at its borders with
+/// `transform::pad` according to the `border` mode. With `BorderMode::Reflect`
+/// a radius greater than or equal to the axis length is supported by mirroring
+/// the image repeatedly. The pixels are filtered in parallel.
+///
+/// # Arguments
+///
+/// * `data`: The input n-dimensional image.
+/// * `radius`: The window radius in pixels of each axis. An axis with a radius
+///   of `0` is not filtered.
+/// * `border`: The border handling of the image. If `None`, then
+///   `border = BorderMode::Reflect`.
+/// * `threads`: The requested number of threads to use for parallel execution.
+///   If `None` or `Some(1)` sequential execution is used. If `Some(0)`, then
+///   the maximum available parallelism is used. Thread counts are clamped to
+///   the systems maximum.
+///
+/// # Returns
+///
+/// * `Ok(Array<f64, D>)`: The uniform filtered image.
+/// * `Err(ImgalError)`: If `radius.len() != data.ndim()`.
+pub fn uniform_filter<'a, T, A, D>(
+    data: A,
+    radius: &[usize],
+    border: Option<BorderMode>,
+    threads: Option<usize>,
+) -> Result<Array<f64, D>, ImgalError>
+where
+    A: AsArray<'a, T, D>,
+    D: Dimension,
+    T: 'a + AsNumeric,
+{
+    let data: ArrayBase<ViewRepr<&'a T>, D> = data.into();
+    if radius.len() != data.ndim() {
+        return Err(ImgalError::InvalidArrayLengthExpected {
+            arr_name: "radius",
+            expected: data.ndim(),
+            got: radius.len(),
+        });
+    }
+    let input: ArrayD<f64> = data.mapv(|v| v.to_f64()).into_dyn();
+    let padded = pad_border(input.view(), radius, border.unwrap_or_default(), threads)?;
+    // the integral image with a leading zero along each axis, so the window
+    // sums need no special case at the lower borders
+    let shape: Vec<usize> = padded.shape().iter().map(|l| l + 1).collect();
+    let mut table = ArrayD::<f64>::zeros(shape);
+    table
+        .slice_each_axis_mut(|_| Slice::from(1..))
+        .assign(&integral_image(&padded, threads));
+    let t_strides: Vec<isize> = table.strides().to_vec();
+    let ndim = data.ndim();
+    // the flat offset and sign of each upper and lower window corner
+    let corners: Vec<(isize, f64)> = (0..1usize << ndim)
+        .map(|c| {
+            let mut offset = 0;
+            let mut sign = 1.0;
+            for ax in 0..ndim {
+                if c >> ax & 1 == 1 {
+                    offset += (2 * radius[ax] + 1) as isize * t_strides[ax];
+                } else {
+                    sign = -sign;
+                }
+            }
+            (offset, sign)
+        })
+        .collect();
+    let volume: f64 = radius.iter().map(|&r| (2 * r + 1) as f64).product();
+    let src = table.as_slice().unwrap();
+    let shape = data.shape().to_vec();
+    let pixel_mean = |j: usize| -> f64 {
+        // unravel the output index into its lower window corner in the table
+        let mut rem = j;
+        let mut pos = 0;
+        for ax in (0..ndim).rev() {
+            pos += (rem % shape[ax]) as isize * t_strides[ax];
+            rem /= shape[ax];
+        }
+        corners
+            .iter()
+            .map(|&(o, s)| s * src[(pos + o) as usize])
+            .sum::<f64>()
+            / volume
+    };
+    let means: Vec<f64> = par!(threads,
+        seq_exp: (0..data.len()).map(pixel_mean).collect(),
+        par_exp: (0..data.len()).into_par_iter().map(pixel_mean).collect());
+    Ok(Array::from_shape_vec(data.raw_dim(), means).unwrap())
+}
+
+/// Compute the mean in hyperrectangle windows of side `2 × rₖ + 1` clipped at
+/// the image borders, *i.e.* only the pixels inside of the image are averaged.
+/// The zero padded `uniform_filter` is rescaled by the ratio of the full to the
+/// clipped window size along each axis.
+pub(crate) fn clipped_uniform_filter<'a, T, A, D>(
+    data: A,
+    radius: &[usize],
+    threads: Option<usize>,
+) -> Result<Array<f64, D>, ImgalError>
+where
+    A: AsArray<'a, T, D>,
+    D: Dimension,
+    T: 'a + AsNumeric,
+{
+    let data: ArrayBase<ViewRepr<&'a T>, D> = data.into();
+    let mut mean = uniform_filter(&data, radius, Some(BorderMode::Constant(0.0)), threads)?;
+    for (ax, &r) in radius.iter().enumerate() {
+        let n = data.len_of(Axis(ax));
+        let scale: Array1<f64> = (0..n)
+            .map(|i| (2 * r + 1) as f64 / ((i + r + 1).min(n) - i.saturating_sub(r)) as f64)
+            .collect();
+        mean.lanes_mut(Axis(ax))
+            .into_iter()
+            .for_each(|mut lane| lane *= &scale);
+    }
+    Ok(mean)
+}
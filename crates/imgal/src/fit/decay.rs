@@ -0,0 +1,400 @@
+use ndarray::{
+    Array1, Array2, Array3, ArrayBase, ArrayView1, ArrayView2, ArrayViewMut1, AsArray, Axis, Ix1,
+    Ix3, ViewRepr, Zip,
+};
+
+use super::rld::{peak_index, summed_peak_index};
+use crate::linalg::{lm_step, solve_dense};
+use crate::prelude::*;
+use crate::validate::{check_axis, check_shapes};
+
+/// The objective function minimized when fitting decay curves.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum FitObjective {
+    /// Weighted least squares (*i.e.* Neyman's χ²), where each bin is weighted
+    /// by `1 / max(yᵢ, 1)`.
+    #[default]
+    LeastSquares,
+    /// Poisson maximum likelihood estimation (MLE), which minimizes the
+    /// Poisson deviance. Unlike least squares, MLE is unbiased for
+    /// photon-starved (low-count) decays.
+    PoissonMle,
+}
+
+/// Fit a multiexponential decay model to a 1D decay curve.
+///
+/// # Description
+///
+/// Fits a multiexponential decay model with a constant background to the tail
+/// of a 1D decay curve (*i.e.* from the `start` bin onwards):
+///
+/// ```text
+/// f(t) = Σ aₖ × e^(-t / τₖ) + b
+/// ```
+///
+/// Where `aₖ` and `τₖ` are the amplitude and lifetime of the `k`th component,
+/// `b` is the background and `t = (i - start) × Δt` with `Δt = period / n`.
+/// The model is fit with the Levenberg-Marquardt algorithm, minimizing either
+/// the weighted least squares objective (Neyman's χ²):
+///
+/// ```text
+/// χ² = Σ (f(tᵢ) - yᵢ)² / max(yᵢ, 1)
+/// ```
+///
+/// Or the Poisson deviance for maximum likelihood estimation (MLE):
+///
+/// ```text
+/// D = 2 × Σ (f(tᵢ) - yᵢ + yᵢ × ln(yᵢ / f(tᵢ)))
+/// ```
+///
+/// Least squares fitting is biased towards shorter lifetimes for low-count
+/// data, the Poisson MLE objective should be preferred for photon-starved
+/// decays. Initial amplitudes and background are computed with linear least
+/// squares using the initial lifetimes. The fit converged if an accepted step
+/// changes the objective by less than a relative tolerance of `1e-10` and the
+/// normal equations at the solution are not singular, stalled fits and fits
+/// with inseparable components (*e.g.* duplicate lifetimes) are not converged.
+///
+/// # Arguments
+///
+/// * `data`: The input 1D decay curve.
+/// * `period`: The period (*i.e.* time interval).
+/// * `taus`: The initial lifetime estimates, one per exponential component
+///   (*e.g.* from `rld`).
+/// * `start`: The bin index where the fit starts. If `None`, then the index of
+///   the decay curve's maximum value (*i.e.* the peak) is used.
+/// * `objective`: The objective function to minimize. If `None`, then
+///   `objective = FitObjective::LeastSquares`.
+/// * `max_iter`: The maximum number of Levenberg-Marquardt iterations. If
+///   `None`, then `max_iter = 100`.
+///
+/// # Returns
+///
+/// * `Ok((Array1<f64>, bool))`: A tuple containing the fitted parameters,
+///   ordered as `[a₁, τ₁, ..., aₖ, τₖ, b]`, and the convergence flag, *i.e.*
+///   `(params, converged)`.
+/// * `Err(ImgalError)`: If `taus` is empty. If any initial lifetime is not
+///   positive. If fewer bins than `2 × k + 2` remain after `start`.
+pub fn fit_decay<'a, T, A, B>(
+    data: A,
+    period: f64,
+    taus: B,
+    start: Option<usize>,
+    objective: Option<FitObjective>,
+    max_iter: Option<usize>,
+) -> Result<(Array1<f64>, bool), ImgalError>
+where
+    A: AsArray<'a, T, Ix1>,
+    B: AsArray<'a, f64, Ix1>,
+    T: 'a + AsNumeric,
+{
+    let data: ArrayBase<ViewRepr<&'a T>, Ix1> = data.into();
+    let taus: ArrayBase<ViewRepr<&'a f64>, Ix1> = taus.into();
+    let taus = taus.to_vec();
+    let n = data.len();
+    let start = start.unwrap_or_else(|| peak_index(data.iter().map(|v| v.to_f64())));
+    validate_fit(n, &taus, start)?;
+    let y: Vec<f64> = data.iter().skip(start).map(|v| v.to_f64()).collect();
+    let (params, converged) = fit_lane(
+        &y,
+        period / n as f64,
+        &taus,
+        objective.unwrap_or_default(),
+        max_iter.unwrap_or(100),
+    );
+    Ok((Array1::from_vec(params), converged))
+}
+
+/// Fit a multiexponential decay model to each pixel of a 3D decay image.
+///
+/// # Description
+///
+/// Fits a multiexponential decay model with a constant background to the tail
+/// of each decay curve (*i.e.* from the `start` bin onwards) of a 3D decay
+/// image:
+///
+/// ```text
+/// f(t) = Σ aₖ × e^(-t / τₖ) + b
+/// ```
+///
+/// Each pixel is fit independently with the Levenberg-Marquardt algorithm,
+/// minimizing either the weighted least squares objective (Neyman's χ²) or the
+/// Poisson deviance for maximum likelihood estimation (MLE). See `fit_decay`
+/// for details on the model and objectives.
+///
+/// # Arguments
+///
+/// * `data`: The input 3D decay image.
+/// * `period`: The period (*i.e.* time interval).
+/// * `taus`: The initial lifetime estimates, one per exponential component,
+///   shared by all pixels.
+/// * `start`: The bin index where the fit starts. If `None`, then the index of
+///   the maximum value of the summed decay curve (*i.e.* the peak of the whole
+///   image) is used.
+/// * `objective`: The objective function to minimize. If `None`, then
+///   `objective = FitObjective::LeastSquares`.
+/// * `max_iter`: The maximum number of Levenberg-Marquardt iterations per
+///   pixel. If `None`, then `max_iter = 100`.
+/// * `mask`: An optional 2D boolean mask. Pixels where the mask is `false` are
+///   not fit, their parameters are set to `0.0` and they are flagged as not
+///   converged.
+/// * `axis`: The decay or lifetime axis. If `None`, then `axis = 2`.
+/// * `threads`: The requested number of threads to use for parallel execution.
+///   If `None` or `Some(1)` sequential execution is used. If `Some(0)`, then
+///   the maximum available parallelism is used. Thread counts are clamped to
+///   the systems maximum.
+///
+/// # Returns
+///
+/// * `Ok((Array3<f64>, Array2<bool>))`: A tuple containing the fitted
+///   parameter image with shape `(row, col, 2 × k + 1)`, where the last axis
+///   is ordered as `[a₁, τ₁, ..., aₖ, τₖ, b]`, and the per-pixel convergence
+///   flags, *i.e.* `(params, converged)`.
+/// * `Err(ImgalError)`: If `axis >= 3`. If `taus` is empty. If any initial
+///   lifetime is not positive. If fewer bins than `2 × k + 2` remain after
+///   `start`. If the `mask` shape does not match the image shape.
+pub fn fit_decay_image<'a, T, A, B>(
+    data: A,
+    period: f64,
+    taus: B,
+    start: Option<usize>,
+    objective: Option<FitObjective>,
+    max_iter: Option<usize>,
+    mask: Option<ArrayView2<bool>>,
+    axis: Option<usize>,
+    threads: Option<usize>,
+) -> Result<(Array3<f64>, Array2<bool>), ImgalError>
+where
+    A: AsArray<'a, T, Ix3>,
+    B: AsArray<'a, f64, Ix1>,
+    T: 'a + AsNumeric,
+{
+    let data: ArrayBase<ViewRepr<&'a T>, Ix3> = data.into();
+    let taus: ArrayBase<ViewRepr<&'a f64>, Ix1> = taus.into();
+    let taus = taus.to_vec();
+    let axis = axis.unwrap_or(2);
+    check_axis(axis, 3)?;
+    let n = data.len_of(Axis(axis));
+    let start = start.unwrap_or_else(|| summed_peak_index(&data, axis));
+    validate_fit(n, &taus, start)?;
+    let objective = objective.unwrap_or_default();
+    let max_iter = max_iter.unwrap_or(100);
+    let dt = period / n as f64;
+    let mut shape = data.shape().to_vec();
+    shape.remove(axis);
+    let (rows, cols) = (shape[0], shape[1]);
+    if let Some(msk) = mask {
+        check_shapes("data", &shape, "mask", msk.shape())?;
+    }
+    let np = 2 * taus.len() + 1;
+    let mut params_arr = Array3::<f64>::zeros((rows, cols, np));
+    let mut conv_arr = Array2::<bool>::from_elem((rows, cols), false);
+    let fit_calc = |idx: (usize, usize), ln: ArrayView1<T>, p: ArrayViewMut1<f64>, c: &mut bool| {
+        if mask.is_some_and(|m| !m[idx]) {
+            return;
+        }
+        let y: Vec<f64> = ln.iter().skip(start).map(|v| v.to_f64()).collect();
+        let (params, converged) = fit_lane(&y, dt, &taus, objective, max_iter);
+        Zip::from(p).and(&params).for_each(|d, s| *d = *s);
+        *c = converged;
+    };
+    par!(threads,
+        seq_exp: Zip::indexed(data.lanes(Axis(axis)))
+            .and(params_arr.lanes_mut(Axis(2)))
+            .and(&mut conv_arr)
+            .for_each(fit_calc),
+        par_exp: Zip::indexed(data.lanes(Axis(axis)))
+            .and(params_arr.lanes_mut(Axis(2)))
+            .and(&mut conv_arr)
+            .par_for_each(fit_calc));
+    Ok((params_arr, conv_arr))
+}
+
+/// Fit the multiexponential decay model to a single decay tail.
+pub(super) fn fit_lane(
+    y: &[f64],
+    dt: f64,
+    taus: &[f64],
+    objective: FitObjective,
+    max_iter: usize,
+) -> (Vec<f64>, bool) {
+    let np = 2 * taus.len() + 1;
+    let m = y.len();
+    if y.iter().all(|&v| v <= 0.0) {
+        return (vec![0.0; np], false);
+    }
+    let t: Vec<f64> = (0..m).map(|i| i as f64 * dt).collect();
+    let mut params = initial_params(y, &t, taus);
+    let mut cost = objective_value(y, &t, &params, objective);
+    if !cost.is_finite() {
+        return (params, false);
+    }
+    let mut lambda = 1e-3;
+    let mut jac = vec![0.0; m * np];
+    let mut res = vec![0.0; m];
+    for _ in 0..max_iter {
+        // both objectives are minimized as weighted least squares problems,
+        // the Poisson MLE weights use the model (Fisher scoring) instead of the
+        // data
+        (0..m).for_each(|i| {
+            let row = &mut jac[i * np..(i + 1) * np];
+            let f = model_gradient(&params, t[i], row);
+            let var = match objective {
+                FitObjective::LeastSquares => y[i].max(1.0),
+                FitObjective::PoissonMle => f.max(1e-10),
+            };
+            let w = var.sqrt().recip();
+            res[i] = (f - y[i]) * w;
+            row.iter_mut().for_each(|v| *v *= w);
+        });
+        loop {
+            let trial = lm_step(&jac, &res, np, lambda).map(|delta| {
+                let mut trial: Vec<f64> = params.iter().zip(&delta).map(|(p, d)| p + d).collect();
+                project_params(&mut trial, dt);
+                trial
+            });
+            let trial_cost = trial
+                .as_ref()
+                .map_or(f64::INFINITY, |p| objective_value(y, &t, p, objective));
+            if trial_cost.is_finite() && trial_cost <= cost {
+                let rel = (cost - trial_cost) / cost.max(f64::MIN_POSITIVE);
+                params = trial.unwrap();
+                cost = trial_cost;
+                lambda = (lambda * 0.1).max(1e-12);
+                if rel < 1e-10 {
+                    // the fit only converged if the undamped normal equations
+                    // are not singular (e.g. duplicate lifetimes)
+                    return (params, lm_step(&jac, &res, np, 0.0).is_some());
+                }
+                break;
+            }
+            lambda *= 10.0;
+            // no step decreases the objective (or every damped system is
+            // singular), the fit stalled
+            if lambda > 1e12 {
+                return (params, false);
+            }
+        }
+    }
+    (params, false)
+}
+
+/// Compute the initial amplitudes and background with linear least squares.
+fn initial_params(y: &[f64], t: &[f64], taus: &[f64]) -> Vec<f64> {
+    let k = taus.len();
+    let nb = k + 1;
+    let mut ata = vec![0.0; nb * nb];
+    let mut aty = vec![0.0; nb];
+    let mut basis = vec![1.0; nb];
+    y.iter().zip(t.iter()).for_each(|(&v, &ti)| {
+        taus.iter()
+            .enumerate()
+            .for_each(|(j, tau)| basis[j] = (-ti / tau).exp());
+        for a in 0..nb {
+            aty[a] += basis[a] * v;
+            for b in 0..nb {
+                ata[a * nb + b] += basis[a] * basis[b];
+            }
+        }
+    });
+    let peak = y.iter().cloned().fold(0.0, f64::max);
+    let sol = solve_dense(&ata, &aty).unwrap_or_else(|| {
+        let mut s = vec![peak / k as f64; nb];
+        s[k] = 0.0;
+        s
+    });
+    let mut params = Vec::with_capacity(2 * k + 1);
+    taus.iter().enumerate().for_each(|(j, &tau)| {
+        params.push(sol[j].max(1e-3 * peak));
+        params.push(tau);
+    });
+    params.push(sol[k].max(0.0));
+    params
+}
+
+/// Evaluate the decay model at time `t` and write the gradient with respect to
+/// the parameters into `grad`.
+fn model_gradient(params: &[f64], t: f64, grad: &mut [f64]) -> f64 {
+    let k = params.len() / 2;
+    let mut f = params[2 * k];
+    (0..k).for_each(|j| {
+        let a = params[2 * j];
+        let tau = params[2 * j + 1];
+        let e = (-t / tau).exp();
+        f += a * e;
+        grad[2 * j] = e;
+        grad[2 * j + 1] = a * e * t / (tau * tau);
+    });
+    grad[2 * k] = 1.0;
+    f
+}
+
+/// Evaluate the decay model at time `t`.
+pub(super) fn model(params: &[f64], t: f64) -> f64 {
+    let k = params.len() / 2;
+    (0..k).fold(params[2 * k], |acc, j| {
+        acc + params[2 * j] * (-t / params[2 * j + 1]).exp()
+    })
+}
+
+/// Compute the objective value of the decay model parameters.
+pub(super) fn objective_value(
+    y: &[f64],
+    t: &[f64],
+    params: &[f64],
+    objective: FitObjective,
+) -> f64 {
+    match objective {
+        FitObjective::LeastSquares => y
+            .iter()
+            .zip(t.iter())
+            .map(|(&v, &ti)| (model(params, ti) - v).powi(2) / v.max(1.0))
+            .sum(),
+        FitObjective::PoissonMle => {
+            let mut dev = 0.0;
+            for (&v, &ti) in y.iter().zip(t.iter()) {
+                let f = model(params, ti);
+                if f < 0.0 || (f == 0.0 && v > 0.0) {
+                    return f64::INFINITY;
+                }
+                dev += if v > 0.0 { f - v + v * (v / f).ln() } else { f };
+            }
+            2.0 * dev
+        }
+    }
+}
+
+/// Project the parameters onto the feasible set (*i.e.* non-negative
+/// amplitudes and background, and positive lifetimes).
+fn project_params(params: &mut [f64], dt: f64) {
+    let k = params.len() / 2;
+    (0..k).for_each(|j| {
+        params[2 * j] = params[2 * j].max(0.0);
+        params[2 * j + 1] = params[2 * j + 1].max(1e-3 * dt);
+    });
+    params[2 * k] = params[2 * k].max(0.0);
+}
+
+/// Validate the initial lifetimes and the fit range.
+pub(super) fn validate_fit(n: usize, taus: &[f64], start: usize) -> Result<(), ImgalError> {
+    if taus.is_empty() {
+        return Err(ImgalError::InvalidParameterEmptyArray { param_name: "taus" });
+    }
+    if let Some(&tau) = taus.iter().find(|&&t| !(t > 0.0 && t.is_finite())) {
+        return Err(ImgalError::InvalidParameterValueOutsideRange {
+            param_name: "taus",
+            value: tau,
+            min: 0.0,
+            max: f64::INFINITY,
+        });
+    }
+    // the fit requires more bins than parameters
+    let min_bins = 2 * taus.len() + 2;
+    if start + min_bins > n {
+        return Err(ImgalError::InvalidParameterValueGreater {
+            param_name: "start",
+            value: n.saturating_sub(min_bins),
+        });
+    }
+    Ok(())
+}
@@ -0,0 +1,401 @@
+use ndarray::{
+    Array1, Array2, Array3, ArrayBase, ArrayView1, ArrayView2, ArrayView3, ArrayViewMut1, AsArray,
+    Axis, Ix1, Ix3, ViewRepr, Zip,
+};
+
+use super::decay::model;
+use super::rld::{peak_index, summed_peak_index};
+use crate::prelude::*;
+use crate::validate::{check_axis, check_shapes};
+
+/// Compute the weighted residuals of a fitted decay model.
+///
+/// # Description
+///
+/// Computes the weighted residuals between a 1D decay curve and a fitted
+/// multiexponential decay model (*e.g.* from `fit_decay`):
+///
+/// ```text
+/// rᵢ = (yᵢ - f(tᵢ)) / √max(yᵢ, 1)
+/// ```
+///
+/// Where `f(t) = Σ aₖ × e^(-t / τₖ) + b` and `t = (i - start) × Δt` with
+/// `Δt = period / n`. For a good fit the weighted residuals are randomly
+/// distributed around `0.0` with unit variance.
+///
+/// # Arguments
+///
+/// * `data`: The input 1D decay curve.
+/// * `params`: The fitted decay model parameters, ordered as
+///   `[a₁, τ₁, ..., aₖ, τₖ, b]`.
+/// * `period`: The period (*i.e.* time interval).
+/// * `start`: The bin index where the fit started. If `None`, then the index of
+///   the decay curve's maximum value (*i.e.* the peak) is used.
+///
+/// # Returns
+///
+/// * `Ok(Array1<f64>)`: The weighted residuals with the same length as `data`.
+///   Bins before `start` are set to `0.0`.
+/// * `Err(ImgalError)`: If `params` is not of length `2 × k + 1`. If
+///   `start >= data.len()`.
+pub fn weighted_residuals<'a, T, A, B>(
+    data: A,
+    params: B,
+    period: f64,
+    start: Option<usize>,
+) -> Result<Array1<f64>, ImgalError>
+where
+    A: AsArray<'a, T, Ix1>,
+    B: AsArray<'a, f64, Ix1>,
+    T: 'a + AsNumeric,
+{
+    let data: ArrayBase<ViewRepr<&'a T>, Ix1> = data.into();
+    let params: ArrayBase<ViewRepr<&'a f64>, Ix1> = params.into();
+    let params = params.to_vec();
+    validate_params(params.len())?;
+    let n = data.len();
+    let start = start.unwrap_or_else(|| peak_index(data.iter().map(|v| v.to_f64())));
+    validate_start(n, start, 0)?;
+    let mut res_arr = Array1::<f64>::zeros(n);
+    residuals_lane(data, &params, period / n as f64, start, res_arr.view_mut());
+    Ok(res_arr)
+}
+
+/// Compute the weighted residual image of a fitted decay image.
+///
+/// # Description
+///
+/// Computes the per-pixel weighted residuals between a 3D decay image and a
+/// fitted multiexponential decay parameter image (*e.g.* from
+/// `fit_decay_image`):
+///
+/// ```text
+/// rᵢ = (yᵢ - f(tᵢ)) / √max(yᵢ, 1)
+/// ```
+///
+/// Where `f(t) = Σ aₖ × e^(-t / τₖ) + b` and `t = (i - start) × Δt` with
+/// `Δt = period / n`.
+///
+/// # Arguments
+///
+/// * `data`: The input 3D decay image.
+/// * `params`: The fitted decay parameter image with shape
+///   `(row, col, 2 × k + 1)`, where the last axis is ordered as
+///   `[a₁, τ₁, ..., aₖ, τₖ, b]`.
+/// * `period`: The period (*i.e.* time interval).
+/// * `start`: The bin index where the fit started. If `None`, then the index of
+///   the maximum value of the summed decay curve (*i.e.* the peak of the whole
+///   image) is used.
+/// * `axis`: The decay or lifetime axis. If `None`, then `axis = 2`.
+/// * `threads`: The requested number of threads to use for parallel execution.
+///   If `None` or `Some(1)` sequential execution is used. If `Some(0)`, then
+///   the maximum available parallelism is used. Thread counts are clamped to
+///   the systems maximum.
+///
+/// # Returns
+///
+/// * `Ok(Array3<f64>)`: The weighted residual image with the same shape as
+///   `data`. Bins before `start` are set to `0.0`.
+/// * `Err(ImgalError)`: If `axis >= 3`. If axis 2 of `params` is not of length
+///   `2 × k + 1`. If the `params` image shape does not match the image shape.
+///   If `start` is greater than or equal to the length of `axis`.
+pub fn weighted_residuals_image<'a, T, A, B>(
+    data: A,
+    params: B,
+    period: f64,
+    start: Option<usize>,
+    axis: Option<usize>,
+    threads: Option<usize>,
+) -> Result<Array3<f64>, ImgalError>
+where
+    A: AsArray<'a, T, Ix3>,
+    B: AsArray<'a, f64, Ix3>,
+    T: 'a + AsNumeric,
+{
+    let data: ArrayBase<ViewRepr<&'a T>, Ix3> = data.into();
+    let params: ArrayBase<ViewRepr<&'a f64>, Ix3> = params.into();
+    let axis = validate_image(&data.view(), &params.view(), axis)?;
+    let n = data.len_of(Axis(axis));
+    let start = start.unwrap_or_else(|| summed_peak_index(&data, axis));
+    validate_start(n, start, 0)?;
+    let dt = period / n as f64;
+    let mut res_arr = Array3::<f64>::zeros(data.dim());
+    let res_calc = |ln: ArrayView1<T>, p: ArrayView1<f64>, r: ArrayViewMut1<f64>| {
+        residuals_lane(ln, &p.to_vec(), dt, start, r);
+    };
+    par!(threads,
+        seq_exp: Zip::from(data.lanes(Axis(axis)))
+            .and(params.lanes(Axis(2)))
+            .and(res_arr.lanes_mut(Axis(axis)))
+            .for_each(res_calc),
+        par_exp: Zip::from(data.lanes(Axis(axis)))
+            .and(params.lanes(Axis(2)))
+            .and(res_arr.lanes_mut(Axis(axis)))
+            .par_for_each(res_calc));
+    Ok(res_arr)
+}
+
+/// Compute the reduced chi-square of a fitted decay model.
+///
+/// # Description
+///
+/// Computes the reduced chi-square (χ²ᵣ) goodness of fit statistic between a
+/// 1D decay curve and a fitted multiexponential decay model (*e.g.* from
+/// `fit_decay`):
+///
+/// ```text
+/// χ²ᵣ = Σ ((yᵢ - f(tᵢ))² / max(yᵢ, 1)) / (m - p)
+/// ```
+///
+/// Where the sum runs over the `m` bins from `start` onwards and `p` is the
+/// number of model parameters. For a good fit of Poisson distributed data
+/// `χ²ᵣ ≈ 1`.
+///
+/// # Arguments
+///
+/// * `data`: The input 1D decay curve.
+/// * `params`: The fitted decay model parameters, ordered as
+///   `[a₁, τ₁, ..., aₖ, τₖ, b]`.
+/// * `period`: The period (*i.e.* time interval).
+/// * `start`: The bin index where the fit started. If `None`, then the index of
+///   the decay curve's maximum value (*i.e.* the peak) is used.
+///
+/// # Returns
+///
+/// * `Ok(f64)`: The reduced chi-square.
+/// * `Err(ImgalError)`: If `params` is not of length `2 × k + 1`. If fewer bins
+///   than `2 × k + 2` remain after `start`.
+pub fn reduced_chi_square<'a, T, A, B>(
+    data: A,
+    params: B,
+    period: f64,
+    start: Option<usize>,
+) -> Result<f64, ImgalError>
+where
+    A: AsArray<'a, T, Ix1>,
+    B: AsArray<'a, f64, Ix1>,
+    T: 'a + AsNumeric,
+{
+    let data: ArrayBase<ViewRepr<&'a T>, Ix1> = data.into();
+    let params: ArrayBase<ViewRepr<&'a f64>, Ix1> = params.into();
+    let params = params.to_vec();
+    validate_params(params.len())?;
+    let n = data.len();
+    let start = start.unwrap_or_else(|| peak_index(data.iter().map(|v| v.to_f64())));
+    validate_start(n, start, params.len())?;
+    Ok(chi_square_lane(data, &params, period / n as f64, start))
+}
+
+/// Compute the reduced chi-square image of a fitted decay image.
+///
+/// # Description
+///
+/// Computes the per-pixel reduced chi-square (χ²ᵣ) goodness of fit statistic
+/// between a 3D decay image and a fitted multiexponential decay parameter
+/// image (*e.g.* from `fit_decay_image`):
+///
+/// ```text
+/// χ²ᵣ = Σ ((yᵢ - f(tᵢ))² / max(yᵢ, 1)) / (m - p)
+/// ```
+///
+/// Where the sum runs over the `m` bins from `start` onwards and `p` is the
+/// number of model parameters.
+///
+/// # Arguments
+///
+/// * `data`: The input 3D decay image.
+/// * `params`: The fitted decay parameter image with shape
+///   `(row, col, 2 × k + 1)`, where the last axis is ordered as
+///   `[a₁, τ₁, ..., aₖ, τₖ, b]`.
+/// * `period`: The period (*i.e.* time interval).
+/// * `start`: The bin index where the fit started. If `None`, then the index of
+///   the maximum value of the summed decay curve (*i.e.* the peak of the whole
+///   image) is used.
+/// * `mask`: An optional 2D boolean mask. Pixels where the mask is `false` are
+///   set to `0.0`.
+/// * `axis`: The decay or lifetime axis. If `None`, then `axis = 2`.
+/// * `threads`: The requested number of threads to use for parallel execution.
+///   If `None` or `Some(1)` sequential execution is used. If `Some(0)`, then
+///   the maximum available parallelism is used. Thread counts are clamped to
+///   the systems maximum.
+///
+/// # Returns
+///
+/// * `Ok(Array2<f64>)`: The 2D reduced chi-square image.
+/// * `Err(ImgalError)`: If `axis >= 3`. If axis 2 of `params` is not of length
+///   `2 × k + 1`. If the `params` or `mask` shapes do not match the image
+///   shape. If fewer bins than `2 × k + 2` remain after `start`.
+pub fn reduced_chi_square_image<'a, T, A, B>(
+    data: A,
+    params: B,
+    period: f64,
+    start: Option<usize>,
+    mask: Option<ArrayView2<bool>>,
+    axis: Option<usize>,
+    threads: Option<usize>,
+) -> Result<Array2<f64>, ImgalError>
+where
+    A: AsArray<'a, T, Ix3>,
+    B: AsArray<'a, f64, Ix3>,
+    T: 'a + AsNumeric,
+{
+    let data: ArrayBase<ViewRepr<&'a T>, Ix3> = data.into();
+    let params: ArrayBase<ViewRepr<&'a f64>, Ix3> = params.into();
+    let axis = validate_image(&data.view(), &params.view(), axis)?;
+    let n = data.len_of(Axis(axis));
+    let start = start.unwrap_or_else(|| summed_peak_index(&data, axis));
+    validate_start(n, start, params.len_of(Axis(2)))?;
+    let (rows, cols, _) = params.dim();
+    if let Some(msk) = mask {
+        check_shapes("params", &[rows, cols], "mask", msk.shape())?;
+    }
+    let dt = period / n as f64;
+    let mut chi_arr = Array2::<f64>::zeros((rows, cols));
+    let chi_calc = |idx: (usize, usize), ln: ArrayView1<T>, p: ArrayView1<f64>, c: &mut f64| {
+        if mask.is_some_and(|m| !m[idx]) {
+            return;
+        }
+        *c = chi_square_lane(ln, &p.to_vec(), dt, start);
+    };
+    par!(threads,
+        seq_exp: Zip::indexed(data.lanes(Axis(axis)))
+            .and(params.lanes(Axis(2)))
+            .and(&mut chi_arr)
+            .for_each(chi_calc),
+        par_exp: Zip::indexed(data.lanes(Axis(axis)))
+            .and(params.lanes(Axis(2)))
+            .and(&mut chi_arr)
+            .par_for_each(chi_calc));
+    Ok(chi_arr)
+}
+
+/// Compute the normalized autocorrelation of a residual trace.
+///
+/// # Description
+///
+/// Computes the normalized autocorrelation of a residual trace (*e.g.* from
+/// `weighted_residuals`) at lags `0` to `max_lag`:
+///
+/// ```text
+/// A(k) = (Σ rᵢ × rᵢ₊ₖ / (m - k)) / (Σ rᵢ² / m)
+/// ```
+///
+/// Where `m` is the length of the residual trace. For a good fit the residuals
+/// are uncorrelated and `A(k) ≈ 0` for all `k > 0`, systematic deviations
+/// (*e.g.* a missing exponential component or an unaccounted IRF) appear as
+/// low frequency oscillations.
+///
+/// # Arguments
+///
+/// * `residuals`: The input 1D residual trace.
+/// * `max_lag`: The maximum lag. If `None`, then `max_lag = m / 2`.
+///
+/// # Returns
+///
+/// * `Ok(Array1<f64>)`: The normalized autocorrelation with length
+///   `max_lag + 1`, where `A(0) = 1.0`. If all residuals are `0.0`, the
+///   autocorrelation is `0.0`.
+/// * `Err(ImgalError)`: If `residuals` is empty. If `max_lag >= m`.
+pub fn residual_autocorrelation<'a, A>(
+    residuals: A,
+    max_lag: Option<usize>,
+) -> Result<Array1<f64>, ImgalError>
+where
+    A: AsArray<'a, f64, Ix1>,
+{
+    let residuals: ArrayBase<ViewRepr<&'a f64>, Ix1> = residuals.into();
+    let m = residuals.len();
+    if m == 0 {
+        return Err(ImgalError::InvalidParameterEmptyArray {
+            param_name: "residuals",
+        });
+    }
+    let max_lag = max_lag.unwrap_or(m / 2);
+    if max_lag >= m {
+        return Err(ImgalError::InvalidParameterValueGreater {
+            param_name: "max_lag",
+            value: m - 1,
+        });
+    }
+    let var = residuals.iter().map(|r| r * r).sum::<f64>() / m as f64;
+    let mut ac_arr = Array1::<f64>::zeros(max_lag + 1);
+    if var == 0.0 {
+        return Ok(ac_arr);
+    }
+    ac_arr.iter_mut().enumerate().for_each(|(k, a)| {
+        let cov = (0..m - k)
+            .map(|i| residuals[i] * residuals[i + k])
+            .sum::<f64>()
+            / (m - k) as f64;
+        *a = cov / var;
+    });
+    Ok(ac_arr)
+}
+
+/// Compute the weighted residuals of a single decay lane.
+fn residuals_lane<T>(
+    data: ArrayView1<T>,
+    params: &[f64],
+    dt: f64,
+    start: usize,
+    mut res: ArrayViewMut1<f64>,
+) where
+    T: AsNumeric,
+{
+    (start..data.len()).for_each(|i| {
+        let y = data[i].to_f64();
+        let f = model(params, (i - start) as f64 * dt);
+        res[i] = (y - f) / y.max(1.0).sqrt();
+    });
+}
+
+/// Compute the reduced chi-square of a single decay lane.
+fn chi_square_lane<T>(data: ArrayView1<T>, params: &[f64], dt: f64, start: usize) -> f64
+where
+    T: AsNumeric,
+{
+    let chi: f64 = (start..data.len())
+        .map(|i| {
+            let y = data[i].to_f64();
+            let f = model(params, (i - start) as f64 * dt);
+            (y - f).powi(2) / y.max(1.0)
+        })
+        .sum();
+    chi / (data.len() - start - params.len()) as f64
+}
+
+/// Validate the decay image and parameter image shapes and return the axis.
+fn validate_image<T>(
+    data: &ArrayView3<T>,
+    params: &ArrayView3<f64>,
+    axis: Option<usize>,
+) -> Result<usize, ImgalError> {
+    let axis = axis.unwrap_or(2);
+    check_axis(axis, 3)?;
+    validate_params(params.len_of(Axis(2)))?;
+    let mut shape = data.shape().to_vec();
+    shape.remove(axis);
+    check_shapes("data", &shape, "params", &params.shape()[..2])?;
+    Ok(axis)
+}
+
+/// Validate the number of decay model parameters.
+fn validate_params(len: usize) -> Result<(), ImgalError> {
+    if len < 3 || len.is_multiple_of(2) {
+        return Err(ImgalError::InvalidGeneric {
+            msg: "Invalid decay parameters, expected 2 × k + 1 parameters ordered as [a₁, τ₁, ..., aₖ, τₖ, b].",
+        });
+    }
+    Ok(())
+}
+
+/// Validate that more than `n_params` bins remain after `start`.
+fn validate_start(n: usize, start: usize, n_params: usize) -> Result<(), ImgalError> {
+    if start + n_params >= n {
+        return Err(ImgalError::InvalidParameterValueGreater {
+            param_name: "start",
+            value: n.saturating_sub(n_params + 1),
+        });
+    }
+    Ok(())
+}
@@ -0,0 +1,373 @@
+use ndarray::{Array2, ArrayBase, ArrayView1, ArrayView2, AsArray, Axis, Ix1, Ix3, ViewRepr, Zip};
+
+use super::rld::{peak_index, summed_peak_index};
+use crate::linalg::solve_dense;
+use crate::prelude::*;
+use crate::validate::{check_axis, check_shapes};
+
+/// Estimate the mean lifetime of a 1D decay curve with a Laguerre expansion.
+///
+/// # Description
+///
+/// Expands the decay curve (or, if an `irf` is given, the impulse response
+/// `h` with `y = irf ⊛ h`) on a basis of discrete Laguerre functions with the
+/// parameter `α`:
+///
+/// ```text
+/// b₀(k) = √(1 - α²) × αᵏ
+/// bⱼ(k) = α × bⱼ(k - 1) + bⱼ₋₁(k - 1) - α × bⱼ₋₁(k)
+/// ```
+///
+/// The orthonormal Laguerre functions represent multiexponential decays with
+/// few coefficients, which are found with weighted linear least squares (each
+/// bin weighted by `1 / max(yᵢ, 1)`) instead of an iterative nonlinear fit.
+/// The expansion order `L` is selected automatically, from `1` to `max_order`,
+/// as the order with the lowest Akaike information criterion
+/// `AIC = χ² + 2 × L`. The mean lifetime is computed from the first moment
+/// `m` (in bins) of the expanded impulse response `h`, where the tail beyond
+/// the `N` bin window is extrapolated with the decay ratio `r` of the last
+/// quarter of the window:
+///
+/// ```text
+/// g = r / (1 - r)
+/// tail₀ = hₙ₋₁ × g
+/// tail₁ = hₙ₋₁ × ((N - 1) × g + g / (1 - r))
+/// m = (Σₖ k × hₖ + tail₁) / (Σₖ hₖ + tail₀)
+/// τ = Δt / ln(1 + 1 / m)
+/// ```
+///
+/// For a multiexponential decay this approximates the intensity weighted mean
+/// lifetime `Σ aₖ × τₖ² / Σ aₖ × τₖ` and it is exact for a monoexponential
+/// decay. If `alpha` is not given it is estimated from the first moment of the
+/// decay curve, so that `b₀` matches a monoexponential decay with the same
+/// mean. Without an iterative fit the expansion is fast and does not depend on
+/// initial lifetime estimates, which makes it robust at low photon counts.
+///
+/// # Arguments
+///
+/// * `data`: The input 1D decay curve.
+/// * `period`: The period (*i.e.* time interval).
+/// * `irf`: An optional instrument response function (IRF) with the same
+///   length as `data`. If `None`, then the decay tail is expanded directly.
+/// * `alpha`: The Laguerre parameter in the open interval `(0.0, 1.0)`. If
+///   `None`, then `alpha` is estimated from the decay curve.
+/// * `max_order`: The maximum expansion order. If `None`, then
+///   `max_order = 5`.
+/// * `start`: The bin index where the expansion starts. If `None`, then the
+///   index of the decay curve's maximum value (*i.e.* the peak) is used without
+///   an `irf` and `0` with an `irf`.
+///
+/// # Returns
+///
+/// * `Ok((f64, usize))`: A tuple containing the mean lifetime and the selected
+///   expansion order, *i.e.* `(tau, order)`. If the decay curve has no positive
+///   counts after `start` or the expansion does not describe a decay, then
+///   `(0.0, 0)`.
+/// * `Err(ImgalError)`: If `max_order == 0`. If `alpha` is outside the open
+///   interval `(0.0, 1.0)`. If the `irf` length does not match the `data`
+///   length. If fewer than `max_order + 1` bins remain after `start`.
+///
+/// # Reference
+///
+/// <https://doi.org/10.1364/OPEX.12.004900>
+pub fn laguerre_lifetime<'a, T, A>(
+    data: A,
+    period: f64,
+    irf: Option<ArrayView1<f64>>,
+    alpha: Option<f64>,
+    max_order: Option<usize>,
+    start: Option<usize>,
+) -> Result<(f64, usize), ImgalError>
+where
+    A: AsArray<'a, T, Ix1>,
+    T: 'a + AsNumeric,
+{
+    let data: ArrayBase<ViewRepr<&'a T>, Ix1> = data.into();
+    let n = data.len();
+    let max_order = max_order.unwrap_or(5);
+    let start = match (start, irf) {
+        (Some(s), _) => s,
+        (None, Some(_)) => 0,
+        (None, None) => peak_index(data.iter().map(|v| v.to_f64())),
+    };
+    validate_expansion(n, irf, alpha, max_order, start)?;
+    let y: Vec<f64> = data.iter().skip(start).map(|v| v.to_f64()).collect();
+    let alpha = alpha.unwrap_or_else(|| estimate_alpha(&y));
+    let basis = laguerre_basis(n, alpha, max_order, irf, start);
+    Ok(expand_lane(&y, &basis, period / n as f64))
+}
+
+/// Estimate the mean lifetime of each pixel of a 3D decay image with a
+/// Laguerre expansion.
+///
+/// # Description
+///
+/// Expands each pixel's decay curve on a shared basis of discrete Laguerre
+/// functions and computes the mean lifetime with an automatically selected
+/// expansion order. See `laguerre_lifetime` for details on the expansion.
+///
+/// # Arguments
+///
+/// * `data`: The input 3D decay image.
+/// * `period`: The period (*i.e.* time interval).
+/// * `irf`: An optional instrument response function (IRF) with the same
+///   length as the decay axis. If `None`, then the decay tails are expanded
+///   directly.
+/// * `alpha`: The Laguerre parameter in the open interval `(0.0, 1.0)`. If
+///   `None`, then `alpha` is estimated from the summed decay curve of the
+///   image.
+/// * `max_order`: The maximum expansion order. If `None`, then
+///   `max_order = 5`.
+/// * `start`: The bin index where the expansion starts. If `None`, then the
+///   index of the maximum value of the summed decay curve (*i.e.* the peak of
+///   the whole image) is used without an `irf` and `0` with an `irf`.
+/// * `mask`: An optional 2D boolean mask. Pixels where the mask is `false` are
+///   set to `0.0` with order `0`.
+/// * `axis`: The decay or lifetime axis. If `None`, then `axis = 2`.
+/// * `threads`: The requested number of threads to use for parallel execution.
+///   If `None` or `Some(1)` sequential execution is used. If `Some(0)`, then
+///   the maximum available parallelism is used. Thread counts are clamped to
+///   the systems maximum.
+///
+/// # Returns
+///
+/// * `Ok((Array2<f64>, Array2<usize>))`: A tuple containing the mean lifetime
+///   image and the selected expansion order of each pixel, *i.e.*
+///   `(tau, order)`.
+/// * `Err(ImgalError)`: If `axis >= 3`. If `max_order == 0`. If `alpha` is
+///   outside the open interval `(0.0, 1.0)`. If the `irf` length does not match
+///   the length of `axis`. If fewer than `max_order + 1` bins remain after
+///   `start`. If the `mask` shape does not match the image shape.
+pub fn laguerre_lifetime_image<'a, T, A>(
+    data: A,
+    period: f64,
+    irf: Option<ArrayView1<f64>>,
+    alpha: Option<f64>,
+    max_order: Option<usize>,
+    start: Option<usize>,
+    mask: Option<ArrayView2<bool>>,
+    axis: Option<usize>,
+    threads: Option<usize>,
+) -> Result<(Array2<f64>, Array2<usize>), ImgalError>
+where
+    A: AsArray<'a, T, Ix3>,
+    T: 'a + AsNumeric,
+{
+    let data: ArrayBase<ViewRepr<&'a T>, Ix3> = data.into();
+    let axis = axis.unwrap_or(2);
+    check_axis(axis, 3)?;
+    let n = data.len_of(Axis(axis));
+    let max_order = max_order.unwrap_or(5);
+    let start = match (start, irf) {
+        (Some(s), _) => s,
+        (None, Some(_)) => 0,
+        (None, None) => summed_peak_index(&data, axis),
+    };
+    validate_expansion(n, irf, alpha, max_order, start)?;
+    let mut shape = data.shape().to_vec();
+    shape.remove(axis);
+    let (rows, cols) = (shape[0], shape[1]);
+    if let Some(msk) = mask {
+        check_shapes("data", &shape, "mask", msk.shape())?;
+    }
+    let alpha = alpha.unwrap_or_else(|| {
+        let summed: Vec<f64> = (start..n)
+            .map(|i| {
+                data.index_axis(Axis(axis), i)
+                    .iter()
+                    .map(|v| v.to_f64())
+                    .sum::<f64>()
+            })
+            .collect();
+        estimate_alpha(&summed)
+    });
+    let basis = laguerre_basis(n, alpha, max_order, irf, start);
+    let dt = period / n as f64;
+    let mut tau_arr = Array2::<f64>::zeros((rows, cols));
+    let mut order_arr = Array2::<usize>::zeros((rows, cols));
+    let lag_calc = |idx: (usize, usize), ln: ArrayView1<T>, t: &mut f64, o: &mut usize| {
+        if mask.is_some_and(|m| !m[idx]) {
+            return;
+        }
+        let y: Vec<f64> = ln.iter().skip(start).map(|v| v.to_f64()).collect();
+        (*t, *o) = expand_lane(&y, &basis, dt);
+    };
+    par!(threads,
+        seq_exp: Zip::indexed(data.lanes(Axis(axis)))
+            .and(&mut tau_arr)
+            .and(&mut order_arr)
+            .for_each(lag_calc),
+        par_exp: Zip::indexed(data.lanes(Axis(axis)))
+            .and(&mut tau_arr)
+            .and(&mut order_arr)
+            .par_for_each(lag_calc));
+    Ok((tau_arr, order_arr))
+}
+
+/// Compute the discrete Laguerre functions `b₀` to `b_order` of the impulse
+/// response and the design basis for the bins from `start` onwards, *i.e.* the
+/// Laguerre functions convolved with the IRF (if any).
+fn laguerre_basis(
+    n: usize,
+    alpha: f64,
+    max_order: usize,
+    irf: Option<ArrayView1<f64>>,
+    start: usize,
+) -> (Vec<Vec<f64>>, Vec<Vec<f64>>) {
+    // with an IRF the impulse response starts at bin 0, otherwise at "start"
+    let len = if irf.is_some() { n } else { n - start };
+    let mut basis: Vec<Vec<f64>> = Vec::with_capacity(max_order + 1);
+    let scale = (1.0 - alpha * alpha).sqrt();
+    basis.push((0..len).map(|k| scale * alpha.powi(k as i32)).collect());
+    for j in 1..=max_order {
+        let prev = &basis[j - 1];
+        let mut cur = vec![0.0; len];
+        cur[0] = -alpha * prev[0];
+        for k in 1..len {
+            cur[k] = alpha * cur[k - 1] + prev[k - 1] - alpha * prev[k];
+        }
+        basis.push(cur);
+    }
+    let design = match irf {
+        Some(irf) => basis
+            .iter()
+            .map(|b| {
+                (start..n)
+                    .map(|k| (0..=k).map(|i| irf[i] * b[k - i]).sum())
+                    .collect()
+            })
+            .collect(),
+        None => basis.clone(),
+    };
+    (basis, design)
+}
+
+/// Estimate the Laguerre parameter from the first moment of a decay tail.
+fn estimate_alpha(y: &[f64]) -> f64 {
+    let (s0, s1) = y.iter().enumerate().fold((0.0, 0.0), |(s0, s1), (k, &v)| {
+        let v = v.max(0.0);
+        (s0 + v, s1 + k as f64 * v)
+    });
+    let m = if s0 > 0.0 { s1 / s0 } else { 1.0 };
+    (m / (m + 1.0)).clamp(0.01, 0.99)
+}
+
+/// Expand a single decay tail, select the order and compute the mean lifetime.
+fn expand_lane(
+    y: &[f64],
+    (basis, design): &(Vec<Vec<f64>>, Vec<Vec<f64>>),
+    dt: f64,
+) -> (f64, usize) {
+    if y.iter().all(|&v| v <= 0.0) {
+        return (0.0, 0);
+    }
+    let nb = design.len();
+    let mut ata = vec![0.0; nb * nb];
+    let mut aty = vec![0.0; nb];
+    y.iter().enumerate().for_each(|(k, &v)| {
+        let w = v.max(1.0).recip();
+        for a in 0..nb {
+            aty[a] += w * design[a][k] * v;
+            for b in a..nb {
+                ata[a * nb + b] += w * design[a][k] * design[b][k];
+            }
+        }
+    });
+    (0..nb).for_each(|a| (0..a).for_each(|b| ata[a * nb + b] = ata[b * nb + a]));
+    // select the order (i.e. the number of basis functions - 1) with AIC
+    let mut best: Option<(f64, Vec<f64>)> = None;
+    for l in 1..nb {
+        let m = l + 1;
+        let sub: Vec<f64> = (0..m)
+            .flat_map(|a| ata[a * nb..a * nb + m].to_vec())
+            .collect();
+        let Some(coef) = solve_dense(&sub, &aty[..m]) else {
+            continue;
+        };
+        let chi2: f64 = y
+            .iter()
+            .enumerate()
+            .map(|(k, &v)| {
+                let f: f64 = coef.iter().enumerate().map(|(j, c)| c * design[j][k]).sum();
+                (v - f).powi(2) / v.max(1.0)
+            })
+            .sum();
+        let aic = chi2 + 2.0 * l as f64;
+        if best.as_ref().is_none_or(|(b, _)| aic < *b) {
+            best = Some((aic, coef));
+        }
+    }
+    let Some((_, coef)) = best else {
+        return (0.0, 0);
+    };
+    // first moment of the expanded impulse response over the window, with the
+    // tail beyond the window extrapolated with the decay ratio of the last
+    // quarter of the window
+    let len = basis[0].len();
+    let h: Vec<f64> = (0..len)
+        .map(|k| coef.iter().enumerate().map(|(j, c)| c * basis[j][k]).sum())
+        .collect();
+    let (mut s0, mut s1) = h
+        .iter()
+        .enumerate()
+        .fold((0.0, 0.0), |(s0, s1), (k, &v)| (s0 + v, s1 + k as f64 * v));
+    let q = (len / 4).max(1);
+    let (h_end, h_q) = (h[len - 1], h[len - 1 - q]);
+    if h_end > 0.0 && h_q > h_end {
+        let r = (h_end / h_q).powf(1.0 / q as f64);
+        let g = r / (1.0 - r);
+        s0 += h_end * g;
+        s1 += h_end * ((len - 1) as f64 * g + g / (1.0 - r));
+    }
+    let m = s1 / s0;
+    let tau = dt / (1.0 / m).ln_1p();
+    if tau.is_finite() && tau > 0.0 {
+        (tau, coef.len() - 1)
+    } else {
+        (0.0, 0)
+    }
+}
+
+/// Validate the expansion parameters and the expansion range.
+fn validate_expansion(
+    n: usize,
+    irf: Option<ArrayView1<f64>>,
+    alpha: Option<f64>,
+    max_order: usize,
+    start: usize,
+) -> Result<(), ImgalError> {
+    if max_order == 0 {
+        return Err(ImgalError::InvalidParameterValueEqual {
+            param_name: "max_order",
+            value: 0,
+        });
+    }
+    if let Some(a) = alpha
+        && !(a > 0.0 && a < 1.0)
+    {
+        return Err(ImgalError::InvalidParameterValueOutsideRange {
+            param_name: "alpha",
+            value: a,
+            min: 0.0,
+            max: 1.0,
+        });
+    }
+    if let Some(irf) = irf
+        && irf.len() != n
+    {
+        return Err(ImgalError::MismatchedArrayLengths {
+            a_arr_name: "data",
+            a_arr_len: n,
+            b_arr_name: "irf",
+            b_arr_len: irf.len(),
+        });
+    }
+    // the expansion requires more bins than coefficients
+    if start + max_order + 1 >= n {
+        return Err(ImgalError::InvalidParameterValueGreater {
+            param_name: "start",
+            value: n.saturating_sub(max_order + 2),
+        });
+    }
+    Ok(())
+}
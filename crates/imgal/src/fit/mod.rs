@@ -0,0 +1,27 @@
+//! Decay curve fitting functions.
+//!
+//! This module provides functions for estimating fluorescence lifetimes from
+//! decay curves and images, either with closed form estimators or by
+//! iteratively fitting decay models, and for assessing the goodness of fits.
+
+mod decay;
+mod goodness;
+mod laguerre;
+mod model_select;
+mod rld;
+
+pub use decay::FitObjective;
+pub use decay::fit_decay;
+pub use decay::fit_decay_image;
+pub use goodness::reduced_chi_square;
+pub use goodness::reduced_chi_square_image;
+pub use goodness::residual_autocorrelation;
+pub use goodness::weighted_residuals;
+pub use goodness::weighted_residuals_image;
+pub use laguerre::laguerre_lifetime;
+pub use laguerre::laguerre_lifetime_image;
+pub use model_select::ModelCriterion;
+pub use model_select::model_select;
+pub use model_select::model_select_image;
+pub use rld::rld;
+pub use rld::rld_image;
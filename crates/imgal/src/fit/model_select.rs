@@ -0,0 +1,290 @@
+use ndarray::{
+    Array1, Array2, ArrayBase, ArrayView1, ArrayView2, AsArray, Axis, Ix1, Ix3, ViewRepr, Zip,
+};
+
+use super::decay::{FitObjective, fit_lane, objective_value, validate_fit};
+use super::rld::{peak_index, summed_peak_index};
+use crate::distribution::f_cdf;
+use crate::prelude::*;
+use crate::validate::{check_axis, check_shapes};
+
+/// The criterion used to select the number of exponential components.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum ModelCriterion {
+    /// The Akaike information criterion, `AIC = c + 2 × p`, where `c` is the
+    /// fit objective value and `p` the number of parameters.
+    #[default]
+    Aic,
+    /// The Bayesian information criterion, `BIC = c + p × ln(m)`, where `m` is
+    /// the number of fitted bins. BIC penalizes additional components more
+    /// strongly than AIC.
+    Bic,
+    /// Sequential F-tests of nested models, an additional component is only
+    /// accepted if the improvement of the fit is significant at the given
+    /// significance level (*e.g.* `0.05`).
+    FTest(f64),
+}
+
+/// Select the number of exponential components of a 1D decay curve.
+///
+/// # Description
+///
+/// Fits multiexponential decay models with `1` to `max_components` components
+/// (see `fit_decay`) to a 1D decay curve and selects the number of components
+/// with the given `criterion`. For the information criteria (AIC and BIC) the
+/// model with the lowest criterion value is selected. For the F-test, starting
+/// with a single component, the `k + 1` component model is accepted while the
+/// F statistic of the nested models is significant:
+///
+/// ```text
+/// F = ((cₖ - cₖ₊₁) / (pₖ₊₁ - pₖ)) / (cₖ₊₁ / (m - pₖ₊₁))
+/// ```
+///
+/// Where `cₖ` is the objective value (χ² or Poisson deviance) of the `k`
+/// component fit, `pₖ = 2 × k + 1` its number of parameters and `m` the number
+/// of fitted bins. The initial lifetimes of the `k` component model are spread
+/// geometrically around `tau` (*i.e.* `τ / 4`, `τ` and `4 × τ` for `k = 3`).
+///
+/// # Arguments
+///
+/// * `data`: The input 1D decay curve.
+/// * `period`: The period (*i.e.* time interval).
+/// * `tau`: The initial lifetime estimate (*e.g.* from `rld`).
+/// * `max_components`: The maximum number of exponential components. If
+///   `None`, then `max_components = 3`.
+/// * `start`: The bin index where the fit starts. If `None`, then the index of
+///   the decay curve's maximum value (*i.e.* the peak) is used.
+/// * `criterion`: The model selection criterion. If `None`, then
+///   `criterion = ModelCriterion::Aic`.
+/// * `objective`: The objective function to minimize. If `None`, then
+///   `objective = FitObjective::LeastSquares`.
+///
+/// # Returns
+///
+/// * `Ok((usize, Array1<f64>))`: A tuple containing the selected number of
+///   components `k` and the fitted parameters of the selected model, ordered as
+///   `[a₁, τ₁, ..., aₖ, τₖ, b]`. If the decay curve has no positive counts
+///   after `start`, then `k = 0`.
+/// * `Err(ImgalError)`: If `max_components == 0`. If `tau` is not positive. If
+///   fewer bins than `2 × max_components + 2` remain after `start`. If the
+///   F-test significance level is outside the open interval `(0.0, 1.0)`.
+///
+/// # Reference
+///
+/// <https://doi.org/10.1109/TAC.1974.1100705>
+pub fn model_select<'a, T, A>(
+    data: A,
+    period: f64,
+    tau: f64,
+    max_components: Option<usize>,
+    start: Option<usize>,
+    criterion: Option<ModelCriterion>,
+    objective: Option<FitObjective>,
+) -> Result<(usize, Array1<f64>), ImgalError>
+where
+    A: AsArray<'a, T, Ix1>,
+    T: 'a + AsNumeric,
+{
+    let data: ArrayBase<ViewRepr<&'a T>, Ix1> = data.into();
+    let n = data.len();
+    let max_components = max_components.unwrap_or(3);
+    let start = start.unwrap_or_else(|| peak_index(data.iter().map(|v| v.to_f64())));
+    let criterion = criterion.unwrap_or_default();
+    validate_selection(n, tau, max_components, start, criterion)?;
+    let y: Vec<f64> = data.iter().skip(start).map(|v| v.to_f64()).collect();
+    let (k, params) = select_lane(
+        &y,
+        period / n as f64,
+        tau,
+        max_components,
+        criterion,
+        objective.unwrap_or_default(),
+    );
+    Ok((k, Array1::from_vec(params)))
+}
+
+/// Select the number of exponential components of each pixel of a 3D decay
+/// image.
+///
+/// # Description
+///
+/// Selects the number of exponential components of each pixel's decay curve
+/// independently. See `model_select` for details on the fitted models and
+/// the selection criteria.
+///
+/// # Arguments
+///
+/// * `data`: The input 3D decay image.
+/// * `period`: The period (*i.e.* time interval).
+/// * `tau`: The initial lifetime estimate, shared by all pixels.
+/// * `max_components`: The maximum number of exponential components. If
+///   `None`, then `max_components = 3`.
+/// * `start`: The bin index where the fit starts. If `None`, then the index of
+///   the maximum value of the summed decay curve (*i.e.* the peak of the whole
+///   image) is used.
+/// * `criterion`: The model selection criterion. If `None`, then
+///   `criterion = ModelCriterion::Aic`.
+/// * `objective`: The objective function to minimize. If `None`, then
+///   `objective = FitObjective::LeastSquares`.
+/// * `mask`: An optional 2D boolean mask. Pixels where the mask is `false` are
+///   not fit and their component count is set to `0`.
+/// * `axis`: The decay or lifetime axis. If `None`, then `axis = 2`.
+/// * `threads`: The requested number of threads to use for parallel execution.
+///   If `None` or `Some(1)` sequential execution is used. If `Some(0)`, then
+///   the maximum available parallelism is used. Thread counts are clamped to
+///   the systems maximum.
+///
+/// # Returns
+///
+/// * `Ok(Array2<usize>)`: The selected number of components of each pixel with
+///   shape `(row, col)`. Pixels without positive counts after `start` are set
+///   to `0`.
+/// * `Err(ImgalError)`: If `axis >= 3`. If `max_components == 0`. If `tau` is
+///   not positive. If fewer bins than `2 × max_components + 2` remain after
+///   `start`. If the F-test significance level is outside the open interval
+///   `(0.0, 1.0)`. If the `mask` shape does not match the image shape.
+pub fn model_select_image<'a, T, A>(
+    data: A,
+    period: f64,
+    tau: f64,
+    max_components: Option<usize>,
+    start: Option<usize>,
+    criterion: Option<ModelCriterion>,
+    objective: Option<FitObjective>,
+    mask: Option<ArrayView2<bool>>,
+    axis: Option<usize>,
+    threads: Option<usize>,
+) -> Result<Array2<usize>, ImgalError>
+where
+    A: AsArray<'a, T, Ix3>,
+    T: 'a + AsNumeric,
+{
+    let data: ArrayBase<ViewRepr<&'a T>, Ix3> = data.into();
+    let axis = axis.unwrap_or(2);
+    check_axis(axis, 3)?;
+    let n = data.len_of(Axis(axis));
+    let max_components = max_components.unwrap_or(3);
+    let start = start.unwrap_or_else(|| summed_peak_index(&data, axis));
+    let criterion = criterion.unwrap_or_default();
+    validate_selection(n, tau, max_components, start, criterion)?;
+    let objective = objective.unwrap_or_default();
+    let dt = period / n as f64;
+    let mut shape = data.shape().to_vec();
+    shape.remove(axis);
+    let (rows, cols) = (shape[0], shape[1]);
+    if let Some(msk) = mask {
+        check_shapes("data", &shape, "mask", msk.shape())?;
+    }
+    let mut k_arr = Array2::<usize>::zeros((rows, cols));
+    let select_calc = |idx: (usize, usize), ln: ArrayView1<T>, k: &mut usize| {
+        if mask.is_some_and(|m| !m[idx]) {
+            return;
+        }
+        let y: Vec<f64> = ln.iter().skip(start).map(|v| v.to_f64()).collect();
+        *k = select_lane(&y, dt, tau, max_components, criterion, objective).0;
+    };
+    par!(threads,
+        seq_exp: Zip::indexed(data.lanes(Axis(axis)))
+            .and(&mut k_arr)
+            .for_each(select_calc),
+        par_exp: Zip::indexed(data.lanes(Axis(axis)))
+            .and(&mut k_arr)
+            .par_for_each(select_calc));
+    Ok(k_arr)
+}
+
+/// Fit the `1` to `max_components` component models to a single decay tail
+/// and select the number of components.
+fn select_lane(
+    y: &[f64],
+    dt: f64,
+    tau: f64,
+    max_components: usize,
+    criterion: ModelCriterion,
+    objective: FitObjective,
+) -> (usize, Vec<f64>) {
+    if y.iter().all(|&v| v <= 0.0) {
+        return (0, vec![0.0; 3]);
+    }
+    let m = y.len() as f64;
+    let t: Vec<f64> = (0..y.len()).map(|i| i as f64 * dt).collect();
+    let mut fits: Vec<(Vec<f64>, f64)> = Vec::with_capacity(max_components);
+    for k in 1..=max_components {
+        let (params, _) = fit_lane(y, dt, &initial_taus(tau, k), objective, 100);
+        let cost = objective_value(y, &t, &params, objective);
+        fits.push((
+            params,
+            if cost.is_finite() {
+                cost
+            } else {
+                f64::INFINITY
+            },
+        ));
+    }
+    let n_params = |k: usize| (2 * k + 1) as f64;
+    let best = match criterion {
+        ModelCriterion::Aic | ModelCriterion::Bic => {
+            let penalty = match criterion {
+                ModelCriterion::Bic => m.ln(),
+                _ => 2.0,
+            };
+            (1..=max_components)
+                .min_by(|&a, &b| {
+                    let ca = fits[a - 1].1 + penalty * n_params(a);
+                    let cb = fits[b - 1].1 + penalty * n_params(b);
+                    ca.total_cmp(&cb)
+                })
+                .unwrap_or(1)
+        }
+        ModelCriterion::FTest(alpha) => {
+            let mut k = 1;
+            while k < max_components {
+                let (c_k, c_next) = (fits[k - 1].1, fits[k].1);
+                let dof = m - n_params(k + 1);
+                let f = ((c_k - c_next) / 2.0) / (c_next / dof);
+                let p = 1.0 - f_cdf(f, 2.0, dof).unwrap_or(0.0);
+                if p.is_nan() || p >= alpha {
+                    break;
+                }
+                k += 1;
+            }
+            k
+        }
+    };
+    (best, fits.swap_remove(best - 1).0)
+}
+
+/// Spread the initial lifetimes of a `k` component model geometrically around
+/// `tau`.
+fn initial_taus(tau: f64, k: usize) -> Vec<f64> {
+    (0..k)
+        .map(|j| tau * 4.0_f64.powf(j as f64 - (k - 1) as f64 / 2.0))
+        .collect()
+}
+
+/// Validate the model selection parameters and the fit range.
+fn validate_selection(
+    n: usize,
+    tau: f64,
+    max_components: usize,
+    start: usize,
+    criterion: ModelCriterion,
+) -> Result<(), ImgalError> {
+    if max_components == 0 {
+        return Err(ImgalError::InvalidParameterValueEqual {
+            param_name: "max_components",
+            value: 0,
+        });
+    }
+    if let ModelCriterion::FTest(alpha) = criterion
+        && !(alpha > 0.0 && alpha < 1.0)
+    {
+        return Err(ImgalError::InvalidParameterValueOutsideRange {
+            param_name: "alpha",
+            value: alpha,
+            min: 0.0,
+            max: 1.0,
+        });
+    }
+    validate_fit(n, &initial_taus(tau, max_components), start)
+}
@@ -0,0 +1,247 @@
+use ndarray::{
+    Array2, ArrayBase, ArrayView1, ArrayView2, ArrayView3, AsArray, Axis, Ix1, Ix3, ViewRepr, Zip,
+};
+
+use crate::prelude::*;
+use crate::validate::{check_axis, check_shapes};
+
+/// Compute the lifetime of a 1D decay curve with rapid lifetime determination
+/// (RLD).
+///
+/// # Description
+///
+/// Computes the lifetime (τ) of a 1D decay curve using the rapid lifetime
+/// determination (RLD) estimator. Starting at the `start` bin, the decay curve
+/// is integrated into `gates` contiguous gates of equal width `Δt`. For two
+/// gates the lifetime is computed with the classic two-gate estimator:
+///
+/// ```text
+/// τ = Δt / ln(D₀ / D₁)
+/// ```
+///
+/// Where `D₀` and `D₁` are the integrated counts of the first and second gate.
+/// For more than two gates (*i.e.* multi-gate RLD) the lifetime is computed
+/// from the slope of a weighted log-linear least squares fit of the gate
+/// counts, where each gate is weighted by its counts (`Var(ln Dᵢ) ≈ 1 / Dᵢ`):
+///
+/// ```text
+/// τ = -1 / slope(ln Dᵢ, i × Δt)
+/// ```
+///
+/// Both estimators are closed form and do not require iterative fitting.
+///
+/// # Arguments
+///
+/// * `data`: The input 1D decay curve.
+/// * `period`: The period (*i.e.* time interval).
+/// * `gates`: The number of gates. If `None`, then `gates = 2`.
+/// * `start`: The bin index where the first gate starts. If `None`, then the
+///   index of the decay curve's maximum value (*i.e.* the peak) is used.
+///
+/// # Returns
+///
+/// * `Ok(f64)`: The RLD lifetime. If the gate counts do not describe a decay
+///   (*e.g.* a gate has zero counts) `0.0` is returned.
+/// * `Err(ImgalError)`: If `gates < 2`. If `start + gates > data.len()`.
+pub fn rld<'a, T, A>(
+    data: A,
+    period: f64,
+    gates: Option<usize>,
+    start: Option<usize>,
+) -> Result<f64, ImgalError>
+where
+    A: AsArray<'a, T, Ix1>,
+    T: 'a + AsNumeric,
+{
+    let data: ArrayBase<ViewRepr<&'a T>, Ix1> = data.into();
+    let gates = gates.unwrap_or(2);
+    let n = data.len();
+    let start = start.unwrap_or_else(|| peak_index(data.iter().map(|v| v.to_f64())));
+    let width = validate_gates(n, gates, start)?;
+    let dt = period / n as f64;
+    Ok(rld_lane(data, gates, start, width, width as f64 * dt))
+}
+
+/// Compute a lifetime image of a 3D decay image with rapid lifetime
+/// determination (RLD).
+///
+/// # Description
+///
+/// Computes a per-pixel lifetime (τ) image of a 3D decay image using the rapid
+/// lifetime determination (RLD) estimator. Starting at the `start` bin, each
+/// decay curve is integrated into `gates` contiguous gates of equal width `Δt`.
+/// For two gates the lifetime is computed with the classic two-gate estimator:
+///
+/// ```text
+/// τ = Δt / ln(D₀ / D₁)
+/// ```
+///
+/// For more than two gates (*i.e.* multi-gate RLD) the lifetime is computed
+/// from the slope of a weighted log-linear least squares fit of the gate
+/// counts:
+///
+/// ```text
+/// τ = -1 / slope(ln Dᵢ, i × Δt)
+/// ```
+///
+/// RLD lifetimes are fast to compute and are well suited as initial guesses
+/// for iterative fitting or for real-time previews.
+///
+/// # Arguments
+///
+/// * `data`: The input 3D decay image.
+/// * `period`: The period (*i.e.* time interval).
+/// * `gates`: The number of gates. If `None`, then `gates = 2`.
+/// * `start`: The bin index where the first gate starts. If `None`, then the
+///   index of the maximum value of the summed decay curve (*i.e.* the peak of
+///   the whole image) is used.
+/// * `mask`: An optional 2D boolean mask. Pixels where the mask is `false` are
+///   set to `0.0`.
+/// * `axis`: The decay or lifetime axis. If `None`, then `axis = 2`.
+/// * `threads`: The requested number of threads to use for parallel execution.
+///   If `None` or `Some(1)` sequential execution is used. If `Some(0)`, then
+///   the maximum available parallelism is used. Thread counts are clamped to
+///   the systems maximum.
+///
+/// # Returns
+///
+/// * `Ok(Array2<f64>)`: The 2D RLD lifetime image. Pixels where the gate
+///   counts do not describe a decay are set to `0.0`.
+/// * `Err(ImgalError)`: If `axis >= 3`. If `gates < 2`. If
+///   `start + gates` is greater than the length of `axis`. If the `mask` shape
+///   does not match the image shape.
+pub fn rld_image<'a, T, A>(
+    data: A,
+    period: f64,
+    gates: Option<usize>,
+    start: Option<usize>,
+    mask: Option<ArrayView2<bool>>,
+    axis: Option<usize>,
+    threads: Option<usize>,
+) -> Result<Array2<f64>, ImgalError>
+where
+    A: AsArray<'a, T, Ix3>,
+    T: 'a + AsNumeric,
+{
+    let data: ArrayBase<ViewRepr<&'a T>, Ix3> = data.into();
+    let axis = axis.unwrap_or(2);
+    check_axis(axis, 3)?;
+    let gates = gates.unwrap_or(2);
+    let n = data.len_of(Axis(axis));
+    let start = start.unwrap_or_else(|| summed_peak_index(&data, axis));
+    let width = validate_gates(n, gates, start)?;
+    let dt = period / n as f64;
+    let gate_time = width as f64 * dt;
+    let mut shape = data.shape().to_vec();
+    shape.remove(axis);
+    let mut tau_arr = Array2::<f64>::zeros((shape[0], shape[1]));
+    if let Some(msk) = mask {
+        check_shapes("data", tau_arr.shape(), "mask", msk.shape())?;
+        let rld_msk_calc = |ln: ArrayView1<T>, m: &bool, t: &mut f64| {
+            *t = if *m {
+                rld_lane(ln, gates, start, width, gate_time)
+            } else {
+                0.0
+            };
+        };
+        par!(threads,
+            seq_exp: Zip::from(data.lanes(Axis(axis))).and(msk).and(&mut tau_arr)
+                .for_each(rld_msk_calc),
+            par_exp: Zip::from(data.lanes(Axis(axis))).and(msk).and(&mut tau_arr)
+                .par_for_each(rld_msk_calc));
+    } else {
+        let rld_calc = |ln: ArrayView1<T>, t: &mut f64| {
+            *t = rld_lane(ln, gates, start, width, gate_time);
+        };
+        par!(threads,
+            seq_exp: Zip::from(data.lanes(Axis(axis))).and(&mut tau_arr)
+                .for_each(rld_calc),
+            par_exp: Zip::from(data.lanes(Axis(axis))).and(&mut tau_arr)
+                .par_for_each(rld_calc));
+    }
+    Ok(tau_arr)
+}
+
+/// Compute the RLD lifetime of a single decay lane.
+fn rld_lane<T>(data: ArrayView1<T>, gates: usize, start: usize, width: usize, gate_time: f64) -> f64
+where
+    T: AsNumeric,
+{
+    let counts: Vec<f64> = (0..gates)
+        .map(|g| {
+            let s = start + g * width;
+            (s..s + width).map(|i| data[i].to_f64()).sum()
+        })
+        .collect();
+    if counts.iter().any(|&c| c <= 0.0) {
+        return 0.0;
+    }
+    let tau = if gates == 2 {
+        gate_time / (counts[0] / counts[1]).ln()
+    } else {
+        // weighted log-linear least squares, weights are the gate counts
+        let (mut sw, mut swx, mut swy, mut swxx, mut swxy) = (0.0, 0.0, 0.0, 0.0, 0.0);
+        counts.iter().enumerate().for_each(|(i, &c)| {
+            let x = i as f64 * gate_time;
+            let y = c.ln();
+            sw += c;
+            swx += c * x;
+            swy += c * y;
+            swxx += c * x * x;
+            swxy += c * x * y;
+        });
+        let slope = (sw * swxy - swx * swy) / (sw * swxx - swx * swx);
+        -1.0 / slope
+    };
+    if tau.is_finite() && tau > 0.0 {
+        tau
+    } else {
+        0.0
+    }
+}
+
+/// Find the index of the maximum value of an iterator.
+pub(super) fn peak_index<I>(values: I) -> usize
+where
+    I: Iterator<Item = f64>,
+{
+    values
+        .enumerate()
+        .fold(
+            (0, f64::MIN),
+            |acc, (i, v)| if v > acc.1 { (i, v) } else { acc },
+        )
+        .0
+}
+
+/// Find the index of the maximum value of the summed decay curve of a 3D decay
+/// image.
+pub(super) fn summed_peak_index<T>(data: &ArrayView3<T>, axis: usize) -> usize
+where
+    T: AsNumeric,
+{
+    let summed = (0..data.len_of(Axis(axis))).map(|i| {
+        data.index_axis(Axis(axis), i)
+            .iter()
+            .map(|v| v.to_f64())
+            .sum::<f64>()
+    });
+    peak_index(summed)
+}
+
+/// Validate the gate configuration and return the gate width.
+fn validate_gates(n: usize, gates: usize, start: usize) -> Result<usize, ImgalError> {
+    if gates < 2 {
+        return Err(ImgalError::InvalidParameterValueLess {
+            param_name: "gates",
+            value: 2,
+        });
+    }
+    if start + gates > n {
+        return Err(ImgalError::InvalidParameterValueGreater {
+            param_name: "start",
+            value: n.saturating_sub(gates),
+        });
+    }
+    Ok((n - start) / gates)
+}
@@ -0,0 +1,19 @@
+//! Fluorescence lifetime imaging microscopy (FLIM) functions.
+//!
+//! This module provides functions for characterizing and correcting FLIM
+//! instrumentation, such as the instrument response function (IRF), its
+//! alignment with measured decays, time-correlated single photon counting
+//! (TCSPC) pile-up and the rebinning of decay histograms.
+
+mod irf;
+mod pileup;
+mod rebin;
+mod shift;
+
+pub use irf::estimate_irf;
+pub use irf::extract_irf;
+pub use pileup::pileup_correction;
+pub use rebin::rebin_decay;
+pub use rebin::resample_decay;
+pub use shift::estimate_irf_shift;
+pub use shift::shift_irf;
@@ -0,0 +1,119 @@
+use ndarray::{Array, ArrayBase, ArrayViewMut1, AsArray, Axis, Dimension, ViewRepr, Zip};
+
+use crate::prelude::*;
+use crate::validate::check_axis;
+
+/// Correct TCSPC decay histograms for pile-up and detector dead time.
+///
+/// # Description
+///
+/// Applies the Coates pile-up correction to time-correlated single photon
+/// counting (TCSPC) decay histograms. TCSPC electronics register at most one
+/// photon per excitation cycle, so at high count rates late photons are
+/// under-counted and the decay appears shortened. Given the number of
+/// excitation cycles `E`, the corrected counts are:
+///
+/// ```text
+/// Nᵢ' = -E × ln(1 - Nᵢ / (E - Σⱼ₍ⱼ<ᵢ₎ Nⱼ))
+/// ```
+///
+/// Where `Nᵢ` are the measured counts of bin `i`. Excitation cycles that occur
+/// while the detector is dead can not register a photon. For a non-paralyzable
+/// detector each detected photon blocks the following `dead_time × rate`
+/// excitation cycles, so the number of available cycles is:
+///
+/// ```text
+/// E = rate × acquisition_time - N × dead_time × rate
+/// ```
+///
+/// Where `N` is the total number of detected photons of the histogram. The
+/// correction is applied to each decay histogram (*i.e.* lane) along `axis`
+/// independently. The corresponding forward model, for simulating pile-up
+/// distorted data, is `simulation::instrument::dead_time_pileup`.
+///
+/// # Arguments
+///
+/// * `data`: The input n-dimensional decay histogram(s).
+/// * `rate`: The excitation (laser repetition) rate in Hz.
+/// * `dead_time`: The detector and electronics dead time in seconds.
+/// * `acquisition_time`: The acquisition (*i.e.* integration or dwell) time of
+///   each decay histogram in seconds.
+/// * `axis`: The decay or lifetime axis. If `None`, then the last axis is used.
+/// * `threads`: The requested number of threads to use for parallel execution.
+///   If `None` or `Some(1)` sequential execution is used. If `Some(0)`, then
+///   the maximum available parallelism is used. Thread counts are clamped to
+///   the systems maximum.
+///
+/// # Returns
+///
+/// * `Ok(Array<f64, D>)`: The pile-up corrected decay histogram(s) with the
+///   same shape as `data`.
+/// * `Err(ImgalError)`: If `axis >= data.ndim()`. If `rate <= 0.0`. If
+///   `dead_time < 0.0`. If `acquisition_time <= 0.0`. If the counts of any
+///   decay histogram exceed the number of available excitation cycles.
+///
+/// # Reference
+///
+/// <https://doi.org/10.1088/0022-3735/1/8/437>
+pub fn pileup_correction<'a, T, A, D>(
+    data: A,
+    rate: f64,
+    dead_time: f64,
+    acquisition_time: f64,
+    axis: Option<usize>,
+    threads: Option<usize>,
+) -> Result<Array<f64, D>, ImgalError>
+where
+    A: AsArray<'a, T, D>,
+    D: Dimension,
+    T: 'a + AsNumeric,
+{
+    let data: ArrayBase<ViewRepr<&'a T>, D> = data.into();
+    let axis = axis.unwrap_or(data.ndim().saturating_sub(1));
+    check_axis(axis, data.ndim())?;
+    let positive = |name: &'static str, value: f64, allow_zero: bool| {
+        if value > 0.0 || (allow_zero && value == 0.0) {
+            Ok(())
+        } else {
+            Err(ImgalError::InvalidParameterValueOutsideRange {
+                param_name: name,
+                value,
+                min: 0.0,
+                max: f64::INFINITY,
+            })
+        }
+    };
+    positive("rate", rate, false)?;
+    positive("dead_time", dead_time, true)?;
+    positive("acquisition_time", acquisition_time, false)?;
+    let cycles = rate * acquisition_time;
+    let blocked = dead_time * rate;
+    let mut corr_arr = data.mapv(|v| v.to_f64());
+    let saturated = |ln: &ArrayViewMut1<f64>| {
+        let total: f64 = ln.sum();
+        total >= cycles - total * blocked
+    };
+    if corr_arr
+        .lanes_mut(Axis(axis))
+        .into_iter()
+        .any(|ln| saturated(&ln))
+    {
+        return Err(ImgalError::InvalidGeneric {
+            msg: "Invalid pile-up correction, the decay counts exceed the number of available excitation cycles.",
+        });
+    }
+    let corr_calc = |mut ln: ArrayViewMut1<f64>| {
+        let total: f64 = ln.sum();
+        let available = cycles - total * blocked;
+        let mut preceding = 0.0;
+        ln.iter_mut().for_each(|v| {
+            let counts = *v;
+            *v = -available * (1.0 - counts / (available - preceding)).ln();
+            preceding += counts;
+        });
+    };
+    par!(threads,
+        seq_exp: Zip::from(corr_arr.lanes_mut(Axis(axis))).for_each(corr_calc),
+        par_exp: Zip::from(corr_arr.lanes_mut(Axis(axis))).par_for_each(corr_calc));
+    Ok(corr_arr)
+}
@@ -0,0 +1,190 @@
+use ndarray::{
+    Array, ArrayBase, ArrayView1, ArrayViewMut1, AsArray, Axis, Dimension, RemoveAxis, ViewRepr,
+    Zip,
+};
+
+use crate::prelude::*;
+use crate::validate::check_axis;
+
+/// Rebin decay histograms by summing adjacent time bins.
+///
+/// # Description
+///
+/// Reduces the number of time bins of decay histograms by summing each group
+/// of `factor` adjacent bins along `axis`:
+///
+/// ```text
+/// N'ᵢ = Σⱼ₌₀ᶠ⁻¹ N[i × f + j]
+/// ```
+///
+/// Where `f` is the rebinning factor. The photon counts and the period are
+/// preserved, the bin width grows to `f × Δt`. Rebinning increases the counts
+/// per bin (*e.g.* for fitting low count decays) and matches decays recorded
+/// with different numbers of time bins.
+///
+/// # Arguments
+///
+/// * `data`: The input n-dimensional decay histogram(s).
+/// * `factor`: The number of adjacent time bins to sum.
+/// * `axis`: The decay or lifetime axis. If `None`, then the last axis is used.
+/// * `threads`: The requested number of threads to use for parallel execution.
+///   If `None` or `Some(1)` sequential execution is used. If `Some(0)`, then
+///   the maximum available parallelism is used. Thread counts are clamped to
+///   the systems maximum.
+///
+/// # Returns
+///
+/// * `Ok(Array<f64, D>)`: The rebinned decay histogram(s), with `n / factor`
+///   time bins along `axis`.
+/// * `Err(ImgalError)`: If `axis >= data.ndim()`. If `factor == 0`. If the
+///   number of time bins is not divisible by `factor`.
+pub fn rebin_decay<'a, T, A, D>(
+    data: A,
+    factor: usize,
+    axis: Option<usize>,
+    threads: Option<usize>,
+) -> Result<Array<f64, D>, ImgalError>
+where
+    A: AsArray<'a, T, D>,
+    D: Dimension + RemoveAxis,
+    T: 'a + AsNumeric,
+{
+    let data: ArrayBase<ViewRepr<&'a T>, D> = data.into();
+    let axis = axis.unwrap_or(data.ndim().saturating_sub(1));
+    check_axis(axis, data.ndim())?;
+    if factor == 0 {
+        return Err(ImgalError::InvalidParameterValueEqual {
+            param_name: "factor",
+            value: 0,
+        });
+    }
+    let n = data.len_of(Axis(axis));
+    if !n.is_multiple_of(factor) {
+        return Err(ImgalError::InvalidGeneric {
+            msg: "Invalid rebinning factor, the number of time bins must be divisible by the factor.",
+        });
+    }
+    let mut shape = data.raw_dim();
+    shape[axis] = n / factor;
+    let mut rebin_arr = Array::<f64, D>::zeros(shape);
+    let rebin_calc = |ln: ArrayView1<T>, mut out: ArrayViewMut1<f64>| {
+        ln.iter()
+            .enumerate()
+            .for_each(|(i, v)| out[i / factor] += v.to_f64());
+    };
+    par!(threads,
+        seq_exp: Zip::from(data.lanes(Axis(axis)))
+            .and(rebin_arr.lanes_mut(Axis(axis)))
+            .for_each(rebin_calc),
+        par_exp: Zip::from(data.lanes(Axis(axis)))
+            .and(rebin_arr.lanes_mut(Axis(axis)))
+            .par_for_each(rebin_calc));
+    Ok(rebin_arr)
+}
+
+/// Resample decay histograms to a new number of time bins and period.
+///
+/// # Description
+///
+/// Resamples decay histograms recorded with `n` time bins over `period` to
+/// `samples` time bins over `new_period`, *e.g.* to match data from different
+/// instruments before joint analysis. The histogram is treated as a piecewise
+/// constant photon density, and each new bin receives the counts of the
+/// overlapping part of the original bins. This is the linear interpolation of
+/// the cumulative counts `C(t)`:
+///
+/// ```text
+/// N'ᵢ = C((i + 1) × Δt') - C(i × Δt'), Δt' = new_period / samples
+/// ```
+///
+/// The photon counts are conserved when `new_period == period`. New bins past
+/// the end of the original `period` receive no counts and a shorter
+/// `new_period` truncates the decay. Resampling to `n / f` bins over the same
+/// period is identical to `rebin_decay` with factor `f`.
+///
+/// # Arguments
+///
+/// * `data`: The input n-dimensional decay histogram(s).
+/// * `period`: The period (*i.e.* time interval) of the input histograms.
+/// * `samples`: The number of time bins of the resampled histograms.
+/// * `new_period`: The period of the resampled histograms. If `None`, then
+///   `new_period = period`.
+/// * `axis`: The decay or lifetime axis. If `None`, then the last axis is used.
+/// * `threads`: The requested number of threads to use for parallel execution.
+///   If `None` or `Some(1)` sequential execution is used. If `Some(0)`, then
+///   the maximum available parallelism is used. Thread counts are clamped to
+///   the systems maximum.
+///
+/// # Returns
+///
+/// * `Ok(Array<f64, D>)`: The resampled decay histogram(s), with `samples`
+///   time bins along `axis`.
+/// * `Err(ImgalError)`: If `axis >= data.ndim()`. If `samples == 0`. If
+///   `period <= 0.0` or `new_period <= 0.0`.
+pub fn resample_decay<'a, T, A, D>(
+    data: A,
+    period: f64,
+    samples: usize,
+    new_period: Option<f64>,
+    axis: Option<usize>,
+    threads: Option<usize>,
+) -> Result<Array<f64, D>, ImgalError>
+where
+    A: AsArray<'a, T, D>,
+    D: Dimension + RemoveAxis,
+    T: 'a + AsNumeric,
+{
+    let data: ArrayBase<ViewRepr<&'a T>, D> = data.into();
+    let axis = axis.unwrap_or(data.ndim().saturating_sub(1));
+    check_axis(axis, data.ndim())?;
+    if samples == 0 {
+        return Err(ImgalError::InvalidParameterValueEqual {
+            param_name: "samples",
+            value: 0,
+        });
+    }
+    let new_period = new_period.unwrap_or(period);
+    for (name, value) in [("period", period), ("new_period", new_period)] {
+        if value.is_nan() || value <= 0.0 {
+            return Err(ImgalError::InvalidParameterValueOutsideRange {
+                param_name: name,
+                value,
+                min: 0.0,
+                max: f64::INFINITY,
+            });
+        }
+    }
+    let n = data.len_of(Axis(axis));
+    // new bin edges in units of the original bins
+    let scale = new_period * n as f64 / (period * samples as f64);
+    let mut shape = data.raw_dim();
+    shape[axis] = samples;
+    let mut resample_arr = Array::<f64, D>::zeros(shape);
+    let resample_calc = |ln: ArrayView1<T>, mut out: ArrayViewMut1<f64>| {
+        let mut cumulative = Vec::with_capacity(n + 1);
+        cumulative.push(0.0);
+        ln.iter()
+            .for_each(|v| cumulative.push(cumulative[cumulative.len() - 1] + v.to_f64()));
+        let counts_at = |x: f64| {
+            let x = x.min(n as f64);
+            let j = (x.floor() as usize).min(n.saturating_sub(1));
+            cumulative[j] + (x - j as f64) * (cumulative[j + 1] - cumulative[j])
+        };
+        let mut prev = 0.0;
+        out.iter_mut().enumerate().for_each(|(i, v)| {
+            let next = counts_at((i + 1) as f64 * scale);
+            *v = next - prev;
+            prev = next;
+        });
+    };
+    if n > 0 {
+        par!(threads,
+            seq_exp: Zip::from(data.lanes(Axis(axis)))
+                .and(resample_arr.lanes_mut(Axis(axis)))
+                .for_each(resample_calc),
+            par_exp: Zip::from(data.lanes(Axis(axis)))
+                .and(resample_arr.lanes_mut(Axis(axis)))
+                .par_for_each(resample_calc));
+    }
+    Ok(resample_arr)
+}
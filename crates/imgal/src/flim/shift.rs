@@ -0,0 +1,184 @@
+use ndarray::{Array1, ArrayBase, AsArray, Ix1, ViewRepr};
+
+use crate::prelude::*;
+
+/// Estimate the temporal shift between an instrument response function (IRF)
+/// and a measured decay curve.
+///
+/// # Description
+///
+/// Estimates the temporal shift between an IRF and a measured decay curve
+/// (*e.g.* caused by cable delays or wavelength dependent detector transit
+/// times) by circular cross-correlation. A decay is the IRF convolved with the
+/// decay kernel, so the IRF is correlated with the rising edge of the decay
+/// rather than the decay itself, which removes the lifetime dependent delay of
+/// the decay maximum:
+///
+/// ```text
+/// C[k] = Σᵢ IRF[i] × max(R[i + k] - q × R[i + k - 1], 0), q = e^(-Δt / τ)
+/// ```
+///
+/// Where `Δt = period / n`. For a monoexponential decay with a known lifetime
+/// (τ) the rising edge is the shifted IRF (see `extract_irf`). Without a known
+/// lifetime `q = 1.0` (*i.e.* the backward difference), which biases the
+/// shift by a fraction of the IRF width for short lifetimes.
+///
+/// The integer lag of the cross-correlation maximum is refined to a subsample
+/// shift by fitting a parabola through the maximum and its two neighbors:
+///
+/// ```text
+/// δ = (C[k - 1] - C[k + 1]) / (2 × (C[k - 1] - 2C[k] + C[k + 1]))
+/// ```
+///
+/// The shift is wrapped into the range `(-period / 2, period / 2]`.
+///
+/// # Arguments
+///
+/// * `irf`: The input 1D IRF.
+/// * `data`: The measured 1D decay curve, with the same length as `irf`.
+/// * `period`: The period (*i.e.* time interval).
+/// * `tau`: The (dominant) lifetime of the decay. If `None`, then the forward
+///   backward difference of the decay is used as the rising edge.
+///
+/// # Returns
+///
+/// * `Ok(f64)`: The shift of the decay relative to the IRF in the units of
+///   `period`. A positive shift means the decay is
+///   delayed with respect to the IRF. Pass the shift to `shift_irf` to align
+///   the IRF with the decay.
+/// * `Err(ImgalError)`: If `irf.len() < 3`. If `irf.len() != data.len()`. If
+///   `tau <= 0.0`. If `irf` or the rising edge of `data` has no positive
+///   values.
+pub fn estimate_irf_shift<'a, T, A>(
+    irf: A,
+    data: A,
+    period: f64,
+    tau: Option<f64>,
+) -> Result<f64, ImgalError>
+where
+    A: AsArray<'a, T, Ix1>,
+    T: 'a + AsNumeric,
+{
+    let irf: ArrayBase<ViewRepr<&'a T>, Ix1> = irf.into();
+    let data: ArrayBase<ViewRepr<&'a T>, Ix1> = data.into();
+    let n = irf.len();
+    if n < 3 {
+        return Err(ImgalError::InvalidArrayLengthMinimum {
+            arr_name: "irf",
+            arr_len: n,
+            min_len: 3,
+        });
+    }
+    if data.len() != n {
+        return Err(ImgalError::MismatchedArrayLengths {
+            a_arr_name: "irf",
+            a_arr_len: n,
+            b_arr_name: "data",
+            b_arr_len: data.len(),
+        });
+    }
+    let q = match tau {
+        Some(t) if t > 0.0 => (-(period / n as f64) / t).exp(),
+        Some(t) => {
+            return Err(ImgalError::InvalidParameterValueOutsideRange {
+                param_name: "tau",
+                value: t,
+                min: 0.0,
+                max: f64::INFINITY,
+            });
+        }
+        None => 1.0,
+    };
+    let irf: Vec<f64> = irf.iter().map(|v| v.to_f64()).collect();
+    let edge: Vec<f64> = (0..n)
+        .map(|i| (data[i].to_f64() - q * data[(i + n - 1) % n].to_f64()).max(0.0))
+        .collect();
+    if !irf.iter().any(|&v| v > 0.0) || !edge.iter().any(|&v| v > 0.0) {
+        return Err(ImgalError::InvalidGeneric {
+            msg: "Failed to estimate the IRF shift, the IRF or the decay rising edge has no positive values.",
+        });
+    }
+    let xcorr: Vec<f64> = (0..n)
+        .map(|k| {
+            irf.iter()
+                .enumerate()
+                .map(|(i, &v)| v * edge[(i + k) % n])
+                .sum()
+        })
+        .collect();
+    let (peak, _) =
+        xcorr.iter().enumerate().fold(
+            (0, f64::MIN),
+            |acc, (k, &v)| if v > acc.1 { (k, v) } else { acc },
+        );
+    let prev = xcorr[(peak + n - 1) % n];
+    let next = xcorr[(peak + 1) % n];
+    let denom = prev - 2.0 * xcorr[peak] + next;
+    let delta = if denom.abs() > f64::EPSILON {
+        0.5 * (prev - next) / denom
+    } else {
+        0.0
+    };
+    let mut lag = peak as f64 + delta;
+    if lag > 0.5 * n as f64 {
+        lag -= n as f64;
+    }
+    Ok(lag * period / n as f64)
+}
+
+/// Shift an instrument response function (IRF) along the time axis.
+///
+/// # Description
+///
+/// Circularly shifts an IRF by a (sub)sample `shift` in the units of `period`
+/// with linear interpolation, where `Δt = period / n`:
+///
+/// ```text
+/// IRF'[i] = IRF(i - shift / Δt)
+/// ```
+///
+/// Samples shifted past the end of the period wrap around to the start, so the
+/// sum of the IRF is preserved.
+///
+/// # Arguments
+///
+/// * `irf`: The input 1D IRF.
+/// * `shift`: The shift in the units of `period`, *e.g.* from
+///   `estimate_irf_shift`. Positive values delay the IRF.
+/// * `period`: The period (*i.e.* time interval).
+///
+/// # Returns
+///
+/// * `Ok(Array1<f64>)`: The shifted IRF with the same length as `irf`.
+/// * `Err(ImgalError)`: If `irf` is empty. If `period <= 0.0`.
+pub fn shift_irf<'a, T, A>(irf: A, shift: f64, period: f64) -> Result<Array1<f64>, ImgalError>
+where
+    A: AsArray<'a, T, Ix1>,
+    T: 'a + AsNumeric,
+{
+    let irf: ArrayBase<ViewRepr<&'a T>, Ix1> = irf.into();
+    let n = irf.len();
+    if n == 0 {
+        return Err(ImgalError::InvalidParameterEmptyArray { param_name: "irf" });
+    }
+    if period <= 0.0 {
+        return Err(ImgalError::InvalidParameterValueOutsideRange {
+            param_name: "period",
+            value: period,
+            min: 0.0,
+            max: f64::INFINITY,
+        });
+    }
+    let bins = shift * n as f64 / period;
+    let whole = bins.floor();
+    let frac = bins - whole;
+    let offset = (whole as i64).rem_euclid(n as i64) as usize;
+    let mut shift_arr = Array1::<f64>::zeros(n);
+    shift_arr.iter_mut().enumerate().for_each(|(i, v)| {
+        // sample the IRF between the two bins preceding the shifted position
+        let a = irf[(i + 2 * n - offset) % n].to_f64();
+        let b = irf[(i + 2 * n - offset - 1) % n].to_f64();
+        *v = (1.0 - frac) * a + frac * b;
+    });
+    Ok(shift_arr)
+}
@@ -0,0 +1,150 @@
+use ndarray::{Array2, ArrayBase, ArrayView2, AsArray, Ix2, ViewRepr, Zip};
+use rayon::prelude::*;
+
+use crate::linalg::solve_dense;
+use crate::prelude::*;
+use crate::validate::check_shapes;
+
+/// Fit and subtract a low-order polynomial background from a 2D image.
+///
+/// # Description
+///
+/// Estimates a smooth background (*e.g.* an illumination gradient or a slowly
+/// varying offset) by fitting a 2D polynomial surface with least squares and
+/// subtracts it from the image:
+///
+/// ```text
+/// B(x, y) = Σ cᵢⱼ × xⁱ × yʲ,  for i + j ≤ order
+/// C(x, y) = I(x, y) - B(x, y)
+/// ```
+///
+/// Where `B` is the fitted background, `C` is the corrected image and the
+/// coordinates `x` and `y` are rescaled to the range `-1.0` to `1.0` to keep
+/// the normal equations well conditioned. If a `mask` is given, only the
+/// pixels inside the mask (*i.e.* the background pixels) contribute to the
+/// fit, while the background is evaluated over the entire image. Unlike
+/// morphological background estimation (*e.g.* a rolling ball) the fit is
+/// deterministic, fast and does not depend on the object sizes, but it only
+/// models gradients that are smooth on the scale of the image.
+///
+/// # Arguments
+///
+/// * `data`: The input 2D image.
+/// * `order`: The maximum total degree of the polynomial. If `None`, then
+///   `order = 2`.
+/// * `mask`: An optional boolean mask with the same shape as `data`, where
+///   `true` marks the background pixels used for the fit. If `None`, all
+///   pixels are used.
+/// * `threads`: The requested number of threads to use for parallel execution.
+///   If `None` or `Some(1)` sequential execution is used. If `Some(0)`, then
+///   the maximum available parallelism is used. Thread counts are clamped to
+///   the systems maximum.
+///
+/// # Returns
+///
+/// * `Ok((Array2<f64>, Array2<f64>))`: A tuple of the fitted background and
+///   the background corrected image.
+/// * `Err(ImgalError)`: If `data` is empty. If the `mask` shape does not match
+///   the `data` shape. If the fit has fewer pixels than polynomial
+///   coefficients. If the least squares system is singular.
+pub fn fit_polynomial_background<'a, T, A>(
+    data: A,
+    order: Option<usize>,
+    mask: Option<ArrayView2<bool>>,
+    threads: Option<usize>,
+) -> Result<(Array2<f64>, Array2<f64>), ImgalError>
+where
+    A: AsArray<'a, T, Ix2>,
+    T: 'a + AsNumeric,
+{
+    let data: ArrayBase<ViewRepr<&'a T>, Ix2> = data.into();
+    if data.is_empty() {
+        return Err(ImgalError::InvalidParameterEmptyArray { param_name: "data" });
+    }
+    if let Some(msk) = mask.as_ref() {
+        check_shapes("mask", msk.shape(), "data", data.shape())?;
+    }
+    let order = order.unwrap_or(2);
+    let (rows, cols) = data.dim();
+    // the monomial exponents (i, j) of x and y, ordered by total degree
+    let terms: Vec<(usize, usize)> = (0..=order)
+        .flat_map(|d| (0..=d).map(move |j| (d - j, j)))
+        .collect();
+    let n_terms = terms.len();
+    let n_fit = match mask.as_ref() {
+        Some(m) => m.iter().filter(|&&v| v).count(),
+        None => data.len(),
+    };
+    if n_fit < n_terms {
+        return Err(ImgalError::InvalidArrayLengthMinimum {
+            arr_name: "mask",
+            arr_len: n_fit,
+            min_len: n_terms,
+        });
+    }
+    let scale = |i: usize, len: usize| {
+        if len > 1 {
+            2.0 * i as f64 / (len - 1) as f64 - 1.0
+        } else {
+            0.0
+        }
+    };
+    let basis = |r: usize, c: usize, out: &mut [f64]| {
+        let (x, y) = (scale(c, cols), scale(r, rows));
+        out.iter_mut()
+            .zip(terms.iter())
+            .for_each(|(b, &(i, j))| *b = x.powi(i as i32) * y.powi(j as i32));
+    };
+    // accumulate the normal equations AᵀA and Aᵀb row by row
+    let zero = || vec![0.0; n_terms * n_terms + n_terms];
+    let accumulate = |mut acc: Vec<f64>, r: usize| {
+        let mut b = vec![0.0; n_terms];
+        for c in 0..cols {
+            if let Some(m) = mask.as_ref()
+                && !m[[r, c]]
+            {
+                continue;
+            }
+            basis(r, c, &mut b);
+            let v = data[[r, c]].to_f64();
+            for p in 0..n_terms {
+                for q in p..n_terms {
+                    acc[p * n_terms + q] += b[p] * b[q];
+                }
+                acc[n_terms * n_terms + p] += b[p] * v;
+            }
+        }
+        acc
+    };
+    let add = |mut x: Vec<f64>, y: Vec<f64>| {
+        x.iter_mut().zip(y.iter()).for_each(|(a, b)| *a += b);
+        x
+    };
+    let mut normal = par!(threads,
+        seq_exp: (0..rows).fold(zero(), accumulate),
+        par_exp: (0..rows).into_par_iter().fold(zero, accumulate).reduce(zero, add));
+    for p in 0..n_terms {
+        for q in 0..p {
+            normal[p * n_terms + q] = normal[q * n_terms + p];
+        }
+    }
+    let (ata, atb) = normal.split_at(n_terms * n_terms);
+    let coefs = solve_dense(ata, atb).ok_or(ImgalError::InvalidGeneric {
+        msg: "Invalid polynomial background fit, the least squares system is singular.",
+    })?;
+    let mut background = Array2::<f64>::zeros((rows, cols));
+    let eval = |(r, c): (usize, usize), bg: &mut f64| {
+        let mut b = vec![0.0; n_terms];
+        basis(r, c, &mut b);
+        *bg = b.iter().zip(coefs.iter()).map(|(b, k)| b * k).sum();
+    };
+    par!(threads,
+        seq_exp: Zip::indexed(background.view_mut()).for_each(eval),
+        par_exp: Zip::indexed(background.view_mut()).par_for_each(eval));
+    let mut corrected = Array2::<f64>::zeros((rows, cols));
+    let subtract = |c: &mut f64, &v: &T, &bg: &f64| *c = v.to_f64() - bg;
+    par!(threads,
+        seq_exp: Zip::from(corrected.view_mut()).and(&data).and(&background).for_each(subtract),
+        par_exp: Zip::from(corrected.view_mut()).and(&data).and(&background).par_for_each(subtract));
+    Ok((background, corrected))
+}
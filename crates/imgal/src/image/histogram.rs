@@ -3,7 +3,22 @@ use rayon::current_num_threads;
 use rayon::prelude::*;
 
 use crate::prelude::*;
-use crate::statistics::min_max;
+use crate::statistics::{linear_percentiles, min_max};
+
+/// The maximum number of bins selected by a `BinRule`.
+const MAX_AUTO_BINS: usize = 1 << 16;
+
+/// The rule to automatically select the number of histogram bins.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum BinRule {
+    /// The Freedman-Diaconis rule, a bin width of `2 × IQR × n^(-1/3)`. Robust
+    /// to outliers and suited for large or heavy-tailed data.
+    #[default]
+    FreedmanDiaconis,
+    /// Sturges' rule, `⌈log₂(n)⌉ + 1` bins. Assumes approximately normal data
+    /// and selects few bins for large data.
+    Sturges,
+}
 
 /// Create an image histogram from an n-dimensional image.
 ///
@@ -51,10 +66,16 @@ where
     }
     let max_bin_idx = bins.saturating_sub(1);
     let (min, max) = min_max(&data, threads)?;
+    // integer values are binned exactly in 128-bit precision, large 64-bit
+    // integers lose precision when converted to f64
+    let int_range = min.to_i128().zip(max.to_i128()).filter(|(lo, hi)| hi > lo);
     let (min, max) = (min.to_f64(), max.to_f64());
     let inv_bin_width = bins as f64 / (max - min);
     let hist_op = |v: T| -> usize {
-        let bin_idx = ((v.to_f64() - min) * inv_bin_width) as usize;
+        let bin_idx = match (int_range, v.to_i128()) {
+            (Some((lo, hi)), Some(x)) => ((x - lo) * bins as i128 / (hi - lo)) as usize,
+            _ => ((v.to_f64() - min) * inv_bin_width) as usize,
+        };
         if bin_idx < max_bin_idx {
             bin_idx
         } else {
@@ -86,6 +107,116 @@ where
     })))
 }
 
+/// Create an image histogram from an n-dimensional image with an automatically
+/// selected number of bins.
+///
+/// # Description
+///
+/// Selects the number of bins with a `BinRule` and creates a 1D image
+/// histogram with `histogram`, returning the histogram and its bin edges. The
+/// Freedman-Diaconis rule derives the bin width from the interquartile range
+/// (IQR), so the bin width is not inflated by outliers (*e.g.* hot pixels in
+/// float data):
+///
+/// ```text
+/// w = 2 × IQR / ∛n
+/// bins = ⌈(max - min) / w⌉
+/// ```
+///
+/// Sturges' rule selects `⌈log₂(n)⌉ + 1` bins. If the IQR is `0.0`, the
+/// Freedman-Diaconis rule falls back to Sturges' rule. Constant data has a
+/// single bin and the number of bins is clamped to `65536`.
+///
+/// # Arguments
+///
+/// * `data`: The input n-dimensional image.
+/// * `rule`: The bin selection rule. If `None`, then
+///   `rule = BinRule::FreedmanDiaconis`.
+/// * `threads`: The requested number of threads to use for parallel execution.
+///   If `None` or `Some(1)` sequential execution is used. If `Some(0)`, then
+///   the maximum available parallelism is used. Thread counts are clamped to
+///   the systems maximum.
+///
+/// # Returns
+///
+/// * `Ok((Array1<i64>, Array1<f64>))`: A tuple of the image histogram and its
+///   `bins + 1` bin edges, from the minimum to the maximum value of `data`.
+/// * `Err(ImgalError)`: If the input data array is empty.
+pub fn histogram_auto<'a, T, A, D>(
+    data: A,
+    rule: Option<BinRule>,
+    threads: Option<usize>,
+) -> Result<(Array1<i64>, Array1<f64>), ImgalError>
+where
+    A: AsArray<'a, T, D>,
+    D: Dimension,
+    T: 'a + AsNumeric,
+{
+    let data: ArrayBase<ViewRepr<&'a T>, D> = data.into();
+    let bins = histogram_bin_count(&data, rule, threads)?;
+    let hist = histogram(&data, Some(bins), threads)?;
+    let (min, max) = min_max(&data, threads)?;
+    let edges = Array1::linspace(min.to_f64(), max.to_f64(), bins + 1);
+    Ok((hist, edges))
+}
+
+/// Select the number of histogram bins of an n-dimensional image.
+///
+/// # Description
+///
+/// Selects the number of histogram bins of `data` with a `BinRule`, see
+/// `histogram_auto` for the rules.
+///
+/// # Arguments
+///
+/// * `data`: The input n-dimensional image.
+/// * `rule`: The bin selection rule. If `None`, then
+///   `rule = BinRule::FreedmanDiaconis`.
+/// * `threads`: The requested number of threads to use for parallel execution.
+///   If `None` or `Some(1)` sequential execution is used. If `Some(0)`, then
+///   the maximum available parallelism is used. Thread counts are clamped to
+///   the systems maximum.
+///
+/// # Returns
+///
+/// * `Ok(usize)`: The number of bins, in the range `1` to `65536`.
+/// * `Err(ImgalError)`: If the input data array is empty.
+pub fn histogram_bin_count<'a, T, A, D>(
+    data: A,
+    rule: Option<BinRule>,
+    threads: Option<usize>,
+) -> Result<usize, ImgalError>
+where
+    A: AsArray<'a, T, D>,
+    D: Dimension,
+    T: 'a + AsNumeric,
+{
+    let data: ArrayBase<ViewRepr<&'a T>, D> = data.into();
+    if data.is_empty() {
+        return Err(ImgalError::InvalidParameterEmptyArray { param_name: "data" });
+    }
+    let n = data.len() as f64;
+    let (min, max) = min_max(&data, threads)?;
+    let range = max.to_f64() - min.to_f64();
+    if range <= 0.0 {
+        return Ok(1);
+    }
+    let sturges = n.log2().ceil() + 1.0;
+    let bins = match rule.unwrap_or_default() {
+        BinRule::FreedmanDiaconis => {
+            let q = linear_percentiles(&data, &[25.0, 75.0], None, None, threads)?;
+            let iqr = q[1] - q[0];
+            if iqr > 0.0 {
+                (range / (2.0 * iqr / n.cbrt())).ceil()
+            } else {
+                sturges
+            }
+        }
+        BinRule::Sturges => sturges,
+    };
+    Ok((bins as usize).clamp(1, MAX_AUTO_BINS))
+}
+
 /// Compute the histogram bin midpoint value from a bin index.
 ///
 /// # Description
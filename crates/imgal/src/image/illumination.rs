@@ -0,0 +1,135 @@
+use ndarray::{Array2, ArrayBase, AsArray, Axis, Ix2, Ix3, ViewRepr};
+
+use crate::prelude::*;
+use crate::statistics::linear_percentile;
+
+/// Estimate the illumination (flat-field) profile from a batch of images.
+///
+/// # Description
+///
+/// Retrospectively estimates the illumination profile (*i.e.* the flat-field)
+/// of an acquisition from a stack of many images of different fields of view,
+/// for cases where no calibration images exist. Sample structures vary between
+/// the fields of view while the illumination profile is shared, so a robust per
+/// pixel statistic (*e.g.* the median) across the stack recovers the profile:
+///
+/// ```text
+/// P(x) = percentile(I₁(x), I₂(x), ..., Iₙ(x))
+/// ```
+///
+/// The profile is optionally smoothed with a Gaussian filter to suppress
+/// residual sample structure and noise, and is normalized to a mean of `1.0`
+/// such that a flat-field corrected image is `I(x) / P(x)`.
+///
+/// # Arguments
+///
+/// * `data`: The input 3D image stack.
+/// * `percentile`: The per pixel percentile in the range `0.0` to `100.0`. If
+///   `None`, then `percentile = 50.0` (*i.e.* the median).
+/// * `sigma`: The standard deviation of the Gaussian smoothing filter in
+///   pixels. If `None`, no smoothing is performed.
+/// * `axis`: The stack axis to aggregate along. If `None`, then `axis = 0`.
+/// * `threads`: The requested number of threads to use for parallel execution.
+///   If `None` or `Some(1)` sequential execution is used. If `Some(0)`, then
+///   the maximum available parallelism is used. Thread counts are clamped to
+///   the systems maximum.
+///
+/// # Returns
+///
+/// * `Ok(Array2<f64>)`: The 2D illumination profile normalized to a mean of
+///   `1.0`.
+/// * `Err(ImgalError)`: If `data` is empty. If `axis >= 3`. If `percentile` is
+///   outside the range `0.0` to `100.0`. If `sigma <= 0.0`. If the mean of the
+///   profile is not positive.
+pub fn estimate_illumination_profile<'a, T, A>(
+    data: A,
+    percentile: Option<f64>,
+    sigma: Option<f64>,
+    axis: Option<usize>,
+    threads: Option<usize>,
+) -> Result<Array2<f64>, ImgalError>
+where
+    A: AsArray<'a, T, Ix3>,
+    T: 'a + AsNumeric,
+{
+    let data: ArrayBase<ViewRepr<&'a T>, Ix3> = data.into();
+    let percentile = percentile.unwrap_or(50.0);
+    if !(0.0..=100.0).contains(&percentile) {
+        return Err(ImgalError::InvalidParameterValueOutsideRange {
+            param_name: "percentile",
+            value: percentile,
+            min: 0.0,
+            max: 100.0,
+        });
+    }
+    if let Some(s) = sigma
+        && s <= 0.0
+    {
+        return Err(ImgalError::InvalidParameterValueOutsideRange {
+            param_name: "sigma",
+            value: s,
+            min: 0.0,
+            max: f64::INFINITY,
+        });
+    }
+    let axis = axis.unwrap_or(0);
+    let mut profile = linear_percentile(&data, percentile, Some(axis), None, threads)?
+        .into_dimensionality::<Ix2>()
+        .expect("Failed to reshape the percentile image into an Array2<f64>.");
+    if let Some(s) = sigma {
+        profile = gaussian_smooth(profile, s);
+    }
+    let mean = profile.mean().unwrap_or(0.0);
+    if mean <= 0.0 || mean.is_nan() {
+        return Err(ImgalError::InvalidGeneric {
+            msg: "Failed to estimate the illumination profile, the profile mean is not positive.",
+        });
+    }
+    profile.mapv_inplace(|v| v / mean);
+    Ok(profile)
+}
+
+/// Smooth a 2D image with a separable Gaussian filter using reflected borders.
+fn gaussian_smooth(data: Array2<f64>, sigma: f64) -> Array2<f64> {
+    let radius = (3.0 * sigma).ceil() as isize;
+    let kernel: Vec<f64> = (-radius..=radius)
+        .map(|i| (-((i * i) as f64) / (2.0 * sigma * sigma)).exp())
+        .collect();
+    let k_sum: f64 = kernel.iter().sum();
+    let kernel: Vec<f64> = kernel.iter().map(|k| k / k_sum).collect();
+    let smooth_axis = |src: &Array2<f64>, ax: usize| -> Array2<f64> {
+        let mut dst = Array2::<f64>::zeros(src.dim());
+        let n = src.len_of(Axis(ax)) as isize;
+        src.lanes(Axis(ax))
+            .into_iter()
+            .zip(dst.lanes_mut(Axis(ax)))
+            .for_each(|(s, mut d)| {
+                d.iter_mut().enumerate().for_each(|(i, v)| {
+                    *v = kernel
+                        .iter()
+                        .enumerate()
+                        .map(|(j, k)| {
+                            let idx = reflect(i as isize + j as isize - radius, n);
+                            k * s[idx]
+                        })
+                        .sum();
+                });
+            });
+        dst
+    };
+    let rows = smooth_axis(&data, 0);
+    smooth_axis(&rows, 1)
+}
+
+/// Reflect an out of bounds index back into the range `0..n`.
+fn reflect(mut i: isize, n: isize) -> usize {
+    if n == 1 {
+        return 0;
+    }
+    let period = 2 * (n - 1);
+    i = i.rem_euclid(period);
+    if i >= n {
+        i = period - i;
+    }
+    i as usize
+}
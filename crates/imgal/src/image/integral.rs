@@ -0,0 +1,46 @@
+use ndarray::{Array, ArrayBase, AsArray, Dimension, ViewRepr};
+
+use crate::prelude::*;
+use crate::statistics::cumsum;
+
+/// Compute the integral image (summed-area table) of an n-dimensional image.
+///
+/// # Description
+///
+/// Computes the n-dimensional integral image, where each pixel holds the sum
+/// of all input pixels with smaller or equal indices along every axis:
+///
+/// ```text
+/// S(x₀, ..., xᴰ) = Σ I(y₀, ..., yᴰ),  yₖ ≤ xₖ
+/// ```
+///
+/// The integral image is computed with `statistics::cumsum` along each axis in
+/// turn. The sum of any hyperrectangle is then the inclusion-exclusion sum of
+/// the `2ᴰ` integral image values at its corners, independent of its size
+/// (*e.g.* for box filters and local statistics, see `filter::uniform_filter`).
+///
+/// # Arguments
+///
+/// * `data`: The input n-dimensional image.
+/// * `threads`: The requested number of threads to use for parallel execution.
+///   If `None` or `Some(1)` sequential execution is used. If `Some(0)`, then
+///   the maximum available parallelism is used. Thread counts are clamped to
+///   the systems maximum.
+///
+/// # Returns
+///
+/// * `Array<f64, D>`: The integral image with the same shape as `data`.
+pub fn integral_image<'a, T, A, D>(data: A, threads: Option<usize>) -> Array<f64, D>
+where
+    A: AsArray<'a, T, D>,
+    D: Dimension,
+    T: 'a + AsNumeric,
+{
+    let data: ArrayBase<ViewRepr<&'a T>, D> = data.into();
+    let mut table = data.mapv(|v| v.to_f64());
+    for ax in 0..table.ndim() {
+        table = cumsum(&table, Some(ax), threads)
+            .expect("Failed to compute the cumulative sum along a valid axis.");
+    }
+    table
+}
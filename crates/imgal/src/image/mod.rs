@@ -1,9 +1,19 @@
 //! Image functions.
 
+mod background;
 mod histogram;
+mod illumination;
+mod integral;
 mod normalization;
 
+pub use background::fit_polynomial_background;
+pub use histogram::BinRule;
 pub use histogram::histogram;
+pub use histogram::histogram_auto;
+pub use histogram::histogram_bin_count;
 pub use histogram::histogram_bin_midpoint;
 pub use histogram::histogram_bin_range;
+pub use illumination::estimate_illumination_profile;
+pub use integral::integral_image;
 pub use normalization::percentile_normalize;
+pub use normalization::zscore_normalize;
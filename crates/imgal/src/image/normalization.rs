@@ -4,7 +4,8 @@ use ndarray::{
 use rayon::prelude::*;
 
 use crate::prelude::*;
-use crate::statistics::linear_percentile;
+use crate::statistics::{Welford, linear_percentiles};
+use crate::validate::{check_axis, check_shapes};
 
 /// Normalize an n-dimensional image using percentile-based minimum and maximum.
 ///
@@ -88,17 +89,11 @@ where
     let epsilon = epsilon.unwrap_or(1e-20);
     match axis {
         Some(ax) => {
-            if ax >= data.ndim() {
-                return Err(ImgalError::InvalidAxis {
-                    axis_idx: ax,
-                    dim_len: data.ndim(),
-                });
-            }
+            check_axis(ax, data.ndim())?;
             let ax = Axis(ax);
             let mm: Vec<(f64, f64)> = data.axis_iter(ax).try_fold(Vec::new(), |mut acc, s| {
-                let pmin = linear_percentile(&s, min, None, None, None)?[0];
-                let pmax = linear_percentile(&s, max, None, None, None)?[0];
-                acc.push((pmin, pmax));
+                let p = linear_percentiles(&s, &[min, max], None, None, None)?;
+                acc.push((p[0], p[1]));
                 Ok(acc)
             })?;
             let mut norm_arr = Array::from_elem(data.dim(), 0.0);
@@ -122,8 +117,8 @@ where
             Ok(norm_arr)
         }
         None => {
-            let pmin = linear_percentile(&data, min, None, None, None)?[0];
-            let pmax = linear_percentile(&data, max, None, None, None)?[0];
+            let p = linear_percentiles(&data, &[min, max], None, None, None)?;
+            let (pmin, pmax) = (p[0], p[1]);
             let denom = pmax - pmin + epsilon;
             let mut norm_arr = Array::from_elem(data.dim(), 0.0);
             let norm_calc = |v: &T, n: &mut f64| {
@@ -139,3 +134,134 @@ where
         }
     }
 }
+
+/// Normalize an n-dimensional image to zero mean and unit standard deviation.
+///
+/// # Description
+///
+/// Performs z-score standardization of an input n-dimensional image:
+///
+/// ```text
+/// y = (x - μ) / (σ + ε)
+/// ```
+///
+/// Where:
+/// - `y` is the normalized output.
+/// - `x` is the input.
+/// - `μ` is the mean.
+/// - `σ` is the population standard deviation.
+/// - `ε` is a small epsilon value to prevent division by zero.
+///
+/// The mean and standard deviation are accumulated with Welford's algorithm
+/// (see `statistics::variance`). If a `mask` is given, only the pixels inside
+/// the mask contribute to `μ` and `σ` (*e.g.* to standardize with respect to
+/// the foreground or a background region), while all pixels are normalized.
+///
+/// # Arguments
+///
+/// * `data`: The input n-dimensional image to normalize.
+/// * `mask`: An optional boolean mask with the same shape as `data`. If
+///   `None`, all pixels are used to compute the mean and standard deviation.
+/// * `axis`: The axis to compute the mean and standard deviation independently
+///   along. Each subview along this axis is normalized with its own mean and
+///   standard deviation (*e.g.* per channel). If `None`, then the input `data`
+///   is flattened.
+/// * `epsilon`: A small positive value to avoid division by zero. If `None`,
+///   then `epsilon = 1e-20`.
+/// * `threads`: The requested number of threads to use for parallel execution.
+///   If `None` or `Some(1)` sequential execution is used. If `Some(0)`, then
+///   the maximum available parallelism is used. Thread counts are clamped to
+///   the systems maximum.
+///
+/// # Returns
+///
+/// * `Ok(Array<f64, D>)`: The z-score normalized n-dimensional image.
+/// * `Err(ImgalError)`: If `data` is empty. If `axis >= data.ndim()`. If the
+///   `mask` shape does not match the `data` shape. If the mask of `data` (or of
+///   a subview along `axis`) has no `true` pixels.
+pub fn zscore_normalize<'a, T, A, D>(
+    data: A,
+    mask: Option<ArrayView<bool, D>>,
+    axis: Option<usize>,
+    epsilon: Option<f64>,
+    threads: Option<usize>,
+) -> Result<Array<f64, D>, ImgalError>
+where
+    A: AsArray<'a, T, D>,
+    D: Dimension + RemoveAxis,
+    T: 'a + AsNumeric,
+{
+    let data: ArrayBase<ViewRepr<&'a T>, D> = data.into();
+    if data.is_empty() {
+        return Err(ImgalError::InvalidParameterEmptyArray { param_name: "data" });
+    }
+    if let Some(msk) = mask.as_ref() {
+        check_shapes("mask", msk.shape(), "data", data.shape())?;
+    }
+    let epsilon = epsilon.unwrap_or(1e-20);
+    let mut norm_arr = Array::from_elem(data.dim(), 0.0);
+    match axis {
+        Some(ax) => {
+            check_axis(ax, data.ndim())?;
+            let ax = Axis(ax);
+            let stats: Vec<(f64, f64)> = data
+                .axis_iter(ax)
+                .enumerate()
+                .map(|(i, s)| masked_mean_std(s, mask.as_ref().map(|m| m.index_axis(ax, i))))
+                .collect::<Result<_, _>>()?;
+            let norm_calc =
+                |i: usize, a: ArrayView<T, D::Smaller>, mut b: ArrayViewMut<f64, D::Smaller>| {
+                    let (mu, sigma) = stats[i];
+                    Zip::from(a)
+                        .and(b.view_mut())
+                        .for_each(|&v, n| *n = (v.to_f64() - mu) / (sigma + epsilon));
+                };
+            par!(threads,
+                seq_exp: data.axis_iter(ax).zip(norm_arr.axis_iter_mut(ax))
+                    .enumerate()
+                    .for_each(|(i, (a, b))| norm_calc(i, a, b)),
+                par_exp: data.axis_iter(ax).zip(norm_arr.axis_iter_mut(ax))
+                    .enumerate()
+                    .par_bridge()
+                    .for_each(|(i, (a, b))| norm_calc(i, a, b)));
+        }
+        None => {
+            let (mu, sigma) = masked_mean_std(data.view(), mask)?;
+            let norm_calc = |v: &T, n: &mut f64| *n = (v.to_f64() - mu) / (sigma + epsilon);
+            par!(threads,
+                seq_exp: Zip::from(&data).and(norm_arr.view_mut())
+                    .for_each(norm_calc),
+                par_exp: Zip::from(&data).and(norm_arr.view_mut())
+                    .par_for_each(norm_calc));
+        }
+    }
+    Ok(norm_arr)
+}
+
+/// Compute the mean and population standard deviation of the (masked) values of
+/// an n-dimensional view with Welford's algorithm.
+fn masked_mean_std<T, E>(
+    data: ArrayView<T, E>,
+    mask: Option<ArrayView<bool, E>>,
+) -> Result<(f64, f64), ImgalError>
+where
+    E: Dimension,
+    T: AsNumeric,
+{
+    let w = match mask {
+        Some(m) => Zip::from(&data)
+            .and(&m)
+            .fold(Welford::default(), |acc, v, &keep| {
+                if keep { acc.push(v.to_f64()) } else { acc }
+            }),
+        None => data
+            .iter()
+            .fold(Welford::default(), |acc, v| acc.push(v.to_f64())),
+    };
+    if w.n == 0.0 {
+        return Err(ImgalError::InvalidGeneric {
+            msg: "Invalid z-score normalization, the mask has no true pixels.",
+        });
+    }
+    Ok((w.mean, w.variance(0).sqrt()))
+}
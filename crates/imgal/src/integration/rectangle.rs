@@ -1,7 +1,7 @@
 use ndarray::{ArrayBase, AsArray, Dimension, ViewRepr};
 
 use crate::prelude::*;
-use crate::statistics::sum;
+use crate::statistics::sum_f64;
 
 /// Integrate a curve with the midpoint rule.
 ///
@@ -34,5 +34,5 @@ where
     T: 'a + AsNumeric,
 {
     let x: ArrayBase<ViewRepr<&'a T>, D> = x.into();
-    delta_x.unwrap_or(1.0) * sum(x, threads).to_f64()
+    delta_x.unwrap_or(1.0) * sum_f64(x, threads)
 }
@@ -0,0 +1,138 @@
+use ndarray::{Array, ArrayBase, AsArray, Dimension, ViewRepr};
+use rayon::prelude::*;
+
+/// The placement of the boundary pixels of labeled objects.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum BoundaryMode {
+    /// The outermost pixels of each object, inside the object.
+    #[default]
+    Inner,
+    /// The pixels just outside of each object, in the background or in a
+    /// touching object.
+    Outer,
+    /// Both the inner and outer boundary pixels, a two pixel wide boundary.
+    Thick,
+}
+
+/// Find the boundaries of the objects in an n-dimensional label image.
+///
+/// # Description
+///
+/// Marks the boundary pixels of the objects in a label image, where the label
+/// `0` is the background. A pixel is on a boundary if one of its `2 × ndim`
+/// axis aligned neighbors has a different label:
+///
+/// ```text
+/// Inner: L(x) ≠ 0 and L(y) ≠ L(x)
+/// Outer: L(y) ≠ 0 and L(y) ≠ L(x)
+/// Thick: L(y) ≠ L(x)
+/// ```
+///
+/// For some neighbor `y` of `x`. Touching objects share their boundary
+/// pixels on both sides of the contact in all modes. Pixels outside of the
+/// image are not neighbors, so objects touching the image border are not
+/// outlined along the border.
+///
+/// # Arguments
+///
+/// * `labels`: The n-dimensional label image.
+/// * `mode`: The placement of the boundary pixels. If `None`, then
+///   `mode = BoundaryMode::Inner`.
+/// * `threads`: The requested number of threads to use for parallel execution.
+///   If `None` or `Some(1)` sequential execution is used. If `Some(0)`, then
+///   the maximum available parallelism is used. Thread counts are clamped to
+///   the systems maximum.
+///
+/// # Returns
+///
+/// * `Array<bool, D>`: The boolean boundary image, where `true` marks the
+///   boundary pixels.
+pub fn boundaries<'a, A, D>(
+    labels: A,
+    mode: Option<BoundaryMode>,
+    threads: Option<usize>,
+) -> Array<bool, D>
+where
+    A: AsArray<'a, u64, D>,
+    D: Dimension,
+{
+    boundary_labels(labels, mode, threads).mapv(|v| v != 0)
+}
+
+/// Find the labeled boundaries of the objects in an n-dimensional label image.
+///
+/// # Description
+///
+/// Marks the boundary pixels of the objects in a label image with the label
+/// of the object they outline, see `boundaries` for the boundary modes. Inner
+/// boundary pixels keep their own label, while outer boundary pixels take the
+/// largest label of their neighbors that differs from their own. The labeled
+/// boundaries can be colored per object (*e.g.* with a lookup table) for
+/// quality control overlays.
+///
+/// # Arguments
+///
+/// * `labels`: The n-dimensional label image.
+/// * `mode`: The placement of the boundary pixels. If `None`, then
+///   `mode = BoundaryMode::Inner`.
+/// * `threads`: The requested number of threads to use for parallel execution.
+///   If `None` or `Some(1)` sequential execution is used. If `Some(0)`, then
+///   the maximum available parallelism is used. Thread counts are clamped to
+///   the systems maximum.
+///
+/// # Returns
+///
+/// * `Array<u64, D>`: The labeled boundary image, where the boundary pixels
+///   have the label of their object and all other pixels are `0`.
+pub fn boundary_labels<'a, A, D>(
+    labels: A,
+    mode: Option<BoundaryMode>,
+    threads: Option<usize>,
+) -> Array<u64, D>
+where
+    A: AsArray<'a, u64, D>,
+    D: Dimension,
+{
+    let labels: ArrayBase<ViewRepr<&'a u64>, D> = labels.into();
+    let mode = mode.unwrap_or_default();
+    let shape = labels.shape().to_vec();
+    let ndim = shape.len();
+    let strides: Vec<usize> = (0..ndim)
+        .map(|ax| shape[ax + 1..].iter().product())
+        .collect();
+    let src = labels.as_standard_layout();
+    let src = src.as_slice().unwrap();
+    let boundary_label = |i: usize| -> u64 {
+        let own = src[i];
+        // the largest differing neighbor label, and if any neighbor differs
+        let mut other = 0;
+        let mut differs = false;
+        for ax in 0..ndim {
+            let coord = (i / strides[ax]) % shape[ax];
+            let below = (coord > 0).then(|| src[i - strides[ax]]);
+            let above = (coord + 1 < shape[ax]).then(|| src[i + strides[ax]]);
+            for n in below.into_iter().chain(above) {
+                if n != own {
+                    differs = true;
+                    other = other.max(n);
+                }
+            }
+        }
+        match mode {
+            BoundaryMode::Inner if differs && own != 0 => own,
+            BoundaryMode::Outer => other,
+            BoundaryMode::Thick if differs => {
+                if own != 0 {
+                    own
+                } else {
+                    other
+                }
+            }
+            _ => 0,
+        }
+    };
+    let output: Vec<u64> = par!(threads,
+        seq_exp: (0..src.len()).map(boundary_label).collect(),
+        par_exp: (0..src.len()).into_par_iter().map(boundary_label).collect());
+    Array::from_shape_vec(labels.raw_dim(), output).unwrap()
+}
@@ -0,0 +1,11 @@
+//! Label image functions.
+//!
+//! This module provides functions operating on *n*-dimensional label images,
+//! where each object is a connected set of pixels sharing a positive integer
+//! label and `0` is the background.
+
+mod boundaries;
+
+pub use boundaries::BoundaryMode;
+pub use boundaries::boundaries;
+pub use boundaries::boundary_labels;
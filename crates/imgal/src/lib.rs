@@ -12,28 +12,51 @@
 //! ## Crate Status
 //!
 //! This crate is still under active development and it's API is not stable.
+//!
+//! ## Crate Features
+//!
+//! - `half`: Implements `AsNumeric` for the half-precision `half::f16` and
+//!   `half::bf16` types, so that half-precision arrays (*e.g.* exported
+//!   feature maps of machine learning models) can be used directly without an
+//!   intermediate cast copy.
 
 #[macro_use]
 mod macros;
+pub mod classify;
 pub mod colocalization;
 pub mod constants;
 pub mod copy;
 pub mod distribution;
 mod error;
+pub mod feature;
 pub mod filter;
+pub mod fit;
+pub mod flim;
 pub mod image;
 pub mod integration;
 pub mod kernel;
+pub mod label;
+mod linalg;
+pub mod measure;
+pub mod metrics;
+pub mod morphology;
+pub mod optimize;
 pub mod overlay;
 pub mod parameter;
 pub mod phasor;
 pub mod prelude;
+pub mod registration;
+pub mod restoration;
+pub mod signal;
 mod simd_hint;
 pub mod simulation;
 pub mod spatial;
 pub mod statistics;
 pub mod threshold;
+pub mod timeseries;
+pub mod tracking;
 mod traits;
 pub mod transform;
+mod validate;
 pub use error::ImgalError;
-pub use traits::numeric::AsNumeric;
+pub use traits::numeric::{AsAccumulator, AsNumeric};
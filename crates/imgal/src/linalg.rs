@@ -0,0 +1,248 @@
+//! Linear algebra helpers.
+//!
+//! This module provides small dense and banded linear algebra routines (*e.g.*
+//! solving normal equations) used internally by imgal's fitting functions.
+
+/// Solve a dense square linear system.
+///
+/// # Description
+///
+/// Solves the linear system `A × x = b` using Gaussian elimination with partial
+/// pivoting. The matrix `A` is expected in row-major order.
+///
+/// # Arguments
+///
+/// * `a`: The row-major `n × n` coefficient matrix.
+/// * `b`: The right-hand side vector of length `n`.
+///
+/// # Returns
+///
+/// * `Some(Vec<f64>)`: The solution vector `x`.
+/// * `None`: If the system is singular (or numerically close to singular).
+pub fn solve_dense(a: &[f64], b: &[f64]) -> Option<Vec<f64>> {
+    let n = b.len();
+    let mut m = a.to_vec();
+    let mut x = b.to_vec();
+    let scale = m.iter().fold(0.0_f64, |acc, v| acc.max(v.abs()));
+    if scale == 0.0 || !scale.is_finite() {
+        return None;
+    }
+    let tol = scale * 1e-14;
+    for k in 0..n {
+        // find the pivot row for column "k" and swap it into place
+        let p = (k..n)
+            .max_by(|&i, &j| m[i * n + k].abs().total_cmp(&m[j * n + k].abs()))
+            .unwrap();
+        if m[p * n + k].abs() <= tol {
+            return None;
+        }
+        if p != k {
+            (0..n).for_each(|c| m.swap(p * n + c, k * n + c));
+            x.swap(p, k);
+        }
+        for i in (k + 1)..n {
+            let f = m[i * n + k] / m[k * n + k];
+            if f != 0.0 {
+                for c in k..n {
+                    m[i * n + c] -= f * m[k * n + c];
+                }
+                x[i] -= f * x[k];
+            }
+        }
+    }
+    for k in (0..n).rev() {
+        let s = ((k + 1)..n).fold(x[k], |acc, c| acc - m[k * n + c] * x[c]);
+        x[k] = s / m[k * n + k];
+    }
+    Some(x)
+}
+
+/// Solve a damped least squares step with Levenberg-Marquardt.
+///
+/// # Description
+///
+/// Computes the Levenberg-Marquardt parameter update `δ` by solving the damped
+/// normal equations:
+///
+/// ```text
+/// (JᵀJ + λ × diag(JᵀJ)) × δ = -Jᵀr
+/// ```
+///
+/// # Arguments
+///
+/// * `jac`: The row-major `m × n` Jacobian matrix.
+/// * `res`: The residual vector of length `m`.
+/// * `n`: The number of parameters.
+/// * `lambda`: The damping factor.
+///
+/// # Returns
+///
+/// * `Some(Vec<f64>)`: The parameter update `δ`.
+/// * `None`: If the damped normal equations are singular.
+pub fn lm_step(jac: &[f64], res: &[f64], n: usize, lambda: f64) -> Option<Vec<f64>> {
+    let mut jtj = vec![0.0; n * n];
+    let mut jtr = vec![0.0; n];
+    res.iter().enumerate().for_each(|(i, r)| {
+        let row = &jac[i * n..(i + 1) * n];
+        for a in 0..n {
+            jtr[a] -= row[a] * r;
+            for b in a..n {
+                jtj[a * n + b] += row[a] * row[b];
+            }
+        }
+    });
+    for a in 0..n {
+        for b in 0..a {
+            jtj[a * n + b] = jtj[b * n + a];
+        }
+        let d = jtj[a * n + a];
+        jtj[a * n + a] = d + lambda * d.max(1e-12);
+    }
+    solve_dense(&jtj, &jtr)
+}
+
+/// Compute the real roots of a monic cubic polynomial.
+///
+/// # Description
+///
+/// Computes the real roots of `x³ + c2 × x² + c1 × x + c0 = 0` using the
+/// trigonometric (three real roots) or Cardano (one real root) solution.
+///
+/// # Returns
+///
+/// * `Vec<f64>`: The real roots of the cubic polynomial.
+pub fn cubic_real_roots(c2: f64, c1: f64, c0: f64) -> Vec<f64> {
+    // depress the cubic with x = t - c2 / 3 to get t³ + p × t + q = 0
+    let shift = c2 / 3.0;
+    let p = c1 - c2 * c2 / 3.0;
+    let q = 2.0 * c2 * c2 * c2 / 27.0 - c2 * c1 / 3.0 + c0;
+    let disc = (q / 2.0).powi(2) + (p / 3.0).powi(3);
+    if p.abs() < 1e-300 {
+        return vec![(-q).cbrt() - shift];
+    }
+    if disc > 0.0 {
+        let sd = disc.sqrt();
+        let u = (-q / 2.0 + sd).cbrt();
+        let v = (-q / 2.0 - sd).cbrt();
+        vec![u + v - shift]
+    } else {
+        let r = 2.0 * (-p / 3.0).sqrt();
+        let arg = (3.0 * q / (p * r)).clamp(-1.0, 1.0);
+        let phi = arg.acos() / 3.0;
+        (0..3)
+            .map(|k| r * (phi - 2.0 * std::f64::consts::PI * k as f64 / 3.0).cos() - shift)
+            .collect()
+    }
+}
+
+/// Solve a symmetric positive definite banded linear system.
+///
+/// # Description
+///
+/// Solves the linear system `A × x = b` with a banded Cholesky decomposition,
+/// where `A` is a symmetric positive definite matrix with bandwidth `bw`. Only
+/// the lower band of `A` is used, stored row by row such that
+/// `band[i × (bw + 1) + (i - j)] = A[i][j]` for `i - bw <= j <= i`.
+///
+/// # Arguments
+///
+/// * `band`: The lower band of the coefficient matrix.
+/// * `bw`: The bandwidth of the coefficient matrix.
+/// * `b`: The right-hand side vector of length `n`.
+///
+/// # Returns
+///
+/// * `Some(Vec<f64>)`: The solution vector `x`.
+/// * `None`: If the matrix is not positive definite.
+pub fn solve_banded_spd(band: &[f64], bw: usize, b: &[f64]) -> Option<Vec<f64>> {
+    let n = b.len();
+    let w = bw + 1;
+    let mut l = vec![0.0; n * w];
+    for i in 0..n {
+        let lo = i.saturating_sub(bw);
+        for j in lo..=i {
+            let s = (lo.max(j.saturating_sub(bw))..j).fold(band[i * w + (i - j)], |acc, k| {
+                acc - l[i * w + (i - k)] * l[j * w + (j - k)]
+            });
+            if i == j {
+                if s <= 0.0 || !s.is_finite() {
+                    return None;
+                }
+                l[i * w] = s.sqrt();
+            } else {
+                l[i * w + (i - j)] = s / l[j * w];
+            }
+        }
+    }
+    // forward substitution with L and backward substitution with Lᵀ
+    let mut y = vec![0.0; n];
+    for i in 0..n {
+        let lo = i.saturating_sub(bw);
+        let s = (lo..i).fold(b[i], |acc, k| acc - l[i * w + (i - k)] * y[k]);
+        y[i] = s / l[i * w];
+    }
+    let mut x = vec![0.0; n];
+    for i in (0..n).rev() {
+        let hi = (i + bw).min(n - 1);
+        let s = ((i + 1)..=hi).fold(y[i], |acc, k| acc - l[k * w + (k - i)] * x[k]);
+        x[i] = s / l[i * w];
+    }
+    Some(x)
+}
+
+/// Compute the eigenvalues of a symmetric matrix.
+///
+/// # Description
+///
+/// Computes the eigenvalues of a real symmetric matrix with the cyclic Jacobi
+/// method, which rotates away the off-diagonal elements until they are
+/// negligible. Suited to the small matrices (*e.g.* covariance matrices of
+/// point coordinates) used internally.
+///
+/// # Arguments
+///
+/// * `a`: The row-major `n × n` symmetric matrix.
+/// * `n`: The order of the matrix.
+///
+/// # Returns
+///
+/// * `Vec<f64>`: The eigenvalues in descending order.
+pub fn symmetric_eigenvalues(a: &[f64], n: usize) -> Vec<f64> {
+    let mut m = a.to_vec();
+    for _ in 0..100 {
+        let off: f64 = (0..n)
+            .flat_map(|i| (0..n).filter(move |&j| j != i).map(move |j| (i, j)))
+            .map(|(i, j)| m[i * n + j] * m[i * n + j])
+            .sum();
+        let diag: f64 = (0..n).map(|i| m[i * n + i] * m[i * n + i]).sum();
+        if off <= 1e-30 * diag.max(f64::MIN_POSITIVE) {
+            break;
+        }
+        for p in 0..n {
+            for q in p + 1..n {
+                let apq = m[p * n + q];
+                if apq == 0.0 {
+                    continue;
+                }
+                // the rotation angle that zeroes m[p][q]
+                let theta = (m[q * n + q] - m[p * n + p]) / (2.0 * apq);
+                let t = theta.signum() / (theta.abs() + (theta * theta + 1.0).sqrt());
+                let c = 1.0 / (t * t + 1.0).sqrt();
+                let s = t * c;
+                for k in 0..n {
+                    let (mkp, mkq) = (m[k * n + p], m[k * n + q]);
+                    m[k * n + p] = c * mkp - s * mkq;
+                    m[k * n + q] = s * mkp + c * mkq;
+                }
+                for k in 0..n {
+                    let (mpk, mqk) = (m[p * n + k], m[q * n + k]);
+                    m[p * n + k] = c * mpk - s * mqk;
+                    m[q * n + k] = s * mpk + c * mqk;
+                }
+            }
+        }
+    }
+    let mut eig: Vec<f64> = (0..n).map(|i| m[i * n + i]).collect();
+    eig.sort_by(|a, b| b.total_cmp(a));
+    eig
+}
@@ -0,0 +1,106 @@
+use ndarray::{Array2, ArrayBase, ArrayView2, AsArray, Ix2, ViewRepr, Zip};
+
+use crate::filter::clipped_uniform_filter;
+use crate::kernel::neighborhood::circle_kernel;
+use crate::morphology::{close, open};
+use crate::prelude::*;
+use crate::threshold::global::otsu_value;
+
+/// Estimate the confluence of a brightfield or phase-contrast image.
+///
+/// # Description
+///
+/// Estimates the confluence (*i.e.* the fraction of the image area covered by
+/// cells or colonies) of a 2D brightfield or phase-contrast image. Cells are
+/// textured while the empty background is flat, so instead of the intensity
+/// the local standard deviation within a square window of side length
+/// `2 × radius + 1` is thresholded:
+///
+/// ```text
+/// σ(x) = √(E[I²] - E[I]²)
+/// ```
+///
+/// The texture mask is then cleaned up with a morphological opening (removing
+/// isolated specks) followed by a closing (filling small holes) using a
+/// circular structuring element. The confluence is the fraction of `true`
+/// pixels in the cleaned mask.
+///
+/// # Arguments
+///
+/// * `data`: The input 2D brightfield or phase-contrast image.
+/// * `radius`: The radius of the square local variance window. If `None`, then
+///   `radius = 3`.
+/// * `threshold`: The local standard deviation threshold. If `None`, then the
+///   threshold is computed from the local standard deviation image with Otsu's
+///   method.
+/// * `cleanup_radius`: The radius of the circular structuring element used for
+///   the morphological cleanup. If `None`, then `cleanup_radius = 2`. If
+///   `Some(0)`, no cleanup is performed.
+/// * `threads`: The requested number of threads to use for parallel execution.
+///   If `None` or `Some(1)` sequential execution is used. If `Some(0)`, then
+///   the maximum available parallelism is used. Thread counts are clamped to
+///   the systems maximum.
+///
+/// # Returns
+///
+/// * `Ok((f64, Array2<bool>))`: A tuple containing the confluence in the range
+///   `[0, 1]` and the covered area mask, *i.e.* `(confluence, mask)`.
+/// * `Err(ImgalError)`: If `data` is empty. If `radius == 0`.
+pub fn confluence<'a, T, A>(
+    data: A,
+    radius: Option<usize>,
+    threshold: Option<f64>,
+    cleanup_radius: Option<usize>,
+    threads: Option<usize>,
+) -> Result<(f64, Array2<bool>), ImgalError>
+where
+    A: AsArray<'a, T, Ix2>,
+    T: 'a + AsNumeric,
+{
+    let data: ArrayBase<ViewRepr<&'a T>, Ix2> = data.into();
+    if data.is_empty() {
+        return Err(ImgalError::InvalidParameterEmptyArray { param_name: "data" });
+    }
+    let radius = radius.unwrap_or(3);
+    if radius == 0 {
+        return Err(ImgalError::InvalidParameterValueLess {
+            param_name: "radius",
+            value: 1,
+        });
+    }
+    let std_arr = local_std(&data, radius, threads)?;
+    let threshold = match threshold {
+        Some(t) => t,
+        None => otsu_value(&std_arr, None, threads)?,
+    };
+    let mut mask = std_arr.mapv(|s| s > threshold);
+    let cleanup_radius = cleanup_radius.unwrap_or(2);
+    if cleanup_radius > 0 {
+        let kernel = circle_kernel(cleanup_radius)?;
+        // opening followed by closing
+        mask = open(&mask, kernel.view(), threads)?;
+        mask = close(&mask, kernel.view(), threads)?;
+    }
+    let covered = mask.iter().filter(|&&m| m).count();
+    Ok((covered as f64 / mask.len() as f64, mask))
+}
+
+/// Compute the local standard deviation within a square window from the
+/// clipped window means of the intensity and squared intensity. Windows are
+/// truncated at the image borders.
+fn local_std<T>(
+    data: &ArrayView2<T>,
+    radius: usize,
+    threads: Option<usize>,
+) -> Result<Array2<f64>, ImgalError>
+where
+    T: AsNumeric,
+{
+    let sq = data.mapv(|v| v.to_f64() * v.to_f64());
+    let mut std_arr = clipped_uniform_filter(data, &[radius, radius], threads)?;
+    let mean_sq = clipped_uniform_filter(&sq, &[radius, radius], threads)?;
+    Zip::from(&mut std_arr)
+        .and(&mean_sq)
+        .for_each(|s, &m2| *s = (m2 - *s * *s).max(0.0).sqrt());
+    Ok(std_arr)
+}
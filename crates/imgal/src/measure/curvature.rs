@@ -0,0 +1,111 @@
+use ndarray::{Array1, ArrayBase, AsArray, Ix2, ViewRepr};
+
+use crate::prelude::*;
+
+/// Compute the pointwise curvature, total curvature and tortuosity of a curve.
+///
+/// # Description
+///
+/// Computes curvature measurements along an ordered *n*-dimensional point list
+/// (*e.g.* a polyline or a resampled spline from `fit_spline`). The pointwise
+/// curvature `κᵢ` at each interior point is the Menger curvature, the inverse
+/// radius of the circle passing through the point and its two neighbors:
+///
+/// ```text
+/// κᵢ = 4 × Aᵢ / (|pᵢ - pᵢ₋₁| × |pᵢ₊₁ - pᵢ| × |pᵢ₊₁ - pᵢ₋₁|)
+/// ```
+///
+/// Where `Aᵢ` is the area of the triangle spanned by the three points. The
+/// total curvature is the sum of the turning angles between consecutive
+/// segments, *e.g.* `2π` for a convex closed curve. The tortuosity is the
+/// ratio of the curve's arclength to the Euclidean distance between its end
+/// points (*i.e.* the arc-chord ratio), where `1.0` is a straight line.
+///
+/// # Arguments
+///
+/// * `points`: The ordered point list with shape `(p, D)`, where `p` is the
+///   number of points and `D` is the number of dimensions.
+/// * `closed`: If `true` the point list is treated as a closed curve and the
+///   first and last points are considered neighbors. If `false` the curve is
+///   open and the curvature at both end points is set to `0.0`.
+///
+/// # Returns
+///
+/// * `Ok((Array1<f64>, f64, f64))`: A tuple containing the pointwise
+///   curvature, the total curvature (in radians) and the tortuosity, *i.e.*
+///   `(curvature, total_curvature, tortuosity)`. The tortuosity of closed
+///   curves (or open curves with coincident end points) is `f64::INFINITY`.
+/// * `Err(ImgalError)`: If `p < 3`. If `D == 0`.
+pub fn curvature<'a, T, A>(points: A, closed: bool) -> Result<(Array1<f64>, f64, f64), ImgalError>
+where
+    A: AsArray<'a, T, Ix2>,
+    T: 'a + AsNumeric,
+{
+    let points: ArrayBase<ViewRepr<&'a T>, Ix2> = points.into();
+    let (p, d) = points.dim();
+    if p < 3 {
+        return Err(ImgalError::InvalidArrayLengthMinimum {
+            arr_name: "points",
+            arr_len: p,
+            min_len: 3,
+        });
+    }
+    if d == 0 {
+        return Err(ImgalError::InvalidAxisLengthLess {
+            arr_name: "points",
+            axis_idx: 1,
+            value: 1,
+        });
+    }
+    let point = |i: usize| -> Vec<f64> { points.row(i).iter().map(|v| v.to_f64()).collect() };
+    let sub = |a: &[f64], b: &[f64]| -> Vec<f64> { a.iter().zip(b).map(|(x, y)| x - y).collect() };
+    let dot = |a: &[f64], b: &[f64]| -> f64 { a.iter().zip(b).map(|(x, y)| x * y).sum() };
+    let mut kappa = Array1::<f64>::zeros(p);
+    let mut total_curvature = 0.0;
+    let interior: Vec<usize> = if closed {
+        (0..p).collect()
+    } else {
+        (1..p - 1).collect()
+    };
+    interior.into_iter().for_each(|i| {
+        let prev = point((i + p - 1) % p);
+        let cur = point(i);
+        let next = point((i + 1) % p);
+        let u = sub(&cur, &prev);
+        let v = sub(&next, &cur);
+        let uu = dot(&u, &u);
+        let vv = dot(&v, &v);
+        let uv = dot(&u, &v);
+        if uu == 0.0 || vv == 0.0 {
+            return;
+        }
+        // the triangle area follows from Lagrange's identity which holds for
+        // any number of dimensions, |u × v|² = |u|²|v|² - (u · v)²
+        let cross = (uu * vv - uv * uv).max(0.0).sqrt();
+        let w = sub(&next, &prev);
+        let ww = dot(&w, &w);
+        if ww > 0.0 {
+            kappa[i] = 2.0 * cross / (uu * vv * ww).sqrt();
+        }
+        total_curvature += cross.atan2(uv);
+    });
+    let n_seg = if closed { p } else { p - 1 };
+    let arclength: f64 = (0..n_seg)
+        .map(|i| {
+            let s = sub(&point((i + 1) % p), &point(i));
+            dot(&s, &s).sqrt()
+        })
+        .sum();
+    let chord = if closed {
+        0.0
+    } else {
+        let s = sub(&point(p - 1), &point(0));
+        dot(&s, &s).sqrt()
+    };
+    let tortuosity = if chord > 0.0 {
+        arclength / chord
+    } else {
+        f64::INFINITY
+    };
+    Ok((kappa, total_curvature, tortuosity))
+}
@@ -0,0 +1,433 @@
+use std::f64::consts::{FRAC_PI_2, PI};
+
+use ndarray::{Array1, ArrayBase, ArrayView2, AsArray, Ix2, ViewRepr};
+
+use crate::linalg::{cubic_real_roots, lm_step, solve_dense};
+use crate::prelude::*;
+
+/// The ellipse parameters output of `fit_ellipse`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EllipseFit {
+    /// The ellipse center.
+    pub center: Array1<f64>,
+    /// The semi-major axis length.
+    pub semi_major: f64,
+    /// The semi-minor axis length.
+    pub semi_minor: f64,
+    /// The angle (in radians) of the semi-major axis measured from axis `0`
+    /// towards axis `1` of the input points, in the range `(-π/2, π/2]`.
+    pub orientation: f64,
+}
+
+/// Fit a circle to a 2D point set.
+///
+/// # Description
+///
+/// Fits a circle to a set of 2D points (*e.g.* contour or label boundary
+/// points). An initial estimate is computed with the algebraic Kåsa fit, which
+/// minimizes:
+///
+/// ```text
+/// Σ (xᵢ² + yᵢ² + D × xᵢ + E × yᵢ + F)²
+/// ```
+///
+/// The algebraic estimate is then refined geometrically with Gauss-Newton
+/// iterations that minimize the orthogonal distance of each point to the
+/// circle:
+///
+/// ```text
+/// Σ (√((xᵢ - cx)² + (yᵢ - cy)²) - r)²
+/// ```
+///
+/// # Arguments
+///
+/// * `points`: The 2D point set with shape `(p, 2)`.
+/// * `max_iter`: The maximum number of geometric refinement iterations. If
+///   `None`, then `max_iter = 100`. If `Some(0)`, the algebraic fit is
+///   returned.
+///
+/// # Returns
+///
+/// * `Ok((Array1<f64>, f64))`: A tuple containing the circle center and
+///   radius, *i.e.* `(center, radius)`.
+/// * `Err(ImgalError)`: If axis 1 of `points` is not length `2`. If
+///   `points.dim().0 < 3`. If the points are degenerate (*e.g.* collinear).
+pub fn fit_circle<'a, T, A>(
+    points: A,
+    max_iter: Option<usize>,
+) -> Result<(Array1<f64>, f64), ImgalError>
+where
+    A: AsArray<'a, T, Ix2>,
+    T: 'a + AsNumeric,
+{
+    let points: ArrayBase<ViewRepr<&'a T>, Ix2> = points.into();
+    validate_points(&points, 3)?;
+    let max_iter = max_iter.unwrap_or(100);
+    let (xs, ys, mx, my, scale) = normalize_points(points);
+    // algebraic (Kåsa) fit of the normalized points
+    let mut ata = [0.0; 9];
+    let mut atb = [0.0; 3];
+    xs.iter().zip(ys.iter()).for_each(|(&x, &y)| {
+        let row = [x, y, 1.0];
+        let z = -(x * x + y * y);
+        for i in 0..3 {
+            atb[i] += row[i] * z;
+            for j in 0..3 {
+                ata[i * 3 + j] += row[i] * row[j];
+            }
+        }
+    });
+    let sol = solve_dense(&ata, &atb).ok_or(ImgalError::InvalidGeneric {
+        msg: "Degenerate point set, a circle can not be fit to collinear points.",
+    })?;
+    let mut cx = -sol[0] / 2.0;
+    let mut cy = -sol[1] / 2.0;
+    let mut r = (cx * cx + cy * cy - sol[2]).max(0.0).sqrt();
+    // geometric refinement with Gauss-Newton
+    for _ in 0..max_iter {
+        let mut jtj = [0.0; 9];
+        let mut jtr = [0.0; 3];
+        xs.iter().zip(ys.iter()).for_each(|(&x, &y)| {
+            let dx = x - cx;
+            let dy = y - cy;
+            let d = (dx * dx + dy * dy).sqrt().max(f64::EPSILON);
+            let res = d - r;
+            let jac = [-dx / d, -dy / d, -1.0];
+            for i in 0..3 {
+                jtr[i] -= jac[i] * res;
+                for j in 0..3 {
+                    jtj[i * 3 + j] += jac[i] * jac[j];
+                }
+            }
+        });
+        let delta = match solve_dense(&jtj, &jtr) {
+            Some(d) => d,
+            None => break,
+        };
+        cx += delta[0];
+        cy += delta[1];
+        r += delta[2];
+        if delta.iter().map(|d| d * d).sum::<f64>().sqrt() < 1e-12 {
+            break;
+        }
+    }
+    let center = Array1::from_vec(vec![cx * scale + mx, cy * scale + my]);
+    Ok((center, r.abs() * scale))
+}
+
+/// Fit an ellipse to a 2D point set.
+///
+/// # Description
+///
+/// Fits an ellipse to a set of 2D points (*e.g.* contour or label boundary
+/// points). An initial estimate is computed with the numerically stable direct
+/// least squares fit by Halíř and Flusser, which minimizes the algebraic
+/// distance of the points to the conic:
+///
+/// ```text
+/// A × x² + B × x × y + C × y² + D × x + E × y + F = 0
+/// ```
+///
+/// Subject to the ellipse constraint `4AC - B² = 1`. The algebraic estimate is
+/// then refined geometrically with Levenberg-Marquardt iterations that
+/// minimize the radial distance of each point to the ellipse.
+///
+/// # Arguments
+///
+/// * `points`: The 2D point set with shape `(p, 2)`.
+/// * `max_iter`: The maximum number of geometric refinement iterations. If
+///   `None`, then `max_iter = 100`. If `Some(0)`, the algebraic fit is
+///   returned.
+///
+/// # Returns
+///
+/// * `Ok(EllipseFit)`: The ellipse center, the semi-major and semi-minor axis
+///   lengths and the orientation of the semi-major axis.
+/// * `Err(ImgalError)`: If axis 1 of `points` is not length `2`. If
+///   `points.dim().0 < 5`. If the points are degenerate and no ellipse can be
+///   fit.
+pub fn fit_ellipse<'a, T, A>(points: A, max_iter: Option<usize>) -> Result<EllipseFit, ImgalError>
+where
+    A: AsArray<'a, T, Ix2>,
+    T: 'a + AsNumeric,
+{
+    let points: ArrayBase<ViewRepr<&'a T>, Ix2> = points.into();
+    validate_points(&points, 5)?;
+    let max_iter = max_iter.unwrap_or(100);
+    let degenerate_err = ImgalError::InvalidGeneric {
+        msg: "Degenerate point set, an ellipse can not be fit to the given points.",
+    };
+    let (xs, ys, mx, my, scale) = normalize_points(points);
+    let conic = direct_ellipse_fit(&xs, &ys).ok_or(degenerate_err.clone())?;
+    let mut params = conic_to_parameters(&conic).ok_or(degenerate_err)?;
+    // geometric refinement with Levenberg-Marquardt, the parameters are
+    // (cx, cy, a, b, θ)
+    let cost = |p: &[f64; 5]| -> f64 {
+        xs.iter()
+            .zip(ys.iter())
+            .map(|(&x, &y)| ellipse_residual(p, x, y).powi(2))
+            .sum()
+    };
+    let mut lambda = 1e-3;
+    let mut cur_cost = cost(&params);
+    let n = xs.len();
+    let h = 1e-7;
+    for _ in 0..max_iter {
+        let mut jac = vec![0.0; n * 5];
+        let mut res = vec![0.0; n];
+        xs.iter()
+            .zip(ys.iter())
+            .enumerate()
+            .for_each(|(i, (&x, &y))| {
+                res[i] = ellipse_residual(&params, x, y);
+                for k in 0..5 {
+                    let mut p_hi = params;
+                    let mut p_lo = params;
+                    p_hi[k] += h;
+                    p_lo[k] -= h;
+                    jac[i * 5 + k] =
+                        (ellipse_residual(&p_hi, x, y) - ellipse_residual(&p_lo, x, y)) / (2.0 * h);
+                }
+            });
+        let mut improved = false;
+        while lambda < 1e10 {
+            let delta = match lm_step(&jac, &res, 5, lambda) {
+                Some(d) => d,
+                None => break,
+            };
+            let mut trial = params;
+            trial
+                .iter_mut()
+                .zip(delta.iter())
+                .for_each(|(p, d)| *p += d);
+            let trial_cost = cost(&trial);
+            if trial_cost.is_finite() && trial_cost <= cur_cost {
+                let step = delta.iter().map(|d| d * d).sum::<f64>().sqrt();
+                params = trial;
+                cur_cost = trial_cost;
+                lambda = (lambda / 10.0).max(1e-12);
+                improved = step > 1e-12;
+                break;
+            }
+            lambda *= 10.0;
+        }
+        if !improved {
+            break;
+        }
+    }
+    let [cx, cy, mut a, mut b, mut theta] = params;
+    a = a.abs();
+    b = b.abs();
+    if b > a {
+        std::mem::swap(&mut a, &mut b);
+        theta += FRAC_PI_2;
+    }
+    theta = theta.rem_euclid(PI);
+    if theta > FRAC_PI_2 {
+        theta -= PI;
+    }
+    let center = Array1::from_vec(vec![cx * scale + mx, cy * scale + my]);
+    Ok(EllipseFit {
+        center,
+        semi_major: a * scale,
+        semi_minor: b * scale,
+        orientation: theta,
+    })
+}
+
+/// Convert general conic coefficients into ellipse parameters.
+///
+/// Returns `(cx, cy, a, b, θ)` where `a` and `b` are the semi-major and
+/// semi-minor axis lengths and `θ` is the semi-major axis orientation. Returns
+/// `None` if the conic is not an ellipse.
+fn conic_to_parameters(conic: &[f64; 6]) -> Option<[f64; 5]> {
+    let [a, b, c, d, e, f] = *conic;
+    let den = b * b - 4.0 * a * c;
+    if den >= 0.0 {
+        return None;
+    }
+    let cx = (2.0 * c * d - b * e) / den;
+    let cy = (2.0 * a * e - b * d) / den;
+    // value of the conic at the center, flip signs so the quadratic form is
+    // positive definite and the center value is negative
+    let mut f0 = a * cx * cx + b * cx * cy + c * cy * cy + d * cx + e * cy + f;
+    let (mut qa, mut qb, mut qc) = (a, b, c);
+    if qa + qc < 0.0 {
+        qa = -qa;
+        qb = -qb;
+        qc = -qc;
+        f0 = -f0;
+    }
+    if f0 >= 0.0 {
+        return None;
+    }
+    let mean = (qa + qc) / 2.0;
+    let diff = (((qa - qc) / 2.0).powi(2) + (qb / 2.0).powi(2)).sqrt();
+    let l_max = mean + diff;
+    let l_min = mean - diff;
+    if l_min <= 0.0 {
+        return None;
+    }
+    // the eigenvector of the largest eigenvalue points along the minor axis
+    let theta = 0.5 * qb.atan2(qa - qc) + FRAC_PI_2;
+    Some([cx, cy, (-f0 / l_min).sqrt(), (-f0 / l_max).sqrt(), theta])
+}
+
+/// Compute the direct least squares ellipse fit (Halíř and Flusser).
+///
+/// Returns the conic coefficients `[A, B, C, D, E, F]` or `None` if no
+/// elliptical solution exists.
+fn direct_ellipse_fit(xs: &[f64], ys: &[f64]) -> Option<[f64; 6]> {
+    // scatter matrices of the quadratic (d1) and linear (d2) design matrices
+    let mut s1 = [0.0; 9];
+    let mut s2 = [0.0; 9];
+    let mut s3 = [0.0; 9];
+    xs.iter().zip(ys.iter()).for_each(|(&x, &y)| {
+        let d1 = [x * x, x * y, y * y];
+        let d2 = [x, y, 1.0];
+        for i in 0..3 {
+            for j in 0..3 {
+                s1[i * 3 + j] += d1[i] * d1[j];
+                s2[i * 3 + j] += d1[i] * d2[j];
+                s3[i * 3 + j] += d2[i] * d2[j];
+            }
+        }
+    });
+    // T = -S3⁻¹ × S2ᵀ, solved column by column
+    let mut t = [0.0; 9];
+    for j in 0..3 {
+        let rhs = [-s2[j * 3], -s2[j * 3 + 1], -s2[j * 3 + 2]];
+        let col = solve_dense(&s3, &rhs)?;
+        for i in 0..3 {
+            t[i * 3 + j] = col[i];
+        }
+    }
+    // M = S1 + S2 × T, then premultiply with the inverse constraint matrix
+    let mut m = s1;
+    for i in 0..3 {
+        for j in 0..3 {
+            m[i * 3 + j] += (0..3).map(|k| s2[i * 3 + k] * t[k * 3 + j]).sum::<f64>();
+        }
+    }
+    let m = [
+        m[6] / 2.0,
+        m[7] / 2.0,
+        m[8] / 2.0,
+        -m[3],
+        -m[4],
+        -m[5],
+        m[0] / 2.0,
+        m[1] / 2.0,
+        m[2] / 2.0,
+    ];
+    // solve the 3 x 3 eigenproblem via the characteristic polynomial and keep
+    // the eigenvector that satisfies the ellipse constraint
+    let tr = m[0] + m[4] + m[8];
+    let minors =
+        (m[0] * m[4] - m[1] * m[3]) + (m[0] * m[8] - m[2] * m[6]) + (m[4] * m[8] - m[5] * m[7]);
+    let det = m[0] * (m[4] * m[8] - m[5] * m[7]) - m[1] * (m[3] * m[8] - m[5] * m[6])
+        + m[2] * (m[3] * m[7] - m[4] * m[6]);
+    let mut best: Option<([f64; 3], f64)> = None;
+    for lambda in cubic_real_roots(-tr, minors, -det) {
+        let rows = [
+            [m[0] - lambda, m[1], m[2]],
+            [m[3], m[4] - lambda, m[5]],
+            [m[6], m[7], m[8] - lambda],
+        ];
+        let cross = |u: &[f64; 3], v: &[f64; 3]| {
+            [
+                u[1] * v[2] - u[2] * v[1],
+                u[2] * v[0] - u[0] * v[2],
+                u[0] * v[1] - u[1] * v[0],
+            ]
+        };
+        let norm = |v: &[f64; 3]| v.iter().map(|a| a * a).sum::<f64>();
+        let vec = [
+            cross(&rows[0], &rows[1]),
+            cross(&rows[0], &rows[2]),
+            cross(&rows[1], &rows[2]),
+        ]
+        .into_iter()
+        .max_by(|u, v| norm(u).total_cmp(&norm(v)))
+        .unwrap();
+        let n = norm(&vec).sqrt();
+        if n == 0.0 {
+            continue;
+        }
+        let vec = [vec[0] / n, vec[1] / n, vec[2] / n];
+        let cond = 4.0 * vec[0] * vec[2] - vec[1] * vec[1];
+        if cond > 0.0 && best.is_none_or(|(_, c)| cond > c) {
+            best = Some((vec, cond));
+        }
+    }
+    let (a1, _) = best?;
+    let a2: Vec<f64> = (0..3)
+        .map(|i| (0..3).map(|k| t[i * 3 + k] * a1[k]).sum())
+        .collect();
+    Some([a1[0], a1[1], a1[2], a2[0], a2[1], a2[2]])
+}
+
+/// Compute the radial distance residual of a point to an ellipse.
+#[inline]
+fn ellipse_residual(params: &[f64; 5], x: f64, y: f64) -> f64 {
+    let [cx, cy, a, b, theta] = *params;
+    let (sin, cos) = theta.sin_cos();
+    let dx = x - cx;
+    let dy = y - cy;
+    let u = dx * cos + dy * sin;
+    let v = -dx * sin + dy * cos;
+    let dist = (u * u + v * v).sqrt();
+    let rho = ((u / a).powi(2) + (v / b).powi(2)).sqrt();
+    if rho == 0.0 {
+        return -a.abs().min(b.abs());
+    }
+    dist * (1.0 - 1.0 / rho)
+}
+
+/// Center and scale a 2D point set to improve numerical conditioning.
+///
+/// Returns the normalized coordinates and the mean and scale values used to
+/// normalize the point set.
+fn normalize_points<T>(points: ArrayView2<T>) -> (Vec<f64>, Vec<f64>, f64, f64, f64)
+where
+    T: AsNumeric,
+{
+    let n = points.dim().0 as f64;
+    let (sx, sy) = points.rows().into_iter().fold((0.0, 0.0), |acc, r| {
+        (acc.0 + r[0].to_f64(), acc.1 + r[1].to_f64())
+    });
+    let mx = sx / n;
+    let my = sy / n;
+    let mut xs: Vec<f64> = points.column(0).iter().map(|v| v.to_f64() - mx).collect();
+    let mut ys: Vec<f64> = points.column(1).iter().map(|v| v.to_f64() - my).collect();
+    let ms = xs
+        .iter()
+        .zip(ys.iter())
+        .map(|(x, y)| x * x + y * y)
+        .sum::<f64>()
+        / n;
+    let scale = if ms > 0.0 { ms.sqrt() } else { 1.0 };
+    xs.iter_mut().for_each(|v| *v /= scale);
+    ys.iter_mut().for_each(|v| *v /= scale);
+    (xs, ys, mx, my, scale)
+}
+
+/// Validate the shape of a 2D point set.
+fn validate_points<T>(points: &ArrayView2<T>, min_len: usize) -> Result<(), ImgalError> {
+    if points.dim().1 != 2 {
+        return Err(ImgalError::InvalidAxisLengthExpected {
+            arr_name: "points",
+            axis_idx: 1,
+            expected: 2,
+            got: points.dim().1,
+        });
+    }
+    if points.dim().0 < min_len {
+        return Err(ImgalError::InvalidArrayLengthMinimum {
+            arr_name: "points",
+            arr_len: points.dim().0,
+            min_len,
+        });
+    }
+    Ok(())
+}
@@ -0,0 +1,30 @@
+//! Shape fitting and measurement functions.
+//!
+//! This module provides functions for fitting geometric models (*e.g.* circles,
+//! ellipses and splines) to point sets such as contours, label boundaries,
+//! skeleton branches and tracks, and for measuring curve properties such as
+//! curvature and tortuosity. High-level assay measurements (*e.g.* confluence),
+//! intensity statistics of polygonal regions of interest, the similarity of
+//! the intensity distributions of regions of interest and the shape and
+//! intensity properties of labeled regions are also provided.
+
+mod confluence;
+mod curvature;
+mod fit;
+mod regionprops;
+mod roi;
+mod similarity;
+mod spline;
+
+pub use confluence::confluence;
+pub use curvature::curvature;
+pub use fit::EllipseFit;
+pub use fit::fit_circle;
+pub use fit::fit_ellipse;
+pub use regionprops::RegionProperties;
+pub use regionprops::regionprops;
+pub use roi::RoiStatistics;
+pub use roi::roi_statistics;
+pub use similarity::SimilarityMetric;
+pub use similarity::roi_similarity;
+pub use spline::fit_spline;
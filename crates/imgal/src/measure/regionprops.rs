@@ -0,0 +1,275 @@
+use std::collections::HashMap;
+
+use ndarray::{Array2, ArrayBase, ArrayView, AsArray, Axis, Dimension, ViewRepr};
+use rayon::prelude::*;
+
+use crate::linalg::symmetric_eigenvalues;
+use crate::prelude::*;
+use crate::validate::check_shapes;
+
+/// The properties of the labeled regions of `regionprops`, as a
+/// struct-of-vectors where index `i` of each property is the region with label
+/// `labels[i]`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RegionProperties {
+    /// The region labels in ascending order.
+    pub labels: Vec<u64>,
+    /// The number of pixels of each region, the area in 2D and the volume in
+    /// 3D.
+    pub area: Vec<usize>,
+    /// The centroid of each region with shape `(n, ndim)`.
+    pub centroid: Array2<f64>,
+    /// The bounding box of each region with shape `(n, 2 × ndim)`, the minimum
+    /// index of each axis followed by the exclusive maximum index of each
+    /// axis.
+    pub bbox: Array2<usize>,
+    /// The mean intensity of each region, `None` without an intensity image.
+    pub mean_intensity: Option<Vec<f64>>,
+    /// The maximum intensity of each region, `None` without an intensity
+    /// image.
+    pub max_intensity: Option<Vec<f64>>,
+    /// The boundary length estimate of each region, the perimeter in 2D and
+    /// the surface area in 3D.
+    pub perimeter: Vec<f64>,
+    /// The eccentricity of each region in the range `0.0` (isotropic) to
+    /// `1.0` (a line).
+    pub eccentricity: Vec<f64>,
+}
+
+/// The running sums of a region.
+#[derive(Clone)]
+struct RegionSums {
+    count: usize,
+    coords: Vec<f64>,
+    products: Vec<f64>,
+    min: Vec<usize>,
+    max: Vec<usize>,
+    intensity_sum: f64,
+    intensity_max: f64,
+    faces: usize,
+}
+
+impl RegionSums {
+    fn new(ndim: usize) -> Self {
+        Self {
+            count: 0,
+            coords: vec![0.0; ndim],
+            products: vec![0.0; ndim * ndim],
+            min: vec![usize::MAX; ndim],
+            max: vec![0; ndim],
+            intensity_sum: 0.0,
+            intensity_max: f64::NEG_INFINITY,
+            faces: 0,
+        }
+    }
+
+    fn merge(&mut self, other: &Self) {
+        self.count += other.count;
+        self.coords
+            .iter_mut()
+            .zip(&other.coords)
+            .for_each(|(a, b)| *a += b);
+        self.products
+            .iter_mut()
+            .zip(&other.products)
+            .for_each(|(a, b)| *a += b);
+        self.min
+            .iter_mut()
+            .zip(&other.min)
+            .for_each(|(a, &b)| *a = (*a).min(b));
+        self.max
+            .iter_mut()
+            .zip(&other.max)
+            .for_each(|(a, &b)| *a = (*a).max(b));
+        self.intensity_sum += other.intensity_sum;
+        self.intensity_max = self.intensity_max.max(other.intensity_max);
+        self.faces += other.faces;
+    }
+}
+
+/// Measure the properties of the labeled regions of an n-dimensional label
+/// image.
+///
+/// # Description
+///
+/// Measures the shape and intensity properties of each labeled region of a
+/// label image (*e.g.* from `morphology::label`), where `0` is the background:
+///
+/// * The area (volume in 3D) is the number of pixels of the region.
+/// * The centroid is the mean pixel index along each axis.
+/// * The bounding box is the minimum and exclusive maximum index along each
+///   axis.
+/// * The mean and maximum intensity of the region pixels in the optional
+///   intensity image.
+/// * The perimeter (surface area in 3D) is estimated from the number of pixel
+///   faces `F` shared with other labels or the image border, scaled by the
+///   mean projected length of a randomly oriented boundary, *e.g.*
+///   `P = π / 4 × F` in 2D and `S = 2 / 3 × F` in 3D. Face counts overestimate
+///   oblique boundaries, which the scaling corrects on average.
+/// * The eccentricity is computed from the largest and smallest eigenvalues
+///   `λ₁` and `λₙ` of the covariance matrix of the pixel indices:
+///
+/// ```text
+/// e = √(1 - λₙ / λ₁)
+/// ```
+///
+/// The eccentricity of an ellipse is recovered in 2D, it is `0.0` for a
+/// circle and approaches `1.0` for a line. The image is scanned in parallel.
+///
+/// # Arguments
+///
+/// * `labels`: The n-dimensional label image.
+/// * `intensity`: The intensity image with the same shape as `labels`. If
+///   `None`, the intensity properties are not measured.
+/// * `threads`: The requested number of threads to use for parallel execution.
+///   If `None` or `Some(1)` sequential execution is used. If `Some(0)`, then
+///   the maximum available parallelism is used. Thread counts are clamped to
+///   the systems maximum.
+///
+/// # Returns
+///
+/// * `Ok(RegionProperties)`: The properties of each region, ordered by label.
+/// * `Err(ImgalError)`: If the shape of `intensity` does not match the shape
+///   of `labels`.
+pub fn regionprops<'a, A, D>(
+    labels: A,
+    intensity: Option<ArrayView<f64, D>>,
+    threads: Option<usize>,
+) -> Result<RegionProperties, ImgalError>
+where
+    A: AsArray<'a, u64, D>,
+    D: Dimension,
+{
+    let labels: ArrayBase<ViewRepr<&'a u64>, D> = labels.into();
+    if let Some(intensity) = intensity.as_ref() {
+        check_shapes("intensity", intensity.shape(), "labels", labels.shape())?;
+    }
+    let ndim = labels.ndim();
+    let labels = labels.into_dyn();
+    let intensity = intensity.map(|i| i.into_dyn());
+    let shape = labels.shape().to_vec();
+    let accumulate = |mut map: HashMap<u64, RegionSums>, lane: usize| {
+        // the pixels along the last axis of a lane of the first axis
+        let lane_labels = labels.index_axis(Axis(0), lane);
+        lane_labels.indexed_iter().for_each(|(p, &l)| {
+            if l == 0 {
+                return;
+            }
+            let mut idx = vec![lane];
+            idx.extend_from_slice(p.slice());
+            let sums = map.entry(l).or_insert_with(|| RegionSums::new(ndim));
+            sums.count += 1;
+            for a in 0..ndim {
+                let x = idx[a] as f64;
+                sums.coords[a] += x;
+                sums.products[a * ndim..(a + 1) * ndim]
+                    .iter_mut()
+                    .zip(idx.iter())
+                    .for_each(|(p, &y)| *p += x * y as f64);
+                sums.min[a] = sums.min[a].min(idx[a]);
+                sums.max[a] = sums.max[a].max(idx[a] + 1);
+            }
+            if let Some(intensity) = intensity.as_ref() {
+                let v = intensity[idx.as_slice()];
+                sums.intensity_sum += v;
+                sums.intensity_max = sums.intensity_max.max(v);
+            }
+            // the faces shared with other labels or the image border
+            for a in 0..ndim {
+                for forward in [false, true] {
+                    let exposed = if forward {
+                        idx[a] + 1 == shape[a]
+                    } else {
+                        idx[a] == 0
+                    };
+                    let exposed = exposed || {
+                        let mut n = idx.clone();
+                        n[a] = if forward { n[a] + 1 } else { n[a] - 1 };
+                        labels[n.as_slice()] != l
+                    };
+                    if exposed {
+                        sums.faces += 1;
+                    }
+                }
+            }
+        });
+        map
+    };
+    let merge = |mut a: HashMap<u64, RegionSums>, b: HashMap<u64, RegionSums>| {
+        b.into_iter().for_each(|(l, s)| match a.get_mut(&l) {
+            Some(sums) => sums.merge(&s),
+            None => {
+                a.insert(l, s);
+            }
+        });
+        a
+    };
+    let n_lanes = if ndim == 0 { 0 } else { shape[0] };
+    let sums: HashMap<u64, RegionSums> = par!(threads,
+        seq_exp: (0..n_lanes).fold(HashMap::new(), accumulate),
+        par_exp: (0..n_lanes)
+            .into_par_iter()
+            .fold(HashMap::new, accumulate)
+            .reduce(HashMap::new, merge));
+    let mut region_labels: Vec<u64> = sums.keys().copied().collect();
+    region_labels.sort_unstable();
+    let n = region_labels.len();
+    // the mean absolute projection of a random unit normal on an axis,
+    // E₁ = 1, E₂ = 2 / π and Eₙ = Eₙ₋₂ × (n - 2) / (n - 1)
+    let mut projection = if ndim % 2 == 1 {
+        1.0
+    } else {
+        std::f64::consts::FRAC_2_PI
+    };
+    let mut k = 2 - ndim % 2;
+    while k < ndim {
+        k += 2;
+        projection *= (k - 2) as f64 / (k - 1) as f64;
+    }
+    let face_scale = 1.0 / (ndim.max(1) as f64 * projection);
+    let mut area = Vec::with_capacity(n);
+    let mut centroid = Array2::<f64>::zeros((n, ndim));
+    let mut bbox = Array2::<usize>::zeros((n, 2 * ndim));
+    let mut mean_intensity = Vec::with_capacity(n);
+    let mut max_intensity = Vec::with_capacity(n);
+    let mut perimeter = Vec::with_capacity(n);
+    let mut eccentricity = Vec::with_capacity(n);
+    for (i, l) in region_labels.iter().enumerate() {
+        let s = &sums[l];
+        let count = s.count as f64;
+        area.push(s.count);
+        let mean: Vec<f64> = s.coords.iter().map(|c| c / count).collect();
+        centroid
+            .row_mut(i)
+            .iter_mut()
+            .zip(&mean)
+            .for_each(|(c, m)| *c = *m);
+        for a in 0..ndim {
+            bbox[[i, a]] = s.min[a];
+            bbox[[i, ndim + a]] = s.max[a];
+        }
+        mean_intensity.push(s.intensity_sum / count);
+        max_intensity.push(s.intensity_max);
+        perimeter.push(s.faces as f64 * face_scale);
+        let cov: Vec<f64> = (0..ndim * ndim)
+            .map(|ab| s.products[ab] / count - mean[ab / ndim] * mean[ab % ndim])
+            .collect();
+        let eig = symmetric_eigenvalues(&cov, ndim);
+        let ecc = match (eig.first(), eig.last()) {
+            (Some(&hi), Some(&lo)) if hi > 0.0 => (1.0 - lo.max(0.0) / hi).max(0.0).sqrt(),
+            _ => 0.0,
+        };
+        eccentricity.push(ecc);
+    }
+    let has_intensity = intensity.is_some();
+    Ok(RegionProperties {
+        labels: region_labels,
+        area,
+        centroid,
+        bbox,
+        mean_intensity: has_intensity.then_some(mean_intensity),
+        max_intensity: has_intensity.then_some(max_intensity),
+        perimeter,
+        eccentricity,
+    })
+}
@@ -0,0 +1,193 @@
+use std::collections::HashMap;
+
+use ndarray::{Array2, ArrayBase, ArrayViewMut1, AsArray, Axis, Dimension, IxDyn, ViewRepr};
+use rayon::prelude::*;
+
+use crate::prelude::*;
+use crate::validate::{check_roi_bounds, check_shapes};
+
+/// The similarity measure between the intensity distributions of two regions
+/// of interest (ROIs).
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum SimilarityMetric {
+    /// One minus the mutual information between the intensity and the ROI
+    /// membership of the pooled pixels of both ROIs, normalized by `ln(2)`
+    /// (*i.e.* one minus the Jensen-Shannon divergence). Ranges from `0.0`
+    /// for disjoint distributions to `1.0` for identical distributions.
+    #[default]
+    MutualInformation,
+    /// The Pearson correlation coefficient of the normalized histograms,
+    /// ranging from `-1.0` to `1.0`.
+    Correlation,
+    /// The histogram intersection `Σ min(pᵢ, qᵢ)` of the normalized
+    /// histograms, ranging from `0.0` to `1.0`.
+    HistogramIntersection,
+}
+
+/// Compute the pairwise similarity matrix of the intensity distributions of
+/// regions of interest (ROIs).
+///
+/// # Description
+///
+/// Computes the intensity histogram of each ROI, with `bins` bins spanning the
+/// intensity range of all ROI pixels, and compares the normalized histograms
+/// of every pair of ROIs with the similarity `metric`. If a second channel is
+/// given, the joint 2D histogram of both channels (with `bins × bins` bins)
+/// is compared instead, such that ROIs (*e.g.* cells) are similar if they
+/// share the same co-expression pattern. The similarity matrix can be
+/// clustered to group cells by their expression pattern. The mutual
+/// information similarity is:
+///
+/// ```text
+/// S(p, q) = 1 - (H(m) - (H(p) + H(q)) / 2) / ln(2)
+/// ```
+///
+/// Where `p` and `q` are the normalized histograms of the ROI pair, `m` is
+/// their mixture `(p + q) / 2` and `H` is the Shannon entropy. The ROI sizes
+/// do not affect the similarity, only the shape of the distributions.
+///
+/// # Arguments
+///
+/// * `data_a`: The first n-dimensional image channel.
+/// * `data_b`: An optional second n-dimensional image channel with the same
+///   shape as `data_a`. If `None`, only `data_a` is used.
+/// * `rois`: A map of point clouds representing Regions of Interest (ROIs).
+///   The individual ROIs must have the same dimensionality as the input data.
+/// * `metric`: The similarity measure. If `None`, then
+///   `metric = SimilarityMetric::MutualInformation`.
+/// * `bins`: The number of histogram bins per channel. If `None`, then
+///   `bins = 32`.
+/// * `threads`: The requested number of threads to use for parallel execution.
+///   If `None` or `Some(1)` sequential execution is used. If `Some(0)`, then
+///   the maximum available parallelism is used. Thread counts are clamped to
+///   the systems maximum.
+///
+/// # Returns
+///
+/// * `Ok((Vec<u64>, Array2<f64>))`: A tuple of the ROI label IDs in ascending
+///   order and the symmetric `(n, n)` similarity matrix, where element
+///   `(i, j)` is the similarity of the `i`-th and `j`-th ROI. The correlation
+///   of a constant histogram is `NaN`.
+/// * `Err(ImgalError)`: If the shapes of `data_a` and `data_b` do not match. If
+///   a ROI point is out of bounds of `data_a`. If a ROI has no points. If
+///   `bins == 0`.
+pub fn roi_similarity<'a, T, A, D>(
+    data_a: A,
+    data_b: Option<A>,
+    rois: &HashMap<u64, Array2<usize>>,
+    metric: Option<SimilarityMetric>,
+    bins: Option<usize>,
+    threads: Option<usize>,
+) -> Result<(Vec<u64>, Array2<f64>), ImgalError>
+where
+    A: AsArray<'a, T, D>,
+    D: Dimension,
+    T: 'a + AsNumeric,
+{
+    let data_a: ArrayBase<ViewRepr<&'a T>, IxDyn> = data_a.into().into_dyn();
+    let data_b: Option<ArrayBase<ViewRepr<&'a T>, IxDyn>> = data_b.map(|d| d.into().into_dyn());
+    if let Some(db) = data_b.as_ref() {
+        check_shapes("data_a", data_a.shape(), "data_b", db.shape())?;
+    }
+    check_roi_bounds(rois, data_a.shape())?;
+    if let Some(v) = rois.values().find(|v| v.nrows() == 0) {
+        return Err(ImgalError::InvalidArrayLengthMinimum {
+            arr_name: "rois",
+            arr_len: v.nrows(),
+            min_len: 1,
+        });
+    }
+    let bins = bins.unwrap_or(32);
+    if bins == 0 {
+        return Err(ImgalError::InvalidParameterValueEqual {
+            param_name: "bins",
+            value: 0,
+        });
+    }
+    let metric = metric.unwrap_or_default();
+    let mut labels: Vec<u64> = rois.keys().cloned().collect();
+    labels.sort_unstable();
+    // the intensity range of each channel over all ROI pixels
+    let range = |data: &ArrayBase<ViewRepr<&'a T>, IxDyn>| -> (f64, f64) {
+        rois.values()
+            .flat_map(|v| v.rows().into_iter())
+            .map(|p| data[IxDyn(&p.to_vec())].to_f64())
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), v| {
+                (lo.min(v), hi.max(v))
+            })
+    };
+    let range_a = range(&data_a);
+    let range_b = data_b.as_ref().map(range);
+    let bin = |v: f64, (lo, hi): (f64, f64)| -> usize {
+        if hi > lo {
+            (((v - lo) / (hi - lo) * bins as f64) as usize).min(bins - 1)
+        } else {
+            0
+        }
+    };
+    let n_bins = if data_b.is_some() { bins * bins } else { bins };
+    let roi_hist = |k: &u64| -> Vec<f64> {
+        let cloud = rois[k].as_standard_layout().into_owned();
+        let mut hist = vec![0.0; n_bins];
+        cloud.axis_iter(Axis(0)).for_each(|p| {
+            let pos = IxDyn(p.as_slice().unwrap());
+            let mut i = bin(data_a[&pos].to_f64(), range_a);
+            if let (Some(db), Some(rb)) = (data_b.as_ref(), range_b) {
+                i = i * bins + bin(db[&pos].to_f64(), rb);
+            }
+            hist[i] += 1.0;
+        });
+        let n = cloud.nrows() as f64;
+        hist.iter_mut().for_each(|h| *h /= n);
+        hist
+    };
+    let hists: Vec<Vec<f64>> = par!(threads,
+        seq_exp: labels.iter().map(roi_hist).collect(),
+        par_exp: labels.par_iter().map(roi_hist).collect());
+    let n = labels.len();
+    let mut similarity = Array2::<f64>::zeros((n, n));
+    let row_calc = |(i, mut row): (usize, ArrayViewMut1<f64>)| {
+        row.iter_mut()
+            .enumerate()
+            .for_each(|(j, s)| *s = compare(&hists[i], &hists[j], metric));
+    };
+    par!(threads,
+        seq_exp: similarity.axis_iter_mut(Axis(0)).enumerate().for_each(row_calc),
+        par_exp: similarity.axis_iter_mut(Axis(0)).into_par_iter().enumerate().for_each(row_calc));
+    Ok((labels, similarity))
+}
+
+/// Compare two normalized histograms with a similarity metric.
+fn compare(p: &[f64], q: &[f64], metric: SimilarityMetric) -> f64 {
+    match metric {
+        SimilarityMetric::MutualInformation => {
+            let entropy = |h: f64| if h > 0.0 { -h * h.ln() } else { 0.0 };
+            let divergence: f64 = p
+                .iter()
+                .zip(q.iter())
+                .map(|(&a, &b)| entropy((a + b) / 2.0) - (entropy(a) + entropy(b)) / 2.0)
+                .sum();
+            (1.0 - divergence / std::f64::consts::LN_2).clamp(0.0, 1.0)
+        }
+        SimilarityMetric::Correlation => {
+            let n = p.len() as f64;
+            let (mean_p, mean_q) = (p.iter().sum::<f64>() / n, q.iter().sum::<f64>() / n);
+            let (sq_p, sq_q, cross) =
+                p.iter()
+                    .zip(q.iter())
+                    .fold((0.0, 0.0, 0.0), |acc, (&a, &b)| {
+                        let (da, db) = (a - mean_p, b - mean_q);
+                        (acc.0 + da * da, acc.1 + db * db, acc.2 + da * db)
+                    });
+            let denom = (sq_p * sq_q).sqrt();
+            if denom != 0.0 {
+                cross / denom
+            } else {
+                f64::NAN
+            }
+        }
+        SimilarityMetric::HistogramIntersection => {
+            p.iter().zip(q.iter()).map(|(&a, &b)| a.min(b)).sum()
+        }
+    }
+}
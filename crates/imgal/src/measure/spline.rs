@@ -0,0 +1,264 @@
+use ndarray::{Array2, ArrayBase, AsArray, Ix2, ViewRepr};
+
+use crate::linalg::{solve_banded_spd, solve_dense};
+use crate::prelude::*;
+
+/// Fit a smoothing B-spline to an ordered point list and resample it by
+/// arclength.
+///
+/// # Description
+///
+/// Fits a smoothing cubic B-spline (*i.e.* a penalized spline or P-spline) to
+/// an ordered list of *n*-dimensional points such as contours, skeleton
+/// branches or tracks. The points are parameterized by their cumulative chord
+/// length `u ∈ [0, 1]` and the spline control points `c` are computed by
+/// minimizing the penalized least squares objective:
+///
+/// ```text
+/// ‖P - B × c‖² + λ × ‖Δ²c‖²
+/// ```
+///
+/// Where `B` is the uniform cubic B-spline basis evaluated at each point's
+/// parameter, `Δ²` is the second order difference operator on the control
+/// points and `λ` is the smoothing factor. Larger smoothing factors produce
+/// smoother curves, while `λ = 0` fits the points as closely as the basis
+/// allows. The fitted spline is then resampled at points that are equally
+/// spaced along the spline's arclength.
+///
+/// # Arguments
+///
+/// * `points`: The ordered point list with shape `(p, D)`, where `p` is the
+///   number of points and `D` is the number of dimensions.
+/// * `smoothing`: The non-negative smoothing factor, `λ`. If `None`, then
+///   `smoothing = 1.0`.
+/// * `samples`: The number of arclength equidistant points to resample the
+///   fitted spline with. If `None`, then `samples = p`.
+/// * `closed`: If `true` the point list is treated as a closed curve (*e.g.* a
+///   contour) and a periodic spline is fit. If `false` an open spline is fit.
+///
+/// # Returns
+///
+/// * `Ok(Array2<f64>)`: The resampled spline points with shape `(samples, D)`.
+///   Open splines include both end points. Closed splines do not repeat the
+///   first point at the end.
+/// * `Err(ImgalError)`: If `p < 4`. If `D == 0`. If `samples < 2`. If
+///   `smoothing < 0.0`. If all points are identical.
+pub fn fit_spline<'a, T, A>(
+    points: A,
+    smoothing: Option<f64>,
+    samples: Option<usize>,
+    closed: bool,
+) -> Result<Array2<f64>, ImgalError>
+where
+    A: AsArray<'a, T, Ix2>,
+    T: 'a + AsNumeric,
+{
+    let points: ArrayBase<ViewRepr<&'a T>, Ix2> = points.into();
+    let (p, d) = points.dim();
+    if p < 4 {
+        return Err(ImgalError::InvalidArrayLengthMinimum {
+            arr_name: "points",
+            arr_len: p,
+            min_len: 4,
+        });
+    }
+    if d == 0 {
+        return Err(ImgalError::InvalidAxisLengthLess {
+            arr_name: "points",
+            axis_idx: 1,
+            value: 1,
+        });
+    }
+    let samples = samples.unwrap_or(p);
+    if samples < 2 {
+        return Err(ImgalError::InvalidParameterValueLess {
+            param_name: "samples",
+            value: 2,
+        });
+    }
+    let smoothing = smoothing.unwrap_or(1.0);
+    if smoothing < 0.0 {
+        return Err(ImgalError::InvalidParameterValueOutsideRange {
+            param_name: "smoothing",
+            value: smoothing,
+            min: 0.0,
+            max: f64::INFINITY,
+        });
+    }
+    let coords: Vec<Vec<f64>> = (0..d)
+        .map(|j| points.column(j).iter().map(|v| v.to_f64()).collect())
+        .collect();
+    // parameterize the points by the cumulative chord length
+    let dist = |a: usize, b: usize| -> f64 {
+        coords
+            .iter()
+            .map(|c| (c[a] - c[b]).powi(2))
+            .sum::<f64>()
+            .sqrt()
+    };
+    let mut params = vec![0.0; p];
+    (1..p).for_each(|i| params[i] = params[i - 1] + dist(i, i - 1));
+    let total = if closed {
+        params[p - 1] + dist(0, p - 1)
+    } else {
+        params[p - 1]
+    };
+    if total == 0.0 {
+        return Err(ImgalError::InvalidGeneric {
+            msg: "Degenerate point list, a spline can not be fit to identical points.",
+        });
+    }
+    params.iter_mut().for_each(|u| *u /= total);
+    let spline =
+        BSpline::fit(&params, &coords, smoothing, closed).ok_or(ImgalError::InvalidGeneric {
+            msg: "Failed to solve the spline normal equations.",
+        })?;
+    Ok(spline.resample(samples))
+}
+
+/// A uniform cubic B-spline curve.
+struct BSpline {
+    /// The control points per dimension.
+    ctrl: Vec<Vec<f64>>,
+    /// If `true` the spline is periodic.
+    closed: bool,
+}
+
+impl BSpline {
+    /// Compute the non-zero basis function weights and the control point
+    /// indices at parameter `u`.
+    fn basis(u: f64, k: usize, closed: bool) -> ([usize; 4], [f64; 4]) {
+        let segs = if closed { k } else { k - 3 };
+        let x = if closed {
+            u.rem_euclid(1.0) * segs as f64
+        } else {
+            u.clamp(0.0, 1.0) * segs as f64
+        };
+        let s = (x.floor() as usize).min(segs - 1);
+        let v = x - s as f64;
+        let v2 = v * v;
+        let v3 = v2 * v;
+        let w = [
+            (1.0 - v).powi(3) / 6.0,
+            (3.0 * v3 - 6.0 * v2 + 4.0) / 6.0,
+            (-3.0 * v3 + 3.0 * v2 + 3.0 * v + 1.0) / 6.0,
+            v3 / 6.0,
+        ];
+        let idx = if closed {
+            [s % k, (s + 1) % k, (s + 2) % k, (s + 3) % k]
+        } else {
+            [s, s + 1, s + 2, s + 3]
+        };
+        (idx, w)
+    }
+
+    /// Evaluate the spline at parameter `u`.
+    fn eval(&self, u: f64) -> Vec<f64> {
+        let k = self.ctrl[0].len();
+        let (idx, w) = Self::basis(u, k, self.closed);
+        self.ctrl
+            .iter()
+            .map(|c| (0..4).map(|j| w[j] * c[idx[j]]).sum())
+            .collect()
+    }
+
+    /// Fit the spline control points with penalized least squares.
+    fn fit(params: &[f64], coords: &[Vec<f64>], smoothing: f64, closed: bool) -> Option<Self> {
+        let k = params.len();
+        // open splines have a banded normal matrix (bandwidth 3) and are stored
+        // as a lower band, closed splines wrap around and are stored densely
+        let bw = 3;
+        let mut normal = if closed {
+            vec![0.0; k * k]
+        } else {
+            vec![0.0; k * (bw + 1)]
+        };
+        let mut add = |i: usize, j: usize, v: f64| {
+            if closed {
+                normal[i * k + j] += v;
+            } else if j <= i {
+                normal[i * (bw + 1) + (i - j)] += v;
+            }
+        };
+        let mut rhs = vec![vec![0.0; k]; coords.len()];
+        params.iter().enumerate().for_each(|(i, &u)| {
+            let (idx, w) = Self::basis(u, k, closed);
+            for a in 0..4 {
+                for b in 0..4 {
+                    add(idx[a], idx[b], w[a] * w[b]);
+                }
+                rhs.iter_mut()
+                    .zip(coords.iter())
+                    .for_each(|(r, c)| r[idx[a]] += w[a] * c[i]);
+            }
+        });
+        // add the second order difference penalty, periodic for closed curves
+        let n_diff = if closed { k } else { k - 2 };
+        let coef = [1.0, -2.0, 1.0];
+        (0..n_diff).for_each(|r| {
+            let idx = [r % k, (r + 1) % k, (r + 2) % k];
+            for a in 0..3 {
+                for b in 0..3 {
+                    add(idx[a], idx[b], smoothing * coef[a] * coef[b]);
+                }
+            }
+        });
+        // a small ridge term keeps the system positive definite when some
+        // basis functions have no support from the data
+        let diag = |i: usize| if closed { i * k + i } else { i * (bw + 1) };
+        let ridge = 1e-10 * (0..k).map(|i| normal[diag(i)]).sum::<f64>() / k as f64;
+        (0..k).for_each(|i| normal[diag(i)] += ridge.max(f64::MIN_POSITIVE));
+        let ctrl = rhs
+            .iter()
+            .map(|r| {
+                if closed {
+                    solve_dense(&normal, r)
+                } else {
+                    solve_banded_spd(&normal, bw, r)
+                }
+            })
+            .collect::<Option<Vec<Vec<f64>>>>()?;
+        Some(Self { ctrl, closed })
+    }
+
+    /// Resample the spline at arclength equidistant points.
+    fn resample(&self, samples: usize) -> Array2<f64> {
+        let d = self.ctrl.len();
+        let m = 16 * samples.max(self.ctrl[0].len());
+        // densely evaluate the spline to build an arclength lookup table
+        let pts: Vec<Vec<f64>> = (0..=m).map(|j| self.eval(j as f64 / m as f64)).collect();
+        let mut arc = vec![0.0; m + 1];
+        (1..=m).for_each(|j| {
+            let seg = pts[j]
+                .iter()
+                .zip(pts[j - 1].iter())
+                .map(|(a, b)| (a - b).powi(2))
+                .sum::<f64>()
+                .sqrt();
+            arc[j] = arc[j - 1] + seg;
+        });
+        let total = arc[m];
+        let step = if self.closed {
+            total / samples as f64
+        } else {
+            total / (samples - 1) as f64
+        };
+        let mut out = Array2::<f64>::zeros((samples, d));
+        (0..samples).for_each(|i| {
+            let target = (i as f64 * step).min(total);
+            let j = arc.partition_point(|&a| a < target).clamp(1, m);
+            let seg = arc[j] - arc[j - 1];
+            let frac = if seg > 0.0 {
+                (target - arc[j - 1]) / seg
+            } else {
+                0.0
+            };
+            let u = (j as f64 - 1.0 + frac) / m as f64;
+            self.eval(u)
+                .into_iter()
+                .enumerate()
+                .for_each(|(c, v)| out[[i, c]] = v);
+        });
+        out
+    }
+}
@@ -0,0 +1,165 @@
+use ndarray::{Array2, ArrayBase, AsArray, Ix2, ViewRepr};
+
+use crate::optimize::hungarian;
+use crate::prelude::*;
+
+/// The evaluation scores of predicted point detections against a ground truth.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DetectionScores {
+    /// The number of predicted points.
+    pub n_pred: usize,
+    /// The number of ground truth points.
+    pub n_truth: usize,
+    /// The number of matched point pairs (*i.e.* the true positives).
+    pub true_positives: usize,
+    /// The fraction of predicted points that are matched, `NaN` if there are
+    /// no predicted points.
+    pub precision: f64,
+    /// The fraction of ground truth points that are matched, `NaN` if there
+    /// are no ground truth points.
+    pub recall: f64,
+    /// The harmonic mean of the precision and recall, `NaN` if there are no
+    /// points.
+    pub f1: f64,
+    /// The root mean square distance of the matched point pairs, `NaN` if no
+    /// points are matched.
+    pub rmse: f64,
+}
+
+/// Match predicted points to ground truth points one-to-one within a maximum
+/// distance.
+///
+/// # Description
+///
+/// Matches the predicted points (*e.g.* detected spot centers) to the ground
+/// truth points (*e.g.* simulated spot positions) one-to-one by solving the
+/// linear assignment problem with `optimize::hungarian` on their Euclidean
+/// distances. Point pairs farther apart than `max_distance` are never
+/// matched. The assignment maximizes the number of matched pairs and, among
+/// those assignments, minimizes the sum of the matched distances.
+///
+/// # Arguments
+///
+/// * `pred`: The predicted points with shape `(p, D)`.
+/// * `truth`: The ground truth points with shape `(q, D)`.
+/// * `max_distance`: The maximum distance between matched points.
+///
+/// # Returns
+///
+/// * `Ok(Vec<(usize, usize, f64)>)`: The matched `(pred, truth, distance)`
+///   point index pairs and distances, in ascending order of `pred` indices.
+/// * `Err(ImgalError)`: If `pred` and `truth` do not have the same number of
+///   columns. If `max_distance < 0.0`.
+pub fn match_points<'a, A>(
+    pred: A,
+    truth: A,
+    max_distance: f64,
+) -> Result<Vec<(usize, usize, f64)>, ImgalError>
+where
+    A: AsArray<'a, f64, Ix2>,
+{
+    let pred: ArrayBase<ViewRepr<&'a f64>, Ix2> = pred.into();
+    let truth: ArrayBase<ViewRepr<&'a f64>, Ix2> = truth.into();
+    if pred.ncols() != truth.ncols() {
+        return Err(ImgalError::InvalidAxisLengthExpected {
+            arr_name: "pred",
+            axis_idx: 1,
+            expected: truth.ncols(),
+            got: pred.ncols(),
+        });
+    }
+    if max_distance.is_nan() || max_distance < 0.0 {
+        return Err(ImgalError::InvalidParameterValueOutsideRange {
+            param_name: "max_distance",
+            value: max_distance,
+            min: 0.0,
+            max: f64::INFINITY,
+        });
+    }
+    let (n, m) = (pred.nrows(), truth.nrows());
+    if n == 0 || m == 0 {
+        return Ok(Vec::new());
+    }
+    let dist = Array2::from_shape_fn((n, m), |(i, j)| {
+        pred.row(i)
+            .iter()
+            .zip(truth.row(j).iter())
+            .map(|(a, b)| (a - b).powi(2))
+            .sum::<f64>()
+            .sqrt()
+    });
+    // pairs beyond the cutoff cost more than any set of allowed pairs, so the
+    // assignment first maximizes the number of allowed pairs
+    let forbidden = (n.min(m) as f64 + 1.0) * max_distance + 1.0;
+    let cost = dist.mapv(|d| if d <= max_distance { d } else { forbidden });
+    Ok(hungarian(&cost)?
+        .into_iter()
+        .map(|(i, j)| (i, j, dist[[i, j]]))
+        .filter(|&(_, _, d)| d <= max_distance)
+        .collect())
+}
+
+/// Evaluate predicted point detections against ground truth points.
+///
+/// # Description
+///
+/// Matches the predicted points to the ground truth points with `match_points`
+/// and computes the detection scores:
+///
+/// ```text
+/// precision = TP / n_pred
+/// recall = TP / n_truth
+/// F1 = 2 × TP / (n_pred + n_truth)
+/// RMSE = √(Σ dᵢ² / TP)
+/// ```
+///
+/// Where `TP` is the number of matched point pairs and `dᵢ` are their
+/// distances. Used to benchmark spot and blob detectors against simulated
+/// ground truth positions, as a function of the distance threshold.
+///
+/// # Arguments
+///
+/// * `pred`: The predicted points with shape `(p, D)`.
+/// * `truth`: The ground truth points with shape `(q, D)`.
+/// * `max_distance`: The maximum distance between matched points.
+///
+/// # Returns
+///
+/// * `Ok(DetectionScores)`: The detection scores.
+/// * `Err(ImgalError)`: If `pred` and `truth` do not have the same number of
+///   columns. If `max_distance < 0.0`.
+///
+/// # Reference
+///
+/// <https://doi.org/10.1038/nmeth.2808>
+pub fn detection_scores<'a, A>(
+    pred: A,
+    truth: A,
+    max_distance: f64,
+) -> Result<DetectionScores, ImgalError>
+where
+    A: AsArray<'a, f64, Ix2>,
+{
+    let pred: ArrayBase<ViewRepr<&'a f64>, Ix2> = pred.into();
+    let truth: ArrayBase<ViewRepr<&'a f64>, Ix2> = truth.into();
+    let matches = match_points(pred.view(), truth.view(), max_distance)?;
+    let (n_pred, n_truth) = (pred.nrows(), truth.nrows());
+    let tp = matches.len();
+    let ratio = |num: f64, denom: usize| {
+        if denom != 0 {
+            num / denom as f64
+        } else {
+            f64::NAN
+        }
+    };
+    let sq_sum: f64 = matches.iter().map(|m| m.2 * m.2).sum();
+    Ok(DetectionScores {
+        n_pred,
+        n_truth,
+        true_positives: tp,
+        precision: ratio(tp as f64, n_pred),
+        recall: ratio(tp as f64, n_truth),
+        f1: ratio(2.0 * tp as f64, n_pred + n_truth),
+        rmse: ratio(sq_sum, tp).sqrt(),
+    })
+}
@@ -0,0 +1,10 @@
+//! Evaluation metric functions.
+//!
+//! This module provides functions for evaluating the results of image analysis
+//! pipelines (*e.g.* segmentations, spot detections and tracks) against a
+//! ground truth, and the residual misregistration between channels.
+
+pub mod detection;
+pub mod registration;
+pub mod segmentation;
+pub mod tracking;
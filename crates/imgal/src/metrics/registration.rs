@@ -0,0 +1,185 @@
+use ndarray::{Array1, Array2, Array3, ArrayBase, AsArray, Axis, Ix2, ViewRepr, s};
+
+use crate::metrics::detection::match_points;
+use crate::prelude::*;
+use crate::statistics::linear_percentile;
+
+/// The residual displacement report of a bead-based channel co-registration.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RegistrationReport {
+    /// The number of beads in the moving channel.
+    pub n_moving: usize,
+    /// The number of beads in the reference channel.
+    pub n_reference: usize,
+    /// The matched `(moving, reference)` bead index pairs, in ascending order
+    /// of `moving` indices.
+    pub matches: Vec<(usize, usize)>,
+    /// The residual displacement `moving - reference` of each matched bead
+    /// pair, with shape `(n_matches, D)`.
+    pub displacements: Array2<f64>,
+    /// The mean residual displacement vector (*i.e.* the remaining systematic
+    /// shift), `NaN` if no beads are matched.
+    pub mean_displacement: Array1<f64>,
+    /// The mean residual distance, `NaN` if no beads are matched.
+    pub mean_error: f64,
+    /// The 95th percentile of the residual distances, `NaN` if no beads are
+    /// matched.
+    pub percentile_95_error: f64,
+    /// The maximum residual distance, `NaN` if no beads are matched.
+    pub max_error: f64,
+    /// The mean residual displacement vector of the matched beads in each cell
+    /// of the field grid, with shape `(grid_rows, grid_cols, D)`. `NaN` for
+    /// cells without beads.
+    pub field_displacement: Array3<f64>,
+    /// The mean residual distance of the matched beads in each cell of the
+    /// field grid. `NaN` for cells without beads.
+    pub field_error: Array2<f64>,
+    /// The number of matched beads in each cell of the field grid.
+    pub field_counts: Array2<usize>,
+}
+
+/// Report the residual misregistration between bead coordinates detected in
+/// two channels.
+///
+/// # Description
+///
+/// Matches the bead coordinates of a moving channel (*e.g.* after applying a
+/// chromatic or affine registration) to the bead coordinates of a reference
+/// channel one-to-one with `metrics::detection::match_points`, and summarizes
+/// the residual displacements `dᵢ = mᵢ - rᵢ` of the matched pairs:
+///
+/// ```text
+/// mean displacement = Σ dᵢ / n
+/// mean error = Σ ‖dᵢ‖ / n
+/// ```
+///
+/// Together with the 95th percentile (linear interpolation) and maximum of the
+/// residual distances `‖dᵢ‖`. Field-dependent errors (*e.g.* lateral
+/// chromatic magnification or distortion towards the edges) are mapped by
+/// averaging the displacements in a regular grid of cells over the bounding
+/// box of the matched reference beads along the last two coordinate axes
+/// (*i.e.* `row` and `col`). The report is the quality control step before a
+/// colocalization analysis: residuals comparable to the colocalization
+/// distance or a systematic field pattern invalidate the registration.
+///
+/// # Arguments
+///
+/// * `moving`: The bead coordinates of the moving channel with shape
+///   `(p, D)`, where `D >= 2`.
+/// * `reference`: The bead coordinates of the reference channel with shape
+///   `(q, D)`.
+/// * `max_distance`: The maximum distance between matched beads.
+/// * `grid`: The number of `(row, col)` cells of the field map. If `None`,
+///   then `grid = (4, 4)`.
+///
+/// # Returns
+///
+/// * `Ok(RegistrationReport)`: The residual displacement report.
+/// * `Err(ImgalError)`: If `moving` and `reference` do not have the same
+///   number of columns. If the coordinates have fewer than `2` columns. If
+///   `max_distance < 0.0`. If a `grid` dimension is `0`.
+pub fn registration_report<'a, A>(
+    moving: A,
+    reference: A,
+    max_distance: f64,
+    grid: Option<(usize, usize)>,
+) -> Result<RegistrationReport, ImgalError>
+where
+    A: AsArray<'a, f64, Ix2>,
+{
+    let moving: ArrayBase<ViewRepr<&'a f64>, Ix2> = moving.into();
+    let reference: ArrayBase<ViewRepr<&'a f64>, Ix2> = reference.into();
+    let ndim = reference.ncols();
+    if ndim < 2 {
+        return Err(ImgalError::InvalidAxisLengthExpected {
+            arr_name: "reference",
+            axis_idx: 1,
+            expected: 2,
+            got: ndim,
+        });
+    }
+    let grid = grid.unwrap_or((4, 4));
+    if grid.0 == 0 || grid.1 == 0 {
+        return Err(ImgalError::InvalidParameterValueEqual {
+            param_name: "grid",
+            value: 0,
+        });
+    }
+    let pairs = match_points(moving.view(), reference.view(), max_distance)?;
+    let n_matches = pairs.len();
+    let matches: Vec<(usize, usize)> = pairs.iter().map(|&(i, j, _)| (i, j)).collect();
+    let errors: Array1<f64> = pairs.iter().map(|&(_, _, d)| d).collect();
+    let displacements = Array2::from_shape_fn((n_matches, ndim), |(k, ax)| {
+        let (i, j) = matches[k];
+        moving[[i, ax]] - reference[[j, ax]]
+    });
+    let mut field_displacement = Array3::<f64>::zeros((grid.0, grid.1, ndim));
+    let mut field_error = Array2::<f64>::zeros(grid);
+    let mut field_counts = Array2::<usize>::zeros(grid);
+    if n_matches == 0 {
+        field_displacement.fill(f64::NAN);
+        field_error.fill(f64::NAN);
+        return Ok(RegistrationReport {
+            n_moving: moving.nrows(),
+            n_reference: reference.nrows(),
+            matches,
+            displacements,
+            mean_displacement: Array1::from_elem(ndim, f64::NAN),
+            mean_error: f64::NAN,
+            percentile_95_error: f64::NAN,
+            max_error: f64::NAN,
+            field_displacement,
+            field_error,
+            field_counts,
+        });
+    }
+    // the field cell of each matched reference bead along the row and col axes
+    let cell_axes = [(ndim - 2, grid.0), (ndim - 1, grid.1)];
+    let bounds = cell_axes.map(|(ax, _)| {
+        matches
+            .iter()
+            .map(|&(_, j)| reference[[j, ax]])
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), v| {
+                (lo.min(v), hi.max(v))
+            })
+    });
+    let cell = |j: usize, a: usize| -> usize {
+        let (ax, n_cells) = cell_axes[a];
+        let (lo, hi) = bounds[a];
+        if hi > lo {
+            (((reference[[j, ax]] - lo) / (hi - lo) * n_cells as f64) as usize).min(n_cells - 1)
+        } else {
+            0
+        }
+    };
+    for (k, &(_, j)) in matches.iter().enumerate() {
+        let (r, c) = (cell(j, 0), cell(j, 1));
+        field_counts[[r, c]] += 1;
+        field_error[[r, c]] += errors[k];
+        field_displacement
+            .slice_mut(s![r, c, ..])
+            .scaled_add(1.0, &displacements.row(k));
+    }
+    field_error
+        .iter_mut()
+        .zip(field_counts.iter())
+        .for_each(|(e, &n)| *e /= n as f64);
+    field_displacement
+        .lanes_mut(Axis(2))
+        .into_iter()
+        .zip(field_counts.iter())
+        .for_each(|(mut d, &n)| d /= n as f64);
+    Ok(RegistrationReport {
+        n_moving: moving.nrows(),
+        n_reference: reference.nrows(),
+        matches,
+        mean_displacement: displacements.mean_axis(Axis(0)).unwrap(),
+        displacements,
+        mean_error: errors.mean().unwrap(),
+        percentile_95_error: linear_percentile(&errors, 95.0, None, None, None)?[0],
+        max_error: errors.iter().copied().fold(f64::NEG_INFINITY, f64::max),
+        field_displacement,
+        field_error,
+        field_counts,
+    })
+}
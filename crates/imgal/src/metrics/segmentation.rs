@@ -0,0 +1,408 @@
+use std::collections::HashMap;
+
+use ndarray::{
+    Array2, ArrayBase, ArrayView1, AsArray, Axis, Dimension, IntoDimension, ViewRepr, Zip,
+};
+use rayon::prelude::*;
+
+use crate::label::boundaries;
+use crate::prelude::*;
+use crate::spatial::KDTree;
+use crate::validate::check_shapes;
+
+/// The evaluation scores of a predicted segmentation against a ground truth.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SegmentationScores {
+    /// The number of predicted objects.
+    pub n_pred: usize,
+    /// The number of ground truth objects.
+    pub n_truth: usize,
+    /// The mean intersection over union (IoU) of the matched objects over all
+    /// ground truth objects, unmatched ground truth objects count as `0.0`.
+    pub mean_iou: f64,
+    /// The mean Dice coefficient of the matched objects over all ground truth
+    /// objects, unmatched ground truth objects count as `0.0`.
+    pub mean_dice: f64,
+    /// The average precision `TP / (TP + FP + FN)` at each IoU threshold.
+    pub average_precision: Vec<f64>,
+    /// The number of ground truth objects split into two or more predicted
+    /// objects.
+    pub splits: usize,
+    /// The number of predicted objects merging two or more ground truth
+    /// objects.
+    pub merges: usize,
+}
+
+/// The label IDs and intersection over union output of `iou_matrix`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct IouMatrix {
+    /// The predicted label IDs in ascending order.
+    pub pred_labels: Vec<u64>,
+    /// The ground truth label IDs in ascending order.
+    pub truth_labels: Vec<u64>,
+    /// The IoU matrix of shape `(n_pred, n_truth)`.
+    pub iou: Array2<f64>,
+}
+
+/// Compute the intersection over union (IoU) matrix of two n-dimensional label
+/// images.
+///
+/// # Description
+///
+/// Computes the intersection over union of every pair of objects in a
+/// predicted and a ground truth label image:
+///
+/// ```text
+/// IoU(P, T) = |P ∩ T| / |P ∪ T|
+/// ```
+///
+/// The label `0` is the background.
+///
+/// # Arguments
+///
+/// * `pred`: The predicted n-dimensional label image.
+/// * `truth`: The ground truth n-dimensional label image.
+/// * `threads`: The requested number of threads to use for parallel execution.
+///   If `None` or `Some(1)` sequential execution is used. If `Some(0)`, then
+///   the maximum available parallelism is used. Thread counts are clamped to
+///   the systems maximum.
+///
+/// # Returns
+///
+/// * `Ok(IouMatrix)`: The predicted and ground truth label IDs in ascending
+///   order and the IoU matrix of shape `(n_pred, n_truth)`.
+/// * `Err(ImgalError)`: If the shapes of `pred` and `truth` do not match.
+pub fn iou_matrix<'a, A, D>(
+    pred: A,
+    truth: A,
+    threads: Option<usize>,
+) -> Result<IouMatrix, ImgalError>
+where
+    A: AsArray<'a, u64, D>,
+    D: Dimension,
+{
+    let overlap = Overlap::count(pred, truth, threads)?;
+    let iou = overlap.iou();
+    Ok(IouMatrix {
+        pred_labels: overlap.pred_labels,
+        truth_labels: overlap.truth_labels,
+        iou,
+    })
+}
+
+/// Evaluate a predicted n-dimensional label image against a ground truth.
+///
+/// # Description
+///
+/// Matches the objects of a predicted segmentation to the objects of a ground
+/// truth segmentation one-to-one, greedily in descending order of their
+/// intersection over union (IoU, see `iou_matrix`), and computes:
+///
+/// ```text
+/// Dice = 2 × IoU / (1 + IoU)
+/// AP(t) = TP(t) / (TP(t) + FP(t) + FN(t))
+/// ```
+///
+/// Where `TP(t)` is the number of matched pairs with `IoU >= t`, `FP(t)` and
+/// `FN(t)` are the remaining predicted and ground truth objects respectively.
+/// For thresholds `t >= 0.5` the greedy matching is optimal, as each object
+/// can overlap at most one other object with `IoU > 0.5`. A ground truth
+/// object is split if it contains the majority of the pixels of two or more
+/// predicted objects, and a predicted object is a merge if it contains the
+/// majority of the pixels of two or more ground truth objects. The label `0`
+/// is the background.
+///
+/// # Arguments
+///
+/// * `pred`: The predicted n-dimensional label image.
+/// * `truth`: The ground truth n-dimensional label image.
+/// * `thresholds`: The IoU thresholds of the average precision in the range
+///   `0.0` to `1.0`. If `None`, then `thresholds = [0.5, 0.55, ..., 0.95]`.
+/// * `threads`: The requested number of threads to use for parallel execution.
+///   If `None` or `Some(1)` sequential execution is used. If `Some(0)`, then
+///   the maximum available parallelism is used. Thread counts are clamped to
+///   the systems maximum.
+///
+/// # Returns
+///
+/// * `Ok(SegmentationScores)`: The evaluation scores. The mean IoU and Dice
+///   are `NaN` if `truth` has no objects, the average precision is `1.0` if
+///   neither image has objects.
+/// * `Err(ImgalError)`: If the shapes of `pred` and `truth` do not match. If a
+///   threshold is outside the range `0.0` to `1.0`.
+///
+/// # Reference
+///
+/// <https://doi.org/10.1038/s41592-019-0612-7>
+pub fn segmentation_scores<'a, A, D>(
+    pred: A,
+    truth: A,
+    thresholds: Option<&[f64]>,
+    threads: Option<usize>,
+) -> Result<SegmentationScores, ImgalError>
+where
+    A: AsArray<'a, u64, D>,
+    D: Dimension,
+{
+    let default_thresholds: Vec<f64> = (0..10).map(|i| 0.5 + 0.05 * i as f64).collect();
+    let thresholds = thresholds.unwrap_or(&default_thresholds);
+    if let Some(&t) = thresholds.iter().find(|t| !(0.0..=1.0).contains(*t)) {
+        return Err(ImgalError::InvalidParameterValueOutsideRange {
+            param_name: "thresholds",
+            value: t,
+            min: 0.0,
+            max: 1.0,
+        });
+    }
+    let overlap = Overlap::count(pred, truth, threads)?;
+    let (n_pred, n_truth) = (overlap.pred_labels.len(), overlap.truth_labels.len());
+    let matched = greedy_match(&overlap.iou());
+    let (iou_sum, dice_sum) = matched.iter().fold((0.0, 0.0), |acc, &(_, _, v)| {
+        (acc.0 + v, acc.1 + 2.0 * v / (1.0 + v))
+    });
+    let average_precision = thresholds
+        .iter()
+        .map(|&t| {
+            let tp = matched.iter().filter(|m| m.2 >= t).count();
+            let denom = n_pred + n_truth - tp;
+            if denom == 0 {
+                1.0
+            } else {
+                tp as f64 / denom as f64
+            }
+        })
+        .collect();
+    // each predicted object is assigned to the ground truth object holding the
+    // majority of its pixels and vice versa
+    let mut pred_per_truth = vec![0usize; n_truth];
+    let mut truth_per_pred = vec![0usize; n_pred];
+    overlap.inter.indexed_iter().for_each(|((p, t), &n)| {
+        if 2 * n > overlap.pred_sizes[p] {
+            pred_per_truth[t] += 1;
+        }
+        if 2 * n > overlap.truth_sizes[t] {
+            truth_per_pred[p] += 1;
+        }
+    });
+    let splits = pred_per_truth.iter().filter(|&&n| n >= 2).count();
+    let merges = truth_per_pred.iter().filter(|&&n| n >= 2).count();
+    Ok(SegmentationScores {
+        n_pred,
+        n_truth,
+        mean_iou: iou_sum / n_truth as f64,
+        mean_dice: dice_sum / n_truth as f64,
+        average_precision,
+        splits,
+        merges,
+    })
+}
+
+/// Compute the boundary F-score of a predicted n-dimensional label image
+/// against a ground truth.
+///
+/// # Description
+///
+/// Compares the object boundaries (see `label::boundaries`) of a predicted and
+/// a ground truth segmentation. A boundary pixel is matched if a boundary
+/// pixel of the other segmentation is within the Euclidean distance
+/// `tolerance`, which makes the score insensitive to small boundary shifts:
+///
+/// ```text
+/// precision = |matched predicted boundary pixels| / |predicted boundary pixels|
+/// recall = |matched ground truth boundary pixels| / |ground truth boundary pixels|
+/// F = 2 × precision × recall / (precision + recall)
+/// ```
+///
+/// # Arguments
+///
+/// * `pred`: The predicted n-dimensional label image.
+/// * `truth`: The ground truth n-dimensional label image.
+/// * `tolerance`: The maximum distance in pixels between matched boundary
+///   pixels. If `None`, then `tolerance = 2.0`.
+/// * `threads`: The requested number of threads to use for parallel execution.
+///   If `None` or `Some(1)` sequential execution is used. If `Some(0)`, then
+///   the maximum available parallelism is used. Thread counts are clamped to
+///   the systems maximum.
+///
+/// # Returns
+///
+/// * `Ok(f64)`: The boundary F-score in the range `0.0` to `1.0`. `1.0` if
+///   neither image has boundaries and `0.0` if only one of them does.
+/// * `Err(ImgalError)`: If the shapes of `pred` and `truth` do not match. If
+///   `tolerance < 0.0`.
+///
+/// # Reference
+///
+/// <https://doi.org/10.5244/C.27.32>
+pub fn boundary_f_score<'a, A, D>(
+    pred: A,
+    truth: A,
+    tolerance: Option<f64>,
+    threads: Option<usize>,
+) -> Result<f64, ImgalError>
+where
+    A: AsArray<'a, u64, D>,
+    D: Dimension,
+{
+    let pred: ArrayBase<ViewRepr<&'a u64>, D> = pred.into();
+    let truth: ArrayBase<ViewRepr<&'a u64>, D> = truth.into();
+    check_shapes("pred", pred.shape(), "truth", truth.shape())?;
+    let tolerance = tolerance.unwrap_or(2.0);
+    if tolerance.is_nan() || tolerance < 0.0 {
+        return Err(ImgalError::InvalidParameterValueOutsideRange {
+            param_name: "tolerance",
+            value: tolerance,
+            min: 0.0,
+            max: f64::INFINITY,
+        });
+    }
+    let ndim = pred.ndim();
+    let points = |labels: &ArrayBase<ViewRepr<&'a u64>, D>| -> Array2<f64> {
+        let bounds = boundaries(labels, None, threads);
+        let coords: Vec<f64> = bounds
+            .indexed_iter()
+            .filter(|(_, b)| **b)
+            .flat_map(|(p, _)| {
+                p.into_dimension()
+                    .slice()
+                    .to_vec()
+                    .into_iter()
+                    .map(|c| c as f64)
+            })
+            .collect();
+        Array2::from_shape_vec((coords.len() / ndim.max(1), ndim), coords).unwrap()
+    };
+    let pred_points = points(&pred);
+    let truth_points = points(&truth);
+    match (pred_points.nrows(), truth_points.nrows()) {
+        (0, 0) => return Ok(1.0),
+        (0, _) | (_, 0) => return Ok(0.0),
+        _ => {}
+    }
+    let matched_fraction = |query: &Array2<f64>, cloud: &Array2<f64>| -> Result<f64, ImgalError> {
+        let tree = KDTree::build(cloud);
+        let is_matched = |q: ArrayView1<f64>| -> Result<usize, ImgalError> {
+            Ok(!tree.search_for_indices(q, tolerance)?.is_empty() as usize)
+        };
+        let n: usize = par!(threads,
+            seq_exp: query.axis_iter(Axis(0)).map(is_matched).sum::<Result<usize, ImgalError>>(),
+            par_exp: query.axis_iter(Axis(0)).into_par_iter().map(is_matched)
+                .sum::<Result<usize, ImgalError>>())?;
+        Ok(n as f64 / query.nrows() as f64)
+    };
+    let precision = matched_fraction(&pred_points, &truth_points)?;
+    let recall = matched_fraction(&truth_points, &pred_points)?;
+    if precision + recall == 0.0 {
+        return Ok(0.0);
+    }
+    Ok(2.0 * precision * recall / (precision + recall))
+}
+
+/// The object sizes and pairwise intersections of two label images.
+struct Overlap {
+    pred_labels: Vec<u64>,
+    truth_labels: Vec<u64>,
+    pred_sizes: Vec<usize>,
+    truth_sizes: Vec<usize>,
+    inter: Array2<usize>,
+}
+
+impl Overlap {
+    /// Count the object sizes and intersections of two label images, the
+    /// labels are sorted in ascending order and the background is dropped.
+    fn count<'a, A, D>(pred: A, truth: A, threads: Option<usize>) -> Result<Self, ImgalError>
+    where
+        A: AsArray<'a, u64, D>,
+        D: Dimension,
+    {
+        let pred: ArrayBase<ViewRepr<&'a u64>, D> = pred.into();
+        let truth: ArrayBase<ViewRepr<&'a u64>, D> = truth.into();
+        check_shapes("pred", pred.shape(), "truth", truth.shape())?;
+        type Counts = (
+            HashMap<u64, usize>,
+            HashMap<u64, usize>,
+            HashMap<(u64, u64), usize>,
+        );
+        let count = |mut acc: Counts, &p: &u64, &t: &u64| {
+            if p != 0 {
+                *acc.0.entry(p).or_insert(0) += 1;
+            }
+            if t != 0 {
+                *acc.1.entry(t).or_insert(0) += 1;
+            }
+            if p != 0 && t != 0 {
+                *acc.2.entry((p, t)).or_insert(0) += 1;
+            }
+            acc
+        };
+        let merge = |mut a: Counts, b: Counts| {
+            b.0.into_iter()
+                .for_each(|(k, v)| *a.0.entry(k).or_insert(0) += v);
+            b.1.into_iter()
+                .for_each(|(k, v)| *a.1.entry(k).or_insert(0) += v);
+            b.2.into_iter()
+                .for_each(|(k, v)| *a.2.entry(k).or_insert(0) += v);
+            a
+        };
+        let empty = || (HashMap::new(), HashMap::new(), HashMap::new());
+        let (pred_map, truth_map, pairs) = par!(threads,
+            seq_exp: Zip::from(&pred).and(&truth).fold(empty(), count),
+            par_exp: Zip::from(&pred).and(&truth).par_fold(empty, count, merge));
+        let sorted = |sizes: &HashMap<u64, usize>| -> (Vec<u64>, Vec<usize>, HashMap<u64, usize>) {
+            let mut labels: Vec<u64> = sizes.keys().copied().collect();
+            labels.sort_unstable();
+            let counts = labels.iter().map(|k| sizes[k]).collect();
+            let index = labels.iter().enumerate().map(|(i, &k)| (k, i)).collect();
+            (labels, counts, index)
+        };
+        let (pred_labels, pred_sizes, pred_idx) = sorted(&pred_map);
+        let (truth_labels, truth_sizes, truth_idx) = sorted(&truth_map);
+        let mut inter = Array2::<usize>::zeros((pred_labels.len(), truth_labels.len()));
+        pairs
+            .iter()
+            .for_each(|(&(p, t), &n)| inter[[pred_idx[&p], truth_idx[&t]]] = n);
+        Ok(Self {
+            pred_labels,
+            truth_labels,
+            pred_sizes,
+            truth_sizes,
+            inter,
+        })
+    }
+
+    /// Compute the intersection over union matrix.
+    fn iou(&self) -> Array2<f64> {
+        let mut iou = Array2::<f64>::zeros(self.inter.raw_dim());
+        Zip::indexed(&mut iou)
+            .and(&self.inter)
+            .for_each(|(p, t), v, &n| {
+                let union = self.pred_sizes[p] + self.truth_sizes[t] - n;
+                *v = n as f64 / union as f64;
+            });
+        iou
+    }
+}
+
+/// Match the rows and columns of an IoU matrix one-to-one greedily in
+/// descending order of IoU, returning the `(row, col, iou)` matches.
+fn greedy_match(iou: &Array2<f64>) -> Vec<(usize, usize, f64)> {
+    let mut pairs: Vec<(usize, usize, f64)> = iou
+        .indexed_iter()
+        .filter(|(_, v)| **v > 0.0)
+        .map(|((r, c), &v)| (r, c, v))
+        .collect();
+    pairs.sort_by(|a, b| b.2.total_cmp(&a.2).then(a.0.cmp(&b.0)).then(a.1.cmp(&b.1)));
+    let mut row_used = vec![false; iou.nrows()];
+    let mut col_used = vec![false; iou.ncols()];
+    pairs
+        .into_iter()
+        .filter(|&(r, c, _)| {
+            if row_used[r] || col_used[c] {
+                return false;
+            }
+            row_used[r] = true;
+            col_used[c] = true;
+            true
+        })
+        .collect()
+}
@@ -0,0 +1,158 @@
+use std::collections::{BTreeMap, HashMap};
+
+use ndarray::Axis;
+
+use crate::metrics::detection::match_points;
+use crate::prelude::*;
+use crate::tracking::TrackTable;
+
+/// The evaluation scores of predicted tracks against ground truth tracks.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TrackingScores {
+    /// The multiple object tracking accuracy (MOTA), `NaN` if there are no
+    /// ground truth detections.
+    pub mota: f64,
+    /// The multiple object tracking precision (MOTP), the mean distance of the
+    /// matched detections. `NaN` if no detections are matched.
+    pub motp: f64,
+    /// The number of matched detection pairs.
+    pub true_positives: usize,
+    /// The number of unmatched predicted detections.
+    pub false_positives: usize,
+    /// The number of unmatched ground truth detections.
+    pub false_negatives: usize,
+    /// The number of identity switches, where a ground truth track is matched
+    /// to a different predicted track than at its previous match.
+    pub id_switches: usize,
+    /// The mean fraction of the detections of each predicted track matched to
+    /// its dominant ground truth track. `NaN` if there are no predicted
+    /// tracks.
+    pub track_purity: f64,
+    /// The mean fraction of the detections of each ground truth track matched
+    /// to its dominant predicted track. `NaN` if there are no ground truth
+    /// tracks.
+    pub target_effectiveness: f64,
+}
+
+/// Evaluate predicted tracks against ground truth tracks.
+///
+/// # Description
+///
+/// Matches the predicted detections to the ground truth detections in each
+/// frame one-to-one within `max_distance` (see
+/// `metrics::detection::match_points`) and scores the tracks with the CLEAR
+/// MOT metrics and the track purity:
+///
+/// ```text
+/// MOTA = 1 - (FN + FP + IDSW) / n_truth
+/// MOTP = Σ dᵢ / TP
+/// ```
+///
+/// Where `n_truth` is the number of ground truth detections and `IDSW` is the
+/// number of identity switches. The track purity is the mean over predicted
+/// tracks of the fraction of their detections matched to the same, dominant
+/// ground truth track, low purity indicates tracks that jump between objects.
+/// The target effectiveness is the same fraction over ground truth tracks,
+/// low effectiveness indicates broken tracks. Used to benchmark trackers
+/// against simulated ground truth trajectories (*e.g.* from
+/// `simulate_tracks`).
+///
+/// # Arguments
+///
+/// * `pred`: The predicted track table.
+/// * `truth`: The ground truth track table.
+/// * `max_distance`: The maximum distance between matched detections.
+///
+/// # Returns
+///
+/// * `Ok(TrackingScores)`: The tracking scores.
+/// * `Err(ImgalError)`: If the coordinates of `pred` and `truth` do not have
+///   the same dimensionality. If `max_distance < 0.0`.
+///
+/// # Reference
+///
+/// <https://doi.org/10.1155/2008/246309>
+pub fn tracking_scores(
+    pred: &TrackTable,
+    truth: &TrackTable,
+    max_distance: f64,
+) -> Result<TrackingScores, ImgalError> {
+    if !pred.is_empty() && !truth.is_empty() && pred.ndim() != truth.ndim() {
+        return Err(ImgalError::MismatchedArrayLengths {
+            a_arr_name: "pred.coords",
+            a_arr_len: pred.ndim(),
+            b_arr_name: "truth.coords",
+            b_arr_len: truth.ndim(),
+        });
+    }
+    if max_distance.is_nan() || max_distance < 0.0 {
+        return Err(ImgalError::InvalidParameterValueOutsideRange {
+            param_name: "max_distance",
+            value: max_distance,
+            min: 0.0,
+            max: f64::INFINITY,
+        });
+    }
+    // the detection rows of each frame
+    let mut frames: BTreeMap<usize, (Vec<usize>, Vec<usize>)> = BTreeMap::new();
+    pred.frame()
+        .iter()
+        .enumerate()
+        .for_each(|(i, &f)| frames.entry(f).or_default().0.push(i));
+    truth
+        .frame()
+        .iter()
+        .enumerate()
+        .for_each(|(i, &f)| frames.entry(f).or_default().1.push(i));
+    let (mut tp, mut dist_sum, mut id_switches) = (0, 0.0, 0);
+    let mut last_match: HashMap<u64, u64> = HashMap::new();
+    let mut co_occurrence: HashMap<(u64, u64), usize> = HashMap::new();
+    for (p_rows, t_rows) in frames.values() {
+        let p_coords = pred.coords().select(Axis(0), p_rows);
+        let t_coords = truth.coords().select(Axis(0), t_rows);
+        for (pi, ti, d) in match_points(&p_coords, &t_coords, max_distance)? {
+            let p_track = pred.track_id()[p_rows[pi]];
+            let t_track = truth.track_id()[t_rows[ti]];
+            if let Some(prev) = last_match.insert(t_track, p_track)
+                && prev != p_track
+            {
+                id_switches += 1;
+            }
+            *co_occurrence.entry((p_track, t_track)).or_insert(0) += 1;
+            tp += 1;
+            dist_sum += d;
+        }
+    }
+    let (n_pred, n_truth) = (pred.len(), truth.len());
+    let dominant_fraction = |lengths: HashMap<u64, usize>, pred_side: bool| -> f64 {
+        let mut best: HashMap<u64, usize> = HashMap::new();
+        co_occurrence.iter().for_each(|(&(p, t), &n)| {
+            let k = if pred_side { p } else { t };
+            let b = best.entry(k).or_insert(0);
+            *b = (*b).max(n);
+        });
+        let sum: f64 = lengths
+            .iter()
+            .map(|(k, &len)| best.get(k).copied().unwrap_or(0) as f64 / len as f64)
+            .sum();
+        sum / lengths.len() as f64
+    };
+    let ratio = |num: f64, denom: usize| {
+        if denom != 0 {
+            num / denom as f64
+        } else {
+            f64::NAN
+        }
+    };
+    let (fp, fn_) = (n_pred - tp, n_truth - tp);
+    Ok(TrackingScores {
+        mota: 1.0 - ratio((fn_ + fp + id_switches) as f64, n_truth),
+        motp: ratio(dist_sum, tp),
+        true_positives: tp,
+        false_positives: fp,
+        false_negatives: fn_,
+        id_switches,
+        track_purity: dominant_fraction(pred.track_lengths(), true),
+        target_effectiveness: dominant_fraction(truth.track_lengths(), false),
+    })
+}
@@ -0,0 +1,189 @@
+use ndarray::{Array, ArrayView, AsArray, Dimension};
+
+use crate::morphology::element::footprint_reduce;
+use crate::prelude::*;
+
+/// Erode an n-dimensional boolean mask.
+///
+/// # Description
+///
+/// Sets each pixel of the mask to `true` only if every pixel of its
+/// neighborhood, given by a boolean structuring element `footprint` (*e.g.* a
+/// circle or sphere from `kernel::neighborhood`) centered on the pixel, is
+/// `true`:
+///
+/// ```text
+/// (A ⊖ B)(x) = ∧ A(x + b), b ∈ B
+/// ```
+///
+/// Erosion shrinks objects and removes objects and protrusions smaller than
+/// the footprint. Neighbors outside of the mask are ignored, so objects
+/// touching the border are not eroded from the border.
+///
+/// # Arguments
+///
+/// * `data`: The input n-dimensional boolean mask.
+/// * `footprint`: The boolean structuring element with the same dimensionality
+///   as `data` and an odd length along each axis, centered on the pixel.
+/// * `threads`: The requested number of threads to use for parallel execution.
+///   If `None` or `Some(1)` sequential execution is used. If `Some(0)`, then
+///   the maximum available parallelism is used. Thread counts are clamped to
+///   the systems maximum.
+///
+/// # Returns
+///
+/// * `Ok(Array<bool, D>)`: The eroded mask.
+/// * `Err(ImgalError)`: If `footprint` and `data` do not have the same number
+///   of dimensions. If an axis length of `footprint` is even. If `footprint`
+///   has no `true` values.
+pub fn erode<'a, A, D>(
+    data: A,
+    footprint: ArrayView<bool, D>,
+    threads: Option<usize>,
+) -> Result<Array<bool, D>, ImgalError>
+where
+    A: AsArray<'a, bool, D>,
+    D: Dimension,
+{
+    let data: ArrayView<bool, D> = data.into();
+    let eroded = footprint_reduce(data.mapv(u8::from).view(), footprint, 1, threads, |a, b| {
+        a & b
+    })?;
+    Ok(eroded.mapv(|v| v != 0))
+}
+
+/// Dilate an n-dimensional boolean mask.
+///
+/// # Description
+///
+/// Sets each pixel of the mask to `true` if any pixel of its neighborhood,
+/// given by a boolean structuring element `footprint` centered on the pixel,
+/// is `true`:
+///
+/// ```text
+/// (A ⊕ B)(x) = ∨ A(x + b), b ∈ B
+/// ```
+///
+/// Dilation grows objects and fills holes and gaps smaller than the
+/// footprint. Neighbors outside of the mask are ignored (*i.e.* `false`). For
+/// a footprint that is not symmetric about its center, the footprint is not
+/// reflected.
+///
+/// # Arguments
+///
+/// * `data`: The input n-dimensional boolean mask.
+/// * `footprint`: The boolean structuring element with the same dimensionality
+///   as `data` and an odd length along each axis, centered on the pixel.
+/// * `threads`: The requested number of threads to use for parallel execution.
+///   If `None` or `Some(1)` sequential execution is used. If `Some(0)`, then
+///   the maximum available parallelism is used. Thread counts are clamped to
+///   the systems maximum.
+///
+/// # Returns
+///
+/// * `Ok(Array<bool, D>)`: The dilated mask.
+/// * `Err(ImgalError)`: If `footprint` and `data` do not have the same number
+///   of dimensions. If an axis length of `footprint` is even. If `footprint`
+///   has no `true` values.
+pub fn dilate<'a, A, D>(
+    data: A,
+    footprint: ArrayView<bool, D>,
+    threads: Option<usize>,
+) -> Result<Array<bool, D>, ImgalError>
+where
+    A: AsArray<'a, bool, D>,
+    D: Dimension,
+{
+    let data: ArrayView<bool, D> = data.into();
+    let dilated = footprint_reduce(data.mapv(u8::from).view(), footprint, 0, threads, |a, b| {
+        a | b
+    })?;
+    Ok(dilated.mapv(|v| v != 0))
+}
+
+/// Open an n-dimensional boolean mask.
+///
+/// # Description
+///
+/// Computes the morphological opening, an erosion followed by a dilation with
+/// the same `footprint`:
+///
+/// ```text
+/// A ∘ B = (A ⊖ B) ⊕ B
+/// ```
+///
+/// Opening removes objects and protrusions smaller than the footprint (*e.g.*
+/// noise specks of a threshold mask) while preserving the shape of larger
+/// objects.
+///
+/// # Arguments
+///
+/// * `data`: The input n-dimensional boolean mask.
+/// * `footprint`: The boolean structuring element with the same dimensionality
+///   as `data` and an odd length along each axis, centered on the pixel.
+/// * `threads`: The requested number of threads to use for parallel execution.
+///   If `None` or `Some(1)` sequential execution is used. If `Some(0)`, then
+///   the maximum available parallelism is used. Thread counts are clamped to
+///   the systems maximum.
+///
+/// # Returns
+///
+/// * `Ok(Array<bool, D>)`: The opened mask.
+/// * `Err(ImgalError)`: If `footprint` and `data` do not have the same number
+///   of dimensions. If an axis length of `footprint` is even. If `footprint`
+///   has no `true` values.
+pub fn open<'a, A, D>(
+    data: A,
+    footprint: ArrayView<bool, D>,
+    threads: Option<usize>,
+) -> Result<Array<bool, D>, ImgalError>
+where
+    A: AsArray<'a, bool, D>,
+    D: Dimension,
+{
+    let eroded = erode(data, footprint.view(), threads)?;
+    dilate(&eroded, footprint, threads)
+}
+
+/// Close an n-dimensional boolean mask.
+///
+/// # Description
+///
+/// Computes the morphological closing, a dilation followed by an erosion with
+/// the same `footprint`:
+///
+/// ```text
+/// A • B = (A ⊕ B) ⊖ B
+/// ```
+///
+/// Closing fills holes and gaps smaller than the footprint (*e.g.* dark
+/// nuclei inside a cell mask) while preserving the shape of larger objects.
+///
+/// # Arguments
+///
+/// * `data`: The input n-dimensional boolean mask.
+/// * `footprint`: The boolean structuring element with the same dimensionality
+///   as `data` and an odd length along each axis, centered on the pixel.
+/// * `threads`: The requested number of threads to use for parallel execution.
+///   If `None` or `Some(1)` sequential execution is used. If `Some(0)`, then
+///   the maximum available parallelism is used. Thread counts are clamped to
+///   the systems maximum.
+///
+/// # Returns
+///
+/// * `Ok(Array<bool, D>)`: The closed mask.
+/// * `Err(ImgalError)`: If `footprint` and `data` do not have the same number
+///   of dimensions. If an axis length of `footprint` is even. If `footprint`
+///   has no `true` values.
+pub fn close<'a, A, D>(
+    data: A,
+    footprint: ArrayView<bool, D>,
+    threads: Option<usize>,
+) -> Result<Array<bool, D>, ImgalError>
+where
+    A: AsArray<'a, bool, D>,
+    D: Dimension,
+{
+    let dilated = dilate(data, footprint.view(), threads)?;
+    erode(&dilated, footprint, threads)
+}
@@ -0,0 +1,158 @@
+use ndarray::{Array, ArrayBase, AsArray, Dimension, ViewRepr};
+use rayon::prelude::*;
+
+/// The pixel connectivity of connected components.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum Connectivity {
+    /// Pixels sharing a face are connected, the `2 × ndim` axis aligned
+    /// neighbors (*i.e.* 4-connectivity in 2D and 6-connectivity in 3D).
+    #[default]
+    Face,
+    /// Pixels sharing a face, an edge or a corner are connected, the
+    /// `3ⁿᵈⁱᵐ - 1` neighbors (*i.e.* 8-connectivity in 2D and 26-connectivity
+    /// in 3D).
+    Full,
+}
+
+/// Label the connected components of an n-dimensional boolean mask.
+///
+/// # Description
+///
+/// Assigns each connected set of `true` pixels of a mask (*e.g.* from
+/// `threshold::otsu_mask`) a unique positive label, the background is `0`.
+/// The components are found in two raster scans with a union-find structure,
+/// the first scan assigns provisional labels and records the equivalences of
+/// the labels meeting at a pixel, the second scan replaces each provisional
+/// label with the label of its component. The labels are consecutive from `1`
+/// in the raster order of the first pixel of each component, so the maximum
+/// label is the number of components. The label image can be used directly
+/// with `spatial::roi::roi_cloud_map` and the `label` module.
+///
+/// # Arguments
+///
+/// * `data`: The input n-dimensional boolean mask.
+/// * `connectivity`: The pixel connectivity of the components. If `None`, then
+///   `connectivity = Connectivity::Face`.
+/// * `threads`: The requested number of threads to use for parallel execution.
+///   If `None` or `Some(1)` sequential execution is used. If `Some(0)`, then
+///   the maximum available parallelism is used. Thread counts are clamped to
+///   the systems maximum.
+///
+/// # Returns
+///
+/// * `Array<u64, D>`: The label image with the same shape as `data`.
+pub fn label<'a, A, D>(
+    data: A,
+    connectivity: Option<Connectivity>,
+    threads: Option<usize>,
+) -> Array<u64, D>
+where
+    A: AsArray<'a, bool, D>,
+    D: Dimension,
+{
+    let data: ArrayBase<ViewRepr<&'a bool>, D> = data.into();
+    let shape = data.shape().to_vec();
+    let ndim = shape.len();
+    let mask: Vec<bool> = data.iter().copied().collect();
+    // the row major strides and the offsets of the neighbors preceding a pixel
+    // in the raster order
+    let mut strides = vec![1isize; ndim];
+    for ax in (0..ndim.saturating_sub(1)).rev() {
+        strides[ax] = strides[ax + 1] * shape[ax + 1] as isize;
+    }
+    let full = connectivity.unwrap_or_default() == Connectivity::Full;
+    let neighbors: Vec<(Vec<isize>, isize)> = (0..3usize.pow(ndim as u32))
+        .map(|code| {
+            let mut rem = code;
+            let mut step = vec![0isize; ndim];
+            for s in step.iter_mut().rev() {
+                *s = (rem % 3) as isize - 1;
+                rem /= 3;
+            }
+            step
+        })
+        .filter(|step| {
+            let n_nonzero = step.iter().filter(|&&s| s != 0).count();
+            step.iter().find(|&&s| s != 0) == Some(&-1) && (full || n_nonzero == 1)
+        })
+        .map(|step| {
+            let offset = step.iter().zip(strides.iter()).map(|(s, st)| s * st).sum();
+            (step, offset)
+        })
+        .collect();
+    // first scan, the parent of each provisional label with the smallest label
+    // of a component as its root
+    let mut parent: Vec<u64> = vec![0];
+    let find = |parent: &mut Vec<u64>, mut l: u64| -> u64 {
+        while parent[l as usize] != l {
+            parent[l as usize] = parent[parent[l as usize] as usize];
+            l = parent[l as usize];
+        }
+        l
+    };
+    let mut provisional = vec![0u64; mask.len()];
+    let mut idx = vec![0usize; ndim];
+    for (i, &m) in mask.iter().enumerate() {
+        if m {
+            let mut current = 0;
+            for (step, offset) in neighbors.iter() {
+                let inside =
+                    idx.iter()
+                        .zip(step.iter())
+                        .zip(shape.iter())
+                        .all(|((&p, &s), &len)| {
+                            let q = p as isize + s;
+                            q >= 0 && q < len as isize
+                        });
+                if !inside {
+                    continue;
+                }
+                let l = provisional[(i as isize + offset) as usize];
+                if l == 0 {
+                    continue;
+                }
+                let root = find(&mut parent, l);
+                if current == 0 {
+                    current = root;
+                } else if root != current {
+                    let (lo, hi) = (root.min(current), root.max(current));
+                    parent[hi as usize] = lo;
+                    current = lo;
+                }
+            }
+            if current == 0 {
+                current = parent.len() as u64;
+                parent.push(current);
+            }
+            provisional[i] = current;
+        }
+        // advance the multi-index in row major order
+        for ax in (0..ndim).rev() {
+            idx[ax] += 1;
+            if idx[ax] < shape[ax] {
+                break;
+            }
+            idx[ax] = 0;
+        }
+    }
+    // the consecutive label of each provisional label, roots are visited
+    // before the labels of their component
+    let mut remap = vec![0u64; parent.len()];
+    let mut n_labels = 0;
+    for l in 1..parent.len() as u64 {
+        let root = find(&mut parent, l);
+        if root == l {
+            n_labels += 1;
+            remap[l as usize] = n_labels;
+        } else {
+            remap[l as usize] = remap[root as usize];
+        }
+    }
+    // second scan
+    let relabel = |v: &mut u64| *v = remap[*v as usize];
+    par!(threads,
+        seq_exp: provisional.iter_mut().for_each(relabel),
+        par_exp: provisional.par_iter_mut().for_each(relabel));
+    Array::from_shape_vec(data.raw_dim(), provisional)
+        .expect("Failed to reshape the labels into the input shape.")
+}
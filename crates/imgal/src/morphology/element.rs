@@ -0,0 +1,41 @@
+use ndarray::{Array, ArrayBase, ArrayView, Dimension, ViewRepr};
+
+use crate::filter::{BorderMode, FootprintScan};
+use crate::prelude::*;
+
+/// Reduce the neighborhood of each pixel of an n-dimensional array, given by
+/// a structuring element `footprint` centered on the pixel, with a binary
+/// operation (*e.g.* `min` for an erosion). The array is padded with `pad`,
+/// the neutral value of the operation, so neighbors outside of the array are
+/// ignored.
+pub(super) fn footprint_reduce<T, D, F>(
+    data: ArrayBase<ViewRepr<&T>, D>,
+    footprint: ArrayView<bool, D>,
+    pad: T,
+    threads: Option<usize>,
+    reduce: F,
+) -> Result<Array<T, D>, ImgalError>
+where
+    D: Dimension,
+    T: AsNumeric,
+    F: Fn(T, T) -> T + Send + Sync,
+{
+    let scan = FootprintScan::new(data, footprint, BorderMode::Constant(pad.to_f64()), threads)?;
+    let src = scan.src();
+    let offsets = scan.offsets();
+    let step = scan.step();
+    Ok(scan.scan(
+        threads,
+        || (),
+        |_, base, lane: &mut [T]| {
+            lane.iter_mut().enumerate().for_each(|(x, v)| {
+                let pos = base + x as isize * step;
+                *v = offsets[1..]
+                    .iter()
+                    .fold(src[(pos + offsets[0]) as usize], |acc, &o| {
+                        reduce(acc, src[(pos + o) as usize])
+                    });
+            });
+        },
+    ))
+}
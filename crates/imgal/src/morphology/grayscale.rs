@@ -0,0 +1,249 @@
+use ndarray::{Array, ArrayView, AsArray, Dimension, Zip};
+
+use crate::morphology::element::footprint_reduce;
+use crate::prelude::*;
+
+/// Erode an n-dimensional grayscale image.
+///
+/// # Description
+///
+/// Replaces each pixel with the minimum of its neighborhood, given by a flat
+/// boolean structuring element `footprint` (*e.g.* a circle or sphere from
+/// `kernel::neighborhood`) centered on the pixel:
+///
+/// ```text
+/// (f ⊖ B)(x) = min f(x + b), b ∈ B
+/// ```
+///
+/// Grayscale erosion shrinks bright objects and removes bright details
+/// smaller than the footprint. Neighbors outside of the image are ignored, as
+/// with the binary `erode`. Unlike `filter::minimum_filter`, the image is not
+/// reflected at its borders.
+///
+/// # Arguments
+///
+/// * `data`: The input n-dimensional image.
+/// * `footprint`: The boolean structuring element with the same dimensionality
+///   as `data` and an odd length along each axis, centered on the pixel.
+/// * `threads`: The requested number of threads to use for parallel execution.
+///   If `None` or `Some(1)` sequential execution is used. If `Some(0)`, then
+///   the maximum available parallelism is used. Thread counts are clamped to
+///   the systems maximum.
+///
+/// # Returns
+///
+/// * `Ok(Array<T, D>)`: The eroded image.
+/// * `Err(ImgalError)`: If `footprint` and `data` do not have the same number
+///   of dimensions. If an axis length of `footprint` is even. If `footprint`
+///   has no `true` values.
+pub fn grayscale_erode<'a, T, A, D>(
+    data: A,
+    footprint: ArrayView<bool, D>,
+    threads: Option<usize>,
+) -> Result<Array<T, D>, ImgalError>
+where
+    A: AsArray<'a, T, D>,
+    D: Dimension,
+    T: 'a + AsNumeric,
+{
+    footprint_reduce(data.into(), footprint, T::MAX, threads, |a, b| {
+        if b < a { b } else { a }
+    })
+}
+
+/// Dilate an n-dimensional grayscale image.
+///
+/// # Description
+///
+/// Replaces each pixel with the maximum of its neighborhood, given by a flat
+/// boolean structuring element `footprint` centered on the pixel:
+///
+/// ```text
+/// (f ⊕ B)(x) = max f(x + b), b ∈ B
+/// ```
+///
+/// Grayscale dilation grows bright objects and fills dark details smaller than
+/// the footprint. Neighbors outside of the image are ignored. For a footprint
+/// that is not symmetric about its center, the footprint is not reflected.
+///
+/// # Arguments
+///
+/// * `data`: The input n-dimensional image.
+/// * `footprint`: The boolean structuring element with the same dimensionality
+///   as `data` and an odd length along each axis, centered on the pixel.
+/// * `threads`: The requested number of threads to use for parallel execution.
+///   If `None` or `Some(1)` sequential execution is used. If `Some(0)`, then
+///   the maximum available parallelism is used. Thread counts are clamped to
+///   the systems maximum.
+///
+/// # Returns
+///
+/// * `Ok(Array<T, D>)`: The dilated image.
+/// * `Err(ImgalError)`: If `footprint` and `data` do not have the same number
+///   of dimensions. If an axis length of `footprint` is even. If `footprint`
+///   has no `true` values.
+pub fn grayscale_dilate<'a, T, A, D>(
+    data: A,
+    footprint: ArrayView<bool, D>,
+    threads: Option<usize>,
+) -> Result<Array<T, D>, ImgalError>
+where
+    A: AsArray<'a, T, D>,
+    D: Dimension,
+    T: 'a + AsNumeric,
+{
+    footprint_reduce(data.into(), footprint, T::MIN, threads, |a, b| {
+        if b > a { b } else { a }
+    })
+}
+
+/// Open an n-dimensional grayscale image.
+///
+/// # Description
+///
+/// Computes the grayscale opening, an erosion followed by a dilation with the
+/// same `footprint`:
+///
+/// ```text
+/// f ∘ B = (f ⊖ B) ⊕ B
+/// ```
+///
+/// Opening removes bright details smaller than the footprint (*e.g.* spots)
+/// while preserving larger bright structures, the opening subtracted from the
+/// image is the white top-hat transform.
+///
+/// # Arguments
+///
+/// * `data`: The input n-dimensional image.
+/// * `footprint`: The boolean structuring element with the same dimensionality
+///   as `data` and an odd length along each axis, centered on the pixel.
+/// * `threads`: The requested number of threads to use for parallel execution.
+///   If `None` or `Some(1)` sequential execution is used. If `Some(0)`, then
+///   the maximum available parallelism is used. Thread counts are clamped to
+///   the systems maximum.
+///
+/// # Returns
+///
+/// * `Ok(Array<T, D>)`: The opened image.
+/// * `Err(ImgalError)`: If `footprint` and `data` do not have the same number
+///   of dimensions. If an axis length of `footprint` is even. If `footprint`
+///   has no `true` values.
+pub fn grayscale_open<'a, T, A, D>(
+    data: A,
+    footprint: ArrayView<bool, D>,
+    threads: Option<usize>,
+) -> Result<Array<T, D>, ImgalError>
+where
+    A: AsArray<'a, T, D>,
+    D: Dimension,
+    T: 'a + AsNumeric,
+{
+    let eroded = grayscale_erode(data, footprint.view(), threads)?;
+    grayscale_dilate(&eroded, footprint, threads)
+}
+
+/// Close an n-dimensional grayscale image.
+///
+/// # Description
+///
+/// Computes the grayscale closing, a dilation followed by an erosion with the
+/// same `footprint`:
+///
+/// ```text
+/// f • B = (f ⊕ B) ⊖ B
+/// ```
+///
+/// Closing removes dark details smaller than the footprint (*e.g.* gaps in
+/// filaments) while preserving larger dark structures.
+///
+/// # Arguments
+///
+/// * `data`: The input n-dimensional image.
+/// * `footprint`: The boolean structuring element with the same dimensionality
+///   as `data` and an odd length along each axis, centered on the pixel.
+/// * `threads`: The requested number of threads to use for parallel execution.
+///   If `None` or `Some(1)` sequential execution is used. If `Some(0)`, then
+///   the maximum available parallelism is used. Thread counts are clamped to
+///   the systems maximum.
+///
+/// # Returns
+///
+/// * `Ok(Array<T, D>)`: The closed image.
+/// * `Err(ImgalError)`: If `footprint` and `data` do not have the same number
+///   of dimensions. If an axis length of `footprint` is even. If `footprint`
+///   has no `true` values.
+pub fn grayscale_close<'a, T, A, D>(
+    data: A,
+    footprint: ArrayView<bool, D>,
+    threads: Option<usize>,
+) -> Result<Array<T, D>, ImgalError>
+where
+    A: AsArray<'a, T, D>,
+    D: Dimension,
+    T: 'a + AsNumeric,
+{
+    let dilated = grayscale_dilate(data, footprint.view(), threads)?;
+    grayscale_erode(&dilated, footprint, threads)
+}
+
+/// Compute the morphological gradient of an n-dimensional grayscale image.
+///
+/// # Description
+///
+/// Computes the difference of the grayscale dilation and erosion with the same
+/// `footprint`, the range of values in the neighborhood of each pixel:
+///
+/// ```text
+/// ∇f = (f ⊕ B) - (f ⊖ B)
+/// ```
+///
+/// The morphological gradient highlights the edges of objects with a
+/// thickness of about the footprint size and is `0` in flat regions. The
+/// difference is computed exactly for integer types and returned as `f64`, as
+/// the range of a signed image does not fit its type (*e.g.* `127 - (-128)`
+/// for `i8`).
+///
+/// # Arguments
+///
+/// * `data`: The input n-dimensional image.
+/// * `footprint`: The boolean structuring element with the same dimensionality
+///   as `data` and an odd length along each axis, centered on the pixel.
+/// * `threads`: The requested number of threads to use for parallel execution.
+///   If `None` or `Some(1)` sequential execution is used. If `Some(0)`, then
+///   the maximum available parallelism is used. Thread counts are clamped to
+///   the systems maximum.
+///
+/// # Returns
+///
+/// * `Ok(Array<f64, D>)`: The morphological gradient.
+/// * `Err(ImgalError)`: If `footprint` and `data` do not have the same number
+///   of dimensions. If an axis length of `footprint` is even. If `footprint`
+///   has no `true` values.
+pub fn morphological_gradient<'a, T, A, D>(
+    data: A,
+    footprint: ArrayView<bool, D>,
+    threads: Option<usize>,
+) -> Result<Array<f64, D>, ImgalError>
+where
+    A: AsArray<'a, T, D>,
+    D: Dimension,
+    T: 'a + AsNumeric,
+{
+    let data: ArrayView<T, D> = data.into();
+    let dilated = grayscale_dilate(data.view(), footprint.view(), threads)?;
+    let eroded = grayscale_erode(data, footprint, threads)?;
+    // a neighborhood entirely outside of the image has no range
+    let range = |&d: &T, &e: &T| -> f64 {
+        if d > e {
+            match (d.to_i128(), e.to_i128()) {
+                (Some(d), Some(e)) => (d - e) as f64,
+                _ => d.to_f64() - e.to_f64(),
+            }
+        } else {
+            0.0
+        }
+    };
+    Ok(par!(threads,
+        seq_exp: Zip::from(&dilated).and(&eroded).map_collect(range),
+        par_exp: Zip::from(&dilated).and(&eroded).par_map_collect(range)))
+}
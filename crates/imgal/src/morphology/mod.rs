@@ -0,0 +1,18 @@
+//! Mathematical morphology functions.
+//!
+//! This module provides *n*-dimensional binary (*e.g.* for the post-processing
+//! of threshold masks) and grayscale morphology functions with structuring
+//! elements from `kernel::neighborhood`, and the connected component labeling
+//! of masks.
+
+mod binary;
+mod components;
+mod element;
+mod grayscale;
+
+pub use binary::{close, dilate, erode, open};
+pub use components::Connectivity;
+pub use components::label;
+pub use grayscale::{
+    grayscale_close, grayscale_dilate, grayscale_erode, grayscale_open, morphological_gradient,
+};
@@ -0,0 +1,170 @@
+use ndarray::{Array1, ArrayBase, ArrayView1, AsArray, Ix1, ViewRepr, Zip};
+
+use crate::optimize::CsrMatrix;
+use crate::prelude::*;
+
+/// The solution and convergence output of `conjugate_gradient`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConjugateGradientOutput {
+    /// The solution vector `x`.
+    pub x: Array1<f64>,
+    /// The number of iterations performed.
+    pub iterations: usize,
+    /// The relative residual norm `‖b - A × x‖ / ‖b‖` of the solution.
+    pub residual: f64,
+    /// `true` if the relative residual reached the tolerance.
+    pub converged: bool,
+}
+
+/// Solve a sparse symmetric positive definite linear system with the
+/// preconditioned conjugate gradient method.
+///
+/// # Description
+///
+/// Iteratively solves the linear system `A × x = b` for a sparse symmetric
+/// positive definite matrix `A` (*e.g.* a graph Laplacian with boundary
+/// conditions in random walker segmentation, or the discrete Poisson equation
+/// in gradient-domain fusion) with the conjugate gradient method and a Jacobi
+/// (diagonal) preconditioner `M = diag(A)`:
+///
+/// ```text
+/// r₀ = b - A × x₀,  z₀ = M⁻¹ × r₀,  p₀ = z₀
+/// αₖ = rₖ · zₖ / (pₖ · A × pₖ)
+/// xₖ₊₁ = xₖ + αₖ × pₖ
+/// rₖ₊₁ = rₖ - αₖ × A × pₖ
+/// zₖ₊₁ = M⁻¹ × rₖ₊₁
+/// pₖ₊₁ = zₖ₊₁ + (rₖ₊₁ · zₖ₊₁ / rₖ · zₖ) × pₖ
+/// ```
+///
+/// The iterations stop when the relative residual `‖rₖ‖ / ‖b‖` is less than
+/// or equal to `tolerance`, or after `max_iterations`. Only matrix-vector
+/// products are needed, so the cost per iteration is proportional to the
+/// number of stored values. The symmetry of `A` is not checked.
+///
+/// # Arguments
+///
+/// * `a`: The sparse symmetric positive definite square matrix.
+/// * `b`: The right-hand side vector with length `n`.
+/// * `x0`: The initial guess of the solution with length `n` (*e.g.* the
+///   solution of a previous, similar system). If `None`, then `x0 = 0`.
+/// * `tolerance`: The relative residual tolerance. If `None`, then
+///   `tolerance = 1e-8`.
+/// * `max_iterations`: The maximum number of iterations. If `None`, then
+///   `max_iterations = max(n, 100)`.
+/// * `threads`: The requested number of threads to use for parallel execution.
+///   If `None` or `Some(1)` sequential execution is used. If `Some(0)`, then
+///   the maximum available parallelism is used. Thread counts are clamped to
+///   the systems maximum.
+///
+/// # Returns
+///
+/// * `Ok(ConjugateGradientOutput)`: The solution, the number of iterations,
+///   the relative residual and whether the tolerance was reached.
+/// * `Err(ImgalError)`: If `a` is not square. If the lengths of `b` or `x0`
+///   do not match the size of `a`. If a diagonal value of `a` is not
+///   positive. If `tolerance < 0.0`.
+///
+/// # Reference
+///
+/// <https://doi.org/10.6028/jres.049.044>
+pub fn conjugate_gradient<'a, A>(
+    a: &CsrMatrix,
+    b: A,
+    x0: Option<ArrayView1<f64>>,
+    tolerance: Option<f64>,
+    max_iterations: Option<usize>,
+    threads: Option<usize>,
+) -> Result<ConjugateGradientOutput, ImgalError>
+where
+    A: AsArray<'a, f64, Ix1>,
+{
+    let b: ArrayBase<ViewRepr<&'a f64>, Ix1> = b.into();
+    let (n, n_cols) = a.shape();
+    if n != n_cols {
+        return Err(ImgalError::InvalidGeneric {
+            msg: "Invalid conjugate gradient matrix, the matrix must be square.",
+        });
+    }
+    if b.len() != n {
+        return Err(ImgalError::InvalidArrayLengthExpected {
+            arr_name: "b",
+            expected: n,
+            got: b.len(),
+        });
+    }
+    let tolerance = tolerance.unwrap_or(1e-8);
+    if tolerance.is_nan() || tolerance < 0.0 {
+        return Err(ImgalError::InvalidParameterValueOutsideRange {
+            param_name: "tolerance",
+            value: tolerance,
+            min: 0.0,
+            max: f64::INFINITY,
+        });
+    }
+    let max_iterations = max_iterations.unwrap_or(n.max(100));
+    let diag = a.diagonal();
+    if diag.iter().any(|d| d.is_nan() || *d <= 0.0) {
+        return Err(ImgalError::InvalidGeneric {
+            msg: "Invalid conjugate gradient matrix, the diagonal values must be positive.",
+        });
+    }
+    let mut x = match x0 {
+        Some(x0) => {
+            if x0.len() != n {
+                return Err(ImgalError::InvalidArrayLengthExpected {
+                    arr_name: "x0",
+                    expected: n,
+                    got: x0.len(),
+                });
+            }
+            x0.to_owned()
+        }
+        None => Array1::<f64>::zeros(n),
+    };
+    let b_norm = b.dot(&b).sqrt();
+    if b_norm == 0.0 {
+        // the unique solution of a positive definite system is zero
+        return Ok(ConjugateGradientOutput {
+            x: Array1::zeros(n),
+            iterations: 0,
+            residual: 0.0,
+            converged: true,
+        });
+    }
+    let mut ap = a.dot(&x, threads)?;
+    let mut r = &b - &ap;
+    let mut z = &r / &diag;
+    let mut p = z.clone();
+    let mut rz = r.dot(&z);
+    let mut residual = r.dot(&r).sqrt() / b_norm;
+    let mut iterations = 0;
+    while residual > tolerance && iterations < max_iterations {
+        a.dot_into(p.as_slice().unwrap(), ap.as_slice_mut().unwrap(), threads);
+        let p_ap = p.dot(&ap);
+        if p_ap <= 0.0 {
+            // the matrix is not positive definite along the search direction
+            break;
+        }
+        let alpha = rz / p_ap;
+        x.scaled_add(alpha, &p);
+        r.scaled_add(-alpha, &ap);
+        Zip::from(&mut z)
+            .and(&r)
+            .and(&diag)
+            .for_each(|z, &r, &d| *z = r / d);
+        let rz_next = r.dot(&z);
+        let beta = rz_next / rz;
+        rz = rz_next;
+        Zip::from(&mut p)
+            .and(&z)
+            .for_each(|p, &z| *p = z + beta * *p);
+        residual = r.dot(&r).sqrt() / b_norm;
+        iterations += 1;
+    }
+    Ok(ConjugateGradientOutput {
+        x,
+        iterations,
+        residual,
+        converged: residual <= tolerance,
+    })
+}
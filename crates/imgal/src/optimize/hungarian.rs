@@ -0,0 +1,130 @@
+use ndarray::{Array2, ArrayBase, AsArray, Ix2, ViewRepr};
+
+use crate::prelude::*;
+
+/// Solve the rectangular linear assignment problem with the Hungarian
+/// algorithm.
+///
+/// # Description
+///
+/// Assigns the rows of a `(n, m)` cost matrix to its columns one-to-one such
+/// that the total cost of the assigned pairs is minimal:
+///
+/// ```text
+/// min Σ C(i, σ(i))
+/// ```
+///
+/// Where `σ` assigns each of the `min(n, m)` rows (or columns, if there are
+/// fewer columns) to a distinct column (or row). The problem is solved
+/// exactly with the O(n²m) shortest augmenting path variant of the Hungarian
+/// (Kuhn-Munkres) algorithm with row and column potentials. Used to match
+/// objects between frames or channels and detections to a ground truth. To
+/// forbid a pair, give it a large finite cost (*e.g.* larger than the sum of
+/// all allowed costs) and drop the pairs with that cost from the result.
+///
+/// # Arguments
+///
+/// * `cost`: The cost matrix with shape `(n, m)`.
+///
+/// # Returns
+///
+/// * `Ok(Vec<(usize, usize)>)`: The `min(n, m)` assigned `(row, column)`
+///   index pairs, in ascending order of the row indices.
+/// * `Err(ImgalError)`: If `cost` contains `NaN` or infinite values.
+///
+/// # Reference
+///
+/// <https://doi.org/10.1002/nav.3800020109>
+pub fn hungarian<'a, A>(cost: A) -> Result<Vec<(usize, usize)>, ImgalError>
+where
+    A: AsArray<'a, f64, Ix2>,
+{
+    let cost: ArrayBase<ViewRepr<&'a f64>, Ix2> = cost.into();
+    if cost.iter().any(|v| !v.is_finite()) {
+        return Err(ImgalError::InvalidGeneric {
+            msg: "Invalid cost matrix, the costs must be finite.",
+        });
+    }
+    let (n, m) = cost.dim();
+    if n == 0 || m == 0 {
+        return Ok(Vec::new());
+    }
+    let mut pairs: Vec<(usize, usize)> = if n <= m {
+        solve(&cost.to_owned())
+            .into_iter()
+            .enumerate()
+            .filter_map(|(i, j)| j.map(|j| (i, j)))
+            .collect()
+    } else {
+        solve(&cost.t().to_owned())
+            .into_iter()
+            .enumerate()
+            .filter_map(|(j, i)| i.map(|i| (i, j)))
+            .collect()
+    };
+    pairs.sort_unstable();
+    Ok(pairs)
+}
+
+/// Solve the assignment problem of a `(n, m)` cost matrix with `n <= m`,
+/// returning the assigned column of each row.
+fn solve(cost: &Array2<f64>) -> Vec<Option<usize>> {
+    let (n, m) = cost.dim();
+    // the row and column potentials, the row assigned to each column and the
+    // previous column on the augmenting path, with the dummy column 0
+    let mut u = vec![0.0; n + 1];
+    let mut v = vec![0.0; m + 1];
+    let mut p = vec![0usize; m + 1];
+    let mut way = vec![0usize; m + 1];
+    for i in 1..=n {
+        p[0] = i;
+        let mut j0 = 0;
+        let mut min_v = vec![f64::INFINITY; m + 1];
+        let mut used = vec![false; m + 1];
+        loop {
+            used[j0] = true;
+            let i0 = p[j0];
+            let mut delta = f64::INFINITY;
+            let mut j1 = 0;
+            for j in 1..=m {
+                if !used[j] {
+                    let cur = cost[[i0 - 1, j - 1]] - u[i0] - v[j];
+                    if cur < min_v[j] {
+                        min_v[j] = cur;
+                        way[j] = j0;
+                    }
+                    if min_v[j] < delta {
+                        delta = min_v[j];
+                        j1 = j;
+                    }
+                }
+            }
+            for j in 0..=m {
+                if used[j] {
+                    u[p[j]] += delta;
+                    v[j] -= delta;
+                } else {
+                    min_v[j] -= delta;
+                }
+            }
+            j0 = j1;
+            if p[j0] == 0 {
+                break;
+            }
+        }
+        // augment along the path
+        loop {
+            let j1 = way[j0];
+            p[j0] = p[j1];
+            j0 = j1;
+            if j0 == 0 {
+                break;
+            }
+        }
+    }
+    let mut assignment = vec![None; n];
+    (1..=m)
+        .filter(|&j| p[j] != 0)
+        .for_each(|j| assignment[p[j] - 1] = Some(j - 1));
+    assignment
+}
@@ -0,0 +1,15 @@
+//! Optimization functions.
+//!
+//! This module provides solvers for combinatorial optimization problems, such
+//! as the linear assignment problem used to match objects, detections and
+//! tracks, and for the large sparse linear systems of graph and grid based
+//! image algorithms.
+
+mod conjugate_gradient;
+mod hungarian;
+mod sparse;
+
+pub use conjugate_gradient::ConjugateGradientOutput;
+pub use conjugate_gradient::conjugate_gradient;
+pub use hungarian::hungarian;
+pub use sparse::CsrMatrix;
@@ -0,0 +1,236 @@
+use ndarray::{Array1, ArrayBase, AsArray, Ix1, ViewRepr};
+use rayon::prelude::*;
+
+use crate::prelude::*;
+
+/// A sparse matrix in compressed sparse row (CSR) format.
+///
+/// # Description
+///
+/// Stores the nonzero values of a `(n_rows, n_cols)` matrix row by row. The
+/// values of row `i` are `data[indptr[i]..indptr[i + 1]]` in the columns
+/// `indices[indptr[i]..indptr[i + 1]]`, sorted in ascending order. Used for
+/// the large, sparse linear systems of graph and grid based image algorithms
+/// (*e.g.* the graph Laplacian of random walker segmentation or the Poisson
+/// equation of gradient-domain fusion), see `optimize::conjugate_gradient`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CsrMatrix {
+    /// The number of rows.
+    n_rows: usize,
+    /// The number of columns.
+    n_cols: usize,
+    /// The start of each row in `indices` and `data`, with length
+    /// `n_rows + 1`.
+    indptr: Vec<usize>,
+    /// The column index of each stored value.
+    indices: Vec<usize>,
+    /// The stored values.
+    data: Vec<f64>,
+}
+
+impl CsrMatrix {
+    /// Create a new CSR matrix from its compressed arrays.
+    ///
+    /// # Arguments
+    ///
+    /// * `n_rows`: The number of rows.
+    /// * `n_cols`: The number of columns.
+    /// * `indptr`: The start of each row in `indices` and `data`, with length
+    ///   `n_rows + 1`.
+    /// * `indices`: The column index of each stored value, in ascending order
+    ///   within each row.
+    /// * `data`: The stored values.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(CsrMatrix)`: The CSR matrix.
+    /// * `Err(ImgalError)`: If `indptr.len() != n_rows + 1`. If the lengths of
+    ///   `indices` and `data` do not match. If `indptr` does not start at `0`,
+    ///   decreases or does not end at the number of stored values. If the
+    ///   column indices of a row are not strictly ascending or a column index
+    ///   is greater than or equal to `n_cols`.
+    pub fn new(
+        n_rows: usize,
+        n_cols: usize,
+        indptr: Vec<usize>,
+        indices: Vec<usize>,
+        data: Vec<f64>,
+    ) -> Result<Self, ImgalError> {
+        if indptr.len() != n_rows + 1 {
+            return Err(ImgalError::InvalidArrayLengthExpected {
+                arr_name: "indptr",
+                expected: n_rows + 1,
+                got: indptr.len(),
+            });
+        }
+        if indices.len() != data.len() {
+            return Err(ImgalError::MismatchedArrayLengths {
+                a_arr_name: "indices",
+                a_arr_len: indices.len(),
+                b_arr_name: "data",
+                b_arr_len: data.len(),
+            });
+        }
+        if indptr[0] != 0 || indptr[n_rows] != data.len() || indptr.windows(2).any(|w| w[0] > w[1])
+        {
+            return Err(ImgalError::InvalidGeneric {
+                msg: "Invalid CSR matrix, the row pointers must be non-decreasing from 0 to the number of stored values.",
+            });
+        }
+        let valid_row = |w: &[usize]| {
+            let cols = &indices[w[0]..w[1]];
+            cols.windows(2).all(|c| c[0] < c[1]) && cols.last().is_none_or(|&c| c < n_cols)
+        };
+        if !indptr.windows(2).all(valid_row) {
+            return Err(ImgalError::InvalidGeneric {
+                msg: "Invalid CSR matrix, the column indices of each row must be strictly ascending and less than the number of columns.",
+            });
+        }
+        Ok(Self {
+            n_rows,
+            n_cols,
+            indptr,
+            indices,
+            data,
+        })
+    }
+
+    /// Create a new CSR matrix from `(row, column, value)` triplets.
+    ///
+    /// # Description
+    ///
+    /// Assembles a CSR matrix from unordered triplets, the values of duplicate
+    /// `(row, column)` pairs are summed (*e.g.* when adding the contribution
+    /// of each graph edge to a Laplacian).
+    ///
+    /// # Arguments
+    ///
+    /// * `n_rows`: The number of rows.
+    /// * `n_cols`: The number of columns.
+    /// * `triplets`: The `(row, column, value)` entries of the matrix.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(CsrMatrix)`: The CSR matrix.
+    /// * `Err(ImgalError)`: If a row index is greater than or equal to
+    ///   `n_rows` or a column index is greater than or equal to `n_cols`.
+    pub fn from_triplets(
+        n_rows: usize,
+        n_cols: usize,
+        triplets: &[(usize, usize, f64)],
+    ) -> Result<Self, ImgalError> {
+        if triplets.iter().any(|&(r, c, _)| r >= n_rows || c >= n_cols) {
+            return Err(ImgalError::InvalidGeneric {
+                msg: "Invalid CSR matrix triplets, an index is outside of the matrix shape.",
+            });
+        }
+        let mut sorted = triplets.to_vec();
+        sorted.sort_unstable_by_key(|&(r, c, _)| (r, c));
+        let mut indptr = vec![0; n_rows + 1];
+        let mut indices: Vec<usize> = Vec::with_capacity(sorted.len());
+        let mut data: Vec<f64> = Vec::with_capacity(sorted.len());
+        let mut last: Option<(usize, usize)> = None;
+        for (r, c, v) in sorted {
+            if last == Some((r, c)) {
+                *data.last_mut().unwrap() += v;
+            } else {
+                indptr[r + 1] += 1;
+                indices.push(c);
+                data.push(v);
+                last = Some((r, c));
+            }
+        }
+        for i in 0..n_rows {
+            indptr[i + 1] += indptr[i];
+        }
+        Ok(Self {
+            n_rows,
+            n_cols,
+            indptr,
+            indices,
+            data,
+        })
+    }
+
+    /// Return the `(n_rows, n_cols)` shape of the matrix.
+    pub fn shape(&self) -> (usize, usize) {
+        (self.n_rows, self.n_cols)
+    }
+
+    /// Return the number of stored values.
+    pub fn nnz(&self) -> usize {
+        self.data.len()
+    }
+
+    /// Return the start of each row in `indices` and `data`.
+    pub fn indptr(&self) -> &[usize] {
+        &self.indptr
+    }
+
+    /// Return the column index of each stored value.
+    pub fn indices(&self) -> &[usize] {
+        &self.indices
+    }
+
+    /// Return the stored values.
+    pub fn data(&self) -> &[f64] {
+        &self.data
+    }
+
+    /// Return the main diagonal of the matrix, with zeros for unstored values.
+    pub fn diagonal(&self) -> Array1<f64> {
+        Array1::from_shape_fn(self.n_rows.min(self.n_cols), |i| {
+            let (lo, hi) = (self.indptr[i], self.indptr[i + 1]);
+            self.indices[lo..hi]
+                .binary_search(&i)
+                .map_or(0.0, |k| self.data[lo + k])
+        })
+    }
+
+    /// Multiply the matrix with a vector.
+    ///
+    /// # Arguments
+    ///
+    /// * `x`: The vector with length `n_cols`.
+    /// * `threads`: The requested number of threads to use for parallel
+    ///   execution. If `None` or `Some(1)` sequential execution is used. If
+    ///   `Some(0)`, then the maximum available parallelism is used. Thread
+    ///   counts are clamped to the systems maximum.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Array1<f64>)`: The product `A × x` with length `n_rows`.
+    /// * `Err(ImgalError)`: If `x.len() != n_cols`.
+    pub fn dot<'a, A>(&self, x: A, threads: Option<usize>) -> Result<Array1<f64>, ImgalError>
+    where
+        A: AsArray<'a, f64, Ix1>,
+    {
+        let x: ArrayBase<ViewRepr<&'a f64>, Ix1> = x.into();
+        if x.len() != self.n_cols {
+            return Err(ImgalError::InvalidArrayLengthExpected {
+                arr_name: "x",
+                expected: self.n_cols,
+                got: x.len(),
+            });
+        }
+        let x = x.as_standard_layout();
+        let mut y = Array1::<f64>::zeros(self.n_rows);
+        self.dot_into(x.as_slice().unwrap(), y.as_slice_mut().unwrap(), threads);
+        Ok(y)
+    }
+
+    /// Multiply the matrix with a vector slice into an output slice.
+    pub(crate) fn dot_into(&self, x: &[f64], y: &mut [f64], threads: Option<usize>) {
+        let row_dot = |(i, y): (usize, &mut f64)| {
+            let (lo, hi) = (self.indptr[i], self.indptr[i + 1]);
+            *y = self.indices[lo..hi]
+                .iter()
+                .zip(self.data[lo..hi].iter())
+                .map(|(&c, &v)| v * x[c])
+                .sum();
+        };
+        par!(threads,
+            seq_exp: y.iter_mut().enumerate().for_each(row_dot),
+            par_exp: y.par_iter_mut().enumerate().for_each(row_dot));
+    }
+}
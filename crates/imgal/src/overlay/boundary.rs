@@ -0,0 +1,50 @@
+use ndarray::{ArrayView, ArrayViewMut, Dimension, Zip};
+
+use crate::label::{BoundaryMode, boundaries};
+use crate::prelude::*;
+use crate::validate::check_shapes;
+
+/// Apply the object boundaries of a label image over an n-dimensional image.
+///
+/// # Description
+///
+/// Draws the boundaries of the objects in a label image (see
+/// `label::boundaries`) over an image of the same shape (*e.g.* the raw data
+/// the labels were segmented from), by setting the boundary pixels to the
+/// maximum value of the image type. Used for quality control overlays of
+/// segmentations.
+///
+/// # Arguments
+///
+/// * `data`: The input n-dimensional image.
+/// * `labels`: The n-dimensional label image with the same shape as `data`.
+/// * `mode`: The placement of the boundary pixels. If `None`, then
+///   `mode = BoundaryMode::Inner`.
+/// * `threads`: The requested number of threads to use for parallel execution.
+///   If `None` or `Some(1)` sequential execution is used. If `Some(0)`, then
+///   the maximum available parallelism is used. Thread counts are clamped to
+///   the systems maximum.
+///
+/// # Returns
+///
+/// * `Ok(())`: If the boundaries were applied to `data`.
+/// * `Err(ImgalError)`: If the `labels` shape does not match the `data` shape.
+pub fn boundaries_mut<T, D>(
+    data: &mut ArrayViewMut<T, D>,
+    labels: ArrayView<u64, D>,
+    mode: Option<BoundaryMode>,
+    threads: Option<usize>,
+) -> Result<(), ImgalError>
+where
+    T: AsNumeric,
+    D: Dimension,
+{
+    check_shapes("labels", labels.shape(), "data", data.shape())?;
+    let bounds = boundaries(labels, mode, threads);
+    Zip::from(data).and(&bounds).for_each(|v, &b| {
+        if b {
+            *v = T::MAX;
+        }
+    });
+    Ok(())
+}
@@ -1,3 +1,4 @@
 //! Image overlay functions.
 
+pub mod boundary;
 pub mod grid;
@@ -0,0 +1,40 @@
+use crate::prelude::*;
+
+/// Compute the maximum valid phasor harmonic of a sampled decay.
+///
+/// # Description
+///
+/// Computes the largest integer harmonic, `n`, whose frequency is below the
+/// Nyquist frequency of a decay sampled with `samples` time bins over the
+/// `period`:
+///
+/// ```text
+/// f_Nyquist = samples / (2T)
+/// n / T < f_Nyquist ⇒ n < samples / 2
+/// ```
+///
+/// Where `T` is the period. Phasor coordinates (G, S) computed at harmonics
+/// at or above the Nyquist limit are aliased onto lower harmonics (*e.g.* S is
+/// always `0.0` at `n = samples / 2`).
+///
+/// # Arguments
+///
+/// * `samples`: The number of time bins of the decay.
+/// * `period`: The period (*i.e.* time interval).
+///
+/// # Returns
+///
+/// * `Ok(usize)`: The maximum valid harmonic. A value of `0` means that no
+///   harmonic can be resolved with `samples` time bins.
+/// * `Err(ImgalError)`: If `period <= 0.0`.
+pub fn max_valid_harmonic(samples: usize, period: f64) -> Result<usize, ImgalError> {
+    if period <= 0.0 || !period.is_finite() {
+        return Err(ImgalError::InvalidParameterValueOutsideRange {
+            param_name: "period",
+            value: period,
+            min: 0.0,
+            max: f64::INFINITY,
+        });
+    }
+    Ok(samples.saturating_sub(1) / 2)
+}
@@ -1,7 +1,9 @@
 //! Microscopy and imaging related parameter functions.
 
 mod diffraction;
+mod harmonic;
 mod omega;
 
 pub use diffraction::abbe_diffraction_limit;
+pub use harmonic::max_valid_harmonic;
 pub use omega::omega;
@@ -0,0 +1,206 @@
+use std::collections::HashMap;
+use std::ops::Range;
+
+use ndarray::{Array2, Array3, ArrayBase, ArrayView2, AsArray, Ix2, Ix3, ViewRepr};
+
+use crate::phasor::time_domain::{
+    DegeneratePolicy, gs_image_checked, gs_image_tiled, gs_roi_checked,
+};
+use crate::prelude::*;
+
+/// A builder for computing the real and imaginary (G, S) phasor coordinates of
+/// decay images.
+///
+/// # Description
+///
+/// Wraps the `phasor::time_domain` compute functions with builder-style
+/// parameters, so that only the non-default parameters are named at the call
+/// site instead of passing every optional parameter positionally:
+///
+/// ```text
+/// let gs = Phasor::new(period).harmonic(2.0).mask(&mask).compute(&data)?;
+/// ```
+///
+/// Unset parameters use the defaults of the wrapped functions,
+/// `harmonic = 1.0`, `axis = 2`, `policy = DegeneratePolicy::Nan`, no mask
+/// and sequential execution. A configured `Phasor` can be reused for several
+/// images.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Phasor<'m> {
+    period: f64,
+    harmonic: Option<f64>,
+    mask: Option<ArrayView2<'m, bool>>,
+    axis: Option<usize>,
+    policy: Option<DegeneratePolicy>,
+    threads: Option<usize>,
+}
+
+impl<'m> Phasor<'m> {
+    /// Create a new `Phasor` builder with the given period (*i.e.* time
+    /// interval) and default parameters.
+    pub fn new(period: f64) -> Self {
+        Self {
+            period,
+            harmonic: None,
+            mask: None,
+            axis: None,
+            policy: None,
+            threads: None,
+        }
+    }
+
+    /// Set the harmonic value.
+    pub fn harmonic(mut self, harmonic: f64) -> Self {
+        self.harmonic = Some(harmonic);
+        self
+    }
+
+    /// Set the 2D boolean mask of the pixels to compute. Pixels outside of the
+    /// mask are set to `0.0`. The mask is ignored by `compute_roi`.
+    pub fn mask<M>(mut self, mask: M) -> Self
+    where
+        M: AsArray<'m, bool, Ix2>,
+    {
+        let mask: ArrayBase<ViewRepr<&'m bool>, Ix2> = mask.into();
+        self.mask = Some(mask);
+        self
+    }
+
+    /// Set the decay or lifetime axis.
+    pub fn axis(mut self, axis: usize) -> Self {
+        self.axis = Some(axis);
+        self
+    }
+
+    /// Set the handling of degenerate pixels, whose decay integral is zero.
+    pub fn policy(mut self, policy: DegeneratePolicy) -> Self {
+        self.policy = Some(policy);
+        self
+    }
+
+    /// Set the requested number of threads to use for parallel execution. If
+    /// `1` sequential execution is used. If `0`, then the maximum available
+    /// parallelism is used. Thread counts are clamped to the systems maximum.
+    pub fn threads(mut self, threads: usize) -> Self {
+        self.threads = Some(threads);
+        self
+    }
+
+    /// Compute the real and imaginary (G, S) coordinates of a 3D decay image.
+    ///
+    /// # Arguments
+    ///
+    /// * `data`: The input 3D decay image.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Array3<f64>)`: The G and S coordinates as a 3D (row, col, ch)
+    ///   image, see `phasor::time_domain::gs_image_checked`.
+    /// * `Err(ImgalError)`: See `phasor::time_domain::gs_image_checked`.
+    pub fn compute<'a, T, A>(&self, data: A) -> Result<Array3<f64>, ImgalError>
+    where
+        A: AsArray<'a, T, Ix3>,
+        T: 'a + AsNumeric,
+    {
+        self.compute_checked(data).map(|(gs, _)| gs)
+    }
+
+    /// Compute the real and imaginary (G, S) coordinates of a 3D decay image
+    /// and count the degenerate pixels.
+    ///
+    /// # Arguments
+    ///
+    /// * `data`: The input 3D decay image.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok((Array3<f64>, usize))`: The G and S coordinates as a 3D
+    ///   (row, col, ch) image and the number of degenerate pixels.
+    /// * `Err(ImgalError)`: See `phasor::time_domain::gs_image_checked`.
+    pub fn compute_checked<'a, T, A>(&self, data: A) -> Result<(Array3<f64>, usize), ImgalError>
+    where
+        A: AsArray<'a, T, Ix3>,
+        T: 'a + AsNumeric,
+    {
+        gs_image_checked(
+            data,
+            self.period,
+            self.mask,
+            self.harmonic,
+            self.axis,
+            self.policy,
+            self.threads,
+        )
+    }
+
+    /// Compute the real and imaginary (G, S) coordinates of a 3D decay image
+    /// loaded tile by tile.
+    ///
+    /// # Arguments
+    ///
+    /// * `load_tile`: A closure that loads the decay tile of the given row and
+    ///   col ranges, see `phasor::time_domain::gs_image_tiled`.
+    /// * `shape`: The row and col shape of the full image.
+    /// * `tile_shape`: The row and col shape of the tiles.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok((Array3<f64>, usize))`: The G and S coordinates as a 3D
+    ///   (row, col, ch) image and the number of degenerate pixels.
+    /// * `Err(ImgalError)`: See `phasor::time_domain::gs_image_tiled`.
+    pub fn compute_tiled<T, F>(
+        &self,
+        load_tile: F,
+        shape: (usize, usize),
+        tile_shape: (usize, usize),
+    ) -> Result<(Array3<f64>, usize), ImgalError>
+    where
+        F: FnMut(Range<usize>, Range<usize>) -> Result<Array3<T>, ImgalError>,
+        T: AsNumeric,
+    {
+        gs_image_tiled(
+            load_tile,
+            shape,
+            tile_shape,
+            self.period,
+            self.mask,
+            self.harmonic,
+            self.axis,
+            self.policy,
+            self.threads,
+        )
+    }
+
+    /// Compute the real and imaginary (G, S) coordinates of a HashMap of ROI
+    /// point clouds.
+    ///
+    /// # Arguments
+    ///
+    /// * `data`: The input 3D decay image.
+    /// * `rois`: A HashMap of 2D ROI point clouds.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok((HashMap<u64, Array2<f64>>, usize))`: The G and S coordinates of
+    ///   each ROI point and the number of degenerate points.
+    /// * `Err(ImgalError)`: See `phasor::time_domain::gs_roi_checked`.
+    pub fn compute_roi<'a, T, A>(
+        &self,
+        data: A,
+        rois: &HashMap<u64, Array2<usize>>,
+    ) -> Result<(HashMap<u64, Array2<f64>>, usize), ImgalError>
+    where
+        A: AsArray<'a, T, Ix3>,
+        T: 'a + AsNumeric,
+    {
+        gs_roi_checked(
+            data,
+            self.period,
+            rois,
+            self.harmonic,
+            self.axis,
+            self.policy,
+            self.threads,
+        )
+    }
+}
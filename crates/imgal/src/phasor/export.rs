@@ -0,0 +1,266 @@
+use std::collections::HashMap;
+
+use ndarray::{Array2, ArrayView2, ArrayView3};
+
+use crate::prelude::*;
+use crate::validate::check_shapes;
+
+/// Serialize a phasor image to a NumPy `.npz` archive.
+///
+/// # Description
+///
+/// Serializes the G and S images of a (calibrated) phasor image to an
+/// uncompressed NumPy `.npz` archive with the PhasorPy keys:
+///
+/// - `real`: The G (real) coordinates with shape `(row, col)`, as `float64`.
+/// - `imag`: The S (imaginary) coordinates with shape `(row, col)`, as
+///   `float64`.
+/// - `mean`: The mean intensity image with shape `(row, col)`, as `float64`.
+///   Only written if `mean` is given.
+/// - `harmonic`: The harmonic of the phasor coordinates, a `float64` scalar.
+/// - `frequency`: The laser repetition frequency in MHz, a `float64` scalar.
+///   Only written if `frequency` is given.
+///
+/// The archive can be written with `std::fs::write`, loaded with `numpy.load`
+/// and passed on to PhasorPy (*e.g.*
+/// `phasorpy.plot.plot_phasor(npz["real"], npz["imag"])`) or FLUTE.
+///
+/// # Arguments
+///
+/// * `gs`: The phasor image with shape `(row, col, 2)`, where G and S are
+///   indexed at `0` and `1` of the last axis (*e.g.* from `gs_image`).
+/// * `mean`: The optional mean intensity image with shape `(row, col)`.
+/// * `harmonic`: The harmonic of the phasor coordinates. If `None`, then
+///   `harmonic = 1.0`.
+/// * `frequency`: The optional laser repetition frequency in MHz (*e.g.*
+///   `1000.0 / period` for a period in nanoseconds).
+///
+/// # Returns
+///
+/// * `Ok(Vec<u8>)`: The bytes of the `.npz` archive.
+/// * `Err(ImgalError)`: If the last axis of `gs` is not of length `2`. If the
+///   `mean` shape does not match the `gs` shape without the last axis. If the
+///   archive exceeds 4 GiB.
+///
+/// # Reference
+///
+/// <https://numpy.org/doc/stable/reference/generated/numpy.lib.format.html>
+pub fn gs_image_npz(
+    gs: ArrayView3<f64>,
+    mean: Option<ArrayView2<f64>>,
+    harmonic: Option<f64>,
+    frequency: Option<f64>,
+) -> Result<Vec<u8>, ImgalError> {
+    let (rows, cols, n) = gs.dim();
+    if n != 2 {
+        return Err(ImgalError::InvalidAxisLengthExpected {
+            arr_name: "gs",
+            axis_idx: 2,
+            expected: 2,
+            got: n,
+        });
+    }
+    if let Some(m) = mean {
+        check_shapes("gs", &[rows, cols], "mean", m.shape())?;
+    }
+    let mut entries = vec![
+        (
+            "real",
+            npy_f64(&[rows, cols], gs.iter().step_by(2).copied()),
+        ),
+        (
+            "imag",
+            npy_f64(&[rows, cols], gs.iter().skip(1).step_by(2).copied()),
+        ),
+    ];
+    if let Some(m) = mean {
+        entries.push(("mean", npy_f64(&[rows, cols], m.iter().copied())));
+    }
+    entries.push(("harmonic", npy_f64(&[], [harmonic.unwrap_or(1.0)])));
+    if let Some(f) = frequency {
+        entries.push(("frequency", npy_f64(&[], [f])));
+    }
+    zip_stored(&entries)
+}
+
+/// Serialize per-ROI phasor coordinates to a NumPy `.npz` archive table.
+///
+/// # Description
+///
+/// Serializes the phasor coordinates of each point of each ROI to an
+/// uncompressed NumPy `.npz` archive with one row per point, sorted by ROI
+/// label, and the PhasorPy keys:
+///
+/// - `label`: The ROI label of each point with shape `(p,)`, as `uint64`.
+/// - `real`: The G (real) coordinate of each point with shape `(p,)`, as
+///   `float64`.
+/// - `imag`: The S (imaginary) coordinate of each point with shape `(p,)`, as
+///   `float64`.
+/// - `harmonic`: The harmonic of the phasor coordinates, a `float64` scalar.
+/// - `frequency`: The laser repetition frequency in MHz, a `float64` scalar.
+///   Only written if `frequency` is given.
+///
+/// The archive can be written with `std::fs::write` and loaded with
+/// `numpy.load` (*e.g.* into a pandas `DataFrame`), then grouped by `label` for
+/// per-ROI phasor plots.
+///
+/// # Arguments
+///
+/// * `rois`: A map of per-ROI phasor coordinates, each with shape `(p, 2)`
+///   (*e.g.* from `gs_roi`).
+/// * `harmonic`: The harmonic of the phasor coordinates. If `None`, then
+///   `harmonic = 1.0`.
+/// * `frequency`: The optional laser repetition frequency in MHz (*e.g.*
+///   `1000.0 / period` for a period in nanoseconds).
+///
+/// # Returns
+///
+/// * `Ok(Vec<u8>)`: The bytes of the `.npz` archive.
+/// * `Err(ImgalError)`: If a ROI does not have `2` columns. If the archive
+///   exceeds 4 GiB.
+pub fn gs_roi_npz(
+    rois: &HashMap<u64, Array2<f64>>,
+    harmonic: Option<f64>,
+    frequency: Option<f64>,
+) -> Result<Vec<u8>, ImgalError> {
+    if let Some(v) = rois.values().find(|v| v.ncols() != 2) {
+        return Err(ImgalError::InvalidAxisLengthExpected {
+            arr_name: "rois",
+            axis_idx: 1,
+            expected: 2,
+            got: v.ncols(),
+        });
+    }
+    let mut labels: Vec<u64> = rois.keys().copied().collect();
+    labels.sort_unstable();
+    let p: usize = rois.values().map(|v| v.nrows()).sum();
+    let points = || labels.iter().flat_map(|k| rois[k].rows());
+    let label_iter = labels
+        .iter()
+        .flat_map(|k| std::iter::repeat_n(*k, rois[k].nrows()));
+    let mut entries = vec![
+        ("label", npy_u64(&[p], label_iter)),
+        ("real", npy_f64(&[p], points().map(|r| r[0]))),
+        ("imag", npy_f64(&[p], points().map(|r| r[1]))),
+        ("harmonic", npy_f64(&[], [harmonic.unwrap_or(1.0)])),
+    ];
+    if let Some(f) = frequency {
+        entries.push(("frequency", npy_f64(&[], [f])));
+    }
+    zip_stored(&entries)
+}
+
+/// Encode a C-order `float64` array in the `.npy` format.
+fn npy_f64<I>(shape: &[usize], values: I) -> Vec<u8>
+where
+    I: IntoIterator<Item = f64>,
+{
+    let mut buf = npy_header("<f8", shape);
+    values
+        .into_iter()
+        .for_each(|v| buf.extend_from_slice(&v.to_le_bytes()));
+    buf
+}
+
+/// Encode a C-order `uint64` array in the `.npy` format.
+fn npy_u64<I>(shape: &[usize], values: I) -> Vec<u8>
+where
+    I: IntoIterator<Item = u64>,
+{
+    let mut buf = npy_header("<u8", shape);
+    values
+        .into_iter()
+        .for_each(|v| buf.extend_from_slice(&v.to_le_bytes()));
+    buf
+}
+
+/// Build a version 1.0 `.npy` header, padded so the data is 64 byte aligned.
+fn npy_header(descr: &str, shape: &[usize]) -> Vec<u8> {
+    let shape_str = match shape.len() {
+        0 => "()".to_string(),
+        1 => format!("({},)", shape[0]),
+        _ => format!(
+            "({})",
+            shape
+                .iter()
+                .map(|s| s.to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+    };
+    let mut dict =
+        format!("{{'descr': '{descr}', 'fortran_order': False, 'shape': {shape_str}, }}");
+    // magic (6) + version (2) + header length (2) + dict + newline
+    let pad = (64 - (10 + dict.len() + 1) % 64) % 64;
+    dict.push_str(&" ".repeat(pad));
+    dict.push('\n');
+    let mut buf = Vec::with_capacity(10 + dict.len());
+    buf.extend_from_slice(b"\x93NUMPY\x01\x00");
+    buf.extend_from_slice(&(dict.len() as u16).to_le_bytes());
+    buf.extend_from_slice(dict.as_bytes());
+    buf
+}
+
+/// Write named `.npy` entries into an uncompressed (stored) zip archive.
+fn zip_stored(entries: &[(&str, Vec<u8>)]) -> Result<Vec<u8>, ImgalError> {
+    let mut out: Vec<u8> = Vec::new();
+    let mut central: Vec<u8> = Vec::new();
+    let too_large = || ImgalError::InvalidGeneric {
+        msg: "Invalid npz archive, the archive size exceeds 4 GiB.",
+    };
+    for (name, data) in entries {
+        let name = format!("{name}.npy");
+        let offset = u32::try_from(out.len()).map_err(|_| too_large())?;
+        let size = u32::try_from(data.len()).map_err(|_| too_large())?;
+        let crc = crc32(data);
+        // fields shared by the local and central headers: version needed,
+        // flags, method (stored), time, date (1980-01-01), crc and sizes
+        let mut shared: Vec<u8> = Vec::with_capacity(26);
+        shared.extend_from_slice(&20u16.to_le_bytes());
+        shared.extend_from_slice(&0u16.to_le_bytes());
+        shared.extend_from_slice(&0u16.to_le_bytes());
+        shared.extend_from_slice(&0u16.to_le_bytes());
+        shared.extend_from_slice(&0x21u16.to_le_bytes());
+        shared.extend_from_slice(&crc.to_le_bytes());
+        shared.extend_from_slice(&size.to_le_bytes());
+        shared.extend_from_slice(&size.to_le_bytes());
+        shared.extend_from_slice(&(name.len() as u16).to_le_bytes());
+        shared.extend_from_slice(&0u16.to_le_bytes());
+        out.extend_from_slice(&0x04034b50u32.to_le_bytes());
+        out.extend_from_slice(&shared);
+        out.extend_from_slice(name.as_bytes());
+        out.extend_from_slice(data);
+        central.extend_from_slice(&0x02014b50u32.to_le_bytes());
+        central.extend_from_slice(&20u16.to_le_bytes());
+        central.extend_from_slice(&shared);
+        // comment length, disk number, internal and external attributes
+        central.extend_from_slice(&[0u8; 10]);
+        central.extend_from_slice(&offset.to_le_bytes());
+        central.extend_from_slice(name.as_bytes());
+    }
+    let cd_offset = u32::try_from(out.len()).map_err(|_| too_large())?;
+    let cd_size = u32::try_from(central.len()).map_err(|_| too_large())?;
+    out.extend_from_slice(&central);
+    out.extend_from_slice(&0x06054b50u32.to_le_bytes());
+    out.extend_from_slice(&[0u8; 4]);
+    out.extend_from_slice(&(entries.len() as u16).to_le_bytes());
+    out.extend_from_slice(&(entries.len() as u16).to_le_bytes());
+    out.extend_from_slice(&cd_size.to_le_bytes());
+    out.extend_from_slice(&cd_offset.to_le_bytes());
+    out.extend_from_slice(&0u16.to_le_bytes());
+    u32::try_from(out.len()).map_err(|_| too_large())?;
+    Ok(out)
+}
+
+/// Compute the CRC-32 (IEEE 802.3) checksum of a byte slice.
+fn crc32(data: &[u8]) -> u32 {
+    !data.iter().fold(0xFFFF_FFFFu32, |crc, &b| {
+        (0..8).fold(crc ^ b as u32, |c, _| {
+            if c & 1 == 1 {
+                (c >> 1) ^ 0xEDB8_8320
+            } else {
+                c >> 1
+            }
+        })
+    })
+}
@@ -1,5 +1,7 @@
 //! Phasor compute, calibration, and plot functions.
 
+pub mod builder;
 pub mod calibration;
+pub mod export;
 pub mod plot;
 pub mod time_domain;
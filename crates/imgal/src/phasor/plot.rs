@@ -3,6 +3,7 @@ use std::collections::HashSet;
 use ndarray::{Array2, ArrayBase, ArrayView1, AsArray, Axis, Ix1, Ix3, ViewRepr, Zip};
 
 use crate::prelude::*;
+use crate::validate::check_axis;
 
 /// Map G and S coordinates back to the input phasor array as a boolean mask.
 ///
@@ -56,12 +57,7 @@ where
         });
     }
     let a = axis.unwrap_or(2);
-    if a >= 3 {
-        return Err(ImgalError::InvalidAxis {
-            axis_idx: a,
-            dim_len: 3,
-        });
-    }
+    check_axis(a, 3)?;
     // create a HashSet of G/S coordinates and check if a given G/S value pair
     // is within the set
     let data: ArrayBase<ViewRepr<&'a T>, Ix3> = data.into();
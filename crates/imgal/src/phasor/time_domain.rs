@@ -1,4 +1,5 @@
 use std::collections::HashMap;
+use std::ops::Range;
 
 use ndarray::{
     Array2, Array3, ArrayBase, ArrayView1, ArrayView2, AsArray, Axis, Ix1, Ix3, ViewRepr, Zip, s,
@@ -7,8 +8,22 @@ use ndarray::{
 use rayon::prelude::*;
 
 use crate::integration::midpoint;
-use crate::parameter::omega;
+use crate::parameter::{max_valid_harmonic, omega};
 use crate::prelude::*;
+use crate::validate::{check_axis, check_roi_bounds, check_shapes};
+
+/// The handling of degenerate pixels, whose decay integral is zero (*e.g.*
+/// empty background pixels), when computing phasor coordinates.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum DegeneratePolicy {
+    /// Set the G and S coordinates of degenerate pixels to `NaN`.
+    #[default]
+    Nan,
+    /// Set the G and S coordinates of degenerate pixels to `0.0`.
+    Zero,
+    /// Return an error if any pixel is degenerate.
+    Error,
+}
 
 /// Compute the real and imaginary (G, S) coordinates of a 3D decay image.
 ///
@@ -22,10 +37,15 @@ use crate::prelude::*;
 /// S = ∫(I(t) * sin(nωt) * dt) / ∫(I(t) * dt)
 /// ```
 ///
+/// Pixels whose decay integral is zero produce non-finite coordinates and are
+/// set to `NaN`, see `gs_image_checked` to configure and count them.
+///
 /// # Arguments
 ///
 /// * `data`: The input 3D decay image.
 /// * `period`: The period (*i.e.* time interval).
+/// * `mask`: A 2D boolean mask of the pixels to compute. Pixels outside of the
+///   mask are set to `0.0`. If `None`, all pixels are computed.
 /// * `harmonic`: The harmonic value. If `None`, then `harmonic = 1.0`.
 /// * `axis`: The decay or lifetime axis. If `None`, then `axis = 2`.
 /// * `threads`: The requested number of threads to use for parallel execution.
@@ -38,7 +58,10 @@ use crate::prelude::*;
 /// * `Ok(Array3<f64>)`: The real and imaginary coordinates as a 3D
 ///   (row, col, ch) image, where G and S are indexed at `0` and `1`
 ///   respectively on the *channel* axis.
-/// * `Err(ImgalError)`: If `axis >= 3`.
+/// * `Err(ImgalError)`: If `axis >= 3`. If the shape of `mask` does not match
+///   the shape of `data` without the decay axis. If `harmonic` is greater than
+///   the maximum valid harmonic of the decay sampling (see
+///   `parameter::max_valid_harmonic`).
 pub fn gs_image<'a, T, A>(
     data: A,
     period: f64,
@@ -47,27 +70,78 @@ pub fn gs_image<'a, T, A>(
     axis: Option<usize>,
     threads: Option<usize>,
 ) -> Result<Array3<f64>, ImgalError>
+where
+    A: AsArray<'a, T, Ix3>,
+    T: 'a + AsNumeric,
+{
+    gs_image_checked(data, period, mask, harmonic, axis, None, threads).map(|(gs, _)| gs)
+}
+
+/// Compute the real and imaginary (G, S) coordinates of a 3D decay image with
+/// explicit handling of degenerate pixels.
+///
+/// # Description
+///
+/// Computes the real (G) and imaginary (S) coordinates like `gs_image`.
+/// Pixels whose decay integral, `∫(I(t) * dt)`, is zero (*e.g.* empty
+/// background pixels) have undefined coordinates. Instead of silent `NaN` or
+/// `inf` values, these degenerate pixels are handled with `policy` and counted,
+/// so that downstream analyses (*e.g.* phasor clustering) receive well defined
+/// values. Pixels outside of the `mask` are not degenerate.
+///
+/// # Arguments
+///
+/// * `data`: The input 3D decay image.
+/// * `period`: The period (*i.e.* time interval).
+/// * `mask`: A 2D boolean mask of the pixels to compute. Pixels outside of the
+///   mask are set to `0.0`. If `None`, all pixels are computed.
+/// * `harmonic`: The harmonic value. If `None`, then `harmonic = 1.0`.
+/// * `axis`: The decay or lifetime axis. If `None`, then `axis = 2`.
+/// * `policy`: The handling of degenerate pixels. If `None`, then
+///   `policy = DegeneratePolicy::Nan`.
+/// * `threads`: The requested number of threads to use for parallel execution.
+///   If `None` or `Some(1)` sequential execution is used. If `Some(0)`, then
+///   the maximum available parallelism is used. Thread counts are clamped to
+///   the systems maximum.
+///
+/// # Returns
+///
+/// * `Ok((Array3<f64>, usize))`: The real and imaginary coordinates as a 3D
+///   (row, col, ch) image, where G and S are indexed at `0` and `1`
+///   respectively on the *channel* axis, and the number of degenerate pixels.
+/// * `Err(ImgalError)`: If `axis >= 3`. If the shape of `mask` does not match
+///   the shape of `data` without the decay axis. If `harmonic` is greater than
+///   the maximum valid harmonic of the decay sampling. If `policy` is
+///   `DegeneratePolicy::Error` and a pixel is degenerate.
+pub fn gs_image_checked<'a, T, A>(
+    data: A,
+    period: f64,
+    mask: Option<ArrayView2<bool>>,
+    harmonic: Option<f64>,
+    axis: Option<usize>,
+    policy: Option<DegeneratePolicy>,
+    threads: Option<usize>,
+) -> Result<(Array3<f64>, usize), ImgalError>
 where
     A: AsArray<'a, T, Ix3>,
     T: 'a + AsNumeric,
 {
     let axis = axis.unwrap_or(2);
-    if axis >= 3 {
-        return Err(ImgalError::InvalidAxis {
-            axis_idx: axis,
-            dim_len: 3,
-        });
-    }
+    check_axis(axis, 3)?;
     let data: ArrayBase<ViewRepr<&'a T>, Ix3> = data.into();
     let h = harmonic.unwrap_or(1.0);
     let w = omega(period);
     let n: usize = data.len_of(Axis(axis));
+    check_harmonic(h, n, period)?;
     let dt: f64 = period / n as f64;
     let h_w_dt: f64 = h * w * dt;
     let mut w_cos_buf: Vec<f64> = Vec::with_capacity(n);
     let mut w_sin_buf: Vec<f64> = Vec::with_capacity(n);
     let mut shape = data.shape().to_vec();
     shape.remove(axis);
+    if let Some(msk) = mask.as_ref() {
+        check_shapes("mask", msk.shape(), "data", &shape)?;
+    }
     let mut g_arr = Array2::<f64>::zeros((shape[0], shape[1]));
     let mut s_arr = Array2::<f64>::zeros((shape[0], shape[1]));
     for i in 0..n {
@@ -131,7 +205,132 @@ where
             par_exp: Zip::from(lanes).and(&mut g_arr).and(&mut s_arr)
                 .par_for_each(&gs_calc));
     }
-    Ok(stack(Axis(2), &[g_arr.view(), s_arr.view()]).unwrap())
+    let policy = policy.unwrap_or_default();
+    let n_degenerate = par!(threads,
+        seq_exp: Zip::from(&mut g_arr).and(&mut s_arr)
+            .fold(0, |acc, g, s| acc + replace_degenerate(g, s, policy) as usize),
+        par_exp: Zip::from(&mut g_arr).and(&mut s_arr).par_fold(
+            || 0,
+            |acc, g, s| acc + replace_degenerate(g, s, policy) as usize,
+            |a, b| a + b));
+    check_degenerate(n_degenerate, policy)?;
+    Ok((
+        stack(Axis(2), &[g_arr.view(), s_arr.view()]).unwrap(),
+        n_degenerate,
+    ))
+}
+
+/// Compute the real and imaginary (G, S) coordinates of a large 3D decay image
+/// tile by tile.
+///
+/// # Description
+///
+/// Computes the real (G) and imaginary (S) coordinates like
+/// `gs_image_checked`, but loads the decay image one spatial tile at a time
+/// with `load_tile`, so that decay stacks larger than memory (*e.g.*
+/// whole-slide FLIM acquisitions) can be processed from disk or a chunked
+/// store. The phasor coordinates of each pixel only depend on the pixel's own
+/// decay, so the tiles need no overlap (*i.e.* halo) and the stitched image is
+/// identical to computing `gs_image_checked` on the whole image. Tiles are
+/// loaded in row-major order and the tiles at the right and bottom edges are
+/// cropped to `shape`.
+///
+/// # Arguments
+///
+/// * `load_tile`: A function that loads the decay data of the given row and
+///   column ranges of the image, as a 3D decay image with the decay `axis`.
+/// * `shape`: The spatial shape, `(row, col)`, of the whole image.
+/// * `tile_shape`: The spatial shape, `(row, col)`, of the tiles.
+/// * `period`: The period (*i.e.* time interval).
+/// * `mask`: A 2D boolean mask of the pixels to compute with shape `shape`.
+///   Pixels outside of the mask are set to `0.0`. If `None`, all pixels are
+///   computed.
+/// * `harmonic`: The harmonic value. If `None`, then `harmonic = 1.0`.
+/// * `axis`: The decay or lifetime axis of the loaded tiles. If `None`, then
+///   `axis = 2`.
+/// * `policy`: The handling of degenerate pixels. If `None`, then
+///   `policy = DegeneratePolicy::Nan`.
+/// * `threads`: The requested number of threads to use for parallel execution
+///   within each tile. If `None` or `Some(1)` sequential execution is used. If
+///   `Some(0)`, then the maximum available parallelism is used. Thread counts
+///   are clamped to the systems maximum.
+///
+/// # Returns
+///
+/// * `Ok((Array3<f64>, usize))`: The real and imaginary coordinates of the
+///   whole image as a 3D (row, col, ch) image, where G and S are indexed at `0`
+///   and `1` respectively on the *channel* axis, and the number of degenerate
+///   pixels.
+/// * `Err(ImgalError)`: If a `tile_shape` axis is `0`. If the shape of `mask`
+///   does not match `shape`. If a loaded tile does not match its requested
+///   spatial shape or the decay length of the first tile. If `load_tile`
+///   returns an error. See `gs_image_checked` for the errors of each tile.
+pub fn gs_image_tiled<T, F>(
+    mut load_tile: F,
+    shape: (usize, usize),
+    tile_shape: (usize, usize),
+    period: f64,
+    mask: Option<ArrayView2<bool>>,
+    harmonic: Option<f64>,
+    axis: Option<usize>,
+    policy: Option<DegeneratePolicy>,
+    threads: Option<usize>,
+) -> Result<(Array3<f64>, usize), ImgalError>
+where
+    F: FnMut(Range<usize>, Range<usize>) -> Result<Array3<T>, ImgalError>,
+    T: AsNumeric,
+{
+    let axis = axis.unwrap_or(2);
+    check_axis(axis, 3)?;
+    if tile_shape.0 == 0 || tile_shape.1 == 0 {
+        return Err(ImgalError::InvalidParameterValueEqual {
+            param_name: "tile_shape",
+            value: 0,
+        });
+    }
+    if let Some(msk) = mask.as_ref() {
+        check_shapes("mask", msk.shape(), "shape", &[shape.0, shape.1])?;
+    }
+    let mut gs_arr = Array3::<f64>::zeros((shape.0, shape.1, 2));
+    let mut n_degenerate = 0;
+    let mut n_decay: Option<usize> = None;
+    for r0 in (0..shape.0).step_by(tile_shape.0) {
+        for c0 in (0..shape.1).step_by(tile_shape.1) {
+            let rows = r0..(r0 + tile_shape.0).min(shape.0);
+            let cols = c0..(c0 + tile_shape.1).min(shape.1);
+            let tile = load_tile(rows.clone(), cols.clone())?;
+            let mut tile_dims = tile.shape().to_vec();
+            let n = tile_dims.remove(axis);
+            check_shapes(
+                "tile",
+                &tile_dims,
+                "requested tile",
+                &[rows.len(), cols.len()],
+            )?;
+            let n_first = *n_decay.get_or_insert(n);
+            if n != n_first {
+                return Err(ImgalError::MismatchedDimensionLengths {
+                    a_name: "first tile decay",
+                    a_dim_len: n_first,
+                    b_name: "tile decay",
+                    b_dim_len: n,
+                });
+            }
+            let tile_mask = mask.map(|m| m.slice_move(s![rows.clone(), cols.clone()]));
+            let (tile_gs, tile_degenerate) = gs_image_checked(
+                &tile,
+                period,
+                tile_mask,
+                harmonic,
+                Some(axis),
+                policy,
+                threads,
+            )?;
+            gs_arr.slice_mut(s![rows, cols, ..]).assign(&tile_gs);
+            n_degenerate += tile_degenerate;
+        }
+    }
+    Ok((gs_arr, n_degenerate))
 }
 
 /// Compute the real and imaginary (G, S) coordinates of a HashMap of ROI point
@@ -147,6 +346,9 @@ where
 /// S = ∫(I(t) * sin(nωt) * dt) / ∫(I(t) * dt)
 /// ```
 ///
+/// Points whose decay integral is zero produce non-finite coordinates, see
+/// `gs_roi_checked` to configure and count them.
+///
 /// # Arguments
 ///
 /// * `data`: The input decay 3D image.
@@ -166,7 +368,9 @@ where
 ///   labels and values are the G and S values computed at each point in the
 ///   input ROI point cloud. Each computed ROI point cloud has shape `(p, 2)`,
 ///   where `p` is the number of points.
-/// * `Err(ImgalError)`: If `axis >= 3`.
+/// * `Err(ImgalError)`: If `axis >= 3`. If a ROI is not 2D or a ROI point is
+///   out of bounds of `data` without the decay axis. If `harmonic` is greater
+///   than the maximum valid harmonic of the decay sampling.
 pub fn gs_roi<'a, T, A>(
     data: A,
     period: f64,
@@ -175,18 +379,66 @@ pub fn gs_roi<'a, T, A>(
     axis: Option<usize>,
     threads: Option<usize>,
 ) -> Result<HashMap<u64, Array2<f64>>, ImgalError>
+where
+    A: AsArray<'a, T, Ix3>,
+    T: 'a + AsNumeric,
+{
+    gs_roi_checked(data, period, rois, harmonic, axis, None, threads).map(|(gs, _)| gs)
+}
+
+/// Compute the real and imaginary (G, S) coordinates of a HashMap of ROI point
+/// clouds with explicit handling of degenerate points.
+///
+/// # Description
+///
+/// Computes the real and imaginary (G, S) coordinates like `gs_roi`. Points
+/// whose decay integral is zero have undefined coordinates and are handled
+/// with `policy` and counted (see `gs_image_checked`).
+///
+/// # Arguments
+///
+/// * `data`: The input decay 3D image.
+/// * `period`: The period (*i.e.* time interval).
+/// * `rois`: A HashMap of point clouds representing Regions of Interests
+///   (ROIs). 2D ROIs are expected.
+/// * `harmonic`: The harmonic value. If `None`, then `harmonic = 1.0`.
+/// * `axis`: The decay or lifetime axis. If `None`, then `axis = 2`.
+/// * `policy`: The handling of degenerate points. If `None`, then
+///   `policy = DegeneratePolicy::Nan`.
+/// * `threads`: The requested number of threads to use for parallel execution.
+///   If `None` or `Some(1)` sequential execution is used. If `Some(0)`, then
+///   the maximum available parallelism is used. Thread counts are clamped to
+///   the systems maximum.
+///
+/// # Returns
+///
+/// * `Ok((HashMap<u64, Array2<f64>>, usize))`: A HashMap where the keys are
+///   the ROI labels and values are the G and S values computed at each point
+///   in the input ROI point cloud, and the number of degenerate points.
+/// * `Err(ImgalError)`: If `axis >= 3`. If a ROI is not 2D or a ROI point is
+///   out of bounds of `data` without the decay axis. If `harmonic` is greater
+///   than the maximum valid harmonic of the decay sampling. If `policy` is
+///   `DegeneratePolicy::Error` and a point is degenerate.
+pub fn gs_roi_checked<'a, T, A>(
+    data: A,
+    period: f64,
+    rois: &HashMap<u64, Array2<usize>>,
+    harmonic: Option<f64>,
+    axis: Option<usize>,
+    policy: Option<DegeneratePolicy>,
+    threads: Option<usize>,
+) -> Result<(HashMap<u64, Array2<f64>>, usize), ImgalError>
 where
     A: AsArray<'a, T, Ix3>,
     T: 'a + AsNumeric,
 {
     let data: ArrayBase<ViewRepr<&'a T>, Ix3> = data.into();
     let axis = axis.unwrap_or(2);
-    if axis >= 3 {
-        return Err(ImgalError::InvalidAxis {
-            axis_idx: axis,
-            dim_len: 3,
-        });
-    }
+    check_axis(axis, 3)?;
+    let mut shape = data.shape().to_vec();
+    shape.remove(axis);
+    check_roi_bounds(rois, &shape)?;
+    check_harmonic(harmonic.unwrap_or(1.0), data.len_of(Axis(axis)), period)?;
     let vec_to_arr = |k: u64, v: Vec<Vec<f64>>| {
         let arr = Array2::from_shape_vec((v.len(), v[0].len()), v.into_iter().flatten().collect())
             .expect("Failed to reshape ROI point cloud into an Array2<f64>.");
@@ -242,10 +494,22 @@ where
     let cloud_map = par!(threads,
         seq_exp: roi_gs_calc_seq(),
         par_exp: roi_gs_calc_par());
-    Ok(cloud_map
+    let mut gs_map: HashMap<u64, Array2<f64>> = cloud_map
         .into_iter()
         .map(|(k, v)| vec_to_arr(k, v))
-        .collect())
+        .collect();
+    let policy = policy.unwrap_or_default();
+    let n_degenerate = gs_map.values_mut().fold(0, |acc, v| {
+        v.rows_mut().into_iter().fold(acc, |acc, mut r| {
+            let (mut g, mut s) = (r[0], r[1]);
+            let degenerate = replace_degenerate(&mut g, &mut s, policy);
+            r[0] = g;
+            r[1] = s;
+            acc + degenerate as usize
+        })
+    });
+    check_degenerate(n_degenerate, policy)?;
+    Ok((gs_map, n_degenerate))
 }
 
 /// Compute the imaginary (S) component of a 1D decay array.
@@ -345,3 +609,45 @@ where
         .collect();
     midpoint(&buf, Some(dt), threads) / midpoint(data, Some(dt), threads)
 }
+
+/// Replace non-finite (G, S) coordinates of a degenerate pixel with the policy
+/// fill value, returning `true` if the pixel is degenerate.
+#[inline]
+fn replace_degenerate(g: &mut f64, s: &mut f64, policy: DegeneratePolicy) -> bool {
+    if g.is_finite() && s.is_finite() {
+        return false;
+    }
+    let fill = match policy {
+        DegeneratePolicy::Zero => 0.0,
+        _ => f64::NAN,
+    };
+    *g = fill;
+    *s = fill;
+    true
+}
+
+/// Return an error if the harmonic is at or above the Nyquist limit of the
+/// decay sampling, which silently aliases the (G, S) coordinates.
+fn check_harmonic(harmonic: f64, samples: usize, period: f64) -> Result<(), ImgalError> {
+    let limit = max_valid_harmonic(samples, period)?;
+    if harmonic > limit as f64 {
+        return Err(ImgalError::InvalidParameterValueOutsideRange {
+            param_name: "harmonic",
+            value: harmonic,
+            min: 0.0,
+            max: limit as f64,
+        });
+    }
+    Ok(())
+}
+
+/// Return an error for degenerate pixels if the policy is
+/// `DegeneratePolicy::Error`.
+fn check_degenerate(n_degenerate: usize, policy: DegeneratePolicy) -> Result<(), ImgalError> {
+    if policy == DegeneratePolicy::Error && n_degenerate > 0 {
+        return Err(ImgalError::InvalidDecayIntegral {
+            n_pixels: n_degenerate,
+        });
+    }
+    Ok(())
+}
@@ -1,3 +1,6 @@
-//! The imgal prelude imports commonly used types, enums and macros.
+//! The imgal prelude imports commonly used types, enums, builders and macros.
 
+pub use crate::filter::BorderMode;
+pub use crate::phasor::builder::Phasor;
+pub use crate::phasor::time_domain::DegeneratePolicy;
 pub use crate::{AsNumeric, ImgalError};
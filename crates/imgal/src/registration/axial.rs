@@ -0,0 +1,294 @@
+use ndarray::{Array4, ArrayBase, ArrayView3, AsArray, Axis, Ix2, Ix3, Ix4, ViewRepr, s};
+use rayon::prelude::*;
+
+use crate::metrics::detection::match_points;
+use crate::prelude::*;
+use crate::validate::check_shapes;
+
+/// Estimate the axial offset between two 3D stacks by cross-correlation along
+/// the plane axis.
+///
+/// # Description
+///
+/// Estimates the axial (plane axis) displacement `o` of a `moving` stack
+/// relative to a `reference` stack of the same sample (*e.g.* two channels
+/// with an axial chromatic offset), such that `moving(z) ≈ reference(z - o)`.
+/// The Pearson correlation of the overlapping planes is computed for each
+/// integer shift `s` up to `max_offset`:
+///
+/// ```text
+/// C(s) = corr(reference(z), moving(z + s))
+/// ```
+///
+/// The shift with the maximum correlation is refined to subvoxel precision by
+/// fitting a parabola to the correlations of its neighbors. The shifts are
+/// evaluated in parallel.
+///
+/// # Arguments
+///
+/// * `reference`: The reference 3D stack with shape `(pln, row, col)`.
+/// * `moving`: The moving 3D stack with the same shape as `reference`.
+/// * `max_offset`: The maximum absolute shift in planes. If `None`, then
+///   `max_offset = max(pln / 4, 1)`.
+/// * `threads`: The requested number of threads to use for parallel execution.
+///   If `None` or `Some(1)` sequential execution is used. If `Some(0)`, then
+///   the maximum available parallelism is used. Thread counts are clamped to
+///   the systems maximum.
+///
+/// # Returns
+///
+/// * `Ok(f64)`: The axial offset `o` of `moving` in planes.
+/// * `Err(ImgalError)`: If the shapes of `reference` and `moving` do not
+///   match. If the stacks have fewer than `2` planes. If
+///   `max_offset >= pln`.
+pub fn axial_offset<'a, T, A>(
+    reference: A,
+    moving: A,
+    max_offset: Option<usize>,
+    threads: Option<usize>,
+) -> Result<f64, ImgalError>
+where
+    A: AsArray<'a, T, Ix3>,
+    T: 'a + AsNumeric,
+{
+    let reference: ArrayBase<ViewRepr<&'a T>, Ix3> = reference.into();
+    let moving: ArrayBase<ViewRepr<&'a T>, Ix3> = moving.into();
+    check_shapes("moving", moving.shape(), "reference", reference.shape())?;
+    let pln = reference.len_of(Axis(0));
+    if pln < 2 {
+        return Err(ImgalError::InvalidAxisLengthExpected {
+            arr_name: "reference",
+            axis_idx: 0,
+            expected: 2,
+            got: pln,
+        });
+    }
+    let max_offset = max_offset.unwrap_or((pln / 4).max(1));
+    if max_offset >= pln {
+        return Err(ImgalError::InvalidParameterValueOutsideRange {
+            param_name: "max_offset",
+            value: max_offset as f64,
+            min: 0.0,
+            max: (pln - 1) as f64,
+        });
+    }
+    let reference = reference.mapv(|v| v.to_f64());
+    let moving = moving.mapv(|v| v.to_f64());
+    let correlation = |s: isize| -> f64 {
+        // the overlapping planes reference[z] and moving[z + s]
+        let (lo, hi) = ((-s).max(0), (pln as isize - s).min(pln as isize));
+        let a = reference.slice(s![lo..hi, .., ..]);
+        let b = moving.slice(s![lo + s..hi + s, .., ..]);
+        pearson(a, b)
+    };
+    let shifts: Vec<isize> = (-(max_offset as isize)..=max_offset as isize).collect();
+    let scores: Vec<f64> = par!(threads,
+        seq_exp: shifts.iter().map(|&s| correlation(s)).collect(),
+        par_exp: shifts.par_iter().map(|&s| correlation(s)).collect());
+    let best = scores
+        .iter()
+        .enumerate()
+        .max_by(|a, b| a.1.total_cmp(b.1))
+        .map(|(i, _)| i)
+        .unwrap();
+    let mut offset = shifts[best] as f64;
+    if best > 0 && best + 1 < scores.len() {
+        let (lo, mid, hi) = (scores[best - 1], scores[best], scores[best + 1]);
+        let denom = lo - 2.0 * mid + hi;
+        if denom < 0.0 {
+            offset += (0.5 * (lo - hi) / denom).clamp(-0.5, 0.5);
+        }
+    }
+    Ok(offset)
+}
+
+/// Estimate the axial offset between two channels from matched bead
+/// coordinates.
+///
+/// # Description
+///
+/// Matches the bead coordinates detected in a moving channel to the bead
+/// coordinates of a reference channel one-to-one with
+/// `metrics::detection::match_points` and returns the median axial (first
+/// column) displacement `moving - reference` of the matched beads, which is
+/// robust to a few mismatched or poorly localized beads.
+///
+/// # Arguments
+///
+/// * `reference`: The bead coordinates of the reference channel with shape
+///   `(p, D)`, where the first column is the plane coordinate.
+/// * `moving`: The bead coordinates of the moving channel with shape `(q, D)`.
+/// * `max_distance`: The maximum distance between matched beads.
+///
+/// # Returns
+///
+/// * `Ok(f64)`: The axial offset of the moving channel in planes.
+/// * `Err(ImgalError)`: If `reference` and `moving` do not have the same
+///   number of columns. If `max_distance < 0.0`. If no beads are matched.
+pub fn bead_axial_offset<'a, A>(
+    reference: A,
+    moving: A,
+    max_distance: f64,
+) -> Result<f64, ImgalError>
+where
+    A: AsArray<'a, f64, Ix2>,
+{
+    let reference: ArrayBase<ViewRepr<&'a f64>, Ix2> = reference.into();
+    let moving: ArrayBase<ViewRepr<&'a f64>, Ix2> = moving.into();
+    let matches = match_points(moving.view(), reference.view(), max_distance)?;
+    if matches.is_empty() {
+        return Err(ImgalError::InvalidGeneric {
+            msg: "Failed to estimate the axial offset, no beads are matched within the maximum distance.",
+        });
+    }
+    let mut dz: Vec<f64> = matches
+        .iter()
+        .map(|&(i, j, _)| moving[[i, 0]] - reference[[j, 0]])
+        .collect();
+    dz.sort_by(f64::total_cmp);
+    let n = dz.len();
+    Ok(if n % 2 == 1 {
+        dz[n / 2]
+    } else {
+        0.5 * (dz[n / 2 - 1] + dz[n / 2])
+    })
+}
+
+/// Correct the axial offsets between the channels of a multichannel 3D image.
+///
+/// # Description
+///
+/// Shifts each channel of a `(ch, pln, row, col)` image along the plane axis
+/// to align it with the reference channel (*e.g.* to correct the axial
+/// chromatic offsets of an objective, complementing a lateral registration).
+/// The offset `oₖ` of each channel is either given (*e.g.* from
+/// `bead_axial_offset` on a bead stack) or estimated from the image itself
+/// with `axial_offset`. The corrected channel is resampled with linear
+/// interpolation between planes, so subvoxel offsets are applied:
+///
+/// ```text
+/// Iₖ'(z) = Iₖ(z + oₖ)
+/// ```
+///
+/// Planes shifted in from outside the stack are set to `0.0`.
+///
+/// # Arguments
+///
+/// * `data`: The multichannel 3D image with shape `(ch, pln, row, col)`.
+/// * `reference_channel`: The index of the reference channel of the offset
+///   estimation.
+/// * `offsets`: The axial offset of each channel in planes, applied as given.
+///   If `None`, the offsets are estimated with `axial_offset` relative to the
+///   reference channel (*i.e.* the reference channel is not shifted).
+/// * `max_offset`: The maximum absolute offset in planes of the estimation.
+///   If `None`, then `max_offset = max(pln / 4, 1)`.
+/// * `threads`: The requested number of threads to use for parallel execution.
+///   If `None` or `Some(1)` sequential execution is used. If `Some(0)`, then
+///   the maximum available parallelism is used. Thread counts are clamped to
+///   the systems maximum.
+///
+/// # Returns
+///
+/// * `Ok((Array4<f64>, Vec<f64>))`: The corrected image and the axial offset
+///   of each channel.
+/// * `Err(ImgalError)`: If `reference_channel` is out of bounds. If
+///   `offsets.len()` does not match the number of channels or an offset is not
+///   finite. If an offset is estimated and the stack has fewer than `2` planes
+///   or `max_offset >= pln`.
+pub fn axial_offset_correct<'a, T, A>(
+    data: A,
+    reference_channel: usize,
+    offsets: Option<&[f64]>,
+    max_offset: Option<usize>,
+    threads: Option<usize>,
+) -> Result<(Array4<f64>, Vec<f64>), ImgalError>
+where
+    A: AsArray<'a, T, Ix4>,
+    T: 'a + AsNumeric,
+{
+    let data: ArrayBase<ViewRepr<&'a T>, Ix4> = data.into();
+    let n_ch = data.len_of(Axis(0));
+    if reference_channel >= n_ch {
+        return Err(ImgalError::InvalidAxisValueGreaterEqual {
+            arr_name: "data",
+            axis_idx: 0,
+            value: reference_channel,
+        });
+    }
+    let offsets: Vec<f64> = match offsets {
+        Some(o) => {
+            if o.len() != n_ch {
+                return Err(ImgalError::InvalidArrayLengthExpected {
+                    arr_name: "offsets",
+                    expected: n_ch,
+                    got: o.len(),
+                });
+            }
+            if let Some(&v) = o.iter().find(|v| !v.is_finite()) {
+                return Err(ImgalError::InvalidParameterValueOutsideRange {
+                    param_name: "offsets",
+                    value: v,
+                    min: f64::NEG_INFINITY,
+                    max: f64::INFINITY,
+                });
+            }
+            o.to_vec()
+        }
+        None => {
+            let reference = data.index_axis(Axis(0), reference_channel);
+            (0..n_ch)
+                .map(|c| {
+                    if c == reference_channel {
+                        Ok(0.0)
+                    } else {
+                        axial_offset(
+                            reference.view(),
+                            data.index_axis(Axis(0), c),
+                            max_offset,
+                            threads,
+                        )
+                    }
+                })
+                .collect::<Result<Vec<f64>, ImgalError>>()?
+        }
+    };
+    let mut corrected = Array4::<f64>::zeros(data.raw_dim());
+    corrected
+        .outer_iter_mut()
+        .zip(data.outer_iter())
+        .zip(offsets.iter())
+        .for_each(|((mut out, ch), &o)| {
+            let pln = ch.len_of(Axis(0));
+            for (z, mut plane) in out.outer_iter_mut().enumerate() {
+                let pos = z as f64 + o;
+                let z0 = pos.floor();
+                let frac = pos - z0;
+                for (zi, w) in [(z0, 1.0 - frac), (z0 + 1.0, frac)] {
+                    if w > 0.0 && zi >= 0.0 && zi < pln as f64 {
+                        plane.zip_mut_with(&ch.index_axis(Axis(0), zi as usize), |p, v| {
+                            *p += w * v.to_f64()
+                        });
+                    }
+                }
+            }
+        });
+    Ok((corrected, offsets))
+}
+
+/// Compute the Pearson correlation coefficient of two equally shaped stacks.
+fn pearson(a: ArrayView3<f64>, b: ArrayView3<f64>) -> f64 {
+    let n = a.len() as f64;
+    let (mean_a, mean_b) = (a.sum() / n, b.sum() / n);
+    let (mut cov, mut var_a, mut var_b) = (0.0, 0.0, 0.0);
+    a.iter().zip(b.iter()).for_each(|(&x, &y)| {
+        let (dx, dy) = (x - mean_a, y - mean_b);
+        cov += dx * dy;
+        var_a += dx * dx;
+        var_b += dy * dy;
+    });
+    if var_a > 0.0 && var_b > 0.0 {
+        cov / (var_a * var_b).sqrt()
+    } else {
+        0.0
+    }
+}
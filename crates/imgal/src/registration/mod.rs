@@ -0,0 +1,11 @@
+//! Image registration functions.
+//!
+//! This module provides functions for aligning the channels of multichannel
+//! images, such as the estimation and subvoxel correction of axial chromatic
+//! offsets in 3D.
+
+mod axial;
+
+pub use axial::axial_offset;
+pub use axial::axial_offset_correct;
+pub use axial::bead_axial_offset;
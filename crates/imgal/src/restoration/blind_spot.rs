@@ -0,0 +1,477 @@
+use std::cmp::Ordering;
+
+use ndarray::{
+    Array, Array2, ArrayBase, ArrayD, ArrayViewD, AsArray, Axis, Dimension, Ix2, IxDyn, Slice,
+    ViewRepr, indices,
+};
+
+use crate::constants::RNG_SEED;
+use crate::prelude::*;
+use crate::simulation::rng::Pcg;
+
+/// The replacement strategy for blind-spot masked pixels.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum ReplacementStrategy {
+    /// Replace the pixel with a uniformly sampled pixel from its neighborhood,
+    /// excluding the pixel itself (*i.e.* Noise2Void).
+    #[default]
+    RandomNeighbor,
+    /// Replace the pixel with the median of its neighborhood, excluding the
+    /// pixel itself (*i.e.* Noise2Void 2).
+    NeighborMedian,
+    /// Replace the pixel with `0.0`.
+    Zero,
+}
+
+/// The masked patch output of `blind_spot_mask`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BlindSpotMask<D: Dimension> {
+    /// The blind-spot masked patch.
+    pub masked: Array<f64, D>,
+    /// The boolean mask of the masked pixels.
+    pub mask: Array<bool, D>,
+}
+
+/// The training patch output of `blind_spot_patches`, each array has the
+/// shape `(n_patches, ...patch_shape)`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BlindSpotPatches {
+    /// The blind-spot masked input patches.
+    pub inputs: ArrayD<f64>,
+    /// The original target patches.
+    pub targets: ArrayD<f64>,
+    /// The boolean masks of the masked pixels.
+    pub masks: ArrayD<bool>,
+}
+
+/// Mask an n-dimensional patch for blind-spot self-supervised denoising.
+///
+/// # Description
+///
+/// Creates a blind-spot masked copy of an n-dimensional patch for training
+/// self-supervised denoising models (*e.g.* Noise2Void). Pixel noise is assumed
+/// to be independent while the signal is not, so a model trained to predict the
+/// value of a masked pixel from its neighborhood learns to predict the signal
+/// and not the noise. Masked pixels are selected with stratified random
+/// sampling, one random pixel per grid box of side length:
+///
+/// ```text
+/// g = round(fraction^(-1 / D))
+/// ```
+///
+/// Where `D` is the number of dimensions, such that roughly `fraction` of the
+/// pixels are masked. Each masked pixel is replaced according to `strategy`
+/// using the neighborhood hypercube of `radius` around the pixel (clipped at
+/// the patch borders). The training loss is computed between the model
+/// prediction and the original patch at the masked positions only.
+///
+/// # Arguments
+///
+/// * `data`: The input n-dimensional patch.
+/// * `fraction`: The fraction of masked pixels in the range `0.0` to `1.0`.
+/// * `radius`: The neighborhood radius for replacement values. If `None`, then
+///   `radius = 2`.
+/// * `strategy`: The replacement strategy of masked pixels.
+/// * `seed`: The seed value for the pseudo-random number generator. If `None`,
+///   then the default seed is used.
+///
+/// # Returns
+///
+/// * `Ok(BlindSpotMask<D>)`: The masked patch and the boolean mask of the
+///   masked pixels.
+/// * `Err(ImgalError)`: If `data` is empty. If `fraction` is outside the range
+///   `0.0` (exclusive) to `1.0`. If `radius == 0`.
+pub fn blind_spot_mask<'a, T, A, D>(
+    data: A,
+    fraction: f64,
+    radius: Option<usize>,
+    strategy: ReplacementStrategy,
+    seed: Option<u64>,
+) -> Result<BlindSpotMask<D>, ImgalError>
+where
+    A: AsArray<'a, T, D>,
+    D: Dimension,
+    T: 'a + AsNumeric,
+{
+    let data: ArrayBase<ViewRepr<&'a T>, D> = data.into();
+    if data.is_empty() {
+        return Err(ImgalError::InvalidParameterEmptyArray { param_name: "data" });
+    }
+    let radius = radius.unwrap_or(2);
+    validate_mask_params(fraction, radius)?;
+    let data = data.mapv(|v| v.to_f64());
+    let mut prng = Pcg::new(seed.unwrap_or(RNG_SEED));
+    let (masked, mask) = mask_patch(
+        data.view().into_dyn(),
+        fraction,
+        radius,
+        strategy,
+        &mut prng,
+    )?;
+    let masked = masked
+        .into_dimensionality::<D>()
+        .expect("Failed to reshape the masked patch into the input dimensionality.");
+    let mask = mask
+        .into_dimensionality::<D>()
+        .expect("Failed to reshape the mask into the input dimensionality.");
+    Ok(BlindSpotMask { masked, mask })
+}
+
+/// Create blind-spot masked training patches from an n-dimensional image.
+///
+/// # Description
+///
+/// Extracts `n_patches` patches of `patch_shape` at uniformly distributed
+/// random positions of an n-dimensional image and masks each patch with
+/// `blind_spot_mask`, creating a training set for blind-spot self-supervised
+/// denoising models (*e.g.* Noise2Void).
+///
+/// # Arguments
+///
+/// * `data`: The input n-dimensional image.
+/// * `patch_shape`: The shape of each patch, with the same number of dimensions
+///   as `data`.
+/// * `n_patches`: The number of patches.
+/// * `fraction`: The fraction of masked pixels in the range `0.0` to `1.0`.
+/// * `radius`: The neighborhood radius for replacement values. If `None`, then
+///   `radius = 2`.
+/// * `strategy`: The replacement strategy of masked pixels.
+/// * `seed`: The seed value for the pseudo-random number generator. If `None`,
+///   then the default seed is used.
+///
+/// # Returns
+///
+/// * `Ok(BlindSpotPatches)`: The masked input patches, the original target
+///   patches and the masks, each with the shape `(n_patches, ...patch_shape)`.
+/// * `Err(ImgalError)`: If `patch_shape.len() != data.ndim()`. If a patch axis
+///   is `0` or larger than the image axis. If `fraction` is outside the range
+///   `0.0` (exclusive) to `1.0`. If `radius == 0`.
+pub fn blind_spot_patches<'a, T, A, D>(
+    data: A,
+    patch_shape: &[usize],
+    n_patches: usize,
+    fraction: f64,
+    radius: Option<usize>,
+    strategy: ReplacementStrategy,
+    seed: Option<u64>,
+) -> Result<BlindSpotPatches, ImgalError>
+where
+    A: AsArray<'a, T, D>,
+    D: Dimension,
+    T: 'a + AsNumeric,
+{
+    let data: ArrayBase<ViewRepr<&'a T>, D> = data.into();
+    validate_patch_shape(data.shape(), patch_shape)?;
+    let radius = radius.unwrap_or(2);
+    validate_mask_params(fraction, radius)?;
+    let data = data.mapv(|v| v.to_f64()).into_dyn();
+    let mut stack_shape = vec![n_patches];
+    stack_shape.extend_from_slice(patch_shape);
+    let mut inputs = ArrayD::<f64>::zeros(IxDyn(&stack_shape));
+    let mut targets = ArrayD::<f64>::zeros(IxDyn(&stack_shape));
+    let mut masks = ArrayD::<bool>::from_elem(IxDyn(&stack_shape), false);
+    let mut prng = Pcg::new(seed.unwrap_or(RNG_SEED));
+    for i in 0..n_patches {
+        let mut patch = data.view();
+        for (ax, (&len, &p)) in data.shape().iter().zip(patch_shape.iter()).enumerate() {
+            let start = prng.next_u32_range(0..=(len - p) as u32)? as usize;
+            patch.slice_axis_inplace(Axis(ax), Slice::from(start..start + p));
+        }
+        let (masked, mask) = mask_patch(patch.view(), fraction, radius, strategy, &mut prng)?;
+        inputs.index_axis_mut(Axis(0), i).assign(&masked);
+        targets.index_axis_mut(Axis(0), i).assign(&patch);
+        masks.index_axis_mut(Axis(0), i).assign(&mask);
+    }
+    Ok(BlindSpotPatches {
+        inputs,
+        targets,
+        masks,
+    })
+}
+
+/// Extract overlapping patches covering an n-dimensional image.
+///
+/// # Description
+///
+/// Extracts overlapping patches of `patch_shape` that cover an n-dimensional
+/// image for patch-wise inference (*e.g.* with a trained denoising model).
+/// Patches are placed with a stride of `patch_shape - overlap` along each axis,
+/// and the last patch of each axis is aligned with the image border. The
+/// processed patches are reassembled with `stitch_patches`.
+///
+/// # Arguments
+///
+/// * `data`: The input n-dimensional image.
+/// * `patch_shape`: The shape of each patch, with the same number of dimensions
+///   as `data`.
+/// * `overlap`: The overlap between neighboring patches in pixels.
+///
+/// # Returns
+///
+/// * `Ok((ArrayD<f64>, Array2<usize>))`: A tuple containing the patch stack
+///   with the shape `(n_patches, ...patch_shape)` and the patch origins with
+///   the shape `(n_patches, D)`, *i.e.* `(patches, origins)`.
+/// * `Err(ImgalError)`: If `patch_shape.len() != data.ndim()`. If a patch axis
+///   is `0` or larger than the image axis. If `overlap` is not smaller than
+///   every patch axis.
+pub fn extract_patches<'a, T, A, D>(
+    data: A,
+    patch_shape: &[usize],
+    overlap: usize,
+) -> Result<(ArrayD<f64>, Array2<usize>), ImgalError>
+where
+    A: AsArray<'a, T, D>,
+    D: Dimension,
+    T: 'a + AsNumeric,
+{
+    let data: ArrayBase<ViewRepr<&'a T>, D> = data.into();
+    validate_patch_shape(data.shape(), patch_shape)?;
+    let min_patch = *patch_shape.iter().min().unwrap_or(&0);
+    if overlap >= min_patch {
+        return Err(ImgalError::InvalidParameterValueGreater {
+            param_name: "overlap",
+            value: min_patch.saturating_sub(1),
+        });
+    }
+    // the patch start positions along each axis
+    let starts: Vec<Vec<usize>> = data
+        .shape()
+        .iter()
+        .zip(patch_shape.iter())
+        .map(|(&len, &p)| {
+            let stride = p - overlap;
+            let mut s: Vec<usize> = (0..len - p).step_by(stride).collect();
+            s.push(len - p);
+            s
+        })
+        .collect();
+    let grid: Vec<usize> = starts.iter().map(|s| s.len()).collect();
+    let n_patches: usize = grid.iter().product();
+    let d = patch_shape.len();
+    let mut stack_shape = vec![n_patches];
+    stack_shape.extend_from_slice(patch_shape);
+    let mut patches = ArrayD::<f64>::zeros(IxDyn(&stack_shape));
+    let mut origins = Array2::<usize>::zeros((n_patches, d));
+    let data = data.view().into_dyn();
+    indices(IxDyn(&grid))
+        .into_iter()
+        .enumerate()
+        .for_each(|(i, g)| {
+            let mut patch = data.view();
+            (0..d).for_each(|ax| {
+                let start = starts[ax][g[ax]];
+                origins[[i, ax]] = start;
+                patch.slice_axis_inplace(Axis(ax), Slice::from(start..start + patch_shape[ax]));
+            });
+            patches
+                .index_axis_mut(Axis(0), i)
+                .zip_mut_with(&patch, |a, b| *a = b.to_f64());
+        });
+    Ok((patches, origins))
+}
+
+/// Stitch a stack of patches into an n-dimensional image.
+///
+/// # Description
+///
+/// Reassembles a stack of (*e.g.* processed) patches into an n-dimensional
+/// image, where each patch is placed at its origin (*e.g.* from
+/// `extract_patches`). Overlapping regions are averaged:
+///
+/// ```text
+/// I(x) = Σₚ Pₚ(x - oₚ) / Nₚ(x)
+/// ```
+///
+/// Where `oₚ` is the origin of patch `p` and `Nₚ(x)` is the number of patches
+/// covering `x`. Positions not covered by any patch are `0.0`.
+///
+/// # Arguments
+///
+/// * `patches`: The patch stack with the shape `(n_patches, ...patch_shape)`.
+/// * `origins`: The patch origins with the shape `(n_patches, D)`.
+/// * `shape`: The shape of the output image.
+///
+/// # Returns
+///
+/// * `Ok(ArrayD<f64>)`: The stitched image with the given `shape`.
+/// * `Err(ImgalError)`: If the number of patches and origins do not match. If
+///   the patch dimensions, origin dimensions and `shape` do not match. If a
+///   patch exceeds the image bounds.
+pub fn stitch_patches<'a, T, A, B>(
+    patches: A,
+    origins: B,
+    shape: &[usize],
+) -> Result<ArrayD<f64>, ImgalError>
+where
+    A: AsArray<'a, T, IxDyn>,
+    B: AsArray<'a, usize, Ix2>,
+    T: 'a + AsNumeric,
+{
+    let patches: ArrayBase<ViewRepr<&'a T>, IxDyn> = patches.into();
+    let origins: ArrayBase<ViewRepr<&'a usize>, Ix2> = origins.into();
+    let (n_origins, d) = origins.dim();
+    if patches.ndim() == 0 || patches.len_of(Axis(0)) != n_origins {
+        return Err(ImgalError::MismatchedArrayLengths {
+            a_arr_name: "patches",
+            a_arr_len: patches.shape().first().copied().unwrap_or(0),
+            b_arr_name: "origins",
+            b_arr_len: n_origins,
+        });
+    }
+    if patches.ndim() != d + 1 || shape.len() != d {
+        return Err(ImgalError::MismatchedDimensionLengths {
+            a_name: "patches",
+            a_dim_len: patches.ndim().saturating_sub(1),
+            b_name: "shape",
+            b_dim_len: shape.len(),
+        });
+    }
+    let patch_shape = &patches.shape()[1..];
+    let mut sum_arr = ArrayD::<f64>::zeros(IxDyn(shape));
+    let mut count_arr = ArrayD::<f64>::zeros(IxDyn(shape));
+    for (patch, origin) in patches.axis_iter(Axis(0)).zip(origins.rows()) {
+        let mut sum_view = sum_arr.view_mut();
+        let mut count_view = count_arr.view_mut();
+        for ax in 0..d {
+            let end = origin[ax] + patch_shape[ax];
+            if end > shape[ax] {
+                return Err(ImgalError::InvalidAxisLengthLess {
+                    arr_name: "shape",
+                    axis_idx: ax,
+                    value: end,
+                });
+            }
+            sum_view.slice_axis_inplace(Axis(ax), Slice::from(origin[ax]..end));
+            count_view.slice_axis_inplace(Axis(ax), Slice::from(origin[ax]..end));
+        }
+        sum_view.zip_mut_with(&patch, |a, b| *a += b.to_f64());
+        count_view.mapv_inplace(|c| c + 1.0);
+    }
+    sum_arr.zip_mut_with(&count_arr, |s, &c| {
+        if c > 0.0 {
+            *s /= c;
+        }
+    });
+    Ok(sum_arr)
+}
+
+/// Mask a patch with stratified random sampling.
+fn mask_patch(
+    data: ArrayViewD<f64>,
+    fraction: f64,
+    radius: usize,
+    strategy: ReplacementStrategy,
+    prng: &mut Pcg,
+) -> Result<(ArrayD<f64>, ArrayD<bool>), ImgalError> {
+    let shape = data.shape().to_vec();
+    let d = shape.len();
+    let box_len = (fraction.powf(-1.0 / d as f64).round() as usize).max(1);
+    let grid: Vec<usize> = shape.iter().map(|&s| s.div_ceil(box_len)).collect();
+    let mut masked = data.to_owned();
+    let mut mask = ArrayD::<bool>::from_elem(IxDyn(&shape), false);
+    let r = radius as isize;
+    for g in indices(IxDyn(&grid)) {
+        // a random pixel within the grid box
+        let mut pos = vec![0usize; d];
+        for ax in 0..d {
+            let start = g[ax] * box_len;
+            let len = box_len.min(shape[ax] - start);
+            pos[ax] = start + prng.next_u32_range(0..len as u32)? as usize;
+        }
+        // the neighborhood bounds, clipped at the patch borders
+        let bounds: Vec<(usize, usize)> = (0..d)
+            .map(|ax| {
+                let lo = (pos[ax] as isize - r).max(0) as usize;
+                let hi = (pos[ax] + radius + 1).min(shape[ax]);
+                (lo, hi)
+            })
+            .collect();
+        let n_hood: usize = bounds.iter().map(|(lo, hi)| hi - lo).product();
+        let neighbor_at = |k: usize| -> Vec<usize> {
+            let mut k = k;
+            let mut idx = vec![0usize; d];
+            for ax in (0..d).rev() {
+                let len = bounds[ax].1 - bounds[ax].0;
+                idx[ax] = bounds[ax].0 + k % len;
+                k /= len;
+            }
+            idx
+        };
+        let value = match strategy {
+            ReplacementStrategy::RandomNeighbor if n_hood > 1 => loop {
+                let idx = neighbor_at(prng.next_u32_range(0..n_hood as u32)? as usize);
+                if idx != pos {
+                    break data[IxDyn(&idx)];
+                }
+            },
+            ReplacementStrategy::NeighborMedian if n_hood > 1 => {
+                let mut vals: Vec<f64> = (0..n_hood)
+                    .map(neighbor_at)
+                    .filter(|idx| *idx != pos)
+                    .map(|idx| data[IxDyn(&idx)])
+                    .collect();
+                let n = vals.len();
+                let cmp = |a: &f64, b: &f64| a.partial_cmp(b).unwrap_or(Ordering::Less);
+                let (lower, upper, _) = vals.select_nth_unstable_by(n / 2, cmp);
+                if n % 2 == 1 {
+                    *upper
+                } else {
+                    0.5 * (lower.iter().cloned().fold(f64::MIN, f64::max) + *upper)
+                }
+            }
+            ReplacementStrategy::Zero => 0.0,
+            // a single pixel patch has no neighbors
+            _ => data[IxDyn(&pos)],
+        };
+        masked[IxDyn(&pos)] = value;
+        mask[IxDyn(&pos)] = true;
+    }
+    Ok((masked, mask))
+}
+
+/// Validate the blind-spot masking parameters.
+fn validate_mask_params(fraction: f64, radius: usize) -> Result<(), ImgalError> {
+    if fraction <= 0.0 || fraction > 1.0 || fraction.is_nan() {
+        return Err(ImgalError::InvalidParameterValueOutsideRange {
+            param_name: "fraction",
+            value: fraction,
+            min: 0.0,
+            max: 1.0,
+        });
+    }
+    if radius == 0 {
+        return Err(ImgalError::InvalidParameterValueLess {
+            param_name: "radius",
+            value: 1,
+        });
+    }
+    Ok(())
+}
+
+/// Validate a patch shape against an image shape.
+fn validate_patch_shape(shape: &[usize], patch_shape: &[usize]) -> Result<(), ImgalError> {
+    if patch_shape.len() != shape.len() {
+        return Err(ImgalError::MismatchedDimensionLengths {
+            a_name: "data",
+            a_dim_len: shape.len(),
+            b_name: "patch_shape",
+            b_dim_len: patch_shape.len(),
+        });
+    }
+    for (ax, (&len, &p)) in shape.iter().zip(patch_shape.iter()).enumerate() {
+        if p == 0 {
+            return Err(ImgalError::InvalidParameterValueLess {
+                param_name: "patch_shape",
+                value: 1,
+            });
+        }
+        if p > len {
+            return Err(ImgalError::InvalidAxisLengthLess {
+                arr_name: "data",
+                axis_idx: ax,
+                value: p,
+            });
+        }
+    }
+    Ok(())
+}
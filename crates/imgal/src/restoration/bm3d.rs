@@ -0,0 +1,322 @@
+use std::cmp::Ordering;
+use std::f64::consts::PI;
+
+use ndarray::{Array2, ArrayBase, AsArray, Ix2, ViewRepr};
+use rayon::prelude::*;
+
+use crate::prelude::*;
+
+/// The hard threshold multiplier of the noise standard deviation.
+const HARD_THRESHOLD: f64 = 2.7;
+
+/// The block matching and collaborative filtering parameters.
+struct Bm3dParams {
+    patch_size: usize,
+    search_radius: usize,
+    max_matches: usize,
+    step: usize,
+}
+
+/// Denoise a 2D image with block-matching and 3D collaborative filtering
+/// (BM3D).
+///
+/// # Description
+///
+/// Denoises a 2D image corrupted by additive white Gaussian noise with a
+/// simplified BM3D algorithm, providing strong classical denoising for very low
+/// SNR fluorescence images (*e.g.* before segmentation). For each reference
+/// patch on a grid with spacing `step`, the most similar patches within the
+/// search window are stacked into a 3D group (*i.e.* block matching). The group
+/// is transformed with a separable orthonormal 3D discrete cosine transform
+/// (DCT), where the sparse representation of the similar patches concentrates
+/// the signal into few coefficients, shrunk and transformed back. The filtered
+/// patches of all groups are aggregated with a weighted average. The algorithm
+/// runs in two stages:
+///
+/// 1. Hard thresholding of the group coefficients below `2.7 × σ`, producing a
+///    basic estimate.
+/// 2. Block matching on the basic estimate and empirical Wiener filtering of
+///    the noisy group coefficients with the shrinkage `B² / (B² + σ²)`, where
+///    `B` are the group coefficients of the basic estimate.
+///
+/// For Poisson (shot) noise, first stabilize the variance with `anscombe` and
+/// use `sigma = 1.0`, then invert the result with `inverse_anscombe`.
+///
+/// # Arguments
+///
+/// * `data`: The input 2D image.
+/// * `sigma`: The standard deviation of the Gaussian noise.
+/// * `patch_size`: The side length of the square patches. If `None`, then
+///   `patch_size = 8`.
+/// * `search_radius`: The radius of the block matching search window. If
+///   `None`, then `search_radius = 16`.
+/// * `max_matches`: The maximum number of patches per group, including the
+///   reference patch. If `None`, then `max_matches = 16`.
+/// * `step`: The spacing between reference patches. If `None`, then
+///   `step = 3`.
+/// * `threads`: The requested number of threads to use for parallel execution.
+///   If `None` or `Some(1)` sequential execution is used. If `Some(0)`, then
+///   the maximum available parallelism is used. Thread counts are clamped to
+///   the systems maximum.
+///
+/// # Returns
+///
+/// * `Ok(Array2<f64>)`: The denoised image with the same shape as `data`.
+/// * `Err(ImgalError)`: If `sigma <= 0.0`. If `patch_size == 0`, `step == 0`
+///   or `max_matches == 0`. If an axis of `data` is smaller than `patch_size`.
+///
+/// # Reference
+///
+/// <https://doi.org/10.1109/TIP.2007.901238>
+pub fn bm3d<'a, T, A>(
+    data: A,
+    sigma: f64,
+    patch_size: Option<usize>,
+    search_radius: Option<usize>,
+    max_matches: Option<usize>,
+    step: Option<usize>,
+    threads: Option<usize>,
+) -> Result<Array2<f64>, ImgalError>
+where
+    A: AsArray<'a, T, Ix2>,
+    T: 'a + AsNumeric,
+{
+    let data: ArrayBase<ViewRepr<&'a T>, Ix2> = data.into();
+    if sigma <= 0.0 {
+        return Err(ImgalError::InvalidParameterValueOutsideRange {
+            param_name: "sigma",
+            value: sigma,
+            min: 0.0,
+            max: f64::INFINITY,
+        });
+    }
+    let params = Bm3dParams {
+        patch_size: patch_size.unwrap_or(8),
+        search_radius: search_radius.unwrap_or(16),
+        max_matches: max_matches.unwrap_or(16),
+        step: step.unwrap_or(3),
+    };
+    for (name, value) in [
+        ("patch_size", params.patch_size),
+        ("max_matches", params.max_matches),
+        ("step", params.step),
+    ] {
+        if value == 0 {
+            return Err(ImgalError::InvalidParameterValueLess {
+                param_name: name,
+                value: 1,
+            });
+        }
+    }
+    let (rows, cols) = data.dim();
+    for (axis_idx, len) in [rows, cols].into_iter().enumerate() {
+        if len < params.patch_size {
+            return Err(ImgalError::InvalidAxisLengthLess {
+                arr_name: "data",
+                axis_idx,
+                value: params.patch_size,
+            });
+        }
+    }
+    let noisy = data.mapv(|v| v.to_f64());
+    let basic = collaborative_filter(&noisy, &noisy, sigma, &params, false, threads);
+    Ok(collaborative_filter(
+        &noisy, &basic, sigma, &params, true, threads,
+    ))
+}
+
+/// Run one block matching and collaborative filtering stage. Patches are
+/// matched on `guide`. If `wiener` is `true`, the groups of `noisy` are Wiener
+/// filtered with the groups of `guide` as the signal estimate, otherwise they
+/// are hard thresholded.
+fn collaborative_filter(
+    noisy: &Array2<f64>,
+    guide: &Array2<f64>,
+    sigma: f64,
+    params: &Bm3dParams,
+    wiener: bool,
+    threads: Option<usize>,
+) -> Array2<f64> {
+    let (rows, cols) = noisy.dim();
+    let ps = params.patch_size;
+    let ref_positions = |len: usize| -> Vec<usize> {
+        let mut pos: Vec<usize> = (0..=len - ps).step_by(params.step).collect();
+        if pos.last() != Some(&(len - ps)) {
+            pos.push(len - ps);
+        }
+        pos
+    };
+    let refs: Vec<(usize, usize)> = ref_positions(rows)
+        .into_iter()
+        .flat_map(|r| ref_positions(cols).into_iter().map(move |c| (r, c)))
+        .collect();
+    // the DCT matrices for the patches and for all possible group sizes
+    let patch_dct = dct_matrix(ps);
+    let group_dcts: Vec<Vec<f64>> = (0..=params.max_matches).map(dct_matrix).collect();
+    let threshold = HARD_THRESHOLD * sigma;
+    let sigma_sq = sigma * sigma;
+    let patch_len = ps * ps;
+    let init = || {
+        (
+            Array2::<f64>::zeros((rows, cols)),
+            Array2::<f64>::zeros((rows, cols)),
+        )
+    };
+    let filter_ref = |mut acc: (Array2<f64>, Array2<f64>), &(r, c): &(usize, usize)| {
+        // block matching within the search window, the reference patch is
+        // always the first patch of its group
+        let r_lo = r.saturating_sub(params.search_radius);
+        let r_hi = (r + params.search_radius).min(rows - ps);
+        let c_lo = c.saturating_sub(params.search_radius);
+        let c_hi = (c + params.search_radius).min(cols - ps);
+        let mut matches: Vec<(f64, usize, usize)> = Vec::new();
+        for i in r_lo..=r_hi {
+            for j in c_lo..=c_hi {
+                if i == r && j == c {
+                    continue;
+                }
+                let mut dist = 0.0;
+                for a in 0..ps {
+                    for b in 0..ps {
+                        dist += (guide[[r + a, c + b]] - guide[[i + a, j + b]]).powi(2);
+                    }
+                }
+                matches.push((dist, i, j));
+            }
+        }
+        let cmp = |a: &(f64, usize, usize), b: &(f64, usize, usize)| {
+            a.0.partial_cmp(&b.0).unwrap_or(Ordering::Less)
+        };
+        let n_similar = (params.max_matches - 1).min(matches.len());
+        if n_similar < matches.len() {
+            if n_similar > 0 {
+                matches.select_nth_unstable_by(n_similar - 1, cmp);
+            }
+            matches.truncate(n_similar);
+        }
+        matches.insert(0, (0.0, r, c));
+        let k = matches.len();
+        let load_group = |src: &Array2<f64>| -> Vec<f64> {
+            let mut group = vec![0.0; k * patch_len];
+            matches.iter().enumerate().for_each(|(m, &(_, i, j))| {
+                for a in 0..ps {
+                    for b in 0..ps {
+                        group[m * patch_len + a * ps + b] = src[[i + a, j + b]];
+                    }
+                }
+            });
+            group
+        };
+        let mut group = load_group(noisy);
+        transform_group(&mut group, k, ps, &patch_dct, &group_dcts[k], false);
+        let weight = if wiener {
+            let mut basic = load_group(guide);
+            transform_group(&mut basic, k, ps, &patch_dct, &group_dcts[k], false);
+            let mut energy = 0.0;
+            group.iter_mut().zip(basic.iter()).for_each(|(g, &b)| {
+                let shrink = b * b / (b * b + sigma_sq);
+                *g *= shrink;
+                energy += shrink * shrink;
+            });
+            1.0 / (sigma_sq * energy.max(1.0))
+        } else {
+            let mut retained = 0usize;
+            group.iter_mut().for_each(|g| {
+                if g.abs() < threshold {
+                    *g = 0.0;
+                } else {
+                    retained += 1;
+                }
+            });
+            1.0 / (sigma_sq * retained.max(1) as f64)
+        };
+        transform_group(&mut group, k, ps, &patch_dct, &group_dcts[k], true);
+        // aggregate the filtered patches
+        matches.iter().enumerate().for_each(|(m, &(_, i, j))| {
+            for a in 0..ps {
+                for b in 0..ps {
+                    acc.0[[i + a, j + b]] += weight * group[m * patch_len + a * ps + b];
+                    acc.1[[i + a, j + b]] += weight;
+                }
+            }
+        });
+        acc
+    };
+    let merge = |mut a: (Array2<f64>, Array2<f64>), b: (Array2<f64>, Array2<f64>)| {
+        a.0 += &b.0;
+        a.1 += &b.1;
+        a
+    };
+    let (num, den) = par!(threads,
+        seq_exp: refs.iter().fold(init(), filter_ref),
+        par_exp: refs.par_iter().fold(init, filter_ref).reduce(init, merge));
+    let mut denoised = num;
+    denoised.zip_mut_with(&den, |v, &w| *v /= w);
+    denoised
+}
+
+/// Create an orthonormal DCT-II matrix of size `n × n` in row-major order.
+fn dct_matrix(n: usize) -> Vec<f64> {
+    let mut mat = vec![0.0; n * n];
+    for k in 0..n {
+        let scale = if k == 0 {
+            (1.0 / n as f64).sqrt()
+        } else {
+            (2.0 / n as f64).sqrt()
+        };
+        for i in 0..n {
+            mat[k * n + i] = scale * (PI * (2 * i + 1) as f64 * k as f64 / (2 * n) as f64).cos();
+        }
+    }
+    mat
+}
+
+/// Apply the separable 3D DCT (or its inverse) to a group of `k` patches of
+/// size `ps × ps`.
+fn transform_group(
+    group: &mut [f64],
+    k: usize,
+    ps: usize,
+    patch_dct: &[f64],
+    group_dct: &[f64],
+    inverse: bool,
+) {
+    // the matrix entry for the forward or inverse (i.e. transposed) transform
+    let coef = |mat: &[f64], n: usize, out: usize, inp: usize| {
+        if inverse {
+            mat[inp * n + out]
+        } else {
+            mat[out * n + inp]
+        }
+    };
+    let mut buf = vec![0.0; ps.max(k)];
+    // the 2D transform of each patch, along the rows and then the columns
+    for patch in group.chunks_exact_mut(ps * ps) {
+        for a in 0..ps {
+            for (u, v) in buf.iter_mut().enumerate().take(ps) {
+                *v = (0..ps)
+                    .map(|b| coef(patch_dct, ps, u, b) * patch[a * ps + b])
+                    .sum();
+            }
+            patch[a * ps..(a + 1) * ps].copy_from_slice(&buf[..ps]);
+        }
+        for b in 0..ps {
+            for (u, v) in buf.iter_mut().enumerate().take(ps) {
+                *v = (0..ps)
+                    .map(|a| coef(patch_dct, ps, u, a) * patch[a * ps + b])
+                    .sum();
+            }
+            (0..ps).for_each(|a| patch[a * ps + b] = buf[a]);
+        }
+    }
+    // the 1D transform along the group axis
+    let patch_len = ps * ps;
+    for p in 0..patch_len {
+        for (u, v) in buf.iter_mut().enumerate().take(k) {
+            *v = (0..k)
+                .map(|m| coef(group_dct, k, u, m) * group[m * patch_len + p])
+                .sum();
+        }
+        (0..k).for_each(|m| group[m * patch_len + p] = buf[m]);
+    }
+}
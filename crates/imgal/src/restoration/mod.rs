@@ -0,0 +1,29 @@
+//! Image restoration functions.
+//!
+//! This module provides functions for restoring noisy and blurred images,
+//! such as Richardson-Lucy deconvolution, block-matching denoising, variance
+//! stabilizing transforms for photon-limited data and the blind-spot masking
+//! and patch stitching infrastructure for self-supervised denoising (*e.g.*
+//! Noise2Void), and for reconstructing structured illumination images.
+
+mod anscombe;
+mod blind_spot;
+mod bm3d;
+mod deconvolve;
+mod sim;
+
+pub use anscombe::anscombe;
+pub use anscombe::inverse_anscombe;
+pub use blind_spot::BlindSpotMask;
+pub use blind_spot::BlindSpotPatches;
+pub use blind_spot::ReplacementStrategy;
+pub use blind_spot::blind_spot_mask;
+pub use blind_spot::blind_spot_patches;
+pub use blind_spot::extract_patches;
+pub use blind_spot::stitch_patches;
+pub use bm3d::bm3d;
+pub use deconvolve::richardson_lucy;
+pub use deconvolve::richardson_lucy_varying;
+pub use sim::SimReconstruction;
+pub(crate) use sim::gaussian_otf;
+pub use sim::sim_reconstruct;
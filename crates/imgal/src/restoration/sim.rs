@@ -0,0 +1,336 @@
+use std::f64::consts::PI;
+
+use ndarray::{Array1, Array2, ArrayBase, AsArray, Axis, Ix3, ViewRepr, Zip};
+use rustfft::{num_complex::Complex, num_traits::Zero};
+
+use crate::filter::fft_nd;
+use crate::prelude::*;
+
+/// The OTF value above which frequencies are used for the pattern estimation.
+const OTF_SUPPORT: f64 = 1e-3;
+
+/// The reconstruction output of `sim_reconstruct`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SimReconstruction {
+    /// The reconstructed image with twice the number of rows and columns of
+    /// the raw frames.
+    pub image: Array2<f64>,
+    /// The estimated pattern wave vector `(k_row, k_col)` of each angle in
+    /// cycles per raw pixel, with shape `(n_angles, 2)`.
+    pub wave_vectors: Array2<f64>,
+    /// The estimated pattern phase of the first frame of each angle in
+    /// radians.
+    pub phases: Array1<f64>,
+    /// The estimated modulation depth of each angle.
+    pub modulations: Array1<f64>,
+}
+
+/// Reconstruct a super-resolved image from 2D structured illumination
+/// microscopy (SIM) frames.
+///
+/// # Description
+///
+/// Reconstructs a 2D structured illumination image from raw frames acquired
+/// with sinusoidal illumination patterns `1 + m × cos(2π k · x + φ)` at
+/// `n_angles` orientations and `n_phases` equally spaced phases per
+/// orientation (*e.g.* from `simulation::pattern::structured_illumination`).
+/// The spectrum of each frame is a sum of three copies of the sample
+/// spectrum `S`, shifted by the pattern wave vector `k`:
+///
+/// ```text
+/// Dₙ(f) = O(f) × (S(f) + a × e^(2πin/N) × S(f - k) + a* × e^(-2πin/N) × S(f + k))
+/// a = m/2 × e^(iφ₀)
+/// ```
+///
+/// Where `O` is the optical transfer function (OTF) and `N` is the number of
+/// phases. For each orientation the three bands are separated by a discrete
+/// Fourier transform over the phases. The pattern is then estimated from the
+/// data: the wave vector `k` is the peak of the phase correlation of the
+/// first order band with the zero order band (refined to subpixel precision
+/// with a parabola), which needs a sample with structure at several
+/// positions (*i.e.* not a single point), and the phase `φ₀` and modulation
+/// `m` follow from the least squares ratio of the overlapping bands. The bands
+/// are moved back to their true position on a grid with twice the sampling and
+/// combined over all orientations with a generalized Wiener filter:
+///
+/// ```text
+/// Ŝ(f) = Σ wⱼ* × Oⱼ* × Sⱼ(f) / (Σ |wⱼ × Oⱼ|² + w)
+/// ```
+///
+/// Where `wⱼ` is `1`, `a` or `a*` for the zero and ±first order bands, `Oⱼ`
+/// is the OTF shifted with the band and `w` is the Wiener parameter. The OTF
+/// is modeled as the transform of a Gaussian PSF. The frames are assumed to
+/// be periodic, apodize or pad frames with objects at their borders.
+///
+/// # Arguments
+///
+/// * `frames`: The raw SIM frames with shape `(n_angles × n_phases, row,
+///   col)`, ordered by orientation and then phase.
+/// * `n_phases`: The number of equally spaced phases per orientation.
+/// * `psf_sigma`: The standard deviation of the Gaussian PSF in raw pixels.
+/// * `wiener`: The Wiener parameter `w`, larger values suppress noise at the
+///   cost of resolution. If `None`, then `wiener = 0.01`.
+/// * `threads`: The requested number of threads to use for parallel execution.
+///   If `None` or `Some(1)` sequential execution is used. If `Some(0)`, then
+///   the maximum available parallelism is used. Thread counts are clamped to
+///   the systems maximum.
+///
+/// # Returns
+///
+/// * `Ok(SimReconstruction)`: The reconstructed image and the estimated
+///   pattern parameters of each orientation.
+/// * `Err(ImgalError)`: If `n_phases < 3`. If the number of frames is `0` or
+///   not a multiple of `n_phases`. If a frame has fewer than `4` rows or
+///   columns. If `psf_sigma <= 0.0`. If `wiener <= 0.0`.
+///
+/// # Reference
+///
+/// <https://doi.org/10.1046/j.1365-2818.2000.00710.x>
+pub fn sim_reconstruct<'a, T, A>(
+    frames: A,
+    n_phases: usize,
+    psf_sigma: f64,
+    wiener: Option<f64>,
+    threads: Option<usize>,
+) -> Result<SimReconstruction, ImgalError>
+where
+    A: AsArray<'a, T, Ix3>,
+    T: 'a + AsNumeric,
+{
+    let frames: ArrayBase<ViewRepr<&'a T>, Ix3> = frames.into();
+    let (n_frames, rows, cols) = frames.dim();
+    if n_phases < 3 {
+        return Err(ImgalError::InvalidParameterValueLess {
+            param_name: "n_phases",
+            value: 3,
+        });
+    }
+    if n_frames == 0 || !n_frames.is_multiple_of(n_phases) {
+        return Err(ImgalError::InvalidAxisValueNotAMultipleOf {
+            arr_name: "frames",
+            axis_idx: 0,
+            multiple: n_phases,
+        });
+    }
+    for (ax, len) in [(1, rows), (2, cols)] {
+        if len < 4 {
+            return Err(ImgalError::InvalidAxisLengthExpected {
+                arr_name: "frames",
+                axis_idx: ax,
+                expected: 4,
+                got: len,
+            });
+        }
+    }
+    if psf_sigma.is_nan() || psf_sigma <= 0.0 {
+        return Err(ImgalError::InvalidParameterValueOutsideRange {
+            param_name: "psf_sigma",
+            value: psf_sigma,
+            min: 0.0,
+            max: f64::INFINITY,
+        });
+    }
+    let wiener = wiener.unwrap_or(0.01);
+    if wiener.is_nan() || wiener <= 0.0 {
+        return Err(ImgalError::InvalidParameterValueOutsideRange {
+            param_name: "wiener",
+            value: wiener,
+            min: 0.0,
+            max: f64::INFINITY,
+        });
+    }
+    let n_angles = n_frames / n_phases;
+    let (big_rows, big_cols) = (2 * rows, 2 * cols);
+    let mut numerator = Array2::<Complex<f64>>::zeros((big_rows, big_cols));
+    let mut denominator = Array2::<f64>::zeros((big_rows, big_cols));
+    let mut wave_vectors = Array2::<f64>::zeros((n_angles, 2));
+    let mut phases = Array1::<f64>::zeros(n_angles);
+    let mut modulations = Array1::<f64>::zeros(n_angles);
+    let otf_raw = gaussian_otf((rows, cols), psf_sigma, (0.0, 0.0), 1.0);
+    let otf_zero = gaussian_otf((big_rows, big_cols), psf_sigma, (0.0, 0.0), 0.5);
+    for a in 0..n_angles {
+        // separate the zero and ±first order bands by a DFT over the phases
+        let mut bands = [
+            Array2::<Complex<f64>>::zeros((rows, cols)),
+            Array2::<Complex<f64>>::zeros((rows, cols)),
+            Array2::<Complex<f64>>::zeros((rows, cols)),
+        ];
+        for n in 0..n_phases {
+            let mut spectrum = frames
+                .index_axis(Axis(0), a * n_phases + n)
+                .mapv(|v| Complex::new(v.to_f64(), 0.0));
+            fft_nd(&mut spectrum, false, threads);
+            for (band, order) in bands.iter_mut().zip([0.0, 1.0, -1.0]) {
+                let w = Complex::from_polar(
+                    1.0 / n_phases as f64,
+                    -order * 2.0 * PI * n as f64 / n_phases as f64,
+                );
+                Zip::from(band).and(&spectrum).for_each(|b, &d| *b += d * w);
+            }
+        }
+        let k = estimate_wave_vector(&bands[0], &bands[1], &otf_raw, threads);
+        wave_vectors[[a, 0]] = k.0;
+        wave_vectors[[a, 1]] = k.1;
+        // move the bands to their true position on the finer grid
+        let [zero, plus, minus] = bands;
+        let s_zero = upsample_band(&zero, (0.0, 0.0), threads);
+        let s_plus = upsample_band(&plus, k, threads);
+        let s_minus = upsample_band(&minus, (-k.0, -k.1), threads);
+        let otf_plus = gaussian_otf((big_rows, big_cols), psf_sigma, k, 0.5);
+        let otf_minus = gaussian_otf((big_rows, big_cols), psf_sigma, (-k.0, -k.1), 0.5);
+        // the least squares ratio of the overlapping first and zero order bands
+        let mut cross = Complex::<f64>::zero();
+        let mut norm = 0.0;
+        Zip::from(&s_plus)
+            .and(&s_zero)
+            .and(&otf_zero)
+            .and(&otf_plus)
+            .for_each(|&p, &z, &o0, &o1| {
+                cross += p * o0 * (z * o1).conj();
+                norm += (z * o1).norm_sqr();
+            });
+        let ratio = if norm > 0.0 {
+            cross / norm
+        } else {
+            Complex::zero()
+        };
+        phases[a] = ratio.arg();
+        modulations[a] = 2.0 * ratio.norm();
+        numerator
+            .indexed_iter_mut()
+            .zip(denominator.iter_mut())
+            .for_each(|((p, num), den)| {
+                let (o0, o1, o2) = (otf_zero[p], otf_plus[p], otf_minus[p]);
+                *num += s_zero[p] * o0 + s_plus[p] * (ratio * o1).conj() + s_minus[p] * ratio * o2;
+                *den += o0 * o0 + ratio.norm_sqr() * (o1 * o1 + o2 * o2);
+            });
+    }
+    Zip::from(&mut numerator)
+        .and(&denominator)
+        .for_each(|n, &d| *n /= d + wiener);
+    fft_nd(&mut numerator, true, threads);
+    let scale = 1.0 / numerator.len() as f64;
+    Ok(SimReconstruction {
+        image: numerator.mapv(|v| v.re * scale),
+        wave_vectors,
+        phases,
+        modulations,
+    })
+}
+
+/// Create the OTF of a Gaussian PSF with standard deviation `sigma` (in raw
+/// pixels), shifted by `-shift` (*i.e.* `O(f + shift)`), on an unshifted FFT
+/// grid with a pixel `spacing` in raw pixels.
+pub(crate) fn gaussian_otf(
+    shape: (usize, usize),
+    sigma: f64,
+    shift: (f64, f64),
+    spacing: f64,
+) -> Array2<f64> {
+    let (rows, cols) = shape;
+    Array2::from_shape_fn(shape, |(r, c)| {
+        let fr = fft_frequency(r, rows, spacing) + shift.0;
+        let fc = fft_frequency(c, cols, spacing) + shift.1;
+        (-2.0 * PI * PI * sigma * sigma * (fr * fr + fc * fc)).exp()
+    })
+}
+
+/// Return the signed frequency (in cycles per raw pixel) of FFT index `i` on a
+/// grid of `n` points with a pixel `spacing` in raw pixels.
+fn fft_frequency(i: usize, n: usize, spacing: f64) -> f64 {
+    let signed = if i < n.div_ceil(2) {
+        i as f64
+    } else {
+        i as f64 - n as f64
+    };
+    signed / (n as f64 * spacing)
+}
+
+/// Estimate the wave vector as the subpixel peak of the phase correlation of
+/// the first order band with the zero order band, within the OTF support.
+fn estimate_wave_vector(
+    zero: &Array2<Complex<f64>>,
+    first: &Array2<Complex<f64>>,
+    otf: &Array2<f64>,
+    threads: Option<usize>,
+) -> (f64, f64) {
+    let (rows, cols) = zero.dim();
+    // phase only spectra, the OTF weighting would bias the peak towards zero
+    let whiten = |band: &Array2<Complex<f64>>| {
+        let mut w = band.clone();
+        Zip::from(&mut w).and(otf).for_each(|v, &o| {
+            let norm = v.norm();
+            *v = if o >= OTF_SUPPORT && norm > 0.0 {
+                *v / norm
+            } else {
+                Complex::zero()
+            };
+        });
+        w
+    };
+    let mut z = whiten(zero);
+    let mut f = whiten(first);
+    fft_nd(&mut z, true, threads);
+    fft_nd(&mut f, true, threads);
+    // Σ F(u) Z*(u - Δ) is the transform of the product f × z*
+    let mut xcorr = f;
+    Zip::from(&mut xcorr)
+        .and(&z)
+        .for_each(|x, &z| *x *= z.conj());
+    fft_nd(&mut xcorr, false, threads);
+    let mag = xcorr.mapv(|v| v.norm());
+    let near_zero = |i: usize, n: usize| i <= 1 || i + 1 >= n;
+    let (pr, pc) = mag
+        .indexed_iter()
+        .filter(|((r, c), _)| !(near_zero(*r, rows) && near_zero(*c, cols)))
+        .max_by(|a, b| a.1.total_cmp(b.1))
+        .map(|(p, _)| p)
+        .unwrap();
+    let refine = |lo: f64, mid: f64, hi: f64| -> f64 {
+        let denom = lo - 2.0 * mid + hi;
+        if denom.abs() <= f64::EPSILON {
+            0.0
+        } else {
+            (0.5 * (lo - hi) / denom).clamp(-0.5, 0.5)
+        }
+    };
+    let mid = mag[[pr, pc]];
+    let dr = refine(
+        mag[[(pr + rows - 1) % rows, pc]],
+        mid,
+        mag[[(pr + 1) % rows, pc]],
+    );
+    let dc = refine(
+        mag[[pr, (pc + cols - 1) % cols]],
+        mid,
+        mag[[pr, (pc + 1) % cols]],
+    );
+    (
+        fft_frequency(pr, rows, 1.0) + dr / rows as f64,
+        fft_frequency(pc, cols, 1.0) + dc / cols as f64,
+    )
+}
+
+/// Zero pad a band spectrum to a grid with twice the sampling and shift it by
+/// `-k` (*i.e.* `S(f + k)`) with a phase ramp in real space.
+fn upsample_band(
+    band: &Array2<Complex<f64>>,
+    k: (f64, f64),
+    threads: Option<usize>,
+) -> Array2<Complex<f64>> {
+    let (rows, cols) = band.dim();
+    let (big_rows, big_cols) = (2 * rows, 2 * cols);
+    let big_index = |i: usize, n: usize| if i < n.div_ceil(2) { i } else { i + n };
+    let mut big = Array2::<Complex<f64>>::zeros((big_rows, big_cols));
+    band.indexed_iter().for_each(|((r, c), &v)| {
+        big[[big_index(r, rows), big_index(c, cols)]] = v * 4.0;
+    });
+    fft_nd(&mut big, true, threads);
+    let scale = 1.0 / big.len() as f64;
+    big.indexed_iter_mut().for_each(|((r, c), v)| {
+        let phase = -2.0 * PI * (k.0 * r as f64 + k.1 * c as f64) * 0.5;
+        *v *= Complex::from_polar(scale, phase);
+    });
+    fft_nd(&mut big, false, threads);
+    big
+}
@@ -0,0 +1,13 @@
+//! 1D signal (*i.e.* trace) analysis functions.
+//!
+//! This module provides functions for analyzing 1D signals such as intensity
+//! traces over time (*e.g.* ΔF/F₀ traces) and line profiles, for example event
+//! detection with peak finding and delay analysis with cross-correlation.
+
+mod peaks;
+mod xcorr;
+
+pub use peaks::Peak;
+pub use peaks::find_peaks;
+pub use xcorr::cross_correlation;
+pub use xcorr::cross_correlation_lag;
@@ -0,0 +1,205 @@
+use std::cmp::Ordering;
+
+use ndarray::{ArrayBase, AsArray, Ix1, ViewRepr};
+
+use crate::prelude::*;
+
+/// A peak (*i.e.* event) found by `find_peaks` and its metrics.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Peak {
+    /// The index of the peak maximum. For flat peaks (*i.e.* plateaus) the
+    /// middle index of the plateau.
+    pub index: usize,
+    /// The value of the peak maximum.
+    pub height: f64,
+    /// The vertical distance between the peak maximum and its highest base.
+    pub prominence: f64,
+    /// The index of the minimum between the peak and the next higher value (or
+    /// the start of the trace) to the left.
+    pub left_base: usize,
+    /// The index of the minimum between the peak and the next higher value (or
+    /// the end of the trace) to the right.
+    pub right_base: usize,
+    /// The width of the peak in samples, measured at the relative height.
+    pub width: f64,
+    /// The interpolated left position of the width measurement.
+    pub left_ip: f64,
+    /// The interpolated right position of the width measurement.
+    pub right_ip: f64,
+}
+
+/// Find peaks (*i.e.* events) in a 1D trace.
+///
+/// # Description
+///
+/// Finds the local maxima of a 1D trace (*e.g.* a ΔF/F₀ trace or a line
+/// profile) and filters them by height, distance, prominence and width. The
+/// prominence of a peak is the height of the peak above its highest base,
+/// where the bases are the minima between the peak and the next higher value
+/// on either side:
+///
+/// ```text
+/// prominence = height - max(min(left), min(right))
+/// ```
+///
+/// The width is measured at the height `height - prominence × rel_height`,
+/// with linear interpolation between samples. The criteria are applied in the
+/// order height, distance (keeping the highest peaks), prominence and width.
+///
+/// # Arguments
+///
+/// * `data`: The input 1D trace.
+/// * `min_height`: The minimum peak height. If `None`, peaks are not filtered
+///   by height.
+/// * `min_distance`: The minimum distance in samples between neighboring
+///   peaks, lower peaks are removed first. If `None`, then `min_distance = 1`.
+/// * `min_prominence`: The minimum peak prominence. If `None`, peaks are not
+///   filtered by prominence.
+/// * `min_width`: The minimum peak width in samples. If `None`, peaks are not
+///   filtered by width.
+/// * `rel_height`: The relative height (of the prominence) at which the peak
+///   width is measured, in the range `0.0` to `1.0`. If `None`, then
+///   `rel_height = 0.5` (*i.e.* the full width at half prominence).
+///
+/// # Returns
+///
+/// * `Ok(Vec<Peak>)`: The peaks and their metrics sorted by index.
+/// * `Err(ImgalError)`: If `min_distance == 0`. If `rel_height` is outside the
+///   range `0.0` to `1.0`.
+pub fn find_peaks<'a, T, A>(
+    data: A,
+    min_height: Option<f64>,
+    min_distance: Option<usize>,
+    min_prominence: Option<f64>,
+    min_width: Option<f64>,
+    rel_height: Option<f64>,
+) -> Result<Vec<Peak>, ImgalError>
+where
+    A: AsArray<'a, T, Ix1>,
+    T: 'a + AsNumeric,
+{
+    let data: ArrayBase<ViewRepr<&'a T>, Ix1> = data.into();
+    let min_distance = min_distance.unwrap_or(1);
+    if min_distance == 0 {
+        return Err(ImgalError::InvalidParameterValueLess {
+            param_name: "min_distance",
+            value: 1,
+        });
+    }
+    let rel_height = rel_height.unwrap_or(0.5);
+    if !(0.0..=1.0).contains(&rel_height) {
+        return Err(ImgalError::InvalidParameterValueOutsideRange {
+            param_name: "rel_height",
+            value: rel_height,
+            min: 0.0,
+            max: 1.0,
+        });
+    }
+    let y: Vec<f64> = data.iter().map(|v| v.to_f64()).collect();
+    let n = y.len();
+    // find the local maxima, including the middle of flat plateaus
+    let mut maxima: Vec<usize> = Vec::new();
+    let mut i = 1;
+    while i + 1 < n {
+        if y[i] > y[i - 1] {
+            let mut ahead = i + 1;
+            while ahead + 1 < n && y[ahead] == y[i] {
+                ahead += 1;
+            }
+            if y[ahead] < y[i] {
+                maxima.push((i + ahead - 1) / 2);
+                i = ahead;
+                continue;
+            }
+        }
+        i += 1;
+    }
+    if let Some(h) = min_height {
+        maxima.retain(|&p| y[p] >= h);
+    }
+    // remove the lower of neighboring peaks closer than the minimum distance
+    if min_distance > 1 {
+        let mut by_height: Vec<usize> = (0..maxima.len()).collect();
+        by_height.sort_by(|&a, &b| {
+            y[maxima[b]]
+                .partial_cmp(&y[maxima[a]])
+                .unwrap_or(Ordering::Equal)
+        });
+        let mut keep = vec![true; maxima.len()];
+        for &k in by_height.iter() {
+            if !keep[k] {
+                continue;
+            }
+            let p = maxima[k];
+            (0..maxima.len())
+                .filter(|&o| o != k && maxima[o].abs_diff(p) < min_distance)
+                .for_each(|o| keep[o] = false);
+        }
+        maxima = maxima
+            .into_iter()
+            .zip(keep)
+            .filter_map(|(p, k)| k.then_some(p))
+            .collect();
+    }
+    let mut peaks: Vec<Peak> = maxima
+        .into_iter()
+        .map(|p| {
+            let height = y[p];
+            // search for the bases until a higher value or the trace border
+            let mut left_base = p;
+            let mut j = p;
+            while j > 0 && y[j - 1] <= height {
+                j -= 1;
+                if y[j] < y[left_base] {
+                    left_base = j;
+                }
+            }
+            let mut right_base = p;
+            let mut j = p;
+            while j + 1 < n && y[j + 1] <= height {
+                j += 1;
+                if y[j] < y[right_base] {
+                    right_base = j;
+                }
+            }
+            let prominence = height - y[left_base].max(y[right_base]);
+            // the interpolated width at the reference height
+            let ref_height = height - prominence * rel_height;
+            let mut l = p;
+            while l > left_base && y[l] > ref_height {
+                l -= 1;
+            }
+            let left_ip = if y[l] < ref_height {
+                l as f64 + (ref_height - y[l]) / (y[l + 1] - y[l])
+            } else {
+                l as f64
+            };
+            let mut r = p;
+            while r < right_base && y[r] > ref_height {
+                r += 1;
+            }
+            let right_ip = if y[r] < ref_height {
+                r as f64 - (ref_height - y[r]) / (y[r - 1] - y[r])
+            } else {
+                r as f64
+            };
+            Peak {
+                index: p,
+                height,
+                prominence,
+                left_base,
+                right_base,
+                width: right_ip - left_ip,
+                left_ip,
+                right_ip,
+            }
+        })
+        .collect();
+    if let Some(min_p) = min_prominence {
+        peaks.retain(|pk| pk.prominence >= min_p);
+    }
+    if let Some(min_w) = min_width {
+        peaks.retain(|pk| pk.width >= min_w);
+    }
+    Ok(peaks)
+}
@@ -0,0 +1,186 @@
+use ndarray::{Array1, Array2, ArrayBase, ArrayView1, AsArray, Axis, Ix1, Ix2, ViewRepr, Zip};
+
+use crate::prelude::*;
+
+/// Compute the normalized cross-correlation between two 1D traces.
+///
+/// # Description
+///
+/// Computes the normalized cross-correlation between two traces of equal
+/// length as a function of the lag `τ`:
+///
+/// ```text
+/// C(τ) = Σₜ (aₜ - mean(a)) × (bₜ₊τ - mean(b)) / √[Σ(aₜ - mean(a))² × Σ(bₜ - mean(b))²]
+/// ```
+///
+/// Where the sum runs over the overlapping samples. The normalization is the
+/// same for all lags, so `C(0)` is the Pearson correlation coefficient and
+/// `|C(τ)| <= 1.0`. A maximum at a positive lag means that `b` lags (*i.e.*
+/// follows) `a`. If either trace is constant, the cross-correlation is `0.0`.
+///
+/// # Arguments
+///
+/// * `data_a`: The first 1D trace.
+/// * `data_b`: The second 1D trace.
+/// * `max_lag`: The maximum lag in samples. If `None`, then
+///   `max_lag = data_a.len() - 1`.
+///
+/// # Returns
+///
+/// * `Ok(Array1<f64>)`: The cross-correlation for the lags `-max_lag` to
+///   `max_lag`, where index `i` is the lag `i - max_lag`.
+/// * `Err(ImgalError)`: If `data_a` is empty. If
+///   `data_a.len() != data_b.len()`. If `max_lag >= data_a.len()`.
+pub fn cross_correlation<'a, T, A>(
+    data_a: A,
+    data_b: A,
+    max_lag: Option<usize>,
+) -> Result<Array1<f64>, ImgalError>
+where
+    A: AsArray<'a, T, Ix1>,
+    T: 'a + AsNumeric,
+{
+    let data_a: ArrayBase<ViewRepr<&'a T>, Ix1> = data_a.into();
+    let data_b: ArrayBase<ViewRepr<&'a T>, Ix1> = data_b.into();
+    let n = data_a.len();
+    if n == 0 {
+        return Err(ImgalError::InvalidParameterEmptyArray {
+            param_name: "data_a",
+        });
+    }
+    if n != data_b.len() {
+        return Err(ImgalError::MismatchedArrayLengths {
+            a_arr_name: "data_a",
+            a_arr_len: n,
+            b_arr_name: "data_b",
+            b_arr_len: data_b.len(),
+        });
+    }
+    let max_lag = validate_max_lag(max_lag, n)?;
+    let a = centered(data_a.view());
+    let b = centered(data_b.view());
+    Ok(normalized_xcorr(&a, &b, max_lag))
+}
+
+/// Compute the pairwise cross-correlation peak lags between 1D traces.
+///
+/// # Description
+///
+/// Computes the normalized cross-correlation (see `cross_correlation`) between
+/// all pairs of traces (*e.g.* the mean intensity traces of ROIs) and extracts
+/// the lag of the cross-correlation maximum, for signal propagation and delay
+/// analyses. The integer peak lag is refined to a subsample lag by fitting a
+/// parabola through the maximum and its two neighbors:
+///
+/// ```text
+/// δ = (C[k - 1] - C[k + 1]) / (2 × (C[k - 1] - 2C[k] + C[k + 1]))
+/// ```
+///
+/// Since the overlap of the traces shrinks with the lag, the peak lag is
+/// slightly biased towards zero for events that are not short compared to the
+/// trace length.
+///
+/// # Arguments
+///
+/// * `traces`: The 2D array of traces with the shape `(n_traces, t)`.
+/// * `max_lag`: The maximum lag in samples. If `None`, then `max_lag = t - 1`.
+/// * `threads`: The requested number of threads to use for parallel execution.
+///   If `None` or `Some(1)` sequential execution is used. If `Some(0)`, then
+///   the maximum available parallelism is used. Thread counts are clamped to
+///   the systems maximum.
+///
+/// # Returns
+///
+/// * `Ok((Array2<f64>, Array2<f64>))`: A tuple containing the peak lag and
+///   peak cross-correlation matrices with the shape `(n_traces, n_traces)`,
+///   *i.e.* `(lags, peaks)`. The element `(i, j)` is the lag of trace `j`
+///   relative to trace `i`, where a positive lag means trace `j` follows trace
+///   `i`.
+/// * `Err(ImgalError)`: If `traces` has no samples. If `max_lag >= t`.
+pub fn cross_correlation_lag<'a, T, A>(
+    traces: A,
+    max_lag: Option<usize>,
+    threads: Option<usize>,
+) -> Result<(Array2<f64>, Array2<f64>), ImgalError>
+where
+    A: AsArray<'a, T, Ix2>,
+    T: 'a + AsNumeric,
+{
+    let traces: ArrayBase<ViewRepr<&'a T>, Ix2> = traces.into();
+    let (n_traces, n) = traces.dim();
+    if n == 0 {
+        return Err(ImgalError::InvalidParameterEmptyArray {
+            param_name: "traces",
+        });
+    }
+    let max_lag = validate_max_lag(max_lag, n)?;
+    let centered_traces: Vec<Vec<f64>> = traces.axis_iter(Axis(0)).map(centered).collect();
+    let mut lag_arr = Array2::<f64>::zeros((n_traces, n_traces));
+    let mut peak_arr = Array2::<f64>::zeros((n_traces, n_traces));
+    let lag_calc = |(i, j): (usize, usize), l: &mut f64, p: &mut f64| {
+        let xcorr = normalized_xcorr(&centered_traces[i], &centered_traces[j], max_lag);
+        let (k, peak) =
+            xcorr.iter().enumerate().fold(
+                (0, f64::MIN),
+                |acc, (k, &v)| if v > acc.1 { (k, v) } else { acc },
+            );
+        let delta = if k > 0 && k + 1 < xcorr.len() {
+            let denom = xcorr[k - 1] - 2.0 * peak + xcorr[k + 1];
+            if denom.abs() > f64::EPSILON {
+                0.5 * (xcorr[k - 1] - xcorr[k + 1]) / denom
+            } else {
+                0.0
+            }
+        } else {
+            0.0
+        };
+        *l = k as f64 + delta - max_lag as f64;
+        *p = peak;
+    };
+    par!(threads,
+        seq_exp: Zip::indexed(&mut lag_arr).and(&mut peak_arr).for_each(lag_calc),
+        par_exp: Zip::indexed(&mut lag_arr).and(&mut peak_arr).par_for_each(lag_calc));
+    Ok((lag_arr, peak_arr))
+}
+
+/// Subtract the mean from a trace.
+fn centered<T: AsNumeric>(data: ArrayView1<T>) -> Vec<f64> {
+    let mean = data.iter().map(|v| v.to_f64()).sum::<f64>() / data.len() as f64;
+    data.iter().map(|v| v.to_f64() - mean).collect()
+}
+
+/// Compute the normalized cross-correlation of two centered traces.
+fn normalized_xcorr(a: &[f64], b: &[f64], max_lag: usize) -> Array1<f64> {
+    let n = a.len();
+    let norm = (a.iter().map(|v| v * v).sum::<f64>() * b.iter().map(|v| v * v).sum::<f64>()).sqrt();
+    let mut xcorr = Array1::<f64>::zeros(2 * max_lag + 1);
+    if norm == 0.0 {
+        return xcorr;
+    }
+    xcorr.iter_mut().enumerate().for_each(|(k, c)| {
+        let lag = k as isize - max_lag as isize;
+        let (a_start, b_start) = if lag >= 0 {
+            (0, lag as usize)
+        } else {
+            ((-lag) as usize, 0)
+        };
+        let len = n - lag.unsigned_abs();
+        *c = (0..len)
+            .map(|t| a[a_start + t] * b[b_start + t])
+            .sum::<f64>()
+            / norm;
+    });
+    xcorr
+}
+
+/// Validate the maximum lag and apply the default.
+fn validate_max_lag(max_lag: Option<usize>, n: usize) -> Result<usize, ImgalError> {
+    let max_lag = max_lag.unwrap_or(n - 1);
+    if max_lag >= n {
+        return Err(ImgalError::InvalidParameterValueGreater {
+            param_name: "max_lag",
+            value: n - 1,
+        });
+    }
+    Ok(max_lag)
+}
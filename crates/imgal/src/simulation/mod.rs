@@ -1,8 +1,13 @@
-//! Decay, instrument, and noise simulation functions.
+//! Decay, instrument, noise, illumination pattern, point spread function,
+//! particle trajectory and tissue region simulation functions.
 
 pub mod blob;
 pub mod decay;
 pub mod gradient;
 pub mod instrument;
 pub mod noise;
+pub mod pattern;
+pub mod psf;
 pub mod rng;
+pub mod tissue;
+pub mod trajectories;
@@ -0,0 +1,282 @@
+use std::f64::consts::PI;
+
+use ndarray::{Array2, ArrayBase, ArrayD, ArrayViewMutD, AsArray, Axis, Ix2, IxDyn, ViewRepr};
+use rayon::prelude::*;
+
+use crate::constants::RNG_SEED;
+use crate::prelude::*;
+use crate::simulation::noise::poisson_noise_mut;
+use crate::simulation::rng::Pcg;
+
+/// The particle motion model used by `simulate_tracks`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MotionModel {
+    /// Free Brownian motion with the diffusion coefficient `diffusion` in
+    /// pixels² per frame.
+    Brownian { diffusion: f64 },
+    /// Brownian motion confined to a hypersphere of radius `radius` (in pixels)
+    /// around the particle's starting position.
+    Confined { diffusion: f64, radius: f64 },
+    /// Brownian motion with a constant drift of `velocity` pixels per frame in
+    /// a random direction per track.
+    Directed { diffusion: f64, velocity: f64 },
+}
+
+/// Simulate n-dimensional particle trajectories.
+///
+/// # Description
+///
+/// Simulates the trajectories of particles undergoing Brownian, confined or
+/// directed motion. Particles start at uniformly distributed random positions
+/// within `shape`. At each frame every particle is displaced along each axis by
+/// a Gaussian random step:
+///
+/// ```text
+/// Δx ~ N(v, 2 × D × Δt)
+/// ```
+///
+/// Where `D` is the diffusion coefficient, `Δt = 1` frame and `v` is the drift
+/// velocity (directed motion only). Confined particles reject steps that would
+/// leave the confinement hypersphere. Particles are allowed to leave the image
+/// bounds.
+///
+/// # Arguments
+///
+/// * `n_tracks`: The number of particle tracks.
+/// * `n_frames`: The number of frames (*i.e.* time points) per track.
+/// * `shape`: The spatial shape of the image the particles start in.
+/// * `motion`: The particle motion model.
+/// * `seed`: The seed value for the pseudo-random number generator. If `None`,
+///   then the default seed is used.
+///
+/// # Returns
+///
+/// * `Ok(Array2<f64>)`: The ground-truth track table with shape
+///   `(n_tracks × n_frames, 2 + D)`, where `D = shape.len()`. Each row is
+///   `[track_id, frame, x₀, ..., xᴰ]`, sorted by track and frame.
+/// * `Err(ImgalError)`: If `shape` is empty. If any diffusion coefficient,
+///   confinement radius or velocity is negative.
+pub fn simulate_tracks(
+    n_tracks: usize,
+    n_frames: usize,
+    shape: &[usize],
+    motion: MotionModel,
+    seed: Option<u64>,
+) -> Result<Array2<f64>, ImgalError> {
+    if shape.is_empty() {
+        return Err(ImgalError::InvalidParameterEmptyArray {
+            param_name: "shape",
+        });
+    }
+    let (diffusion, radius, velocity) = match motion {
+        MotionModel::Brownian { diffusion } => (diffusion, f64::INFINITY, 0.0),
+        MotionModel::Confined { diffusion, radius } => (diffusion, radius, 0.0),
+        MotionModel::Directed {
+            diffusion,
+            velocity,
+        } => (diffusion, f64::INFINITY, velocity),
+    };
+    for (name, value) in [
+        ("diffusion", diffusion),
+        ("radius", radius),
+        ("velocity", velocity),
+    ] {
+        if value < 0.0 {
+            return Err(ImgalError::InvalidParameterValueOutsideRange {
+                param_name: name,
+                value,
+                min: 0.0,
+                max: f64::INFINITY,
+            });
+        }
+    }
+    let d = shape.len();
+    let step_std = (2.0 * diffusion).sqrt();
+    let mut prng = Pcg::new(seed.unwrap_or(RNG_SEED));
+    let mut tracks = Array2::<f64>::zeros((n_tracks * n_frames, 2 + d));
+    for t in 0..n_tracks {
+        let origin: Vec<f64> = shape
+            .iter()
+            .map(|&s| prng.next_f32() as f64 * s as f64)
+            .collect();
+        // a random unit direction for the drift
+        let mut drift: Vec<f64> = (0..d).map(|_| gaussian_sample(&mut prng)).collect();
+        let norm = drift
+            .iter()
+            .map(|v| v * v)
+            .sum::<f64>()
+            .sqrt()
+            .max(f64::EPSILON);
+        drift.iter_mut().for_each(|v| *v *= velocity / norm);
+        let mut pos = origin.clone();
+        for f in 0..n_frames {
+            if f > 0 {
+                // confined particles retry steps that leave the confinement
+                for _ in 0..100 {
+                    let trial: Vec<f64> = pos
+                        .iter()
+                        .zip(drift.iter())
+                        .map(|(p, v)| p + v + step_std * gaussian_sample(&mut prng))
+                        .collect();
+                    let dist = trial
+                        .iter()
+                        .zip(origin.iter())
+                        .map(|(a, b)| (a - b).powi(2))
+                        .sum::<f64>()
+                        .sqrt();
+                    if dist <= radius {
+                        pos = trial;
+                        break;
+                    }
+                }
+            }
+            let mut row = tracks.row_mut(t * n_frames + f);
+            row[0] = t as f64;
+            row[1] = f as f64;
+            pos.iter().enumerate().for_each(|(i, &p)| row[2 + i] = p);
+        }
+    }
+    Ok(tracks)
+}
+
+/// Render particle tracks into an n-dimensional image stack.
+///
+/// # Description
+///
+/// Renders a track table (*e.g.* from `simulate_tracks`) into an image stack
+/// with the shape `(n_frames, ...shape)`. Each particle is rendered as a
+/// Gaussian point spread function (PSF):
+///
+/// ```text
+/// I(x) = b + Σₚ A × exp(-|x - xₚ|² / (2σ²))
+/// ```
+///
+/// Where `b` is the background, `A` is the peak intensity and `σ` is the PSF
+/// standard deviation. Optionally, Poisson (shot) noise is added to the
+/// rendered stack.
+///
+/// # Arguments
+///
+/// * `tracks`: The track table with rows `[track_id, frame, x₀, ..., xᴰ]`.
+/// * `n_frames`: The number of frames of the output stack. Rows with a frame
+///   outside of `0..n_frames` are ignored.
+/// * `shape`: The spatial shape of each frame.
+/// * `sigma`: The standard deviation of the Gaussian PSF in pixels.
+/// * `intensity`: The peak intensity of each particle.
+/// * `background`: The background intensity.
+/// * `noise`: If `true` Poisson noise is added to the rendered stack.
+/// * `seed`: The seed value for the Poisson noise pseudo-random number
+///   generator. If `None`, then the default seed is used.
+/// * `threads`: The requested number of threads to use for parallel execution.
+///   If `None` or `Some(1)` sequential execution is used. If `Some(0)`, then
+///   the maximum available parallelism is used. Thread counts are clamped to
+///   the systems maximum. The Poisson noise is only deterministic with
+///   sequential execution.
+///
+/// # Returns
+///
+/// * `Ok(ArrayD<f64>)`: The rendered image stack with shape
+///   `(n_frames, ...shape)`.
+/// * `Err(ImgalError)`: If axis 1 of `tracks` is not of length
+///   `2 + shape.len()`. If `sigma <= 0.0`.
+pub fn render_tracks<'a, A>(
+    tracks: A,
+    n_frames: usize,
+    shape: &[usize],
+    sigma: f64,
+    intensity: f64,
+    background: f64,
+    noise: bool,
+    seed: Option<u64>,
+    threads: Option<usize>,
+) -> Result<ArrayD<f64>, ImgalError>
+where
+    A: AsArray<'a, f64, Ix2>,
+{
+    let tracks: ArrayBase<ViewRepr<&'a f64>, Ix2> = tracks.into();
+    let d = shape.len();
+    if tracks.dim().1 != 2 + d {
+        return Err(ImgalError::InvalidAxisLengthExpected {
+            arr_name: "tracks",
+            axis_idx: 1,
+            expected: 2 + d,
+            got: tracks.dim().1,
+        });
+    }
+    if sigma <= 0.0 {
+        return Err(ImgalError::InvalidParameterValueOutsideRange {
+            param_name: "sigma",
+            value: sigma,
+            min: 0.0,
+            max: f64::INFINITY,
+        });
+    }
+    // group the particle positions by frame
+    let mut frames: Vec<Vec<Vec<f64>>> = vec![Vec::new(); n_frames];
+    tracks.rows().into_iter().for_each(|row| {
+        let f = row[1];
+        if f >= 0.0 && (f as usize) < n_frames {
+            frames[f as usize].push(row.iter().skip(2).cloned().collect());
+        }
+    });
+    let mut stack_shape = vec![n_frames];
+    stack_shape.extend_from_slice(shape);
+    let mut stack = ArrayD::<f64>::from_elem(IxDyn(&stack_shape), background);
+    let reach = (4.0 * sigma).ceil() as isize;
+    let two_sig_sq = 2.0 * sigma * sigma;
+    let render_frame = |(f, mut frame): (usize, ArrayViewMutD<f64>)| {
+        frames[f].iter().for_each(|p| {
+            // only visit the pixels within 4σ of the particle
+            let lo: Vec<isize> = p.iter().map(|&c| c.round() as isize - reach).collect();
+            let ranges: Vec<(usize, usize)> = lo
+                .iter()
+                .zip(shape.iter())
+                .map(|(&l, &s)| {
+                    let start = l.clamp(0, s as isize) as usize;
+                    let end = (l + 2 * reach + 1).clamp(0, s as isize) as usize;
+                    (start, end)
+                })
+                .collect();
+            if ranges.iter().any(|(s, e)| s >= e) {
+                return;
+            }
+            let mut idx: Vec<usize> = ranges.iter().map(|r| r.0).collect();
+            loop {
+                let dist_sq: f64 = idx
+                    .iter()
+                    .zip(p.iter())
+                    .map(|(&i, &c)| (i as f64 - c).powi(2))
+                    .sum();
+                frame[IxDyn(&idx)] += intensity * (-dist_sq / two_sig_sq).exp();
+                // advance the n-dimensional window index
+                let mut ax = d;
+                loop {
+                    if ax == 0 {
+                        return;
+                    }
+                    ax -= 1;
+                    idx[ax] += 1;
+                    if idx[ax] < ranges[ax].1 {
+                        break;
+                    }
+                    idx[ax] = ranges[ax].0;
+                }
+            }
+        });
+    };
+    par!(threads,
+        seq_exp: stack.axis_iter_mut(Axis(0)).enumerate().for_each(render_frame),
+        par_exp: stack.axis_iter_mut(Axis(0)).into_par_iter().enumerate().for_each(render_frame));
+    if noise {
+        poisson_noise_mut(stack.view_mut(), 1.0, seed, threads);
+    }
+    Ok(stack)
+}
+
+/// Draw a standard normal sample with the Box-Muller transform.
+fn gaussian_sample(prng: &mut Pcg) -> f64 {
+    // shift the uniform sample into (0, 1] to avoid ln(0)
+    let u1 = 1.0 - prng.next_f32() as f64;
+    let u2 = prng.next_f32() as f64;
+    (-2.0 * u1.ln()).sqrt() * (2.0 * PI * u2).cos()
+}
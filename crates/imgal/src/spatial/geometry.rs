@@ -2,7 +2,7 @@ use ndarray::{Array1, ArrayBase, AsArray, Axis, Ix1, Ix2, ViewRepr};
 use rayon::prelude::*;
 
 use crate::prelude::*;
-use crate::statistics::sum;
+use crate::statistics::sum_f64;
 
 /// Compute the centroid of a set of vertices.
 ///
@@ -46,7 +46,7 @@ where
     let centroid = par!(threads,
     seq_exp: vertices.axis_iter(Axis(1))
         .fold(Vec::new(), |mut acc, l| {
-            acc.push(inv_num_verts * sum(l, Some(1)).to_f64());
+            acc.push(inv_num_verts * sum_f64(l, Some(1)));
             acc
         }),
     par_exp: {
@@ -56,7 +56,7 @@ where
             .fold(
                 || vec![0.0; n_dims],
                 |mut acc, (i, l)| {
-                    acc[i] = inv_num_verts * sum(l, Some(1)).to_f64();
+                    acc[i] = inv_num_verts * sum_f64(l, Some(1));
                     acc
                 },
             )
@@ -4,6 +4,7 @@ use ndarray::{Array1, Array2, ArrayBase, AsArray, Axis, Dimension, ViewRepr};
 use rayon::prelude::*;
 
 use crate::prelude::*;
+use crate::validate::check_shapes;
 
 /// Create a ROI point cloud map from an n-dimensional label image.
 ///
@@ -120,14 +121,7 @@ where
 {
     let data: ArrayBase<ViewRepr<&'a T>, D> = data.into();
     let labels: ArrayBase<ViewRepr<&'a u64>, D> = labels.into();
-    if data.shape() != labels.shape() {
-        return Err(ImgalError::MismatchedArrayShapes {
-            a_arr_name: "data",
-            a_shape: data.shape().to_vec(),
-            b_arr_name: "labels",
-            b_shape: labels.shape().to_vec(),
-        });
-    }
+    check_shapes("data", data.shape(), "labels", labels.shape())?;
     let data = data.into_dyn();
     let rcm = roi_cloud_map(labels, threads);
     let mut rdm: HashMap<u64, Array1<T>> = HashMap::new();
@@ -0,0 +1,488 @@
+use std::collections::HashMap;
+
+use ndarray::{ArrayBase, ArrayView, AsArray, Dimension, ViewRepr};
+use rayon::prelude::*;
+
+use crate::prelude::*;
+use crate::validate::check_shapes;
+
+/// The spatial weights between neighboring pixels or objects used by spatial
+/// autocorrelation statistics.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum SpatialWeights {
+    /// Binary weights of the face-adjacent neighbors (*i.e.* 4-connectivity in
+    /// 2D and 6-connectivity in 3D). Objects are neighbors if any of their
+    /// pixels are face-adjacent.
+    #[default]
+    Rook,
+    /// Binary weights of all neighbors, including the diagonal neighbors
+    /// (*i.e.* 8-connectivity in 2D and 26-connectivity in 3D). Objects are
+    /// neighbors if any of their pixels are adjacent.
+    Queen,
+    /// Inverse distance weights, `w = 1 / d`, of all (queen) neighbors. For
+    /// objects `d` is the distance between the centroids of adjacent objects.
+    InverseDistance,
+}
+
+/// Compute the global Moran's I spatial autocorrelation of an n-dimensional
+/// image.
+///
+/// # Description
+///
+/// Computes Moran's I over the pixel lattice of an n-dimensional image, a
+/// measure of the spatial clustering of intensities:
+///
+/// ```text
+/// I = (n / W) × Σᵢ Σⱼ wᵢⱼ zᵢ zⱼ / Σᵢ zᵢ²
+/// ```
+///
+/// Where `zᵢ = xᵢ - mean(x)`, `wᵢⱼ` are the spatial weights between pixels `i`
+/// and `j` and `W = Σᵢ Σⱼ wᵢⱼ`. Values near `1.0` indicate clustering of
+/// similar intensities, values near the expectation `-1 / (n - 1)` a random
+/// arrangement and negative values dispersion (*e.g.* a checkerboard). Pixel
+/// pairs are accumulated in parallel.
+///
+/// # Arguments
+///
+/// * `data`: An n-dimensional image.
+/// * `weights`: The spatial weights of neighboring pixels. If `None`, then
+///   `weights = SpatialWeights::Rook`.
+/// * `mask`: An optional boolean mask with the same shape as `data`. Only the
+///   pixels inside the mask, and their neighbors inside the mask, are used.
+/// * `threads`: The requested number of threads to use for parallel execution.
+///   If `None` or `Some(1)` sequential execution is used. If `Some(0)`, then
+///   the maximum available parallelism is used. Thread counts are clamped to
+///   the systems maximum.
+///
+/// # Returns
+///
+/// * `Ok(f64)`: Moran's I. If the (masked) image is constant or has no
+///   neighboring pixels, then `NaN`.
+/// * `Err(ImgalError)`: If `data` is empty. If the `mask` shape does not match
+///   the `data` shape.
+///
+/// # Reference
+///
+/// <https://doi.org/10.2307/2332142>
+pub fn morans_i<'a, T, A, D>(
+    data: A,
+    weights: Option<SpatialWeights>,
+    mask: Option<ArrayView<bool, D>>,
+    threads: Option<usize>,
+) -> Result<f64, ImgalError>
+where
+    A: AsArray<'a, T, D>,
+    D: Dimension,
+    T: 'a + AsNumeric,
+{
+    Ok(lattice_autocorrelation(data.into(), weights, mask, threads)?.0)
+}
+
+/// Compute the global Geary's C spatial autocorrelation of an n-dimensional
+/// image.
+///
+/// # Description
+///
+/// Computes Geary's C over the pixel lattice of an n-dimensional image, a
+/// measure of the spatial clustering of intensities that is more sensitive to
+/// differences between neighbors than Moran's I (see `morans_i`):
+///
+/// ```text
+/// C = ((n - 1) / (2W)) × Σᵢ Σⱼ wᵢⱼ (xᵢ - xⱼ)² / Σᵢ zᵢ²
+/// ```
+///
+/// Where `zᵢ = xᵢ - mean(x)`, `wᵢⱼ` are the spatial weights between pixels `i`
+/// and `j` and `W = Σᵢ Σⱼ wᵢⱼ`. Values below `1.0` indicate clustering of
+/// similar intensities, `1.0` a random arrangement and values above `1.0`
+/// dispersion.
+///
+/// # Arguments
+///
+/// * `data`: An n-dimensional image.
+/// * `weights`: The spatial weights of neighboring pixels. If `None`, then
+///   `weights = SpatialWeights::Rook`.
+/// * `mask`: An optional boolean mask with the same shape as `data`. Only the
+///   pixels inside the mask, and their neighbors inside the mask, are used.
+/// * `threads`: The requested number of threads to use for parallel execution.
+///   If `None` or `Some(1)` sequential execution is used. If `Some(0)`, then
+///   the maximum available parallelism is used. Thread counts are clamped to
+///   the systems maximum.
+///
+/// # Returns
+///
+/// * `Ok(f64)`: Geary's C. If the (masked) image is constant or has no
+///   neighboring pixels, then `NaN`.
+/// * `Err(ImgalError)`: If `data` is empty. If the `mask` shape does not match
+///   the `data` shape.
+///
+/// # Reference
+///
+/// <https://doi.org/10.2307/2986645>
+pub fn gearys_c<'a, T, A, D>(
+    data: A,
+    weights: Option<SpatialWeights>,
+    mask: Option<ArrayView<bool, D>>,
+    threads: Option<usize>,
+) -> Result<f64, ImgalError>
+where
+    A: AsArray<'a, T, D>,
+    D: Dimension,
+    T: 'a + AsNumeric,
+{
+    Ok(lattice_autocorrelation(data.into(), weights, mask, threads)?.1)
+}
+
+/// Compute the global Moran's I spatial autocorrelation of per-object
+/// measurements over the adjacency graph of a label image.
+///
+/// # Description
+///
+/// Computes Moran's I (see `morans_i`) of per-object measurements (*e.g.* the
+/// mean intensity or area of each cell), where the objects of an
+/// n-dimensional label image are the graph nodes and touching objects are
+/// connected by an edge with the given spatial `weights`. The label `0` is the
+/// background.
+///
+/// # Arguments
+///
+/// * `labels`: The n-dimensional label image.
+/// * `values`: A map of the measurement of each label. Labels without a value
+///   are ignored.
+/// * `weights`: The spatial weights of adjacent objects. If `None`, then
+///   `weights = SpatialWeights::Rook`.
+/// * `threads`: The requested number of threads to use for parallel execution.
+///   If `None` or `Some(1)` sequential execution is used. If `Some(0)`, then
+///   the maximum available parallelism is used. Thread counts are clamped to
+///   the systems maximum.
+///
+/// # Returns
+///
+/// * `Ok(f64)`: Moran's I of the object measurements. If the measurements are
+///   constant or no objects are adjacent, then `NaN`.
+/// * `Err(ImgalError)`: If `labels` is empty.
+pub fn morans_i_labels<'a, A, D>(
+    labels: A,
+    values: &HashMap<u64, f64>,
+    weights: Option<SpatialWeights>,
+    threads: Option<usize>,
+) -> Result<f64, ImgalError>
+where
+    A: AsArray<'a, u64, D>,
+    D: Dimension,
+{
+    Ok(label_autocorrelation(labels.into(), values, weights, threads)?.0)
+}
+
+/// Compute the global Geary's C spatial autocorrelation of per-object
+/// measurements over the adjacency graph of a label image.
+///
+/// # Description
+///
+/// Computes Geary's C (see `gearys_c`) of per-object measurements, where the
+/// objects of an n-dimensional label image are the graph nodes and touching
+/// objects are connected by an edge with the given spatial `weights`. The
+/// label `0` is the background.
+///
+/// # Arguments
+///
+/// * `labels`: The n-dimensional label image.
+/// * `values`: A map of the measurement of each label. Labels without a value
+///   are ignored.
+/// * `weights`: The spatial weights of adjacent objects. If `None`, then
+///   `weights = SpatialWeights::Rook`.
+/// * `threads`: The requested number of threads to use for parallel execution.
+///   If `None` or `Some(1)` sequential execution is used. If `Some(0)`, then
+///   the maximum available parallelism is used. Thread counts are clamped to
+///   the systems maximum.
+///
+/// # Returns
+///
+/// * `Ok(f64)`: Geary's C of the object measurements. If the measurements are
+///   constant or no objects are adjacent, then `NaN`.
+/// * `Err(ImgalError)`: If `labels` is empty.
+pub fn gearys_c_labels<'a, A, D>(
+    labels: A,
+    values: &HashMap<u64, f64>,
+    weights: Option<SpatialWeights>,
+    threads: Option<usize>,
+) -> Result<f64, ImgalError>
+where
+    A: AsArray<'a, u64, D>,
+    D: Dimension,
+{
+    Ok(label_autocorrelation(labels.into(), values, weights, threads)?.1)
+}
+
+/// Compute the forward neighbor offsets (*i.e.* the first non-zero component
+/// is positive) of an n-dimensional lattice and their weights, so that each
+/// unordered neighbor pair is visited once.
+pub(super) fn neighbor_offsets(ndim: usize, weights: SpatialWeights) -> Vec<(Vec<isize>, f64)> {
+    let n_offsets = 3_usize.pow(ndim as u32);
+    (0..n_offsets)
+        .filter_map(|k| {
+            let mut rem = k;
+            let offset: Vec<isize> = (0..ndim)
+                .map(|_| {
+                    let o = (rem % 3) as isize - 1;
+                    rem /= 3;
+                    o
+                })
+                .rev()
+                .collect();
+            let first = offset.iter().find(|&&o| o != 0)?;
+            let n_nonzero = offset.iter().filter(|&&o| o != 0).count();
+            if *first < 0 || (weights == SpatialWeights::Rook && n_nonzero > 1) {
+                return None;
+            }
+            let w = match weights {
+                SpatialWeights::InverseDistance => 1.0 / (n_nonzero as f64).sqrt(),
+                _ => 1.0,
+            };
+            Some((offset, w))
+        })
+        .collect()
+}
+
+/// Find the flat index of the neighbor of the pixel with flat index `i` at
+/// `offset` in a standard layout lattice, `None` if out of bounds.
+pub(super) fn neighbor_index(
+    i: usize,
+    offset: &[isize],
+    shape: &[usize],
+    strides: &[usize],
+) -> Option<usize> {
+    let mut j = i as isize;
+    for ((&o, &len), &stride) in offset.iter().zip(shape).zip(strides) {
+        let pos = (i / stride % len) as isize + o;
+        if pos < 0 || pos >= len as isize {
+            return None;
+        }
+        j += o * stride as isize;
+    }
+    Some(j as usize)
+}
+
+/// Compute the standard layout strides of a shape.
+pub(super) fn standard_strides(shape: &[usize]) -> Vec<usize> {
+    let mut strides = vec![1; shape.len()];
+    for k in (0..shape.len().saturating_sub(1)).rev() {
+        strides[k] = strides[k + 1] * shape[k + 1];
+    }
+    strides
+}
+
+/// Compute Moran's I and Geary's C of the (masked) pixel lattice.
+fn lattice_autocorrelation<T, D>(
+    data: ArrayBase<ViewRepr<&T>, D>,
+    weights: Option<SpatialWeights>,
+    mask: Option<ArrayView<bool, D>>,
+    threads: Option<usize>,
+) -> Result<(f64, f64), ImgalError>
+where
+    D: Dimension,
+    T: AsNumeric,
+{
+    if data.is_empty() {
+        return Err(ImgalError::InvalidParameterEmptyArray { param_name: "data" });
+    }
+    if let Some(msk) = mask.as_ref() {
+        check_shapes("mask", msk.shape(), "data", data.shape())?;
+    }
+    let x: Vec<f64> = data.iter().map(|v| v.to_f64()).collect();
+    let keep: Vec<bool> = match mask {
+        Some(m) => m.iter().copied().collect(),
+        None => vec![true; x.len()],
+    };
+    let shape = data.shape().to_vec();
+    let strides = standard_strides(&shape);
+    let offsets = neighbor_offsets(shape.len(), weights.unwrap_or_default());
+    let (n, sum) = x
+        .iter()
+        .zip(keep.iter())
+        .filter(|(_, k)| **k)
+        .fold((0usize, 0.0), |acc, (v, _)| (acc.0 + 1, acc.1 + v));
+    let mean = sum / n as f64;
+    // accumulate Σzᵢ², W/2, Σwᵢⱼzᵢzⱼ/2 and Σwᵢⱼ(xᵢ - xⱼ)²/2
+    let zero = || [0.0; 4];
+    let pair_calc = |mut acc: [f64; 4], i: usize| {
+        if !keep[i] {
+            return acc;
+        }
+        let zi = x[i] - mean;
+        acc[0] += zi * zi;
+        for (offset, w) in offsets.iter() {
+            if let Some(j) = neighbor_index(i, offset, &shape, &strides)
+                && keep[j]
+            {
+                acc[1] += w;
+                acc[2] += w * zi * (x[j] - mean);
+                acc[3] += w * (x[i] - x[j]).powi(2);
+            }
+        }
+        acc
+    };
+    let add = |a: [f64; 4], b: [f64; 4]| [a[0] + b[0], a[1] + b[1], a[2] + b[2], a[3] + b[3]];
+    let sums = par!(threads,
+        seq_exp: (0..x.len()).fold(zero(), pair_calc),
+        par_exp: (0..x.len()).into_par_iter().fold(zero, pair_calc).reduce(zero, add));
+    Ok(global_statistics(n, sums))
+}
+
+/// Compute Moran's I and Geary's C of per-object measurements over the label
+/// adjacency graph.
+fn label_autocorrelation<D>(
+    labels: ArrayBase<ViewRepr<&u64>, D>,
+    values: &HashMap<u64, f64>,
+    weights: Option<SpatialWeights>,
+    threads: Option<usize>,
+) -> Result<(f64, f64), ImgalError>
+where
+    D: Dimension,
+{
+    let LabelGraph {
+        labels: present,
+        edges,
+    } = label_graph(labels, values, weights, threads)?;
+    let n = present.len();
+    let x: Vec<f64> = present.iter().map(|l| values[l]).collect();
+    let mean = x.iter().sum::<f64>() / n as f64;
+    let mut sums = [0.0; 4];
+    x.iter().for_each(|v| sums[0] += (v - mean).powi(2));
+    edges.iter().for_each(|&(a, b, w)| {
+        sums[1] += w;
+        sums[2] += w * (x[a] - mean) * (x[b] - mean);
+        sums[3] += w * (x[a] - x[b]).powi(2);
+    });
+    Ok(global_statistics(n, sums))
+}
+
+/// The weighted adjacency graph of the objects of a label image.
+pub(super) struct LabelGraph {
+    /// The sorted object labels.
+    pub labels: Vec<u64>,
+    /// The unordered weighted edges between the object indices.
+    pub edges: Vec<(usize, usize, f64)>,
+}
+
+/// Build the weighted adjacency graph of the objects of a label image with a
+/// value.
+pub(super) fn label_graph<D>(
+    labels: ArrayBase<ViewRepr<&u64>, D>,
+    values: &HashMap<u64, f64>,
+    weights: Option<SpatialWeights>,
+    threads: Option<usize>,
+) -> Result<LabelGraph, ImgalError>
+where
+    D: Dimension,
+{
+    if labels.is_empty() {
+        return Err(ImgalError::InvalidParameterEmptyArray {
+            param_name: "labels",
+        });
+    }
+    let weights = weights.unwrap_or_default();
+    let lbl: Vec<u64> = labels.iter().copied().collect();
+    let shape = labels.shape().to_vec();
+    let strides = standard_strides(&shape);
+    // adjacency is found with binary (queen or rook) neighborhoods
+    let offsets = neighbor_offsets(
+        shape.len(),
+        match weights {
+            SpatialWeights::Rook => SpatialWeights::Rook,
+            _ => SpatialWeights::Queen,
+        },
+    );
+    let has_value = |l: u64| l != 0 && values.contains_key(&l);
+    let edge_calc = |mut acc: Vec<(u64, u64)>, i: usize| {
+        let a = lbl[i];
+        if has_value(a) {
+            for (offset, _) in offsets.iter() {
+                if let Some(j) = neighbor_index(i, offset, &shape, &strides) {
+                    let b = lbl[j];
+                    if b != a && has_value(b) {
+                        acc.push((a.min(b), a.max(b)));
+                    }
+                }
+            }
+        }
+        acc
+    };
+    let mut label_edges: Vec<(u64, u64)> = par!(threads,
+    seq_exp: (0..lbl.len()).fold(Vec::new(), edge_calc),
+    par_exp: (0..lbl.len())
+        .into_par_iter()
+        .fold(Vec::new, edge_calc)
+        .reduce(Vec::new, |mut a, mut b| {
+            a.append(&mut b);
+            a
+        }));
+    label_edges.sort_unstable();
+    label_edges.dedup();
+    // the objects with a value, including objects without neighbors
+    let mut present: Vec<u64> = lbl.iter().copied().filter(|&l| has_value(l)).collect();
+    present.sort_unstable();
+    present.dedup();
+    let index: HashMap<u64, usize> = present.iter().enumerate().map(|(i, &l)| (l, i)).collect();
+    let centroids: HashMap<u64, Vec<f64>> = match weights {
+        SpatialWeights::InverseDistance => label_centroids(&lbl, &shape, &strides),
+        _ => HashMap::new(),
+    };
+    let edges = label_edges
+        .iter()
+        .map(|(a, b)| {
+            let w = match weights {
+                SpatialWeights::InverseDistance => {
+                    let d: f64 = centroids[a]
+                        .iter()
+                        .zip(centroids[b].iter())
+                        .map(|(p, q)| (p - q).powi(2))
+                        .sum::<f64>()
+                        .sqrt();
+                    1.0 / d
+                }
+                _ => 1.0,
+            };
+            (index[a], index[b], w)
+        })
+        .collect();
+    Ok(LabelGraph {
+        labels: present,
+        edges,
+    })
+}
+
+/// Compute the centroid of each non-zero label of a standard layout label
+/// lattice.
+fn label_centroids(lbl: &[u64], shape: &[usize], strides: &[usize]) -> HashMap<u64, Vec<f64>> {
+    let mut acc: HashMap<u64, (Vec<f64>, f64)> = HashMap::new();
+    lbl.iter()
+        .enumerate()
+        .filter(|(_, l)| **l != 0)
+        .for_each(|(i, l)| {
+            let entry = acc
+                .entry(*l)
+                .or_insert_with(|| (vec![0.0; shape.len()], 0.0));
+            entry
+                .0
+                .iter_mut()
+                .zip(shape.iter().zip(strides))
+                .for_each(|(c, (&len, &stride))| *c += (i / stride % len) as f64);
+            entry.1 += 1.0;
+        });
+    acc.into_iter()
+        .map(|(l, (sum, count))| (l, sum.into_iter().map(|s| s / count).collect()))
+        .collect()
+}
+
+/// Compute Moran's I and Geary's C from `n` and the sums `[Σzᵢ², W/2,
+/// Σwᵢⱼzᵢzⱼ/2, Σwᵢⱼ(xᵢ - xⱼ)²/2]` over the unordered neighbor pairs.
+fn global_statistics(n: usize, sums: [f64; 4]) -> (f64, f64) {
+    let [m2, w_half, cross, sq_diff] = sums;
+    if n < 2 || m2 == 0.0 || w_half == 0.0 {
+        return (f64::NAN, f64::NAN);
+    }
+    let n = n as f64;
+    let i = n * cross / (w_half * m2);
+    let c = (n - 1.0) * sq_diff / (2.0 * w_half * m2);
+    (i, c)
+}
@@ -0,0 +1,113 @@
+use ndarray::{Array1, ArrayBase, ArrayView1, AsArray, Ix1, ViewRepr};
+use rayon::prelude::*;
+
+use crate::constants::RNG_SEED;
+use crate::prelude::*;
+use crate::simulation::rng::Pcg;
+use crate::statistics::linear_percentiles;
+
+/// Compute a bootstrap confidence interval of a statistic.
+///
+/// # Description
+///
+/// Estimates the confidence interval of an arbitrary `statistic` with the
+/// percentile bootstrap. The 1D `data` (*e.g.* the pixel values of a ROI) is
+/// resampled with replacement `n_resamples` times, the statistic is computed
+/// for each resample and the interval is given by the linear percentiles of
+/// the resampled statistics:
+///
+/// ```text
+/// α = 1 - confidence
+/// CI = [P(100 × α / 2), P(100 × (1 - α / 2))]
+/// ```
+///
+/// Each resample draws its own pseudo-random number generator seed from a
+/// generator seeded with `seed`, so the result is deterministic for a given
+/// `seed` independent of the thread count.
+///
+/// # Arguments
+///
+/// * `data`: The 1D input data.
+/// * `statistic`: The statistic to compute for each resample.
+/// * `n_resamples`: The number of bootstrap resamples. If `None`, then
+///   `n_resamples = 1000`.
+/// * `confidence`: The confidence level in the open interval `(0.0, 1.0)`. If
+///   `None`, then `confidence = 0.95`.
+/// * `seed`: The seed value for the pseudo-random number generator. If `None`,
+///   then `seed = RNG_SEED`.
+/// * `threads`: The requested number of threads to use for parallel execution.
+///   If `None` or `Some(1)` sequential execution is used. If `Some(0)`, then
+///   the maximum available parallelism is used. Thread counts are clamped to
+///   the systems maximum.
+///
+/// # Returns
+///
+/// * `Ok((f64, f64))`: The lower and upper bounds of the confidence interval.
+/// * `Err(ImgalError)`: If `data` is empty. If `n_resamples == 0`. If
+///   `confidence` is outside the open interval `(0.0, 1.0)`. If `data` has
+///   more than `u32::MAX` elements.
+///
+/// # Reference
+///
+/// <https://doi.org/10.1214/aos/1176344552>
+pub fn bootstrap_ci<'a, T, A, F>(
+    data: A,
+    statistic: F,
+    n_resamples: Option<usize>,
+    confidence: Option<f64>,
+    seed: Option<u64>,
+    threads: Option<usize>,
+) -> Result<(f64, f64), ImgalError>
+where
+    A: AsArray<'a, T, Ix1>,
+    T: 'a + AsNumeric,
+    F: Fn(ArrayView1<f64>) -> f64 + Sync,
+{
+    let data: ArrayBase<ViewRepr<&'a T>, Ix1> = data.into();
+    let n_resamples = n_resamples.unwrap_or(1000);
+    let confidence = confidence.unwrap_or(0.95);
+    if data.is_empty() {
+        return Err(ImgalError::InvalidParameterEmptyArray { param_name: "data" });
+    }
+    if n_resamples == 0 {
+        return Err(ImgalError::InvalidParameterValueEqual {
+            param_name: "n_resamples",
+            value: 0,
+        });
+    }
+    if !(confidence > 0.0 && confidence < 1.0) {
+        return Err(ImgalError::InvalidParameterValueOutsideRange {
+            param_name: "confidence",
+            value: confidence,
+            min: 0.0,
+            max: 1.0,
+        });
+    }
+    let n = u32::try_from(data.len()).map_err(|_| ImgalError::InvalidGeneric {
+        msg: "Invalid bootstrap data, the number of elements exceeds u32::MAX.",
+    })?;
+    let samples: Vec<f64> = data.iter().map(|v| v.to_f64()).collect();
+    let mut prng = Pcg::new(seed.unwrap_or(RNG_SEED));
+    let seeds: Vec<u64> = (0..n_resamples)
+        .map(|_| ((prng.next_u32() as u64) << 32) | prng.next_u32() as u64)
+        .collect();
+    let resample = |s: &u64| {
+        let mut g = Pcg::new(*s);
+        let buf: Array1<f64> = (0..n)
+            .map(|_| samples[g.next_u32_range(0..n).unwrap() as usize])
+            .collect();
+        statistic(buf.view())
+    };
+    let stats: Vec<f64> = par!(threads,
+        seq_exp: seeds.iter().map(resample).collect(),
+        par_exp: seeds.par_iter().map(resample).collect());
+    let alpha = 1.0 - confidence;
+    let bounds = linear_percentiles(
+        &stats,
+        &[50.0 * alpha, 100.0 * (1.0 - 0.5 * alpha)],
+        None,
+        None,
+        None,
+    )?;
+    Ok((bounds[0], bounds[1]))
+}
@@ -108,6 +108,67 @@ where
     Ok(numer / denominator)
 }
 
+/// Compute the Spearman rank correlation coefficient between two 1D arrays.
+///
+/// # Description
+///
+/// Computes the Spearman rank correlation coefficient, a measure of the
+/// monotonic (not necessarily linear) correlation between two sets of 1D
+/// data. The values of each array are rank transformed, where tied values are
+/// assigned the average of their ranks, and the Pearson correlation
+/// coefficient of the ranks is computed:
+///
+/// ```text
+/// ρ = pearson(rank(a), rank(b))
+/// ```
+///
+/// With the average ranks of ties, this is the tie corrected Spearman
+/// coefficient. `NaN` values are ranked after all other values as one group
+/// of ties.
+///
+/// # Arguments
+///
+/// * `data_a`: The first array for correlation analysis.
+/// * `data_b`: The second array for correlation analysis.
+/// * `threads`: The requested number of threads to use for parallel execution.
+///   If `None` or `Some(1)` sequential execution is used. If `Some(0)`, then
+///   the maximum available parallelism is used. Thread counts are clamped to
+///   the systems maximum.
+///
+/// # Returns
+///
+/// * `Ok(f64)`: Spearman's rank correlation coefficient ranging between `-1.0`
+///   (perfect negative monotonic correlation), `0.0` (no correlation), and
+///   `1.0` (perfect positive monotonic correlation).
+/// * `Err(ImgalError)`: If `data_a.len() != data_b.len()`. If `data_a.len()` or
+///   `data_b.len()` is <= 2. If all values of an array are tied.
+pub fn spearman_correlation<'a, T, A>(
+    data_a: A,
+    data_b: A,
+    threads: Option<usize>,
+) -> Result<f64, ImgalError>
+where
+    A: AsArray<'a, T, Ix1>,
+    T: 'a + AsNumeric,
+{
+    let data_a: ArrayBase<ViewRepr<&'a T>, Ix1> = data_a.into();
+    let data_b: ArrayBase<ViewRepr<&'a T>, Ix1> = data_b.into();
+    if data_a.len() != data_b.len() {
+        return Err(ImgalError::MismatchedArrayLengths {
+            a_arr_name: "data_a",
+            a_arr_len: data_a.len(),
+            b_arr_name: "data_b",
+            b_arr_len: data_b.len(),
+        });
+    }
+    let vals_a: Vec<f64> = data_a.iter().map(|v| v.to_f64()).collect();
+    let vals_b: Vec<f64> = data_b.iter().map(|v| v.to_f64()).collect();
+    let (ranks_a, ranks_b) = par!(threads,
+        seq_exp: (average_ranks(&vals_a), average_ranks(&vals_b)),
+        par_exp: rayon::join(|| average_ranks(&vals_a), || average_ranks(&vals_b)));
+    pearson(&ranks_a, &ranks_b, threads)
+}
+
 /// Compute the weighted Kendall's Tau-b rank correlation coefficient.
 ///
 /// # Description
@@ -265,3 +326,29 @@ where
     }
     (ranks, tie_corr)
 }
+
+/// Rank values starting at `1.0`, tied values get the average of their ranks.
+/// `NaN` values are ordered after all other values and are tied with each
+/// other, so the ranks do not depend on the input order.
+pub(crate) fn average_ranks(vals: &[f64]) -> Vec<f64> {
+    let tied = |a: f64, b: f64| a == b || (a.is_nan() && b.is_nan());
+    let mut order: Vec<usize> = (0..vals.len()).collect();
+    order.sort_unstable_by(|&a, &b| {
+        let (x, y) = (vals[a], vals[b]);
+        x.partial_cmp(&y)
+            .unwrap_or_else(|| x.is_nan().cmp(&y.is_nan()))
+    });
+    let mut ranks = vec![0.0; vals.len()];
+    let mut i = 0;
+    while i < order.len() {
+        let mut j = i;
+        while j + 1 < order.len() && tied(vals[order[j + 1]], vals[order[i]]) {
+            j += 1;
+        }
+        // the average of the 1-based ranks "i + 1" to "j + 1"
+        let rank = (i + j) as f64 / 2.0 + 1.0;
+        order[i..=j].iter().for_each(|&o| ranks[o] = rank);
+        i = j + 1;
+    }
+    ranks
+}
@@ -0,0 +1,115 @@
+use ndarray::{Array, ArrayBase, ArrayViewMut1, AsArray, Axis, Dimension, ViewRepr, Zip};
+
+use crate::prelude::*;
+use crate::validate::check_axis;
+
+/// Compute the cumulative sum of an n-dimensional image along an axis.
+///
+/// # Description
+///
+/// Computes the running sum of each lane along the given axis:
+///
+/// ```text
+/// yᵢ = Σⱼ₌₀ⁱ xⱼ
+/// ```
+///
+/// Values are accumulated in `f64` so that the sums of integer images can not
+/// overflow. Applying `cumsum` along each axis of a 2D image in turn gives its
+/// integral image (summed-area table).
+///
+/// # Arguments
+///
+/// * `data`: The input n-dimensional image.
+/// * `axis`: The axis to accumulate along. If `None`, then the last axis is
+///   used.
+/// * `threads`: The requested number of threads to use for parallel execution.
+///   If `None` or `Some(1)` sequential execution is used. If `Some(0)`, then
+///   the maximum available parallelism is used. Thread counts are clamped to
+///   the systems maximum.
+///
+/// # Returns
+///
+/// * `Ok(Array<f64, D>)`: The cumulative sum, with the same shape as `data`.
+/// * `Err(ImgalError)`: If `axis` is greater than or equal to the number of
+///   dimensions.
+pub fn cumsum<'a, T, A, D>(
+    data: A,
+    axis: Option<usize>,
+    threads: Option<usize>,
+) -> Result<Array<f64, D>, ImgalError>
+where
+    A: AsArray<'a, T, D>,
+    D: Dimension,
+    T: 'a + AsNumeric,
+{
+    cumulative(data.into(), axis, threads, |acc, v| acc + v)
+}
+
+/// Compute the cumulative product of an n-dimensional image along an axis.
+///
+/// # Description
+///
+/// Computes the running product of each lane along the given axis:
+///
+/// ```text
+/// yᵢ = Πⱼ₌₀ⁱ xⱼ
+/// ```
+///
+/// Values are accumulated in `f64` so that the products of integer images can
+/// not overflow.
+///
+/// # Arguments
+///
+/// * `data`: The input n-dimensional image.
+/// * `axis`: The axis to accumulate along. If `None`, then the last axis is
+///   used.
+/// * `threads`: The requested number of threads to use for parallel execution.
+///   If `None` or `Some(1)` sequential execution is used. If `Some(0)`, then
+///   the maximum available parallelism is used. Thread counts are clamped to
+///   the systems maximum.
+///
+/// # Returns
+///
+/// * `Ok(Array<f64, D>)`: The cumulative product, with the same shape as
+///   `data`.
+/// * `Err(ImgalError)`: If `axis` is greater than or equal to the number of
+///   dimensions.
+pub fn cumprod<'a, T, A, D>(
+    data: A,
+    axis: Option<usize>,
+    threads: Option<usize>,
+) -> Result<Array<f64, D>, ImgalError>
+where
+    A: AsArray<'a, T, D>,
+    D: Dimension,
+    T: 'a + AsNumeric,
+{
+    cumulative(data.into(), axis, threads, |acc, v| acc * v)
+}
+
+/// Accumulate each lane of an n-dimensional image along an axis with a binary
+/// operation.
+fn cumulative<T, D, F>(
+    data: ArrayBase<ViewRepr<&T>, D>,
+    axis: Option<usize>,
+    threads: Option<usize>,
+    op: F,
+) -> Result<Array<f64, D>, ImgalError>
+where
+    D: Dimension,
+    F: Fn(f64, f64) -> f64 + Sync,
+    T: AsNumeric,
+{
+    let axis = axis.unwrap_or(data.ndim().saturating_sub(1));
+    check_axis(axis, data.ndim())?;
+    let mut out = data.mapv(|v| v.to_f64());
+    let acc_lane = |mut ln: ArrayViewMut1<f64>| {
+        for i in 1..ln.len() {
+            ln[i] = op(ln[i - 1], ln[i]);
+        }
+    };
+    par!(threads,
+        seq_exp: Zip::from(out.lanes_mut(Axis(axis))).for_each(acc_lane),
+        par_exp: Zip::from(out.lanes_mut(Axis(axis))).par_for_each(acc_lane));
+    Ok(out)
+}
@@ -0,0 +1,272 @@
+use std::cmp::Ordering;
+
+use ndarray::{ArrayBase, ArrayView, AsArray, Dimension, ViewRepr, Zip};
+use rayon::prelude::*;
+
+use crate::distribution::{normal_cdf, t_cdf};
+use crate::prelude::*;
+use crate::statistics::Welford;
+use crate::validate::check_shapes;
+
+/// The alternative hypothesis of a two-sample test.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum Alternative {
+    /// The distributions of the two samples differ.
+    #[default]
+    TwoSided,
+    /// The first sample is stochastically less than (or has a lower mean than)
+    /// the second sample.
+    Less,
+    /// The first sample is stochastically greater than (or has a greater mean
+    /// than) the second sample.
+    Greater,
+}
+
+/// The result of a two-sample hypothesis test.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TestResult {
+    /// The test statistic.
+    pub statistic: f64,
+    /// The p-value of the test statistic under the null hypothesis.
+    pub p_value: f64,
+}
+
+/// Compute the Mann–Whitney U test of two pixel populations.
+///
+/// # Description
+///
+/// Compares the (masked) pixel values of two n-dimensional images (*e.g.* two
+/// ROIs of the same image, given as masks) with the non-parametric
+/// Mann–Whitney U (Wilcoxon rank-sum) test. The statistic is the U of the
+/// first sample:
+///
+/// ```text
+/// U = Rₐ - nₐ(nₐ + 1) / 2
+/// ```
+///
+/// Where `Rₐ` is the sum of the (average) ranks of the first sample in the
+/// pooled samples. The p-value is computed with the normal approximation,
+/// with a tie correction of the variance and a continuity correction of
+/// `0.5`:
+///
+/// ```text
+/// μ = nₐ × n_b / 2
+/// σ² = nₐ × n_b / 12 × ((n + 1) - Σ(t³ - t) / (n(n - 1)))
+/// ```
+///
+/// Where `t` are the sizes of the groups of tied values. The approximation is
+/// appropriate for the sample sizes of pixel populations, for very small
+/// samples (*e.g.* `n < 20`) an exact test is preferable.
+///
+/// # Arguments
+///
+/// * `data_a`: The first n-dimensional image.
+/// * `data_b`: The second n-dimensional image, can be `data_a` with a
+///   different mask.
+/// * `mask_a`: An optional boolean mask with the same shape as `data_a`
+///   selecting the first sample. If `None`, all pixels are used.
+/// * `mask_b`: An optional boolean mask with the same shape as `data_b`
+///   selecting the second sample. If `None`, all pixels are used.
+/// * `alternative`: The alternative hypothesis. If `None`, then
+///   `alternative = Alternative::TwoSided`.
+/// * `threads`: The requested number of threads to use for parallel execution.
+///   If `None` or `Some(1)` sequential execution is used. If `Some(0)`, then
+///   the maximum available parallelism is used. Thread counts are clamped to
+///   the systems maximum.
+///
+/// # Returns
+///
+/// * `Ok(TestResult)`: The U statistic and p-value. If all values are tied,
+///   then the p-value is `NaN`.
+/// * `Err(ImgalError)`: If a mask shape does not match its image shape. If
+///   either sample is empty.
+///
+/// # Reference
+///
+/// <https://doi.org/10.1214/aoms/1177730491>
+pub fn mann_whitney_u<'a, T, A, D>(
+    data_a: A,
+    data_b: A,
+    mask_a: Option<ArrayView<bool, D>>,
+    mask_b: Option<ArrayView<bool, D>>,
+    alternative: Option<Alternative>,
+    threads: Option<usize>,
+) -> Result<TestResult, ImgalError>
+where
+    A: AsArray<'a, T, D>,
+    D: Dimension,
+    T: 'a + AsNumeric,
+{
+    let a = masked_values(data_a.into(), mask_a, "data_a", "mask_a", 1)?;
+    let b = masked_values(data_b.into(), mask_b, "data_b", "mask_b", 1)?;
+    let (n_a, n_b) = (a.len() as f64, b.len() as f64);
+    let n = n_a + n_b;
+    // pool and sort the samples, flagging the values of the first sample
+    let mut pooled: Vec<(f64, bool)> = a
+        .iter()
+        .map(|&v| (v, true))
+        .chain(b.iter().map(|&v| (v, false)))
+        .collect();
+    let cmp = |x: &(f64, bool), y: &(f64, bool)| x.0.partial_cmp(&y.0).unwrap_or(Ordering::Less);
+    par!(threads,
+        seq_exp: pooled.sort_unstable_by(cmp),
+        par_exp: pooled.par_sort_unstable_by(cmp));
+    // rank sum of the first sample and the tie term
+    let (mut rank_sum_a, mut ties) = (0.0, 0.0);
+    let mut i = 0;
+    while i < pooled.len() {
+        let mut j = i;
+        while j + 1 < pooled.len() && pooled[j + 1].0 == pooled[i].0 {
+            j += 1;
+        }
+        let rank = (i + j) as f64 / 2.0 + 1.0;
+        let t = (j - i + 1) as f64;
+        rank_sum_a += rank * pooled[i..=j].iter().filter(|p| p.1).count() as f64;
+        ties += t * t * t - t;
+        i = j + 1;
+    }
+    let u = rank_sum_a - n_a * (n_a + 1.0) / 2.0;
+    let mu = n_a * n_b / 2.0;
+    let sigma = (n_a * n_b / 12.0 * ((n + 1.0) - ties / (n * (n - 1.0)))).sqrt();
+    let p_value = if sigma > 0.0 && sigma.is_finite() {
+        match alternative.unwrap_or_default() {
+            Alternative::TwoSided => {
+                let z = ((u - mu).abs() - 0.5).max(0.0) / sigma;
+                (2.0 * normal_cdf(-z)).min(1.0)
+            }
+            Alternative::Less => normal_cdf((u - mu + 0.5) / sigma),
+            Alternative::Greater => normal_cdf(-(u - mu - 0.5) / sigma),
+        }
+    } else {
+        f64::NAN
+    };
+    Ok(TestResult {
+        statistic: u,
+        p_value,
+    })
+}
+
+/// Compute Welch's t-test of two pixel populations.
+///
+/// # Description
+///
+/// Compares the means of the (masked) pixel values of two n-dimensional
+/// images (*e.g.* two ROIs of the same image, given as masks) with Welch's
+/// unequal variances t-test:
+///
+/// ```text
+/// t = (mean(a) - mean(b)) / √(s²ₐ / nₐ + s²_b / n_b)
+/// ν = (s²ₐ / nₐ + s²_b / n_b)² / ((s²ₐ / nₐ)² / (nₐ - 1) + (s²_b / n_b)² / (n_b - 1))
+/// ```
+///
+/// Where `s²` are the sample variances and `ν` are the Welch–Satterthwaite
+/// degrees of freedom. The p-value is computed with the cumulative
+/// distribution function of Student's t-distribution (see
+/// `distribution::t_cdf`).
+///
+/// # Arguments
+///
+/// * `data_a`: The first n-dimensional image.
+/// * `data_b`: The second n-dimensional image, can be `data_a` with a
+///   different mask.
+/// * `mask_a`: An optional boolean mask with the same shape as `data_a`
+///   selecting the first sample. If `None`, all pixels are used.
+/// * `mask_b`: An optional boolean mask with the same shape as `data_b`
+///   selecting the second sample. If `None`, all pixels are used.
+/// * `alternative`: The alternative hypothesis. If `None`, then
+///   `alternative = Alternative::TwoSided`.
+/// * `threads`: The requested number of threads to use for parallel execution.
+///   If `None` or `Some(1)` sequential execution is used. If `Some(0)`, then
+///   the maximum available parallelism is used. Thread counts are clamped to
+///   the systems maximum.
+///
+/// # Returns
+///
+/// * `Ok(TestResult)`: The t statistic and p-value. If both samples are
+///   constant, then the statistic and p-value are `NaN`.
+/// * `Err(ImgalError)`: If a mask shape does not match its image shape. If
+///   either sample has less than 2 values.
+///
+/// # Reference
+///
+/// <https://doi.org/10.1093/biomet/34.1-2.28>
+pub fn welch_t_test<'a, T, A, D>(
+    data_a: A,
+    data_b: A,
+    mask_a: Option<ArrayView<bool, D>>,
+    mask_b: Option<ArrayView<bool, D>>,
+    alternative: Option<Alternative>,
+    threads: Option<usize>,
+) -> Result<TestResult, ImgalError>
+where
+    A: AsArray<'a, T, D>,
+    D: Dimension,
+    T: 'a + AsNumeric,
+{
+    let a = masked_values(data_a.into(), mask_a, "data_a", "mask_a", 2)?;
+    let b = masked_values(data_b.into(), mask_b, "data_b", "mask_b", 2)?;
+    let ((mean_a, var_a), (mean_b, var_b)) = par!(threads,
+        seq_exp: (mean_variance(&a), mean_variance(&b)),
+        par_exp: rayon::join(|| mean_variance(&a), || mean_variance(&b)));
+    let (n_a, n_b) = (a.len() as f64, b.len() as f64);
+    let (se_a, se_b) = (var_a / n_a, var_b / n_b);
+    let se = se_a + se_b;
+    if se == 0.0 {
+        return Ok(TestResult {
+            statistic: f64::NAN,
+            p_value: f64::NAN,
+        });
+    }
+    let t = (mean_a - mean_b) / se.sqrt();
+    let df = se * se / (se_a * se_a / (n_a - 1.0) + se_b * se_b / (n_b - 1.0));
+    let p_value = match alternative.unwrap_or_default() {
+        Alternative::TwoSided => 2.0 * t_cdf(-t.abs(), df)?,
+        Alternative::Less => t_cdf(t, df)?,
+        Alternative::Greater => t_cdf(-t, df)?,
+    };
+    Ok(TestResult {
+        statistic: t,
+        p_value,
+    })
+}
+
+/// Collect the (masked) values of an n-dimensional image as `f64`.
+fn masked_values<T, D>(
+    data: ArrayBase<ViewRepr<&T>, D>,
+    mask: Option<ArrayView<bool, D>>,
+    data_name: &'static str,
+    mask_name: &'static str,
+    min_len: usize,
+) -> Result<Vec<f64>, ImgalError>
+where
+    D: Dimension,
+    T: AsNumeric,
+{
+    let vals: Vec<f64> = match mask {
+        Some(m) => {
+            check_shapes(mask_name, m.shape(), data_name, data.shape())?;
+            let mut vals = Vec::new();
+            Zip::from(&data).and(&m).for_each(|v, &keep| {
+                if keep {
+                    vals.push(v.to_f64());
+                }
+            });
+            vals
+        }
+        None => data.iter().map(|v| v.to_f64()).collect(),
+    };
+    if vals.len() < min_len {
+        return Err(ImgalError::InvalidArrayLengthMinimum {
+            arr_name: data_name,
+            arr_len: vals.len(),
+            min_len,
+        });
+    }
+    Ok(vals)
+}
+
+/// Compute the mean and sample variance of a buffer with Welford's algorithm.
+fn mean_variance(vals: &[f64]) -> (f64, f64) {
+    let w = vals.iter().fold(Welford::default(), |acc, &v| acc.push(v));
+    (w.mean, w.variance(1))
+}
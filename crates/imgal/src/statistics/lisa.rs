@@ -0,0 +1,275 @@
+use std::collections::HashMap;
+
+use ndarray::{Array, ArrayBase, ArrayView, AsArray, Dimension, ViewRepr};
+use rayon::prelude::*;
+
+use crate::constants::RNG_SEED;
+use crate::prelude::*;
+use crate::simulation::rng::Pcg;
+use crate::statistics::SpatialWeights;
+use crate::statistics::autocorrelation::{
+    LabelGraph, label_graph, neighbor_index, neighbor_offsets, standard_strides,
+};
+use crate::validate::check_shapes;
+
+/// The local indicators of spatial association (LISA) output of
+/// `local_morans_i`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LisaOutput<D: Dimension> {
+    /// The local Moran's I of each pixel. Pixels outside of the mask are
+    /// `NaN`.
+    pub local_i: Array<f64, D>,
+    /// The pseudo p-value of the conditional permutation test of each pixel.
+    /// Pixels outside of the mask, without neighbors or when no permutations
+    /// are requested are `NaN`.
+    pub p_value: Array<f64, D>,
+}
+
+/// Compute the local Moran's I map of an n-dimensional image.
+///
+/// # Description
+///
+/// Computes the local Moran's I, a local indicator of spatial association
+/// (LISA), of each pixel over the pixel lattice of an n-dimensional image:
+///
+/// ```text
+/// Iᵢ = (zᵢ / m₂) × Σⱼ wᵢⱼ zⱼ
+/// m₂ = Σᵢ zᵢ² / n
+/// ```
+///
+/// Where `zᵢ = xᵢ - mean(x)` and `wᵢⱼ` are the spatial weights between pixels
+/// `i` and `j`. Positive values indicate a pixel within a cluster of similar
+/// intensities (*i.e.* high-high or low-low) and negative values a spatial
+/// outlier (*i.e.* high-low or low-high). The local values sum to the global
+/// Moran's I (see `morans_i`), `I = Σᵢ Iᵢ / W`.
+///
+/// The significance of each pixel is assessed with a conditional permutation
+/// test, where the neighbor values of the pixel are replaced by values drawn
+/// without replacement from the other pixels. The folded pseudo p-value is:
+///
+/// ```text
+/// p = (m + 1) / (n_permutations + 1)
+/// ```
+///
+/// Where `m` is the number of permuted statistics as extreme as, and with the
+/// same sign as, the observed `Iᵢ`. Each pixel draws its own pseudo-random
+/// number generator seed from a generator seeded with `seed`, so the result is
+/// deterministic for a given `seed` independent of the thread count.
+///
+/// # Arguments
+///
+/// * `data`: An n-dimensional image.
+/// * `weights`: The spatial weights of neighboring pixels. If `None`, then
+///   `weights = SpatialWeights::Rook`.
+/// * `mask`: An optional boolean mask with the same shape as `data`. Only the
+///   pixels inside the mask, and their neighbors inside the mask, are used.
+/// * `n_permutations`: The number of conditional permutations per pixel. If
+///   `Some(0)`, the permutation test is skipped. If `None`, then
+///   `n_permutations = 999`.
+/// * `seed`: The seed value for the pseudo-random number generator. If `None`,
+///   then `seed = RNG_SEED`.
+/// * `threads`: The requested number of threads to use for parallel execution.
+///   If `None` or `Some(1)` sequential execution is used. If `Some(0)`, then
+///   the maximum available parallelism is used. Thread counts are clamped to
+///   the systems maximum.
+///
+/// # Returns
+///
+/// * `Ok(LisaOutput<D>)`: The local Moran's I and pseudo p-value maps. If the
+///   (masked) image is constant, then all values are `NaN`.
+/// * `Err(ImgalError)`: If `data` is empty. If the `mask` shape does not match
+///   the `data` shape.
+///
+/// # Reference
+///
+/// <https://doi.org/10.1111/j.1538-4632.1995.tb00338.x>
+pub fn local_morans_i<'a, T, A, D>(
+    data: A,
+    weights: Option<SpatialWeights>,
+    mask: Option<ArrayView<bool, D>>,
+    n_permutations: Option<usize>,
+    seed: Option<u64>,
+    threads: Option<usize>,
+) -> Result<LisaOutput<D>, ImgalError>
+where
+    A: AsArray<'a, T, D>,
+    D: Dimension,
+    T: 'a + AsNumeric,
+{
+    let data: ArrayBase<ViewRepr<&'a T>, D> = data.into();
+    if data.is_empty() {
+        return Err(ImgalError::InvalidParameterEmptyArray { param_name: "data" });
+    }
+    if let Some(msk) = mask.as_ref() {
+        check_shapes("mask", msk.shape(), "data", data.shape())?;
+    }
+    let keep: Vec<bool> = match mask {
+        Some(m) => m.iter().copied().collect(),
+        None => vec![true; data.len()],
+    };
+    // the flat indices of the pixels inside the mask and the reverse map
+    let pixels: Vec<usize> = (0..keep.len()).filter(|&i| keep[i]).collect();
+    let mut compact = vec![usize::MAX; keep.len()];
+    pixels.iter().enumerate().for_each(|(c, &i)| compact[i] = c);
+    let x: Vec<f64> = data.iter().map(|v| v.to_f64()).collect();
+    let vals: Vec<f64> = pixels.iter().map(|&i| x[i]).collect();
+    let shape = data.shape().to_vec();
+    let strides = standard_strides(&shape);
+    // both directions of the forward neighbor offsets
+    let offsets: Vec<(Vec<isize>, f64)> =
+        neighbor_offsets(shape.len(), weights.unwrap_or_default())
+            .into_iter()
+            .flat_map(|(o, w)| [(o.iter().map(|v| -v).collect(), w), (o, w)])
+            .collect();
+    let neighbors = |c: usize| -> Vec<(usize, f64)> {
+        offsets
+            .iter()
+            .filter_map(|(offset, w)| {
+                neighbor_index(pixels[c], offset, &shape, &strides)
+                    .filter(|&j| keep[j])
+                    .map(|j| (compact[j], *w))
+            })
+            .collect()
+    };
+    let lisa = local_statistics(&vals, neighbors, n_permutations, seed, threads);
+    let mut local_i = Array::from_elem(data.raw_dim(), f64::NAN);
+    let mut p_value = Array::from_elem(data.raw_dim(), f64::NAN);
+    local_i
+        .iter_mut()
+        .zip(p_value.iter_mut())
+        .zip(compact.iter())
+        .filter(|(_, c)| **c != usize::MAX)
+        .for_each(|((li, p), &c)| (*li, *p) = lisa[c]);
+    Ok(LisaOutput { local_i, p_value })
+}
+
+/// Compute the local Moran's I of per-object measurements over the adjacency
+/// graph of a label image.
+///
+/// # Description
+///
+/// Computes the local Moran's I and the conditional permutation pseudo p-value
+/// (see `local_morans_i`) of per-object measurements (*e.g.* the mean
+/// intensity of each cell), where the objects of an n-dimensional label image
+/// are the graph nodes and touching objects are connected by an edge with the
+/// given spatial `weights`. The label `0` is the background.
+///
+/// # Arguments
+///
+/// * `labels`: The n-dimensional label image.
+/// * `values`: A map of the measurement of each label. Labels without a value
+///   are ignored.
+/// * `weights`: The spatial weights of adjacent objects. If `None`, then
+///   `weights = SpatialWeights::Rook`.
+/// * `n_permutations`: The number of conditional permutations per object. If
+///   `Some(0)`, the permutation test is skipped. If `None`, then
+///   `n_permutations = 999`.
+/// * `seed`: The seed value for the pseudo-random number generator. If `None`,
+///   then `seed = RNG_SEED`.
+/// * `threads`: The requested number of threads to use for parallel execution.
+///   If `None` or `Some(1)` sequential execution is used. If `Some(0)`, then
+///   the maximum available parallelism is used. Thread counts are clamped to
+///   the systems maximum.
+///
+/// # Returns
+///
+/// * `Ok(HashMap<u64, (f64, f64)>)`: A `HashMap` where the keys are the label
+///   IDs and the values are the local Moran's I and pseudo p-value of each
+///   object respectively.
+/// * `Err(ImgalError)`: If `labels` is empty.
+pub fn local_morans_i_labels<'a, A, D>(
+    labels: A,
+    values: &HashMap<u64, f64>,
+    weights: Option<SpatialWeights>,
+    n_permutations: Option<usize>,
+    seed: Option<u64>,
+    threads: Option<usize>,
+) -> Result<HashMap<u64, (f64, f64)>, ImgalError>
+where
+    A: AsArray<'a, u64, D>,
+    D: Dimension,
+{
+    let LabelGraph {
+        labels: present,
+        edges,
+    } = label_graph(labels.into(), values, weights, threads)?;
+    let mut adjacency: Vec<Vec<(usize, f64)>> = vec![Vec::new(); present.len()];
+    edges.iter().for_each(|&(a, b, w)| {
+        adjacency[a].push((b, w));
+        adjacency[b].push((a, w));
+    });
+    let vals: Vec<f64> = present.iter().map(|l| values[l]).collect();
+    let lisa = local_statistics(
+        &vals,
+        |c| adjacency[c].clone(),
+        n_permutations,
+        seed,
+        threads,
+    );
+    Ok(present.into_iter().zip(lisa).collect())
+}
+
+/// Compute the local Moran's I and conditional permutation pseudo p-value of
+/// each observation, given the weighted neighbors of each observation.
+fn local_statistics<F>(
+    vals: &[f64],
+    neighbors: F,
+    n_permutations: Option<usize>,
+    seed: Option<u64>,
+    threads: Option<usize>,
+) -> Vec<(f64, f64)>
+where
+    F: Fn(usize) -> Vec<(usize, f64)> + Sync,
+{
+    let n = vals.len();
+    let n_permutations = n_permutations.unwrap_or(999);
+    let mean = vals.iter().sum::<f64>() / n as f64;
+    let z: Vec<f64> = vals.iter().map(|v| v - mean).collect();
+    let m2 = z.iter().map(|v| v * v).sum::<f64>() / n as f64;
+    if m2 == 0.0 || n == 0 {
+        return vec![(f64::NAN, f64::NAN); n];
+    }
+    let mut prng = Pcg::new(seed.unwrap_or(RNG_SEED));
+    let seeds: Vec<u64> = (0..n)
+        .map(|_| ((prng.next_u32() as u64) << 32) | prng.next_u32() as u64)
+        .collect();
+    let local_calc = |i: usize| -> (f64, f64) {
+        let nbrs = neighbors(i);
+        let scale = z[i] / m2;
+        let local_i = scale * nbrs.iter().map(|&(j, w)| w * z[j]).sum::<f64>();
+        if nbrs.is_empty() || n_permutations == 0 || nbrs.len() >= n {
+            return (local_i, f64::NAN);
+        }
+        // draw the neighbor values without replacement from the other
+        // observations, skipping the observation itself
+        let mut g = Pcg::new(seeds[i]);
+        let mut drawn: Vec<usize> = Vec::with_capacity(nbrs.len());
+        let mut extreme = 0usize;
+        for _ in 0..n_permutations {
+            drawn.clear();
+            while drawn.len() < nbrs.len() {
+                let mut j = g.next_u32_range(0..(n - 1) as u32).unwrap() as usize;
+                if j >= i {
+                    j += 1;
+                }
+                if !drawn.contains(&j) {
+                    drawn.push(j);
+                }
+            }
+            let perm_i = scale
+                * nbrs
+                    .iter()
+                    .zip(drawn.iter())
+                    .map(|(&(_, w), &j)| w * z[j])
+                    .sum::<f64>();
+            if (local_i >= 0.0 && perm_i >= local_i) || (local_i < 0.0 && perm_i <= local_i) {
+                extreme += 1;
+            }
+        }
+        let p = (extreme + 1) as f64 / (n_permutations + 1) as f64;
+        (local_i, p)
+    };
+    par!(threads,
+        seq_exp: (0..n).map(local_calc).collect(),
+        par_exp: (0..n).into_par_iter().map(local_calc).collect())
+}
@@ -0,0 +1,143 @@
+use ndarray::{ArrayBase, ArrayD, AsArray, Dimension, IxDyn, ViewRepr};
+
+use crate::prelude::*;
+use crate::statistics::reduce_axis;
+
+/// Compute the median of an n-dimensional image.
+///
+/// # Description
+///
+/// Computes the median over the entire array (flattened) or along a specified
+/// axis. The median is found by selection (*i.e.* `O(n)` on average) instead
+/// of a full sort. For an even number of values the median is the mean of the
+/// two middle values.
+///
+/// # Arguments
+///
+/// * `data`: An n-dimensional image.
+/// * `axis`: The axis to compute the median along. If `None`, the input `data`
+///   is flattened and a single median value is returned.
+/// * `threads`: The requested number of threads to use for parallel execution.
+///   If `None` or `Some(1)` sequential execution is used. If `Some(0)`, then
+///   the maximum available parallelism is used. Thread counts are clamped to
+///   the systems maximum.
+///
+/// # Returns
+///
+/// * `Ok(ArrayD<f64>)`: The median of the input data. If `axis` is `None`, the
+///   result shape is `(1,)` and contains the median of the flattened input
+///   `data`. If `axis` is a valid axis value, the result has the same shape as
+///   `data` with `axis` removed and contains the medians along `axis`.
+/// * `Err(ImgalError)`: If `data` is empty. If `axis >= data.ndim()`.
+#[inline]
+pub fn median<'a, T, A, D>(
+    data: A,
+    axis: Option<usize>,
+    threads: Option<usize>,
+) -> Result<ArrayD<f64>, ImgalError>
+where
+    A: AsArray<'a, T, D>,
+    D: Dimension,
+    T: 'a + AsNumeric,
+{
+    let data: ArrayBase<ViewRepr<&'a T>, D> = data.into();
+    reduce_lanes(data.into_dyn(), axis, threads, median_1d)
+}
+
+/// Compute the median absolute deviation (MAD) of an n-dimensional image.
+///
+/// # Description
+///
+/// Computes the median absolute deviation, a robust measure of the spread of
+/// the data, over the entire array (flattened) or along a specified axis:
+///
+/// ```text
+/// MAD = scale × median(|xᵢ - median(x)|)
+/// ```
+///
+/// Use `scale = 1.4826` for a consistent estimator of the standard deviation
+/// of normally distributed data (*e.g.* for robust normalization and outlier
+/// rejection). Both medians are found by selection instead of a full sort.
+///
+/// # Arguments
+///
+/// * `data`: An n-dimensional image.
+/// * `axis`: The axis to compute the MAD along. If `None`, the input `data` is
+///   flattened and a single MAD value is returned.
+/// * `scale`: The scale factor of the MAD. If `None`, then `scale = 1.0`.
+/// * `threads`: The requested number of threads to use for parallel execution.
+///   If `None` or `Some(1)` sequential execution is used. If `Some(0)`, then
+///   the maximum available parallelism is used. Thread counts are clamped to
+///   the systems maximum.
+///
+/// # Returns
+///
+/// * `Ok(ArrayD<f64>)`: The median absolute deviation of the input data, with
+///   the same shape convention as `median`.
+/// * `Err(ImgalError)`: If `data` is empty. If `axis >= data.ndim()`.
+#[inline]
+pub fn mad<'a, T, A, D>(
+    data: A,
+    axis: Option<usize>,
+    scale: Option<f64>,
+    threads: Option<usize>,
+) -> Result<ArrayD<f64>, ImgalError>
+where
+    A: AsArray<'a, T, D>,
+    D: Dimension,
+    T: 'a + AsNumeric,
+{
+    let data: ArrayBase<ViewRepr<&'a T>, D> = data.into();
+    let scale = scale.unwrap_or(1.0);
+    reduce_lanes(data.into_dyn(), axis, threads, |buf| {
+        let med = median_1d(buf);
+        buf.iter_mut().for_each(|v| *v = (*v - med).abs());
+        scale * median_1d(buf)
+    })
+}
+
+/// Reduce the flattened data or each lane along an axis with a function of a
+/// mutable `f64` buffer.
+pub(super) fn reduce_lanes<T, F>(
+    data: ArrayBase<ViewRepr<&T>, IxDyn>,
+    axis: Option<usize>,
+    threads: Option<usize>,
+    reduce: F,
+) -> Result<ArrayD<f64>, ImgalError>
+where
+    T: AsNumeric,
+    F: Fn(&mut [f64]) -> f64 + Sync,
+{
+    if data.is_empty() {
+        return Err(ImgalError::InvalidParameterEmptyArray { param_name: "data" });
+    }
+    match axis {
+        Some(ax) => reduce_axis(
+            &data,
+            ax,
+            |ln| {
+                let mut buf: Vec<f64> = ln.iter().map(|v| v.to_f64()).collect();
+                reduce(&mut buf)
+            },
+            threads,
+        ),
+        None => {
+            let mut buf: Vec<f64> = data.iter().map(|v| v.to_f64()).collect();
+            Ok(ArrayD::from_elem(IxDyn(&[1]), reduce(&mut buf)))
+        }
+    }
+}
+
+/// Find the median of a non-empty buffer by selection, reordering the buffer.
+fn median_1d(buf: &mut [f64]) -> f64 {
+    let n = buf.len();
+    let k = n / 2;
+    let (lower, upper, _) = buf.select_nth_unstable_by(k, |a, b| a.total_cmp(b));
+    let upper = *upper;
+    if n % 2 == 1 {
+        upper
+    } else {
+        let lower = lower.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+        0.5 * (lower + upper)
+    }
+}
@@ -1,18 +1,64 @@
 //! Statistics functions.
 
+mod autocorrelation;
+mod bootstrap;
 mod correlation;
+mod cumulative;
+mod hypothesis;
+mod lisa;
+mod median;
 mod min_max;
+mod moments;
 mod percentile;
+mod reduce;
 mod sample;
 mod sort;
 mod sum;
+mod summary;
+mod tdigest;
+mod trimmed;
 
-pub use correlation::{pearson, weighted_kendall_tau_b};
+pub use autocorrelation::SpatialWeights;
+pub use autocorrelation::gearys_c;
+pub use autocorrelation::gearys_c_labels;
+pub use autocorrelation::morans_i;
+pub use autocorrelation::morans_i_labels;
+pub use bootstrap::bootstrap_ci;
+pub(crate) use correlation::average_ranks;
+pub use correlation::{pearson, spearman_correlation, weighted_kendall_tau_b};
+pub use cumulative::cumprod;
+pub use cumulative::cumsum;
+pub use hypothesis::Alternative;
+pub use hypothesis::TestResult;
+pub use hypothesis::mann_whitney_u;
+pub use hypothesis::welch_t_test;
+pub use lisa::LisaOutput;
+pub use lisa::local_morans_i;
+pub use lisa::local_morans_i_labels;
+pub use median::mad;
+pub use median::median;
 pub use min_max::max;
 pub use min_max::min;
 pub use min_max::min_max;
+pub(crate) use moments::Welford;
+pub use moments::mean;
+pub use moments::std;
+pub use moments::variance;
 pub use percentile::linear_percentile;
+pub use percentile::linear_percentiles;
+pub use reduce::reduce_axis;
 pub use sample::effective_sample_size;
+pub use sample::sample_indices;
+pub use sample::sample_pixels;
+pub use sample::sample_positions;
 pub use sort::weighted_merge_sort_mut;
+pub(crate) use sum::checked_sum;
 pub use sum::kahan_sum;
 pub use sum::sum;
+pub(crate) use sum::sum_f64;
+pub use summary::ChannelSummary;
+pub use summary::channel_summary;
+pub use tdigest::TDigest;
+pub use tdigest::tdigest;
+pub use trimmed::trimmed_mean;
+pub use trimmed::winsorized_mean;
@@ -0,0 +1,222 @@
+use ndarray::{ArrayBase, ArrayD, AsArray, Dimension, IxDyn, ViewRepr, Zip};
+
+use crate::prelude::*;
+use crate::statistics::reduce_axis;
+
+/// Compute the mean of an n-dimensional image.
+///
+/// # Description
+///
+/// Computes the arithmetic mean over the entire array (flattened) or along a
+/// specified axis. The mean is accumulated with Welford's online algorithm
+/// (see `variance`), which avoids the overflow and precision loss of a large
+/// intermediate sum.
+///
+/// # Arguments
+///
+/// * `data`: An n-dimensional image.
+/// * `axis`: The axis to compute the mean along. If `None`, the input `data` is
+///   flattened and a single mean value is returned.
+/// * `threads`: The requested number of threads to use for parallel execution.
+///   If `None` or `Some(1)` sequential execution is used. If `Some(0)`, then
+///   the maximum available parallelism is used. Thread counts are clamped to
+///   the systems maximum.
+///
+/// # Returns
+///
+/// * `Ok(ArrayD<f64>)`: The mean of the input data. If `axis` is `None`, the
+///   result shape is `(1,)` and contains the mean of the flattened input
+///   `data`. If `axis` is a valid axis value, the result has the same shape as
+///   `data` with `axis` removed and contains the means along `axis`.
+/// * `Err(ImgalError)`: If `data` is empty. If `axis >= data.ndim()`.
+#[inline]
+pub fn mean<'a, T, A, D>(
+    data: A,
+    axis: Option<usize>,
+    threads: Option<usize>,
+) -> Result<ArrayD<f64>, ImgalError>
+where
+    A: AsArray<'a, T, D>,
+    D: Dimension,
+    T: 'a + AsNumeric,
+{
+    let data: ArrayBase<ViewRepr<&'a T>, D> = data.into();
+    Ok(welford_moments(data.into_dyn(), axis, threads)?.mapv(|w| w.mean))
+}
+
+/// Compute the variance of an n-dimensional image.
+///
+/// # Description
+///
+/// Computes the variance over the entire array (flattened) or along a specified
+/// axis with Welford's numerically stable online algorithm:
+///
+/// ```text
+/// δ = xₙ - meanₙ₋₁
+/// meanₙ = meanₙ₋₁ + δ / n
+/// M2ₙ = M2ₙ₋₁ + δ × (xₙ - meanₙ)
+/// variance = M2 / (n - ddof)
+/// ```
+///
+/// Partial results of parallel chunks are merged with Chan's pairwise update.
+///
+/// # Arguments
+///
+/// * `data`: An n-dimensional image.
+/// * `axis`: The axis to compute the variance along. If `None`, the input
+///   `data` is flattened and a single variance value is returned.
+/// * `ddof`: The delta degrees of freedom, use `Some(1)` for the unbiased
+///   sample variance. If `None`, then `ddof = 0` (*i.e.* the population
+///   variance).
+/// * `threads`: The requested number of threads to use for parallel execution.
+///   If `None` or `Some(1)` sequential execution is used. If `Some(0)`, then
+///   the maximum available parallelism is used. Thread counts are clamped to
+///   the systems maximum.
+///
+/// # Returns
+///
+/// * `Ok(ArrayD<f64>)`: The variance of the input data, with the same shape
+///   convention as `mean`.
+/// * `Err(ImgalError)`: If `data` is empty. If `axis >= data.ndim()`. If `ddof`
+///   is `>=` the number of reduced elements.
+#[inline]
+pub fn variance<'a, T, A, D>(
+    data: A,
+    axis: Option<usize>,
+    ddof: Option<usize>,
+    threads: Option<usize>,
+) -> Result<ArrayD<f64>, ImgalError>
+where
+    A: AsArray<'a, T, D>,
+    D: Dimension,
+    T: 'a + AsNumeric,
+{
+    let data: ArrayBase<ViewRepr<&'a T>, D> = data.into();
+    let moments = welford_moments(data.into_dyn(), axis, threads)?;
+    let ddof = ddof.unwrap_or(0);
+    let n = moments.first().map_or(0.0, |w| w.n);
+    if ddof as f64 >= n {
+        return Err(ImgalError::InvalidParameterValueGreater {
+            param_name: "ddof",
+            value: n as usize - 1,
+        });
+    }
+    Ok(moments.mapv(|w| w.variance(ddof)))
+}
+
+/// Compute the standard deviation of an n-dimensional image.
+///
+/// # Description
+///
+/// Computes the standard deviation, the square root of the variance (see
+/// `variance`), over the entire array (flattened) or along a specified axis.
+///
+/// # Arguments
+///
+/// * `data`: An n-dimensional image.
+/// * `axis`: The axis to compute the standard deviation along. If `None`, the
+///   input `data` is flattened and a single standard deviation value is
+///   returned.
+/// * `ddof`: The delta degrees of freedom. If `None`, then `ddof = 0`.
+/// * `threads`: The requested number of threads to use for parallel execution.
+///   If `None` or `Some(1)` sequential execution is used. If `Some(0)`, then
+///   the maximum available parallelism is used. Thread counts are clamped to
+///   the systems maximum.
+///
+/// # Returns
+///
+/// * `Ok(ArrayD<f64>)`: The standard deviation of the input data, with the
+///   same shape convention as `mean`.
+/// * `Err(ImgalError)`: If `data` is empty. If `axis >= data.ndim()`. If `ddof`
+///   is `>=` the number of reduced elements.
+#[inline]
+pub fn std<'a, T, A, D>(
+    data: A,
+    axis: Option<usize>,
+    ddof: Option<usize>,
+    threads: Option<usize>,
+) -> Result<ArrayD<f64>, ImgalError>
+where
+    A: AsArray<'a, T, D>,
+    D: Dimension,
+    T: 'a + AsNumeric,
+{
+    Ok(variance(data, axis, ddof, threads)?.mapv(f64::sqrt))
+}
+
+/// The running count, mean and sum of squared deviations of Welford's
+/// algorithm.
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct Welford {
+    pub(crate) n: f64,
+    pub(crate) mean: f64,
+    pub(crate) m2: f64,
+}
+
+impl Welford {
+    /// Add a value to the running moments.
+    pub(crate) fn push(mut self, x: f64) -> Self {
+        self.n += 1.0;
+        let delta = x - self.mean;
+        self.mean += delta / self.n;
+        self.m2 += delta * (x - self.mean);
+        self
+    }
+
+    /// The variance of the running moments with `ddof` delta degrees of
+    /// freedom.
+    pub(crate) fn variance(&self, ddof: usize) -> f64 {
+        self.m2 / (self.n - ddof as f64)
+    }
+
+    /// Merge the running moments of two disjoint partitions.
+    fn merge(self, other: Self) -> Self {
+        if other.n == 0.0 {
+            return self;
+        }
+        if self.n == 0.0 {
+            return other;
+        }
+        let n = self.n + other.n;
+        let delta = other.mean - self.mean;
+        Self {
+            n,
+            mean: self.mean + delta * other.n / n,
+            m2: self.m2 + other.m2 + delta * delta * self.n * other.n / n,
+        }
+    }
+}
+
+/// Compute the Welford moments over the flattened data or along an axis.
+fn welford_moments<T>(
+    data: ArrayBase<ViewRepr<&T>, IxDyn>,
+    axis: Option<usize>,
+    threads: Option<usize>,
+) -> Result<ArrayD<Welford>, ImgalError>
+where
+    T: AsNumeric,
+{
+    if data.is_empty() {
+        return Err(ImgalError::InvalidParameterEmptyArray { param_name: "data" });
+    }
+    match axis {
+        Some(ax) => reduce_axis(
+            &data,
+            ax,
+            |ln| {
+                ln.iter()
+                    .fold(Welford::default(), |acc, v| acc.push(v.to_f64()))
+            },
+            threads,
+        ),
+        None => {
+            let w = par!(threads,
+                seq_exp: data.iter().fold(Welford::default(), |acc, v| acc.push(v.to_f64())),
+                par_exp: Zip::from(&data).par_fold(
+                    Welford::default,
+                    |acc, v| acc.push(v.to_f64()),
+                    Welford::merge));
+            Ok(ArrayD::from_elem(IxDyn(&[1]), w))
+        }
+    }
+}
@@ -1,12 +1,7 @@
-use std::cmp::Ordering;
+use ndarray::{Array, ArrayBase, ArrayD, AsArray, Dimension, IxDyn, ViewRepr};
 
-use ndarray::{
-    Array, ArrayBase, ArrayD, ArrayView1, ArrayViewMut1, AsArray, Axis, Dimension, IxDyn, ViewRepr,
-};
-use rayon::prelude::*;
-
-use crate::copy::copy_into_flat;
 use crate::prelude::*;
+use crate::statistics::reduce_axis;
 
 /// Compute the linear percentile over an n-dimensional image.
 ///
@@ -30,7 +25,8 @@ use crate::prelude::*;
 /// - `⌊h⌋` is the floor function of `h`.
 ///
 /// When `γ` is close to zero (within `epsilon`), the result is simply `v[j]`,
-/// avoiding unnecessary interpolation.
+/// avoiding unnecessary interpolation. `NaN` values are ordered after all
+/// other values.
 ///
 /// # Arguments
 ///
@@ -71,71 +67,156 @@ where
     if data.is_empty() {
         return Err(ImgalError::InvalidParameterEmptyArray { param_name: "data" });
     }
+    let epsilon = epsilon.unwrap_or(1e-12);
     let per_arr = match axis {
         Some(ax) => {
-            if ax >= data.ndim() {
-                return Err(ImgalError::InvalidAxis {
-                    axis_idx: ax,
-                    dim_len: data.ndim(),
-                });
-            }
-            let mut shape = data.shape().to_vec();
-            shape.remove(ax);
-            let mut arr = ArrayD::<f64>::zeros(IxDyn(&shape));
             // compute the percentile for each 1D lane along "axis"
-            let lanes = data.lanes(Axis(ax));
-            let lin_per_calc = |ln: ArrayView1<T>, pr: &mut f64| {
-                let mut ln = Array::from_vec(ln.to_vec());
-                *pr = linear_percentile_1d(ln.view_mut(), percentile, epsilon);
-            };
-            par!(threads,
-                seq_exp: lanes.into_iter().zip(arr.iter_mut())
-                    .for_each(|(ln, pr)| lin_per_calc(ln, pr)),
-                par_exp: lanes.into_iter().zip(arr.iter_mut()).par_bridge()
-                    .for_each(|(ln, pr)| lin_per_calc(ln, pr)));
-            arr
+            reduce_axis(
+                &data,
+                ax,
+                |ln| {
+                    let mut buf: Vec<f64> = ln.iter().map(|v| v.to_f64()).collect();
+                    linear_percentiles_1d(&mut buf, &[percentile], epsilon)[0]
+                },
+                threads,
+            )?
         }
         None => {
-            let mut arr = copy_into_flat(&data, threads);
-            let per = linear_percentile_1d(arr.view_mut(), percentile, epsilon);
-            Array::from_vec(vec![per]).into_dyn()
+            let mut buf: Vec<f64> = data.iter().map(|v| v.to_f64()).collect();
+            Array::from_vec(linear_percentiles_1d(&mut buf, &[percentile], epsilon)).into_dyn()
         }
     };
     Ok(per_arr)
 }
 
-/// 1D linear percentile.
+/// Compute several linear percentiles over an n-dimensional image in a single
+/// pass.
+///
+/// # Description
+///
+/// Calculates each of the `percentiles` with the same linear interpolation as
+/// `linear_percentile`, but from one partial ordering of the data (or of each
+/// lane along `axis`). The interpolation indices are selected in ascending
+/// order, with each selection restricted to the values above the previous one,
+/// so computing *e.g.* the lower and upper percentiles for normalization does
+/// not repeat the selection work. `NaN` values are ordered after all other
+/// values.
 ///
-/// The input data must be contiguous. If it is not then the input data is *not*
-/// 1D and `0.0` is returned. It is up to the caller to ensure the input data is
-/// contiguous
-fn linear_percentile_1d<T>(mut data: ArrayViewMut1<T>, percentile: f64, epsilon: Option<f64>) -> f64
+/// # Arguments
+///
+/// * `data`: An n-dimensional image.
+/// * `percentiles`: The percentile values in the range `0.0` to `100.0`.
+///   Values outside this range will be clamped.
+/// * `axis`: The axis to compute percentiles along. If `None`, the input `data`
+///   is flattened.
+/// * `epsilon`: The tolerance value used to decide the if the fractional index
+///   is an integer. If `None`, then `epsilon = 1e-12`.
+/// * `threads`: The requested number of threads to use for parallel execution.
+///   If `None` or `Some(1)` sequential execution is used. If `Some(0)`, then
+///   the maximum available parallelism is used. Thread counts are clamped to
+///   the systems maximum.
+///
+/// # Returns
+///
+/// * `Ok(ArrayD<f64>)`: The linear percentiles of the input data, in the order
+///   of `percentiles`. If `axis` is `None`, the result shape is `(k,)`, where
+///   `k` is the number of `percentiles`. If `axis` is a valid axis value, the
+///   result has the shape of `data` with `axis` removed and a trailing axis of
+///   length `k`.
+/// * `Err(ImgalError)`: If `data` or `percentiles` is empty. If
+///   `axis >= data.ndim()`.
+pub fn linear_percentiles<'a, T, A, D>(
+    data: A,
+    percentiles: &[f64],
+    axis: Option<usize>,
+    epsilon: Option<f64>,
+    threads: Option<usize>,
+) -> Result<ArrayD<f64>, ImgalError>
 where
-    T: AsNumeric,
+    A: AsArray<'a, T, D>,
+    D: Dimension,
+    T: 'a + AsNumeric,
 {
+    let data: ArrayBase<ViewRepr<&'a T>, D> = data.into();
+    if data.is_empty() {
+        return Err(ImgalError::InvalidParameterEmptyArray { param_name: "data" });
+    }
+    if percentiles.is_empty() {
+        return Err(ImgalError::InvalidParameterEmptyArray {
+            param_name: "percentiles",
+        });
+    }
     let epsilon = epsilon.unwrap_or(1e-12);
-    // compute the percentile value using linear interpolation instead of
-    // sorting the value array, get the "j" element via unstable selection if
-    // "h" is an integer with epsilon value, return the percentile value
-    let p_clamp = percentile.clamp(0.0, 100.0);
-    match data.as_slice_mut() {
-        Some(val_arr) => {
-            let p = p_clamp / 100.0;
-            let h = (val_arr.len() as f64 - 1.0) * p;
+    match axis {
+        Some(ax) => {
+            // compute all percentiles for each 1D lane along "axis", then
+            // unpack the per lane results into a trailing axis
+            let lane_arr = reduce_axis(
+                &data,
+                ax,
+                |ln| {
+                    let mut buf: Vec<f64> = ln.iter().map(|v| v.to_f64()).collect();
+                    linear_percentiles_1d(&mut buf, percentiles, epsilon)
+                },
+                threads,
+            )?;
+            let mut shape = lane_arr.shape().to_vec();
+            shape.push(percentiles.len());
+            let vals: Vec<f64> = lane_arr.iter().flatten().copied().collect();
+            Ok(ArrayD::from_shape_vec(IxDyn(&shape), vals)
+                .expect("Lane percentiles do not match the output shape."))
+        }
+        None => {
+            let mut buf: Vec<f64> = data.iter().map(|v| v.to_f64()).collect();
+            let vals = linear_percentiles_1d(&mut buf, percentiles, epsilon);
+            Ok(Array::from_vec(vals).into_dyn())
+        }
+    }
+}
+
+/// 1D multi-percentile linear interpolation, reordering the buffer.
+fn linear_percentiles_1d(buf: &mut [f64], percentiles: &[f64], epsilon: f64) -> Vec<f64> {
+    // collect the (j, γ) interpolation pairs and every rank that is needed
+    let n = buf.len();
+    let pairs: Vec<(usize, f64)> = percentiles
+        .iter()
+        .map(|p| {
+            let h = (n as f64 - 1.0) * p.clamp(0.0, 100.0) / 100.0;
             let j = h.floor() as usize;
             let gamma = h - j as f64;
-            if gamma.abs() < epsilon {
-                val_arr
-                    .select_nth_unstable_by(j, |a, b| a.partial_cmp(b).unwrap_or(Ordering::Less));
-                return val_arr[j].to_f64();
+            if gamma.abs() < epsilon || j + 1 >= n {
+                (j, 0.0)
+            } else {
+                (j, gamma)
             }
-            val_arr.select_nth_unstable_by(j, |a, b| a.partial_cmp(b).unwrap_or(Ordering::Less));
-            let v_j = val_arr[j].to_f64();
-            val_arr
-                .select_nth_unstable_by(j + 1, |a, b| a.partial_cmp(b).unwrap_or(Ordering::Less));
-            let v_j1 = val_arr[j + 1].to_f64();
-            (1.0 - gamma) * v_j + gamma * v_j1
-        }
-        None => 0.0,
+        })
+        .collect();
+    let mut ranks: Vec<usize> = pairs
+        .iter()
+        .flat_map(|&(j, gamma)| {
+            if gamma == 0.0 {
+                vec![j]
+            } else {
+                vec![j, j + 1]
+            }
+        })
+        .collect();
+    ranks.sort_unstable();
+    ranks.dedup();
+    // select the ranks in ascending order, each within the unselected tail
+    let mut start = 0;
+    for &r in ranks.iter() {
+        buf[start..].select_nth_unstable_by(r - start, |a, b| a.total_cmp(b));
+        start = r + 1;
     }
+    pairs
+        .iter()
+        .map(|&(j, gamma)| {
+            if gamma == 0.0 {
+                buf[j]
+            } else {
+                (1.0 - gamma) * buf[j] + gamma * buf[j + 1]
+            }
+        })
+        .collect()
 }
@@ -0,0 +1,70 @@
+use ndarray::{ArrayBase, ArrayD, ArrayView1, AsArray, Axis, Dimension, IxDyn, ViewRepr, Zip};
+
+use crate::prelude::*;
+use crate::validate::check_axis;
+
+/// Reduce the lanes along an axis of an n-dimensional image.
+///
+/// # Description
+///
+/// Applies a reducer (*e.g.* a sum, mean, maximum, percentile or any custom
+/// closure) to each 1D lane along `axis`, producing an output with `axis`
+/// removed. The lanes are reduced in parallel, so that axis-aware statistics
+/// only need to implement the 1D reduction.
+///
+/// # Arguments
+///
+/// * `data`: An n-dimensional image.
+/// * `axis`: The axis to reduce along.
+/// * `reducer`: The function that reduces a 1D lane to a single value.
+/// * `threads`: The requested number of threads to use for parallel execution.
+///   If `None` or `Some(1)` sequential execution is used. If `Some(0)`, then
+///   the maximum available parallelism is used. Thread counts are clamped to
+///   the systems maximum.
+///
+/// # Returns
+///
+/// * `Ok(ArrayD<R>)`: The reduced image, with the same shape as `data` with
+///   `axis` removed.
+/// * `Err(ImgalError)`: If `data` is empty. If `axis >= data.ndim()`.
+///
+/// # Example
+///
+/// ```
+/// use ndarray::arr2;
+///
+/// use imgal::statistics::reduce_axis;
+///
+/// let arr = arr2(&[[1, 5, 3], [4, 2, 6]]);
+/// let max = reduce_axis(&arr, 1, |ln| *ln.iter().max().unwrap(), None).unwrap();
+/// assert_eq!(max.as_slice().unwrap(), &[5, 6]);
+/// ```
+#[inline]
+pub fn reduce_axis<'a, T, A, D, R, F>(
+    data: A,
+    axis: usize,
+    reducer: F,
+    threads: Option<usize>,
+) -> Result<ArrayD<R>, ImgalError>
+where
+    A: AsArray<'a, T, D>,
+    D: Dimension,
+    T: 'a + AsNumeric,
+    R: Clone + Default + Send,
+    F: Fn(ArrayView1<T>) -> R + Sync,
+{
+    let data: ArrayBase<ViewRepr<&'a T>, D> = data.into();
+    if data.is_empty() {
+        return Err(ImgalError::InvalidParameterEmptyArray { param_name: "data" });
+    }
+    check_axis(axis, data.ndim())?;
+    let data = data.into_dyn();
+    let mut shape = data.shape().to_vec();
+    shape.remove(axis);
+    let mut arr = ArrayD::<R>::default(IxDyn(&shape));
+    let lane_reduce = |r: &mut R, ln: ArrayView1<T>| *r = reducer(ln);
+    par!(threads,
+        seq_exp: Zip::from(&mut arr).and(data.lanes(Axis(axis))).for_each(lane_reduce),
+        par_exp: Zip::from(&mut arr).and(data.lanes(Axis(axis))).par_for_each(lane_reduce));
+    Ok(arr)
+}
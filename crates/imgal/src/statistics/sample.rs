@@ -1,6 +1,12 @@
-use ndarray::{ArrayBase, AsArray, Dimension, ViewRepr};
+use std::collections::HashSet;
 
+use ndarray::{Array1, Array2, ArrayBase, ArrayView, AsArray, Dimension, ViewRepr};
+
+use crate::constants::RNG_SEED;
 use crate::prelude::*;
+use crate::simulation::rng::Pcg;
+use crate::statistics::autocorrelation::standard_strides;
+use crate::validate::check_shapes;
 
 /// Compute the effective sample size (ESS) of a weighted sample set.
 ///
@@ -42,3 +48,166 @@ where
         (sum_w * sum_w) / sum_sqr_w
     }
 }
+
+/// Sample `k` distinct indices without replacement.
+///
+/// # Description
+///
+/// Samples `k` distinct indices uniformly without replacement from the range
+/// `0..n` with Floyd's algorithm, which uses `k` random draws independent of
+/// `n`. The sample is deterministic for a given `seed`.
+///
+/// # Arguments
+///
+/// * `n`: The size of the population, the indices are sampled from `0..n`.
+/// * `k`: The number of indices to sample.
+/// * `seed`: The seed value for the pseudo-random number generator. If `None`,
+///   then `seed = RNG_SEED`.
+///
+/// # Returns
+///
+/// * `Ok(Vec<usize>)`: The sampled indices in ascending order.
+/// * `Err(ImgalError)`: If `k > n`.
+///
+/// # Reference
+///
+/// <https://doi.org/10.1145/30401.315746>
+pub fn sample_indices(n: usize, k: usize, seed: Option<u64>) -> Result<Vec<usize>, ImgalError> {
+    if k > n {
+        return Err(ImgalError::InvalidParameterValueGreater {
+            param_name: "k",
+            value: n,
+        });
+    }
+    let mut prng = Pcg::new(seed.unwrap_or(RNG_SEED));
+    let mut chosen: HashSet<usize> = HashSet::with_capacity(k);
+    for j in (n - k)..n {
+        let t = next_index(&mut prng, j + 1);
+        if !chosen.insert(t) {
+            chosen.insert(j);
+        }
+    }
+    let mut indices: Vec<usize> = chosen.into_iter().collect();
+    indices.sort_unstable();
+    Ok(indices)
+}
+
+/// Sample `k` pixel positions without replacement from a boolean mask.
+///
+/// # Description
+///
+/// Samples `k` distinct `true` pixel positions of an n-dimensional boolean
+/// mask uniformly without replacement (see `sample_indices`). The positions
+/// are returned as a point cloud, compatible with the ROI point clouds of
+/// `spatial::roi`.
+///
+/// # Arguments
+///
+/// * `mask`: The n-dimensional boolean mask to sample `true` pixels from.
+/// * `k`: The number of pixel positions to sample.
+/// * `seed`: The seed value for the pseudo-random number generator. If `None`,
+///   then `seed = RNG_SEED`.
+///
+/// # Returns
+///
+/// * `Ok(Array2<usize>)`: The sampled pixel positions with shape `(k, D)`, in
+///   row-major (*i.e.* C) order.
+/// * `Err(ImgalError)`: If `k` is greater than the number of `true` pixels.
+pub fn sample_positions<'a, A, D>(
+    mask: A,
+    k: usize,
+    seed: Option<u64>,
+) -> Result<Array2<usize>, ImgalError>
+where
+    A: AsArray<'a, bool, D>,
+    D: Dimension,
+{
+    let mask: ArrayBase<ViewRepr<&'a bool>, D> = mask.into();
+    let positions: Vec<usize> = mask
+        .iter()
+        .enumerate()
+        .filter(|(_, keep)| **keep)
+        .map(|(i, _)| i)
+        .collect();
+    let indices = sample_indices(positions.len(), k, seed)?;
+    let shape = mask.shape();
+    let strides = standard_strides(shape);
+    let mut cloud = Array2::<usize>::zeros((k, mask.ndim()));
+    cloud
+        .rows_mut()
+        .into_iter()
+        .zip(indices.iter())
+        .for_each(|(mut row, &i)| {
+            row.iter_mut()
+                .zip(shape.iter().zip(strides.iter()))
+                .for_each(|(r, (&len, &stride))| *r = positions[i] / stride % len);
+        });
+    Ok(cloud)
+}
+
+/// Sample `k` pixel values without replacement from an n-dimensional image.
+///
+/// # Description
+///
+/// Samples `k` distinct pixels of an n-dimensional image uniformly without
+/// replacement (see `sample_indices`), optionally only from the pixels inside
+/// a mask. Subsampling reduces the cost of statistics that scale poorly with
+/// the number of pixels (*e.g.* rank correlations, bootstrap and Monte Carlo
+/// routines).
+///
+/// # Arguments
+///
+/// * `data`: The n-dimensional image to sample pixels from.
+/// * `k`: The number of pixels to sample.
+/// * `mask`: An optional boolean mask with the same shape as `data`. If
+///   `None`, all pixels are sampled from.
+/// * `seed`: The seed value for the pseudo-random number generator. If `None`,
+///   then `seed = RNG_SEED`.
+///
+/// # Returns
+///
+/// * `Ok(Array1<T>)`: The sampled pixel values in row-major (*i.e.* C) order.
+/// * `Err(ImgalError)`: If the `mask` shape does not match the `data` shape. If
+///   `k` is greater than the number of (masked) pixels.
+pub fn sample_pixels<'a, T, A, D>(
+    data: A,
+    k: usize,
+    mask: Option<ArrayView<bool, D>>,
+    seed: Option<u64>,
+) -> Result<Array1<T>, ImgalError>
+where
+    A: AsArray<'a, T, D>,
+    D: Dimension,
+    T: 'a + AsNumeric,
+{
+    let data: ArrayBase<ViewRepr<&'a T>, D> = data.into();
+    let vals: Vec<T> = match mask {
+        Some(m) => {
+            check_shapes("mask", m.shape(), "data", data.shape())?;
+            data.iter()
+                .zip(m.iter())
+                .filter(|(_, keep)| **keep)
+                .map(|(v, _)| *v)
+                .collect()
+        }
+        None => data.iter().copied().collect(),
+    };
+    let indices = sample_indices(vals.len(), k, seed)?;
+    Ok(indices.iter().map(|&i| vals[i]).collect())
+}
+
+/// Draw a uniform index from `0..n` with rejection sampling, combining two
+/// `u32` draws for populations larger than `u32::MAX`.
+fn next_index(prng: &mut Pcg, n: usize) -> usize {
+    if let Ok(n_32) = u32::try_from(n) {
+        return prng.next_u32_range(0..n_32).unwrap() as usize;
+    }
+    let n = n as u64;
+    let threshold = n.wrapping_neg() % n;
+    loop {
+        let v = ((prng.next_u32() as u64) << 32) | prng.next_u32() as u64;
+        if v >= threshold {
+            return (v % n) as usize;
+        }
+    }
+}
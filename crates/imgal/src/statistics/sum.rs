@@ -1,8 +1,9 @@
-use ndarray::{ArrayBase, AsArray, Dimension, ViewRepr, Zip};
+use ndarray::{ArrayBase, ArrayView, ArrayView1, AsArray, Dimension, ViewRepr, Zip};
 use rayon::prelude::*;
 
 use crate::prelude::*;
 use crate::simd_hint::fast_fold;
+use crate::traits::numeric::AsAccumulator;
 
 /// Compute the sum of an n-dimensional image using Kahan compensated summation.
 ///
@@ -10,20 +11,39 @@ use crate::simd_hint::fast_fold;
 ///
 /// Computes the Kahan sum of an n-dimensional image. The Kahan compensated
 /// summation algorithm corrects for floating-point rounding errors and
-/// precision loss at each step of the summation. To compensate for
-/// floating-point error an error residual is subtracted from each value per
-/// iteration.
+/// precision loss at each step of the summation. This function uses the
+/// Neumaier variant, which also compensates values larger than the running
+/// sum:
+///
+/// ```text
+/// t = s + v
+/// c = c + (s - t) + v    if |s| ≥ |v|
+/// c = c + (v - t) + s    otherwise
+/// s = t
+/// ```
+///
+/// Where `s` is the running sum and `c` the running compensation, the result
+/// is `s + c`. With parallel execution each thread accumulates its own
+/// compensated sum and the partial sums and compensations are merged with the
+/// same compensated addition, so the accuracy is retained across threads.
+/// Floating point values are accumulated in `f64` precision and integer values
+/// are summed exactly in 128-bit precision (see `sum`).
 ///
 /// # Arguments
 ///
 /// * `data`: The input n-dimensional image.
+/// * `threads`: The requested number of threads to use for parallel execution.
+///   If `None` or `Some(1)` sequential execution is used. If `Some(0)`, then
+///   the maximum available parallelism is used. Thread counts are clamped to
+///   the systems maximum.
 ///
 /// # Returns
 ///
-/// * `Ok(T)`: The Kahan sum.
+/// * `Ok(T::Acc)`: The Kahan sum, as an `i128` for integer types and an `f64`
+///   for floating point types.
 /// * `Err(ImgalError)`: If `data.is_empty() == true`.
 #[inline]
-pub fn kahan_sum<'a, T, A, D>(data: A) -> Result<T, ImgalError>
+pub fn kahan_sum<'a, T, A, D>(data: A, threads: Option<usize>) -> Result<T::Acc, ImgalError>
 where
     A: AsArray<'a, T, D>,
     D: Dimension,
@@ -33,22 +53,31 @@ where
     if data.is_empty() {
         return Err(ImgalError::InvalidParameterEmptyArray { param_name: "data" });
     }
-    Ok(data
-        .iter()
-        .fold((T::default(), T::default()), |acc, &v| {
-            let adj = v - acc.1;
-            let new_sum = acc.0 + adj;
-            let comp = (new_sum - acc.0) - adj;
-            (new_sum, comp)
-        })
-        .0)
+    if let Some(total) = integer_sum(&data, threads) {
+        return Ok(T::Acc::from_i128(total));
+    }
+    let zero = || (0.0, 0.0);
+    let add = |acc: (f64, f64), v: &T| neumaier_add(acc, v.to_f64());
+    let (s, c) = par!(threads,
+        seq_exp: data.iter().fold(zero(), add),
+        par_exp: Zip::from(&data).par_fold(zero, add, neumaier_merge));
+    Ok(T::Acc::from_f64(s + c))
 }
 
 /// Compute the sum of an n-dimensional image.
 ///
 /// # Description
 ///
-/// Computes the sum of numerical values in an n-dimensional image.
+/// Computes the sum of numerical values in an n-dimensional image. The sum is
+/// accumulated in the widened accumulator type `T::Acc`, so that it can not
+/// overflow or saturate the range of the image type. Integer values are summed
+/// exactly in 128-bit precision, so that large 64-bit integer images (*e.g.*
+/// `u64` label or intensity volumes) can not silently wrap. Floating point
+/// values (including the half-precision `f16` and `bf16` types) are summed in
+/// `f64` precision with autovectorized partial sums, with parallel execution
+/// the per-thread partial sums are combined with Neumaier compensated
+/// summation (see `kahan_sum`), so that the rounding error does not grow with
+/// the number of partial sums.
 ///
 /// # Arguments
 ///
@@ -60,7 +89,8 @@ where
 ///
 /// # Returns
 ///
-/// * `T`: The sum.
+/// * `T::Acc`: The sum, as an `i128` for integer types and an `f64` for
+///   floating point types.
 ///
 /// # Example
 ///
@@ -72,19 +102,200 @@ where
 /// let arr = [1.82, 3.35, 7.13, 9.25];
 /// let total = sum(&arr, None);
 /// assert_eq!(total, 21.55);
+///
+/// let arr = Array1::<u8>::from_elem(1000, 255);
+/// assert_eq!(sum(&arr, None), 255_000);
 /// ```
 #[inline]
-pub fn sum<'a, T, A, D>(data: A, threads: Option<usize>) -> T
+pub fn sum<'a, T, A, D>(data: A, threads: Option<usize>) -> T::Acc
+where
+    A: AsArray<'a, T, D>,
+    D: Dimension,
+    T: 'a + AsNumeric,
+{
+    let data: ArrayBase<ViewRepr<&'a T>, D> = data.into();
+    match integer_sum(&data, threads) {
+        Some(total) => T::Acc::from_i128(total),
+        None => T::Acc::from_f64(float_sum(data, threads)),
+    }
+}
+
+/// Compute the sum of an n-dimensional image as an `f64`, accumulating
+/// integer values in 128-bit precision so that the sum can not overflow.
+#[inline]
+pub(crate) fn sum_f64<'a, T, A, D>(data: A, threads: Option<usize>) -> f64
 where
     A: AsArray<'a, T, D>,
     D: Dimension,
     T: 'a + AsNumeric,
 {
     let data: ArrayBase<ViewRepr<&'a T>, D> = data.into();
+    match integer_sum(&data, threads) {
+        Some(total) => total as f64,
+        None => float_sum(data, threads),
+    }
+}
+
+/// Compute the sum of an n-dimensional image as `T`, checking that the sum of
+/// integer values fits in the range of `T`.
+#[inline]
+pub(crate) fn checked_sum<'a, T, A, D>(data: A, threads: Option<usize>) -> Result<T, ImgalError>
+where
+    A: AsArray<'a, T, D>,
+    D: Dimension,
+    T: 'a + AsNumeric,
+{
+    let data: ArrayBase<ViewRepr<&'a T>, D> = data.into();
+    match integer_sum(&data, threads) {
+        Some(total) => T::from_i128(total).ok_or(ImgalError::InvalidNumericOverflow {
+            op: "sum",
+            type_name: std::any::type_name::<T>(),
+        }),
+        None => Ok(T::from_f64(float_sum(data, threads))),
+    }
+}
+
+/// Sum floating point values in `f64` precision with autovectorization hints,
+/// combining the parallel partial sums with compensated summation.
+#[inline]
+fn float_sum<T, D>(data: ArrayBase<ViewRepr<&T>, D>, threads: Option<usize>) -> f64
+where
+    D: Dimension,
+    T: AsNumeric,
+{
+    let zero = || (0.0, 0.0);
     par!(threads,
-        seq_exp: fast_fold(data, T::default, T::add),
+    seq_exp: widened_fold(data),
+    par_exp: {
+        let (s, c) = Zip::from(data.rows())
+            .into_par_iter()
+            .fold(zero, |acc, (r,)| neumaier_add(acc, widened_fold(r)))
+            .reduce(zero, neumaier_merge);
+        s + c
+    })
+}
+
+/// Sum floating point values in `f64` precision. `f64` values are folded with
+/// autovectorization hints, narrower types (`f32`, `f16` and `bf16`) are
+/// widened to `f64` on the fly so that the sum does not saturate in the
+/// precision of `T`.
+#[inline]
+fn widened_fold<T, D>(data: ArrayView<T, D>) -> f64
+where
+    D: Dimension,
+    T: AsNumeric,
+{
+    if size_of::<T>() == size_of::<f64>() {
+        return fast_fold(data, T::default, T::add).to_f64();
+    }
+    let row_sum = |r: ArrayView1<T>| match r.as_slice_memory_order() {
+        Some(s) => widened_slice_sum(s),
+        None => r.iter().map(|v| v.to_f64()).sum(),
+    };
+    match data.as_slice_memory_order() {
+        Some(s) => widened_slice_sum(s),
+        None => data.rows().into_iter().map(row_sum).sum(),
+    }
+}
+
+/// Sum a slice of floating point values in `f64` precision using eight
+/// independent accumulation chains, so that the widening loop autovectorizes.
+#[inline]
+fn widened_slice_sum<T>(data: &[T]) -> f64
+where
+    T: AsNumeric,
+{
+    let mut acc = [0.0; 8];
+    let chunks = data.chunks_exact(8);
+    let rem: f64 = chunks.remainder().iter().map(|v| v.to_f64()).sum();
+    for c in chunks {
+        acc.iter_mut().zip(c).for_each(|(a, v)| *a += v.to_f64());
+    }
+    acc.iter().sum::<f64>() + rem
+}
+
+/// Add a value to a `(sum, compensation)` pair with Neumaier compensated
+/// summation.
+#[inline]
+fn neumaier_add(acc: (f64, f64), v: f64) -> (f64, f64) {
+    let (s, c) = acc;
+    let t = s + v;
+    if s.abs() >= v.abs() {
+        (t, c + ((s - t) + v))
+    } else {
+        (t, c + ((v - t) + s))
+    }
+}
+
+/// Merge two `(sum, compensation)` pairs with Neumaier compensated summation.
+#[inline]
+fn neumaier_merge(a: (f64, f64), b: (f64, f64)) -> (f64, f64) {
+    let (s, c) = neumaier_add(a, b.0);
+    (s, c + b.1)
+}
+
+/// Sum integer values in 128-bit precision. Returns `None` for floating point
+/// types.
+///
+/// Contiguous rows are summed as slices so that the loops autovectorize.
+/// Values of at most 16 (32) bits are accumulated in native `i32` (`i64`)
+/// chunks that can not overflow, and only the chunk totals are added in
+/// 128-bit precision.
+#[inline]
+fn integer_sum<T, D>(data: &ArrayBase<ViewRepr<&T>, D>, threads: Option<usize>) -> Option<i128>
+where
+    D: Dimension,
+    T: AsNumeric,
+{
+    T::default().to_i128()?;
+    let row_sum = |r: ArrayView1<T>| match r.as_slice_memory_order() {
+        Some(s) => integer_slice_sum(s),
+        None => r.iter().map(|v| v.to_i128().unwrap_or(0)).sum(),
+    };
+    Some(par!(threads,
+        seq_exp: match data.as_slice_memory_order() {
+            Some(s) => integer_slice_sum(s),
+            None => data.rows().into_iter().map(row_sum).sum(),
+        },
         par_exp: Zip::from(data.rows())
             .into_par_iter()
-            .fold(T::default, |acc, (r,)| acc + fast_fold(r, T::default, T::add))
-            .reduce(T::default, T::add))
+            .map(|(r,)| row_sum(r))
+            .sum()))
+}
+
+/// The number of values summed in one `i32` chunk by `integer_slice_sum`. The
+/// magnitude of a value of at most 16 bits is below `2¹⁶`, so the sum of a
+/// chunk stays below `2³¹`.
+const I32_CHUNK_LEN: usize = 1 << 15;
+
+/// The number of values summed in one `i64` chunk by `integer_slice_sum`. The
+/// magnitude of a value of at most 32 bits is below `2³²`, so the sum of a
+/// chunk stays below `2⁶³`.
+const I64_CHUNK_LEN: usize = 1 << 31;
+
+/// Sum a slice of integer values in 128-bit precision.
+#[inline]
+fn integer_slice_sum<T>(data: &[T]) -> i128
+where
+    T: AsNumeric,
+{
+    match size_of::<T>() {
+        1 | 2 => data
+            .chunks(I32_CHUNK_LEN)
+            .map(|c| {
+                c.iter()
+                    .map(|v| v.to_i128().unwrap_or(0) as i32)
+                    .sum::<i32>() as i128
+            })
+            .sum(),
+        4 => data
+            .chunks(I64_CHUNK_LEN)
+            .map(|c| {
+                c.iter()
+                    .map(|v| v.to_i128().unwrap_or(0) as i64)
+                    .sum::<i64>() as i128
+            })
+            .sum(),
+        _ => data.iter().map(|v| v.to_i128().unwrap_or(0)).sum(),
+    }
 }
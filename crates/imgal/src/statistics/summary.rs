@@ -0,0 +1,153 @@
+use ndarray::{Array1, Array2, ArrayBase, ArrayView1, AsArray, Axis, Dimension, ViewRepr, Zip};
+
+use crate::prelude::*;
+use crate::statistics::linear_percentiles;
+use crate::validate::check_axis;
+
+/// The per-channel and cross-channel summary statistics of a multi-channel
+/// image.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChannelSummary {
+    /// The mean of each channel with shape `(ch,)`.
+    pub mean: Array1<f64>,
+    /// The population standard deviation of each channel with shape `(ch,)`.
+    pub std: Array1<f64>,
+    /// The linear percentiles of each channel with shape `(ch, p)`, where
+    /// column `j` is the `j`-th requested percentile.
+    pub percentiles: Array2<f64>,
+    /// The pairwise Pearson correlation coefficients between the channels with
+    /// shape `(ch, ch)`. Correlations with a constant channel are `NaN`.
+    pub pearson: Array2<f64>,
+}
+
+/// Compute the per-channel and cross-channel summary statistics of a
+/// multi-channel n-dimensional image.
+///
+/// # Description
+///
+/// Computes the mean, the population standard deviation and percentiles of
+/// each channel together with the Pearson correlation coefficients of all
+/// channel pairs, a routine quality control step before deeper analysis. The
+/// sums and cross products of all channels are accumulated in a single
+/// (parallel) pass over the pixels:
+///
+/// ```text
+/// cov(k, l) = (Σ(xₖ × xₗ) - Σxₖ × Σxₗ / n) / n
+/// r(k, l) = cov(k, l) / √[cov(k, k) × cov(l, l)]
+/// ```
+///
+/// Where `n` is the number of pixels per channel. Each channel is shifted by
+/// its first value before accumulation to reduce cancellation errors. The
+/// percentiles are linearly interpolated with `linear_percentiles`.
+///
+/// # Arguments
+///
+/// * `data`: The n-dimensional multi-channel image.
+/// * `percentiles`: The percentiles to compute in the range `0.0` to `100.0`.
+///   If `None`, then `percentiles = [1.0, 50.0, 99.0]`.
+/// * `axis`: The channel axis. If `None`, then `axis = 0`.
+/// * `threads`: The requested number of threads to use for parallel execution.
+///   If `None` or `Some(1)` sequential execution is used. If `Some(0)`, then
+///   the maximum available parallelism is used. Thread counts are clamped to
+///   the systems maximum.
+///
+/// # Returns
+///
+/// * `Ok(ChannelSummary)`: The per-channel and cross-channel summary
+///   statistics.
+/// * `Err(ImgalError)`: If `data` is empty. If `axis >= data.ndim()`. If a
+///   percentile is outside the range `0.0` to `100.0`.
+pub fn channel_summary<'a, T, A, D>(
+    data: A,
+    percentiles: Option<&[f64]>,
+    axis: Option<usize>,
+    threads: Option<usize>,
+) -> Result<ChannelSummary, ImgalError>
+where
+    A: AsArray<'a, T, D>,
+    D: Dimension,
+    T: 'a + AsNumeric,
+{
+    let data: ArrayBase<ViewRepr<&'a T>, D> = data.into();
+    if data.is_empty() {
+        return Err(ImgalError::InvalidParameterEmptyArray { param_name: "data" });
+    }
+    let axis = axis.unwrap_or(0);
+    check_axis(axis, data.ndim())?;
+    let percentiles = percentiles.unwrap_or(&[1.0, 50.0, 99.0]);
+    if let Some(&p) = percentiles.iter().find(|p| !(0.0..=100.0).contains(*p)) {
+        return Err(ImgalError::InvalidParameterValueOutsideRange {
+            param_name: "percentiles",
+            value: p,
+            min: 0.0,
+            max: 100.0,
+        });
+    }
+    // each lane along "axis" holds the channel values of one pixel,
+    // accumulate the shifted sums and cross products of all channels
+    let n_ch = data.len_of(Axis(axis));
+    let n = data.len() / n_ch;
+    let shift: Vec<f64> = data
+        .lanes(Axis(axis))
+        .into_iter()
+        .next()
+        .expect("The data has at least one lane.")
+        .iter()
+        .map(|v| v.to_f64())
+        .collect();
+    let zero = || (vec![0.0; n_ch], vec![0.0; n_ch * n_ch]);
+    let accumulate = |(mut sums, mut cross): (Vec<f64>, Vec<f64>), ln: ArrayView1<T>| {
+        for k in 0..n_ch {
+            let x_k = ln[k].to_f64() - shift[k];
+            sums[k] += x_k;
+            for l in k..n_ch {
+                cross[k * n_ch + l] += x_k * (ln[l].to_f64() - shift[l]);
+            }
+        }
+        (sums, cross)
+    };
+    let merge = |a: (Vec<f64>, Vec<f64>), b: (Vec<f64>, Vec<f64>)| {
+        (
+            a.0.iter().zip(b.0.iter()).map(|(x, y)| x + y).collect(),
+            a.1.iter().zip(b.1.iter()).map(|(x, y)| x + y).collect(),
+        )
+    };
+    let (sums, cross) = par!(threads,
+        seq_exp: Zip::from(data.lanes(Axis(axis))).fold(zero(), accumulate),
+        par_exp: Zip::from(data.lanes(Axis(axis))).par_fold(zero, accumulate, merge));
+    let n_f = n as f64;
+    let cov = |k: usize, l: usize| {
+        let (k, l) = (k.min(l), k.max(l));
+        (cross[k * n_ch + l] - sums[k] * sums[l] / n_f) / n_f
+    };
+    let mean = Array1::from_shape_fn(n_ch, |k| shift[k] + sums[k] / n_f);
+    // clamp rounding below zero without dropping a NaN variance
+    let std = Array1::from_shape_fn(n_ch, |k| {
+        let v = cov(k, k);
+        if v < 0.0 { 0.0 } else { v.sqrt() }
+    });
+    let pearson = Array2::from_shape_fn((n_ch, n_ch), |(k, l)| {
+        let denom = std[k] * std[l];
+        if denom > 0.0 {
+            (cov(k, l) / denom).clamp(-1.0, 1.0)
+        } else {
+            f64::NAN
+        }
+    });
+    let mut per_arr = Array2::<f64>::zeros((n_ch, percentiles.len()));
+    if !percentiles.is_empty() {
+        let data = data.into_dyn();
+        for (mut row, ch) in per_arr
+            .axis_iter_mut(Axis(0))
+            .zip(data.axis_iter(Axis(axis)))
+        {
+            row.assign(&linear_percentiles(&ch, percentiles, None, None, threads)?);
+        }
+    }
+    Ok(ChannelSummary {
+        mean,
+        std,
+        percentiles: per_arr,
+        pearson,
+    })
+}
@@ -0,0 +1,262 @@
+use std::f64::consts::PI;
+
+use ndarray::{ArrayBase, AsArray, Dimension, ViewRepr, Zip};
+
+use crate::prelude::*;
+
+/// A merging t-digest for approximate percentiles of large arrays and streams.
+///
+/// # Description
+///
+/// The t-digest summarizes a data set with a bounded number of weighted
+/// centroids. Centroids near the tails hold fewer values than those near the
+/// median, so extreme percentiles (*e.g.* `0.1` or `99.9`) are estimated with
+/// a small relative error. Centroid sizes are limited with the `k₁` scale
+/// function:
+///
+/// ```text
+/// k(q) = δ / (2π) × asin(2q - 1)
+/// ```
+///
+/// Where `q` is the quantile and `δ` the compression. Adjacent centroids are
+/// only merged if they span at most one unit of `k`. Digests of separate
+/// chunks can be merged, which allows chunked, parallel and out-of-core
+/// computation. Memory usage is `O(δ)` independent of the number of values.
+///
+/// # Reference
+///
+/// <https://doi.org/10.1016/j.simpa.2020.100049>
+#[derive(Debug, Clone, PartialEq)]
+pub struct TDigest {
+    /// The compression parameter `δ`.
+    compression: f64,
+    /// The compressed centroids as `(mean, weight)` pairs, sorted by mean.
+    centroids: Vec<(f64, f64)>,
+    /// The values added since the last compression.
+    buffer: Vec<f64>,
+    /// The total weight of the centroids and buffer.
+    count: f64,
+    /// The minimum value added to the digest.
+    min: f64,
+    /// The maximum value added to the digest.
+    max: f64,
+}
+
+impl TDigest {
+    /// Create a new empty t-digest.
+    ///
+    /// # Arguments
+    ///
+    /// * `compression`: The compression parameter `δ`, larger values give
+    ///   more accurate percentiles with more centroids. If `None`, then
+    ///   `compression = 100.0`.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(TDigest)`: An empty t-digest.
+    /// * `Err(ImgalError)`: If `compression < 10.0` or is not finite.
+    pub fn new(compression: Option<f64>) -> Result<Self, ImgalError> {
+        let compression = compression.unwrap_or(100.0);
+        if !(compression >= 10.0 && compression.is_finite()) {
+            return Err(ImgalError::InvalidParameterValueOutsideRange {
+                param_name: "compression",
+                value: compression,
+                min: 10.0,
+                max: f64::INFINITY,
+            });
+        }
+        Ok(Self {
+            compression,
+            centroids: Vec::new(),
+            buffer: Vec::new(),
+            count: 0.0,
+            min: f64::INFINITY,
+            max: f64::NEG_INFINITY,
+        })
+    }
+
+    /// Add a value to the t-digest. `NaN` values are ignored.
+    ///
+    /// # Arguments
+    ///
+    /// * `value`: The value to add.
+    pub fn add(&mut self, value: f64) {
+        if value.is_nan() {
+            return;
+        }
+        self.buffer.push(value);
+        self.count += 1.0;
+        self.min = self.min.min(value);
+        self.max = self.max.max(value);
+        if self.buffer.len() >= 5 * self.compression as usize {
+            self.compress();
+        }
+    }
+
+    /// Merge another t-digest into this t-digest.
+    ///
+    /// # Arguments
+    ///
+    /// * `other`: The t-digest to merge (*e.g.* of another data chunk).
+    pub fn merge(&mut self, other: &TDigest) {
+        self.centroids.extend_from_slice(&other.centroids);
+        self.buffer.extend_from_slice(&other.buffer);
+        self.count += other.count;
+        self.min = self.min.min(other.min);
+        self.max = self.max.max(other.max);
+        self.compress();
+    }
+
+    /// Return the number of values in the t-digest.
+    ///
+    /// # Returns
+    ///
+    /// * `f64`: The number of values added to the t-digest.
+    pub fn count(&self) -> f64 {
+        self.count
+    }
+
+    /// Estimate a percentile of the values in the t-digest.
+    ///
+    /// # Description
+    ///
+    /// Estimates the percentile by linear interpolation between the centroid
+    /// means, each centroid is placed at the center of its cumulative weight.
+    /// The minimum and maximum values are exact.
+    ///
+    /// # Arguments
+    ///
+    /// * `percentile`: The percentile value in the range `0.0` to `100.0`.
+    ///   Values outside this range will be clamped.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(f64)`: The estimated percentile.
+    /// * `Err(ImgalError)`: If the t-digest is empty.
+    pub fn percentile(&self, percentile: f64) -> Result<f64, ImgalError> {
+        if self.count == 0.0 {
+            return Err(ImgalError::InvalidParameterEmptyArray {
+                param_name: "TDigest",
+            });
+        }
+        // estimate from a compressed copy if values are still buffered
+        if !self.buffer.is_empty() {
+            let mut digest = self.clone();
+            digest.compress();
+            return digest.percentile(percentile);
+        }
+        let q = percentile.clamp(0.0, 100.0) / 100.0;
+        let c = &self.centroids;
+        if q == 0.0 {
+            return Ok(self.min);
+        }
+        if q == 1.0 {
+            return Ok(self.max);
+        }
+        let index = q * self.count;
+        // left tail, between the minimum and the first centroid
+        let first = c[0];
+        if index < 0.5 * first.1 {
+            return Ok(self.min + index / (0.5 * first.1) * (first.0 - self.min));
+        }
+        let mut cumulative = 0.5 * first.1;
+        for w in c.windows(2) {
+            let dw = 0.5 * (w[0].1 + w[1].1);
+            if index < cumulative + dw {
+                let t = (index - cumulative) / dw;
+                return Ok(w[0].0 + t * (w[1].0 - w[0].0));
+            }
+            cumulative += dw;
+        }
+        // right tail, between the last centroid and the maximum
+        let last = c[c.len() - 1];
+        let t = ((index - cumulative) / (0.5 * last.1)).min(1.0);
+        Ok(last.0 + t * (self.max - last.0))
+    }
+
+    /// Merge the buffered values and centroids into compressed centroids.
+    fn compress(&mut self) {
+        let mut points: Vec<(f64, f64)> = self.buffer.drain(..).map(|v| (v, 1.0)).collect();
+        points.append(&mut self.centroids);
+        if points.is_empty() {
+            return;
+        }
+        points.sort_unstable_by(|a, b| a.0.total_cmp(&b.0));
+        let total = self.count;
+        let delta = self.compression;
+        let k = |q: f64| delta / (2.0 * PI) * (2.0 * q - 1.0).asin();
+        let k_inv = |k: f64| 0.5 * ((2.0 * PI * k / delta).min(0.5 * PI).sin() + 1.0);
+        let mut merged: Vec<(f64, f64)> = Vec::with_capacity(delta.ceil() as usize);
+        let mut cur = points[0];
+        let mut w_before = 0.0;
+        let mut q_limit = k_inv(k(0.0) + 1.0) * total;
+        for &p in points.iter().skip(1) {
+            if w_before + cur.1 + p.1 <= q_limit {
+                // merge the point into the current centroid (weighted mean)
+                let w = cur.1 + p.1;
+                cur.0 += (p.0 - cur.0) * p.1 / w;
+                cur.1 = w;
+            } else {
+                w_before += cur.1;
+                merged.push(cur);
+                q_limit = k_inv(k((w_before / total).min(1.0)) + 1.0) * total;
+                cur = p;
+            }
+        }
+        merged.push(cur);
+        self.centroids = merged;
+    }
+}
+
+/// Build a t-digest of an n-dimensional image.
+///
+/// # Description
+///
+/// Builds a `TDigest` of all values of an n-dimensional image, for approximate
+/// percentiles that do not require a copy of the image (see
+/// `linear_percentile` for exact percentiles). With parallel execution each
+/// thread builds a digest of its chunk of the image and the digests are
+/// merged.
+///
+/// # Arguments
+///
+/// * `data`: An n-dimensional image.
+/// * `compression`: The compression parameter `δ`. If `None`, then
+///   `compression = 100.0`.
+/// * `threads`: The requested number of threads to use for parallel execution.
+///   If `None` or `Some(1)` sequential execution is used. If `Some(0)`, then
+///   the maximum available parallelism is used. Thread counts are clamped to
+///   the systems maximum.
+///
+/// # Returns
+///
+/// * `Ok(TDigest)`: The t-digest of the input data.
+/// * `Err(ImgalError)`: If `compression < 10.0` or is not finite.
+pub fn tdigest<'a, T, A, D>(
+    data: A,
+    compression: Option<f64>,
+    threads: Option<usize>,
+) -> Result<TDigest, ImgalError>
+where
+    A: AsArray<'a, T, D>,
+    D: Dimension,
+    T: 'a + AsNumeric,
+{
+    let data: ArrayBase<ViewRepr<&'a T>, D> = data.into();
+    let empty = TDigest::new(compression)?;
+    let add = |mut d: TDigest, v: &T| {
+        d.add(v.to_f64());
+        d
+    };
+    let digest = par!(threads,
+    seq_exp: data.iter().fold(empty, add),
+    par_exp: Zip::from(&data).par_fold(
+        || empty.clone(),
+        add,
+        |mut a, b| {
+            a.merge(&b);
+            a
+        },
+    ));
+    Ok(digest)
+}
@@ -0,0 +1,141 @@
+use ndarray::{ArrayBase, ArrayD, AsArray, Dimension, ViewRepr};
+
+use super::median::reduce_lanes;
+use crate::prelude::*;
+
+/// Compute the trimmed mean of an n-dimensional image.
+///
+/// # Description
+///
+/// Computes the mean after discarding the `k = ⌊trim × n⌋` smallest and `k`
+/// largest values, over the entire array (flattened) or along a specified
+/// axis:
+///
+/// ```text
+/// trimmed mean = Σᵢ₌ₖⁿ⁻ᵏ⁻¹ x₍ᵢ₎ / (n - 2k)
+/// ```
+///
+/// Where `x₍ᵢ₎` are the sorted values. The trimmed mean is a robust estimate of
+/// the location (*e.g.* the background level) of data with outliers, such as
+/// hot pixels or bright objects. The trimmed values are found by selection
+/// instead of a full sort.
+///
+/// # Arguments
+///
+/// * `data`: An n-dimensional image.
+/// * `axis`: The axis to compute the trimmed mean along. If `None`, the input
+///   `data` is flattened and a single trimmed mean value is returned.
+/// * `trim`: The fraction of values to discard from each end, in the range
+///   `0.0` to `0.5` (exclusive). If `None`, then `trim = 0.1`.
+/// * `threads`: The requested number of threads to use for parallel execution.
+///   If `None` or `Some(1)` sequential execution is used. If `Some(0)`, then
+///   the maximum available parallelism is used. Thread counts are clamped to
+///   the systems maximum.
+///
+/// # Returns
+///
+/// * `Ok(ArrayD<f64>)`: The trimmed mean of the input data, with the same
+///   shape convention as `mean`.
+/// * `Err(ImgalError)`: If `data` is empty. If `axis >= data.ndim()`. If
+///   `trim` is outside the range `0.0` to `0.5` (exclusive).
+pub fn trimmed_mean<'a, T, A, D>(
+    data: A,
+    axis: Option<usize>,
+    trim: Option<f64>,
+    threads: Option<usize>,
+) -> Result<ArrayD<f64>, ImgalError>
+where
+    A: AsArray<'a, T, D>,
+    D: Dimension,
+    T: 'a + AsNumeric,
+{
+    let data: ArrayBase<ViewRepr<&'a T>, D> = data.into();
+    let trim = check_trim(trim)?;
+    reduce_lanes(data.into_dyn(), axis, threads, |buf| {
+        let k = (trim * buf.len() as f64) as usize;
+        let middle = trim_select(buf, k);
+        middle.iter().sum::<f64>() / middle.len() as f64
+    })
+}
+
+/// Compute the winsorized mean of an n-dimensional image.
+///
+/// # Description
+///
+/// Computes the mean after replacing the `k = ⌊trim × n⌋` smallest values with
+/// the smallest and the `k` largest values with the largest of the remaining
+/// values, over the entire array (flattened) or along a specified axis:
+///
+/// ```text
+/// winsorized mean = (k × x₍ₖ₎ + Σᵢ₌ₖⁿ⁻ᵏ⁻¹ x₍ᵢ₎ + k × x₍ₙ₋ₖ₋₁₎) / n
+/// ```
+///
+/// Where `x₍ᵢ₎` are the sorted values. Unlike the trimmed mean (see
+/// `trimmed_mean`) all `n` values keep their weight, while the influence of
+/// outliers is limited to the clipped values.
+///
+/// # Arguments
+///
+/// * `data`: An n-dimensional image.
+/// * `axis`: The axis to compute the winsorized mean along. If `None`, the
+///   input `data` is flattened and a single winsorized mean value is returned.
+/// * `trim`: The fraction of values to clip at each end, in the range `0.0` to
+///   `0.5` (exclusive). If `None`, then `trim = 0.1`.
+/// * `threads`: The requested number of threads to use for parallel execution.
+///   If `None` or `Some(1)` sequential execution is used. If `Some(0)`, then
+///   the maximum available parallelism is used. Thread counts are clamped to
+///   the systems maximum.
+///
+/// # Returns
+///
+/// * `Ok(ArrayD<f64>)`: The winsorized mean of the input data, with the same
+///   shape convention as `mean`.
+/// * `Err(ImgalError)`: If `data` is empty. If `axis >= data.ndim()`. If
+///   `trim` is outside the range `0.0` to `0.5` (exclusive).
+pub fn winsorized_mean<'a, T, A, D>(
+    data: A,
+    axis: Option<usize>,
+    trim: Option<f64>,
+    threads: Option<usize>,
+) -> Result<ArrayD<f64>, ImgalError>
+where
+    A: AsArray<'a, T, D>,
+    D: Dimension,
+    T: 'a + AsNumeric,
+{
+    let data: ArrayBase<ViewRepr<&'a T>, D> = data.into();
+    let trim = check_trim(trim)?;
+    reduce_lanes(data.into_dyn(), axis, threads, |buf| {
+        let n = buf.len();
+        let k = (trim * n as f64) as usize;
+        let middle = trim_select(buf, k);
+        let lo = middle.iter().copied().fold(f64::INFINITY, f64::min);
+        let hi = middle.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+        (middle.iter().sum::<f64>() + k as f64 * (lo + hi)) / n as f64
+    })
+}
+
+/// Reorder a buffer by selection so that the `k` smallest and `k` largest
+/// values are at its ends and return the remaining middle values.
+fn trim_select(buf: &mut [f64], k: usize) -> &[f64] {
+    let n = buf.len();
+    if k > 0 {
+        buf.select_nth_unstable_by(k, |a, b| a.total_cmp(b));
+        buf[k..].select_nth_unstable_by(n - 2 * k - 1, |a, b| a.total_cmp(b));
+    }
+    &buf[k..n - k]
+}
+
+/// Validate the trim fraction, returning the default of `0.1` if `None`.
+fn check_trim(trim: Option<f64>) -> Result<f64, ImgalError> {
+    let trim = trim.unwrap_or(0.1);
+    if !(0.0..0.5).contains(&trim) {
+        return Err(ImgalError::InvalidParameterValueOutsideRange {
+            param_name: "trim",
+            value: trim,
+            min: 0.0,
+            max: 0.5,
+        });
+    }
+    Ok(trim)
+}
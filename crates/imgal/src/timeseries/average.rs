@@ -0,0 +1,140 @@
+use std::cmp::Ordering;
+
+use ndarray::{Array, ArrayBase, ArrayView1, AsArray, Axis, Dimension, RemoveAxis, ViewRepr, Zip};
+
+use crate::prelude::*;
+use crate::validate::check_axis;
+
+/// The frame averaging method used by `robust_average`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AverageMethod {
+    /// The arithmetic mean of all frames.
+    Mean,
+    /// The median of all frames.
+    Median,
+    /// The mean of all frames after iteratively rejecting outliers that are
+    /// more than `sigma` standard deviations away from the median. Clipping
+    /// stops when no further values are rejected or after `max_iter`
+    /// iterations.
+    SigmaClip { sigma: f64, max_iter: usize },
+}
+
+/// Average an image time series across frames with optional outlier rejection.
+///
+/// # Description
+///
+/// Computes the per pixel average across the frames of an image time series
+/// (*e.g.* for denoising static samples or building reference and background
+/// frames). The average is computed as the mean, the median or a sigma-clipped
+/// mean. Sigma clipping iteratively rejects values where:
+///
+/// ```text
+/// |xᵢ - median(x)| > sigma × std(x)
+/// ```
+///
+/// And computes the mean of the remaining values, which removes transient
+/// outliers (*e.g.* cosmic rays, debris or passing cells) while keeping the
+/// noise reduction of the mean.
+///
+/// # Arguments
+///
+/// * `data`: The input n-dimensional image time series.
+/// * `method`: The averaging method.
+/// * `axis`: The frame (time) axis. If `None`, then `axis = 0`.
+/// * `threads`: The requested number of threads to use for parallel execution.
+///   If `None` or `Some(1)` sequential execution is used. If `Some(0)`, then
+///   the maximum available parallelism is used. Thread counts are clamped to
+///   the systems maximum.
+///
+/// # Returns
+///
+/// * `Ok(Array<f64, D::Smaller>)`: The averaged image with the same shape as
+///   `data` with `axis` removed.
+/// * `Err(ImgalError)`: If `data` is empty. If `axis >= data.ndim()`. If
+///   `sigma <= 0.0` for `AverageMethod::SigmaClip`.
+pub fn robust_average<'a, T, A, D>(
+    data: A,
+    method: AverageMethod,
+    axis: Option<usize>,
+    threads: Option<usize>,
+) -> Result<Array<f64, D::Smaller>, ImgalError>
+where
+    A: AsArray<'a, T, D>,
+    D: Dimension + RemoveAxis,
+    T: 'a + AsNumeric,
+{
+    let data: ArrayBase<ViewRepr<&'a T>, D> = data.into();
+    if data.is_empty() {
+        return Err(ImgalError::InvalidParameterEmptyArray { param_name: "data" });
+    }
+    let axis = axis.unwrap_or(0);
+    check_axis(axis, data.ndim())?;
+    if let AverageMethod::SigmaClip { sigma, .. } = method
+        && sigma <= 0.0
+    {
+        return Err(ImgalError::InvalidParameterValueOutsideRange {
+            param_name: "sigma",
+            value: sigma,
+            min: 0.0,
+            max: f64::INFINITY,
+        });
+    }
+    let mut avg_arr = Array::<f64, D::Smaller>::zeros(data.raw_dim().remove_axis(Axis(axis)));
+    let avg_calc = |ln: ArrayView1<T>, a: &mut f64| {
+        let mut vals: Vec<f64> = ln.iter().map(|v| v.to_f64()).collect();
+        *a = match method {
+            AverageMethod::Mean => mean(&vals),
+            AverageMethod::Median => median(&mut vals),
+            AverageMethod::SigmaClip { sigma, max_iter } => {
+                sigma_clipped_mean(&mut vals, sigma, max_iter)
+            }
+        };
+    };
+    par!(threads,
+        seq_exp: Zip::from(data.lanes(Axis(axis))).and(&mut avg_arr).for_each(avg_calc),
+        par_exp: Zip::from(data.lanes(Axis(axis))).and(&mut avg_arr).par_for_each(avg_calc));
+    Ok(avg_arr)
+}
+
+/// Compute the mean of a slice.
+fn mean(vals: &[f64]) -> f64 {
+    vals.iter().sum::<f64>() / vals.len() as f64
+}
+
+/// Compute the median of a slice, the slice is partially reordered.
+fn median(vals: &mut [f64]) -> f64 {
+    let n = vals.len();
+    let cmp = |a: &f64, b: &f64| a.partial_cmp(b).unwrap_or(Ordering::Less);
+    let (lower, upper, _) = vals.select_nth_unstable_by(n / 2, cmp);
+    let upper = *upper;
+    if n % 2 == 1 {
+        upper
+    } else {
+        let lower = lower.iter().cloned().fold(f64::MIN, f64::max);
+        0.5 * (lower + upper)
+    }
+}
+
+/// Compute the sigma-clipped mean of a slice, the slice is reordered.
+fn sigma_clipped_mean(vals: &mut [f64], sigma: f64, max_iter: usize) -> f64 {
+    let mut kept = vals.len();
+    for _ in 0..max_iter {
+        let cur = &mut vals[..kept];
+        let m = mean(cur);
+        let std = (cur.iter().map(|v| (v - m).powi(2)).sum::<f64>() / kept as f64).sqrt();
+        let center = median(cur);
+        // move the kept values to the front of the slice
+        let mut next = 0;
+        for i in 0..kept {
+            if (cur[i] - center).abs() <= sigma * std {
+                cur.swap(next, i);
+                next += 1;
+            }
+        }
+        if next == kept || next == 0 {
+            break;
+        }
+        kept = next;
+    }
+    mean(&vals[..kept])
+}
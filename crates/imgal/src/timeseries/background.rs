@@ -0,0 +1,162 @@
+use std::cmp::Ordering;
+use std::collections::VecDeque;
+
+use ndarray::{
+    Array, ArrayBase, ArrayView1, ArrayViewMut1, AsArray, Axis, Dimension, ViewRepr, Zip,
+};
+
+use crate::prelude::*;
+use crate::validate::check_axis;
+
+/// The background subtracted time series and background output of
+/// `rolling_background`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RollingBackground<D: Dimension> {
+    /// The background subtracted time series.
+    pub corrected: Array<f64, D>,
+    /// The rolling background.
+    pub background: Array<f64, D>,
+}
+
+/// Subtract a rolling temporal background from an image time series.
+///
+/// # Description
+///
+/// Estimates the per pixel background of an image time series with a sliding
+/// window minimum or low percentile over time and subtracts it. This isolates
+/// transient events (*e.g.* vesicle fusion or calcium sparks) from slowly
+/// varying backgrounds (*e.g.* photobleaching or cytosolic fluorescence). For
+/// each frame `t` the background is:
+///
+/// ```text
+/// B(x, t) = percentile(I(x, t - w/2), ..., I(x, t + w/2))
+/// ```
+///
+/// Where `w` is the window size, and the window is clipped at the start and
+/// end of the time series. The percentile uses linear interpolation between
+/// the closest ranks. If `percentile` is `None` the window minimum is used,
+/// which is computed in linear time with a monotonic queue.
+///
+/// # Arguments
+///
+/// * `data`: The input n-dimensional image time series.
+/// * `window`: The size of the sliding window in frames. Even window sizes are
+///   extended by one frame to center the window.
+/// * `percentile`: The background percentile in the range `0.0` to `100.0`. If
+///   `None`, then the window minimum is used.
+/// * `axis`: The frame (time) axis. If `None`, then `axis = 0`.
+/// * `threads`: The requested number of threads to use for parallel execution.
+///   If `None` or `Some(1)` sequential execution is used. If `Some(0)`, then
+///   the maximum available parallelism is used. Thread counts are clamped to
+///   the systems maximum.
+///
+/// # Returns
+///
+/// * `Ok(RollingBackground<D>)`: The background subtracted time series and the
+///   background, both with the same shape as `data`.
+/// * `Err(ImgalError)`: If `data` is empty. If `axis >= data.ndim()`. If
+///   `window == 0`. If `percentile` is outside the range `0.0` to `100.0`.
+pub fn rolling_background<'a, T, A, D>(
+    data: A,
+    window: usize,
+    percentile: Option<f64>,
+    axis: Option<usize>,
+    threads: Option<usize>,
+) -> Result<RollingBackground<D>, ImgalError>
+where
+    A: AsArray<'a, T, D>,
+    D: Dimension,
+    T: 'a + AsNumeric,
+{
+    let data: ArrayBase<ViewRepr<&'a T>, D> = data.into();
+    if data.is_empty() {
+        return Err(ImgalError::InvalidParameterEmptyArray { param_name: "data" });
+    }
+    let axis = axis.unwrap_or(0);
+    check_axis(axis, data.ndim())?;
+    if window == 0 {
+        return Err(ImgalError::InvalidParameterValueLess {
+            param_name: "window",
+            value: 1,
+        });
+    }
+    if let Some(p) = percentile
+        && !(0.0..=100.0).contains(&p)
+    {
+        return Err(ImgalError::InvalidParameterValueOutsideRange {
+            param_name: "percentile",
+            value: p,
+            min: 0.0,
+            max: 100.0,
+        });
+    }
+    let half = window / 2;
+    let mut bg_arr = Array::<f64, D>::zeros(data.raw_dim());
+    let bg_calc = |ln: ArrayView1<T>, bg: ArrayViewMut1<f64>| {
+        let vals: Vec<f64> = ln.iter().map(|v| v.to_f64()).collect();
+        match percentile {
+            Some(p) => rolling_percentile(&vals, half, p, bg),
+            None => rolling_min(&vals, half, bg),
+        }
+    };
+    par!(threads,
+        seq_exp: Zip::from(data.lanes(Axis(axis)))
+            .and(bg_arr.lanes_mut(Axis(axis)))
+            .for_each(bg_calc),
+        par_exp: Zip::from(data.lanes(Axis(axis)))
+            .and(bg_arr.lanes_mut(Axis(axis)))
+            .par_for_each(bg_calc));
+    let mut corr_arr = data.mapv(|v| v.to_f64());
+    corr_arr -= &bg_arr;
+    Ok(RollingBackground {
+        corrected: corr_arr,
+        background: bg_arr,
+    })
+}
+
+/// Compute the centered sliding window minimum with a monotonic queue.
+fn rolling_min(vals: &[f64], half: usize, mut bg: ArrayViewMut1<f64>) {
+    let n = vals.len();
+    // indices of increasing values, the front is the window minimum
+    let mut queue: VecDeque<usize> = VecDeque::new();
+    let mut next = 0;
+    for t in 0..n {
+        let hi = (t + half).min(n - 1);
+        while next <= hi {
+            while queue.back().is_some_and(|&b| vals[b] >= vals[next]) {
+                queue.pop_back();
+            }
+            queue.push_back(next);
+            next += 1;
+        }
+        while queue.front().is_some_and(|&f| f + half < t) {
+            queue.pop_front();
+        }
+        bg[t] = vals[queue[0]];
+    }
+}
+
+/// Compute the centered sliding window linear percentile.
+fn rolling_percentile(vals: &[f64], half: usize, percentile: f64, mut bg: ArrayViewMut1<f64>) {
+    let n = vals.len();
+    let cmp = |a: &f64, b: &f64| a.partial_cmp(b).unwrap_or(Ordering::Less);
+    let mut buf: Vec<f64> = Vec::with_capacity(2 * half + 1);
+    for t in 0..n {
+        let lo = t.saturating_sub(half);
+        let hi = (t + half).min(n - 1);
+        buf.clear();
+        buf.extend_from_slice(&vals[lo..=hi]);
+        let h = (buf.len() - 1) as f64 * percentile / 100.0;
+        let j = h.floor() as usize;
+        let gamma = h - j as f64;
+        buf.select_nth_unstable_by(j, cmp);
+        let v_j = buf[j];
+        bg[t] = if j + 1 < buf.len() && gamma > 0.0 {
+            // the next rank is the minimum of the values above rank "j"
+            let v_j1 = buf[j + 1..].iter().cloned().fold(f64::INFINITY, f64::min);
+            (1.0 - gamma) * v_j + gamma * v_j1
+        } else {
+            v_j
+        };
+    }
+}
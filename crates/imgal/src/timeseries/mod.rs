@@ -0,0 +1,12 @@
+//! Time series functions.
+//!
+//! This module provides functions for processing image time series (*e.g.*
+//! frame stacks), such as frame averaging and rolling background subtraction.
+
+mod average;
+mod background;
+
+pub use average::AverageMethod;
+pub use average::robust_average;
+pub use background::RollingBackground;
+pub use background::rolling_background;
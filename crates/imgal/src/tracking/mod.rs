@@ -0,0 +1,8 @@
+//! Object tracking functions.
+//!
+//! This module provides data structures for the tracks of objects (*e.g.*
+//! particles or cells) over the frames of a time series.
+
+mod table;
+
+pub use table::TrackTable;
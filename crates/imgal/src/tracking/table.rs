@@ -0,0 +1,312 @@
+use std::collections::{BTreeSet, HashMap};
+
+use ndarray::{Array2, ArrayBase, ArrayView1, ArrayView2, AsArray, Axis, Ix2, ViewRepr, s};
+
+use crate::prelude::*;
+
+/// A table of tracked object detections.
+///
+/// # Description
+///
+/// Stores one row per detection of a tracked object (*e.g.* a particle or a
+/// cell) with its track ID, frame, n-dimensional coordinates and an optional
+/// object label (*e.g.* the ID of the segmented object in its frame, `0` if
+/// unused). The rows are kept sorted by track ID and frame. A track table can
+/// be created from and converted to the `[track_id, frame, x₀, ..., xᴰ]` array
+/// layout of `simulation::trajectories::simulate_tracks`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrackTable {
+    /// The track ID of each detection.
+    track_id: Vec<u64>,
+    /// The frame of each detection.
+    frame: Vec<usize>,
+    /// The coordinates of each detection with shape `(n, D)`.
+    coords: Array2<f64>,
+    /// The object label of each detection.
+    label: Vec<u64>,
+}
+
+impl TrackTable {
+    /// Create a new track table.
+    ///
+    /// # Arguments
+    ///
+    /// * `track_id`: The track ID of each detection.
+    /// * `frame`: The frame of each detection.
+    /// * `coords`: The coordinates of each detection with shape `(n, D)`.
+    /// * `label`: The object label of each detection. If `None`, all labels
+    ///   are `0`.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(TrackTable)`: The track table sorted by track ID and frame.
+    /// * `Err(ImgalError)`: If the lengths of `track_id`, `frame`, `label` and
+    ///   the rows of `coords` do not match. If a track has two detections in
+    ///   the same frame.
+    pub fn new<'a, A>(
+        track_id: Vec<u64>,
+        frame: Vec<usize>,
+        coords: A,
+        label: Option<Vec<u64>>,
+    ) -> Result<Self, ImgalError>
+    where
+        A: AsArray<'a, f64, Ix2>,
+    {
+        let coords: ArrayBase<ViewRepr<&'a f64>, Ix2> = coords.into();
+        let n = track_id.len();
+        let label = label.unwrap_or_else(|| vec![0; n]);
+        for (name, len) in [
+            ("frame", frame.len()),
+            ("coords", coords.nrows()),
+            ("label", label.len()),
+        ] {
+            if len != n {
+                return Err(ImgalError::MismatchedArrayLengths {
+                    a_arr_name: "track_id",
+                    a_arr_len: n,
+                    b_arr_name: name,
+                    b_arr_len: len,
+                });
+            }
+        }
+        let mut order: Vec<usize> = (0..n).collect();
+        order.sort_by_key(|&i| (track_id[i], frame[i]));
+        if order
+            .windows(2)
+            .any(|w| (track_id[w[0]], frame[w[0]]) == (track_id[w[1]], frame[w[1]]))
+        {
+            return Err(ImgalError::InvalidGeneric {
+                msg: "Invalid track table, a track has two detections in the same frame.",
+            });
+        }
+        Ok(Self {
+            track_id: order.iter().map(|&i| track_id[i]).collect(),
+            frame: order.iter().map(|&i| frame[i]).collect(),
+            coords: coords.select(Axis(0), &order),
+            label: order.iter().map(|&i| label[i]).collect(),
+        })
+    }
+
+    /// Create a track table from a `[track_id, frame, x₀, ..., xᴰ]` array.
+    ///
+    /// # Arguments
+    ///
+    /// * `table`: The track array with shape `(n, 2 + D)` (*e.g.* from
+    ///   `simulate_tracks`).
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(TrackTable)`: The track table with all labels `0`.
+    /// * `Err(ImgalError)`: If `table` has fewer than `3` columns. If a track
+    ///   ID or frame is negative or not an integer. If a track has two
+    ///   detections in the same frame.
+    pub fn from_array<'a, A>(table: A) -> Result<Self, ImgalError>
+    where
+        A: AsArray<'a, f64, Ix2>,
+    {
+        let table: ArrayBase<ViewRepr<&'a f64>, Ix2> = table.into();
+        if table.ncols() < 3 {
+            return Err(ImgalError::InvalidAxisLengthExpected {
+                arr_name: "table",
+                axis_idx: 1,
+                expected: 3,
+                got: table.ncols(),
+            });
+        }
+        let as_index = |v: f64| -> Result<u64, ImgalError> {
+            if v < 0.0 || v.fract() != 0.0 || !v.is_finite() {
+                return Err(ImgalError::InvalidGeneric {
+                    msg: "Invalid track table, the track IDs and frames must be non-negative integers.",
+                });
+            }
+            Ok(v as u64)
+        };
+        let track_id = table
+            .column(0)
+            .iter()
+            .map(|&v| as_index(v))
+            .collect::<Result<Vec<u64>, ImgalError>>()?;
+        let frame = table
+            .column(1)
+            .iter()
+            .map(|&v| as_index(v).map(|f| f as usize))
+            .collect::<Result<Vec<usize>, ImgalError>>()?;
+        Self::new(track_id, frame, table.slice(s![.., 2..]), None)
+    }
+
+    /// Convert the track table to a `[track_id, frame, x₀, ..., xᴰ]` array.
+    ///
+    /// # Returns
+    ///
+    /// * `Array2<f64>`: The track array with shape `(n, 2 + D)`, the labels
+    ///   are dropped.
+    pub fn to_array(&self) -> Array2<f64> {
+        let mut table = Array2::<f64>::zeros((self.len(), 2 + self.ndim()));
+        table
+            .axis_iter_mut(Axis(0))
+            .enumerate()
+            .for_each(|(i, mut row)| {
+                row[0] = self.track_id[i] as f64;
+                row[1] = self.frame[i] as f64;
+                row.iter_mut()
+                    .skip(2)
+                    .zip(self.coords.row(i).iter())
+                    .for_each(|(r, &c)| *r = c);
+            });
+        table
+    }
+
+    /// Return the number of detections in the track table.
+    pub fn len(&self) -> usize {
+        self.track_id.len()
+    }
+
+    /// Return `true` if the track table has no detections.
+    pub fn is_empty(&self) -> bool {
+        self.track_id.is_empty()
+    }
+
+    /// Return the dimensionality of the detection coordinates.
+    pub fn ndim(&self) -> usize {
+        self.coords.ncols()
+    }
+
+    /// Return the track ID of each detection.
+    pub fn track_id(&self) -> &[u64] {
+        &self.track_id
+    }
+
+    /// Return the frame of each detection.
+    pub fn frame(&self) -> &[usize] {
+        &self.frame
+    }
+
+    /// Return the coordinates of each detection with shape `(n, D)`.
+    pub fn coords(&self) -> ArrayView2<'_, f64> {
+        self.coords.view()
+    }
+
+    /// Return the object label of each detection.
+    pub fn label(&self) -> &[u64] {
+        &self.label
+    }
+
+    /// Return the unique track IDs in ascending order.
+    pub fn track_ids(&self) -> Vec<u64> {
+        let ids: BTreeSet<u64> = self.track_id.iter().copied().collect();
+        ids.into_iter().collect()
+    }
+
+    /// Return the number of detections of each track.
+    pub fn track_lengths(&self) -> HashMap<u64, usize> {
+        let mut lengths = HashMap::new();
+        self.track_id
+            .iter()
+            .for_each(|&k| *lengths.entry(k).or_insert(0) += 1);
+        lengths
+    }
+
+    /// Keep the detections that satisfy a predicate.
+    ///
+    /// # Arguments
+    ///
+    /// * `predicate`: A function of the track ID, frame, coordinates and label
+    ///   of a detection, returning `true` to keep the detection.
+    ///
+    /// # Returns
+    ///
+    /// * `TrackTable`: The filtered track table.
+    pub fn filter<F>(&self, predicate: F) -> TrackTable
+    where
+        F: Fn(u64, usize, ArrayView1<f64>, u64) -> bool,
+    {
+        let keep: Vec<usize> = (0..self.len())
+            .filter(|&i| {
+                predicate(
+                    self.track_id[i],
+                    self.frame[i],
+                    self.coords.row(i),
+                    self.label[i],
+                )
+            })
+            .collect();
+        self.select(&keep)
+    }
+
+    /// Keep the tracks with at least `min_length` detections (*e.g.* to drop
+    /// spurious short tracks).
+    ///
+    /// # Arguments
+    ///
+    /// * `min_length`: The minimum number of detections of a track.
+    ///
+    /// # Returns
+    ///
+    /// * `TrackTable`: The filtered track table.
+    pub fn filter_by_length(&self, min_length: usize) -> TrackTable {
+        let lengths = self.track_lengths();
+        self.filter(|k, _, _, _| lengths[&k] >= min_length)
+    }
+
+    /// Merge the detections of another track table into this track table.
+    ///
+    /// # Description
+    ///
+    /// Appends the detections of `other`, with its track IDs offset by one
+    /// more than the largest track ID of this table, so tracks of the two
+    /// tables are never joined (*e.g.* when combining the tracks of separate
+    /// fields of view or channels).
+    ///
+    /// # Arguments
+    ///
+    /// * `other`: The track table to merge.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(TrackTable)`: The merged track table.
+    /// * `Err(ImgalError)`: If the dimensionality of the coordinates does not
+    ///   match.
+    pub fn merge(&self, other: &TrackTable) -> Result<TrackTable, ImgalError> {
+        if !self.is_empty() && !other.is_empty() && self.ndim() != other.ndim() {
+            return Err(ImgalError::MismatchedArrayLengths {
+                a_arr_name: "self.coords",
+                a_arr_len: self.ndim(),
+                b_arr_name: "other.coords",
+                b_arr_len: other.ndim(),
+            });
+        }
+        if self.is_empty() {
+            return Ok(other.clone());
+        }
+        if other.is_empty() {
+            return Ok(self.clone());
+        }
+        let offset = self.track_id.iter().max().map_or(0, |m| m + 1);
+        let mut track_id = self.track_id.clone();
+        track_id.extend(other.track_id.iter().map(|k| k + offset));
+        let mut frame = self.frame.clone();
+        frame.extend_from_slice(&other.frame);
+        let mut label = self.label.clone();
+        label.extend_from_slice(&other.label);
+        let mut coords = self.coords.clone();
+        coords
+            .append(Axis(0), other.coords.view())
+            .expect("Failed to append the track coordinates.");
+        Ok(TrackTable {
+            track_id,
+            frame,
+            coords,
+            label,
+        })
+    }
+
+    /// Select the detections at the given sorted row indices.
+    fn select(&self, rows: &[usize]) -> TrackTable {
+        TrackTable {
+            track_id: rows.iter().map(|&i| self.track_id[i]).collect(),
+            frame: rows.iter().map(|&i| self.frame[i]).collect(),
+            coords: self.coords.select(Axis(0), rows),
+            label: rows.iter().map(|&i| self.label[i]).collect(),
+        }
+    }
+}
@@ -24,6 +24,10 @@ pub trait AsNumeric:
     const MAX: Self;
     const MIN: Self;
 
+    /// The widened accumulator type for sums of this type, `i128` for integer
+    /// types and `f64` for floating point types.
+    type Acc: AsAccumulator;
+
     /// Convert from this type to f64 with potential precision loss.
     fn to_usize(self) -> usize;
 
@@ -35,11 +39,24 @@ pub trait AsNumeric:
 
     /// Convert from i32 to this type with potential precision loss.
     fn from_i32(value: i32) -> Self;
+
+    /// Convert from this type to i128 without precision loss. Returns `None`
+    /// for floating point types.
+    fn to_i128(self) -> Option<i128> {
+        None
+    }
+
+    /// Convert from i128 to this type. Returns `None` if the value is out of
+    /// range or for floating point types.
+    fn from_i128(_value: i128) -> Option<Self> {
+        None
+    }
 }
 
 impl AsNumeric for usize {
     const MAX: Self = usize::MAX;
     const MIN: Self = usize::MIN;
+    type Acc = i128;
 
     fn to_usize(self) -> usize {
         self
@@ -56,11 +73,20 @@ impl AsNumeric for usize {
     fn from_i32(value: i32) -> Self {
         value as usize
     }
+
+    fn to_i128(self) -> Option<i128> {
+        Some(self as i128)
+    }
+
+    fn from_i128(value: i128) -> Option<Self> {
+        Self::try_from(value).ok()
+    }
 }
 
 impl AsNumeric for u8 {
     const MAX: Self = u8::MAX;
     const MIN: Self = u8::MIN;
+    type Acc = i128;
 
     fn to_usize(self) -> usize {
         self as usize
@@ -77,11 +103,20 @@ impl AsNumeric for u8 {
     fn from_i32(value: i32) -> Self {
         value as u8
     }
+
+    fn to_i128(self) -> Option<i128> {
+        Some(self as i128)
+    }
+
+    fn from_i128(value: i128) -> Option<Self> {
+        Self::try_from(value).ok()
+    }
 }
 
 impl AsNumeric for u16 {
     const MAX: Self = u16::MAX;
     const MIN: Self = u16::MIN;
+    type Acc = i128;
 
     fn to_usize(self) -> usize {
         self as usize
@@ -98,11 +133,20 @@ impl AsNumeric for u16 {
     fn from_i32(value: i32) -> Self {
         value as u16
     }
+
+    fn to_i128(self) -> Option<i128> {
+        Some(self as i128)
+    }
+
+    fn from_i128(value: i128) -> Option<Self> {
+        Self::try_from(value).ok()
+    }
 }
 
 impl AsNumeric for u32 {
     const MAX: Self = u32::MAX;
     const MIN: Self = u32::MIN;
+    type Acc = i128;
 
     fn to_usize(self) -> usize {
         self as usize
@@ -119,11 +163,20 @@ impl AsNumeric for u32 {
     fn from_i32(value: i32) -> Self {
         value as u32
     }
+
+    fn to_i128(self) -> Option<i128> {
+        Some(self as i128)
+    }
+
+    fn from_i128(value: i128) -> Option<Self> {
+        Self::try_from(value).ok()
+    }
 }
 
 impl AsNumeric for u64 {
     const MAX: Self = u64::MAX;
     const MIN: Self = u64::MIN;
+    type Acc = i128;
 
     fn to_usize(self) -> usize {
         self as usize
@@ -140,11 +193,20 @@ impl AsNumeric for u64 {
     fn from_i32(value: i32) -> Self {
         value as u64
     }
+
+    fn to_i128(self) -> Option<i128> {
+        Some(self as i128)
+    }
+
+    fn from_i128(value: i128) -> Option<Self> {
+        Self::try_from(value).ok()
+    }
 }
 
 impl AsNumeric for i8 {
     const MAX: Self = i8::MAX;
     const MIN: Self = i8::MIN;
+    type Acc = i128;
 
     fn to_usize(self) -> usize {
         self as usize
@@ -161,11 +223,20 @@ impl AsNumeric for i8 {
     fn from_i32(value: i32) -> Self {
         value as i8
     }
+
+    fn to_i128(self) -> Option<i128> {
+        Some(self as i128)
+    }
+
+    fn from_i128(value: i128) -> Option<Self> {
+        Self::try_from(value).ok()
+    }
 }
 
 impl AsNumeric for i16 {
     const MAX: Self = i16::MAX;
     const MIN: Self = i16::MIN;
+    type Acc = i128;
 
     fn to_usize(self) -> usize {
         self as usize
@@ -182,11 +253,20 @@ impl AsNumeric for i16 {
     fn from_i32(value: i32) -> Self {
         value as i16
     }
+
+    fn to_i128(self) -> Option<i128> {
+        Some(self as i128)
+    }
+
+    fn from_i128(value: i128) -> Option<Self> {
+        Self::try_from(value).ok()
+    }
 }
 
 impl AsNumeric for i32 {
     const MAX: Self = i32::MAX;
     const MIN: Self = i32::MIN;
+    type Acc = i128;
 
     fn to_usize(self) -> usize {
         self as usize
@@ -203,11 +283,20 @@ impl AsNumeric for i32 {
     fn from_i32(value: i32) -> Self {
         value
     }
+
+    fn to_i128(self) -> Option<i128> {
+        Some(self as i128)
+    }
+
+    fn from_i128(value: i128) -> Option<Self> {
+        Self::try_from(value).ok()
+    }
 }
 
 impl AsNumeric for i64 {
     const MAX: Self = i64::MAX;
     const MIN: Self = i64::MIN;
+    type Acc = i128;
 
     fn to_usize(self) -> usize {
         self as usize
@@ -224,11 +313,20 @@ impl AsNumeric for i64 {
     fn from_i32(value: i32) -> Self {
         value as i64
     }
+
+    fn to_i128(self) -> Option<i128> {
+        Some(self as i128)
+    }
+
+    fn from_i128(value: i128) -> Option<Self> {
+        Self::try_from(value).ok()
+    }
 }
 
 impl AsNumeric for f32 {
     const MAX: Self = f32::MAX;
     const MIN: Self = f32::MIN;
+    type Acc = f64;
 
     fn to_usize(self) -> usize {
         self as usize
@@ -250,6 +348,7 @@ impl AsNumeric for f32 {
 impl AsNumeric for f64 {
     const MAX: Self = f64::MAX;
     const MIN: Self = f64::MIN;
+    type Acc = f64;
 
     fn to_usize(self) -> usize {
         self as usize
@@ -267,3 +366,78 @@ impl AsNumeric for f64 {
         value as f64
     }
 }
+
+#[cfg(feature = "half")]
+impl AsNumeric for half::f16 {
+    const MAX: Self = half::f16::MAX;
+    const MIN: Self = half::f16::MIN;
+    type Acc = f64;
+
+    fn to_usize(self) -> usize {
+        self.to_f32() as usize
+    }
+
+    fn to_f64(self) -> f64 {
+        self.to_f64()
+    }
+
+    fn from_f64(value: f64) -> Self {
+        half::f16::from_f64(value)
+    }
+
+    fn from_i32(value: i32) -> Self {
+        half::f16::from_f32(value as f32)
+    }
+}
+
+#[cfg(feature = "half")]
+impl AsNumeric for half::bf16 {
+    const MAX: Self = half::bf16::MAX;
+    const MIN: Self = half::bf16::MIN;
+    type Acc = f64;
+
+    fn to_usize(self) -> usize {
+        self.to_f32() as usize
+    }
+
+    fn to_f64(self) -> f64 {
+        self.to_f64()
+    }
+
+    fn from_f64(value: f64) -> Self {
+        half::bf16::from_f64(value)
+    }
+
+    fn from_i32(value: i32) -> Self {
+        half::bf16::from_f32(value as f32)
+    }
+}
+
+/// Trait for the widened accumulator types of sums over `AsNumeric` types.
+pub trait AsAccumulator: Copy + Debug + Default + PartialOrd + Send + Sync {
+    /// Convert from an exact i128 integer sum to this type.
+    fn from_i128(value: i128) -> Self;
+
+    /// Convert from an f64 floating point sum to this type.
+    fn from_f64(value: f64) -> Self;
+}
+
+impl AsAccumulator for i128 {
+    fn from_i128(value: i128) -> Self {
+        value
+    }
+
+    fn from_f64(value: f64) -> Self {
+        value as i128
+    }
+}
+
+impl AsAccumulator for f64 {
+    fn from_i128(value: i128) -> Self {
+        value as f64
+    }
+
+    fn from_f64(value: f64) -> Self {
+        value
+    }
+}
@@ -0,0 +1,303 @@
+use ndarray::{
+    Array, ArrayBase, ArrayView1, ArrayViewMut1, AsArray, Axis, Dimension, ViewRepr, Zip, s,
+};
+
+use crate::prelude::*;
+use crate::validate::check_axis;
+
+/// The reduction of the samples in a bin.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum BinMode {
+    /// Sum the samples of a bin, conserving the total counts.
+    #[default]
+    Sum,
+    /// Average the samples of a bin, preserving the intensity scale.
+    Mean,
+}
+
+/// Bin groups of consecutive samples along one axis of an n-dimensional
+/// image.
+///
+/// # Description
+///
+/// Reduces each group of `factor` consecutive samples along `axis` to a single
+/// sample by summing or averaging, *e.g.* to trade the temporal resolution of
+/// a decay or time-lapse stack for signal-to-noise ratio before phasor
+/// analysis or fitting:
+///
+/// ```text
+/// y[..., k, ...] = Σⱼ₌₀ᶠ⁻¹ x[..., k × f + j, ...]
+/// ```
+///
+/// The other axes are unchanged, this is `downsample_sum` (or
+/// `downsample_mean`) with a factor of `1` on every other axis. The length of
+/// `axis` is `⌈len / factor⌉`, a partial group at the end of the axis is
+/// reduced over the remaining samples. For decays whose number of time bins
+/// must be divisible by the factor see `flim::rebin::rebin_decay`.
+///
+/// # Arguments
+///
+/// * `data`: The input n-dimensional image.
+/// * `factor`: The number of consecutive samples per bin.
+/// * `axis`: The axis to bin. If `None`, then the last axis is used.
+/// * `mode`: The reduction of the samples in a bin. If `None`, then
+///   `mode = BinMode::Sum`.
+/// * `threads`: The requested number of threads to use for parallel execution.
+///   If `None` or `Some(1)` sequential execution is used. If `Some(0)`, then
+///   the maximum available parallelism is used. Thread counts are clamped to
+///   the systems maximum.
+///
+/// # Returns
+///
+/// * `Ok(Array<f64, D>)`: The binned image.
+/// * `Err(ImgalError)`: If `axis >= data.ndim()`. If `factor == 0`.
+pub fn bin_axis<'a, T, A, D>(
+    data: A,
+    factor: usize,
+    axis: Option<usize>,
+    mode: Option<BinMode>,
+    threads: Option<usize>,
+) -> Result<Array<f64, D>, ImgalError>
+where
+    A: AsArray<'a, T, D>,
+    D: Dimension,
+    T: 'a + AsNumeric,
+{
+    let data: ArrayBase<ViewRepr<&'a T>, D> = data.into();
+    let axis = axis.unwrap_or(data.ndim().saturating_sub(1));
+    check_axis(axis, data.ndim())?;
+    let mut factors = vec![1; data.ndim()];
+    factors[axis] = factor;
+    block_reduce(
+        data,
+        &factors,
+        mode.unwrap_or_default() == BinMode::Mean,
+        threads,
+    )
+}
+
+/// Downsample an n-dimensional image by summing blocks of pixels.
+///
+/// # Description
+///
+/// Downsamples an n-dimensional image by an integer factor per axis, where
+/// each output pixel is the sum of a block of `factors[0] × factors[1] × ...`
+/// input pixels (*i.e.* sum pooling or binning):
+///
+/// ```text
+/// y[i, j, ...] = Σ x[i × f₀ + p, j × f₁ + q, ...]
+/// ```
+///
+/// Unlike interpolation, binning is photon-conserving, the total of the
+/// downsampled image equals the total of the input image, so count data
+/// (*e.g.* FLIM decays) stays Poisson distributed for subsequent statistics
+/// and phasor analysis. The length of an axis is `⌈len / factor⌉`, a partial
+/// block at the end of an axis is summed over the remaining pixels so no
+/// counts are dropped. The blocks are reduced one axis at a time.
+///
+/// # Arguments
+///
+/// * `data`: The input n-dimensional image.
+/// * `factors`: The integer downsampling factor of each axis, a factor of `1`
+///   leaves the axis unchanged.
+/// * `threads`: The requested number of threads to use for parallel execution.
+///   If `None` or `Some(1)` sequential execution is used. If `Some(0)`, then
+///   the maximum available parallelism is used. Thread counts are clamped to
+///   the systems maximum.
+///
+/// # Returns
+///
+/// * `Ok(Array<f64, D>)`: The block summed image.
+/// * `Err(ImgalError)`: If `factors.len() != data.ndim()`. If a factor is `0`.
+pub fn downsample_sum<'a, T, A, D>(
+    data: A,
+    factors: &[usize],
+    threads: Option<usize>,
+) -> Result<Array<f64, D>, ImgalError>
+where
+    A: AsArray<'a, T, D>,
+    D: Dimension,
+    T: 'a + AsNumeric,
+{
+    block_reduce(data.into(), factors, false, threads)
+}
+
+/// Downsample an n-dimensional image by averaging blocks of pixels.
+///
+/// # Description
+///
+/// Downsamples an n-dimensional image by an integer factor per axis, where
+/// each output pixel is the mean of a block of `factors[0] × factors[1] × ...`
+/// input pixels (see `downsample_sum`). A partial block at the end of an axis
+/// is averaged over the remaining pixels.
+///
+/// # Arguments
+///
+/// * `data`: The input n-dimensional image.
+/// * `factors`: The integer downsampling factor of each axis, a factor of `1`
+///   leaves the axis unchanged.
+/// * `threads`: The requested number of threads to use for parallel execution.
+///   If `None` or `Some(1)` sequential execution is used. If `Some(0)`, then
+///   the maximum available parallelism is used. Thread counts are clamped to
+///   the systems maximum.
+///
+/// # Returns
+///
+/// * `Ok(Array<f64, D>)`: The block averaged image.
+/// * `Err(ImgalError)`: If `factors.len() != data.ndim()`. If a factor is `0`.
+pub fn downsample_mean<'a, T, A, D>(
+    data: A,
+    factors: &[usize],
+    threads: Option<usize>,
+) -> Result<Array<f64, D>, ImgalError>
+where
+    A: AsArray<'a, T, D>,
+    D: Dimension,
+    T: 'a + AsNumeric,
+{
+    block_reduce(data.into(), factors, true, threads)
+}
+
+/// Bin blocks of pixels across the spatial axes of an n-dimensional image.
+///
+/// # Description
+///
+/// Reduces each `k × k` (or `k × k × k`) block of pixels of the spatial axes
+/// to a single pixel by summing or averaging, leaving the other axes (*e.g.*
+/// the decay or channel axis) unchanged. Spatial binning is the standard way
+/// to increase the photon counts per pixel of FLIM data before phasor
+/// analysis or fitting, at the cost of spatial resolution:
+///
+/// ```text
+/// y[i, j, t] = Σₚ Σq x[i × k + p, j × k + q, t]
+/// ```
+///
+/// This is `downsample_sum` (or `downsample_mean`) with a factor of `k` on the
+/// spatial axes and `1` on the other axes. The length of a spatial axis is
+/// `⌈len / k⌉`, a partial block at the end of an axis is reduced over the
+/// remaining pixels.
+///
+/// # Arguments
+///
+/// * `data`: The input n-dimensional image.
+/// * `factor`: The block size `k` along each spatial axis.
+/// * `axes`: The spatial axes to bin, *e.g.* `&[0, 1]` for a
+///   `(row, col, t)` decay image. If `None` and `data` has 3 or more
+///   dimensions, all axes except the last (decay) axis are binned. If `None`
+///   and `data` has fewer than 3 dimensions, all axes are binned.
+/// * `mode`: The reduction of the pixels in a block. If `None`, then
+///   `mode = BinMode::Sum`.
+/// * `threads`: The requested number of threads to use for parallel execution.
+///   If `None` or `Some(1)` sequential execution is used. If `Some(0)`, then
+///   the maximum available parallelism is used. Thread counts are clamped to
+///   the systems maximum.
+///
+/// # Returns
+///
+/// * `Ok(Array<f64, D>)`: The binned image.
+/// * `Err(ImgalError)`: If an axis in `axes` is `>= data.ndim()` or repeated.
+///   If `factor == 0`.
+pub fn spatial_bin<'a, T, A, D>(
+    data: A,
+    factor: usize,
+    axes: Option<&[usize]>,
+    mode: Option<BinMode>,
+    threads: Option<usize>,
+) -> Result<Array<f64, D>, ImgalError>
+where
+    A: AsArray<'a, T, D>,
+    D: Dimension,
+    T: 'a + AsNumeric,
+{
+    let data: ArrayBase<ViewRepr<&'a T>, D> = data.into();
+    if factor == 0 {
+        return Err(ImgalError::InvalidParameterValueEqual {
+            param_name: "factor",
+            value: 0,
+        });
+    }
+    let mut factors = vec![1; data.ndim()];
+    match axes {
+        Some(axes) => {
+            let mut binned = vec![false; data.ndim()];
+            for &ax in axes {
+                check_axis(ax, data.ndim())?;
+                if std::mem::replace(&mut binned[ax], true) {
+                    return Err(ImgalError::InvalidGeneric {
+                        msg: "Invalid spatial axes, an axis is repeated.",
+                    });
+                }
+                factors[ax] = factor;
+            }
+        }
+        None => {
+            // the last axis is the decay axis of 3D or higher stacks
+            let n_spatial = if data.ndim() >= 3 {
+                data.ndim() - 1
+            } else {
+                data.ndim()
+            };
+            factors[..n_spatial].fill(factor);
+        }
+    }
+    block_reduce(
+        data,
+        &factors,
+        mode.unwrap_or_default() == BinMode::Mean,
+        threads,
+    )
+}
+
+/// Reduce blocks of an n-dimensional image by sum or mean, one axis at a time.
+fn block_reduce<T, D>(
+    data: ArrayBase<ViewRepr<&T>, D>,
+    factors: &[usize],
+    mean: bool,
+    threads: Option<usize>,
+) -> Result<Array<f64, D>, ImgalError>
+where
+    D: Dimension,
+    T: AsNumeric,
+{
+    if factors.len() != data.ndim() {
+        return Err(ImgalError::InvalidArrayLengthExpected {
+            arr_name: "factors",
+            expected: data.ndim(),
+            got: factors.len(),
+        });
+    }
+    if factors.contains(&0) {
+        return Err(ImgalError::InvalidParameterValueEqual {
+            param_name: "factors",
+            value: 0,
+        });
+    }
+    let mut reduced = data.mapv(|v| v.to_f64());
+    for (ax, &f) in factors.iter().enumerate() {
+        if f == 1 {
+            continue;
+        }
+        let mut shape = reduced.raw_dim();
+        shape[ax] = reduced.len_of(Axis(ax)).div_ceil(f);
+        let mut out = Array::<f64, D>::zeros(shape);
+        let reduce_lane = |src: ArrayView1<f64>, mut dst: ArrayViewMut1<f64>| {
+            let len = src.len();
+            dst.iter_mut().enumerate().for_each(|(k, d)| {
+                // the last block is partial if the axis length is not a
+                // multiple of the factor
+                let block = src.slice(s![k * f..((k + 1) * f).min(len)]);
+                let sum = block.sum();
+                *d = if mean { sum / block.len() as f64 } else { sum };
+            });
+        };
+        par!(threads,
+            seq_exp: Zip::from(reduced.lanes(Axis(ax)))
+                .and(out.lanes_mut(Axis(ax)))
+                .for_each(reduce_lane),
+            par_exp: Zip::from(reduced.lanes(Axis(ax)))
+                .and(out.lanes_mut(Axis(ax)))
+                .par_for_each(reduce_lane));
+        reduced = out;
+    }
+    Ok(reduced)
+}
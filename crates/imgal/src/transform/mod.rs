@@ -1,5 +1,12 @@
 //! Image transformation functions.
 
+pub mod blend;
+pub mod downsample;
 pub mod pad;
+pub mod prefetch;
 pub mod project;
+pub mod pyramid;
+pub mod shift;
 pub mod tile;
+pub mod upsample;
+pub mod wells;
@@ -0,0 +1,115 @@
+use std::sync::mpsc::{Receiver, sync_channel};
+use std::thread::{self, JoinHandle};
+
+use crate::prelude::*;
+
+/// A background-thread tile prefetcher for streaming tiled pipelines.
+///
+/// The `TilePrefetcher` loads the tiles of a tiled pipeline (*e.g.* zarr or
+/// TIFF chunks read from disk) on a background thread while the caller
+/// processes the previous tiles, hiding the I/O latency. Up to `depth` loaded
+/// tiles are buffered ahead of the consumer, bounding the memory use. The
+/// tiles are yielded in order as an `Iterator`. Dropping the prefetcher stops
+/// loading after the tile currently being loaded. If the loader panics, the
+/// panic is propagated to the consumer on the next call to `next` instead of
+/// ending the stream early.
+#[derive(Debug)]
+pub struct TilePrefetcher<R> {
+    /// The receiving end of the loaded tile queue.
+    receiver: Option<Receiver<R>>,
+    /// The background loader thread.
+    handle: Option<JoinHandle<()>>,
+    /// The number of tiles to load.
+    n_tiles: usize,
+    /// The number of tiles yielded so far.
+    n_delivered: usize,
+}
+
+impl<R> TilePrefetcher<R>
+where
+    R: Send + 'static,
+{
+    /// Create a new tile prefetcher and start loading tiles.
+    ///
+    /// # Description
+    ///
+    /// Spawns a background thread that calls `loader` for the tile indices
+    /// `0` to `n_tiles - 1` in order, keeping at most `depth` loaded tiles
+    /// buffered. The loader is typically a closure that reads a chunk from a
+    /// file or store, and returns (*e.g.*) a `Result` with the tile array.
+    ///
+    /// # Arguments
+    ///
+    /// * `n_tiles`: The number of tiles to load.
+    /// * `depth`: The maximum number of loaded tiles buffered ahead of the
+    ///   consumer. If `None`, then `depth = 2`.
+    /// * `loader`: The function that loads the tile with the given index.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(TilePrefetcher<R>)`: The tile prefetcher, an iterator over the
+    ///   loaded tiles.
+    /// * `Err(ImgalError)`: If `depth == 0`.
+    pub fn new<F>(n_tiles: usize, depth: Option<usize>, mut loader: F) -> Result<Self, ImgalError>
+    where
+        F: FnMut(usize) -> R + Send + 'static,
+    {
+        let depth = depth.unwrap_or(2);
+        if depth == 0 {
+            return Err(ImgalError::InvalidParameterValueEqual {
+                param_name: "depth",
+                value: 0,
+            });
+        }
+        // the bounded channel blocks the loader once "depth" tiles are queued
+        let (sender, receiver) = sync_channel(depth);
+        let handle = thread::spawn(move || {
+            for i in 0..n_tiles {
+                if sender.send(loader(i)).is_err() {
+                    break;
+                }
+            }
+        });
+        Ok(Self {
+            receiver: Some(receiver),
+            handle: Some(handle),
+            n_tiles,
+            n_delivered: 0,
+        })
+    }
+}
+
+impl<R> Iterator for TilePrefetcher<R> {
+    type Item = R;
+
+    fn next(&mut self) -> Option<R> {
+        match self.receiver.as_ref()?.recv() {
+            Ok(tile) => {
+                self.n_delivered += 1;
+                Some(tile)
+            }
+            Err(_) => {
+                // the queue disconnects before the last tile only if the
+                // loader thread panicked, re-raise that panic here
+                self.receiver.take();
+                if let Some(handle) = self.handle.take()
+                    && let Err(payload) = handle.join()
+                    && self.n_delivered < self.n_tiles
+                {
+                    std::panic::resume_unwind(payload);
+                }
+                None
+            }
+        }
+    }
+}
+
+impl<R> Drop for TilePrefetcher<R> {
+    fn drop(&mut self) {
+        // disconnect the queue first so that a blocked loader thread exits
+        self.receiver.take();
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
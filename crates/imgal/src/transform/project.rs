@@ -2,7 +2,7 @@ use ndarray::{Array, ArrayBase, AsArray, Axis, Dimension, RemoveAxis, ViewRepr,
 use rustfft::num_traits::Zero;
 
 use crate::prelude::*;
-use crate::statistics::sum;
+use crate::statistics::checked_sum;
 
 /// Project an n-dimensional image by summing along a specified axis.
 ///
@@ -27,7 +27,7 @@ use crate::statistics::sum;
 ///
 /// * `Ok(Array<T, D::Smaller>)`: The sum projected image.
 /// * `Err(ImgalError)`: If `axis` is greater than or equal to the number of
-///   dimensions.
+///   dimensions. If the sum of integer values overflows the range of `T`.
 pub fn sum_project<'a, T, A, D>(
     data: A,
     axis: Option<usize>,
@@ -48,7 +48,11 @@ where
         });
     }
     let lanes = data.lanes(Axis(axis));
-    Ok(par!(threads,
-        seq_exp: Zip::from(lanes).map_collect(|l| sum(l, Some(1))),
-        par_exp: Zip::from(lanes).par_map_collect(|l| l.sum())))
+    let sums = par!(threads,
+        seq_exp: Zip::from(lanes).map_collect(|l| checked_sum(l, Some(1))),
+        par_exp: Zip::from(lanes).par_map_collect(|l| checked_sum(l, Some(1))));
+    if let Some(Err(e)) = sums.iter().find(|s| s.is_err()) {
+        return Err(e.clone());
+    }
+    Ok(sums.mapv(|s| s.unwrap_or_default()))
 }
@@ -0,0 +1,266 @@
+use ndarray::{ArrayBase, ArrayD, AsArray, Dimension, Slice, ViewRepr};
+
+use crate::filter::{correlate_axis, gaussian_kernel_1d};
+use crate::prelude::*;
+use crate::validate::check_axis;
+
+/// Create a Gaussian image pyramid of an n-dimensional image.
+///
+/// # Description
+///
+/// Creates a multiscale image pyramid (*e.g.* for OME-NGFF multiscale output,
+/// see `ngff_multiscales_metadata`) by repeatedly smoothing the previous level
+/// with a separable Gaussian filter and keeping every `downscale`-th pixel
+/// along the downsampled axes:
+///
+/// ```text
+/// levelₖ₊₁ = (G_σ * levelₖ)[::downscale]
+/// ```
+///
+/// The Gaussian smoothing suppresses aliasing. The image borders are extended
+/// by reflection (see `BorderMode::Reflect`). The
+/// length of a downsampled axis at level `k` is `⌈len / downscaleᵏ⌉`.
+///
+/// # Arguments
+///
+/// * `data`: The input n-dimensional image.
+/// * `levels`: The number of pyramid levels, including the full resolution
+///   level.
+/// * `downscale`: The downsampling factor between consecutive levels. If
+///   `None`, then `downscale = 2`.
+/// * `sigma`: The standard deviation of the Gaussian filter in pixels. If
+///   `None`, then `sigma = 2 × downscale / 6`.
+/// * `axes`: The axes to downsample, *i.e.* the spatial axes (*e.g.*
+///   `[1, 2, 3]` of a `czyx` image). Channel and time axes should not be
+///   included, as they would be smoothed and subsampled.
+/// * `threads`: The requested number of threads to use for parallel execution.
+///   If `None` or `Some(1)` sequential execution is used. If `Some(0)`, then
+///   the maximum available parallelism is used. Thread counts are clamped to
+///   the systems maximum.
+///
+/// # Returns
+///
+/// * `Ok(Vec<ArrayD<f64>>)`: The pyramid levels from full to lowest
+///   resolution. The length of the vector is `levels`.
+/// * `Err(ImgalError)`: If `levels == 0`. If `downscale < 2`. If
+///   `sigma <= 0.0`. If an axis in `axes` is `>= data.ndim()`.
+pub fn pyramid_gaussian<'a, T, A, D>(
+    data: A,
+    levels: usize,
+    downscale: Option<usize>,
+    sigma: Option<f64>,
+    axes: &[usize],
+    threads: Option<usize>,
+) -> Result<Vec<ArrayD<f64>>, ImgalError>
+where
+    A: AsArray<'a, T, D>,
+    D: Dimension,
+    T: 'a + AsNumeric,
+{
+    let data: ArrayBase<ViewRepr<&'a T>, D> = data.into();
+    if levels == 0 {
+        return Err(ImgalError::InvalidParameterValueEqual {
+            param_name: "levels",
+            value: 0,
+        });
+    }
+    let downscale = downscale.unwrap_or(2);
+    if downscale < 2 {
+        return Err(ImgalError::InvalidParameterValueLess {
+            param_name: "downscale",
+            value: 2,
+        });
+    }
+    let sigma = sigma.unwrap_or(2.0 * downscale as f64 / 6.0);
+    if sigma <= 0.0 {
+        return Err(ImgalError::InvalidParameterValueOutsideRange {
+            param_name: "sigma",
+            value: sigma,
+            min: 0.0,
+            max: f64::INFINITY,
+        });
+    }
+    check_downsampled_axes(axes, data.ndim())?;
+    let kernel = gaussian_kernel_1d(sigma);
+    let mut pyramid: Vec<ArrayD<f64>> = Vec::with_capacity(levels);
+    pyramid.push(data.mapv(|v| v.to_f64()).into_dyn());
+    for _ in 1..levels {
+        let mut level = pyramid.last().unwrap().clone();
+        for &ax in axes.iter() {
+            correlate_axis(&mut level, ax, &kernel, BorderMode::Reflect, threads)?;
+        }
+        let level = level
+            .slice_each_axis(|ad| {
+                if axes.contains(&ad.axis.index()) {
+                    Slice::new(0, None, downscale as isize)
+                } else {
+                    Slice::from(..)
+                }
+            })
+            .to_owned();
+        pyramid.push(level);
+    }
+    Ok(pyramid)
+}
+
+/// Create the OME-NGFF multiscales metadata of an image pyramid.
+///
+/// # Description
+///
+/// Creates the OME-NGFF (version `0.4`) `multiscales` JSON attributes (*i.e.*
+/// the `.zattrs` content of the image group) for a pyramid created with
+/// `pyramid_gaussian`, so that viewers such as napari and neuroglancer open
+/// the multiscale image. The pyramid levels are the datasets `"0"`, `"1"`,
+/// ... of the group, and the scale of level `k` along a downsampled axis is
+/// `spacing × downscaleᵏ`. The axis types are derived from the axis names:
+/// `t` is a time axis, `c` is a channel axis and all other axes are space
+/// axes with the physical `unit`. The `name`, `unit` and axis names are
+/// escaped as JSON strings.
+///
+/// # Arguments
+///
+/// * `name`: The name of the image.
+/// * `axes`: The unique axis names in order, one character per axis (*e.g.*
+///   `"czyx"`).
+/// * `spacing`: The physical pixel spacing of each axis at full resolution.
+/// * `unit`: The physical unit of the space axes. If `None`, then
+///   `unit = "micrometer"`.
+/// * `levels`: The number of pyramid levels.
+/// * `downscale`: The downsampling factor between consecutive levels.
+/// * `downsampled`: The downsampled axes. If `None`, all space axes (*i.e.*
+///   all axes except `t` and `c`) are downsampled.
+///
+/// # Returns
+///
+/// * `Ok(String)`: The OME-NGFF multiscales metadata JSON.
+/// * `Err(ImgalError)`: If `spacing.len() != axes.len()`. If `axes` contains
+///   an axis name more than once. If a `spacing` value is not finite or
+///   `<= 0.0`. If `levels == 0`. If `downscale < 2`. If an axis in
+///   `downsampled` is `>= axes.len()`.
+///
+/// # Reference
+///
+/// <https://ngff.openmicroscopy.org/0.4/>
+pub fn ngff_multiscales_metadata(
+    name: &str,
+    axes: &str,
+    spacing: &[f64],
+    unit: Option<&str>,
+    levels: usize,
+    downscale: usize,
+    downsampled: Option<&[usize]>,
+) -> Result<String, ImgalError> {
+    let names: Vec<char> = axes.chars().collect();
+    if spacing.len() != names.len() {
+        return Err(ImgalError::MismatchedArrayLengths {
+            a_arr_name: "spacing",
+            a_arr_len: spacing.len(),
+            b_arr_name: "axes",
+            b_arr_len: names.len(),
+        });
+    }
+    if names
+        .iter()
+        .enumerate()
+        .any(|(i, n)| names[i + 1..].contains(n))
+    {
+        return Err(ImgalError::InvalidGeneric {
+            msg: "Invalid axes, each axis name must be unique.",
+        });
+    }
+    if let Some(&s) = spacing.iter().find(|s| !s.is_finite() || **s <= 0.0) {
+        return Err(ImgalError::InvalidParameterValueOutsideRange {
+            param_name: "spacing",
+            value: s,
+            min: 0.0,
+            max: f64::INFINITY,
+        });
+    }
+    if levels == 0 {
+        return Err(ImgalError::InvalidParameterValueEqual {
+            param_name: "levels",
+            value: 0,
+        });
+    }
+    if downscale < 2 {
+        return Err(ImgalError::InvalidParameterValueLess {
+            param_name: "downscale",
+            value: 2,
+        });
+    }
+    let downsampled: Vec<usize> = match downsampled {
+        Some(d) => {
+            check_downsampled_axes(d, names.len())?;
+            d.to_vec()
+        }
+        None => (0..names.len())
+            .filter(|&ax| !matches!(names[ax], 't' | 'c'))
+            .collect(),
+    };
+    let unit = json_string(unit.unwrap_or("micrometer"));
+    let axes_json: Vec<String> = names
+        .iter()
+        .map(|&n| {
+            let n_json = json_string(n.encode_utf8(&mut [0; 4]));
+            match n {
+                't' => format!("{{\"name\": {}, \"type\": \"time\"}}", n_json),
+                'c' => format!("{{\"name\": {}, \"type\": \"channel\"}}", n_json),
+                _ => format!(
+                    "{{\"name\": {}, \"type\": \"space\", \"unit\": {}}}",
+                    n_json, unit
+                ),
+            }
+        })
+        .collect();
+    let datasets_json: Vec<String> = (0..levels)
+        .map(|k| {
+            let scale: Vec<String> = spacing
+                .iter()
+                .enumerate()
+                .map(|(ax, s)| {
+                    if downsampled.contains(&ax) {
+                        format!("{:?}", s * (downscale as f64).powi(k as i32))
+                    } else {
+                        format!("{:?}", s)
+                    }
+                })
+                .collect();
+            format!(
+                "{{\"path\": \"{}\", \"coordinateTransformations\": [{{\"type\": \"scale\", \"scale\": [{}]}}]}}",
+                k,
+                scale.join(", ")
+            )
+        })
+        .collect();
+    Ok(format!(
+        "{{\"multiscales\": [{{\"version\": \"0.4\", \"name\": {}, \"axes\": [{}], \"datasets\": [{}], \"type\": \"gaussian\"}}]}}",
+        json_string(name),
+        axes_json.join(", "),
+        datasets_json.join(", ")
+    ))
+}
+
+/// Quote and escape a string as a JSON string.
+fn json_string(value: &str) -> String {
+    let mut out = String::with_capacity(value.len() + 2);
+    out.push('"');
+    value.chars().for_each(|ch| match ch {
+        '"' => out.push_str("\\\""),
+        '\\' => out.push_str("\\\\"),
+        '\n' => out.push_str("\\n"),
+        '\r' => out.push_str("\\r"),
+        '\t' => out.push_str("\\t"),
+        c if c.is_control() => out.push_str(&format!("\\u{:04x}", c as u32)),
+        c => out.push(c),
+    });
+    out.push('"');
+    out
+}
+
+/// Validate that each downsampled axis is an axis of an `n_dims` image.
+fn check_downsampled_axes(axes: &[usize], n_dims: usize) -> Result<(), ImgalError> {
+    for &ax in axes.iter() {
+        check_axis(ax, n_dims)?;
+    }
+    Ok(())
+}
@@ -2,6 +2,7 @@ use ndarray::{ArrayBase, ArrayD, ArrayView, AsArray, Axis, Dimension, IxDyn, Sli
 use rayon::prelude::*;
 
 use crate::prelude::*;
+use crate::validate::check_shapes;
 
 /// Tile an n-dimensional image using division tiling.
 ///
@@ -149,14 +150,12 @@ where
         });
     }
     let tile_shape: Vec<usize> = shape.iter().map(|&v| v / div).collect();
-    if tile_shape != tile_stack[0].shape() {
-        return Err(ImgalError::MismatchedArrayShapes {
-            a_arr_name: "expected tile",
-            a_shape: tile_shape,
-            b_arr_name: "input tile",
-            b_shape: tile_stack[0].shape().to_vec(),
-        });
-    }
+    check_shapes(
+        "expected tile",
+        &tile_shape,
+        "input tile",
+        tile_stack[0].shape(),
+    )?;
     let mut untile_arr: ArrayD<T> = ArrayD::from_elem(IxDyn(shape), T::default());
     (0..n_tiles).for_each(|t| {
         let tile_view = tile_stack[t].view();
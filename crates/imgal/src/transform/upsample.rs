@@ -0,0 +1,114 @@
+use ndarray::{
+    Array, ArrayBase, ArrayView1, ArrayViewMut1, AsArray, Axis, Dimension, ViewRepr, Zip,
+};
+use rustfft::{FftPlanner, num_complex::Complex, num_traits::Zero};
+
+use crate::prelude::*;
+
+/// Upsample an n-dimensional image with band-limited Fourier interpolation.
+///
+/// # Description
+///
+/// Upsamples an n-dimensional image (*e.g.* a 1D decay curve or a 2D image)
+/// by an integer factor per axis with sinc interpolation, implemented by
+/// zero-padding the spectrum in the frequency domain. Along each upsampled
+/// axis the lanes are transformed with the FFT, the spectrum of length `n` is
+/// padded with zeros at the high frequencies to length `n × factor` and
+/// transformed back:
+///
+/// ```text
+/// y = IFFT(pad(FFT(x))) × factor
+/// ```
+///
+/// The Nyquist bin of even length axes is split equally between the positive
+/// and negative frequencies, so real inputs give real outputs. The upsampled
+/// image is band-limited, it passes through the original samples (*i.e.*
+/// `y[k × factor] = x[k]`) and conserves the mean. The input is treated as
+/// periodic, so discontinuities between the first and last sample of an axis
+/// cause ringing near the borders.
+///
+/// # Arguments
+///
+/// * `data`: The input n-dimensional image.
+/// * `factors`: The integer upsampling factor of each axis, a factor of `1`
+///   leaves the axis unchanged.
+/// * `threads`: The requested number of threads to use for parallel execution.
+///   If `None` or `Some(1)` sequential execution is used. If `Some(0)`, then
+///   the maximum available parallelism is used. Thread counts are clamped to
+///   the systems maximum.
+///
+/// # Returns
+///
+/// * `Ok(Array<f64, D>)`: The upsampled image, where the length of each axis
+///   is multiplied by its factor.
+/// * `Err(ImgalError)`: If `factors.len() != data.ndim()`. If a factor is `0`.
+pub fn fourier_upsample<'a, T, A, D>(
+    data: A,
+    factors: &[usize],
+    threads: Option<usize>,
+) -> Result<Array<f64, D>, ImgalError>
+where
+    A: AsArray<'a, T, D>,
+    D: Dimension,
+    T: 'a + AsNumeric,
+{
+    let data: ArrayBase<ViewRepr<&'a T>, D> = data.into();
+    if factors.len() != data.ndim() {
+        return Err(ImgalError::InvalidArrayLengthExpected {
+            arr_name: "factors",
+            expected: data.ndim(),
+            got: factors.len(),
+        });
+    }
+    if factors.contains(&0) {
+        return Err(ImgalError::InvalidParameterValueEqual {
+            param_name: "factors",
+            value: 0,
+        });
+    }
+    let mut upsampled = data.mapv(|v| v.to_f64());
+    let mut planner = FftPlanner::new();
+    for (ax, &f) in factors.iter().enumerate() {
+        let n = upsampled.len_of(Axis(ax));
+        if f == 1 || n == 0 {
+            continue;
+        }
+        let m = n * f;
+        let fft = planner.plan_fft_forward(n);
+        let ifft = planner.plan_fft_inverse(m);
+        let mut shape = upsampled.raw_dim();
+        shape[ax] = m;
+        let mut out = Array::<f64, D>::zeros(shape);
+        // the forward transform is unnormalized and the inverse is scaled by
+        // "1 / n" to preserve the sample values
+        let scale = 1.0 / n as f64;
+        let n_pos = n.div_ceil(2);
+        let upsample_lane = |src: ArrayView1<f64>, mut dst: ArrayViewMut1<f64>| {
+            let mut spec: Vec<Complex<f64>> = src.iter().map(|&v| Complex::new(v, 0.0)).collect();
+            fft.process(&mut spec);
+            let mut buf = vec![Complex::zero(); m];
+            buf[..n_pos].copy_from_slice(&spec[..n_pos]);
+            buf[m - (n - n_pos)..].copy_from_slice(&spec[n_pos..]);
+            if n.is_multiple_of(2) {
+                // split the Nyquist bin between the positive and negative
+                // frequencies
+                let nyquist = spec[n / 2] * 0.5;
+                buf[n / 2] = nyquist;
+                buf[m - n / 2] = nyquist;
+            }
+            ifft.process(&mut buf);
+            dst.iter_mut()
+                .zip(buf.iter())
+                .for_each(|(d, v)| *d = v.re * scale);
+        };
+        par!(threads,
+            seq_exp: Zip::from(upsampled.lanes(Axis(ax)))
+                .and(out.lanes_mut(Axis(ax)))
+                .for_each(upsample_lane),
+            par_exp: Zip::from(upsampled.lanes(Axis(ax)))
+                .and(out.lanes_mut(Axis(ax)))
+                .par_for_each(upsample_lane));
+        upsampled = out;
+    }
+    Ok(upsampled)
+}
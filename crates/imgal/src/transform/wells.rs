@@ -0,0 +1,265 @@
+use ndarray::{ArrayBase, ArrayView2, AsArray, Axis, Ix2, Slice, ViewRepr};
+use rayon::prelude::*;
+
+use crate::prelude::*;
+
+/// The well grid geometry output of `detect_well_grid`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WellGrid {
+    /// The center of the first (top-left) well, *i.e.* `(row, col)`.
+    pub offset: (f64, f64),
+    /// The center-to-center well spacing along the row and column axes.
+    pub spacing: (f64, f64),
+}
+
+/// Detect the well grid geometry of a 2D plate or grid image.
+///
+/// # Description
+///
+/// Detects the well centers of a scanned multi-well plate (or any regular grid
+/// of sub-images) with a known number of rows and columns from the intensity
+/// projections of the image. The image is mean projected onto each axis, the
+/// projection is smoothed with a box filter and the grid offset `o` and
+/// spacing `s` are found by maximizing the contrast between the well centers
+/// and the gaps between them:
+///
+/// ```text
+/// score(o, s) = mean(P(o + i × s)) - mean(P(o + (i + 0.5) × s))
+/// ```
+///
+/// Where `P` is the smoothed projection. The spacing is searched in steps of
+/// `0.5` pixels between `len / (2 × n)` and `(len - 1) / (n - 1)`, where `n`
+/// is the number of wells along the axis. The wells are assumed to be brighter
+/// than the gaps, invert the image (*e.g.* for brightfield scans) otherwise.
+///
+/// # Arguments
+///
+/// * `data`: The input 2D plate image.
+/// * `grid`: The number of well rows and columns, *i.e.* `(rows, cols)`.
+/// * `threads`: The requested number of threads to use for parallel execution.
+///   If `None` or `Some(1)` sequential execution is used. If `Some(0)`, then
+///   the maximum available parallelism is used. Thread counts are clamped to
+///   the systems maximum.
+///
+/// # Returns
+///
+/// * `Ok(WellGrid)`: The center of the first (top-left) well and the
+///   center-to-center well spacing along the row and column axes.
+/// * `Err(ImgalError)`: If `grid.0 == 0` or `grid.1 == 0`. If an axis of
+///   `data` is shorter than twice the number of wells along the axis.
+pub fn detect_well_grid<'a, T, A>(
+    data: A,
+    grid: (usize, usize),
+    threads: Option<usize>,
+) -> Result<WellGrid, ImgalError>
+where
+    A: AsArray<'a, T, Ix2>,
+    T: 'a + AsNumeric,
+{
+    let data: ArrayBase<ViewRepr<&'a T>, Ix2> = data.into();
+    validate_grid(grid)?;
+    let counts = [grid.0, grid.1];
+    for (axis_idx, (&len, &n)) in data.shape().iter().zip(counts.iter()).enumerate() {
+        if len < 2 * n {
+            return Err(ImgalError::InvalidAxisLengthLess {
+                arr_name: "data",
+                axis_idx,
+                value: 2 * n,
+            });
+        }
+    }
+    let fit_axis = |axis: usize| -> (f64, f64) {
+        // the mean projection onto "axis"
+        let profile: Vec<f64> = data
+            .axis_iter(Axis(axis))
+            .map(|ln| ln.iter().map(|v| v.to_f64()).sum::<f64>() / ln.len() as f64)
+            .collect();
+        fit_grid_axis(&profile, counts[axis])
+    };
+    let ((o_r, s_r), (o_c, s_c)) = par!(threads,
+        seq_exp: (fit_axis(0), fit_axis(1)),
+        par_exp: rayon::join(|| fit_axis(0), || fit_axis(1)));
+    Ok(WellGrid {
+        offset: (o_r, o_c),
+        spacing: (s_r, s_c),
+    })
+}
+
+/// Split a 2D plate or grid image into per-well sub-images.
+///
+/// # Description
+///
+/// Slices a large scanned multi-well plate (or any regular grid of sub-images)
+/// into per-well sub-images given the grid geometry. The center of the well in
+/// row `i` and column `j` is:
+///
+/// ```text
+/// center(i, j) = (offset.0 + i × spacing.0, offset.1 + j × spacing.1)
+/// ```
+///
+/// Each well is cropped with `well_shape` around its center (rounded to the
+/// nearest pixel). If `spacing` or `offset` is `None`, the grid geometry is
+/// detected with `detect_well_grid`. The wells are views into `data`, in
+/// row-major (*i.e.* A1, A2, ..., B1, ...) order.
+///
+/// # Arguments
+///
+/// * `data`: The input 2D plate image.
+/// * `grid`: The number of well rows and columns, *i.e.* `(rows, cols)`.
+/// * `spacing`: The center-to-center well spacing along the row and column
+///   axes. If `None`, the spacing is detected from `data`.
+/// * `offset`: The center of the first (top-left) well. If `None`, the offset
+///   is detected from `data`.
+/// * `well_shape`: The shape of the well sub-images. If `None`, then
+///   `well_shape = (floor(spacing.0), floor(spacing.1))`.
+/// * `threads`: The requested number of threads to use for parallel execution.
+///   If `None` or `Some(1)` sequential execution is used. If `Some(0)`, then
+///   the maximum available parallelism is used. Thread counts are clamped to
+///   the systems maximum.
+///
+/// # Returns
+///
+/// * `Ok(Vec<ArrayView2<'a, T>>)`: A vector containing views of all wells in
+///   row-major order. The length of the vector is `grid.0 × grid.1`.
+/// * `Err(ImgalError)`: If `grid.0 == 0` or `grid.1 == 0`. If `spacing` or
+///   `well_shape` contain values `<= 0`. If a well extends beyond the image
+///   borders. If the grid detection fails.
+pub fn split_wells<'a, T, A>(
+    data: A,
+    grid: (usize, usize),
+    spacing: Option<(f64, f64)>,
+    offset: Option<(f64, f64)>,
+    well_shape: Option<(usize, usize)>,
+    threads: Option<usize>,
+) -> Result<Vec<ArrayView2<'a, T>>, ImgalError>
+where
+    A: AsArray<'a, T, Ix2>,
+    T: 'a + AsNumeric,
+{
+    let data: ArrayBase<ViewRepr<&'a T>, Ix2> = data.into();
+    validate_grid(grid)?;
+    let (offset, spacing) = match (offset, spacing) {
+        (Some(o), Some(s)) => (o, s),
+        (o, s) => {
+            let detected = detect_well_grid(data.view(), grid, threads)?;
+            (o.unwrap_or(detected.offset), s.unwrap_or(detected.spacing))
+        }
+    };
+    for (name, value) in [("spacing.0", spacing.0), ("spacing.1", spacing.1)] {
+        if value <= 0.0 {
+            return Err(ImgalError::InvalidParameterValueOutsideRange {
+                param_name: name,
+                value,
+                min: 0.0,
+                max: f64::INFINITY,
+            });
+        }
+    }
+    let well_shape = well_shape.unwrap_or((
+        (spacing.0.floor() as usize).max(1),
+        (spacing.1.floor() as usize).max(1),
+    ));
+    if well_shape.0 == 0 || well_shape.1 == 0 {
+        return Err(ImgalError::InvalidParameterValueLess {
+            param_name: "well_shape",
+            value: 1,
+        });
+    }
+    let (rows, cols) = data.dim();
+    // the start position of a well along an axis
+    let well_start = |o: f64, s: f64, i: usize, size: usize, len: usize| -> Option<isize> {
+        let start = (o + i as f64 * s - (size as f64 - 1.0) / 2.0).round() as isize;
+        (start >= 0 && start as usize + size <= len).then_some(start)
+    };
+    let mut positions: Vec<(isize, isize)> = Vec::with_capacity(grid.0 * grid.1);
+    for i in 0..grid.0 {
+        for j in 0..grid.1 {
+            let r = well_start(offset.0, spacing.0, i, well_shape.0, rows);
+            let c = well_start(offset.1, spacing.1, j, well_shape.1, cols);
+            match (r, c) {
+                (Some(r), Some(c)) => positions.push((r, c)),
+                _ => {
+                    return Err(ImgalError::InvalidGeneric {
+                        msg: "A well extends beyond the borders of the plate image.",
+                    });
+                }
+            }
+        }
+    }
+    let well_view = |&(r, c): &(isize, isize)| {
+        let mut well = data;
+        well.slice_axis_inplace(Axis(0), Slice::from(r..r + well_shape.0 as isize));
+        well.slice_axis_inplace(Axis(1), Slice::from(c..c + well_shape.1 as isize));
+        well
+    };
+    Ok(par!(threads,
+        seq_exp: positions.iter().map(well_view).collect::<Vec<ArrayView2<T>>>(),
+        par_exp: positions.par_iter().map(well_view).collect::<Vec<ArrayView2<T>>>()))
+}
+
+/// Fit the offset and spacing of `n` evenly spaced wells to a 1D projection.
+fn fit_grid_axis(profile: &[f64], n: usize) -> (f64, f64) {
+    let len = profile.len();
+    if n == 1 {
+        let center = profile
+            .iter()
+            .enumerate()
+            .fold(
+                (0, f64::MIN),
+                |acc, (i, &v)| if v > acc.1 { (i, v) } else { acc },
+            )
+            .0;
+        return (center as f64, len as f64);
+    }
+    // smooth the projection with a box filter a quarter of the minimum spacing
+    let half = len / (8 * n);
+    let smooth: Vec<f64> = (0..len)
+        .map(|i| {
+            let lo = i.saturating_sub(half);
+            let hi = (i + half).min(len - 1);
+            profile[lo..=hi].iter().sum::<f64>() / (hi - lo + 1) as f64
+        })
+        .collect();
+    let sample = |x: f64| -> f64 {
+        let i = (x.floor() as usize).min(len - 1);
+        let frac = x - i as f64;
+        if i + 1 < len {
+            smooth[i] * (1.0 - frac) + smooth[i + 1] * frac
+        } else {
+            smooth[i]
+        }
+    };
+    let s_min = len as f64 / (2 * n) as f64;
+    let s_max = (len - 1) as f64 / (n - 1) as f64;
+    let mut best = (0.0, s_max, f64::MIN);
+    let mut s = s_min;
+    while s <= s_max {
+        let span = (n - 1) as f64 * s;
+        let mut o = 0.0;
+        while o + span <= (len - 1) as f64 {
+            let centers = (0..n).map(|i| sample(o + i as f64 * s)).sum::<f64>() / n as f64;
+            let gaps = (0..n - 1)
+                .map(|i| sample(o + (i as f64 + 0.5) * s))
+                .sum::<f64>()
+                / (n - 1) as f64;
+            let score = centers - gaps;
+            if score > best.2 {
+                best = (o, s, score);
+            }
+            o += 1.0;
+        }
+        s += 0.5;
+    }
+    (best.0, best.1)
+}
+
+/// Validate that the grid has at least one row and column.
+fn validate_grid(grid: (usize, usize)) -> Result<(), ImgalError> {
+    if grid.0 == 0 || grid.1 == 0 {
+        return Err(ImgalError::InvalidParameterValueLess {
+            param_name: "grid",
+            value: 1,
+        });
+    }
+    Ok(())
+}
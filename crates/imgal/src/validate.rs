@@ -0,0 +1,104 @@
+//! Parameter validation helpers.
+//!
+//! This module provides shared up front checks of array shapes, axes, masks
+//! and ROI coordinates, so that invalid inputs return an `ImgalError` instead
+//! of panicking deep inside ndarray indexing.
+
+use std::collections::HashMap;
+
+use ndarray::Array2;
+
+use crate::prelude::*;
+
+/// Check that an axis is within the bounds of an array's dimensions.
+///
+/// # Arguments
+///
+/// * `axis`: The axis index.
+/// * `n_dims`: The number of dimensions of the array.
+///
+/// # Returns
+///
+/// * `Ok(())`: If `axis < n_dims`.
+/// * `Err(ImgalError)`: If `axis >= n_dims`.
+pub fn check_axis(axis: usize, n_dims: usize) -> Result<(), ImgalError> {
+    if axis >= n_dims {
+        return Err(ImgalError::InvalidAxis {
+            axis_idx: axis,
+            dim_len: n_dims,
+        });
+    }
+    Ok(())
+}
+
+/// Check that the shapes of two arrays (*e.g.* an image pair or an image and
+/// its mask) match.
+///
+/// # Arguments
+///
+/// * `a_name`: The name of the first array.
+/// * `a_shape`: The shape of the first array.
+/// * `b_name`: The name of the second array.
+/// * `b_shape`: The shape of the second array.
+///
+/// # Returns
+///
+/// * `Ok(())`: If the shapes match.
+/// * `Err(ImgalError)`: If `a_shape != b_shape`.
+pub fn check_shapes(
+    a_name: &'static str,
+    a_shape: &[usize],
+    b_name: &'static str,
+    b_shape: &[usize],
+) -> Result<(), ImgalError> {
+    if a_shape != b_shape {
+        return Err(ImgalError::MismatchedArrayShapes {
+            a_arr_name: a_name,
+            a_shape: a_shape.to_vec(),
+            b_arr_name: b_name,
+            b_shape: b_shape.to_vec(),
+        });
+    }
+    Ok(())
+}
+
+/// Check that the points of a ROI map index into an array of the given shape.
+///
+/// # Arguments
+///
+/// * `rois`: A map of point clouds representing Regions of Interest (ROIs),
+///   each with shape `(p, D)`.
+/// * `shape`: The shape of the indexed array.
+///
+/// # Returns
+///
+/// * `Ok(())`: If all ROI points are within the bounds of `shape`.
+/// * `Err(ImgalError)`: If the dimensionality of a ROI does not match
+///   `shape.len()`. If a ROI point is out of bounds of `shape`.
+pub fn check_roi_bounds(
+    rois: &HashMap<u64, Array2<usize>>,
+    shape: &[usize],
+) -> Result<(), ImgalError> {
+    for (&k, v) in rois.iter() {
+        if v.ncols() != shape.len() {
+            return Err(ImgalError::InvalidAxisLengthExpected {
+                arr_name: "rois",
+                axis_idx: 1,
+                expected: shape.len(),
+                got: v.ncols(),
+            });
+        }
+        if let Some(p) = v
+            .rows()
+            .into_iter()
+            .find(|p| p.iter().zip(shape.iter()).any(|(&c, &s)| c >= s))
+        {
+            return Err(ImgalError::InvalidRoiCoordinates {
+                roi_label: k,
+                coords: p.to_vec(),
+                shape: shape.to_vec(),
+            });
+        }
+    }
+    Ok(())
+}
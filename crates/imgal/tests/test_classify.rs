@@ -0,0 +1,81 @@
+use ndarray::{Array2, Array3, Axis};
+
+use imgal::classify::postprocess;
+use imgal::prelude::*;
+
+const THREADS: Option<usize> = Some(0);
+
+/// Create two class probability maps of a left (class 0) and right (class 1)
+/// half, with a misclassified pixel and a misclassified 2x2 block.
+fn half_plane_probs() -> Array3<f64> {
+    let mut probs = Array3::<f64>::zeros((2, 20, 20));
+    for r in 0..20 {
+        for c in 0..20 {
+            let p1 = if c < 10 { 0.2 } else { 0.8 };
+            probs[[0, r, c]] = 1.0 - p1;
+            probs[[1, r, c]] = p1;
+        }
+    }
+    // an isolated misclassified pixel and block in the left half
+    for (r, c) in [(5, 4), (14, 3), (14, 4), (15, 3), (15, 4)] {
+        probs[[0, r, c]] = 0.4;
+        probs[[1, r, c]] = 0.6;
+    }
+    probs
+}
+
+/// Tests that the `postprocess` function assigns the argmax class, removes
+/// small regions and smooths isolated pixels with the guided filter.
+#[test]
+fn postprocess_postprocess_expected_results() -> Result<(), ImgalError> {
+    let probs = half_plane_probs();
+    let guide = Array2::from_shape_fn((20, 20), |(_, c)| if c < 10 { 10.0 } else { 100.0 });
+
+    // plain argmax keeps the misclassified pixels
+    let labels = postprocess(&probs, None, None, None, None, None, THREADS)?;
+    assert_eq!(labels.sum(), 200 + 5);
+    assert_eq!(labels[[5, 4]], 1);
+    assert_eq!(labels[[14, 3]], 1);
+
+    // the minimum size removes the pixel and the block
+    let labels = postprocess(&probs, None, None, None, Some(10), None, THREADS)?;
+    assert_eq!(labels.sum(), 200);
+    assert!(labels.column(9).iter().all(|&v| v == 0));
+    assert!(labels.column(10).iter().all(|&v| v == 1));
+
+    // the guided filter removes the pixel and the block and keeps the edge
+    let labels = postprocess(&probs, Some(guide.view()), None, None, None, None, THREADS)?;
+    assert_eq!(labels.sum(), 200);
+    assert!(labels.column(9).iter().all(|&v| v == 0));
+    assert!(labels.column(10).iter().all(|&v| v == 1));
+
+    // a class axis last gives the same labels
+    let mut probs_last = probs.clone();
+    probs_last.swap_axes(0, 2);
+    probs_last.swap_axes(0, 1);
+    let labels_last = postprocess(&probs_last, None, None, None, Some(10), Some(2), THREADS)?;
+    assert_eq!(
+        labels_last,
+        postprocess(&probs, None, None, None, Some(10), None, THREADS)?
+    );
+
+    // invalid parameters
+    assert!(postprocess(&probs, None, None, None, None, Some(3), THREADS).is_err());
+    assert!(postprocess(&probs, None, None, Some(0.0), None, None, THREADS).is_err());
+    let bad_guide = Array2::<f64>::zeros((10, 20));
+    assert!(
+        postprocess(
+            &probs,
+            Some(bad_guide.view()),
+            None,
+            None,
+            None,
+            None,
+            THREADS
+        )
+        .is_err()
+    );
+    let empty = probs.slice_axis(Axis(0), (0..0).into()).to_owned();
+    assert!(postprocess(&empty, None, None, None, None, None, THREADS).is_err());
+    Ok(())
+}
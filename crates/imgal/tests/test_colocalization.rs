@@ -0,0 +1,431 @@
+use std::collections::HashMap;
+
+use ndarray::{Array2, Array3, Axis, Ix2, arr2, s};
+
+use imgal::colocalization::{
+    ColocCoefficient, coloc_report, coloc_roi_report, object_coloc, pairwise_matrix,
+    pairwise_roi_matrix, pearson_roi_coloc, saca_2d, saca_2d_full, saca_nd, saca_nd_full,
+    spearman_coloc, spearman_roi_coloc,
+};
+use imgal::prelude::*;
+use imgal::statistics::{pearson, weighted_kendall_tau_b};
+
+const TOLERANCE: f64 = 1e-10;
+const THREADS: Option<usize> = Some(0);
+
+fn approx_equal(a: f64, b: f64, tol: Option<f64>) -> bool {
+    (a - b).abs() < tol.unwrap_or(TOLERANCE)
+}
+
+/// Tests that `coloc_report` matches the individual colocalization
+/// coefficients and that `coloc_roi_report` reports each ROI.
+#[test]
+fn report_coloc_report_expected_results() -> Result<(), ImgalError> {
+    let data_a = Array2::from_shape_fn((8, 10), |(r, c)| {
+        if c < 5 {
+            0.0
+        } else {
+            5.0 + ((r * 10 + c) as f64 * 0.9).sin()
+        }
+    });
+    let data_b = Array2::from_shape_fn((8, 10), |(r, c)| {
+        if c < 3 {
+            0.0
+        } else {
+            2.0 + ((r * 10 + c) as f64 * 0.9).sin().powi(3)
+        }
+    });
+    let report_par = coloc_report(&data_a, &data_b, None, None, THREADS)?;
+    let report_seq = coloc_report(&data_a, &data_b, None, None, None)?;
+    let flat_a: Vec<f64> = data_a.iter().cloned().collect();
+    let flat_b: Vec<f64> = data_b.iter().cloned().collect();
+    let ones = vec![1.0; flat_a.len()];
+    assert!(approx_equal(
+        report_par.pearson,
+        pearson(&flat_a, &flat_b, None)?,
+        None
+    ));
+    assert!(approx_equal(report_par.pearson, report_seq.pearson, None));
+    assert!(approx_equal(
+        report_par.spearman,
+        spearman_coloc(&data_a, &data_b, None)?,
+        None
+    ));
+    assert!(approx_equal(
+        report_par.kendall_tau,
+        weighted_kendall_tau_b(&flat_a, &flat_b, &ones)?,
+        None
+    ));
+    // all intensity of "a" overlaps "b", 2 of 7 columns of "b" do not overlap "a"
+    assert!(approx_equal(report_par.manders_m1, 1.0, None));
+    let b_total = data_b.sum();
+    let b_overlap = data_b.slice(s![.., 5..]).sum();
+    assert!(approx_equal(
+        report_par.manders_m2,
+        b_overlap / b_total,
+        None
+    ));
+    assert!(report_par.li_icq > 0.0 && report_par.li_icq <= 0.5);
+    // a single ROI covering the image matches the full image report
+    let rois = HashMap::from([(
+        1,
+        Array2::from_shape_fn((80, 2), |(i, k)| if k == 0 { i / 10 } else { i % 10 }),
+    )]);
+    let roi_reports = coloc_roi_report(&data_a, &data_b, &rois, None, None, THREADS)?;
+    assert!(approx_equal(
+        roi_reports[&1].pearson,
+        report_par.pearson,
+        None
+    ));
+    assert!(approx_equal(
+        roi_reports[&1].manders_m2,
+        report_par.manders_m2,
+        None
+    ));
+    // undefined coefficients of constant images are NaN
+    let flat = Array2::<f64>::from_elem((8, 10), 1.0);
+    assert!(
+        coloc_report(&flat, &data_b, None, None, None)?
+            .pearson
+            .is_nan()
+    );
+    assert!(coloc_report(data_a.view(), data_b.slice(s![..4, ..]), None, None, None).is_err());
+    Ok(())
+}
+
+/// Tests that `pairwise_matrix` and `pairwise_roi_matrix` match `coloc_report`
+/// for every channel pair.
+#[test]
+fn pairwise_pairwise_matrix_expected_results() -> Result<(), ImgalError> {
+    let data = Array3::from_shape_fn((3, 8, 10), |(ch, r, c)| {
+        let v = ((r * 10 + c) as f64 * 0.9).sin();
+        match ch {
+            0 if c < 5 => 0.0,
+            0 => 5.0 + v,
+            1 if c < 3 => 0.0,
+            1 => 2.0 + v.powi(3),
+            _ => 1.0 + (c as f64 * 0.3).cos(),
+        }
+    });
+    let pearson_par = pairwise_matrix(&data, None, None, None, THREADS)?;
+    let pearson_seq = pairwise_matrix(&data, Some(ColocCoefficient::Pearson), None, None, None)?;
+    assert_eq!(pearson_par, pearson_seq);
+    let spearman = pairwise_matrix(&data, Some(ColocCoefficient::Spearman), None, None, THREADS)?;
+    let manders = pairwise_matrix(&data, Some(ColocCoefficient::Manders), None, Some(0), None)?;
+    let icq = pairwise_matrix(&data, Some(ColocCoefficient::LiIcq), None, None, THREADS)?;
+    let kendall = pairwise_matrix(&data, Some(ColocCoefficient::KendallTau), None, None, None)?;
+    for i in 0..3 {
+        assert!(approx_equal(pearson_par[[i, i]], 1.0, None));
+        for j in 0..3 {
+            let (a, b) = (data.index_axis(Axis(0), i), data.index_axis(Axis(0), j));
+            let report = coloc_report(a, b, None, None, None)?;
+            assert!(approx_equal(pearson_par[[i, j]], report.pearson, None));
+            assert!(approx_equal(spearman[[i, j]], report.spearman, None));
+            assert!(approx_equal(manders[[i, j]], report.manders_m1, None));
+            assert!(approx_equal(manders[[j, i]], report.manders_m2, None));
+            assert!(approx_equal(icq[[i, j]], report.li_icq, None));
+            assert!(approx_equal(kendall[[i, j]], report.kendall_tau, None));
+        }
+    }
+    // the channel axis last and per channel thresholds
+    let data_last = data.view().permuted_axes([1, 2, 0]);
+    let thresholds = [5.5, 2.0, 0.0];
+    let manders_last = pairwise_matrix(
+        data_last,
+        Some(ColocCoefficient::Manders),
+        Some(&thresholds),
+        Some(2),
+        THREADS,
+    )?;
+    let report = coloc_report(
+        data.index_axis(Axis(0), 0),
+        data.index_axis(Axis(0), 1),
+        Some(5.5),
+        Some(2.0),
+        None,
+    )?;
+    assert!(approx_equal(manders_last[[0, 1]], report.manders_m1, None));
+    assert!(approx_equal(manders_last[[1, 0]], report.manders_m2, None));
+    // a ROI covering the image matches the full image matrix
+    let rois = HashMap::from([
+        (
+            1,
+            Array2::from_shape_fn((80, 2), |(i, k)| if k == 0 { i / 10 } else { i % 10 }),
+        ),
+        (2, arr2(&[[0, 0], [1, 1], [2, 2], [3, 3]])),
+    ]);
+    let roi_matrices = pairwise_roi_matrix(&data, &rois, None, None, None, THREADS)?;
+    assert_eq!(roi_matrices.len(), 2);
+    roi_matrices[&1]
+        .iter()
+        .zip(pearson_par.iter())
+        .for_each(|(a, b)| assert!(approx_equal(*a, *b, None)));
+    assert!(pairwise_matrix(&data, None, Some(&[0.0]), None, None).is_err());
+    assert!(pairwise_matrix(&data, None, None, Some(3), None).is_err());
+    assert!(
+        pairwise_roi_matrix(
+            &data,
+            &HashMap::from([(1, arr2(&[[9, 0]]))]),
+            None,
+            None,
+            None,
+            None
+        )
+        .is_err()
+    );
+    Ok(())
+}
+
+/// Tests that `spearman_coloc` returns a perfect correlation for monotonic
+/// nonlinear intensity relationships and handles ties.
+#[test]
+fn spearman_spearman_coloc_expected_results() -> Result<(), ImgalError> {
+    let data_a = Array2::from_shape_fn((10, 12), |(r, c)| (r * 12 + c) as f64);
+    let data_b = data_a.mapv(|v| (v / 20.0).exp());
+    let data_c = data_a.mapv(|v| -v.powi(3));
+    let rho_par = spearman_coloc(&data_a, &data_b, THREADS)?;
+    let rho_seq = spearman_coloc(&data_a, &data_b, None)?;
+    assert!(approx_equal(rho_par, 1.0, None));
+    assert!(approx_equal(rho_par, rho_seq, None));
+    assert!(approx_equal(
+        spearman_coloc(&data_a, &data_c, None)?,
+        -1.0,
+        None
+    ));
+    // the Pearson coefficient of the nonlinear relationship is lower
+    let flat_a: Vec<f64> = data_a.iter().cloned().collect();
+    let flat_b: Vec<f64> = data_b.iter().cloned().collect();
+    assert!(pearson(&flat_a, &flat_b, None)? < 0.95);
+    // tied values get average ranks, i.e. ranks a: [1, 2.5, 2.5, 4] and
+    // b: [1, 2, 3, 4]
+    let tied = spearman_coloc(&[1.0, 2.0, 2.0, 3.0], &[1.0, 2.0, 3.0, 4.0], None)?;
+    assert!(approx_equal(tied, 0.9f64.sqrt(), None));
+    assert!(spearman_coloc(data_a.view(), data_a.t(), None).is_err());
+    Ok(())
+}
+
+/// Tests that `spearman_roi_coloc` computes the rank correlation per ROI.
+#[test]
+fn spearman_spearman_roi_coloc_expected_results() -> Result<(), ImgalError> {
+    let data_a = arr2(&[[1.0, 2.0, 3.0, 4.0], [5.0, 6.0, 7.0, 8.0]]);
+    let data_b = arr2(&[[1.0, 8.0, 27.0, 64.0], [9.0, 7.0, 5.0, 1.0]]);
+    let mut rois: HashMap<u64, Array2<usize>> = HashMap::new();
+    rois.insert(1, arr2(&[[0, 0], [0, 1], [0, 2], [0, 3]]));
+    rois.insert(2, arr2(&[[1, 0], [1, 1], [1, 2], [1, 3]]));
+    let rho_par = spearman_roi_coloc(&data_a, &data_b, &rois, THREADS)?;
+    let rho_seq = spearman_roi_coloc(&data_a, &data_b, &rois, None)?;
+    let pearson_map = pearson_roi_coloc(&data_a, &data_b, &rois, None)?;
+    assert_eq!(rho_par, rho_seq);
+    assert!(approx_equal(rho_par[&1], 1.0, None));
+    assert!(approx_equal(rho_par[&2], -1.0, None));
+    assert!(pearson_map[&1] < rho_par[&1]);
+    rois.insert(3, arr2(&[[0, 0], [1, 0]]));
+    assert!(spearman_roi_coloc(&data_a, &data_b, &rois, None).is_err());
+    // out of bounds ROI points return an error instead of panicking
+    rois.remove(&3);
+    rois.insert(4, arr2(&[[0, 0], [1, 1], [2, 3]]));
+    let oob = Err(ImgalError::InvalidRoiCoordinates {
+        roi_label: 4,
+        coords: vec![2, 3],
+        shape: vec![2, 4],
+    });
+    assert_eq!(spearman_roi_coloc(&data_a, &data_b, &rois, THREADS), oob);
+    assert_eq!(pearson_roi_coloc(&data_a, &data_b, &rois, None), oob);
+    assert!(coloc_roi_report(&data_a, &data_b, &rois, None, None, None).is_err());
+    Ok(())
+}
+
+/// Tests that `object_coloc` pairs objects by their nearest centroids and
+/// computes the overlap fractions.
+#[test]
+fn object_coloc_object_coloc_expected_results() -> Result<(), ImgalError> {
+    let mut labels_a = Array2::<u64>::zeros((20, 20));
+    let mut labels_b = Array2::<u64>::zeros((20, 20));
+    // object 1: a 4 x 4 square overlapping a 4 x 2 rectangle in "b"
+    labels_a.slice_mut(s![2..6, 2..6]).fill(1);
+    labels_b.slice_mut(s![2..6, 4..6]).fill(7);
+    // object 2: a 2 x 2 square near (but not overlapping) object 8
+    labels_a.slice_mut(s![12..14, 12..14]).fill(2);
+    labels_b.slice_mut(s![12..14, 15..17]).fill(8);
+    // object 3: isolated
+    labels_a.slice_mut(s![16..18, 2..4]).fill(3);
+    let pairs_par = object_coloc(&labels_a, &labels_b, 5.0, THREADS)?;
+    let pairs_seq = object_coloc(&labels_a, &labels_b, 5.0, None)?;
+    assert_eq!(pairs_par.len(), 3);
+    assert_eq!(pairs_par[&1], pairs_seq[&1]);
+    assert_eq!(pairs_par[&2], pairs_seq[&2]);
+    let p1 = pairs_par[&1];
+    assert_eq!(p1.label_b, Some(7));
+    assert!(approx_equal(p1.distance, 1.0, None));
+    assert!(approx_equal(p1.overlap_a, 0.5, None));
+    assert!(approx_equal(p1.overlap_b, 1.0, None));
+    let p2 = pairs_par[&2];
+    assert_eq!(p2.label_b, Some(8));
+    assert!(approx_equal(p2.distance, 3.0, None));
+    assert!(approx_equal(p2.overlap_a, 0.0, None));
+    let p3 = pairs_par[&3];
+    assert_eq!(p3.label_b, None);
+    assert!(p3.distance.is_nan());
+    // a smaller search distance leaves object 2 unpaired
+    let pairs = object_coloc(&labels_a, &labels_b, 2.5, None)?;
+    assert_eq!(pairs[&2].label_b, None);
+    assert!(object_coloc(&labels_a, &labels_b, -1.0, None).is_err());
+    assert!(object_coloc(labels_a.view(), labels_b.slice(s![..10, ..]), 5.0, None).is_err());
+    Ok(())
+}
+
+/// Tests that `saca_nd` matches `saca_2d` for isotropic data and
+/// adapts the neighborhood to anisotropic spacing.
+#[test]
+fn saca_saca_nd_expected_results() -> Result<(), ImgalError> {
+    // colocalized left half and anti-colocalized right half
+    let data_a = Array2::from_shape_fn((12, 12), |(r, c)| {
+        10.0 + 5.0 * ((r * 7 + c * 3) as f64 * 0.7).sin()
+    });
+    let data_b = Array2::from_shape_fn((12, 12), |(r, c)| {
+        let v = data_a[[r, c]] - 10.0;
+        if c < 6 { 10.0 + v } else { 10.0 - v }
+    });
+    let z_2d = saca_2d(&data_a, &data_b, 0.0, 0.0, None, THREADS)?;
+    let z_nd_par = saca_nd(&data_a, &data_b, 0.0, 0.0, None, None, THREADS)?;
+    let z_nd_seq = saca_nd(&data_a, &data_b, 0.0, 0.0, None, Some(&[1.0, 1.0]), None)?;
+    assert_eq!(z_nd_par, z_nd_seq);
+    z_2d.iter()
+        .zip(z_nd_par.iter())
+        .for_each(|(a, b)| assert!(approx_equal(*a, *b, None)));
+    assert!(z_nd_par[[6, 1]] > 0.0);
+    assert!(z_nd_par[[6, 10]] < 0.0);
+    let stack_a = Array3::from_shape_fn((3, 6, 8), |(p, r, c)| data_a[[r + p, c]]);
+    let stack_b = Array3::from_shape_fn((3, 6, 8), |(p, r, c)| data_b[[r + p, c]]);
+    let z_iso = saca_nd(&stack_a, &stack_b, 0.0, 0.0, None, None, THREADS)?;
+    // an axial spacing larger than the lateral spacing changes the neighborhood
+    let z_aniso = saca_nd(
+        &stack_a,
+        &stack_b,
+        0.0,
+        0.0,
+        None,
+        Some(&[3.0, 1.0, 1.0]),
+        THREADS,
+    )?;
+    assert_eq!(z_aniso.shape(), &[3, 6, 8]);
+    assert!(
+        z_aniso
+            .iter()
+            .zip(z_iso.iter())
+            .any(|(a, b)| (a - b).abs() > 1e-6)
+    );
+    assert!(saca_nd(&stack_a, &stack_b, 0.0, 0.0, None, Some(&[1.0, 1.0]), None).is_err());
+    assert!(
+        saca_nd(
+            &stack_a,
+            &stack_b,
+            0.0,
+            0.0,
+            None,
+            Some(&[0.0, 1.0, 1.0]),
+            None
+        )
+        .is_err()
+    );
+    assert!(
+        saca_nd(
+            stack_a.view(),
+            stack_b.slice(s![..2, .., ..]),
+            0.0,
+            0.0,
+            None,
+            None,
+            None
+        )
+        .is_err()
+    );
+    Ok(())
+}
+
+/// Tests that `saca_2d` only computes the *z-score* inside of the mask.
+#[test]
+fn saca_saca_2d_mask_expected_results() -> Result<(), ImgalError> {
+    let data_a = Array2::from_shape_fn((10, 10), |(r, c)| {
+        10.0 + 5.0 * ((r * 7 + c * 3) as f64 * 0.7).sin()
+    });
+    let data_b = data_a.mapv(|v| 2.0 * v);
+    let mask = Array2::from_shape_fn((10, 10), |(r, c)| r < 5 && c < 6);
+    let z_par = saca_2d(&data_a, &data_b, 0.0, 0.0, Some(mask.view()), THREADS)?;
+    let z_seq = saca_2d(&data_a, &data_b, 0.0, 0.0, Some(mask.view()), None)?;
+    z_par
+        .iter()
+        .zip(z_seq.iter())
+        .zip(mask.iter())
+        .for_each(|((a, b), &m)| {
+            if m {
+                assert!(approx_equal(*a, *b, None));
+                assert!(*a > 0.0);
+            } else {
+                assert!(a.is_nan() && b.is_nan());
+            }
+        });
+    // a mask covering the full image does not change the results
+    let full = Array2::from_elem((10, 10), true);
+    let z_full = saca_2d(&data_a, &data_b, 0.0, 0.0, Some(full.view()), None)?;
+    let z = saca_2d(&data_a, &data_b, 0.0, 0.0, None, None)?;
+    assert_eq!(z_full, z);
+    let z_nd = saca_nd(&data_a, &data_b, 0.0, 0.0, Some(mask.view()), None, None)?;
+    z_nd.iter()
+        .zip(z_seq.iter())
+        .for_each(|(a, b)| assert!((a.is_nan() && b.is_nan()) || approx_equal(*a, *b, None)));
+    let bad_mask = Array2::from_elem((5, 10), true);
+    assert!(saca_2d(&data_a, &data_b, 0.0, 0.0, Some(bad_mask.view()), None).is_err());
+    Ok(())
+}
+
+/// Tests that `saca_2d_full` returns the *z-score* of `saca_2d` with the
+/// intermediate Kendall Tau-b and neighborhood radius maps.
+#[test]
+fn saca_saca_2d_full_expected_results() -> Result<(), ImgalError> {
+    let data_a = Array2::from_shape_fn((10, 10), |(r, c)| {
+        10.0 + 5.0 * ((r * 7 + c * 3) as f64 * 0.7).sin()
+    });
+    let data_b = Array2::from_shape_fn((10, 10), |(r, c)| {
+        let v = data_a[[r, c]] - 10.0;
+        if c < 5 { 10.0 + v } else { 10.0 - v }
+    });
+    let mask = Array2::from_shape_fn((10, 10), |(r, _)| r > 0);
+    let out_par = saca_2d_full(&data_a, &data_b, 0.0, 0.0, Some(mask.view()), THREADS)?;
+    let out_seq = saca_2d_full(&data_a, &data_b, 0.0, 0.0, Some(mask.view()), None)?;
+    let z = saca_2d(&data_a, &data_b, 0.0, 0.0, Some(mask.view()), None)?;
+    assert_eq!(out_par.radius, out_seq.radius);
+    out_par
+        .z_score
+        .iter()
+        .zip(z.iter())
+        .for_each(|(a, b)| assert!((a.is_nan() && b.is_nan()) || approx_equal(*a, *b, None)));
+    // the largest neighborhood radius is floor(1.15^14) = 7
+    out_par
+        .tau
+        .iter()
+        .zip(out_par.radius.iter())
+        .zip(mask.iter())
+        .for_each(|((&t, &r), &m)| {
+            if m {
+                assert!((-1.0..=1.0).contains(&t));
+                assert!((1..=7).contains(&r));
+            } else {
+                assert!(t.is_nan());
+                assert_eq!(r, 0);
+            }
+        });
+    assert!(out_par.tau[[5, 1]] > 0.0);
+    assert!(out_par.tau[[5, 8]] < 0.0);
+    let out_nd = saca_nd_full(&data_a, &data_b, 0.0, 0.0, Some(mask.view()), None, None)?;
+    assert_eq!(
+        out_nd.radius.into_dimensionality::<Ix2>().unwrap(),
+        out_seq.radius
+    );
+    out_nd
+        .tau
+        .iter()
+        .zip(out_seq.tau.iter())
+        .for_each(|(a, b)| assert!((a.is_nan() && b.is_nan()) || approx_equal(*a, *b, None)));
+    Ok(())
+}
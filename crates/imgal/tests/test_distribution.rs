@@ -1,4 +1,6 @@
-use imgal::distribution::{inverse_normal_cdf, normalized_gaussian};
+use imgal::distribution::{
+    f_cdf, inverse_normal_cdf, normal_cdf, normalized_gaussian, regularized_incomplete_beta, t_cdf,
+};
 use imgal::integration::midpoint;
 use imgal::prelude::*;
 
@@ -9,6 +11,19 @@ fn approx_equal(a: f64, b: f64, tol: Option<f64>) -> bool {
     (a - b).abs() < tol.unwrap_or(TOLERANCE)
 }
 
+/// Tests that `f_cdf` returns the expected closed form cumulative
+/// probabilities.
+#[test]
+fn distribution_f_cdf_expected_results() -> Result<(), ImgalError> {
+    // with d1 = d2 = 2 the CDF is x / (1 + x)
+    assert!(approx_equal(f_cdf(1.0, 2.0, 2.0)?, 0.5, None));
+    assert!(approx_equal(f_cdf(3.0, 2.0, 2.0)?, 0.75, None));
+    assert_eq!(f_cdf(0.0, 4.0, 10.0)?, 0.0);
+    assert_eq!(f_cdf(-1.0, 4.0, 10.0)?, 0.0);
+    assert!(f_cdf(1.0, 0.0, 10.0).is_err());
+    Ok(())
+}
+
 /// Tests that `inverse_normal_cdf` returns the expected values for known
 /// probabilites and boundary cases.
 #[test]
@@ -21,6 +36,29 @@ fn distribution_inverse_normal_cdf_expected_results() -> Result<(), ImgalError>
     Ok(())
 }
 
+/// Tests that `normal_cdf` returns the expected probabilities in the center
+/// and the tails.
+#[test]
+fn distribution_normal_cdf_expected_results() {
+    assert_eq!(normal_cdf(0.0), 0.5);
+    assert!(approx_equal(
+        normal_cdf(-1.0),
+        0.15865525393145707,
+        Some(1e-14)
+    ));
+    assert!(approx_equal(
+        normal_cdf(1.959963984540054),
+        0.975,
+        Some(1e-14)
+    ));
+    assert!(approx_equal(
+        normal_cdf(-10.0) / 7.619853024160527e-24,
+        1.0,
+        Some(1e-12)
+    ));
+    assert!(normal_cdf(f64::NAN).is_nan());
+}
+
 /// Tests that `normalized_gaussian` returns the expected results for index
 /// `100` and the distribution integrates to approximately `1.0`.
 #[test]
@@ -54,3 +92,49 @@ fn distribution_normalized_gaussian_expected_results() {
     assert!(approx_equal(gauss_arr_a_seq[100], 0.0021260086, None));
     assert!(approx_equal(gauss_arr_b_seq[100], 0.0044655072, None));
 }
+
+/// Tests that `regularized_incomplete_beta` returns the expected closed form
+/// values and errors for invalid parameters.
+#[test]
+fn distribution_regularized_incomplete_beta_expected_results() -> Result<(), ImgalError> {
+    assert!(approx_equal(
+        regularized_incomplete_beta(0.3, 1.0, 1.0)?,
+        0.3,
+        None
+    ));
+    assert!(approx_equal(
+        regularized_incomplete_beta(0.5, 2.0, 3.0)?,
+        0.6875,
+        None
+    ));
+    assert!(approx_equal(
+        regularized_incomplete_beta(0.5, 7.5, 7.5)?,
+        0.5,
+        None
+    ));
+    assert!(approx_equal(
+        regularized_incomplete_beta(0.9, 2.0, 3.0)?,
+        0.9963,
+        None
+    ));
+    assert!(regularized_incomplete_beta(1.5, 2.0, 3.0).is_err());
+    assert!(regularized_incomplete_beta(0.5, -2.0, 3.0).is_err());
+    Ok(())
+}
+
+/// Tests that `t_cdf` returns the expected closed form cumulative
+/// probabilities.
+#[test]
+fn distribution_t_cdf_expected_results() -> Result<(), ImgalError> {
+    // with df = 1 (Cauchy) the CDF is 1/2 + atan(x) / π
+    for x in [-3.0, -0.5, 0.0, 2.0] {
+        let expected = 0.5 + f64::atan(x) / std::f64::consts::PI;
+        assert!(approx_equal(t_cdf(x, 1.0)?, expected, None));
+    }
+    // with df = 2 the CDF is 1/2 + x / (2√(2 + x²))
+    let expected = 0.5 + 1.5 / (2.0 * (2.0 + 1.5 * 1.5_f64).sqrt());
+    assert!(approx_equal(t_cdf(1.5, 2.0)?, expected, None));
+    assert_eq!(t_cdf(f64::INFINITY, 3.0)?, 1.0);
+    assert!(t_cdf(1.0, 0.0).is_err());
+    Ok(())
+}
@@ -0,0 +1,159 @@
+use ndarray::Array2;
+
+use imgal::feature::{FiducialShape, detect_fiducials, wavelet_spots};
+use imgal::prelude::*;
+use imgal::simulation::rng::Pcg;
+
+const THREADS: Option<usize> = Some(0);
+
+fn approx_equal(a: f64, b: f64, tol: f64) -> bool {
+    (a - b).abs() < tol
+}
+
+/// Render anti-aliased markers by supersampling each pixel.
+fn render_markers(
+    shape: (usize, usize),
+    centers: &[(f64, f64)],
+    inside: impl Fn(f64, f64) -> bool,
+) -> Array2<f64> {
+    Array2::from_shape_fn(shape, |(r, c)| {
+        let mut coverage = 0.0;
+        for i in 0..4 {
+            for j in 0..4 {
+                let y = r as f64 + (i as f64 + 0.5) / 4.0 - 0.5;
+                let x = c as f64 + (j as f64 + 0.5) / 4.0 - 0.5;
+                if centers.iter().any(|&(cr, cc)| inside(y - cr, x - cc)) {
+                    coverage += 1.0 / 16.0;
+                }
+            }
+        }
+        10.0 + 90.0 * coverage
+    })
+}
+
+/// Tests that `detect_fiducials` finds cross and square markers with subpixel
+/// precision.
+#[test]
+fn fiducials_detect_fiducials_expected_results() -> Result<(), ImgalError> {
+    let centers = [(20.3, 25.0), (55.0, 60.6)];
+    let cross = |dy: f64, dx: f64| {
+        (dy.abs() <= 7.5 && dx.abs() < 1.5) || (dx.abs() <= 7.5 && dy.abs() < 1.5)
+    };
+    let data = render_markers((80, 80), &centers, cross);
+    let (coords_par, scores_par) = detect_fiducials(
+        &data,
+        FiducialShape::Cross,
+        15,
+        Some(3),
+        None,
+        false,
+        THREADS,
+    )?;
+    let (coords_seq, scores_seq) =
+        detect_fiducials(&data, FiducialShape::Cross, 15, Some(3), None, false, None)?;
+    assert_eq!(coords_par, coords_seq);
+    assert_eq!(scores_par, scores_seq);
+    assert_eq!(coords_par.dim(), (2, 2));
+    assert!(scores_par.iter().all(|&s| s > 0.9));
+    let mut found: Vec<(f64, f64)> = coords_par
+        .rows()
+        .into_iter()
+        .map(|r| (r[0], r[1]))
+        .collect();
+    found.sort_by(|a, b| a.0.total_cmp(&b.0));
+    centers.iter().zip(found.iter()).for_each(|(e, f)| {
+        assert!(approx_equal(e.0, f.0, 0.25));
+        assert!(approx_equal(e.1, f.1, 0.25));
+    });
+    // dark markers are only found with the dark template
+    let inverted = data.mapv(|v| 110.0 - v);
+    let (coords, _) = detect_fiducials(
+        &inverted,
+        FiducialShape::Cross,
+        15,
+        Some(3),
+        None,
+        true,
+        None,
+    )?;
+    assert_eq!(coords.dim(), (2, 2));
+    let (coords, _) = detect_fiducials(
+        &inverted,
+        FiducialShape::Cross,
+        15,
+        Some(3),
+        None,
+        false,
+        None,
+    )?;
+    assert_eq!(coords.dim().0, 0);
+    // squares
+    let square = |dy: f64, dx: f64| dy.abs() < 6.0 && dx.abs() < 6.0;
+    let data = render_markers((60, 60), &[(30.0, 29.5)], square);
+    let (coords, scores) = detect_fiducials(
+        &data,
+        FiducialShape::Square,
+        12,
+        None,
+        Some(0.8),
+        false,
+        None,
+    )?;
+    assert_eq!(coords.dim(), (1, 2));
+    assert!(scores[0] > 0.9);
+    assert!(approx_equal(coords[[0, 0]], 30.0, 0.25));
+    assert!(approx_equal(coords[[0, 1]], 29.5, 0.25));
+    assert!(detect_fiducials(&data, FiducialShape::Cross, 2, None, None, false, None).is_err());
+    assert!(
+        detect_fiducials(&data, FiducialShape::Cross, 15, Some(15), None, false, None).is_err()
+    );
+    assert!(
+        detect_fiducials(
+            &data,
+            FiducialShape::Cross,
+            15,
+            None,
+            Some(1.5),
+            false,
+            None
+        )
+        .is_err()
+    );
+    Ok(())
+}
+
+/// Tests that `wavelet_spots` finds spots of different sizes in uniform noise
+/// without false detections.
+#[test]
+fn wavelet_wavelet_spots_expected_results() -> Result<(), ImgalError> {
+    let spots = [(12.0, 15.0, 1.0), (40.0, 20.0, 1.5), (30.0, 48.0, 2.0)];
+    let mut rng = Pcg::new(3);
+    let data = Array2::from_shape_fn((64, 64), |(r, c)| {
+        let signal: f64 = spots
+            .iter()
+            .map(|&(sr, sc, s)| {
+                let d2 = (r as f64 - sr).powi(2) + (c as f64 - sc).powi(2);
+                100.0 * (-d2 / (2.0 * s * s)).exp()
+            })
+            .sum();
+        20.0 + signal + 10.0 * rng.next_f32() as f64
+    });
+    let (mask_par, coords_par) = wavelet_spots(&data, None, None, Some(3), THREADS)?;
+    let (mask_seq, coords_seq) = wavelet_spots(&data, None, None, Some(3), None)?;
+    assert_eq!(mask_par, mask_seq);
+    assert_eq!(coords_par, coords_seq);
+    assert_eq!(coords_par.nrows(), spots.len());
+    spots.iter().for_each(|&(sr, sc, _)| {
+        assert!(mask_par[[sr as usize, sc as usize]]);
+        assert!(
+            coords_par
+                .rows()
+                .into_iter()
+                .any(|p| { approx_equal(p[0], sr, 0.5) && approx_equal(p[1], sc, 0.5) })
+        );
+    });
+    assert!(wavelet_spots(&data, Some(0), None, None, None).is_err());
+    assert!(wavelet_spots(&data, None, Some(-1.0), None, None).is_err());
+    assert!(wavelet_spots(&data, Some(6), None, None, None).is_err());
+    Ok(())
+}
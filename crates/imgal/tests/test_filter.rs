@@ -1,4 +1,14 @@
-use imgal::filter::{fft_convolve_1d, fft_deconvolve_1d};
+use std::f64::consts::{FRAC_PI_2, FRAC_PI_4, PI};
+
+use ndarray::{Array2, Array3, ArrayD, IxDyn, arr2, s};
+
+use imgal::filter::{
+    BorderMode, Conductance, ConvolveMode, anisotropic_diffusion, census_transform,
+    difference_of_gaussians, fft_convolve, fft_convolve_1d, fft_deconvolve_1d, gaussian_blur,
+    guided, maximum_filter, median_filter, minimum_filter, percentile_filter, rank_transform,
+    sobel, structure_tensor, uniform_filter,
+};
+use imgal::kernel::neighborhood::{circle_kernel, sphere_kernel};
 use imgal::prelude::*;
 use imgal::simulation::decay::{gaussian_exponential_decay_1d, ideal_exponential_decay_1d};
 use imgal::simulation::instrument::gaussian_irf_1d;
@@ -18,6 +28,163 @@ fn approx_equal(a: f64, b: f64, tol: Option<f64>) -> bool {
     (a - b).abs() < tol.unwrap_or(TOLERANCE)
 }
 
+/// Tests that `anisotropic_diffusion` smooths small variations, preserves a
+/// strong edge and conserves the total intensity.
+#[test]
+fn filter_anisotropic_diffusion_expected_results() -> Result<(), ImgalError> {
+    // a step edge with a small checkerboard texture
+    let data = Array2::from_shape_fn((16, 16), |(r, c)| {
+        let step = if c < 8 { 0.0 } else { 100.0 };
+        step + if (r + c) % 2 == 0 { 1.0 } else { -1.0 }
+    });
+    let diff_par = anisotropic_diffusion(&data, 10.0, None, Some(20), None, THREADS)?;
+    let diff_seq = anisotropic_diffusion(&data, 10.0, None, Some(20), None, None)?;
+    assert_eq!(diff_par, diff_seq);
+    assert!(approx_equal(diff_par.sum(), data.sum(), Some(1e-8)));
+    assert!((diff_par[[8, 3]] - diff_par[[8, 4]]).abs() < 0.1);
+    assert!(diff_par[[8, 8]] - diff_par[[8, 7]] > 95.0);
+    let quad = anisotropic_diffusion(
+        &data,
+        10.0,
+        Some(Conductance::Quadratic),
+        Some(20),
+        Some(0.1),
+        None,
+    )?;
+    assert!((quad[[8, 3]] - quad[[8, 4]]).abs() < 0.5);
+    assert!(quad[[8, 8]] - quad[[8, 7]] > 90.0);
+    let unchanged = anisotropic_diffusion(&data, 10.0, None, Some(0), None, None)?;
+    assert_eq!(unchanged, data);
+    let volume = Array3::from_shape_fn((4, 5, 6), |(p, r, c)| ((p * 7 + r * 3 + c) % 5) as f64);
+    let vol_diff = anisotropic_diffusion(&volume, 2.0, None, Some(5), None, THREADS)?;
+    assert!(approx_equal(vol_diff.sum(), volume.sum(), Some(1e-8)));
+    assert!(anisotropic_diffusion(&data, 0.0, None, None, None, None).is_err());
+    assert!(anisotropic_diffusion(&data, 1.0, None, None, Some(0.3), None).is_err());
+    assert!(anisotropic_diffusion(&volume, 1.0, None, None, Some(0.2), None).is_err());
+    Ok(())
+}
+
+/// Tests that `census_transform` encodes the neighbors less than the center
+/// and is invariant to monotonic intensity changes.
+#[test]
+fn filter_census_transform_expected_results() -> Result<(), ImgalError> {
+    let data = arr2(&[[1.0, 5.0, 2.0], [7.0, 4.0, 3.0], [9.0, 0.0, 8.0]]);
+    let footprint = Array2::from_elem((3, 3), true);
+    let census_par = census_transform(&data, footprint.view(), None, THREADS)?;
+    let census_seq = census_transform(&data, footprint.view(), None, None)?;
+    // neighbors of the center in row-major order: 1, 5, 2, 7, 3, 9, 0, 8
+    assert_eq!(census_par[[1, 1]], 0b0101_0101);
+    assert_eq!(census_par, census_seq);
+    let scaled = data.mapv(|v| 3.0 * v + 10.0);
+    assert_eq!(
+        census_transform(&scaled, footprint.view(), None, None)?,
+        census_par
+    );
+    let cross = arr2(&[
+        [false, true, false],
+        [true, true, true],
+        [false, true, false],
+    ]);
+    let census_cross =
+        census_transform(&data, cross.view(), Some(BorderMode::Constant(0.0)), None)?;
+    // neighbors of the top left pixel: 0 (pad), 0 (pad), 5, 7
+    assert_eq!(census_cross[[0, 0]], 0b0011);
+    assert!(census_transform(&data, Array2::from_elem((2, 3), true).view(), None, None).is_err());
+    assert!(census_transform(&data, Array2::from_elem((9, 9), true).view(), None, None).is_err());
+    Ok(())
+}
+
+/// Tests that `difference_of_gaussians` matches the difference of two Gaussian
+/// blurs and enhances a spot over a smooth background.
+#[test]
+fn filter_difference_of_gaussians_expected_results() -> Result<(), ImgalError> {
+    let data = Array2::from_shape_fn((32, 32), |(r, c)| {
+        let d2 = (r as f64 - 16.0).powi(2) + (c as f64 - 12.0).powi(2);
+        0.5 * c as f64 + 10.0 * (-d2 / 8.0).exp()
+    });
+    let dog_par = difference_of_gaussians(&data, &[1.0, 1.0], None, None, None, THREADS)?;
+    let dog_seq = difference_of_gaussians(&data, &[1.0, 1.0], None, None, None, None)?;
+    let low = gaussian_blur(&data, &[1.0, 1.0], None, None)?;
+    let high = gaussian_blur(&data, &[1.6, 1.6], None, None)?;
+    dog_par
+        .iter()
+        .zip(dog_seq.iter())
+        .zip((&low - &high).iter())
+        .for_each(|((a, b), c)| {
+            assert!(approx_equal(*a, *b, None));
+            assert!(approx_equal(*a, *c, None));
+        });
+    let explicit =
+        difference_of_gaussians(&data, &[1.0, 1.0], Some(&[2.0, 3.0]), None, None, None)?;
+    let wide = gaussian_blur(&data, &[2.0, 3.0], None, None)?;
+    assert!(approx_equal(
+        explicit[[5, 5]],
+        low[[5, 5]] - wide[[5, 5]],
+        None
+    ));
+    // the spot is the maximum response and the linear background is removed
+    let peak =
+        dog_par.indexed_iter().fold(
+            ((0, 0), f64::MIN),
+            |acc, (p, &v)| {
+                if v > acc.1 { (p, v) } else { acc }
+            },
+        );
+    assert_eq!(peak.0, (16, 12));
+    assert!(dog_par[[16, 26]].abs() < 1e-6);
+    assert!(difference_of_gaussians(&data, &[1.0, 1.0], None, Some(1.0), None, None).is_err());
+    // images smaller than the kernels are reflected repeatedly
+    let small = Array2::<f64>::from_elem((4, 5), 2.0);
+    let dog_small = difference_of_gaussians(&small, &[1.0, 1.0], None, None, None, THREADS)?;
+    assert!(dog_small.iter().all(|&v| approx_equal(v, 0.0, Some(1e-12))));
+    assert!(
+        difference_of_gaussians(&data, &[2.0, 2.0], Some(&[1.0, 3.0]), None, None, None).is_err()
+    );
+    assert!(difference_of_gaussians(&data, &[1.0], None, None, None, None).is_err());
+    Ok(())
+}
+
+/// Tests that `fft_convolve` matches a direct convolution in all output modes.
+#[test]
+fn filter_fft_convolve_expected_results() -> Result<(), ImgalError> {
+    let data = Array2::from_shape_fn((9, 12), |(r, c)| ((r * 5 + c * 3) % 7) as f64);
+    let kernel = Array2::from_shape_fn((3, 4), |(r, c)| (r + 2 * c) as f64 + 1.0);
+    // direct full convolution
+    let mut direct = Array2::<f64>::zeros((11, 15));
+    data.indexed_iter().for_each(|((r, c), &d)| {
+        kernel
+            .indexed_iter()
+            .for_each(|((i, j), &k)| direct[[r + i, c + j]] += d * k);
+    });
+    let full = fft_convolve(&data, &kernel, Some(ConvolveMode::Full), THREADS)?;
+    let same = fft_convolve(&data, &kernel, None, THREADS)?;
+    let valid = fft_convolve(&data, &kernel, Some(ConvolveMode::Valid), None)?;
+    assert_eq!(full.dim(), (11, 15));
+    assert_eq!(same.dim(), (9, 12));
+    assert_eq!(valid.dim(), (7, 9));
+    full.iter()
+        .zip(direct.iter())
+        .for_each(|(a, b)| assert!(approx_equal(*a, *b, Some(1e-9))));
+    same.iter()
+        .zip(direct.slice(s![1..10, 1..13]).iter())
+        .for_each(|(a, b)| assert!(approx_equal(*a, *b, Some(1e-9))));
+    valid
+        .iter()
+        .zip(direct.slice(s![2..9, 3..12]).iter())
+        .for_each(|(a, b)| assert!(approx_equal(*a, *b, Some(1e-9))));
+    // a 3D impulse kernel is the identity in same mode
+    let volume = Array3::from_shape_fn((4, 5, 6), |(p, r, c)| (p * 30 + r * 6 + c) as f64);
+    let mut impulse = Array3::<f64>::zeros((3, 3, 3));
+    impulse[[1, 1, 1]] = 1.0;
+    let ident = fft_convolve(&volume, &impulse, None, THREADS)?;
+    ident
+        .iter()
+        .zip(volume.iter())
+        .for_each(|(a, b)| assert!(approx_equal(*a, *b, Some(1e-9))));
+    assert!(fft_convolve(&kernel, &data, Some(ConvolveMode::Valid), THREADS).is_err());
+    Ok(())
+}
+
 /// Tests that `fft_convolve_1d` returns the expected values for photon count,
 /// and a point on the curve of an ideal bioexponential decay curve convolved
 /// with a Gaussian IRF.
@@ -32,6 +199,19 @@ fn filter_fft_convolve_1d_expected_results() -> Result<(), ImgalError> {
     assert!(approx_equal(sum(&conv_seq, None), 4960.5567668085, None));
     assert!(approx_equal(conv_par[68], 135.7148429095, None));
     assert!(approx_equal(conv_seq[68], 135.7148429095, None));
+    // the cached plans give the same result on repeated calls, after a call
+    // of another length and on other threads
+    let short = fft_convolve_1d(decay_arr.slice(s![..100]), irf_arr.slice(s![..100]), None);
+    assert_eq!(short.len(), 100);
+    assert_eq!(fft_convolve_1d(&decay_arr, &irf_arr, None), conv_seq);
+    std::thread::scope(|scope| {
+        let handles: Vec<_> = (0..4)
+            .map(|_| scope.spawn(|| fft_convolve_1d(&decay_arr, &irf_arr, THREADS)))
+            .collect();
+        handles
+            .into_iter()
+            .for_each(|h| assert_eq!(h.join().unwrap(), conv_par));
+    });
     Ok(())
 }
 
@@ -60,3 +240,383 @@ fn filter_fft_deconvolve_1d_expected_results() -> Result<(), ImgalError> {
     assert!(approx_equal(dconv_seq[62], 0.090544374, None));
     Ok(())
 }
+
+/// Tests that `gaussian_blur` conserves the intensity of an impulse, blurs only
+/// the requested axes and handles the borders.
+#[test]
+fn filter_gaussian_blur_expected_results() -> Result<(), ImgalError> {
+    let mut impulse = Array2::<f64>::zeros((21, 21));
+    impulse[[10, 10]] = 1.0;
+    let par = gaussian_blur(&impulse, &[2.0, 2.0], None, THREADS)?;
+    let seq = gaussian_blur(&impulse, &[2.0, 2.0], None, None)?;
+    assert_eq!(par, seq);
+    assert!(approx_equal(par.sum(), 1.0, None));
+    // the separable 1D kernel value at the center is 1 / Σ exp(-x² / 8)
+    let k_sum: f64 = (-6..=6).map(|x: i32| (-(x * x) as f64 / 8.0).exp()).sum();
+    assert!(approx_equal(par[[10, 10]], 1.0 / (k_sum * k_sum), None));
+    assert!(approx_equal(par[[10, 7]], par[[13, 10]], None));
+    // a zero sigma axis is not blurred
+    let rows = gaussian_blur(&impulse, &[0.0, 2.0], None, THREADS)?;
+    assert!(approx_equal(rows.row(10).sum(), 1.0, None));
+    assert_eq!(rows.row(9).sum(), 0.0);
+    // reflected borders preserve a constant image, constant borders darken it
+    let flat = Array3::<u16>::from_elem((5, 12, 12), 10);
+    let reflect = gaussian_blur(&flat, &[0.5, 1.0, 1.0], None, THREADS)?;
+    assert!(reflect.iter().all(|&v| approx_equal(v, 10.0, Some(1e-9))));
+    let constant = gaussian_blur(
+        &flat,
+        &[0.5, 1.0, 1.0],
+        Some(BorderMode::Constant(0.0)),
+        THREADS,
+    )?;
+    assert!(constant[[2, 0, 0]] < constant[[2, 1, 1]]);
+    assert!(approx_equal(constant[[2, 6, 6]], 10.0, Some(1e-9)));
+    // a kernel radius exceeding the axis is reflected repeatedly
+    let wide = gaussian_blur(&flat, &[2.0, 1.0, 1.0], None, THREADS)?;
+    assert!(wide.iter().all(|&v| approx_equal(v, 10.0, Some(1e-9))));
+    let single = gaussian_blur(
+        &Array2::<f64>::from_elem((1, 1), 4.0),
+        &[1.0, 1.0],
+        None,
+        None,
+    )?;
+    assert!(approx_equal(single[[0, 0]], 4.0, Some(1e-12)));
+    // invalid sigma values
+    assert!(gaussian_blur(&impulse, &[2.0], None, THREADS).is_err());
+    assert!(gaussian_blur(&impulse, &[2.0, -1.0], None, THREADS).is_err());
+    Ok(())
+}
+
+/// Tests that `guided` preserves the edges of the guide while smoothing noise
+/// and leaves linear images unchanged.
+#[test]
+fn filter_guided_expected_results() -> Result<(), ImgalError> {
+    // a step edge with alternating noise
+    let step = Array2::from_shape_fn((16, 16), |(r, c)| {
+        let base = if c < 8 { 0.0 } else { 1.0 };
+        base + if (r + c) % 2 == 0 { 0.05 } else { -0.05 }
+    });
+    let guide = Array2::from_shape_fn((16, 16), |(_, c)| if c < 8 { 0.0 } else { 1.0 });
+    let par = guided(&step, Some(guide.view()), Some(2), Some(1e-4), THREADS)?;
+    let seq = guided(&step, Some(guide.view()), Some(2), Some(1e-4), None)?;
+    par.iter()
+        .zip(seq.iter())
+        .for_each(|(a, b)| assert!(approx_equal(*a, *b, Some(1e-12))));
+    // the noise is removed while the edge is kept
+    assert!((par[[8, 3]] - 0.0).abs() < 0.02);
+    assert!((par[[8, 12]] - 1.0).abs() < 0.02);
+    assert!(par[[8, 8]] - par[[8, 7]] > 0.9);
+    // a self-guided linear ramp is a local linear model of itself
+    let ramp = Array3::from_shape_fn((4, 6, 6), |(p, r, c)| (p + r + 2 * c) as f64);
+    let flat = guided(&ramp, None, Some(1), Some(1e-6), THREADS)?;
+    flat.iter()
+        .zip(ramp.iter())
+        .for_each(|(a, b)| assert!(approx_equal(*a, *b, Some(1e-3))));
+    let bad_guide = Array2::<f64>::zeros((4, 4));
+    assert!(guided(&step, Some(bad_guide.view()), None, None, THREADS).is_err());
+    assert!(guided(&step, None, None, Some(0.0), THREADS).is_err());
+    Ok(())
+}
+
+/// Tests that `minimum_filter` and `maximum_filter` match the brute force
+/// minimum and maximum for the histogram (integer) and sort (float) paths.
+#[test]
+fn filter_minimum_maximum_filter_expected_results() -> Result<(), ImgalError> {
+    let data = Array2::from_shape_fn((11, 14), |(r, c)| ((r * 7919 + c * 104729) % 251) as u8);
+    let footprint = circle_kernel(2)?;
+    let min_par = minimum_filter(&data, footprint.view(), None, THREADS)?;
+    let min_seq = minimum_filter(&data, footprint.view(), None, None)?;
+    let max_par = maximum_filter(&data, footprint.view(), None, THREADS)?;
+    let max_seq = maximum_filter(&data, footprint.view(), None, None)?;
+    assert_eq!(min_par, min_seq);
+    assert_eq!(max_par, max_seq);
+    let float = data.mapv(|v| v as f64);
+    assert_eq!(
+        minimum_filter(&float, footprint.view(), None, THREADS)?,
+        min_par.mapv(|v| v as f64)
+    );
+    assert_eq!(
+        maximum_filter(&float, footprint.view(), None, THREADS)?,
+        max_par.mapv(|v| v as f64)
+    );
+    for r in 2..9 {
+        for c in 2..12 {
+            let window: Vec<u8> = footprint
+                .indexed_iter()
+                .filter(|(_, v)| **v)
+                .map(|((i, j), _)| data[[r + i - 2, c + j - 2]])
+                .collect();
+            assert_eq!(min_par[[r, c]], *window.iter().min().unwrap());
+            assert_eq!(max_par[[r, c]], *window.iter().max().unwrap());
+        }
+    }
+    // a 3D sphere erodes and dilates a single bright voxel
+    let mut spot = Array3::<u16>::zeros((5, 5, 5));
+    spot[[2, 2, 2]] = 9;
+    let sphere = sphere_kernel(1)?;
+    assert!(
+        minimum_filter(&spot, sphere.view(), None, THREADS)?
+            .iter()
+            .all(|&v| v == 0)
+    );
+    let dilated = maximum_filter(&spot, sphere.view(), None, THREADS)?;
+    assert_eq!(dilated.iter().filter(|&&v| v == 9).count(), 7);
+    let empty = Array2::from_elem((3, 3), false);
+    assert!(minimum_filter(&data, empty.view(), None, THREADS).is_err());
+    assert!(maximum_filter(&data, Array2::from_elem((2, 3), true).view(), None, THREADS).is_err());
+    Ok(())
+}
+
+/// Tests that `median_filter` matches a brute force median for the histogram
+/// (integer) and sort (float) paths and removes impulse noise.
+#[test]
+fn filter_median_filter_expected_results() -> Result<(), ImgalError> {
+    let data = Array2::from_shape_fn((13, 17), |(r, c)| ((r * 7919 + c * 104729) % 251) as u16);
+    let footprint = circle_kernel(2)?;
+    let par = median_filter(&data, footprint.view(), None, THREADS)?;
+    let seq = median_filter(&data, footprint.view(), None, None)?;
+    assert_eq!(par, seq);
+    // the float path gives the same result
+    let float = median_filter(&data.mapv(|v| v as f64), footprint.view(), None, THREADS)?;
+    assert_eq!(float, par.mapv(|v| v as f64));
+    // brute force lower median of an interior pixel
+    let (r, c) = (6, 8);
+    let mut window: Vec<u16> = footprint
+        .indexed_iter()
+        .filter(|(_, v)| **v)
+        .map(|((i, j), _)| data[[r + i - 2, c + j - 2]])
+        .collect();
+    window.sort();
+    assert_eq!(par[[r, c]], window[(window.len() - 1) / 2]);
+    // a constant border of zeros biases the corners down with a rectangle
+    let rect = Array2::from_elem((3, 5), true);
+    let ones = Array2::<u8>::ones((6, 6));
+    let constant = median_filter(&ones, rect.view(), Some(BorderMode::Constant(0.0)), THREADS)?;
+    assert_eq!(constant[[0, 0]], 0);
+    assert_eq!(constant[[2, 2]], 1);
+    // a 3D sphere removes isolated impulses
+    let mut noisy = Array3::<u16>::from_elem((7, 7, 7), 100);
+    noisy[[3, 3, 3]] = u16::MAX;
+    noisy[[1, 5, 2]] = 0;
+    let clean = median_filter(&noisy, sphere_kernel(1)?.view(), None, THREADS)?;
+    assert!(clean.iter().all(|&v| v == 100));
+    // a footprint wider than the image is reflected repeatedly (1 5 | 1 5 | 1 5)
+    let pair = arr2(&[[1u8, 5]]);
+    let wide = median_filter(&pair, Array2::from_elem((3, 5), true).view(), None, THREADS)?;
+    assert_eq!(wide, pair);
+    // even footprints, empty footprints and footprints with a different
+    // number of dimensions are invalid
+    let even = Array2::from_elem((2, 3), true);
+    assert!(median_filter(&data, even.view(), None, THREADS).is_err());
+    let empty = Array2::from_elem((3, 3), false);
+    assert!(median_filter(&data, empty.view(), None, THREADS).is_err());
+    let cube = ArrayD::from_elem(IxDyn(&[3, 3, 3]), true);
+    assert!(median_filter(data.view().into_dyn(), cube.view(), None, THREADS).is_err());
+    Ok(())
+}
+
+/// Tests that `percentile_filter` selects the nearest rank value and matches
+/// the minimum, median and maximum filters at `0`, `50` and `100`.
+#[test]
+fn filter_percentile_filter_expected_results() -> Result<(), ImgalError> {
+    let data = Array2::from_shape_fn((12, 10), |(r, c)| ((r * 31 + c * 17) % 23) as f64);
+    let rect = Array2::from_elem((3, 3), true);
+    let p25_par = percentile_filter(&data, rect.view(), 25.0, None, THREADS)?;
+    let p25_seq = percentile_filter(&data, rect.view(), 25.0, None, None)?;
+    assert_eq!(p25_par, p25_seq);
+    let (r, c) = (5, 4);
+    let mut window: Vec<f64> = data
+        .slice(s![r - 1..=r + 1, c - 1..=c + 1])
+        .iter()
+        .copied()
+        .collect();
+    window.sort_by(f64::total_cmp);
+    assert_eq!(p25_par[[r, c]], window[2]);
+    assert_eq!(
+        percentile_filter(&data, rect.view(), 0.0, None, THREADS)?,
+        minimum_filter(&data, rect.view(), None, THREADS)?
+    );
+    assert_eq!(
+        percentile_filter(&data, rect.view(), 50.0, None, THREADS)?,
+        median_filter(&data, rect.view(), None, THREADS)?
+    );
+    assert_eq!(
+        percentile_filter(&data.mapv(|v| v as u16), rect.view(), 100.0, None, THREADS)?,
+        maximum_filter(&data.mapv(|v| v as u16), rect.view(), None, THREADS)?
+    );
+    assert!(percentile_filter(&data, rect.view(), -1.0, None, THREADS).is_err());
+    assert!(percentile_filter(&data, rect.view(), 100.5, None, THREADS).is_err());
+    Ok(())
+}
+
+/// Tests that `rank_transform` counts the neighbors less than the center and
+/// is invariant to monotonic intensity changes.
+#[test]
+fn filter_rank_transform_expected_results() -> Result<(), ImgalError> {
+    let data = Array3::from_shape_fn((4, 6, 7), |(p, r, c)| {
+        ((p * 11 + r * 5 + c * 3) % 13) as f64
+    });
+    let footprint = sphere_kernel(1)?;
+    let rank_par = rank_transform(&data, footprint.view(), None, THREADS)?;
+    let rank_seq = rank_transform(&data, footprint.view(), None, None)?;
+    assert_eq!(rank_par, rank_seq);
+    let center = data[[2, 3, 3]];
+    let expected = [
+        [1, 3, 3],
+        [3, 2, 3],
+        [3, 4, 3],
+        [3, 3, 2],
+        [3, 3, 4],
+        [2, 3, 3],
+        [3, 3, 3],
+    ]
+    .iter()
+    .filter(|p| data[**p] < center)
+    .count() as u64;
+    assert_eq!(rank_par[[2, 3, 3]], expected);
+    let exp = data.mapv(|v| v.exp());
+    assert_eq!(
+        rank_transform(&exp, footprint.view(), None, None)?,
+        rank_par
+    );
+    let u8_data = data.mapv(|v| v as u8);
+    assert_eq!(
+        rank_transform(&u8_data, footprint.view(), None, None)?,
+        rank_par
+    );
+    assert!(rank_transform(&data, Array3::from_elem((3, 3, 2), true).view(), None, None).is_err());
+    Ok(())
+}
+
+/// Tests that `sobel` returns the expected derivatives of a 2D step edge and a
+/// 3D linear ramp.
+#[test]
+fn filter_sobel_expected_results() -> Result<(), ImgalError> {
+    let step = Array2::from_shape_fn((10, 10), |(_, c)| if c < 5 { 0.0 } else { 1.0 });
+    let out_par = sobel(&step, None, THREADS)?;
+    let out_seq = sobel(&step, None, None)?;
+    assert_eq!(out_par, out_seq);
+    assert_eq!(out_par.gradients.len(), 2);
+    assert!(out_par.gradients[0].iter().all(|&g| g == 0.0));
+    assert_eq!(out_par.gradients[1][[5, 4]], 4.0);
+    assert_eq!(out_par.gradients[1][[5, 5]], 4.0);
+    assert_eq!(out_par.gradients[1][[5, 2]], 0.0);
+    assert_eq!(out_par.magnitude, out_par.gradients[1].mapv(f64::abs));
+    let ramp = Array3::from_shape_fn((5, 6, 7), |(p, r, _)| 2.0 * p as f64 + r as f64);
+    let out_3d = sobel(&ramp, None, THREADS)?;
+    // the central difference of the ramp times the smoothing weights (4 × 4)
+    assert_eq!(out_3d.gradients[0][[2, 3, 3]], 64.0);
+    assert_eq!(out_3d.gradients[1][[2, 3, 3]], 32.0);
+    assert_eq!(out_3d.gradients[2][[2, 3, 3]], 0.0);
+    assert!(approx_equal(
+        out_3d.magnitude[[2, 3, 3]],
+        (64.0_f64 * 64.0 + 32.0 * 32.0).sqrt(),
+        None
+    ));
+    // a single pixel axis is extended with its value
+    let line = sobel(&arr2(&[[0.0, 1.0, 2.0, 3.0, 4.0]]), None, None)?;
+    assert!(line.gradients[0].iter().all(|&g| g == 0.0));
+    assert_eq!(line.gradients[1][[0, 2]], 8.0);
+    Ok(())
+}
+
+/// Tests that `structure_tensor` finds the orientation of straight and
+/// diagonal stripes with a high coherence, and no coherence in flat images.
+#[test]
+fn filter_structure_tensor_expected_results() -> Result<(), ImgalError> {
+    // stripes along the row axis, the intensity varies along the col axis
+    let stripes = Array2::from_shape_fn((32, 32), |(_, c)| (2.0 * PI * c as f64 / 8.0).cos());
+    let out_par = structure_tensor(&stripes, 2.0, None, None, THREADS)?;
+    let out_seq = structure_tensor(&stripes, 2.0, None, None, None)?;
+    assert_eq!(out_par, out_seq);
+    let inner = s![8..24, 8..24];
+    assert!(
+        out_par
+            .jrr
+            .slice(inner)
+            .iter()
+            .all(|&v| approx_equal(v, 0.0, None))
+    );
+    assert!(out_par.jcc.slice(inner).iter().all(|&v| v > 0.0));
+    assert!(
+        out_par
+            .orientation
+            .slice(inner)
+            .iter()
+            .all(|&o| approx_equal(o.abs(), FRAC_PI_2, Some(1e-6)))
+    );
+    assert!(
+        out_par
+            .coherence
+            .slice(inner)
+            .iter()
+            .all(|&c| approx_equal(c, 1.0, Some(1e-6)))
+    );
+    assert_eq!(out_par.energy, &out_par.jrr + &out_par.jcc);
+    // diagonal stripes along the (1, -1) direction
+    let diagonal =
+        Array2::from_shape_fn((32, 32), |(r, c)| (2.0 * PI * (r + c) as f64 / 8.0).cos());
+    let out = structure_tensor(&diagonal, 2.0, Some(0.0), None, None)?;
+    assert!(approx_equal(
+        out.orientation[[16, 16]],
+        -FRAC_PI_4,
+        Some(1e-6)
+    ));
+    assert!(approx_equal(out.coherence[[16, 16]], 1.0, Some(1e-6)));
+    // a radially symmetric blob has no dominant orientation at its center
+    let blob = Array2::from_shape_fn((33, 33), |(r, c)| {
+        let d2 = (r as f64 - 16.0).powi(2) + (c as f64 - 16.0).powi(2);
+        (-d2 / 50.0).exp()
+    });
+    let out = structure_tensor(&blob, 3.0, None, None, None)?;
+    assert!(out.coherence[[16, 16]] < 1e-6);
+    let flat = structure_tensor(&Array2::<f64>::ones((16, 16)), 1.0, None, None, None)?;
+    assert!(flat.coherence.iter().all(|&c| c == 0.0));
+    assert!(flat.energy.iter().all(|&e| e == 0.0));
+    assert!(structure_tensor(&stripes, -1.0, None, None, None).is_err());
+    assert!(structure_tensor(&stripes, 1.0, Some(f64::NAN), None, None).is_err());
+    Ok(())
+}
+
+/// Tests that `uniform_filter` matches the direct window mean of a 3D image
+/// with reflected and constant borders.
+#[test]
+fn filter_uniform_filter_expected_results() -> Result<(), ImgalError> {
+    let data = Array3::from_shape_fn((6, 7, 8), |(p, r, c)| {
+        ((p * 31 + r * 7 + c * 3) % 11) as f64
+    });
+    let radius = [1, 2, 0];
+    let out_par = uniform_filter(&data, &radius, Some(BorderMode::Constant(0.0)), THREADS)?;
+    let out_seq = uniform_filter(&data, &radius, Some(BorderMode::Constant(0.0)), None)?;
+    assert_eq!(out_par.shape(), data.shape());
+    out_par
+        .iter()
+        .zip(out_seq.iter())
+        .for_each(|(a, b)| assert!(approx_equal(*a, *b, None)));
+    // the direct window mean with zeros outside of the image
+    let direct = |p: usize, r: usize, c: usize| -> f64 {
+        let mut sum = 0.0;
+        for dp in -1_isize..=1 {
+            for dr in -2_isize..=2 {
+                let (pp, rr) = (p as isize + dp, r as isize + dr);
+                if (0..6).contains(&pp) && (0..7).contains(&rr) {
+                    sum += data[[pp as usize, rr as usize, c]];
+                }
+            }
+        }
+        sum / 15.0
+    };
+    out_par
+        .indexed_iter()
+        .for_each(|((p, r, c), v)| assert!(approx_equal(*v, direct(p, r, c), None)));
+    // a reflected border preserves a constant image
+    let flat = uniform_filter(&Array2::<f64>::from_elem((5, 5), 3.0), &[2, 2], None, None)?;
+    assert!(flat.iter().all(|&v| approx_equal(v, 3.0, None)));
+    // a radius exceeding the axis is reflected repeatedly (1 2 1 | 0 1 2 | 1 0 1)
+    let wide = uniform_filter(&arr2(&[[0.0, 1.0, 2.0]]), &[0, 3], None, None)?;
+    wide.iter()
+        .zip([8.0 / 7.0, 1.0, 6.0 / 7.0])
+        .for_each(|(a, b)| assert!(approx_equal(*a, b, None)));
+    assert!(uniform_filter(&data, &[1, 1], None, None).is_err());
+    Ok(())
+}
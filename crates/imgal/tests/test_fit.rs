@@ -0,0 +1,389 @@
+use ndarray::{Array2, Array3};
+
+use imgal::fit::{
+    FitObjective, ModelCriterion, fit_decay, fit_decay_image, laguerre_lifetime,
+    laguerre_lifetime_image, model_select, model_select_image, reduced_chi_square,
+    reduced_chi_square_image, residual_autocorrelation, rld, rld_image, weighted_residuals,
+    weighted_residuals_image,
+};
+use imgal::prelude::*;
+use imgal::simulation::decay::{
+    ideal_exponential_decay_1d, ideal_exponential_decay_3d, irf_exponential_decay_1d,
+};
+use imgal::simulation::instrument::gaussian_irf_1d;
+use imgal::simulation::noise::poisson_noise;
+
+const TOLERANCE: f64 = 1e-10;
+const SAMPLES: usize = 256;
+const PERIOD: f64 = 12.5;
+const TAU: [f64; 1] = [2.0];
+const FRACTION: [f64; 1] = [1.0];
+const TOTAL_COUNTS: f64 = 5000.0;
+const SHAPE: (usize, usize) = (10, 10);
+const THREADS: Option<usize> = Some(0);
+
+fn approx_equal(a: f64, b: f64, tol: Option<f64>) -> bool {
+    (a - b).abs() < tol.unwrap_or(TOLERANCE)
+}
+
+// the simulated decays are sampled with a bin width of "period / (samples - 1)"
+// while the RLD estimator uses a bin width of "period / samples"
+fn expected_tau() -> f64 {
+    TAU[0] * (SAMPLES - 1) as f64 / SAMPLES as f64
+}
+
+/// Tests that `rld` recovers the lifetime of an ideal monoexponential decay
+/// with the two-gate and multi-gate estimators.
+#[test]
+fn fit_rld_expected_results() -> Result<(), ImgalError> {
+    let decay = ideal_exponential_decay_1d(SAMPLES, PERIOD, &TAU, &FRACTION, TOTAL_COUNTS, None)?;
+    let tau_two = rld(&decay, PERIOD, None, None)?;
+    let tau_multi = rld(&decay, PERIOD, Some(4), Some(0))?;
+    assert!(approx_equal(tau_two, expected_tau(), None));
+    assert!(approx_equal(tau_multi, expected_tau(), None));
+    assert!(rld(&decay, PERIOD, Some(1), None).is_err());
+    assert!(rld(&decay, PERIOD, Some(4), Some(SAMPLES - 2)).is_err());
+    Ok(())
+}
+
+/// Tests that `rld_image` returns the expected lifetime image with and without
+/// a mask.
+#[test]
+fn fit_rld_image_expected_results() -> Result<(), ImgalError> {
+    let data =
+        ideal_exponential_decay_3d(SAMPLES, PERIOD, &TAU, &FRACTION, TOTAL_COUNTS, SHAPE, None)?;
+    let mut mask = Array2::<bool>::from_elem(SHAPE, true);
+    mask[[0, 0]] = false;
+    let tau_par = rld_image(&data, PERIOD, None, None, None, None, THREADS)?;
+    let tau_seq = rld_image(&data, PERIOD, Some(3), None, None, None, None)?;
+    let tau_msk = rld_image(&data, PERIOD, None, None, Some(mask.view()), None, THREADS)?;
+    assert_eq!(tau_par.shape(), [10, 10]);
+    assert!(approx_equal(tau_par[[5, 5]], expected_tau(), None));
+    assert!(approx_equal(tau_seq[[5, 5]], expected_tau(), None));
+    assert_eq!(tau_msk[[0, 0]], 0.0);
+    assert!(approx_equal(tau_msk[[9, 9]], expected_tau(), None));
+    assert!(rld_image(&data, PERIOD, None, None, None, Some(3), None).is_err());
+    Ok(())
+}
+
+/// Tests that `fit_decay` recovers the lifetime of an ideal monoexponential
+/// decay with both objectives.
+#[test]
+fn fit_fit_decay_expected_results() -> Result<(), ImgalError> {
+    let decay = ideal_exponential_decay_1d(SAMPLES, PERIOD, &TAU, &FRACTION, TOTAL_COUNTS, None)?;
+    let (params_ls, conv_ls) = fit_decay(&decay, PERIOD, &[1.0], None, None, None)?;
+    let (params_mle, conv_mle) = fit_decay(
+        &decay,
+        PERIOD,
+        &[1.0],
+        None,
+        Some(FitObjective::PoissonMle),
+        None,
+    )?;
+    assert!(conv_ls && conv_mle);
+    assert_eq!(params_ls.len(), 3);
+    assert!(approx_equal(params_ls[1], expected_tau(), Some(1e-6)));
+    assert!(approx_equal(params_mle[1], expected_tau(), Some(1e-6)));
+    assert!(approx_equal(params_mle[2], 0.0, Some(1e-6)));
+    assert!(fit_decay(&decay, PERIOD, &[] as &[f64], None, None, None).is_err());
+    assert!(fit_decay(&decay, PERIOD, &[-1.0], None, None, None).is_err());
+    assert!(fit_decay(&decay, PERIOD, &[1.0], Some(SAMPLES - 3), None, None).is_err());
+    Ok(())
+}
+
+/// Tests that `fit_decay_image` fits each pixel, flags convergence and that
+/// the Poisson MLE objective is less biased than least squares for low-count
+/// decays.
+#[test]
+fn fit_fit_decay_image_expected_results() -> Result<(), ImgalError> {
+    let data =
+        ideal_exponential_decay_3d(SAMPLES, PERIOD, &TAU, &FRACTION, TOTAL_COUNTS, SHAPE, None)?;
+    let mut mask = Array2::<bool>::from_elem(SHAPE, true);
+    mask[[0, 0]] = false;
+    let (params_par, conv_par) =
+        fit_decay_image(&data, PERIOD, &[1.0], None, None, None, None, None, THREADS)?;
+    let (params_msk, conv_msk) = fit_decay_image(
+        &data,
+        PERIOD,
+        &[1.0],
+        None,
+        None,
+        None,
+        Some(mask.view()),
+        None,
+        None,
+    )?;
+    assert_eq!(params_par.shape(), [10, 10, 3]);
+    assert!(conv_par.iter().all(|&c| c));
+    assert!(approx_equal(
+        params_par[[5, 5, 1]],
+        expected_tau(),
+        Some(1e-6)
+    ));
+    assert!(!conv_msk[[0, 0]]);
+    assert_eq!(params_msk[[0, 0, 1]], 0.0);
+    assert!(approx_equal(
+        params_msk[[9, 9, 1]],
+        expected_tau(),
+        Some(1e-6)
+    ));
+    // photon-starved decays with ~100 photons per pixel
+    let low = ideal_exponential_decay_3d(SAMPLES, PERIOD, &TAU, &FRACTION, 100.0, SHAPE, None)?;
+    let noisy = poisson_noise(&low, 1.0, Some(42), None);
+    let mean_tau = |objective: FitObjective| -> Result<f64, ImgalError> {
+        let (params, _) = fit_decay_image(
+            &noisy,
+            PERIOD,
+            &[1.0],
+            Some(0),
+            Some(objective),
+            None,
+            None,
+            None,
+            THREADS,
+        )?;
+        Ok(params.index_axis(ndarray::Axis(2), 1).mean().unwrap())
+    };
+    let err_ls = (mean_tau(FitObjective::LeastSquares)? - expected_tau()).abs();
+    let err_mle = (mean_tau(FitObjective::PoissonMle)? - expected_tau()).abs();
+    assert!(err_mle < err_ls);
+    assert!(err_mle < 0.1 * TAU[0]);
+    Ok(())
+}
+
+/// Tests that `weighted_residuals` and `reduced_chi_square` measure the fit
+/// quality of ideal and noisy decays.
+#[test]
+fn fit_goodness_expected_results() -> Result<(), ImgalError> {
+    let decay = ideal_exponential_decay_1d(SAMPLES, PERIOD, &TAU, &FRACTION, TOTAL_COUNTS, None)?;
+    let (params, _) = fit_decay(&decay, PERIOD, &[1.0], None, None, None)?;
+    let res = weighted_residuals(&decay, &params, PERIOD, None)?;
+    assert_eq!(res.len(), SAMPLES);
+    assert!(res.iter().all(|r| r.abs() < 1e-6));
+    assert!(reduced_chi_square(&decay, &params, PERIOD, None)? < 1e-10);
+    let noisy = poisson_noise(&decay, 1.0, Some(42), None);
+    let (noisy_params, _) = fit_decay(
+        &noisy,
+        PERIOD,
+        &[1.0],
+        Some(0),
+        Some(FitObjective::PoissonMle),
+        None,
+    )?;
+    let chi = reduced_chi_square(&noisy, &noisy_params, PERIOD, Some(0))?;
+    assert!(chi > 0.7 && chi < 1.3);
+    // a wrong lifetime leaves systematic, correlated residuals
+    let wrong = ndarray::arr1(&[noisy_params[0], 1.0, noisy_params[2]]);
+    let chi_wrong = reduced_chi_square(&noisy, &wrong, PERIOD, Some(0))?;
+    let res_wrong = weighted_residuals(&noisy, &wrong, PERIOD, Some(0))?;
+    let res_good = weighted_residuals(&noisy, &noisy_params, PERIOD, Some(0))?;
+    let ac_wrong = residual_autocorrelation(&res_wrong, Some(5))?;
+    let ac_good = residual_autocorrelation(&res_good, Some(5))?;
+    assert!(chi_wrong > 2.0 * chi);
+    assert_eq!(ac_good.len(), 6);
+    assert!(approx_equal(ac_good[0], 1.0, None));
+    assert!(ac_wrong[1] > 0.5);
+    assert!(ac_good[1].abs() < 0.3);
+    assert!(reduced_chi_square(&decay, &[1.0, 2.0], PERIOD, None).is_err());
+    assert!(weighted_residuals(&decay, &params, PERIOD, Some(SAMPLES)).is_err());
+    Ok(())
+}
+
+/// Tests that `residual_autocorrelation` returns the expected results for an
+/// alternating residual trace.
+#[test]
+fn fit_residual_autocorrelation_expected_results() -> Result<(), ImgalError> {
+    let res = ndarray::arr1(&[1.0, -1.0, 1.0, -1.0, 1.0, -1.0]);
+    let ac = residual_autocorrelation(&res, None)?;
+    assert_eq!(ac.len(), 4);
+    assert!(approx_equal(ac[0], 1.0, None));
+    assert!(approx_equal(ac[1], -1.0, None));
+    assert!(approx_equal(ac[2], 1.0, None));
+    assert!(residual_autocorrelation(&res, Some(6)).is_err());
+    Ok(())
+}
+
+/// Tests that `weighted_residuals_image` and `reduced_chi_square_image` return
+/// the expected shapes and values for a fitted decay image.
+#[test]
+fn fit_goodness_image_expected_results() -> Result<(), ImgalError> {
+    let data =
+        ideal_exponential_decay_3d(SAMPLES, PERIOD, &TAU, &FRACTION, TOTAL_COUNTS, SHAPE, None)?;
+    let mut mask = Array2::<bool>::from_elem(SHAPE, true);
+    mask[[0, 0]] = false;
+    let (params, _) =
+        fit_decay_image(&data, PERIOD, &[1.0], None, None, None, None, None, THREADS)?;
+    let res = weighted_residuals_image(&data, &params, PERIOD, None, None, THREADS)?;
+    let chi_par = reduced_chi_square_image(&data, &params, PERIOD, None, None, None, THREADS)?;
+    let chi_seq = reduced_chi_square_image(&data, &params, PERIOD, None, None, None, None)?;
+    let chi_msk =
+        reduced_chi_square_image(&data, &params, PERIOD, None, Some(mask.view()), None, None)?;
+    assert_eq!(res.shape(), data.shape());
+    assert!(res.iter().all(|r| r.abs() < 1e-6));
+    assert_eq!(chi_par.shape(), [10, 10]);
+    assert_eq!(chi_par, chi_seq);
+    assert!(chi_par[[5, 5]] < 1e-10);
+    assert_eq!(chi_msk[[0, 0]], 0.0);
+    assert!(weighted_residuals_image(&data, &params, PERIOD, None, Some(0), None).is_err());
+    Ok(())
+}
+
+/// Tests that `model_select` and `model_select_image` select one component
+/// for a monoexponential decay and two components for a biexponential decay.
+#[test]
+fn fit_model_select_expected_results() -> Result<(), ImgalError> {
+    let mono = ideal_exponential_decay_1d(SAMPLES, PERIOD, &TAU, &FRACTION, 1e5, None)?;
+    let bi = poisson_noise(
+        &ideal_exponential_decay_1d(SAMPLES, PERIOD, &[0.5, 3.0], &[0.5, 0.5], 1e6, None)?,
+        1.0,
+        None,
+        None,
+    );
+    let (k_mono, params_mono) = model_select(&mono, PERIOD, 1.0, None, None, None, None)?;
+    assert_eq!(k_mono, 1);
+    assert_eq!(params_mono.len(), 3);
+    assert!(approx_equal(params_mono[1], expected_tau(), Some(1e-4)));
+    for criterion in [
+        ModelCriterion::Aic,
+        ModelCriterion::Bic,
+        ModelCriterion::FTest(0.05),
+    ] {
+        let (k_bi, params_bi) = model_select(&bi, PERIOD, 1.0, None, None, Some(criterion), None)?;
+        assert_eq!(k_bi, 2);
+        assert_eq!(params_bi.len(), 5);
+    }
+    let mut data = Array3::<f64>::zeros((2, 3, SAMPLES));
+    data.indexed_iter_mut().for_each(|((r, _, t), v)| {
+        *v = if r == 0 { mono[t] } else { bi[t] };
+    });
+    let mut mask = Array2::from_elem((2, 3), true);
+    mask[[1, 2]] = false;
+    let k_par = model_select_image(
+        &data,
+        PERIOD,
+        1.0,
+        Some(2),
+        None,
+        Some(ModelCriterion::Bic),
+        None,
+        Some(mask.view()),
+        None,
+        THREADS,
+    )?;
+    let k_seq = model_select_image(
+        &data,
+        PERIOD,
+        1.0,
+        Some(2),
+        None,
+        Some(ModelCriterion::Bic),
+        None,
+        Some(mask.view()),
+        None,
+        None,
+    )?;
+    assert_eq!(k_par, k_seq);
+    assert_eq!(k_par.row(0).to_vec(), vec![1, 1, 1]);
+    assert_eq!(k_par.row(1).to_vec(), vec![2, 2, 0]);
+    assert!(model_select(&mono, PERIOD, 1.0, Some(0), None, None, None).is_err());
+    assert!(model_select(&mono, PERIOD, -1.0, None, None, None, None).is_err());
+    assert!(
+        model_select(
+            &mono,
+            PERIOD,
+            1.0,
+            None,
+            None,
+            Some(ModelCriterion::FTest(1.5)),
+            None
+        )
+        .is_err()
+    );
+    Ok(())
+}
+
+/// Tests that `laguerre_lifetime` and `laguerre_lifetime_image` recover the
+/// mean lifetime of mono- and biexponential decays, with and without an IRF.
+#[test]
+fn fit_laguerre_lifetime_expected_results() -> Result<(), ImgalError> {
+    let mono = ideal_exponential_decay_1d(SAMPLES, PERIOD, &TAU, &FRACTION, TOTAL_COUNTS, None)?;
+    let (tau, order) = laguerre_lifetime(&mono, PERIOD, None, None, None, None)?;
+    assert_eq!(order, 1);
+    assert!(approx_equal(tau, expected_tau(), Some(1e-4)));
+    // the intensity weighted mean lifetime of a biexponential decay
+    let bi = ideal_exponential_decay_1d(SAMPLES, PERIOD, &[0.8, 3.0], &[0.5, 0.5], 1e5, None)?;
+    let (tau_bi, _) = laguerre_lifetime(&bi, PERIOD, None, None, None, None)?;
+    let tau_mean = 1.9 * (SAMPLES - 1) as f64 / SAMPLES as f64;
+    assert!(approx_equal(tau_bi, tau_mean, Some(0.02 * tau_mean)));
+    // deconvolve a Gaussian IRF
+    let irf = gaussian_irf_1d(SAMPLES, PERIOD, 1.5, 0.3, None);
+    let conv = irf_exponential_decay_1d(&irf, SAMPLES, PERIOD, &TAU, &FRACTION, 1e5, None)?;
+    let (tau_irf, _) = laguerre_lifetime(&conv, PERIOD, Some(irf.view()), None, None, None)?;
+    assert!(approx_equal(
+        tau_irf,
+        expected_tau(),
+        Some(0.02 * expected_tau())
+    ));
+    let mut data = Array3::<f64>::zeros((2, 3, SAMPLES));
+    data.indexed_iter_mut().for_each(|((r, _, t), v)| {
+        *v = if r == 0 { mono[t] } else { mono[t] * 0.1 };
+    });
+    let mut mask = Array2::from_elem((2, 3), true);
+    mask[[1, 2]] = false;
+    let (tau_par, order_par) = laguerre_lifetime_image(
+        &data,
+        PERIOD,
+        None,
+        None,
+        None,
+        None,
+        Some(mask.view()),
+        None,
+        THREADS,
+    )?;
+    let (tau_seq, order_seq) = laguerre_lifetime_image(
+        &data,
+        PERIOD,
+        None,
+        None,
+        None,
+        None,
+        Some(mask.view()),
+        None,
+        None,
+    )?;
+    assert_eq!(tau_par, tau_seq);
+    assert_eq!(order_par, order_seq);
+    assert!(approx_equal(tau_par[[0, 1]], expected_tau(), Some(1e-4)));
+    assert!(approx_equal(tau_par[[1, 0]], expected_tau(), Some(1e-3)));
+    assert_eq!((tau_par[[1, 2]], order_par[[1, 2]]), (0.0, 0));
+    assert!(laguerre_lifetime(&mono, PERIOD, None, None, Some(0), None).is_err());
+    assert!(laguerre_lifetime(&mono, PERIOD, None, Some(1.0), None, None).is_err());
+    assert!(
+        laguerre_lifetime(
+            &mono,
+            PERIOD,
+            Some(irf.slice(ndarray::s![..10])),
+            None,
+            None,
+            None
+        )
+        .is_err()
+    );
+    assert!(laguerre_lifetime(&mono, PERIOD, None, None, None, Some(SAMPLES - 3)).is_err());
+    Ok(())
+}
+
+/// Tests that `fit_decay` does not flag fits with duplicate lifetimes, whose
+/// amplitudes can not be separated, as converged.
+#[test]
+fn fit_fit_decay_duplicate_taus_not_converged() -> Result<(), ImgalError> {
+    let decay = ideal_exponential_decay_1d(SAMPLES, PERIOD, &TAU, &FRACTION, TOTAL_COUNTS, None)?;
+    for objective in [FitObjective::LeastSquares, FitObjective::PoissonMle] {
+        let (params, converged) =
+            fit_decay(&decay, PERIOD, &[1.0, 1.0], None, Some(objective), None)?;
+        assert_eq!(params.len(), 5);
+        assert!(!converged);
+    }
+    Ok(())
+}
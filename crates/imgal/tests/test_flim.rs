@@ -0,0 +1,200 @@
+use ndarray::{Array3, s};
+
+use imgal::flim::{
+    estimate_irf, estimate_irf_shift, extract_irf, pileup_correction, rebin_decay, resample_decay,
+    shift_irf,
+};
+use imgal::phasor::time_domain::{imaginary_coord, real_coord};
+use imgal::prelude::*;
+use imgal::simulation::decay::{ideal_exponential_decay_1d, irf_exponential_decay_1d};
+use imgal::simulation::instrument::{dead_time_pileup, gaussian_irf_1d};
+
+const TOLERANCE: f64 = 1e-10;
+const SAMPLES: usize = 256;
+const PERIOD: f64 = 12.5;
+const TAU: f64 = 2.0;
+const IRF_CENTER: f64 = 1.5;
+const IRF_WIDTH: f64 = 0.4;
+
+fn approx_equal(a: f64, b: f64, tol: Option<f64>) -> bool {
+    (a - b).abs() < tol.unwrap_or(TOLERANCE)
+}
+
+// the simulated curves are sampled with a bin width of "period / (samples - 1)"
+// while the IRF functions use a bin width of "period / samples"
+fn to_analysis_time(t: f64) -> f64 {
+    t * (SAMPLES - 1) as f64 / SAMPLES as f64
+}
+
+/// Tests that `extract_irf` recovers a Gaussian IRF from a simulated reference
+/// decay with a known lifetime.
+#[test]
+fn flim_extract_irf_expected_results() -> Result<(), ImgalError> {
+    let irf = gaussian_irf_1d(SAMPLES, PERIOD, IRF_CENTER, IRF_WIDTH, None);
+    let reference = irf_exponential_decay_1d(&irf, SAMPLES, PERIOD, &[TAU], &[1.0], 10000.0, None)?;
+    let extracted = extract_irf(&reference, PERIOD, to_analysis_time(TAU))?;
+    assert_eq!(extracted.len(), SAMPLES);
+    assert!(approx_equal(extracted.sum(), 1.0, None));
+    extracted
+        .iter()
+        .zip(irf.iter())
+        .for_each(|(a, b)| assert!(approx_equal(*a, *b, Some(1e-6))));
+    assert!(extract_irf(&reference, PERIOD, 0.0).is_err());
+    assert!(extract_irf(&[1.0], PERIOD, TAU).is_err());
+    Ok(())
+}
+
+/// Tests that `estimate_irf` recovers the center and FWHM of a Gaussian IRF.
+#[test]
+fn flim_estimate_irf_expected_results() -> Result<(), ImgalError> {
+    let irf = gaussian_irf_1d(SAMPLES, PERIOD, IRF_CENTER, IRF_WIDTH, None);
+    let (center, fwhm) = estimate_irf(&irf, PERIOD)?;
+    assert!(approx_equal(
+        center,
+        to_analysis_time(IRF_CENTER),
+        Some(1e-6)
+    ));
+    assert!(approx_equal(fwhm, to_analysis_time(IRF_WIDTH), Some(1e-6)));
+    assert!(estimate_irf(&[0.0, 0.0, 0.0], PERIOD).is_err());
+    Ok(())
+}
+
+/// Tests that `pileup_correction` recovers the true decay of a simulated
+/// pile-up distorted histogram.
+#[test]
+fn flim_pileup_correction_expected_results() -> Result<(), ImgalError> {
+    // the probability of a photon arriving in each bin per excitation cycle
+    let probs: Vec<f64> = (0..SAMPLES)
+        .map(|i| 0.002 * (-(i as f64) * PERIOD / SAMPLES as f64 / TAU).exp())
+        .collect();
+    let cycles = 1e6;
+    // only the first photon of each cycle is detected
+    let mut survive = 1.0;
+    let measured: Vec<f64> = probs
+        .iter()
+        .map(|p| {
+            let detected = cycles * survive * (1.0 - (-p).exp());
+            survive *= (-p).exp();
+            detected
+        })
+        .collect();
+    let mut data = Array3::<f64>::zeros((2, 3, SAMPLES));
+    data.indexed_iter_mut()
+        .for_each(|((_, _, t), v)| *v = measured[t]);
+    let corr_par = pileup_correction(&data, 80e6, 0.0, cycles / 80e6, None, Some(0))?;
+    let corr_seq = pileup_correction(&data, 80e6, 0.0, cycles / 80e6, Some(2), None)?;
+    assert_eq!(corr_par.shape(), data.shape());
+    assert_eq!(corr_par, corr_seq);
+    probs.iter().enumerate().for_each(|(t, p)| {
+        assert!(approx_equal(corr_par[[1, 2, t]], cycles * p, Some(1e-6)));
+    });
+    let corr_dead = pileup_correction(&data, 80e6, 50e-9, cycles / 80e6, None, None)?;
+    assert!(corr_dead[[0, 0, 0]] > corr_par[[0, 0, 0]]);
+    assert!(pileup_correction(&data, 80e6, 0.0, 1e-9, None, None).is_err());
+    assert!(pileup_correction(&data, 0.0, 0.0, 1.0, None, None).is_err());
+    assert!(pileup_correction(&data, 80e6, 0.0, 1.0, Some(3), None).is_err());
+    Ok(())
+}
+
+/// Tests that `pileup_correction` restores the phasor coordinates of a decay
+/// distorted by pile-up and dead time at a high count rate.
+#[test]
+fn flim_pileup_correction_phasor_expected_results() -> Result<(), ImgalError> {
+    let decay = ideal_exponential_decay_1d(SAMPLES, PERIOD, &[TAU], &[1.0], 2e4, None)?;
+    let (rate, dead_time, acq) = (80e6, 100e-9, 1e-3);
+    let measured = dead_time_pileup(&decay, rate, dead_time, acq, None, None)?;
+    let corrected = pileup_correction(&measured, rate, dead_time, acq, None, None)?;
+    let g_true = real_coord(&decay, PERIOD, None, None);
+    let s_true = imaginary_coord(&decay, PERIOD, None, None);
+    let g_meas = real_coord(&measured, PERIOD, None, None);
+    let g_corr = real_coord(&corrected, PERIOD, None, None);
+    let s_corr = imaginary_coord(&corrected, PERIOD, None, None);
+    // pile-up shortens the apparent lifetime, moving G towards 1
+    assert!(g_meas - g_true > 1e-3);
+    assert!(approx_equal(g_corr, g_true, Some(1e-6)));
+    assert!(approx_equal(s_corr, s_true, Some(1e-6)));
+    Ok(())
+}
+
+/// Tests that `estimate_irf_shift` recovers the delay between an IRF and a
+/// simulated decay, and that `shift_irf` aligns the IRF with the decay.
+#[test]
+fn flim_estimate_irf_shift_expected_results() -> Result<(), ImgalError> {
+    let irf = gaussian_irf_1d(SAMPLES, PERIOD, IRF_CENTER, IRF_WIDTH, None);
+    let delay = 0.37;
+    let delayed_irf = gaussian_irf_1d(SAMPLES, PERIOD, IRF_CENTER + delay, IRF_WIDTH, None);
+    let decay =
+        irf_exponential_decay_1d(&delayed_irf, SAMPLES, PERIOD, &[TAU], &[1.0], 10000.0, None)?;
+    let shift = estimate_irf_shift(&irf, &decay, PERIOD, Some(to_analysis_time(TAU)))?;
+    let approx_shift = estimate_irf_shift(&irf, &decay, PERIOD, None)?;
+    assert!(approx_equal(shift, to_analysis_time(delay), Some(1e-3)));
+    assert!(approx_equal(
+        approx_shift,
+        to_analysis_time(delay),
+        Some(0.1)
+    ));
+    // a decay leading the IRF results in a negative shift
+    let early_irf = gaussian_irf_1d(SAMPLES, PERIOD, IRF_CENTER - delay, IRF_WIDTH, None);
+    let early =
+        irf_exponential_decay_1d(&early_irf, SAMPLES, PERIOD, &[TAU], &[1.0], 10000.0, None)?;
+    let early_shift = estimate_irf_shift(&irf, &early, PERIOD, Some(to_analysis_time(TAU)))?;
+    assert!(approx_equal(
+        early_shift,
+        -to_analysis_time(delay),
+        Some(1e-3)
+    ));
+    // the shifted IRF matches the delayed IRF
+    let shifted = shift_irf(&irf, to_analysis_time(delay), PERIOD)?;
+    let (center, _) = estimate_irf(&shifted, PERIOD)?;
+    assert!(approx_equal(
+        center,
+        to_analysis_time(IRF_CENTER + delay),
+        Some(1e-3)
+    ));
+    assert!(approx_equal(shifted.sum(), irf.sum(), None));
+    let whole = shift_irf(&irf, 2.0 * PERIOD / SAMPLES as f64, PERIOD)?;
+    assert!(approx_equal(whole[102], irf[100], None));
+    assert!(estimate_irf_shift(irf.view(), irf.slice(ndarray::s![..10]), PERIOD, None).is_err());
+    assert!(estimate_irf_shift(&irf, &decay, PERIOD, Some(0.0)).is_err());
+    assert!(shift_irf(&irf, 0.1, 0.0).is_err());
+    Ok(())
+}
+
+/// Tests that `rebin_decay` and `resample_decay` conserve the photon counts
+/// and agree for integer rebinning factors.
+#[test]
+fn flim_rebin_resample_decay_expected_results() -> Result<(), ImgalError> {
+    let decay = ideal_exponential_decay_1d(SAMPLES, PERIOD, &[TAU], &[1.0], 1e4, None)?;
+    let mut data = Array3::<f64>::zeros((2, SAMPLES, 3));
+    data.indexed_iter_mut()
+        .for_each(|((_, t, _), v)| *v = decay[t]);
+    let rebin = rebin_decay(&decay, 4, None, None)?;
+    assert_eq!(rebin.len(), SAMPLES / 4);
+    assert!(approx_equal(rebin[1], decay.slice(s![4..8]).sum(), None));
+    assert!(approx_equal(rebin.sum(), decay.sum(), Some(1e-8)));
+    let resample = resample_decay(&decay, PERIOD, SAMPLES / 4, None, None, None)?;
+    rebin
+        .iter()
+        .zip(resample.iter())
+        .for_each(|(a, b)| assert!(approx_equal(*a, *b, Some(1e-8))));
+    // along the middle axis of an image, in parallel
+    let rebin_img = rebin_decay(&data, 4, Some(1), Some(0))?;
+    assert_eq!(rebin_img.shape(), [2, SAMPLES / 4, 3]);
+    assert_eq!(rebin_img[[1, 1, 2]], rebin[1]);
+    // a finer resampling conserves counts, a longer period pads with zeros
+    let fine = resample_decay(&data, PERIOD, 3 * SAMPLES, None, Some(1), Some(0))?;
+    assert!(approx_equal(
+        fine.slice(s![0, .., 0]).sum(),
+        decay.sum(),
+        Some(1e-8)
+    ));
+    let long = resample_decay(&decay, PERIOD, 2 * SAMPLES, Some(2.0 * PERIOD), None, None)?;
+    assert!(approx_equal(long[10], decay[10], None));
+    assert_eq!(long[SAMPLES + 1], 0.0);
+    assert!(rebin_decay(&decay, 3, None, None).is_err());
+    assert!(rebin_decay(&decay, 0, None, None).is_err());
+    assert!(resample_decay(&decay, PERIOD, 0, None, None, None).is_err());
+    assert!(resample_decay(&decay, 0.0, 16, None, None, None).is_err());
+    assert!(resample_decay(&data, PERIOD, 16, None, Some(3), None).is_err());
+    Ok(())
+}
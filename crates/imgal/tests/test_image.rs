@@ -1,9 +1,13 @@
-use ndarray::arr2;
+use ndarray::{Array1, Array2, Array3, arr2, s};
 
-use imgal::image::{histogram, histogram_bin_midpoint, histogram_bin_range, percentile_normalize};
+use imgal::image::{
+    BinRule, estimate_illumination_profile, fit_polynomial_background, histogram, histogram_auto,
+    histogram_bin_count, histogram_bin_midpoint, histogram_bin_range, integral_image,
+    percentile_normalize, zscore_normalize,
+};
 use imgal::prelude::*;
 use imgal::simulation::blob::gaussian_metaballs;
-use imgal::statistics::min_max;
+use imgal::statistics::{mean, min_max, std};
 
 const TOLERANCE: f64 = 1e-10;
 const CENTER: [[f64; 2]; 1] = [[25.0, 25.0]];
@@ -18,6 +22,59 @@ fn approx_equal(a: f64, b: f64, tol: Option<f64>) -> bool {
     (a - b).abs() < tol.unwrap_or(TOLERANCE)
 }
 
+/// Tests that `fit_polynomial_background` recovers a quadratic background and
+/// that the mask excludes a bright object from the fit.
+#[test]
+fn image_fit_polynomial_background_expected_results() -> Result<(), ImgalError> {
+    let plane = |r: usize, c: usize| -> f64 {
+        let (y, x) = (r as f64, c as f64);
+        10.0 + 0.5 * x - 0.2 * y + 0.01 * x * x + 0.02 * x * y
+    };
+    let data = Array2::from_shape_fn((24, 32), |(r, c)| plane(r, c));
+    let (bg_par, corr_par) = fit_polynomial_background(&data, None, None, THREADS)?;
+    let (bg_seq, corr_seq) = fit_polynomial_background(&data, None, None, None)?;
+    bg_par
+        .indexed_iter()
+        .for_each(|((r, c), v)| assert!(approx_equal(*v, plane(r, c), Some(1e-8))));
+    corr_par
+        .iter()
+        .for_each(|v| assert!(approx_equal(*v, 0.0, Some(1e-8))));
+    bg_par
+        .iter()
+        .zip(bg_seq.iter())
+        .for_each(|(a, b)| assert!(approx_equal(*a, *b, Some(1e-8))));
+    assert_eq!(corr_par.shape(), corr_seq.shape());
+    // a bright object is excluded from the fit by the background mask
+    let mut object = data.clone();
+    object
+        .slice_mut(s![8..16, 10..20])
+        .mapv_inplace(|v| v + 100.0);
+    let mut mask = Array2::from_elem((24, 32), true);
+    mask.slice_mut(s![8..16, 10..20]).fill(false);
+    let (bg_mask, corr_mask) =
+        fit_polynomial_background(&object, Some(2), Some(mask.view()), THREADS)?;
+    assert!(approx_equal(bg_mask[[12, 15]], plane(12, 15), Some(1e-8)));
+    assert!(approx_equal(corr_mask[[12, 15]], 100.0, Some(1e-8)));
+    let (bg_flat, _) = fit_polynomial_background(&object, Some(0), None, None)?;
+    assert!(approx_equal(
+        bg_flat[[0, 0]],
+        object.mean().unwrap(),
+        Some(1e-8)
+    ));
+    assert!(fit_polynomial_background(&data, None, Some(mask.slice(s![..4, ..])), None).is_err());
+    assert!(
+        fit_polynomial_background(
+            &data,
+            Some(3),
+            Some(Array2::from_elem((24, 32), false).view()),
+            None
+        )
+        .is_err()
+    );
+    assert!(fit_polynomial_background(&Array2::<f64>::zeros((0, 0)), None, None, None).is_err());
+    Ok(())
+}
+
 /// Tests that `histogram` returns the expected values for the min/max of the
 /// image histogram and values at the beginning, middle and end of the
 /// histogram.
@@ -46,6 +103,49 @@ fn image_histogram_expected_results() -> Result<(), ImgalError> {
     assert_eq!(hist_seq[127], 16);
     assert_eq!(hist_par[255], 9);
     assert_eq!(hist_seq[255], 9);
+    // large 64-bit integers are binned without f64 precision loss
+    let base = u64::MAX - 3;
+    let u64_data = [base, base + 1, base + 2, base + 3];
+    let hist_u64 = histogram(&u64_data, Some(4), THREADS)?;
+    assert_eq!(hist_u64.to_vec(), vec![1, 1, 1, 1]);
+    Ok(())
+}
+
+/// Tests that `histogram_auto` and `histogram_bin_count` select the expected
+/// number of bins with the Freedman-Diaconis and Sturges' rules.
+#[test]
+fn image_histogram_auto_expected_results() -> Result<(), ImgalError> {
+    let data = Array1::from_shape_fn(1000, |i| i as f64);
+    // IQR = 499.5, w = 2 × 499.5 / 10 = 99.9, bins = ⌈999 / 99.9⌉ = 10
+    assert_eq!(histogram_bin_count(&data, None, THREADS)?, 10);
+    // ⌈log₂(1000)⌉ + 1 = 11
+    assert_eq!(
+        histogram_bin_count(&data, Some(BinRule::Sturges), None)?,
+        11
+    );
+    let (hist, edges) = histogram_auto(&data, None, THREADS)?;
+    assert_eq!(hist.len(), 10);
+    assert_eq!(edges.len(), 11);
+    assert_eq!(hist.sum(), 1000);
+    assert!(hist.iter().all(|&h| h == 100));
+    assert!(approx_equal(edges[0], 0.0, None));
+    assert!(approx_equal(edges[10], 999.0, None));
+    // an outlier narrows the bins of the Freedman-Diaconis rule
+    let mut outlier = data.clone();
+    outlier[999] = 99_900.0;
+    let (hist, edges) = histogram_auto(&outlier, None, None)?;
+    assert_eq!(hist.len(), 1000);
+    assert_eq!(edges.len(), 1001);
+    assert_eq!((hist[0], hist[999]), (100, 1));
+    // constant data and zero IQR
+    assert_eq!(
+        histogram_bin_count(&Array1::from_elem(10, 3.0), None, None)?,
+        1
+    );
+    let mut spike = Array1::<f64>::zeros(8);
+    spike[0] = 1.0;
+    assert_eq!(histogram_bin_count(&spike, None, None)?, 4);
+    assert!(histogram_auto(&Array1::<f64>::zeros(0), None, None).is_err());
     Ok(())
 }
 
@@ -72,6 +172,26 @@ fn image_histogram_bin_range_expected_results() -> Result<(), ImgalError> {
     Ok(())
 }
 
+/// Tests that `integral_image` returns the expected summed-area table of a 2D
+/// image and the total sum of a 3D image.
+#[test]
+fn image_integral_image_expected_results() -> Result<(), ImgalError> {
+    let data = arr2(&[[1, 2, 3], [4, 5, 6]]);
+    let table_par = integral_image(&data, THREADS);
+    let table_seq = integral_image(&data, None);
+    assert_eq!(table_par, table_seq);
+    assert_eq!(table_par, arr2(&[[1.0, 3.0, 6.0], [5.0, 12.0, 21.0]]));
+    let cube = Array3::from_shape_fn((4, 5, 6), |(p, r, c)| (p + r * c) as f64);
+    let table_3d = integral_image(&cube, THREADS);
+    assert!(approx_equal(table_3d[[3, 4, 5]], cube.sum(), None));
+    assert!(approx_equal(
+        table_3d[[1, 2, 3]],
+        cube.slice(s![..2, ..3, ..4]).sum(),
+        None
+    ));
+    Ok(())
+}
+
 /// Tests that `percentile_normalize` returns the expected values for per axis
 /// and flat normalization with precentiles `1.0` and `99.8` (with and without
 /// clipping).
@@ -132,3 +252,106 @@ fn image_percentile_normalize_expected_results() -> Result<(), ImgalError> {
     assert_eq!(min_max(&ax_clip_seq, None)?, (0.0, 1.0));
     Ok(())
 }
+
+/// Tests that `estimate_illumination_profile` recovers a vignetting profile
+/// from a stack of images with moving bright objects.
+#[test]
+fn image_estimate_illumination_profile_expected_results() -> Result<(), ImgalError> {
+    let profile = |r: usize, c: usize| -> f64 {
+        let dr = r as f64 - 16.0;
+        let dc = c as f64 - 16.0;
+        1.0 - 0.001 * (dr * dr + dc * dc)
+    };
+    let mut data = Array3::<f64>::zeros((9, 32, 32));
+    data.indexed_iter_mut().for_each(|((k, r, c), v)| {
+        // each image has a bright object at a different position
+        let object = r / 8 == k / 3 && c / 8 == k % 3;
+        *v = profile(r, c) * if object { 500.0 } else { 100.0 };
+    });
+    let mean: f64 = (0..32)
+        .flat_map(|r| (0..32).map(move |c| profile(r, c)))
+        .sum::<f64>()
+        / 1024.0;
+    let est_par = estimate_illumination_profile(&data, None, None, None, THREADS)?;
+    let est_seq = estimate_illumination_profile(&data, None, None, None, None)?;
+    let est_smooth = estimate_illumination_profile(&data, None, Some(1.0), None, None)?;
+    assert_eq!(est_par.shape(), [32, 32]);
+    assert_eq!(est_par, est_seq);
+    assert!(approx_equal(est_par.mean().unwrap(), 1.0, None));
+    est_par
+        .indexed_iter()
+        .for_each(|((r, c), v)| assert!(approx_equal(*v, profile(r, c) / mean, None)));
+    assert!(approx_equal(
+        est_smooth[[16, 16]],
+        profile(16, 16) / mean,
+        Some(1e-2)
+    ));
+    assert!(estimate_illumination_profile(&data, Some(101.0), None, None, None).is_err());
+    assert!(estimate_illumination_profile(&data, None, Some(0.0), None, None).is_err());
+    assert!(estimate_illumination_profile(&data, None, None, Some(3), None).is_err());
+    Ok(())
+}
+
+/// Tests that `zscore_normalize` returns images with zero mean and unit
+/// standard deviation for flat, per axis and masked normalization.
+#[test]
+fn image_zscore_normalize_expected_results() -> Result<(), ImgalError> {
+    let data = gaussian_metaballs(
+        &arr2(&CENTER),
+        &RADIUS,
+        &INTENSITY,
+        &FALLOFF,
+        BACKGROUND,
+        &SHAPE,
+        None,
+    )?;
+    let flat_par = zscore_normalize(&data, None, None, None, THREADS)?;
+    let flat_seq = zscore_normalize(&data, None, None, None, None)?;
+    assert_eq!(flat_par, flat_seq);
+    assert!(approx_equal(mean(&flat_par, None, None)?[0], 0.0, None));
+    assert!(approx_equal(
+        std(&flat_par, None, None, None)?[0],
+        1.0,
+        None
+    ));
+    let ax = zscore_normalize(&data, None, Some(0), None, THREADS)?;
+    let row_means = mean(&ax, Some(1), None)?;
+    let row_stds = std(&ax, Some(1), None, None)?;
+    row_means
+        .iter()
+        .for_each(|&m| assert!(approx_equal(m, 0.0, None)));
+    row_stds
+        .iter()
+        .for_each(|&s| assert!(approx_equal(s, 1.0, None)));
+    // standardize with respect to the upper half of the image
+    let mut mask = data.mapv(|_| false);
+    mask.slice_mut(s![..25, ..]).fill(true);
+    let masked = zscore_normalize(&data, Some(mask.view()), None, None, None)?;
+    let upper = masked.slice(s![..25, ..]);
+    assert_eq!(masked.shape(), data.shape());
+    assert!(approx_equal(mean(&upper, None, None)?[0], 0.0, None));
+    assert!(approx_equal(std(&upper, None, None, None)?[0], 1.0, None));
+    let masked_ax = zscore_normalize(&data, Some(mask.view()), Some(1), None, THREADS)?;
+    assert!(approx_equal(
+        mean(&masked_ax.slice(s![..25, 7]), None, None)?[0],
+        0.0,
+        None
+    ));
+    // a constant image is mapped to zero
+    let flat = zscore_normalize(&[3.0, 3.0, 3.0], None, None, None, None)?;
+    assert_eq!(flat.to_vec(), [0.0, 0.0, 0.0]);
+    assert!(zscore_normalize(&data, None, Some(2), None, None).is_err());
+    assert!(
+        zscore_normalize(
+            &data,
+            Some(mask.slice(s![.., ..3]).into_dyn()),
+            None,
+            None,
+            None
+        )
+        .is_err()
+    );
+    let empty_mask = data.mapv(|_| false);
+    assert!(zscore_normalize(&data, Some(empty_mask.view()), None, None, None).is_err());
+    Ok(())
+}
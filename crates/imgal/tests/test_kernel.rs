@@ -1,3 +1,5 @@
+#![allow(clippy::bool_assert_comparison)]
+
 use imgal::ImgalError;
 use imgal::kernel::neighborhood::{
     circle_kernel, sphere_kernel, weighted_circle_kernel, weighted_sphere_kernel,
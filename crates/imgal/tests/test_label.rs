@@ -0,0 +1,51 @@
+use ndarray::{Array3, arr2, s};
+
+use imgal::label::{BoundaryMode, boundaries, boundary_labels};
+
+const THREADS: Option<usize> = Some(0);
+
+/// Tests that `boundaries` and `boundary_labels` return the expected inner,
+/// outer and thick boundaries of touching objects.
+#[test]
+fn label_boundaries_expected_results() {
+    let labels = arr2(&[
+        [0, 0, 0, 0, 0, 0],
+        [0, 1, 1, 2, 2, 0],
+        [0, 1, 1, 2, 2, 0],
+        [0, 0, 0, 0, 0, 0],
+    ]);
+    let inner = boundary_labels(&labels, None, THREADS);
+    let outer = boundary_labels(&labels, Some(BoundaryMode::Outer), THREADS);
+    let thick = boundary_labels(&labels, Some(BoundaryMode::Thick), THREADS);
+    // all object pixels touch the background or the other object
+    assert_eq!(inner, labels);
+    assert_eq!(
+        outer,
+        arr2(&[
+            [0, 1, 1, 2, 2, 0],
+            [1, 0, 2, 1, 0, 2],
+            [1, 0, 2, 1, 0, 2],
+            [0, 1, 1, 2, 2, 0],
+        ])
+    );
+    assert_eq!(
+        thick,
+        arr2(&[
+            [0, 1, 1, 2, 2, 0],
+            [1, 1, 1, 2, 2, 2],
+            [1, 1, 1, 2, 2, 2],
+            [0, 1, 1, 2, 2, 0],
+        ])
+    );
+    assert_eq!(boundaries(&labels, None, None), inner.mapv(|v| v != 0));
+    // only the surface of a 3D cube is an inner boundary
+    let mut cube = Array3::<u64>::zeros((5, 5, 5));
+    cube.slice_mut(s![1..4, 1..4, 1..4]).fill(7);
+    let cube_par = boundaries(&cube, None, THREADS);
+    let cube_seq = boundaries(&cube, None, None);
+    assert_eq!(cube_par, cube_seq);
+    assert_eq!(cube_par.iter().filter(|&&b| b).count(), 27 - 1);
+    assert!(!cube_par[[2, 2, 2]]);
+    let cube_outer = boundaries(&cube, Some(BoundaryMode::Outer), None);
+    assert_eq!(cube_outer.iter().filter(|&&b| b).count(), 6 * 9);
+}
@@ -0,0 +1,357 @@
+use ndarray::{Array2, Array3, arr2, s};
+
+use imgal::measure::{
+    SimilarityMetric, confluence, curvature, fit_circle, fit_ellipse, fit_spline, regionprops,
+    roi_similarity, roi_statistics,
+};
+use imgal::morphology::label;
+use imgal::prelude::*;
+use imgal::simulation::noise::poisson_noise;
+use imgal::spatial::roi::roi_cloud_map;
+
+const TOLERANCE: f64 = 1e-10;
+const THREADS: Option<usize> = Some(0);
+
+fn approx_equal(a: f64, b: f64, tol: Option<f64>) -> bool {
+    (a - b).abs() < tol.unwrap_or(TOLERANCE)
+}
+
+// helper function to sample points on an ellipse with an optional wobble
+fn ellipse_points(
+    center: (f64, f64),
+    axes: (f64, f64),
+    theta: f64,
+    n: usize,
+    wobble: f64,
+) -> Array2<f64> {
+    let (sin, cos) = theta.sin_cos();
+    let mut points = Array2::<f64>::zeros((n, 2));
+    (0..n).for_each(|i| {
+        let t = 2.0 * std::f64::consts::PI * i as f64 / n as f64;
+        let w = 1.0 + wobble * (7.0 * t).sin();
+        let u = axes.0 * w * t.cos();
+        let v = axes.1 * w * t.sin();
+        points[[i, 0]] = center.0 + u * cos - v * sin;
+        points[[i, 1]] = center.1 + u * sin + v * cos;
+    });
+    points
+}
+
+/// Tests that `fit_circle` recovers the center and radius of points sampled on
+/// a circle and returns an error for collinear points.
+#[test]
+fn measure_fit_circle_expected_results() -> Result<(), ImgalError> {
+    let points = ellipse_points((12.5, -4.0), (7.0, 7.0), 0.0, 40, 0.0);
+    let (center_alg, radius_alg) = fit_circle(&points, Some(0))?;
+    let (center_geo, radius_geo) = fit_circle(&points, None)?;
+    assert!(approx_equal(center_alg[0], 12.5, None));
+    assert!(approx_equal(center_alg[1], -4.0, None));
+    assert!(approx_equal(radius_alg, 7.0, None));
+    assert!(approx_equal(center_geo[0], 12.5, None));
+    assert!(approx_equal(center_geo[1], -4.0, None));
+    assert!(approx_equal(radius_geo, 7.0, None));
+    let line =
+        Array2::from_shape_vec((4, 2), vec![0.0, 0.0, 1.0, 1.0, 2.0, 2.0, 3.0, 3.0]).unwrap();
+    assert!(fit_circle(&line, None).is_err());
+    Ok(())
+}
+
+/// Tests that `fit_ellipse` recovers the center, axes and orientation of
+/// points sampled on a rotated ellipse and that the geometric refinement
+/// reduces the fit error on noisy contours.
+#[test]
+fn measure_fit_ellipse_expected_results() -> Result<(), ImgalError> {
+    let points = ellipse_points((10.0, 20.0), (8.0, 3.0), 0.5, 50, 0.0);
+    let fit = fit_ellipse(&points, None)?;
+    assert!(approx_equal(fit.center[0], 10.0, Some(1e-8)));
+    assert!(approx_equal(fit.center[1], 20.0, Some(1e-8)));
+    assert!(approx_equal(fit.semi_major, 8.0, Some(1e-8)));
+    assert!(approx_equal(fit.semi_minor, 3.0, Some(1e-8)));
+    assert!(approx_equal(fit.orientation, 0.5, Some(1e-8)));
+    let noisy = ellipse_points((-3.0, 5.0), (6.0, 4.0), -1.2, 64, 0.02);
+    let fit = fit_ellipse(&noisy, None)?;
+    assert!(approx_equal(fit.center[0], -3.0, Some(1e-2)));
+    assert!(approx_equal(fit.center[1], 5.0, Some(1e-2)));
+    assert!(approx_equal(fit.semi_major, 6.0, Some(5e-2)));
+    assert!(approx_equal(fit.semi_minor, 4.0, Some(5e-2)));
+    assert!(approx_equal(fit.orientation, -1.2, Some(1e-2)));
+    assert!(fit_ellipse(points.slice(ndarray::s![..4, ..]), None).is_err());
+    Ok(())
+}
+
+/// Tests that `fit_spline` resamples open point lists with equal arclength
+/// spacing and smooths noisy closed contours.
+#[test]
+fn measure_fit_spline_expected_results() -> Result<(), ImgalError> {
+    let line = Array2::from_shape_vec(
+        (6, 2),
+        vec![0.0, 0.0, 1.0, 2.0, 1.5, 3.0, 3.0, 6.0, 4.5, 9.0, 5.0, 10.0],
+    )
+    .unwrap();
+    let line_fit = fit_spline(&line, None, Some(11), false)?;
+    assert_eq!(line_fit.dim(), (11, 2));
+    (0..11).for_each(|i| {
+        assert!(approx_equal(line_fit[[i, 0]], 0.5 * i as f64, Some(1e-6)));
+        assert!(approx_equal(line_fit[[i, 1]], i as f64, Some(1e-6)));
+    });
+    let contour = ellipse_points((0.0, 0.0), (10.0, 10.0), 0.0, 120, 0.01);
+    let contour_fit = fit_spline(&contour, Some(200.0), Some(60), true)?;
+    assert_eq!(contour_fit.dim(), (60, 2));
+    let spacing: Vec<f64> = (0..60)
+        .map(|i| {
+            let j = (i + 1) % 60;
+            ((contour_fit[[j, 0]] - contour_fit[[i, 0]]).powi(2)
+                + (contour_fit[[j, 1]] - contour_fit[[i, 1]]).powi(2))
+            .sqrt()
+        })
+        .collect();
+    contour_fit.rows().into_iter().for_each(|r| {
+        let radius = (r[0] * r[0] + r[1] * r[1]).sqrt();
+        assert!(approx_equal(radius, 10.0, Some(5e-2)));
+    });
+    spacing
+        .iter()
+        .for_each(|s| assert!(approx_equal(*s, spacing[0], Some(1e-2))));
+    assert!(fit_spline(line.slice(ndarray::s![..3, ..]), None, None, false).is_err());
+    Ok(())
+}
+
+/// Tests that `curvature` returns the expected pointwise curvature, total
+/// curvature and tortuosity for circles, arcs and straight lines.
+#[test]
+fn measure_curvature_expected_results() -> Result<(), ImgalError> {
+    let circle = ellipse_points((3.0, -2.0), (5.0, 5.0), 0.0, 200, 0.0);
+    let (kappa, total, tort) = curvature(&circle, true)?;
+    kappa
+        .iter()
+        .for_each(|k| assert!(approx_equal(*k, 0.2, None)));
+    assert!(approx_equal(total, 2.0 * std::f64::consts::PI, None));
+    assert!(tort.is_infinite());
+    let half = circle.slice(ndarray::s![..101, ..]);
+    let (kappa_open, total_open, tort_open) = curvature(half, false)?;
+    assert_eq!(kappa_open[0], 0.0);
+    assert_eq!(kappa_open[100], 0.0);
+    assert!(approx_equal(kappa_open[50], 0.2, None));
+    assert!(approx_equal(
+        total_open,
+        99.0 * std::f64::consts::PI / 100.0,
+        None
+    ));
+    let arc_len = 100.0 * 10.0 * (std::f64::consts::PI / 200.0).sin();
+    assert!(approx_equal(tort_open, arc_len / 10.0, None));
+    let line = Array2::from_shape_vec((4, 3), (0..12).map(|v| v as f64).collect()).unwrap();
+    let (kappa_line, total_line, tort_line) = curvature(&line, false)?;
+    assert!(kappa_line.iter().all(|&k| k == 0.0));
+    assert!(approx_equal(total_line, 0.0, None));
+    assert!(approx_equal(tort_line, 1.0, None));
+    assert!(curvature(line.slice(ndarray::s![..2, ..]), false).is_err());
+    Ok(())
+}
+
+/// Tests that `confluence` estimates the covered area fraction of an image with
+/// a textured (cell covered) half and a flat (empty) half.
+#[test]
+fn measure_confluence_expected_results() -> Result<(), ImgalError> {
+    let mut data = Array2::<f64>::from_elem((64, 64), 100.0);
+    let texture = poisson_noise(
+        &Array2::<f64>::from_elem((64, 32), 100.0),
+        1.0,
+        Some(7),
+        None,
+    );
+    data.slice_mut(ndarray::s![.., ..32]).assign(&texture);
+    let (conf_par, mask_par) = confluence(&data, None, None, None, Some(0))?;
+    let (conf_seq, mask_seq) = confluence(&data, None, None, None, None)?;
+    let (conf_none, _) = confluence(&data, Some(2), Some(1e6), Some(0), None)?;
+    assert_eq!(mask_par.dim(), (64, 64));
+    assert_eq!(mask_par, mask_seq);
+    assert_eq!(conf_par, conf_seq);
+    assert!(approx_equal(conf_par, 0.5, Some(0.06)));
+    assert!(mask_par[[32, 5]]);
+    assert!(!mask_par[[32, 60]]);
+    assert_eq!(conf_none, 0.0);
+    assert!(confluence(&data, Some(0), None, None, None).is_err());
+    Ok(())
+}
+
+/// Tests that `roi_similarity` returns the expected similarities of identical
+/// and disjoint intensity distributions in one and two channels.
+#[test]
+fn measure_roi_similarity_expected_results() -> Result<(), ImgalError> {
+    // ROIs 1 and 2 share the same intensity distribution (in a different
+    // order), ROI 3 only has bright pixels
+    let labels = Array2::from_shape_fn((3, 10), |(r, _)| r as u64 + 1);
+    let data_a = Array2::from_shape_fn((3, 10), |(r, c)| match r {
+        0 => c as f64,
+        1 => (9 - c) as f64,
+        _ => 20.0 + c as f64,
+    });
+    let data_b = Array2::from_shape_fn((3, 10), |(r, c)| if r == 0 { c as f64 } else { 0.0 });
+    let rois = roi_cloud_map(&labels, None);
+    let (ids, mi) = roi_similarity(&data_a, None, &rois, None, Some(10), Some(0))?;
+    let (_, mi_seq) = roi_similarity(&data_a, None, &rois, None, Some(10), None)?;
+    assert_eq!(ids, vec![1, 2, 3]);
+    assert_eq!(mi, mi_seq);
+    assert_eq!(mi, mi.t());
+    assert!(approx_equal(mi[[0, 0]], 1.0, None));
+    assert!(approx_equal(mi[[0, 1]], 1.0, None));
+    assert!(approx_equal(mi[[0, 2]], 0.0, None));
+    let metric = Some(SimilarityMetric::HistogramIntersection);
+    let (_, inter) = roi_similarity(&data_a, None, &rois, metric, Some(10), None)?;
+    assert!(approx_equal(inter[[0, 1]], 1.0, None));
+    assert!(approx_equal(inter[[1, 2]], 0.0, None));
+    let metric = Some(SimilarityMetric::Correlation);
+    let (_, corr) = roi_similarity(&data_a, None, &rois, metric, Some(10), None)?;
+    assert!(approx_equal(corr[[0, 1]], 1.0, None));
+    assert!(corr[[0, 2]] < 0.0);
+    // ROIs 1 and 2 differ in the second channel
+    let (_, joint) = roi_similarity(&data_a, Some(&data_b), &rois, None, Some(10), None)?;
+    assert!(joint[[0, 1]] < 0.5);
+    assert!(approx_equal(joint[[2, 2]], 1.0, None));
+    assert!(roi_similarity(&data_a, None, &rois, None, Some(0), None).is_err());
+    assert!(
+        roi_similarity(
+            &data_a,
+            Some(&Array2::zeros((2, 10))),
+            &rois,
+            None,
+            None,
+            None
+        )
+        .is_err()
+    );
+    Ok(())
+}
+
+/// Tests that `regionprops` measures the area, centroid, bounding box,
+/// intensity, perimeter and eccentricity of labeled regions in 2D and 3D.
+#[test]
+fn measure_regionprops_expected_results() -> Result<(), ImgalError> {
+    let mut labels = Array2::<u64>::zeros((40, 50));
+    // a disk of radius 10 and a 4 × 6 rectangle
+    labels.indexed_iter_mut().for_each(|((r, c), l)| {
+        let (dr, dc) = (r as f64 - 15.0, c as f64 - 15.0);
+        if dr * dr + dc * dc <= 100.0 {
+            *l = 3;
+        }
+    });
+    labels.slice_mut(s![30..34, 40..46]).fill(7);
+    let mut intensity = labels.mapv(|l| l as f64 * 2.0);
+    intensity[[31, 42]] = 50.0;
+    let par = regionprops(&labels, Some(intensity.view()), THREADS)?;
+    let seq = regionprops(&labels, Some(intensity.view()), None)?;
+    assert_eq!(par, seq);
+    assert_eq!(par.labels, vec![3, 7]);
+    assert_eq!(par.area[1], 24);
+    assert!(approx_equal(par.centroid[[0, 0]], 15.0, None));
+    assert!(approx_equal(par.centroid[[0, 1]], 15.0, None));
+    assert!(approx_equal(par.centroid[[1, 0]], 31.5, None));
+    assert!(approx_equal(par.centroid[[1, 1]], 42.5, None));
+    assert_eq!(par.bbox.row(0).to_vec(), vec![5, 5, 26, 26]);
+    assert_eq!(par.bbox.row(1).to_vec(), vec![30, 40, 34, 46]);
+    let mean = par.mean_intensity.as_ref().unwrap();
+    let max = par.max_intensity.as_ref().unwrap();
+    assert!(approx_equal(mean[0], 6.0, None));
+    assert!(approx_equal(mean[1], (23.0 * 14.0 + 50.0) / 24.0, None));
+    assert_eq!(max[1], 50.0);
+    // the perimeter of the pixel squares of the disk is close to 2π(r + 0.5),
+    // the rectangle is π / 4 × (2 × 4 + 2 × 6) with axis aligned faces
+    assert!(approx_equal(
+        par.perimeter[0],
+        2.0 * std::f64::consts::PI * 10.5,
+        Some(1.0)
+    ));
+    assert!(approx_equal(
+        par.perimeter[1],
+        std::f64::consts::FRAC_PI_4 * 20.0,
+        None
+    ));
+    // the eccentricity of the uniform rectangle from the index variances
+    // (n² - 1) / 12
+    assert!(par.eccentricity[0] < 0.05);
+    assert!(approx_equal(
+        par.eccentricity[1],
+        (1.0 - 15.0 / 35.0_f64).sqrt(),
+        Some(1e-8)
+    ));
+    let no_intensity = regionprops(&labels, None, THREADS)?;
+    assert!(no_intensity.mean_intensity.is_none());
+    assert_eq!(no_intensity.area, par.area);
+    assert!(regionprops(&labels, Some(intensity.slice(s![..39, ..])), None).is_err());
+    // a 3 × 3 × 3 cube and a line of voxels from a connected component mask
+    let mut mask = Array3::<bool>::default((6, 6, 8));
+    mask.slice_mut(s![1..4, 1..4, 1..4]).fill(true);
+    mask.slice_mut(s![5, 5, 2..8]).fill(true);
+    let labels_3d = label(&mask, None, THREADS);
+    let props = regionprops(&labels_3d, None, THREADS)?;
+    assert_eq!(props.labels, vec![1, 2]);
+    assert_eq!(props.area, vec![27, 6]);
+    assert!(approx_equal(props.perimeter[0], 2.0 / 3.0 * 54.0, None));
+    assert!(approx_equal(props.eccentricity[0], 0.0, Some(1e-8)));
+    assert!(approx_equal(props.eccentricity[1], 1.0, None));
+    assert_eq!(props.bbox.row(1).to_vec(), vec![5, 5, 2, 6, 6, 8]);
+    Ok(())
+}
+
+/// Tests that `roi_statistics` returns the expected statistics of rectangular,
+/// concave and out of bounds polygonal ROIs.
+#[test]
+fn measure_roi_statistics_expected_results() -> Result<(), ImgalError> {
+    let data = Array2::from_shape_fn((10, 12), |(r, c)| (r * 12 + c) as f64);
+    let rect = arr2(&[[1.5, 1.5], [1.5, 4.5], [4.5, 4.5], [4.5, 1.5]]);
+    // a concave "U" shape, the pixels are checked against a brute force
+    // even-odd point in polygon test of the pixel centers
+    let concave = arr2(&[
+        [0.5, 0.5],
+        [8.2, 0.5],
+        [8.2, 10.7],
+        [0.5, 10.7],
+        [0.5, 7.3],
+        [5.0, 7.3],
+        [5.0, 3.6],
+        [0.5, 3.6],
+    ]);
+    let outside = arr2(&[[20.0, 20.0], [25.0, 20.0], [25.0, 25.0]]);
+    let partial = arr2(&[[-5.0, -5.0], [-5.0, 1.5], [1.5, 1.5], [1.5, -5.0]]);
+    let rois = [rect.view(), concave.view(), outside.view(), partial.view()];
+    let stats = roi_statistics(&data, &rois, Some(0))?;
+    let stats_seq = roi_statistics(&data, &rois, None)?;
+    assert_eq!(stats[..2], stats_seq[..2]);
+    assert_eq!(stats[3], stats_seq[3]);
+    let rect_values = [26.0, 27.0, 28.0, 38.0, 39.0, 40.0, 50.0, 51.0, 52.0];
+    assert_eq!(stats[0].area, 9);
+    assert_eq!(stats[0].sum, rect_values.iter().sum::<f64>());
+    assert!(approx_equal(stats[0].mean, 39.0, None));
+    assert!(approx_equal(
+        stats[0].std,
+        (96.0_f64 + 2.0 / 3.0).sqrt(),
+        None
+    ));
+    assert_eq!((stats[0].min, stats[0].max), (26.0, 52.0));
+    let inside = |y: f64, x: f64| {
+        let p = concave.nrows();
+        (0..p).fold(false, |acc, i| {
+            let (y0, x0) = (concave[[i, 0]], concave[[i, 1]]);
+            let (y1, x1) = (concave[[(i + 1) % p, 0]], concave[[(i + 1) % p, 1]]);
+            let crosses = (y0 <= y) != (y1 <= y) && x < x0 + (y - y0) / (y1 - y0) * (x1 - x0);
+            acc ^ crosses
+        })
+    };
+    let (area, sum) = data.indexed_iter().fold((0, 0.0), |acc, ((r, c), v)| {
+        if inside(r as f64, c as f64) {
+            (acc.0 + 1, acc.1 + v)
+        } else {
+            acc
+        }
+    });
+    assert_eq!(stats[1].area, area);
+    assert_eq!(stats[1].sum, sum);
+    assert_eq!(stats[1].area, 8 * 10 - 4 * 4);
+    assert_eq!(stats[2].area, 0);
+    assert!(stats[2].mean.is_nan());
+    assert_eq!(stats[3].area, 4);
+    assert_eq!(stats[3].sum, 0.0 + 1.0 + 12.0 + 13.0);
+    assert!(roi_statistics(&data, &[arr2(&[[0.0, 0.0], [1.0, 1.0]]).view()], None).is_err());
+    assert!(roi_statistics(&data, &[arr2(&[[0.0], [1.0], [2.0]]).view()], None).is_err());
+    Ok(())
+}
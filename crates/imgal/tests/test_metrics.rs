@@ -0,0 +1,257 @@
+use ndarray::{Array2, arr2, s};
+
+use imgal::metrics::detection::{detection_scores, match_points};
+use imgal::metrics::registration::registration_report;
+use imgal::metrics::segmentation::{boundary_f_score, iou_matrix, segmentation_scores};
+use imgal::metrics::tracking::tracking_scores;
+use imgal::prelude::*;
+use imgal::tracking::TrackTable;
+
+const TOLERANCE: f64 = 1e-10;
+const THREADS: Option<usize> = Some(0);
+
+fn approx_equal(a: f64, b: f64, tol: Option<f64>) -> bool {
+    (a - b).abs() < tol.unwrap_or(TOLERANCE)
+}
+
+/// Tests that `match_points` and `detection_scores` find the assignment with
+/// the most matches where a greedy nearest neighbor matching would not.
+#[test]
+fn detection_detection_scores_expected_results() -> Result<(), ImgalError> {
+    let truth = arr2(&[[0.0, 0.0], [2.0, 0.0], [20.0, 0.0]]);
+    let pred = arr2(&[[1.1, 0.0], [3.0, 0.0], [10.0, 0.0]]);
+    let matches = match_points(&pred, &truth, 1.2)?;
+    assert_eq!(matches.len(), 2);
+    assert_eq!((matches[0].0, matches[0].1), (0, 0));
+    assert_eq!((matches[1].0, matches[1].1), (1, 1));
+    assert!(approx_equal(matches[0].2, 1.1, None));
+    let scores = detection_scores(&pred, &truth, 1.2)?;
+    assert_eq!(scores.true_positives, 2);
+    assert!(approx_equal(scores.precision, 2.0 / 3.0, None));
+    assert!(approx_equal(scores.recall, 2.0 / 3.0, None));
+    assert!(approx_equal(scores.f1, 2.0 / 3.0, None));
+    assert!(approx_equal(
+        scores.rmse,
+        ((1.21 + 1.0) / 2.0_f64).sqrt(),
+        None
+    ));
+    // more predicted than ground truth points
+    let extra = arr2(&[[1.1, 0.0], [3.0, 0.0], [10.0, 0.0], [0.5, 0.0]]);
+    let scores = detection_scores(&extra, &truth.slice(s![..2, ..]).to_owned(), 1.2)?;
+    assert_eq!(scores.true_positives, 2);
+    assert!(approx_equal(scores.recall, 1.0, None));
+    assert!(approx_equal(scores.precision, 0.5, None));
+    assert!(approx_equal(
+        scores.rmse,
+        ((0.25 + 0.81) / 2.0_f64).sqrt(),
+        None
+    ));
+    let empty = Array2::<f64>::zeros((0, 2));
+    let none = detection_scores(&empty, &truth, 1.0)?;
+    assert_eq!(none.true_positives, 0);
+    assert!(none.precision.is_nan());
+    assert_eq!(none.recall, 0.0);
+    assert!(match_points(&pred, &Array2::zeros((2, 3)), 1.0).is_err());
+    assert!(match_points(&pred, &truth, -1.0).is_err());
+    Ok(())
+}
+
+// helper function to create the ground truth and predicted label images, with
+// an exact match, a split, a missed and a false positive object
+/// Tests that `registration_report` summarizes the residual displacements of
+/// matched beads and maps a field-dependent misregistration.
+#[test]
+fn registration_registration_report_expected_results() -> Result<(), ImgalError> {
+    let positions = [10.0, 30.0, 50.0, 70.0];
+    let mut reference = Array2::<f64>::zeros((16, 2));
+    let mut moving = Array2::<f64>::zeros((17, 2));
+    for (i, &r) in positions.iter().enumerate() {
+        for (j, &c) in positions.iter().enumerate() {
+            let k = 4 * i + j;
+            reference[[k, 0]] = r;
+            reference[[k, 1]] = c;
+            // a lateral shift on the right half of the field
+            moving[[k, 0]] = r + 0.1;
+            moving[[k, 1]] = if c > 40.0 { c + 0.5 } else { c };
+        }
+    }
+    // an unmatched bead in the moving channel only
+    moving[[16, 0]] = 100.0;
+    moving[[16, 1]] = 100.0;
+    let report = registration_report(&moving, &reference, 2.0, Some((2, 2)))?;
+    let right_error = 0.26_f64.sqrt();
+    assert_eq!(report.n_moving, 17);
+    assert_eq!(report.n_reference, 16);
+    assert_eq!(report.matches, (0..16).map(|k| (k, k)).collect::<Vec<_>>());
+    assert_eq!(report.displacements.dim(), (16, 2));
+    assert!(approx_equal(report.mean_displacement[0], 0.1, None));
+    assert!(approx_equal(report.mean_displacement[1], 0.25, None));
+    assert!(approx_equal(
+        report.mean_error,
+        0.5 * (0.1 + right_error),
+        None
+    ));
+    assert!(approx_equal(report.percentile_95_error, right_error, None));
+    assert!(approx_equal(report.max_error, right_error, None));
+    assert_eq!(report.field_counts, arr2(&[[4, 4], [4, 4]]));
+    for r in 0..2 {
+        assert!(approx_equal(report.field_error[[r, 0]], 0.1, None));
+        assert!(approx_equal(report.field_error[[r, 1]], right_error, None));
+        assert!(approx_equal(
+            report.field_displacement[[r, 0, 1]],
+            0.0,
+            None
+        ));
+        assert!(approx_equal(
+            report.field_displacement[[r, 1, 1]],
+            0.5,
+            None
+        ));
+    }
+    // empty cells and unmatched channels are reported as NaN
+    let report = registration_report(&moving, &reference, 2.0, Some((3, 5)))?;
+    assert_eq!(report.field_counts.sum(), 16);
+    assert!(report.field_error[[0, 2]].is_nan());
+    let report = registration_report(moving.slice(s![16.., ..]), reference.view(), 2.0, None)?;
+    assert!(report.matches.is_empty());
+    assert!(report.mean_error.is_nan());
+    assert!(report.mean_displacement.iter().all(|v| v.is_nan()));
+    assert!(report.field_error.iter().all(|v| v.is_nan()));
+    assert!(registration_report(&moving, &reference, 2.0, Some((0, 2))).is_err());
+    assert!(registration_report(&moving, &reference, -1.0, None).is_err());
+    assert!(
+        registration_report(
+            moving.slice(s![.., ..1]),
+            reference.slice(s![.., ..1]),
+            2.0,
+            None
+        )
+        .is_err()
+    );
+    Ok(())
+}
+
+fn label_pair() -> (Array2<u64>, Array2<u64>) {
+    let mut truth = Array2::<u64>::zeros((20, 20));
+    truth.slice_mut(s![0..5, 0..5]).fill(1);
+    truth.slice_mut(s![10..16, 10..16]).fill(2);
+    truth.slice_mut(s![0..4, 12..20]).fill(3);
+    let mut pred = Array2::<u64>::zeros((20, 20));
+    pred.slice_mut(s![0..5, 0..5]).fill(10);
+    pred.slice_mut(s![10..16, 10..13]).fill(20);
+    pred.slice_mut(s![10..16, 13..16]).fill(21);
+    pred.slice_mut(s![17..19, 0..3]).fill(30);
+    (truth, pred)
+}
+
+/// Tests that `iou_matrix` returns the expected intersection over union of
+/// each object pair.
+#[test]
+fn segmentation_iou_matrix_expected_results() -> Result<(), ImgalError> {
+    let (truth, pred) = label_pair();
+    let output = iou_matrix(&pred, &truth, THREADS)?;
+    let output_seq = iou_matrix(&pred, &truth, None)?;
+    assert_eq!(output, output_seq);
+    assert_eq!(output.pred_labels, vec![10, 20, 21, 30]);
+    assert_eq!(output.truth_labels, vec![1, 2, 3]);
+    let iou = output.iou;
+    assert_eq!(iou.shape(), [4, 3]);
+    assert!(approx_equal(iou[[0, 0]], 1.0, None));
+    assert!(approx_equal(iou[[1, 1]], 0.5, None));
+    assert!(approx_equal(iou[[2, 1]], 0.5, None));
+    assert_eq!(iou.column(2).sum(), 0.0);
+    assert!(iou_matrix(&pred, &truth.slice(s![..10, ..]).to_owned(), None).is_err());
+    Ok(())
+}
+
+/// Tests that `segmentation_scores` returns the expected matched IoU, Dice,
+/// average precision and split and merge counts.
+#[test]
+fn segmentation_segmentation_scores_expected_results() -> Result<(), ImgalError> {
+    let (truth, pred) = label_pair();
+    let scores = segmentation_scores(&pred, &truth, Some(&[0.5, 0.55]), THREADS)?;
+    assert_eq!((scores.n_pred, scores.n_truth), (4, 3));
+    assert!(approx_equal(scores.mean_iou, 0.5, None));
+    assert!(approx_equal(
+        scores.mean_dice,
+        (1.0 + 2.0 / 3.0) / 3.0,
+        None
+    ));
+    assert!(approx_equal(scores.average_precision[0], 2.0 / 5.0, None));
+    assert!(approx_equal(scores.average_precision[1], 1.0 / 6.0, None));
+    assert_eq!((scores.splits, scores.merges), (1, 0));
+    let default = segmentation_scores(&pred, &truth, None, None)?;
+    assert_eq!(default.average_precision.len(), 10);
+    // swapping the images turns the split into a merge
+    let swapped = segmentation_scores(&truth, &pred, None, None)?;
+    assert_eq!((swapped.splits, swapped.merges), (0, 1));
+    let perfect = segmentation_scores(&truth, &truth, None, None)?;
+    assert!(perfect.average_precision.iter().all(|&ap| ap == 1.0));
+    assert!(approx_equal(perfect.mean_iou, 1.0, None));
+    assert!(segmentation_scores(&pred, &truth, Some(&[1.5]), None).is_err());
+    Ok(())
+}
+
+/// Tests that `boundary_f_score` matches the boundaries within the tolerance.
+#[test]
+fn segmentation_boundary_f_score_expected_results() -> Result<(), ImgalError> {
+    let mut truth = Array2::<u64>::zeros((20, 20));
+    truth.slice_mut(s![5..15, 5..15]).fill(1);
+    let mut shifted = Array2::<u64>::zeros((20, 20));
+    shifted.slice_mut(s![6..16, 5..15]).fill(1);
+    assert!(approx_equal(
+        boundary_f_score(&truth, &truth, None, THREADS)?,
+        1.0,
+        None
+    ));
+    assert!(approx_equal(
+        boundary_f_score(&shifted, &truth, Some(1.0), THREADS)?,
+        1.0,
+        None
+    ));
+    let strict = boundary_f_score(&shifted, &truth, Some(0.0), None)?;
+    assert!(strict > 0.0 && strict < 1.0);
+    let empty = Array2::<u64>::zeros((20, 20));
+    assert_eq!(boundary_f_score(&empty, &empty, None, None)?, 1.0);
+    assert_eq!(boundary_f_score(&empty, &truth, None, None)?, 0.0);
+    assert!(boundary_f_score(&truth, &truth, Some(-1.0), None).is_err());
+    Ok(())
+}
+
+/// Tests that `tracking_scores` scores a perfect tracking and counts the
+/// identity switches of two tracks that swap objects halfway.
+#[test]
+fn tracking_tracking_scores_expected_results() -> Result<(), ImgalError> {
+    let frames = vec![0, 1, 2, 3, 0, 1, 2, 3];
+    let coords = Array2::from_shape_fn(
+        (8, 2),
+        |(i, j)| {
+            if j == 0 { 10.0 * (i / 4) as f64 } else { 0.0 }
+        },
+    );
+    let truth = TrackTable::new(vec![0, 0, 0, 0, 1, 1, 1, 1], frames.clone(), &coords, None)?;
+    let perfect = tracking_scores(&truth, &truth, 1.0)?;
+    assert_eq!(perfect.true_positives, 8);
+    assert_eq!(perfect.id_switches, 0);
+    assert!(approx_equal(perfect.mota, 1.0, None));
+    assert!(approx_equal(perfect.motp, 0.0, None));
+    assert!(approx_equal(perfect.track_purity, 1.0, None));
+    assert!(approx_equal(perfect.target_effectiveness, 1.0, None));
+    // the predicted tracks swap objects from frame 2 and miss one detection
+    let pred = TrackTable::new(vec![5, 5, 6, 6, 6, 6, 5, 5], frames, &coords, None)?
+        .filter(|k, f, _, _| !(k == 6 && f == 3));
+    let scores = tracking_scores(&pred, &truth, 1.0)?;
+    assert_eq!(scores.true_positives, 7);
+    assert_eq!(scores.false_positives, 0);
+    assert_eq!(scores.false_negatives, 1);
+    assert_eq!(scores.id_switches, 2);
+    assert!(approx_equal(scores.mota, 1.0 - 3.0 / 8.0, None));
+    assert!(approx_equal(
+        scores.track_purity,
+        (2.0 / 4.0 + 2.0 / 3.0) / 2.0,
+        None
+    ));
+    assert!(approx_equal(scores.target_effectiveness, 0.5, None));
+    assert!(tracking_scores(&pred, &truth, -1.0).is_err());
+    Ok(())
+}
@@ -0,0 +1,236 @@
+use ndarray::{Array2, Array3, ArrayD, IxDyn, arr2, s};
+
+use imgal::filter::minimum_filter;
+use imgal::kernel::neighborhood::{circle_kernel, sphere_kernel};
+use imgal::morphology::{
+    Connectivity, close, dilate, erode, grayscale_close, grayscale_dilate, grayscale_erode,
+    grayscale_open, label, morphological_gradient, open,
+};
+use imgal::prelude::*;
+use imgal::spatial::roi::roi_cloud_map;
+
+const THREADS: Option<usize> = Some(0);
+
+/// Tests that `erode` and `dilate` shrink and grow a square by the footprint
+/// radius, ignoring the neighbors outside of the mask.
+#[test]
+fn binary_erode_dilate_expected_results() -> Result<(), ImgalError> {
+    let mut mask = Array2::<bool>::default((9, 9));
+    mask.slice_mut(s![2..7, 2..7]).fill(true);
+    let cross = circle_kernel(1)?;
+    let square = Array2::<bool>::from_elem((3, 3), true);
+    let eroded_par = erode(&mask, square.view(), THREADS)?;
+    let eroded_seq = erode(&mask, square.view(), None)?;
+    assert_eq!(eroded_par, eroded_seq);
+    let mut expected = Array2::<bool>::default((9, 9));
+    expected.slice_mut(s![3..6, 3..6]).fill(true);
+    assert_eq!(eroded_par, expected);
+    let dilated_par = dilate(&mask, cross.view(), THREADS)?;
+    let dilated_seq = dilate(&mask, cross.view(), None)?;
+    assert_eq!(dilated_par, dilated_seq);
+    assert_eq!(dilated_par.iter().filter(|&&v| v).count(), 25 + 4 * 5);
+    assert!(dilated_par[[1, 4]] && !dilated_par[[1, 1]]);
+    // objects touching the border are not eroded from the border
+    let full = Array2::<bool>::from_elem((4, 5), true);
+    assert_eq!(erode(&full, square.view(), THREADS)?, full);
+    // an asymmetric footprint shifts the mask
+    let shift = arr2(&[
+        [false, false, false],
+        [false, false, true],
+        [false, false, false],
+    ]);
+    let shifted = dilate(&mask, shift.view(), THREADS)?;
+    assert_eq!(shifted.slice(s![2..7, 1..6]), mask.slice(s![2..7, 2..7]));
+    assert!(erode(&mask, Array2::<bool>::from_elem((2, 3), true).view(), None).is_err());
+    assert!(dilate(&mask, Array2::<bool>::default((3, 3)).view(), None).is_err());
+    let cube = ArrayD::<bool>::from_elem(IxDyn(&[3, 3, 3]), true);
+    assert!(erode(mask.view().into_dyn(), cube.view(), None).is_err());
+    Ok(())
+}
+
+/// Tests that `open` removes objects smaller than the footprint and that
+/// `close` fills holes smaller than the footprint, in 2D and 3D.
+#[test]
+fn binary_open_close_expected_results() -> Result<(), ImgalError> {
+    let mut mask = Array2::<bool>::default((12, 12));
+    mask.slice_mut(s![2..9, 2..9]).fill(true);
+    mask[[11, 11]] = true;
+    mask[[5, 5]] = false;
+    let square = Array2::<bool>::from_elem((3, 3), true);
+    let opened = open(&mask, square.view(), THREADS)?;
+    assert_eq!(opened, open(&mask, square.view(), None)?);
+    assert!(!opened[[11, 11]]);
+    assert!(opened[[2, 2]] && opened[[8, 8]]);
+    let closed = close(&mask, square.view(), THREADS)?;
+    assert_eq!(closed, close(&mask, square.view(), None)?);
+    assert!(closed[[5, 5]] && closed[[11, 11]]);
+    let mut expected = mask.clone();
+    expected[[5, 5]] = true;
+    assert_eq!(closed, expected);
+    // opening and closing are idempotent
+    assert_eq!(open(&opened, square.view(), THREADS)?, opened);
+    assert_eq!(close(&closed, square.view(), THREADS)?, closed);
+    let mut volume = Array3::<bool>::default((9, 9, 9));
+    volume.slice_mut(s![1..8, 1..8, 1..8]).fill(true);
+    volume[[4, 4, 4]] = false;
+    volume[[0, 0, 8]] = true;
+    let ball = sphere_kernel(1)?;
+    let cleaned = close(
+        open(&volume, ball.view(), THREADS)?.view(),
+        ball.view(),
+        THREADS,
+    )?;
+    assert!(cleaned[[4, 4, 4]]);
+    assert!(!cleaned[[0, 0, 8]]);
+    assert!(cleaned[[1, 4, 4]]);
+    Ok(())
+}
+
+/// Tests that `label` finds the face and fully connected components of 2D and
+/// 3D masks with consecutive labels in raster order.
+#[test]
+fn components_label_expected_results() {
+    // a "U" whose arms meet at the bottom, two diagonal pixels and a bar
+    let mask = arr2(&[
+        [true, false, true, false, false, false],
+        [true, false, true, false, true, false],
+        [true, true, true, false, false, true],
+        [false, false, false, false, false, false],
+        [true, true, true, true, true, true],
+    ]);
+    let face = label(&mask, None, THREADS);
+    assert_eq!(face, label(&mask, Some(Connectivity::Face), None));
+    let expected = arr2(&[
+        [1, 0, 1, 0, 0, 0],
+        [1, 0, 1, 0, 2, 0],
+        [1, 1, 1, 0, 0, 3],
+        [0, 0, 0, 0, 0, 0],
+        [4, 4, 4, 4, 4, 4],
+    ]);
+    assert_eq!(face, expected);
+    let full = label(&mask, Some(Connectivity::Full), THREADS);
+    assert_eq!(full, label(&mask, Some(Connectivity::Full), None));
+    assert_eq!(full[[1, 4]], full[[2, 5]]);
+    assert_eq!(full[[4, 0]], 3);
+    assert_eq!(*full.iter().max().unwrap(), 3);
+    // the labels feed the ROI functions
+    let rois = roi_cloud_map(&face, THREADS);
+    assert_eq!(rois.len(), 4);
+    assert_eq!(rois[&1].nrows(), 7);
+    assert_eq!(rois[&4].nrows(), 6);
+    // 3D components touching by a face, an edge and a corner
+    let mut volume = Array3::<bool>::default((4, 4, 4));
+    volume[[0, 0, 0]] = true;
+    volume[[1, 0, 0]] = true;
+    volume[[2, 1, 0]] = true;
+    volume[[3, 2, 1]] = true;
+    let face_3d = label(&volume, None, THREADS);
+    assert_eq!(*face_3d.iter().max().unwrap(), 3);
+    assert_eq!(face_3d[[0, 0, 0]], face_3d[[1, 0, 0]]);
+    let full_3d = label(&volume, Some(Connectivity::Full), THREADS);
+    assert_eq!(*full_3d.iter().max().unwrap(), 1);
+    assert_eq!(full_3d.iter().filter(|&&l| l == 1).count(), 4);
+    // a comb whose teeth are only joined by the last row
+    let comb = Array2::from_shape_fn((6, 9), |(r, c)| r == 5 || c % 2 == 0);
+    let comb_labels = label(&comb, None, THREADS);
+    assert!(
+        comb_labels
+            .iter()
+            .zip(comb.iter())
+            .all(|(&l, &m)| l == m as u64)
+    );
+    assert!(
+        label(&Array2::<bool>::default((3, 3)), None, None)
+            .iter()
+            .all(|&l| l == 0)
+    );
+}
+
+/// Tests that the grayscale erosion and dilation are the neighborhood minimum
+/// and maximum and that they reduce to the binary versions on 0/1 images.
+#[test]
+fn grayscale_erode_dilate_expected_results() -> Result<(), ImgalError> {
+    let data = Array2::from_shape_fn((10, 11), |(r, c)| ((r * 7 + c * 13) % 17) as u16);
+    let disk = circle_kernel(2)?;
+    let eroded = grayscale_erode(&data, disk.view(), THREADS)?;
+    assert_eq!(eroded, grayscale_erode(&data, disk.view(), None)?);
+    let dilated = grayscale_dilate(&data, disk.view(), THREADS)?;
+    assert_eq!(dilated, grayscale_dilate(&data, disk.view(), None)?);
+    // interior pixels match the minimum filter, where the borders do not
+    // matter
+    let minimum = minimum_filter(&data, disk.view(), None, THREADS)?;
+    assert_eq!(eroded.slice(s![2..8, 2..9]), minimum.slice(s![2..8, 2..9]));
+    assert!(eroded.iter().zip(data.iter()).all(|(e, d)| e <= d));
+    assert!(dilated.iter().zip(data.iter()).all(|(e, d)| e >= d));
+    // a brute force neighborhood maximum at a border pixel, ignoring the
+    // neighbors outside of the image
+    let max_corner = disk
+        .indexed_iter()
+        .filter(|&((r, c), &k)| k && r >= 2 && c >= 2)
+        .map(|((r, c), _)| data[[r - 2, c - 2]])
+        .max()
+        .unwrap();
+    assert_eq!(dilated[[0, 0]], max_corner);
+    let mut mask = Array3::<bool>::default((6, 7, 8));
+    mask.slice_mut(s![1..5, 2..6, 1..7]).fill(true);
+    mask[[3, 3, 3]] = false;
+    let ball = sphere_kernel(1)?;
+    let binary = mask.mapv(|v| v as u8);
+    assert_eq!(
+        grayscale_erode(&binary, ball.view(), THREADS)?,
+        erode(&mask, ball.view(), THREADS)?.mapv(|v| v as u8)
+    );
+    assert_eq!(
+        grayscale_dilate(&binary, ball.view(), THREADS)?,
+        dilate(&mask, ball.view(), THREADS)?.mapv(|v| v as u8)
+    );
+    assert!(grayscale_erode(&data, Array2::<bool>::from_elem((3, 4), true).view(), None).is_err());
+    Ok(())
+}
+
+/// Tests that the grayscale opening and closing remove bright and dark
+/// details smaller than the footprint and that the morphological gradient
+/// highlights edges.
+#[test]
+fn grayscale_open_close_gradient_expected_results() -> Result<(), ImgalError> {
+    let mut data = Array2::<f64>::from_elem((15, 15), 10.0);
+    data.slice_mut(s![3..12, 3..12]).fill(50.0);
+    data[[1, 1]] = 100.0;
+    data[[7, 7]] = 0.0;
+    let square = Array2::<bool>::from_elem((3, 3), true);
+    let opened = grayscale_open(&data, square.view(), THREADS)?;
+    assert_eq!(opened, grayscale_open(&data, square.view(), None)?);
+    assert_eq!(opened[[1, 1]], 10.0);
+    assert_eq!(opened[[3, 3]], 50.0);
+    assert!(opened.iter().zip(data.iter()).all(|(o, d)| o <= d));
+    let closed = grayscale_close(&data, square.view(), THREADS)?;
+    assert_eq!(closed, grayscale_close(&data, square.view(), None)?);
+    assert_eq!(closed[[7, 7]], 50.0);
+    assert!(closed.iter().zip(data.iter()).all(|(c, d)| c >= d));
+    assert_eq!(grayscale_open(&opened, square.view(), THREADS)?, opened);
+    let gradient = morphological_gradient(&data, square.view(), THREADS)?;
+    assert_eq!(
+        gradient,
+        morphological_gradient(&data, square.view(), None)?
+    );
+    assert_eq!(gradient[[5, 3]], 40.0);
+    assert_eq!(gradient[[5, 2]], 40.0);
+    assert_eq!(gradient[[13, 7]], 0.0);
+    assert_eq!(gradient[[7, 7]], 50.0);
+    let flat = Array2::<u8>::from_elem((5, 5), 3);
+    assert!(
+        morphological_gradient(&flat, square.view(), THREADS)?
+            .iter()
+            .all(|&v| v == 0.0)
+    );
+    // the range of signed images exceeds the input type
+    let signed = arr2(&[[i8::MIN, i8::MAX, 0]]);
+    let row = Array2::<bool>::from_elem((1, 3), true);
+    let gradient_i8 = morphological_gradient(&signed, row.view(), THREADS)?;
+    assert_eq!(gradient_i8, arr2(&[[255.0, 255.0, 127.0]]));
+    let signed = arr2(&[[i16::MIN], [i16::MAX]]);
+    let col = Array2::<bool>::from_elem((3, 1), true);
+    let gradient_i16 = morphological_gradient(&signed, col.view(), None)?;
+    assert!(gradient_i16.iter().all(|&v| v == 65535.0));
+    Ok(())
+}
@@ -0,0 +1,132 @@
+use ndarray::{Array1, Array2, arr1, arr2};
+
+use imgal::optimize::{CsrMatrix, conjugate_gradient, hungarian};
+use imgal::prelude::*;
+use imgal::simulation::rng::Pcg;
+
+const THREADS: Option<usize> = Some(0);
+
+/// Find the minimal total cost of a `(n, m)` cost matrix with `n <= m` by
+/// enumerating all assignments.
+fn brute_force(cost: &Array2<f64>, row: usize, used: &mut Vec<bool>) -> f64 {
+    if row == cost.nrows() {
+        return 0.0;
+    }
+    let mut best = f64::INFINITY;
+    for j in 0..cost.ncols() {
+        if !used[j] {
+            used[j] = true;
+            best = best.min(cost[[row, j]] + brute_force(cost, row + 1, used));
+            used[j] = false;
+        }
+    }
+    best
+}
+
+/// Create the 1D Poisson matrix `tridiag(-1, 2, -1)` of size `n`.
+fn poisson_1d(n: usize) -> Result<CsrMatrix, ImgalError> {
+    let mut triplets = Vec::new();
+    for i in 0..n {
+        triplets.push((i, i, 2.0));
+        if i + 1 < n {
+            triplets.push((i, i + 1, -1.0));
+            triplets.push((i + 1, i, -1.0));
+        }
+    }
+    CsrMatrix::from_triplets(n, n, &triplets)
+}
+
+/// Tests that `conjugate_gradient` solves sparse symmetric positive definite
+/// systems to the requested tolerance.
+#[test]
+fn conjugate_gradient_conjugate_gradient_expected_results() -> Result<(), ImgalError> {
+    let n = 64;
+    let a = poisson_1d(n)?;
+    let expected = Array1::from_shape_fn(n, |i| (i as f64 * 0.3).sin() + 1.0);
+    let b = a.dot(&expected, None)?;
+    let out_par = conjugate_gradient(&a, &b, None, Some(1e-12), None, THREADS)?;
+    let out_seq = conjugate_gradient(&a, &b, None, Some(1e-12), None, None)?;
+    assert_eq!(out_par, out_seq);
+    assert!(out_par.converged);
+    assert!(out_par.residual <= 1e-12);
+    // conjugate gradient converges in at most n steps in exact arithmetic
+    assert!(out_par.iterations <= n + 5);
+    out_par
+        .x
+        .iter()
+        .zip(expected.iter())
+        .for_each(|(x, e)| assert!((x - e).abs() < 1e-8));
+    // an exact initial guess needs no iterations
+    let warm = conjugate_gradient(&a, &b, Some(expected.view()), None, None, None)?;
+    assert_eq!(warm.iterations, 0);
+    // too few iterations do not converge
+    let short = conjugate_gradient(&a, &b, None, Some(1e-12), Some(3), None)?;
+    assert!(!short.converged);
+    assert_eq!(short.iterations, 3);
+    let zero = conjugate_gradient(&a, &Array1::<f64>::zeros(n), None, None, None, None)?;
+    assert!(zero.x.iter().all(|&v| v == 0.0));
+    assert!(conjugate_gradient(&a, &arr1(&[1.0, 2.0]), None, None, None, None).is_err());
+    let singular = CsrMatrix::from_triplets(2, 2, &[(0, 0, 1.0)])?;
+    assert!(conjugate_gradient(&singular, &arr1(&[1.0, 1.0]), None, None, None, None).is_err());
+    let rect = CsrMatrix::from_triplets(2, 3, &[(0, 0, 1.0)])?;
+    assert!(conjugate_gradient(&rect, &arr1(&[1.0, 1.0]), None, None, None, None).is_err());
+    Ok(())
+}
+
+/// Tests that `CsrMatrix` assembles triplets with duplicates, multiplies
+/// vectors and rejects invalid compressed arrays.
+#[test]
+fn sparse_csr_matrix_expected_results() -> Result<(), ImgalError> {
+    let triplets = [
+        (1, 2, 3.0),
+        (0, 0, 1.0),
+        (1, 0, 2.0),
+        (0, 0, 4.0),
+        (2, 1, -1.0),
+    ];
+    let a = CsrMatrix::from_triplets(3, 3, &triplets)?;
+    let b = CsrMatrix::new(
+        3,
+        3,
+        vec![0, 1, 3, 4],
+        vec![0, 0, 2, 1],
+        vec![5.0, 2.0, 3.0, -1.0],
+    )?;
+    assert_eq!(a, b);
+    assert_eq!(a.shape(), (3, 3));
+    assert_eq!(a.nnz(), 4);
+    assert_eq!(a.diagonal(), arr1(&[5.0, 0.0, 0.0]));
+    let x = arr1(&[1.0, 2.0, 3.0]);
+    assert_eq!(a.dot(&x, THREADS)?, arr1(&[5.0, 11.0, -2.0]));
+    assert_eq!(a.dot(&x, THREADS)?, a.dot(&x, None)?);
+    assert!(a.dot(&arr1(&[1.0]), None).is_err());
+    assert!(CsrMatrix::from_triplets(2, 2, &[(2, 0, 1.0)]).is_err());
+    assert!(CsrMatrix::new(2, 2, vec![0, 1], vec![0], vec![1.0]).is_err());
+    assert!(CsrMatrix::new(1, 2, vec![0, 2], vec![1, 0], vec![1.0, 1.0]).is_err());
+    assert!(CsrMatrix::new(1, 2, vec![0, 1], vec![2], vec![1.0]).is_err());
+    assert!(CsrMatrix::new(1, 2, vec![0, 1], vec![0], vec![]).is_err());
+    Ok(())
+}
+
+/// Tests that `hungarian` finds the optimal assignment of square and
+/// rectangular cost matrices.
+#[test]
+fn hungarian_hungarian_expected_results() -> Result<(), ImgalError> {
+    let cost = arr2(&[[4.0, 1.0, 3.0], [2.0, 0.0, 5.0], [3.0, 2.0, 2.0]]);
+    assert_eq!(hungarian(&cost)?, vec![(0, 1), (1, 0), (2, 2)]);
+    // more rows than columns leaves the costliest rows unassigned
+    let tall = arr2(&[[1.0, 9.0], [9.0, 9.0], [9.0, 1.0]]);
+    assert_eq!(hungarian(&tall)?, vec![(0, 0), (2, 1)]);
+    assert_eq!(hungarian(tall.t())?, vec![(0, 0), (1, 2)]);
+    let mut rng = Pcg::new(11);
+    for _ in 0..10 {
+        let cost = Array2::from_shape_fn((4, 6), |_| rng.next_f32() as f64);
+        let pairs = hungarian(&cost)?;
+        let total: f64 = pairs.iter().map(|&(i, j)| cost[[i, j]]).sum();
+        assert_eq!(pairs.len(), 4);
+        assert!((total - brute_force(&cost, 0, &mut vec![false; 6])).abs() < 1e-12);
+    }
+    assert!(hungarian(&Array2::<f64>::zeros((0, 3)))?.is_empty());
+    assert!(hungarian(&arr2(&[[1.0, f64::NAN]])).is_err());
+    Ok(())
+}
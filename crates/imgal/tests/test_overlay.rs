@@ -0,0 +1,30 @@
+use ndarray::{Array2, s};
+
+use imgal::label::BoundaryMode;
+use imgal::overlay::boundary::boundaries_mut;
+use imgal::prelude::*;
+
+/// Tests that `boundaries_mut` sets the object boundary pixels to the maximum
+/// value of the image type.
+#[test]
+fn overlay_boundaries_mut_expected_results() -> Result<(), ImgalError> {
+    let mut data = Array2::<u8>::from_elem((6, 6), 10);
+    let mut labels = Array2::<u64>::zeros((6, 6));
+    labels.slice_mut(s![1..5, 1..5]).fill(1);
+    boundaries_mut(&mut data.view_mut(), labels.view(), None, Some(0))?;
+    assert_eq!(data.iter().filter(|&&v| v == u8::MAX).count(), 12);
+    assert_eq!(data[[1, 1]], u8::MAX);
+    assert_eq!(data[[2, 2]], 10);
+    assert_eq!(data[[0, 0]], 10);
+    let mut outer = Array2::<f64>::zeros((6, 6));
+    boundaries_mut(
+        &mut outer.view_mut(),
+        labels.view(),
+        Some(BoundaryMode::Outer),
+        None,
+    )?;
+    assert_eq!(outer[[0, 1]], f64::MAX);
+    assert_eq!(outer[[1, 1]], 0.0);
+    assert!(boundaries_mut(&mut data.view_mut(), labels.slice(s![..5, ..]), None, None).is_err());
+    Ok(())
+}
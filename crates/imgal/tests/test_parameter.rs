@@ -1,4 +1,5 @@
-use imgal::parameter::{abbe_diffraction_limit, omega};
+use imgal::parameter::{abbe_diffraction_limit, max_valid_harmonic, omega};
+use imgal::prelude::*;
 
 /// Tests that `abbe_diffraction_limit` returns the expected result for a given
 /// wavelength and numerical aperature.
@@ -12,6 +13,19 @@ fn parameter_abbe_diffraction_limit_expected_results() {
     assert_eq!(abbe_diffraction_limit(570, 1.45), 196.55172413793105);
 }
 
+/// Tests that `max_valid_harmonic` returns the largest harmonic below the
+/// Nyquist limit of the decay sampling.
+#[test]
+fn parameter_max_valid_harmonic_expected_results() -> Result<(), ImgalError> {
+    assert_eq!(max_valid_harmonic(256, 12.5)?, 127);
+    assert_eq!(max_valid_harmonic(255, 12.5)?, 127);
+    assert_eq!(max_valid_harmonic(4, 10.0)?, 1);
+    assert_eq!(max_valid_harmonic(2, 10.0)?, 0);
+    assert_eq!(max_valid_harmonic(0, 10.0)?, 0);
+    assert!(max_valid_harmonic(256, 0.0).is_err());
+    Ok(())
+}
+
 /// Tests that `omega` returns the expected result for a given period.
 #[test]
 fn parameter_omega_expected_results() {
@@ -1,11 +1,21 @@
-use ndarray::{Array2, Axis, s};
+#![allow(clippy::bool_assert_comparison)]
+
+use std::collections::HashMap;
+use std::ops::Range;
+
+use ndarray::{Array2, Array3, Axis, arr2, s};
 
 use imgal::parameter::omega;
+use imgal::phasor::builder::Phasor;
 use imgal::phasor::calibration::{
     calibrate_coords, calibrate_gs_image, calibrate_gs_image_mut, modulation_and_phase,
 };
+use imgal::phasor::export::{gs_image_npz, gs_roi_npz};
 use imgal::phasor::plot::{gs_mask, gs_modulation, gs_phase, monoexponential_coords};
-use imgal::phasor::time_domain::{gs_image, imaginary_coord, real_coord};
+use imgal::phasor::time_domain::{
+    DegeneratePolicy, gs_image, gs_image_checked, gs_image_tiled, gs_roi, gs_roi_checked,
+    imaginary_coord, real_coord,
+};
 use imgal::prelude::*;
 use imgal::simulation::decay::{gaussian_exponential_decay_3d, ideal_exponential_decay_1d};
 use imgal::simulation::noise::poisson_noise_mut;
@@ -49,8 +59,105 @@ fn get_circle_mask(shape: (usize, usize), center: (isize, isize), radius: isize)
     mask
 }
 
+// read the (name, npy header, data) entries of a stored (uncompressed) zip
+fn read_stored_zip(bytes: &[u8]) -> Vec<(String, String, Vec<u8>)> {
+    let u16_at = |i: usize| u16::from_le_bytes([bytes[i], bytes[i + 1]]) as usize;
+    let u32_at = |i: usize| u32::from_le_bytes(bytes[i..i + 4].try_into().unwrap()) as usize;
+    let mut entries = Vec::new();
+    let mut i = 0;
+    while u32_at(i) == 0x04034b50 {
+        let (size, name_len) = (u32_at(i + 18), u16_at(i + 26));
+        let name = String::from_utf8(bytes[i + 30..i + 30 + name_len].to_vec()).unwrap();
+        let npy = &bytes[i + 30 + name_len..i + 30 + name_len + size];
+        let header_len = u16::from_le_bytes([npy[8], npy[9]]) as usize;
+        let header = String::from_utf8(npy[10..10 + header_len].to_vec()).unwrap();
+        entries.push((name, header, npy[10 + header_len..].to_vec()));
+        i += 30 + name_len + size;
+    }
+    entries
+}
+
+fn le_f64(data: &[u8]) -> Vec<f64> {
+    data.chunks(8)
+        .map(|c| f64::from_le_bytes(c.try_into().unwrap()))
+        .collect()
+}
 /// Tests that `calibrate_coords` returns the expected calibrated G and S
 /// values.
+/// Tests that the `Phasor` builder matches the flat `phasor::time_domain`
+/// functions with the same parameters.
+#[test]
+fn builder_phasor_expected_results() -> Result<(), ImgalError> {
+    let mut data = gaussian_exponential_decay_3d(
+        SAMPLES,
+        PERIOD,
+        &TAUS,
+        &FRACTIONS,
+        TOTAL_COUNTS,
+        IRF_CENTER,
+        IRF_WIDTH,
+        SHAPE,
+        None,
+    )?;
+    data.slice_mut(s![0, .., ..]).fill(0.0);
+    let mask = get_circle_mask(SHAPE, (5, 5), 3);
+    let phasor = Phasor::new(PERIOD).harmonic(2.0).mask(&mask).threads(0);
+    assert_eq!(
+        phasor.compute(&data)?,
+        gs_image(
+            data.view(),
+            PERIOD,
+            Some(mask.view()),
+            Some(2.0),
+            None,
+            THREADS
+        )?
+    );
+    // the decay axis and degenerate policy are forwarded
+    let permuted = data.view().permuted_axes([2, 0, 1]);
+    let (gs_axis, n_axis) = Phasor::new(PERIOD)
+        .axis(0)
+        .policy(DegeneratePolicy::Zero)
+        .compute_checked(permuted)?;
+    let (gs, n) = gs_image_checked(
+        data.view(),
+        PERIOD,
+        None,
+        None,
+        None,
+        Some(DegeneratePolicy::Zero),
+        None,
+    )?;
+    assert_eq!(n_axis, SHAPE.1);
+    assert_eq!(n, n_axis);
+    assert!(
+        gs_axis
+            .iter()
+            .zip(gs.iter())
+            .all(|(a, b)| approx_equal(*a, *b, None))
+    );
+    assert_eq!(
+        Phasor::new(PERIOD)
+            .policy(DegeneratePolicy::Error)
+            .compute(&data),
+        Err(ImgalError::InvalidDecayIntegral { n_pixels: SHAPE.1 })
+    );
+    let (gs_tiled, _) = phasor.compute_tiled(
+        |rows: Range<usize>, cols: Range<usize>| Ok(data.slice(s![rows, cols, ..]).to_owned()),
+        SHAPE,
+        (4, 4),
+    )?;
+    assert_eq!(gs_tiled, phasor.compute(&data)?);
+    let mut rois: HashMap<u64, Array2<usize>> = HashMap::new();
+    rois.insert(1, arr2(&[[1, 1], [5, 5], [9, 2]]));
+    let (gs_map, _) = phasor.compute_roi(&data, &rois)?;
+    assert_eq!(
+        gs_map,
+        gs_roi(data.view(), PERIOD, &rois, Some(2.0), None, None)?
+    );
+    Ok(())
+}
+
 #[test]
 fn calibration_calibrate_coords_expected_results() {
     let g = -0.37;
@@ -266,6 +373,130 @@ fn time_domain_gs_image_expected_results() -> Result<(), ImgalError> {
     Ok(())
 }
 
+/// Tests that `gs_image_tiled` stitches the per-tile phasor coordinates into
+/// the same image as `gs_image_checked`.
+#[test]
+fn time_domain_gs_image_tiled_expected_results() -> Result<(), ImgalError> {
+    let mut data = gaussian_exponential_decay_3d(
+        SAMPLES,
+        PERIOD,
+        &TAUS,
+        &FRACTIONS,
+        TOTAL_COUNTS,
+        IRF_CENTER,
+        IRF_WIDTH,
+        (23, 17),
+        None,
+    )?;
+    // empty pixels in two different tiles
+    data.slice_mut(s![0, 0, ..]).fill(0.0);
+    data.slice_mut(s![20, 16, ..]).fill(0.0);
+    let mask = get_circle_mask((23, 17), (11, 8), 6);
+    let policy = Some(DegeneratePolicy::Zero);
+    let (gs, n_degenerate) = gs_image_checked(&data, PERIOD, None, None, None, policy, THREADS)?;
+    let mut n_loads = 0;
+    let load = |rows: Range<usize>, cols: Range<usize>| {
+        n_loads += 1;
+        Ok(data.slice(s![rows, cols, ..]).to_owned())
+    };
+    let (gs_tiled, n_tiled) = gs_image_tiled(
+        load,
+        (23, 17),
+        (8, 5),
+        PERIOD,
+        None,
+        None,
+        None,
+        policy,
+        THREADS,
+    )?;
+    assert_eq!(n_loads, 12);
+    assert_eq!(gs_tiled, gs);
+    assert_eq!(n_tiled, n_degenerate);
+    assert_eq!(n_tiled, 2);
+    // masked and with the decay on the first axis
+    let data_t = data.view().permuted_axes([2, 0, 1]);
+    let load_t =
+        |rows: Range<usize>, cols: Range<usize>| Ok(data_t.slice(s![.., rows, cols]).to_owned());
+    let (gs_masked, _) = gs_image_tiled(
+        load_t,
+        (23, 17),
+        (10, 10),
+        PERIOD,
+        Some(mask.view()),
+        None,
+        Some(0),
+        policy,
+        None,
+    )?;
+    let (gs_masked_whole, _) =
+        gs_image_checked(&data, PERIOD, Some(mask.view()), None, None, policy, None)?;
+    assert_eq!(gs_masked, gs_masked_whole);
+    // tiles with the wrong shape or decay length
+    let bad_shape = |_: Range<usize>, _: Range<usize>| Ok(Array3::<f64>::ones((2, 2, SAMPLES)));
+    assert!(
+        gs_image_tiled(
+            bad_shape,
+            (4, 4),
+            (3, 3),
+            PERIOD,
+            None,
+            None,
+            None,
+            None,
+            None
+        )
+        .is_err()
+    );
+    let mut calls = 0;
+    let bad_decay = |rows: Range<usize>, cols: Range<usize>| {
+        calls += 1;
+        Ok(Array3::<f64>::ones((
+            rows.len(),
+            cols.len(),
+            SAMPLES + calls,
+        )))
+    };
+    assert!(
+        gs_image_tiled(
+            bad_decay,
+            (4, 4),
+            (2, 2),
+            PERIOD,
+            None,
+            None,
+            None,
+            None,
+            None
+        )
+        .is_err()
+    );
+    let load_none = |_: Range<usize>, _: Range<usize>| {
+        Err::<Array3<f64>, _>(ImgalError::InvalidGeneric {
+            msg: "Failed to load tile.",
+        })
+    };
+    assert!(
+        gs_image_tiled(
+            load_none,
+            (4, 4),
+            (2, 2),
+            PERIOD,
+            None,
+            None,
+            None,
+            None,
+            None
+        )
+        .is_err()
+    );
+    let ok = |rows: Range<usize>, cols: Range<usize>| {
+        Ok(Array3::<f64>::ones((rows.len(), cols.len(), SAMPLES)))
+    };
+    assert!(gs_image_tiled(ok, (4, 4), (0, 2), PERIOD, None, None, None, None, None).is_err());
+    Ok(())
+}
+
 /// Tests that `imaginary_coord` returns the expected imaginary (S) coordinate.
 #[test]
 fn time_domain_imaginary_coord_expected_results() -> Result<(), ImgalError> {
@@ -287,3 +518,173 @@ fn time_domain_real_coord_expected_results() -> Result<(), ImgalError> {
     assert!(approx_equal(g_coord_seq, 0.660137605, None));
     Ok(())
 }
+
+/// Tests that `gs_image` and `gs_roi` return errors for mismatched masks and
+/// out of bounds ROI points instead of panicking.
+#[test]
+fn time_domain_gs_validation_expected_results() -> Result<(), ImgalError> {
+    let data = Array3::<f64>::ones((4, 5, 16));
+    let mask = Array2::<bool>::from_elem((5, 4), true);
+    assert!(matches!(
+        gs_image(data.view(), PERIOD, Some(mask.view()), None, None, None),
+        Err(ImgalError::MismatchedArrayShapes { .. })
+    ));
+    // the mask matches the spatial shape when the decay axis is the first axis
+    let data_t = Array3::<f64>::ones((16, 5, 4));
+    assert!(
+        gs_image(
+            data_t.view(),
+            PERIOD,
+            Some(mask.view()),
+            None,
+            Some(0),
+            None
+        )
+        .is_ok()
+    );
+    // harmonics above the Nyquist limit of 16 time bins are aliased
+    assert!(gs_image(data.view(), PERIOD, None, Some(7.0), None, None).is_ok());
+    assert!(matches!(
+        gs_image(data.view(), PERIOD, None, Some(8.0), None, None),
+        Err(ImgalError::InvalidParameterValueOutsideRange { .. })
+    ));
+    let mut rois: HashMap<u64, Array2<usize>> = HashMap::new();
+    rois.insert(1, arr2(&[[0, 0], [3, 4]]));
+    assert_eq!(
+        gs_roi(data.view(), PERIOD, &rois, None, None, THREADS)?[&1].dim(),
+        (2, 2)
+    );
+    assert!(gs_roi(data.view(), PERIOD, &rois, Some(8.0), None, None).is_err());
+    rois.insert(2, arr2(&[[1, 1], [4, 0]]));
+    assert_eq!(
+        gs_roi(data.view(), PERIOD, &rois, None, None, None),
+        Err(ImgalError::InvalidRoiCoordinates {
+            roi_label: 2,
+            coords: vec![4, 0],
+            shape: vec![4, 5],
+        })
+    );
+    rois.remove(&2);
+    rois.insert(3, arr2(&[[1, 1, 1]]));
+    assert!(gs_roi(data.view(), PERIOD, &rois, None, Some(3), None).is_err());
+    assert!(matches!(
+        gs_roi(data.view(), PERIOD, &rois, None, None, None),
+        Err(ImgalError::InvalidAxisLengthExpected { .. })
+    ));
+    Ok(())
+}
+
+/// Tests that `gs_image_checked` and `gs_roi_checked` count degenerate pixels
+/// with a zero decay integral and apply the degenerate pixel policy.
+#[test]
+fn time_domain_gs_checked_expected_results() -> Result<(), ImgalError> {
+    let mut data = Array3::<f64>::zeros((3, 3, 16));
+    data.slice_mut(s![0, .., ..]).fill(1.0);
+    let (gs_nan, n_nan) = gs_image_checked(data.view(), PERIOD, None, None, None, None, THREADS)?;
+    let (gs_zero, n_zero) = gs_image_checked(
+        data.view(),
+        PERIOD,
+        None,
+        None,
+        None,
+        Some(DegeneratePolicy::Zero),
+        None,
+    )?;
+    assert_eq!(n_nan, 6);
+    assert_eq!(n_zero, 6);
+    assert!(gs_nan[[1, 1, 0]].is_nan());
+    assert!(gs_nan[[0, 1, 0]].is_finite());
+    assert_eq!(gs_zero[[2, 2, 1]], 0.0);
+    assert_eq!(gs_zero[[0, 0, 0]], gs_nan[[0, 0, 0]]);
+    assert!(gs_image(data.view(), PERIOD, None, None, None, None)?[[2, 0, 0]].is_nan());
+    assert_eq!(
+        gs_image_checked(
+            data.view(),
+            PERIOD,
+            None,
+            None,
+            None,
+            Some(DegeneratePolicy::Error),
+            THREADS
+        ),
+        Err(ImgalError::InvalidDecayIntegral { n_pixels: 6 })
+    );
+    // masked out pixels are not degenerate
+    let mut mask = Array2::<bool>::from_elem((3, 3), false);
+    mask[[0, 0]] = true;
+    let (_, n_mask) = gs_image_checked(
+        data.view(),
+        PERIOD,
+        Some(mask.view()),
+        None,
+        None,
+        Some(DegeneratePolicy::Error),
+        None,
+    )?;
+    assert_eq!(n_mask, 0);
+    let mut rois: HashMap<u64, Array2<usize>> = HashMap::new();
+    rois.insert(1, arr2(&[[0, 0], [1, 0], [2, 2]]));
+    let (gs_map, n_roi) = gs_roi_checked(
+        data.view(),
+        PERIOD,
+        &rois,
+        None,
+        None,
+        Some(DegeneratePolicy::Zero),
+        THREADS,
+    )?;
+    assert_eq!(n_roi, 2);
+    assert_eq!(gs_map[&1].iter().filter(|v| **v == 0.0).count(), 4);
+    Ok(())
+}
+
+/// Tests that `gs_image_npz` and `gs_roi_npz` write the documented `.npz`
+/// keys, shapes and values.
+#[test]
+fn export_gs_npz_expected_results() -> Result<(), ImgalError> {
+    let gs = Array3::from_shape_fn((2, 3, 2), |(r, c, k)| (r * 3 + c) as f64 + 0.5 * k as f64);
+    let mean = Array2::from_elem((2, 3), 7.0);
+    let bytes = gs_image_npz(gs.view(), Some(mean.view()), Some(2.0), Some(80.0))?;
+    let entries = read_stored_zip(&bytes);
+    let names: Vec<&str> = entries.iter().map(|e| e.0.as_str()).collect();
+    assert_eq!(
+        names,
+        [
+            "real.npy",
+            "imag.npy",
+            "mean.npy",
+            "harmonic.npy",
+            "frequency.npy"
+        ]
+    );
+    assert!(entries[0].1.contains("'descr': '<f8'"));
+    assert!(entries[0].1.contains("'shape': (2, 3)"));
+    assert!(entries[3].1.contains("'shape': ()"));
+    // the array data is 64 byte aligned within each npy entry
+    assert!(entries.iter().all(|e| (10 + e.1.len()) % 64 == 0));
+    assert_eq!(le_f64(&entries[0].2), [0.0, 1.0, 2.0, 3.0, 4.0, 5.0]);
+    assert_eq!(le_f64(&entries[1].2), [0.5, 1.5, 2.5, 3.5, 4.5, 5.5]);
+    assert_eq!(le_f64(&entries[3].2), [2.0]);
+    assert_eq!(le_f64(&entries[4].2), [80.0]);
+    let minimal = read_stored_zip(&gs_image_npz(gs.view(), None, None, None)?);
+    assert_eq!(minimal.len(), 3);
+    let mut rois = HashMap::new();
+    rois.insert(9, arr2(&[[0.1, 0.2]]));
+    rois.insert(4, arr2(&[[0.3, 0.4], [0.5, 0.6]]));
+    let roi_entries = read_stored_zip(&gs_roi_npz(&rois, None, None)?);
+    assert_eq!(roi_entries[0].0, "label.npy");
+    assert!(roi_entries[0].1.contains("'descr': '<u8'"));
+    assert!(roi_entries[0].1.contains("'shape': (3,)"));
+    let labels: Vec<u64> = roi_entries[0]
+        .2
+        .chunks(8)
+        .map(|c| u64::from_le_bytes(c.try_into().unwrap()))
+        .collect();
+    assert_eq!(labels, [4, 4, 9]);
+    assert_eq!(le_f64(&roi_entries[1].2), [0.3, 0.5, 0.1]);
+    assert_eq!(le_f64(&roi_entries[2].2), [0.4, 0.6, 0.2]);
+    assert!(gs_image_npz(gs.view(), Some(Array2::zeros((3, 3)).view()), None, None).is_err());
+    rois.insert(1, Array2::zeros((1, 3)));
+    assert!(gs_roi_npz(&rois, None, None).is_err());
+    Ok(())
+}
@@ -0,0 +1,107 @@
+use ndarray::{Array2, Array4, Axis, s};
+
+use imgal::prelude::*;
+use imgal::registration::{axial_offset, axial_offset_correct, bead_axial_offset};
+
+const TOLERANCE: f64 = 1e-10;
+const THREADS: Option<usize> = Some(0);
+
+fn approx_equal(a: f64, b: f64, tol: Option<f64>) -> bool {
+    (a - b).abs() < tol.unwrap_or(TOLERANCE)
+}
+
+const BEADS: [[f64; 3]; 4] = [
+    [8.0, 5.0, 6.0],
+    [12.0, 14.0, 10.0],
+    [10.0, 9.0, 16.0],
+    [14.0, 17.0, 4.0],
+];
+
+/// Create a `(2, 24, 20, 20)` stack of Gaussian beads, with the second channel
+/// axially displaced by `offset` planes.
+fn bead_stack(offset: f64) -> Array4<f64> {
+    Array4::from_shape_fn((2, 24, 20, 20), |(ch, z, r, c)| {
+        let dz = if ch == 1 { offset } else { 0.0 };
+        BEADS
+            .iter()
+            .map(|b| {
+                let d2 = ((z as f64 - b[0] - dz) / 2.0).powi(2)
+                    + (r as f64 - b[1]).powi(2)
+                    + (c as f64 - b[2]).powi(2);
+                100.0 * (-0.5 * d2 / 1.5).exp()
+            })
+            .sum()
+    })
+}
+
+/// Tests that `axial_offset` estimates a subvoxel axial displacement and that
+/// `axial_offset_correct` aligns the channels.
+#[test]
+fn axial_axial_offset_correct_expected_results() -> Result<(), ImgalError> {
+    let data = bead_stack(1.4);
+    let reference = data.index_axis(Axis(0), 0);
+    let moving = data.index_axis(Axis(0), 1);
+    let offset = axial_offset(reference, moving, None, THREADS)?;
+    let offset_seq = axial_offset(reference, moving, None, None)?;
+    assert_eq!(offset, offset_seq);
+    assert!(approx_equal(offset, 1.4, Some(0.1)));
+    assert!(approx_equal(
+        axial_offset(moving, reference, Some(3), None)?,
+        -1.4,
+        Some(0.1)
+    ));
+    assert!(approx_equal(
+        axial_offset(reference, reference, None, None)?,
+        0.0,
+        None
+    ));
+    let (corrected, offsets) = axial_offset_correct(&data, 0, None, None, THREADS)?;
+    assert_eq!(offsets.len(), 2);
+    assert_eq!(offsets[0], 0.0);
+    assert_eq!(offsets[1], offset);
+    assert_eq!(corrected.index_axis(Axis(0), 0), reference);
+    // the corrected channel matches the reference up to the interpolation error
+    let residual = (&corrected.slice(s![1, 2..20, .., ..]) - &reference.slice(s![2..20, .., ..]))
+        .mapv(f64::abs)
+        .sum();
+    let uncorrected = (&moving.slice(s![2..20, .., ..]) - &reference.slice(s![2..20, .., ..]))
+        .mapv(f64::abs)
+        .sum();
+    assert!(residual < 0.1 * uncorrected);
+    // a given integer offset shifts the planes exactly, with zeros shifted in
+    let (shifted, _) = axial_offset_correct(&data, 0, Some(&[0.0, 2.0]), None, None)?;
+    assert_eq!(
+        shifted.slice(s![1, ..22, .., ..]),
+        data.slice(s![1, 2.., .., ..])
+    );
+    assert!(shifted.slice(s![1, 22.., .., ..]).iter().all(|&v| v == 0.0));
+    assert!(axial_offset(reference, moving, Some(24), None).is_err());
+    assert!(axial_offset(reference, moving.slice(s![..5, .., ..]), None, None).is_err());
+    assert!(axial_offset_correct(&data, 2, None, None, None).is_err());
+    assert!(axial_offset_correct(&data, 0, Some(&[0.0]), None, None).is_err());
+    assert!(axial_offset_correct(&data, 0, Some(&[0.0, f64::NAN]), None, None).is_err());
+    Ok(())
+}
+
+/// Tests that `bead_axial_offset` returns the median axial displacement of the
+/// matched beads, robust to an outlier.
+#[test]
+fn axial_bead_axial_offset_expected_results() -> Result<(), ImgalError> {
+    let reference = Array2::from_shape_fn((4, 3), |(i, j)| BEADS[i][j]);
+    let mut moving = reference.clone();
+    moving.column_mut(0).iter_mut().for_each(|z| *z += 0.7);
+    moving[[3, 0]] += 1.0;
+    assert!(approx_equal(
+        bead_axial_offset(&reference, &moving, 3.0)?,
+        0.7,
+        None
+    ));
+    assert!(approx_equal(
+        bead_axial_offset(reference.slice(s![..3, ..]), moving.slice(s![..3, ..]), 3.0)?,
+        0.7,
+        None
+    ));
+    assert!(bead_axial_offset(&reference, &moving, 0.1).is_err());
+    assert!(bead_axial_offset(reference.view(), moving.slice(s![.., ..2]), 3.0).is_err());
+    Ok(())
+}
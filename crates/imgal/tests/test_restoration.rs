@@ -0,0 +1,422 @@
+use ndarray::{Array2, Array3, Axis, s};
+
+use imgal::filter::fft_convolve;
+use imgal::prelude::*;
+use imgal::restoration::{
+    ReplacementStrategy, anscombe, blind_spot_mask, blind_spot_patches, bm3d, extract_patches,
+    inverse_anscombe, richardson_lucy, richardson_lucy_varying, sim_reconstruct, stitch_patches,
+};
+use imgal::simulation::noise::poisson_noise;
+use imgal::simulation::pattern::{sinusoidal_patterns, structured_illumination};
+use imgal::simulation::rng::Pcg;
+
+const TOLERANCE: f64 = 1e-10;
+const THREADS: Option<usize> = Some(0);
+
+fn approx_equal(a: f64, b: f64, tol: Option<f64>) -> bool {
+    (a - b).abs() < tol.unwrap_or(TOLERANCE)
+}
+
+fn gaussian_psf(sigma: f64) -> Array2<f64> {
+    let psf = Array2::from_shape_fn((9, 9), |(r, c)| {
+        let (dr, dc) = (r as f64 - 4.0, c as f64 - 4.0);
+        (-(dr * dr + dc * dc) / (2.0 * sigma * sigma)).exp()
+    });
+    let total = psf.sum();
+    psf / total
+}
+
+fn point_grid(rows: usize, cols: usize) -> Array2<f64> {
+    Array2::from_shape_fn((rows, cols), |(r, c)| {
+        if r % 8 == 4 && c % 8 == 4 { 100.0 } else { 1.0 }
+    })
+}
+
+fn ramp_image(rows: usize, cols: usize) -> Array2<f64> {
+    Array2::from_shape_fn((rows, cols), |(r, c)| (r * cols + c) as f64)
+}
+
+/// Tests that `anscombe` stabilizes the variance of Poisson noise and that
+/// `inverse_anscombe` is unbiased.
+#[test]
+fn anscombe_anscombe_expected_results() -> Result<(), ImgalError> {
+    for lambda in [5.0, 50.0] {
+        let data = Array2::<f64>::from_elem((200, 200), lambda);
+        let noisy = poisson_noise(&data, 1.0, Some(5), None);
+        let gat_par = anscombe(&noisy, None, None, None, THREADS)?;
+        let gat_seq = anscombe(&noisy, None, None, None, None)?;
+        assert_eq!(gat_par, gat_seq);
+        let mean = gat_par.mean().unwrap();
+        let var = gat_par.mapv(|v| (v - mean).powi(2)).mean().unwrap();
+        assert!(approx_equal(var, 1.0, Some(0.1)));
+        // the unbiased inverse of the expected value recovers the intensity,
+        // while the algebraic inverse "(D / 2)² - 3/8" is biased low
+        let inv = inverse_anscombe(&[mean], None, None, None, None)?;
+        let algebraic = (mean / 2.0).powi(2) - 0.375;
+        assert!((inv[0] - lambda).abs() < (algebraic - lambda).abs());
+        assert!(approx_equal(inv[0], lambda, Some(0.15)));
+    }
+    // the generalized transform with gain, read noise and offset
+    let gat = anscombe(&[110.0, 0.0], Some(2.0), Some(3.0), Some(10.0), None)?;
+    assert!(approx_equal(
+        gat[0],
+        (220.0f64 + 1.5 + 9.0 - 20.0).sqrt(),
+        None
+    ));
+    assert_eq!(gat[1], 0.0);
+    let inv = inverse_anscombe(&[20.0, 0.0], Some(2.0), Some(3.0), Some(10.0), None)?;
+    let expected = 100.0 + 0.25 * 1.5f64.sqrt() / 20.0 - 1.375 / 400.0
+        + 0.625 * 1.5f64.sqrt() / 8000.0
+        - 0.125
+        - 2.25;
+    assert!(approx_equal(inv[0], 2.0 * expected + 10.0, None));
+    assert_eq!(inv[1], 10.0);
+    assert!(anscombe(&[1.0], Some(0.0), None, None, None).is_err());
+    assert!(inverse_anscombe(&[1.0], None, Some(-1.0), None, None).is_err());
+    Ok(())
+}
+
+/// Tests that `blind_spot_mask` masks a stratified fraction of pixels with
+/// neighborhood replacement values.
+#[test]
+fn blind_spot_blind_spot_mask_expected_results() -> Result<(), ImgalError> {
+    let data = ramp_image(32, 32);
+    let output = blind_spot_mask(
+        &data,
+        1.0 / 16.0,
+        None,
+        ReplacementStrategy::RandomNeighbor,
+        None,
+    )?;
+    let (masked, mask) = (output.masked, output.mask);
+    assert_eq!(masked.dim(), data.dim());
+    // one masked pixel per 4x4 grid box
+    assert_eq!(mask.iter().filter(|&&m| m).count(), 64);
+    mask.exact_chunks((4, 4))
+        .into_iter()
+        .for_each(|b| assert_eq!(b.iter().filter(|&&m| m).count(), 1));
+    masked.indexed_iter().for_each(|((r, c), &v)| {
+        if mask[[r, c]] {
+            // the replacement is a different pixel within the radius
+            assert!(v != data[[r, c]]);
+            let (nr, nc) = ((v as usize) / 32, (v as usize) % 32);
+            assert!(nr.abs_diff(r) <= 2 && nc.abs_diff(c) <= 2);
+        } else {
+            assert_eq!(v, data[[r, c]]);
+        }
+    });
+    // the neighborhood median of a linear ramp is the masked pixel value
+    let output = blind_spot_mask(
+        &data,
+        0.1,
+        Some(1),
+        ReplacementStrategy::NeighborMedian,
+        Some(3),
+    )?;
+    let (median, mask) = (output.masked, output.mask);
+    median.indexed_iter().for_each(|((r, c), &v)| {
+        if mask[[r, c]] && r > 0 && r < 31 && c > 0 && c < 31 {
+            assert!(approx_equal(v, data[[r, c]], None));
+        }
+    });
+    let zeros = blind_spot_mask(&data, 1.0, None, ReplacementStrategy::Zero, None)?;
+    assert!(zeros.mask.iter().all(|&m| m));
+    assert!(zeros.masked.iter().all(|&v| v == 0.0));
+    assert!(blind_spot_mask(&data, 0.0, None, ReplacementStrategy::Zero, None).is_err());
+    assert!(blind_spot_mask(&data, 0.5, Some(0), ReplacementStrategy::Zero, None).is_err());
+    Ok(())
+}
+
+/// Tests that `blind_spot_patches` creates masked training patches from an
+/// image.
+#[test]
+fn blind_spot_blind_spot_patches_expected_results() -> Result<(), ImgalError> {
+    let data = Array3::from_shape_fn((8, 40, 30), |(z, r, c)| (z * 1200 + r * 30 + c) as f64);
+    let output = blind_spot_patches(
+        &data,
+        &[4, 16, 16],
+        10,
+        1.0 / 8.0,
+        None,
+        ReplacementStrategy::RandomNeighbor,
+        Some(11),
+    )?;
+    let (inputs, targets, masks) = (output.inputs, output.targets, output.masks);
+    assert_eq!(inputs.shape(), [10, 4, 16, 16]);
+    assert_eq!(targets.shape(), [10, 4, 16, 16]);
+    assert_eq!(masks.shape(), [10, 4, 16, 16]);
+    targets.axis_iter(Axis(0)).for_each(|t| {
+        // the targets are contiguous image patches
+        let origin = t[[0, 0, 0]] as usize;
+        let (z, r, c) = (origin / 1200, (origin % 1200) / 30, origin % 30);
+        assert_eq!(t[[3, 15, 15]], data[[z + 3, r + 15, c + 15]]);
+    });
+    // one masked pixel per 2x2x2 grid box
+    masks
+        .axis_iter(Axis(0))
+        .for_each(|m| assert_eq!(m.iter().filter(|&&v| v).count(), 128));
+    inputs
+        .iter()
+        .zip(targets.iter())
+        .zip(masks.iter())
+        .filter(|(_, m)| !**m)
+        .for_each(|((a, b), _)| assert_eq!(a, b));
+    assert!(
+        blind_spot_patches(
+            &data,
+            &[4, 16],
+            1,
+            0.1,
+            None,
+            ReplacementStrategy::Zero,
+            None
+        )
+        .is_err()
+    );
+    assert!(
+        blind_spot_patches(
+            &data,
+            &[9, 16, 16],
+            1,
+            0.1,
+            None,
+            ReplacementStrategy::Zero,
+            None
+        )
+        .is_err()
+    );
+    Ok(())
+}
+
+/// Tests that `extract_patches` and `stitch_patches` reconstruct an image from
+/// overlapping patches.
+#[test]
+fn blind_spot_stitch_patches_expected_results() -> Result<(), ImgalError> {
+    let data = ramp_image(37, 29);
+    let (patches, origins) = extract_patches(&data, &[16, 12], 4)?;
+    // rows: 0, 12, 21 and cols: 0, 8, 16, 17
+    assert_eq!(patches.shape(), [12, 16, 12]);
+    assert_eq!(origins.dim(), (12, 2));
+    assert_eq!(origins.row(11).to_vec(), vec![21, 17]);
+    assert_eq!(patches[[5, 0, 0]], data[[12, 8]]);
+    let stitched = stitch_patches(&patches, &origins, &[37, 29])?;
+    assert_eq!(stitched.shape(), [37, 29]);
+    stitched
+        .iter()
+        .zip(data.iter())
+        .for_each(|(a, b)| assert!(approx_equal(*a, *b, None)));
+    // overlapping regions are averaged
+    let mut ones = patches.mapv(|_| 1.0);
+    ones.index_axis_mut(Axis(0), 0).fill(3.0);
+    let avg = stitch_patches(&ones, &origins, &[37, 29])?;
+    assert!(approx_equal(avg[[0, 0]], 3.0, None));
+    // covered by four patches, one of which is 3.0
+    assert!(approx_equal(avg[[12, 8]], 1.5, None));
+    assert!(approx_equal(avg[[36, 28]], 1.0, None));
+    assert!(extract_patches(&data, &[16, 12], 12).is_err());
+    assert!(extract_patches(&data, &[40, 12], 0).is_err());
+    assert!(stitch_patches(&patches, &origins, &[20, 20]).is_err());
+    assert!(stitch_patches(&patches, origins.slice(ndarray::s![..3, ..]), &[37, 29]).is_err());
+    Ok(())
+}
+
+/// Tests that `bm3d` removes Gaussian noise from a piecewise constant image.
+#[test]
+fn bm3d_bm3d_expected_results() -> Result<(), ImgalError> {
+    let clean = Array2::from_shape_fn((48, 40), |(r, c)| {
+        if (r as f64 - 24.0).powi(2) + (c as f64 - 20.0).powi(2) < 100.0 {
+            100.0
+        } else if c < 10 {
+            60.0
+        } else {
+            20.0
+        }
+    });
+    // additive Gaussian noise with "σ = 10.0" from a seeded Box-Muller
+    let mut prng = Pcg::new(42);
+    let noisy = clean.mapv(|v| {
+        let u1 = 1.0 - prng.next_f32() as f64;
+        let u2 = prng.next_f32() as f64;
+        v + 10.0 * (-2.0 * u1.ln()).sqrt() * (2.0 * std::f64::consts::PI * u2).cos()
+    });
+    let rmse = |a: &Array2<f64>| {
+        (a.iter()
+            .zip(clean.iter())
+            .map(|(x, y)| (x - y).powi(2))
+            .sum::<f64>()
+            / a.len() as f64)
+            .sqrt()
+    };
+    let denoised_par = bm3d(&noisy, 10.0, None, Some(8), None, None, THREADS)?;
+    let denoised_seq = bm3d(&noisy, 10.0, None, Some(8), None, None, None)?;
+    assert_eq!(denoised_par.dim(), (48, 40));
+    denoised_par
+        .iter()
+        .zip(denoised_seq.iter())
+        .for_each(|(a, b)| assert!(approx_equal(*a, *b, Some(1e-9))));
+    assert!(rmse(&noisy) > 9.0);
+    assert!(rmse(&denoised_par) < 0.4 * rmse(&noisy));
+    let flat = Array2::<f64>::from_elem((16, 16), 5.0);
+    let flat_denoised = bm3d(&flat, 1.0, Some(4), Some(4), Some(4), Some(2), None)?;
+    flat_denoised
+        .iter()
+        .for_each(|v| assert!(approx_equal(*v, 5.0, Some(0.01))));
+    assert!(bm3d(&noisy, 0.0, None, None, None, None, None).is_err());
+    assert!(bm3d(&noisy, 1.0, Some(64), None, None, None, None).is_err());
+    assert!(bm3d(&noisy, 1.0, None, None, None, Some(0), None).is_err());
+    Ok(())
+}
+
+/// Tests that `richardson_lucy` sharpens blurred points while preserving the
+/// total intensity, and is the identity for a delta PSF.
+#[test]
+fn deconvolve_richardson_lucy_expected_results() -> Result<(), ImgalError> {
+    let truth = point_grid(32, 32);
+    let psf = gaussian_psf(1.0);
+    let blurred = fft_convolve(&truth, &psf, None, None)?;
+    let deconv = richardson_lucy(&blurred, psf.view(), Some(30), THREADS)?;
+    let deconv_seq = richardson_lucy(&blurred, psf.view(), Some(30), None)?;
+    assert_eq!(deconv.dim(), (32, 32));
+    assert!(
+        deconv
+            .iter()
+            .zip(deconv_seq.iter())
+            .all(|(&a, &b)| approx_equal(a, b, Some(1e-9)))
+    );
+    assert!(deconv.iter().all(|&v| v >= 0.0));
+    assert!(approx_equal(deconv.sum() / blurred.sum(), 1.0, Some(0.01)));
+    // the points are restored towards their true intensity
+    assert!(blurred[[12, 12]] < 20.0);
+    assert!(deconv[[12, 12]] > 60.0);
+    let sq_err = |a: &Array2<f64>| (a - &truth).mapv(|v| v * v).sum();
+    assert!(sq_err(&deconv) < 0.5 * sq_err(&blurred));
+    // a delta PSF leaves the image unchanged
+    let mut delta = Array2::<f64>::zeros((3, 3));
+    delta[[1, 1]] = 2.0;
+    let same = richardson_lucy(&blurred, delta.view(), Some(5), None)?;
+    assert!(
+        same.iter()
+            .zip(blurred.iter())
+            .all(|(&a, &b)| approx_equal(a, b, Some(1e-9)))
+    );
+    // a PSF wider than the image pads by mirroring the image repeatedly
+    let tiny = blurred.slice(s![..3, ..3]).to_owned();
+    let tiny_deconv = richardson_lucy(&tiny, gaussian_psf(1.5).view(), Some(5), None)?;
+    assert_eq!(tiny_deconv.dim(), (3, 3));
+    assert!(tiny_deconv.iter().all(|v| v.is_finite() && *v >= 0.0));
+    assert!(richardson_lucy(&blurred, Array2::<f64>::zeros((3, 3)).view(), None, None).is_err());
+    assert!(richardson_lucy(&blurred, (-&delta).view(), None, None).is_err());
+    assert!(richardson_lucy(&blurred, Array2::<f64>::zeros((0, 3)).view(), None, None).is_err());
+    Ok(())
+}
+
+/// Tests that `richardson_lucy_varying` deconvolves each tile with its own
+/// PSF and matches `richardson_lucy` for a uniform PSF grid.
+#[test]
+fn deconvolve_richardson_lucy_varying_expected_results() -> Result<(), ImgalError> {
+    let truth = point_grid(32, 64);
+    let (narrow, wide) = (gaussian_psf(0.7), gaussian_psf(1.5));
+    // the left half is blurred with the narrow PSF, the right half with the wide PSF
+    let mut blurred = fft_convolve(&truth, &narrow, None, None)?;
+    let blurred_wide = fft_convolve(&truth, &wide, None, None)?;
+    blurred
+        .slice_mut(s![.., 32..])
+        .assign(&blurred_wide.slice(s![.., 32..]));
+    let global = richardson_lucy(&blurred, narrow.view(), Some(30), None)?;
+    let single = richardson_lucy_varying(&blurred, &[narrow.view()], (1, 1), Some(30), None)?;
+    assert_eq!(single, global);
+    let uniform =
+        richardson_lucy_varying(&blurred, &[narrow.view(); 6], (2, 3), Some(30), THREADS)?;
+    assert!(
+        uniform
+            .iter()
+            .zip(global.iter())
+            .all(|(&a, &b)| approx_equal(a, b, Some(0.1)))
+    );
+    let global_wide = richardson_lucy(&blurred, wide.view(), Some(30), None)?;
+    let varying = richardson_lucy_varying(
+        &blurred,
+        &[narrow.view(), wide.view()],
+        (1, 2),
+        Some(30),
+        THREADS,
+    )?;
+    // the outer quarters are deconvolved with only their own tile PSF
+    assert!(
+        varying
+            .slice(s![.., ..16])
+            .iter()
+            .zip(global.slice(s![.., ..16]).iter())
+            .all(|(&a, &b)| approx_equal(a, b, Some(0.05)))
+    );
+    assert!(approx_equal(
+        varying[[12, 52]],
+        global_wide[[12, 52]],
+        Some(1.0)
+    ));
+    assert!(varying[[12, 52]] > 2.0 * global[[12, 52]]);
+    assert!(approx_equal(varying.sum() / blurred.sum(), 1.0, Some(0.02)));
+    let psfs = [narrow.view(), wide.view()];
+    assert!(richardson_lucy_varying(&blurred, &psfs, (2, 2), None, None).is_err());
+    assert!(richardson_lucy_varying(&blurred, &psfs, (0, 2), None, None).is_err());
+    assert!(richardson_lucy_varying(&blurred, &psfs[..1], (1, 100), None, None).is_err());
+    Ok(())
+}
+
+/// Tests that `sim_reconstruct` estimates the illumination pattern from the
+/// raw frames and resolves two points that the widefield image does not.
+#[test]
+fn sim_sim_reconstruct_expected_results() -> Result<(), ImgalError> {
+    let mut prng = Pcg::new(42);
+    let mut sample =
+        Array2::from_shape_fn(
+            (64, 64),
+            |_| {
+                if prng.next_f32() < 0.05 { 50.0 } else { 0.0 }
+            },
+        );
+    sample[[32, 30]] = 100.0;
+    sample[[32, 33]] = 100.0;
+    let patterns = sinusoidal_patterns((64, 64), 4.0, 3, 3, None, Some(0.8))?;
+    let frames = structured_illumination(&sample, patterns.view(), 1.5, THREADS)?;
+    let recon = sim_reconstruct(&frames, 3, 1.5, None, THREADS)?;
+    let recon_seq = sim_reconstruct(&frames, 3, 1.5, None, None)?;
+    assert_eq!(recon.image.dim(), (128, 128));
+    assert_eq!(recon.wave_vectors.dim(), (3, 2));
+    assert!(
+        recon
+            .image
+            .iter()
+            .zip(recon_seq.image.iter())
+            .all(|(&a, &b)| approx_equal(a, b, Some(1e-9)))
+    );
+    // the wave vector of the first angle lies on an integer frequency bin
+    assert!(approx_equal(recon.wave_vectors[[0, 0]], 0.0, Some(1e-3)));
+    assert!(approx_equal(recon.wave_vectors[[0, 1]], 0.25, Some(1e-3)));
+    assert!(approx_equal(recon.phases[0], 0.0, Some(0.01)));
+    recon.wave_vectors.outer_iter().for_each(|k| {
+        assert!(approx_equal(k[0].hypot(k[1]), 0.25, Some(0.005)));
+    });
+    recon
+        .modulations
+        .iter()
+        .for_each(|&m| assert!(approx_equal(m, 0.8, Some(0.05))));
+    // the mean intensity is preserved
+    assert!(approx_equal(
+        recon.image.mean().unwrap(),
+        sample.mean().unwrap(),
+        Some(0.05)
+    ));
+    // the widefield profile has no dip between the points, the reconstruction does
+    let widefield = frames.mean_axis(Axis(0)).unwrap();
+    let wide_mid = widefield[[32, 31]].min(widefield[[32, 32]]);
+    assert!(wide_mid >= widefield[[32, 30]].min(widefield[[32, 33]]));
+    let recon_mid = recon.image[[64, 63]];
+    assert!(recon_mid < 0.5 * recon.image[[64, 60]].min(recon.image[[64, 66]]));
+    assert!(sim_reconstruct(&frames, 2, 1.5, None, None).is_err());
+    assert!(sim_reconstruct(&frames, 4, 1.5, None, None).is_err());
+    assert!(sim_reconstruct(&frames, 3, 0.0, None, None).is_err());
+    assert!(sim_reconstruct(&frames, 3, 1.5, Some(0.0), None).is_err());
+    assert!(sim_reconstruct(&Array3::<f64>::zeros((3, 2, 8)), 3, 1.5, None, None).is_err());
+    Ok(())
+}
@@ -0,0 +1,118 @@
+use ndarray::{Array1, Array2};
+
+use imgal::prelude::*;
+use imgal::signal::{cross_correlation, cross_correlation_lag, find_peaks};
+
+const TOLERANCE: f64 = 1e-10;
+const THREADS: Option<usize> = Some(0);
+
+fn approx_equal(a: f64, b: f64, tol: Option<f64>) -> bool {
+    (a - b).abs() < tol.unwrap_or(TOLERANCE)
+}
+
+/// Tests that `find_peaks` finds the expected peaks and per peak metrics.
+#[test]
+fn peaks_find_peaks_expected_results() -> Result<(), ImgalError> {
+    let data = [0.0, 1.0, 4.0, 1.0, 0.0, 2.0, 2.0, 2.0, 0.5, 3.0, 0.0];
+    let peaks = find_peaks(&data, None, None, None, None, None)?;
+    let indices: Vec<usize> = peaks.iter().map(|p| p.index).collect();
+    // the plateau peak is at the middle of the plateau
+    assert_eq!(indices, vec![2, 6, 9]);
+    // the first peak is the highest, its first base minima are at the trace start and index 4
+    assert_eq!(peaks[0].left_base, 0);
+    assert_eq!(peaks[0].right_base, 4);
+    assert!(approx_equal(peaks[0].prominence, 4.0, None));
+    // the width at half prominence (i.e. a height of 2.0)
+    assert!(approx_equal(peaks[0].left_ip, 1.0 + 1.0 / 3.0, None));
+    assert!(approx_equal(peaks[0].right_ip, 3.0 - 1.0 / 3.0, None));
+    assert!(approx_equal(peaks[0].width, 4.0 / 3.0, None));
+    // the plateau peak bases are the minima before the higher neighbors
+    assert_eq!(peaks[1].left_base, 4);
+    assert_eq!(peaks[1].right_base, 8);
+    assert!(approx_equal(peaks[1].prominence, 1.5, None));
+    assert!(approx_equal(peaks[2].prominence, 3.0, None));
+    // filters
+    let high = find_peaks(&data, Some(2.5), None, None, None, None)?;
+    assert_eq!(high.iter().map(|p| p.index).collect::<Vec<_>>(), vec![2, 9]);
+    let prominent = find_peaks(&data, None, None, Some(2.0), None, None)?;
+    assert_eq!(
+        prominent.iter().map(|p| p.index).collect::<Vec<_>>(),
+        vec![2, 9]
+    );
+    let distant = find_peaks(&data, None, Some(5), None, None, None)?;
+    assert_eq!(
+        distant.iter().map(|p| p.index).collect::<Vec<_>>(),
+        vec![2, 9]
+    );
+    let wide = find_peaks(&data, None, None, None, Some(2.0), None)?;
+    assert_eq!(wide.iter().map(|p| p.index).collect::<Vec<_>>(), vec![6]);
+    // a Gaussian event has a full width at half maximum of 2.355σ
+    let gauss = Array1::from_shape_fn(201, |i| (-((i as f64 - 100.0) / 10.0).powi(2) / 2.0).exp());
+    let event = find_peaks(&gauss, None, None, None, None, None)?;
+    assert_eq!(event.len(), 1);
+    assert!(approx_equal(event[0].width, 23.548, Some(0.01)));
+    assert!(find_peaks(&data, None, Some(0), None, None, None).is_err());
+    assert!(find_peaks(&data, None, None, None, None, Some(1.5)).is_err());
+    Ok(())
+}
+
+/// Tests that `cross_correlation` returns the normalized cross-correlation of
+/// two traces.
+#[test]
+fn xcorr_cross_correlation_expected_results() -> Result<(), ImgalError> {
+    let a = [0.0, 1.0, 0.0, 0.0, 0.0];
+    let b = [0.0, 0.0, 0.0, 1.0, 0.0];
+    let xcorr = cross_correlation(&a, &b, None)?;
+    assert_eq!(xcorr.len(), 9);
+    // the maximum is at a lag of +2, i.e. "b" follows "a"
+    let peak = xcorr.iter().enumerate().fold(
+        (0, f64::MIN),
+        |acc, (k, &v)| if v > acc.1 { (k, v) } else { acc },
+    );
+    assert_eq!(peak.0 as isize - 4, 2);
+    // the zero lag value is the Pearson correlation coefficient
+    assert!(approx_equal(xcorr[4], -0.25, None));
+    let auto = cross_correlation(&a, &a, Some(1))?;
+    assert_eq!(auto.len(), 3);
+    assert!(approx_equal(auto[1], 1.0, None));
+    let flat = cross_correlation(&a, &[1.0; 5], None)?;
+    assert!(flat.iter().all(|&v| v == 0.0));
+    assert!(cross_correlation(&a, &b, Some(5)).is_err());
+    assert!(cross_correlation(&a[..], &[1.0, 2.0][..], None).is_err());
+    Ok(())
+}
+
+/// Tests that `cross_correlation_lag` recovers the delays between shifted
+/// traces.
+#[test]
+fn xcorr_cross_correlation_lag_expected_results() -> Result<(), ImgalError> {
+    // Gaussian transients delayed by 0, 5 and -3.5 samples
+    let delays = [0.0, 5.0, -3.5];
+    let traces = Array2::from_shape_fn((3, 400), |(i, t)| {
+        (-((t as f64 - 200.0 - delays[i]) / 6.0).powi(2) / 2.0).exp()
+    });
+    let (lags_par, peaks_par) = cross_correlation_lag(&traces, Some(20), THREADS)?;
+    let (lags_seq, peaks_seq) = cross_correlation_lag(&traces, Some(20), None)?;
+    assert_eq!(lags_par.dim(), (3, 3));
+    assert_eq!(lags_par, lags_seq);
+    assert_eq!(peaks_par, peaks_seq);
+    for i in 0..3 {
+        assert!(approx_equal(lags_par[[i, i]], 0.0, None));
+        assert!(approx_equal(peaks_par[[i, i]], 1.0, None));
+        for j in 0..3 {
+            assert!(approx_equal(
+                lags_par[[i, j]],
+                delays[j] - delays[i],
+                Some(0.05)
+            ));
+            assert!(approx_equal(
+                lags_par[[i, j]],
+                -lags_par[[j, i]],
+                Some(1e-9)
+            ));
+        }
+    }
+    assert!(cross_correlation_lag(&traces, Some(400), None).is_err());
+    assert!(cross_correlation_lag(&Array2::<f64>::zeros((2, 0)), None, None).is_err());
+    Ok(())
+}
@@ -1,6 +1,9 @@
-use ndarray::{arr2, array, s};
+use std::f64::consts::{FRAC_PI_2, PI};
+
+use ndarray::{Array2, Axis, arr2, array, s};
 
 use imgal::constants::RNG_SEED;
+use imgal::flim::pileup_correction;
 use imgal::integration::midpoint;
 use imgal::prelude::*;
 use imgal::simulation::blob::gaussian_metaballs;
@@ -8,9 +11,15 @@ use imgal::simulation::decay::{
     gaussian_exponential_decay_1d, gaussian_exponential_decay_3d, ideal_exponential_decay_1d,
     ideal_exponential_decay_3d, irf_exponential_decay_1d, irf_exponential_decay_3d,
 };
-use imgal::simulation::instrument::gaussian_irf_1d;
+use imgal::simulation::instrument::{afterpulsing, dead_time_pileup, gaussian_irf_1d};
 use imgal::simulation::noise::{poisson_noise, poisson_noise_mut};
+use imgal::simulation::pattern::{
+    sinusoidal_pattern, sinusoidal_patterns, structured_illumination,
+};
+use imgal::simulation::psf::{depth_varying_psf, gibson_lanni_psf};
 use imgal::simulation::rng::Pcg;
+use imgal::simulation::tissue::{paint_regions, region_parameters, tissue_regions};
+use imgal::simulation::trajectories::{MotionModel, render_tracks, simulate_tracks};
 use imgal::statistics::sum;
 
 const TOLERANCE: f64 = 1e-10;
@@ -285,6 +294,50 @@ fn instrument_gaussian_irf_1d_expected_results() {
     assert!(approx_equal(irf_seq[82], 9.058e-7, None));
 }
 
+/// Tests that `afterpulsing` adds a uniform background with the expected
+/// total and that out of range probabilities return an error.
+#[test]
+fn instrument_afterpulsing_expected_results() -> Result<(), ImgalError> {
+    let decay = ideal_exponential_decay_1d(SAMPLES, PERIOD, &TAUS, &FRACTIONS, TOTAL_COUNTS, None)?;
+    let ap_par = afterpulsing(&decay, 0.01, None, THREADS)?;
+    let ap_seq = afterpulsing(&decay, 0.01, None, None)?;
+    let background = 0.01 * sum(&decay, None) / SAMPLES as f64;
+    assert!(approx_equal(
+        sum(&ap_par, None),
+        1.01 * sum(&decay, None),
+        Some(1e-8)
+    ));
+    assert!(approx_equal(ap_par[10] - decay[10], background, None));
+    assert!(approx_equal(ap_seq[200] - decay[200], background, None));
+    assert!(afterpulsing(&decay, 1.5, None, None).is_err());
+    assert!(afterpulsing(&decay, 0.01, Some(1), None).is_err());
+    Ok(())
+}
+
+/// Tests that `dead_time_pileup` under-counts late bins and is inverted by
+/// `pileup_correction`.
+#[test]
+fn instrument_dead_time_pileup_expected_results() -> Result<(), ImgalError> {
+    let decay = ideal_exponential_decay_1d(SAMPLES, PERIOD, &TAUS, &FRACTIONS, TOTAL_COUNTS, None)?;
+    let data = decay.broadcast((2, SAMPLES)).unwrap().to_owned();
+    let (rate, dead_time, acq) = (80e6, 100e-9, 1e-3);
+    let dist_par = dead_time_pileup(&data, rate, dead_time, acq, None, THREADS)?;
+    let dist_seq = dead_time_pileup(&data, rate, dead_time, acq, None, None)?;
+    assert!(sum(&dist_par, None) < sum(&data, None));
+    assert!(dist_par[[0, 200]] / decay[200] < dist_par[[0, 0]] / decay[0]);
+    let corr = pileup_correction(&dist_seq, rate, dead_time, acq, None, None)?;
+    for i in [0, 68, 200] {
+        assert!(approx_equal(corr[[1, i]], decay[i], Some(1e-6)));
+        assert!(approx_equal(dist_par[[0, i]], dist_seq[[1, i]], None));
+    }
+    let ideal = dead_time_pileup(&decay, 1e12, 0.0, 1.0, None, None)?;
+    assert!(approx_equal(ideal[68], decay[68], Some(1e-6)));
+    assert!(dead_time_pileup(&decay, -1.0, dead_time, acq, None, None).is_err());
+    assert!(dead_time_pileup(&decay, rate, -1.0, acq, None, None).is_err());
+    assert!(dead_time_pileup(&decay, rate, dead_time, 0.0, None, None).is_err());
+    Ok(())
+}
+
 /// Tests that `poisson_noise` returns the expected input arrays with Poisson
 /// noise applied. This test *only* tests the sequential output. The parallel
 /// outputs are *not* reproducible because each thread forks the internal PCG
@@ -340,6 +393,167 @@ fn noise_poisson_noise_mut_expected_results() -> Result<(), ImgalError> {
     Ok(())
 }
 
+/// Tests that `sinusoidal_pattern` creates stripes with the expected period,
+/// orientation, phase and modulation, and that `sinusoidal_patterns` orders
+/// the patterns by angle and then phase.
+#[test]
+fn pattern_sinusoidal_patterns_expected_results() -> Result<(), ImgalError> {
+    let pattern = sinusoidal_pattern((8, 12), 4.0, 0.0, 0.0, Some(0.5))?;
+    // stripes along the rows with a period of 4 columns
+    assert!(approx_equal(pattern[[0, 0]], 1.5, None));
+    assert!(approx_equal(pattern[[5, 1]], 1.0, None));
+    assert!(approx_equal(pattern[[3, 2]], 0.5, None));
+    assert!(approx_equal(pattern[[7, 4]], 1.5, None));
+    assert!(approx_equal(pattern.mean().unwrap(), 1.0, None));
+    let rotated = sinusoidal_pattern((8, 8), 4.0, FRAC_PI_2, FRAC_PI_2, None)?;
+    assert!(approx_equal(rotated[[0, 5]], 1.0, None));
+    assert!(approx_equal(rotated[[1, 5]], 0.0, None));
+    assert!(approx_equal(rotated[[3, 0]], 2.0, None));
+    let patterns = sinusoidal_patterns((16, 16), 4.0, 3, 3, Some(0.1), Some(0.8))?;
+    assert_eq!(patterns.dim(), (9, 16, 16));
+    let fourth = sinusoidal_pattern((16, 16), 4.0, 0.1 + PI / 3.0, 2.0 * PI / 3.0, Some(0.8))?;
+    patterns
+        .slice(s![4, .., ..])
+        .iter()
+        .zip(fourth.iter())
+        .for_each(|(&a, &b)| assert!(approx_equal(a, b, None)));
+    // the phases of each angle sum to a uniform illumination
+    let total = patterns.slice(s![3..6, .., ..]).sum_axis(Axis(0));
+    assert!(total.iter().all(|&v| approx_equal(v, 3.0, Some(1e-9))));
+    // a uniform sample images the pattern blurred by the PSF
+    let patterns = sinusoidal_patterns((16, 16), 4.0, 2, 3, None, Some(0.8))?;
+    let sample = Array2::<f64>::ones((16, 16));
+    let frames = structured_illumination(&sample, patterns.view(), 1.0, THREADS)?;
+    let frames_seq = structured_illumination(&sample, patterns.view(), 1.0, None)?;
+    assert_eq!(frames.dim(), patterns.dim());
+    assert_eq!(frames, frames_seq);
+    let contrast = (-2.0 * PI * PI / 16.0_f64).exp();
+    frames.iter().zip(patterns.iter()).for_each(|(&f, &p)| {
+        assert!(approx_equal(f, 1.0 + contrast * (p - 1.0), Some(1e-9)));
+    });
+    assert!(sinusoidal_pattern((4, 4), 0.0, 0.0, 0.0, None).is_err());
+    assert!(sinusoidal_pattern((4, 4), 4.0, 0.0, 0.0, Some(1.5)).is_err());
+    assert!(sinusoidal_patterns((4, 4), 4.0, 0, 3, None, None).is_err());
+    assert!(structured_illumination(&sample, patterns.slice(s![.., ..8, ..]), 1.0, None).is_err());
+    assert!(structured_illumination(&sample, patterns.view(), 0.0, None).is_err());
+    Ok(())
+}
+
+/// Tests that `gibson_lanni_psf` creates a normalized, symmetric Airy-like PSF
+/// with matched refractive indices, independent of the depth.
+#[test]
+fn psf_gibson_lanni_psf_expected_results() -> Result<(), ImgalError> {
+    let psf = gibson_lanni_psf(
+        (21, 17, 17),
+        (0.2, 0.05),
+        1.4,
+        0.5,
+        (1.518, 1.518),
+        0.0,
+        THREADS,
+    )?;
+    let psf_seq = gibson_lanni_psf(
+        (21, 17, 17),
+        (0.2, 0.05),
+        1.4,
+        0.5,
+        (1.518, 1.518),
+        0.0,
+        None,
+    )?;
+    let deep = gibson_lanni_psf(
+        (21, 17, 17),
+        (0.2, 0.05),
+        1.4,
+        0.5,
+        (1.518, 1.518),
+        10.0,
+        None,
+    )?;
+    assert_eq!(psf.dim(), (21, 17, 17));
+    assert_eq!(psf, psf_seq);
+    assert!(approx_equal(psf.sum(), 1.0, Some(1e-9)));
+    assert!(
+        psf.iter()
+            .zip(deep.iter())
+            .all(|(&a, &b)| approx_equal(a, b, None))
+    );
+    // the peak is at the center and the PSF is symmetric along each axis
+    let peak = psf[[10, 8, 8]];
+    assert!(psf.iter().all(|&v| v <= peak));
+    for z in 0..10 {
+        assert!(approx_equal(
+            psf[[z, 8, 8]],
+            psf[[20 - z, 8, 8]],
+            Some(1e-12)
+        ));
+    }
+    assert!(approx_equal(psf[[10, 8, 5]], psf[[10, 5, 8]], None));
+    // the first Airy minimum is at 0.61 × λ / NA ≈ 4.4 pixels
+    assert!(psf[[10, 8, 12]] < 0.01 * peak);
+    assert!(psf[[10, 8, 10]] > 0.3 * peak);
+    assert!(
+        gibson_lanni_psf(
+            (0, 17, 17),
+            (0.2, 0.05),
+            1.4,
+            0.5,
+            (1.518, 1.518),
+            0.0,
+            None
+        )
+        .is_err()
+    );
+    assert!(gibson_lanni_psf((9, 9, 9), (0.2, 0.0), 1.4, 0.5, (1.518, 1.518), 0.0, None).is_err());
+    assert!(gibson_lanni_psf((9, 9, 9), (0.2, 0.05), 1.6, 0.5, (1.518, 1.518), 0.0, None).is_err());
+    assert!(gibson_lanni_psf((9, 9, 9), (0.2, 0.05), 1.4, 0.5, (1.518, 1.33), -1.0, None).is_err());
+    Ok(())
+}
+
+/// Tests that `depth_varying_psf` shifts and weakens the PSF peak with depth
+/// under a refractive index mismatch.
+#[test]
+fn psf_depth_varying_psf_expected_results() -> Result<(), ImgalError> {
+    let depths = [0.0, 2.0, 4.0];
+    let stack = depth_varying_psf(
+        (41, 9, 9),
+        (0.1, 0.05),
+        1.2,
+        0.5,
+        (1.33, 1.45),
+        &depths,
+        THREADS,
+    )?;
+    assert_eq!(stack.dim(), (3, 41, 9, 9));
+    let single = gibson_lanni_psf((41, 9, 9), (0.1, 0.05), 1.2, 0.5, (1.33, 1.45), 2.0, None)?;
+    assert!(
+        stack
+            .index_axis(Axis(0), 1)
+            .iter()
+            .zip(single.iter())
+            .all(|(&a, &b)| approx_equal(a, b, Some(1e-12)))
+    );
+    let axial_peak = |d: usize| {
+        (0..41)
+            .max_by(|&a, &b| stack[[d, a, 4, 4]].total_cmp(&stack[[d, b, 4, 4]]))
+            .unwrap()
+    };
+    // the PSF at the coverslip is unaberrated, deeper PSFs are shifted
+    assert_eq!(axial_peak(0), 20);
+    assert!(axial_peak(1) != 20);
+    assert!(axial_peak(2).abs_diff(20) > axial_peak(1).abs_diff(20));
+    assert!(stack[[2, axial_peak(2), 4, 4]] < stack[[0, 20, 4, 4]]);
+    for d in 0..3 {
+        assert!(approx_equal(
+            stack.index_axis(Axis(0), d).sum(),
+            1.0,
+            Some(1e-9)
+        ));
+    }
+    assert!(depth_varying_psf((9, 9, 9), (0.1, 0.05), 1.2, 0.5, (1.33, 1.45), &[], None).is_err());
+    Ok(())
+}
+
 /// Tests that the `Pcg` returns the expected random f32 and u32 numbers.
 #[test]
 fn rng_pcg_expected_results() -> Result<(), ImgalError> {
@@ -371,3 +585,173 @@ fn rng_pcg_expected_results() -> Result<(), ImgalError> {
     assert_eq!(rand_vals_u32_range, rand_vals_u32_range_exp);
     Ok(())
 }
+
+/// Tests that `tissue_regions` creates a seeded label map with all regions,
+/// and that the warp field deforms the Voronoi boundaries.
+#[test]
+fn tissue_tissue_regions_expected_results() -> Result<(), ImgalError> {
+    let labels_par = tissue_regions(&[64, 48], 12, None, None, Some(7), THREADS)?;
+    let labels_seq = tissue_regions(&[64, 48], 12, None, None, Some(7), None)?;
+    let voronoi = tissue_regions(&[64, 48], 12, Some(0.0), None, Some(7), None)?;
+    assert_eq!(labels_par.shape(), [64, 48]);
+    assert_eq!(labels_par, labels_seq);
+    assert!(labels_par.iter().all(|&l| (1..=12).contains(&l)));
+    assert!(labels_par != voronoi);
+    // a 3D map with at least most regions present
+    let labels_3d = tissue_regions(&[16, 16, 16], 5, Some(2.0), Some(8.0), None, None)?;
+    let present = (1..=5u64)
+        .filter(|l| labels_3d.iter().any(|v| v == l))
+        .count();
+    assert!(present >= 4);
+    // most pixels keep their Voronoi label with a small warp
+    let same = labels_par
+        .iter()
+        .zip(voronoi.iter())
+        .filter(|(a, b)| a == b)
+        .count();
+    assert!(same as f64 > 0.5 * labels_par.len() as f64);
+    assert!(tissue_regions(&[], 3, None, None, None, None).is_err());
+    assert!(tissue_regions(&[8, 8], 0, None, None, None, None).is_err());
+    assert!(tissue_regions(&[8, 8], 2, Some(-1.0), None, None, None).is_err());
+    Ok(())
+}
+
+/// Tests that `region_parameters` and `paint_regions` create region-wise
+/// parameter maps.
+#[test]
+fn tissue_paint_regions_expected_results() -> Result<(), ImgalError> {
+    let params = region_parameters(4, (100.0, 200.0), (0.5, 4.0), None)?;
+    assert_eq!(params.dim(), (4, 3));
+    params.rows().into_iter().enumerate().for_each(|(i, row)| {
+        assert_eq!(row[0], (i + 1) as f64);
+        assert!((100.0..=200.0).contains(&row[1]));
+        assert!((0.5..=4.0).contains(&row[2]));
+    });
+    let labels = arr2(&[[0u64, 1, 2], [3, 4, 4]]);
+    let taus = params.column(2).to_owned();
+    let tau_map = paint_regions(&labels, &taus, -1.0, THREADS)?;
+    assert_eq!(tau_map.shape(), [2, 3]);
+    assert_eq!(tau_map[[0, 0]], -1.0);
+    assert_eq!(tau_map[[0, 2]], params[[1, 2]]);
+    assert_eq!(tau_map[[1, 2]], params[[3, 2]]);
+    assert!(paint_regions(&labels, &taus.slice(s![..3]), 0.0, None).is_err());
+    assert!(region_parameters(2, (2.0, 1.0), (0.5, 4.0), None).is_err());
+    assert!(region_parameters(2, (1.0, 2.0), (-0.5, 4.0), None).is_err());
+    Ok(())
+}
+
+/// Tests that `simulate_tracks` produces track tables with the expected layout
+/// and the expected Brownian, confined and directed motion statistics.
+#[test]
+fn trajectories_simulate_tracks_expected_results() -> Result<(), ImgalError> {
+    let shape = [64, 64];
+    let brownian = simulate_tracks(
+        500,
+        11,
+        &shape,
+        MotionModel::Brownian { diffusion: 0.5 },
+        None,
+    )?;
+    assert_eq!(brownian.dim(), (500 * 11, 4));
+    assert_eq!(brownian[[11, 0]], 1.0);
+    assert_eq!(brownian[[12, 1]], 1.0);
+    // the 2D mean squared displacement after "t" frames is 4 × D × t
+    let msd: f64 = (0..500)
+        .map(|t| {
+            let a = brownian.row(t * 11);
+            let b = brownian.row(t * 11 + 10);
+            (b[2] - a[2]).powi(2) + (b[3] - a[3]).powi(2)
+        })
+        .sum::<f64>()
+        / 500.0;
+    assert!(approx_equal(msd, 20.0, Some(2.0)));
+    let confined = simulate_tracks(
+        20,
+        50,
+        &shape,
+        MotionModel::Confined {
+            diffusion: 1.0,
+            radius: 2.0,
+        },
+        Some(3),
+    )?;
+    (0..20).for_each(|t| {
+        let o = confined.row(t * 50);
+        (0..50).for_each(|f| {
+            let p = confined.row(t * 50 + f);
+            let dist = ((p[2] - o[2]).powi(2) + (p[3] - o[3]).powi(2)).sqrt();
+            assert!(dist <= 2.0);
+        });
+    });
+    let directed = simulate_tracks(
+        10,
+        21,
+        &shape,
+        MotionModel::Directed {
+            diffusion: 0.0,
+            velocity: 1.5,
+        },
+        None,
+    )?;
+    let (a, b) = (directed.row(0), directed.row(20));
+    let dist = ((b[2] - a[2]).powi(2) + (b[3] - a[3]).powi(2)).sqrt();
+    assert!(approx_equal(dist, 30.0, Some(1e-9)));
+    assert!(simulate_tracks(1, 1, &[], MotionModel::Brownian { diffusion: 1.0 }, None).is_err());
+    assert!(
+        simulate_tracks(
+            1,
+            1,
+            &shape,
+            MotionModel::Brownian { diffusion: -1.0 },
+            None
+        )
+        .is_err()
+    );
+    Ok(())
+}
+
+/// Tests that `render_tracks` renders Gaussian spots at the track positions.
+#[test]
+fn trajectories_render_tracks_expected_results() -> Result<(), ImgalError> {
+    let tracks = arr2(&[
+        [0.0, 0.0, 10.0, 12.0],
+        [0.0, 1.0, 11.0, 12.0],
+        [1.0, 1.0, 30.0, 5.0],
+        [1.0, 5.0, 30.0, 5.0],
+    ]);
+    let stack_par = render_tracks(
+        &tracks,
+        2,
+        &[32, 32],
+        1.5,
+        100.0,
+        10.0,
+        false,
+        None,
+        THREADS,
+    )?;
+    let stack_seq = render_tracks(&tracks, 2, &[32, 32], 1.5, 100.0, 10.0, false, None, None)?;
+    let noisy = render_tracks(&tracks, 2, &[32, 32], 1.5, 100.0, 10.0, true, None, None)?;
+    assert_eq!(stack_par.shape(), [2, 32, 32]);
+    assert_eq!(stack_par, stack_seq);
+    assert!(approx_equal(stack_par[[0, 10, 12]], 110.0, None));
+    assert!(approx_equal(stack_par[[1, 11, 12]], 110.0, None));
+    assert!(approx_equal(stack_par[[1, 30, 5]], 110.0, None));
+    assert!(approx_equal(stack_par[[0, 30, 5]], 10.0, None));
+    assert!(noisy.iter().all(|v| v.fract() == 0.0));
+    assert!(
+        render_tracks(
+            &tracks,
+            2,
+            &[32, 32, 32],
+            1.5,
+            100.0,
+            10.0,
+            false,
+            None,
+            None
+        )
+        .is_err()
+    );
+    Ok(())
+}
@@ -1,10 +1,16 @@
-use ndarray::arr2;
+use std::collections::HashMap;
+
+use ndarray::{Array2, Array3, ArrayView1, Axis, arr1, arr2};
 
 use imgal::prelude::*;
 use imgal::simulation::blob::gaussian_metaballs;
 use imgal::statistics::{
-    effective_sample_size, kahan_sum, linear_percentile, max, min, min_max, sum,
-    weighted_kendall_tau_b, weighted_merge_sort_mut,
+    Alternative, SpatialWeights, TDigest, bootstrap_ci, channel_summary, cumprod, cumsum,
+    effective_sample_size, gearys_c, gearys_c_labels, kahan_sum, linear_percentile,
+    linear_percentiles, local_morans_i, local_morans_i_labels, mad, mann_whitney_u, max, mean,
+    median, min, min_max, morans_i, morans_i_labels, pearson, reduce_axis, sample_indices,
+    sample_pixels, sample_positions, spearman_correlation, std, sum, tdigest, trimmed_mean,
+    variance, weighted_kendall_tau_b, weighted_merge_sort_mut, welch_t_test, winsorized_mean,
 };
 
 const TOLERANCE: f64 = 1e-10;
@@ -20,6 +26,130 @@ fn approx_equal(a: f64, b: f64, tol: Option<f64>) -> bool {
     (a - b).abs() < tol.unwrap_or(TOLERANCE)
 }
 
+/// Tests that `bootstrap_ci` returns a deterministic confidence interval of
+/// the mean that contains the sample mean.
+#[test]
+fn statistics_bootstrap_ci_expected_results() -> Result<(), ImgalError> {
+    let data: Vec<f64> = (0..100).map(|v| v as f64).collect();
+    let stat = |x: ArrayView1<f64>| x.mean().unwrap();
+    let ci_par = bootstrap_ci(&data, stat, Some(500), None, None, THREADS)?;
+    let ci_seq = bootstrap_ci(&data, stat, Some(500), None, None, None)?;
+    let ci_90 = bootstrap_ci(&data, stat, Some(500), Some(0.9), None, None)?;
+    assert!(approx_equal(ci_par.0, ci_seq.0, None));
+    assert!(approx_equal(ci_par.1, ci_seq.1, None));
+    assert!(ci_par.0 < 49.5 && ci_par.1 > 49.5);
+    assert!(ci_par.0 > 40.0 && ci_par.1 < 59.0);
+    assert!(ci_90.0 >= ci_seq.0 && ci_90.1 <= ci_seq.1);
+    assert!(bootstrap_ci(&data, stat, Some(0), None, None, None).is_err());
+    assert!(bootstrap_ci(&data, stat, None, Some(1.0), None, None).is_err());
+    assert!(bootstrap_ci(&Vec::<f64>::new(), stat, None, None, None, None).is_err());
+    Ok(())
+}
+
+/// Tests that `channel_summary` matches the per-channel statistics and the
+/// pairwise `pearson` correlations.
+#[test]
+fn statistics_channel_summary_expected_results() -> Result<(), ImgalError> {
+    let data = Array3::from_shape_fn((3, 8, 10), |(ch, r, c)| {
+        let v = ((r * 10 + c) as f64 * 0.37).sin();
+        match ch {
+            0 => 1000.0 + v,
+            1 => 5.0 - 2.0 * v + (c as f64) * 0.1,
+            _ => 3.0,
+        }
+    });
+    let summary_par = channel_summary(&data, Some(&[0.0, 50.0, 100.0]), None, THREADS)?;
+    let summary_seq = channel_summary(&data, Some(&[0.0, 50.0, 100.0]), None, None)?;
+    for k in 0..3 {
+        let ch = data.index_axis(Axis(0), k);
+        let flat = ch.iter().cloned().collect::<Vec<f64>>();
+        let mean = flat.iter().sum::<f64>() / 80.0;
+        let var = flat.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / 80.0;
+        assert!(approx_equal(summary_par.mean[k], mean, Some(1e-9)));
+        assert!(approx_equal(summary_par.std[k], var.sqrt(), Some(1e-9)));
+        assert!(approx_equal(
+            summary_par.mean[k],
+            summary_seq.mean[k],
+            Some(1e-9)
+        ));
+        assert_eq!(summary_par.percentiles[[k, 0]], min(&ch, None)?);
+        assert_eq!(summary_par.percentiles[[k, 2]], max(&ch, None)?);
+        let median = linear_percentile(&ch, 50.0, None, None, None)?[0];
+        assert!(approx_equal(summary_par.percentiles[[k, 1]], median, None));
+    }
+    let r = pearson(
+        data.index_axis(Axis(0), 0)
+            .iter()
+            .cloned()
+            .collect::<Vec<f64>>()
+            .as_slice(),
+        data.index_axis(Axis(0), 1)
+            .iter()
+            .cloned()
+            .collect::<Vec<f64>>()
+            .as_slice(),
+        None,
+    )?;
+    assert!(approx_equal(summary_par.pearson[[0, 1]], r, Some(1e-9)));
+    assert!(approx_equal(summary_par.pearson[[1, 0]], r, Some(1e-9)));
+    assert!(approx_equal(summary_par.pearson[[0, 0]], 1.0, Some(1e-9)));
+    assert!(summary_par.pearson[[0, 2]].is_nan());
+    // a channel last layout gives the same results
+    let mut data_last = data.clone();
+    data_last.swap_axes(0, 2);
+    let summary_last = channel_summary(&data_last, None, Some(2), THREADS)?;
+    assert_eq!(summary_last.percentiles.dim(), (3, 3));
+    assert!(approx_equal(
+        summary_last.mean[1],
+        summary_par.mean[1],
+        Some(1e-9)
+    ));
+    // a NaN in a channel propagates to its mean and std
+    let mut data_nan = data.clone();
+    data_nan[[1, 3, 4]] = f64::NAN;
+    let summary_nan = channel_summary(&data_nan, None, None, THREADS)?;
+    assert!(summary_nan.mean[1].is_nan());
+    assert!(summary_nan.std[1].is_nan());
+    assert!(approx_equal(
+        summary_nan.std[0],
+        summary_par.std[0],
+        Some(1e-9)
+    ));
+    assert!(channel_summary(&data, None, Some(3), None).is_err());
+    assert!(channel_summary(&data, Some(&[101.0]), None, None).is_err());
+    Ok(())
+}
+
+/// Tests that `cumsum` and `cumprod` return the expected running sums and
+/// products along an axis.
+#[test]
+fn statistics_cumsum_cumprod_expected_results() -> Result<(), ImgalError> {
+    let data = arr2(&[[1_u8, 2, 3], [4, 5, 6]]);
+    assert_eq!(
+        cumsum(&data, None, THREADS)?,
+        arr2(&[[1.0, 3.0, 6.0], [4.0, 9.0, 15.0]])
+    );
+    assert_eq!(
+        cumsum(&data, Some(0), None)?,
+        arr2(&[[1.0, 2.0, 3.0], [5.0, 7.0, 9.0]])
+    );
+    // the integral image, sums are accumulated in f64 and can not overflow
+    let integral = cumsum(&cumsum(&data, Some(0), THREADS)?, Some(1), THREADS)?;
+    assert_eq!(integral, arr2(&[[1.0, 3.0, 6.0], [5.0, 12.0, 21.0]]));
+    assert_eq!(cumsum(&[200_u8, 100], None, None)?.to_vec(), [200.0, 300.0]);
+    assert_eq!(
+        cumprod(&data, None, THREADS)?,
+        arr2(&[[1.0, 2.0, 6.0], [4.0, 20.0, 120.0]])
+    );
+    assert_eq!(
+        cumprod(&data, Some(0), None)?,
+        arr2(&[[1.0, 2.0, 3.0], [4.0, 10.0, 18.0]])
+    );
+    assert!(cumsum(&data, Some(2), None).is_err());
+    assert!(cumprod(&data, Some(2), None).is_err());
+    Ok(())
+}
+
 /// Tests that `effective_sample_size` returns the expected results for data
 /// that is dominated by a single weight, partially zero, uniform and all zeros.
 #[test]
@@ -38,15 +168,19 @@ fn statistics_effective_sample_size_expected_results() {
 /// integer and floating point data.
 #[test]
 fn statistics_kahan_sum_expected_results() -> Result<(), ImgalError> {
-    let i32_data = vec![2, 5, 10, 23];
-    let f64_data = vec![1.0, 10.5, 3.25, 37.11];
+    let i32_data = vec![2_i32, 5, 10, 23];
+    let f64_data = vec![1.0_f64, 10.5, 3.25, 37.11];
     let f64_error_data = vec![0.1_f64; 1000];
     let mut large_small_data = vec![1e-7_f64; 1_000_000];
     large_small_data.insert(0, 1_000_000.0);
-    assert_eq!(kahan_sum(&i32_data)?, 40);
-    assert_eq!(kahan_sum(&f64_data)?, 51.86);
-    assert_eq!(kahan_sum(&f64_error_data)?, 100.0);
-    assert_eq!(kahan_sum(&large_small_data)?, 1_000_000.1);
+    assert_eq!(kahan_sum(&i32_data, None)?, 40);
+    assert_eq!(kahan_sum(&f64_data, None)?, 51.86);
+    assert_eq!(kahan_sum(&f64_error_data, None)?, 100.0);
+    assert_eq!(kahan_sum(&f64_error_data, THREADS)?, 100.0);
+    assert_eq!(kahan_sum(&large_small_data, None)?, 1_000_000.1);
+    assert_eq!(kahan_sum(&large_small_data, THREADS)?, 1_000_000.1);
+    // values larger than the running sum are compensated (Neumaier)
+    assert_eq!(kahan_sum(&[1.0_f64, 1e100, 1.0, -1e100], None)?, 2.0);
     Ok(())
 }
 
@@ -80,6 +214,141 @@ fn statistics_linear_percentile_expected_results() -> Result<(), ImgalError> {
     Ok(())
 }
 
+/// Tests that `linear_percentiles` matches `linear_percentile` for each
+/// percentile with flat and axis compute.
+#[test]
+fn statistics_linear_percentiles_expected_results() -> Result<(), ImgalError> {
+    let data = gaussian_metaballs(
+        &arr2(&CENTER),
+        &RADIUS,
+        &INTENSITY,
+        &FALLOFF,
+        BACKGROUND,
+        &SHAPE,
+        None,
+    )?;
+    let pers = [99.8, 5.0, 50.0, 0.0, 100.0];
+    let axis_par = linear_percentiles(&data, &pers, Some(0), None, THREADS)?;
+    let axis_seq = linear_percentiles(&data, &pers, Some(0), None, None)?;
+    let flat = linear_percentiles(&data, &pers, None, None, None)?;
+    assert_eq!(axis_par.shape(), [50, 5]);
+    assert_eq!(flat.shape(), [5,]);
+    for (k, &p) in pers.iter().enumerate() {
+        let single_axis = linear_percentile(&data, p, Some(0), None, None)?;
+        let single_flat = linear_percentile(&data, p, None, None, None)?[0];
+        assert!(approx_equal(flat[k], single_flat, None));
+        for i in [0, 17, 25, 49] {
+            assert!(approx_equal(axis_par[[i, k]], single_axis[i], None));
+            assert!(approx_equal(axis_seq[[i, k]], single_axis[i], None));
+        }
+    }
+    assert!(linear_percentiles(&data, &[], None, None, None).is_err());
+    assert!(linear_percentiles(&data, &pers, Some(2), None, None).is_err());
+    Ok(())
+}
+
+/// Tests that `local_morans_i` returns the expected local values, that they
+/// sum to the global Moran's I and that clusters are significant.
+#[test]
+fn statistics_local_morans_i_expected_results() -> Result<(), ImgalError> {
+    // the local I of a checkerboard is minus the number of neighbors
+    let checker = Array2::from_shape_fn((4, 4), |(r, c)| ((r + c) % 2) as f64);
+    let lisa = local_morans_i(&checker, None, None, Some(0), None, THREADS)?;
+    assert!(approx_equal(lisa.local_i[[0, 0]], -2.0, None));
+    assert!(approx_equal(lisa.local_i[[0, 1]], -3.0, None));
+    assert!(approx_equal(lisa.local_i[[1, 1]], -4.0, None));
+    assert!(lisa.p_value.iter().all(|p| p.is_nan()));
+    // the local values sum to the global Moran's I times the weight sum
+    let gradient = Array2::from_shape_fn((6, 6), |(r, c)| (r * c) as f64);
+    let lisa = local_morans_i(
+        &gradient,
+        Some(SpatialWeights::Queen),
+        None,
+        Some(0),
+        None,
+        None,
+    )?;
+    let w_sum = (2 * 6 * 5 + 2 * 5 * 5) as f64 * 2.0;
+    assert!(approx_equal(
+        lisa.local_i.sum() / w_sum,
+        morans_i(&gradient, Some(SpatialWeights::Queen), None, None)?,
+        None
+    ));
+    // a bright block is a significant cluster, the background is not
+    let block = Array2::from_shape_fn((20, 20), |(r, c)| {
+        if (7..13).contains(&r) && (7..13).contains(&c) {
+            10.0
+        } else {
+            0.0
+        }
+    });
+    let par = local_morans_i(&block, None, None, None, Some(7), THREADS)?;
+    let seq = local_morans_i(&block, None, None, None, Some(7), None)?;
+    assert_eq!(par, seq);
+    assert!(par.local_i[[9, 9]] > 0.0);
+    assert!(par.p_value[[9, 9]] <= 0.01);
+    assert!(par.p_value[[0, 0]] > 0.05);
+    // pixels outside the mask are NaN
+    let mask = Array2::from_shape_fn((20, 20), |(r, _)| r < 15);
+    let masked = local_morans_i(&block, None, Some(mask.view()), Some(9), None, THREADS)?;
+    assert!(masked.local_i[[17, 3]].is_nan());
+    assert!(!masked.local_i[[3, 3]].is_nan());
+    // objects in a row with values 1, 2 and 4 sum to the global Moran's I
+    let labels = arr2(&[[1u64, 2, 3, 0, 5], [1, 2, 3, 0, 5]]);
+    let values = HashMap::from([(1, 1.0), (2, 2.0), (3, 4.0)]);
+    let lisa = local_morans_i_labels(&labels, &values, None, Some(19), None, THREADS)?;
+    assert_eq!(lisa.len(), 3);
+    let local_sum: f64 = lisa.values().map(|v| v.0).sum();
+    assert!(approx_equal(
+        local_sum / 4.0,
+        morans_i_labels(&labels, &values, None, THREADS)?,
+        None
+    ));
+    Ok(())
+}
+
+/// Tests that `mann_whitney_u` returns the expected statistic and p-values
+/// with and without ties, and for masked samples of one image.
+#[test]
+fn statistics_mann_whitney_u_expected_results() -> Result<(), ImgalError> {
+    let a = [1.0, 2.0, 3.0, 4.0, 5.0];
+    let b = [6.0, 7.0, 8.0, 9.0, 10.0];
+    let res = mann_whitney_u(&a, &b, None, None, None, THREADS)?;
+    assert_eq!(res.statistic, 0.0);
+    assert!(approx_equal(res.p_value, 0.012185780355344818, None));
+    let less = mann_whitney_u(&a, &b, None, None, Some(Alternative::Less), THREADS)?;
+    assert!(approx_equal(less.p_value, 0.006092890177672409, None));
+    // ties with the tie corrected variance
+    let a = [1.0, 2.0, 2.0, 3.0];
+    let b = [2.0, 3.0, 4.0, 5.0];
+    let res = mann_whitney_u(&a, &b, None, None, None, None)?;
+    assert_eq!(res.statistic, 2.5);
+    assert!(approx_equal(res.p_value, 0.13665824773814753, None));
+    // two masks of the same image give the same result
+    let img = arr2(&[[1.0, 2.0, 2.0, 3.0], [2.0, 3.0, 4.0, 5.0]]);
+    let mask_a = arr2(&[[true; 4], [false; 4]]);
+    let mask_b = mask_a.mapv(|v| !v);
+    let masked = mann_whitney_u(
+        &img,
+        &img,
+        Some(mask_a.view()),
+        Some(mask_b.view()),
+        None,
+        THREADS,
+    )?;
+    assert_eq!(masked, res);
+    // all values tied and empty samples
+    let flat = [1.0; 4];
+    assert!(
+        mann_whitney_u(&flat, &flat, None, None, None, THREADS)?
+            .p_value
+            .is_nan()
+    );
+    let empty = arr2(&[[false; 4], [false; 4]]);
+    assert!(mann_whitney_u(&img, &img, Some(empty.view()), None, None, THREADS).is_err());
+    Ok(())
+}
+
 /// Tests that `max` returns the maximum value from integer, floating point,
 /// string arrays and images.
 #[test]
@@ -104,6 +373,137 @@ fn statistics_max_expected_results() -> Result<(), ImgalError> {
     Ok(())
 }
 
+/// Tests that `mean`, `variance` and `std` return the expected results for
+/// flat and axis computations, and are stable for large offsets.
+#[test]
+fn statistics_mean_variance_std_expected_results() -> Result<(), ImgalError> {
+    let data = Array3::from_shape_fn((4, 5, 6), |(p, r, c)| (p * 30 + r * 6 + c) as f64);
+    // the values 0 to 119 have a mean of 59.5 and a variance of (120² - 1) / 12
+    let m_par = mean(&data, None, THREADS)?;
+    let m_seq = mean(&data, None, None)?;
+    assert_eq!(m_par.shape(), &[1]);
+    assert!(approx_equal(m_par[0], 59.5, None));
+    assert!(approx_equal(m_seq[0], 59.5, None));
+    let var_pop = (120.0_f64.powi(2) - 1.0) / 12.0;
+    assert!(approx_equal(
+        variance(&data, None, None, THREADS)?[0],
+        var_pop,
+        Some(1e-9)
+    ));
+    assert!(approx_equal(
+        variance(&data, None, Some(1), None)?[0],
+        var_pop * 120.0 / 119.0,
+        Some(1e-9)
+    ));
+    assert!(approx_equal(
+        std(&data, None, None, THREADS)?[0],
+        var_pop.sqrt(),
+        Some(1e-9)
+    ));
+    // along the plane axis the values step by 30
+    let m_ax = mean(&data, Some(0), THREADS)?;
+    let v_ax = variance(&data, Some(0), None, THREADS)?;
+    assert_eq!(m_ax.shape(), &[5, 6]);
+    assert!(approx_equal(m_ax[[0, 0]], 45.0, None));
+    assert!(approx_equal(m_ax[[4, 5]], 45.0 + 29.0, None));
+    v_ax.iter()
+        .for_each(|&v| assert!(approx_equal(v, 1125.0, Some(1e-9))));
+    // a large offset does not lose precision
+    let offset = data.mapv(|v| v + 1e9);
+    assert!(approx_equal(
+        variance(&offset, None, None, THREADS)?[0],
+        var_pop,
+        Some(1e-5)
+    ));
+    assert!(mean(&data, Some(3), None).is_err());
+    assert!(variance(&data, Some(0), Some(4), None).is_err());
+    assert!(mean(&Array3::<f64>::zeros((0, 2, 2)), None, None).is_err());
+    Ok(())
+}
+
+/// Tests that half-precision arrays are accepted and converted on the fly, and
+/// that sums and percentiles are accumulated in `f64`, past the range and
+/// precision of `f16` and `bf16`.
+#[cfg(feature = "half")]
+#[test]
+fn statistics_half_precision_expected_results() -> Result<(), ImgalError> {
+    use half::{bf16, f16};
+
+    let data_f16 = ndarray::Array1::from_shape_fn(101, |i| f16::from_f64(i as f64 * 0.5));
+    let data_bf16 = data_f16.mapv(|v| bf16::from_f32(v.to_f32()));
+    assert!(approx_equal(mean(&data_f16, None, THREADS)?[0], 25.0, None));
+    assert!(approx_equal(
+        mean(&data_bf16, None, None)?[0],
+        25.0,
+        Some(0.1)
+    ));
+    assert!(approx_equal(
+        linear_percentile(&data_f16, 50.0, None, None, None)?[0],
+        25.0,
+        None
+    ));
+    assert_eq!(max(&data_f16, THREADS)?, f16::from_f64(50.0));
+    // the f16 sum exceeds f16::MAX (65504) and the bf16 sum stops growing at 256
+    // when accumulated in the input precision
+    let f16_data = Array2::from_shape_fn((100, 1000), |(_, c)| f16::from_f64(c as f64));
+    let bf16_data = Array2::from_shape_fn((100, 1000), |(_, c)| bf16::from_f64((c % 200) as f64));
+    let bf16_ones = Array2::from_elem((100, 1000), bf16::ONE);
+    assert_eq!(sum(&f16_data, None), 49_950_000.0);
+    assert_eq!(sum(&f16_data, THREADS), 49_950_000.0);
+    assert_eq!(kahan_sum(&f16_data, None)?, 49_950_000.0);
+    assert_eq!(kahan_sum(&f16_data, THREADS)?, 49_950_000.0);
+    assert_eq!(sum(&bf16_ones, None), 100_000.0);
+    assert_eq!(sum(&bf16_ones, THREADS), 100_000.0);
+    assert_eq!(kahan_sum(&bf16_ones, None)?, 100_000.0);
+    assert_eq!(sum(&bf16_data, THREADS), 9_950_000.0);
+    // the 100,000 values interpolate between the ranks 49,999 and 50,000
+    assert_eq!(
+        linear_percentile(&f16_data, 50.0, None, None, THREADS)?[0],
+        499.5
+    );
+    assert_eq!(
+        linear_percentile(&bf16_data, 50.0, None, None, None)?[0],
+        99.5
+    );
+    let f16_pers = linear_percentiles(&f16_data, &[0.0, 50.0, 100.0], None, None, None)?;
+    assert_eq!(f16_pers.as_slice().unwrap(), &[0.0, 499.5, 999.0]);
+    let bf16_pers = linear_percentiles(&bf16_data, &[50.0, 100.0], Some(1), None, THREADS)?;
+    assert_eq!(bf16_pers.shape(), &[100, 2]);
+    assert!(
+        bf16_pers
+            .rows()
+            .into_iter()
+            .all(|r| r[0] == 99.5 && r[1] == 199.0)
+    );
+    Ok(())
+}
+
+/// Tests that `median` and `mad` return the expected results for flat and
+/// axis computations with odd and even lengths.
+#[test]
+fn statistics_median_mad_expected_results() -> Result<(), ImgalError> {
+    let odd = arr2(&[[7.0, 1.0, 3.0], [100.0, 2.0, 5.0], [4.0, 6.0, 8.0]]);
+    // the values 1 to 8 and the outlier 100
+    assert_eq!(median(&odd, None, THREADS)?[0], 5.0);
+    assert_eq!(mad(&odd, None, None, THREADS)?[0], 2.0);
+    assert!(approx_equal(
+        mad(&odd, None, Some(1.4826), None)?[0],
+        2.9652,
+        None
+    ));
+    let even = arr2(&[[4.0, 1.0], [3.0, 2.0], [10.0, 6.0]]);
+    assert_eq!(median(&even, None, None)?[0], 3.5);
+    let med_ax0 = median(&even, Some(0), THREADS)?;
+    let med_ax1 = median(&even, Some(1), None)?;
+    assert_eq!(med_ax0.as_slice().unwrap(), &[4.0, 2.0]);
+    assert_eq!(med_ax1.as_slice().unwrap(), &[2.5, 2.5, 8.0]);
+    let mad_ax1 = mad(&even, Some(1), None, THREADS)?;
+    assert_eq!(mad_ax1.as_slice().unwrap(), &[1.5, 0.5, 2.0]);
+    assert!(median(&odd, Some(2), None).is_err());
+    assert!(mad(&Array3::<f64>::zeros((0, 2, 2)), None, None, None).is_err());
+    Ok(())
+}
+
 /// Tests that `min` returns the minimum value from integer, floating point,
 /// string arrays and images.
 #[test]
@@ -162,6 +562,180 @@ fn statistics_min_max_expected_results() -> Result<(), ImgalError> {
     Ok(())
 }
 
+/// Tests that Moran's I and Geary's C detect dispersed and clustered lattices
+/// and object graphs.
+#[test]
+fn statistics_morans_i_gearys_c_expected_results() -> Result<(), ImgalError> {
+    // a checkerboard is perfectly dispersed with rook weights
+    let checker = Array2::from_shape_fn((4, 4), |(r, c)| ((r + c) % 2) as f64);
+    assert!(approx_equal(
+        morans_i(&checker, None, None, THREADS)?,
+        -1.0,
+        None
+    ));
+    assert!(approx_equal(
+        gearys_c(&checker, None, None, THREADS)?,
+        1.875,
+        None
+    ));
+    // a smooth gradient is clustered
+    let gradient = Array2::from_shape_fn((8, 8), |(r, c)| (r + c) as f64);
+    for w in [
+        SpatialWeights::Rook,
+        SpatialWeights::Queen,
+        SpatialWeights::InverseDistance,
+    ] {
+        let i = morans_i(&gradient, Some(w), None, THREADS)?;
+        let c = gearys_c(&gradient, Some(w), None, THREADS)?;
+        assert!(i > 0.5);
+        assert!(c < 0.5);
+        assert!(approx_equal(
+            i,
+            morans_i(&gradient, Some(w), None, None)?,
+            None
+        ));
+    }
+    // a mask restricting the checkerboard to one row keeps the dispersion
+    let mask = Array2::from_shape_fn((4, 4), |(r, _)| r == 0);
+    assert!(approx_equal(
+        morans_i(&checker, None, Some(mask.view()), THREADS)?,
+        -1.0,
+        None
+    ));
+    // constant images are undefined
+    let flat = Array2::<f64>::ones((3, 3));
+    assert!(morans_i(&flat, None, None, THREADS)?.is_nan());
+    // objects in a row with values 1, 2 and 4, label 5 has no value
+    let labels = arr2(&[[1u64, 2, 3, 0, 5], [1, 2, 3, 0, 5]]);
+    let values = HashMap::from([(1, 1.0), (2, 2.0), (3, 4.0)]);
+    for w in [SpatialWeights::Rook, SpatialWeights::InverseDistance] {
+        let i = morans_i_labels(&labels, &values, Some(w), THREADS)?;
+        let c = gearys_c_labels(&labels, &values, Some(w), THREADS)?;
+        assert!(approx_equal(i, -1.0 / 28.0, None));
+        assert!(approx_equal(c, 15.0 / 28.0, None));
+    }
+    // mismatched mask shape
+    let bad_mask = Array2::from_elem((3, 4), true);
+    assert!(morans_i(&checker, None, Some(bad_mask.view()), THREADS).is_err());
+    Ok(())
+}
+
+/// Tests that `reduce_axis` applies built-in and custom reducers along each
+/// axis.
+#[test]
+fn statistics_reduce_axis_expected_results() -> Result<(), ImgalError> {
+    let data = Array3::from_shape_fn((3, 4, 5), |(i, j, k)| (i * 20 + j * 5 + k) as f64);
+    for ax in 0..3 {
+        let red_mean = reduce_axis(&data, ax, |ln| ln.mean().unwrap(), THREADS)?;
+        let red_seq = reduce_axis(&data, ax, |ln| ln.mean().unwrap(), None)?;
+        assert_eq!(red_mean, mean(&data, Some(ax), None)?);
+        assert_eq!(red_mean, red_seq);
+    }
+    let range = reduce_axis(
+        &data,
+        2,
+        |ln| {
+            let (lo, hi) = min_max(ln, None).unwrap();
+            (hi - lo) as usize
+        },
+        THREADS,
+    )?;
+    assert_eq!(range.shape(), &[3, 4]);
+    assert!(range.iter().all(|&r| r == 4));
+    assert!(reduce_axis(&data, 3, |ln| ln.sum(), None).is_err());
+    Ok(())
+}
+
+/// Tests that the sampling functions return distinct, deterministic samples
+/// from the (masked) population.
+#[test]
+fn statistics_sample_expected_results() -> Result<(), ImgalError> {
+    let indices = sample_indices(100, 10, Some(3))?;
+    assert_eq!(indices.len(), 10);
+    assert!(indices.windows(2).all(|w| w[0] < w[1]));
+    assert!(indices.iter().all(|&i| i < 100));
+    assert_eq!(indices, sample_indices(100, 10, Some(3))?);
+    assert_ne!(indices, sample_indices(100, 10, Some(4))?);
+    assert_eq!(sample_indices(5, 5, None)?, vec![0, 1, 2, 3, 4]);
+    assert!(sample_indices(5, 0, None)?.is_empty());
+    assert!(sample_indices(5, 6, None).is_err());
+    // positions are sampled from the true pixels of the mask
+    let mask = Array3::from_shape_fn((4, 5, 6), |(p, r, c)| (p + r + c) % 3 == 0);
+    let n_true = mask.iter().filter(|v| **v).count();
+    let cloud = sample_positions(&mask, 12, None)?;
+    assert_eq!(cloud.dim(), (12, 3));
+    cloud
+        .rows()
+        .into_iter()
+        .for_each(|p| assert!(mask[[p[0], p[1], p[2]]]));
+    assert_eq!(sample_positions(&mask, n_true, None)?.dim(), (n_true, 3));
+    assert!(sample_positions(&mask, n_true + 1, None).is_err());
+    // pixel values are sampled from inside the mask
+    let data = Array3::from_shape_fn((4, 5, 6), |(p, r, c)| (p * 30 + r * 6 + c) as u16);
+    let vals = sample_pixels(&data, 12, Some(mask.view()), None)?;
+    assert_eq!(vals.len(), 12);
+    vals.iter().for_each(|&v| {
+        let v = v as usize;
+        assert!(mask[[v / 30, v / 6 % 5, v % 6]]);
+    });
+    let all = sample_pixels(&data, data.len(), None, Some(1))?;
+    assert_eq!(all.to_vec(), data.iter().copied().collect::<Vec<u16>>());
+    Ok(())
+}
+
+/// Tests that `spearman_correlation` is the Pearson correlation of the average
+/// ranks, invariant to monotonic transforms.
+#[test]
+fn statistics_spearman_correlation_expected_results() -> Result<(), ImgalError> {
+    let a = arr1(&[1.0, 2.0, 3.0, 4.0, 5.0]);
+    let b = arr1(&[5.0, 6.0, 7.0, 8.0, 7.0]);
+    let par = spearman_correlation(&a, &b, THREADS)?;
+    let seq = spearman_correlation(&a, &b, None)?;
+    assert_eq!(par, seq);
+    // the tied values of b get the average rank 3.5
+    assert!(approx_equal(par, 0.8207826816681233, None));
+    assert!(approx_equal(
+        par,
+        pearson(&a, &arr1(&[1.0, 2.0, 3.5, 5.0, 3.5]), None)?,
+        None
+    ));
+    // monotonic transforms do not change the ranks
+    let exp = a.mapv(f64::exp);
+    assert!(approx_equal(
+        spearman_correlation(&a, &exp, THREADS)?,
+        1.0,
+        None
+    ));
+    let neg = a.mapv(|v| -v * v * v);
+    assert!(approx_equal(
+        spearman_correlation(&a, &neg, THREADS)?,
+        -1.0,
+        None
+    ));
+    let ints = arr1(&[3u16, 1, 2]);
+    assert!(approx_equal(
+        spearman_correlation(&ints, &arr1(&[30u16, 10, 20]), None)?,
+        1.0,
+        None
+    ));
+    assert!(spearman_correlation(&a, &arr1(&[1.0, 2.0]), None).is_err());
+    assert!(spearman_correlation(&arr1(&[1.0, 2.0]), &arr1(&[2.0, 1.0]), None).is_err());
+    assert!(spearman_correlation(&a, &arr1(&[2.0; 5]), None).is_err());
+    // NaN values are ranked last as ties, independent of the input order
+    let nan_a = arr1(&[f64::NAN, 1.0, 3.0, f64::NAN, 2.0, 4.0]);
+    let nan_b = arr1(&[6.0, 1.0, 3.0, 5.0, 2.0, 4.0]);
+    let rev_a = nan_a.iter().rev().copied().collect::<Vec<f64>>();
+    let rev_b = nan_b.iter().rev().copied().collect::<Vec<f64>>();
+    let nan_corr = spearman_correlation(&nan_a, &nan_b, None)?;
+    assert_eq!(nan_corr, spearman_correlation(&rev_a, &rev_b, None)?);
+    assert!(approx_equal(
+        nan_corr,
+        pearson(&arr1(&[5.5, 1.0, 3.0, 5.5, 2.0, 4.0]), &nan_b, None)?,
+        None
+    ));
+    Ok(())
+}
+
 /// Tests that `sum` returns expected sum from integer and floating point arrays
 /// as well as images.
 #[test]
@@ -175,7 +749,7 @@ fn statistics_sum_expected_results() -> Result<(), ImgalError> {
         &SHAPE,
         None,
     )?;
-    let i32_data = vec![2, 5, 10, 23];
+    let i32_data = vec![2_i32, 5, 10, 23];
     let f64_data = vec![1.0, 10.5, 3.25, 37.11];
     let f64_error_data = vec![0.1_f64; 1000];
     assert_eq!(sum(&i32_data, THREADS), 40);
@@ -194,6 +768,94 @@ fn statistics_sum_expected_results() -> Result<(), ImgalError> {
         None
     ));
     assert!(approx_equal(sum(&image_data, None), 15630.0102099582, None));
+
+    // parallel row partial sums are combined with compensated summation
+    let cancel_data = arr2(&[[1.0], [1e100], [1.0], [-1e100]]);
+    assert_eq!(sum(&cancel_data, THREADS), 2.0);
+    Ok(())
+}
+
+/// Tests that the parallel partial sums of `kahan_sum` and `sum` are merged
+/// with compensated summation on an ill-conditioned input spread across rows.
+#[test]
+fn statistics_sum_parallel_compensated_merge() -> Result<(), ImgalError> {
+    // "1e16" absorbs each "1.0" in uncompensated f64 summation, the exact
+    // total is the number of ones
+    let (rows, cols) = (4002, 8);
+    let n = ((rows - 2) * cols) as f64;
+    let data = Array2::<f64>::from_shape_fn((rows, cols), |(r, c)| match (r, c) {
+        (0, 0) => 1e16,
+        (r, 0) if r == rows - 1 => -1e16,
+        (r, _) if r == 0 || r == rows - 1 => 0.0,
+        _ => 1.0,
+    });
+    let kahan_seq = kahan_sum(&data, None)?;
+    let kahan_par = kahan_sum(&data, THREADS)?;
+    assert_eq!(kahan_seq, n);
+    assert_eq!(kahan_par, kahan_seq);
+    assert_eq!(sum(&data, THREADS), n);
+    Ok(())
+}
+
+/// Tests that `sum` and `kahan_sum` accumulate integers in 128-bit precision
+/// without wrapping, also if the total overflows the input type.
+#[test]
+fn statistics_sum_integer_overflow_expected_results() -> Result<(), ImgalError> {
+    // the intermediate sum exceeds i64::MAX, but the total is in range
+    let i64_data = [i64::MAX, i64::MAX, -i64::MAX, -i64::MAX, 7];
+    assert_eq!(sum(&i64_data, THREADS), 7);
+    assert_eq!(sum(&i64_data, None), 7);
+    assert_eq!(kahan_sum(&i64_data, None)?, 7);
+    // the total exceeds the range of the input type, but not the accumulator
+    let u64_data = [u64::MAX, 1];
+    assert_eq!(sum(&u64_data, THREADS), u64::MAX as i128 + 1);
+    assert_eq!(sum(&u64_data, None), u64::MAX as i128 + 1);
+    assert_eq!(kahan_sum(&[200_u8, 100], THREADS)?, 300);
+    let u8_data = Array2::<u8>::from_elem((300, 301), 255);
+    assert_eq!(sum(&u8_data, THREADS), 255 * 300 * 301);
+    assert_eq!(sum(&u8_data, None), 255 * 300 * 301);
+    let u16_data = Array2::<u16>::from_elem((300, 301), u16::MAX);
+    assert_eq!(sum(&u16_data, THREADS), u16::MAX as i128 * 300 * 301);
+    // narrow integers are summed in wider chunks, an i16 image spanning
+    // several chunks with large intermediate sums and a small total
+    let i16_data = Array2::from_shape_fn((300, 301), |(r, c)| {
+        if r == 0 && c == 0 {
+            7
+        } else if r < 150 {
+            i16::MAX
+        } else {
+            -i16::MAX
+        }
+    });
+    assert_eq!(sum(&i16_data, THREADS), 7 - i16::MAX as i128);
+    assert_eq!(sum(&i16_data, None), 7 - i16::MAX as i128);
+    assert_eq!(sum(i16_data.t(), None), 7 - i16::MAX as i128);
+    Ok(())
+}
+
+/// Tests that `trimmed_mean` and `winsorized_mean` return the expected robust
+/// means for flat and axis compute.
+#[test]
+fn statistics_trimmed_winsorized_mean_expected_results() -> Result<(), ImgalError> {
+    // an outlier (hot pixel) in an otherwise flat background
+    let data = [3.0, 1.0, 2.0, 1000.0, 5.0, 4.0, 7.0, 6.0, 9.0, 8.0];
+    assert_eq!(trimmed_mean(&data, None, None, THREADS)?[0], 5.5);
+    assert_eq!(trimmed_mean(&data, None, Some(0.2), None)?[0], 5.5);
+    assert_eq!(trimmed_mean(&data, None, Some(0.0), None)?[0], 104.5);
+    assert_eq!(winsorized_mean(&data, None, None, None)?[0], 5.5);
+    assert_eq!(winsorized_mean(&data, None, Some(0.2), THREADS)?[0], 5.5);
+    let skewed = [20, 0, 10, 0, 0];
+    assert_eq!(trimmed_mean(&skewed, None, Some(0.2), None)?[0], 10.0 / 3.0);
+    assert_eq!(winsorized_mean(&skewed, None, Some(0.2), None)?[0], 4.0);
+    let image = arr2(&[[1, 2, 3, 4, 100], [-50, 0, 0, 0, 10]]);
+    let trim_axis = trimmed_mean(&image, Some(1), Some(0.2), THREADS)?;
+    assert_eq!(trim_axis.as_slice().unwrap(), &[3.0, 0.0]);
+    let wins_axis = winsorized_mean(&image, Some(1), Some(0.2), None)?;
+    assert_eq!(wins_axis.as_slice().unwrap(), &[3.0, 0.0]);
+    assert!(trimmed_mean(&data, None, Some(0.5), None).is_err());
+    assert!(winsorized_mean(&data, None, Some(-0.1), None).is_err());
+    assert!(trimmed_mean(&image, Some(2), None, None).is_err());
+    assert!(winsorized_mean(&Vec::<f64>::new(), None, None, None).is_err());
     Ok(())
 }
 
@@ -306,3 +968,54 @@ fn statistics_weighted_merge_sort_mut_expected_results() -> Result<(), ImgalErro
     assert_eq!(pp_long_swaps, 219.0);
     Ok(())
 }
+
+/// Tests that `welch_t_test` returns the expected statistic and p-values.
+#[test]
+fn statistics_welch_t_test_expected_results() -> Result<(), ImgalError> {
+    let a = arr1(&[1.0, 2.0, 3.0, 4.0]);
+    let b = arr1(&[2.0, 4.0, 6.0, 8.0, 10.0]);
+    let res = welch_t_test(&a, &b, None, None, None, THREADS)?;
+    assert!(approx_equal(res.statistic, -2.2514363231593695, None));
+    assert!(approx_equal(res.p_value, 0.06913359320383816, Some(1e-6)));
+    let less = welch_t_test(&a, &b, None, None, Some(Alternative::Less), None)?;
+    let greater = welch_t_test(&a, &b, None, None, Some(Alternative::Greater), None)?;
+    assert!(approx_equal(less.p_value, 0.5 * res.p_value, None));
+    assert!(approx_equal(less.p_value + greater.p_value, 1.0, None));
+    // constant samples and too few values
+    let flat = [2.0; 3];
+    assert!(
+        welch_t_test(&flat, &flat, None, None, None, THREADS)?
+            .p_value
+            .is_nan()
+    );
+    assert!(welch_t_test(&a, &arr1(&[1.0]), None, None, None, THREADS).is_err());
+    Ok(())
+}
+
+/// Tests that `tdigest` and `TDigest` estimate percentiles close to the exact
+/// linear percentiles, with merged chunks and parallel execution.
+#[test]
+fn statistics_tdigest_expected_results() -> Result<(), ImgalError> {
+    let n = 100_000;
+    let data: Vec<f64> = (0..n).map(|i| ((i * 7919) % n) as f64).collect();
+    let digest_par = tdigest(&data, None, THREADS)?;
+    let digest_seq = tdigest(&data, None, None)?;
+    let mut digest_chunks = TDigest::new(None)?;
+    for chunk in data.chunks(8192) {
+        let mut d = TDigest::new(None)?;
+        chunk.iter().for_each(|&v| d.add(v));
+        digest_chunks.merge(&d);
+    }
+    assert_eq!(digest_par.count(), n as f64);
+    for p in [0.1, 1.0, 25.0, 50.0, 99.0, 99.9] {
+        let exact = linear_percentile(&data, p, None, None, None)?[0];
+        for d in [&digest_par, &digest_seq, &digest_chunks] {
+            assert!(approx_equal(d.percentile(p)?, exact, Some(1e-3 * n as f64)));
+        }
+    }
+    assert_eq!(digest_seq.percentile(0.0)?, 0.0);
+    assert_eq!(digest_seq.percentile(100.0)?, (n - 1) as f64);
+    assert!(TDigest::new(Some(1.0)).is_err());
+    assert!(TDigest::new(None)?.percentile(50.0).is_err());
+    Ok(())
+}
@@ -1,3 +1,5 @@
+#![allow(clippy::bool_assert_comparison, clippy::bool_comparison)]
+
 use ndarray::arr2;
 
 use imgal::ImgalError;
@@ -0,0 +1,84 @@
+use ndarray::{Array3, arr1};
+
+use imgal::prelude::*;
+use imgal::timeseries::{AverageMethod, robust_average, rolling_background};
+
+const TOLERANCE: f64 = 1e-10;
+const THREADS: Option<usize> = Some(0);
+
+fn approx_equal(a: f64, b: f64, tol: Option<f64>) -> bool {
+    (a - b).abs() < tol.unwrap_or(TOLERANCE)
+}
+
+/// Tests that `robust_average` returns the expected mean, median and
+/// sigma-clipped averages of a time series with a transient outlier.
+#[test]
+fn timeseries_robust_average_expected_results() -> Result<(), ImgalError> {
+    let trace = [10.0, 11.0, 9.0, 10.0, 12.0, 8.0, 10.0, 11.0, 9.0, 1000.0];
+    let mut data = Array3::<f64>::zeros((10, 4, 5));
+    data.indexed_iter_mut()
+        .for_each(|((t, _, _), v)| *v = trace[t]);
+    let clip = AverageMethod::SigmaClip {
+        sigma: 2.0,
+        max_iter: 5,
+    };
+    let mean = robust_average(&data, AverageMethod::Mean, None, THREADS)?;
+    let median = robust_average(&data, AverageMethod::Median, None, THREADS)?;
+    let clip_par = robust_average(&data, clip, None, THREADS)?;
+    let clip_seq = robust_average(&data, clip, None, None)?;
+    assert_eq!(mean.shape(), [4, 5]);
+    assert!(approx_equal(mean[[0, 0]], 109.0, None));
+    assert!(approx_equal(median[[1, 2]], 10.0, None));
+    assert!(approx_equal(clip_par[[3, 4]], 10.0, None));
+    assert_eq!(clip_par, clip_seq);
+    let frames_last = robust_average(arr1(&trace).view(), AverageMethod::Median, Some(0), None)?;
+    assert!(approx_equal(frames_last[()], 10.0, None));
+    assert!(robust_average(&data, AverageMethod::Mean, Some(3), None).is_err());
+    let bad_clip = AverageMethod::SigmaClip {
+        sigma: 0.0,
+        max_iter: 5,
+    };
+    assert!(robust_average(&data, bad_clip, None, None).is_err());
+    Ok(())
+}
+
+/// Tests that `rolling_background` isolates a transient event from a slowly
+/// decaying background.
+#[test]
+fn timeseries_rolling_background_expected_results() -> Result<(), ImgalError> {
+    // a bleaching background with a transient event at frames 20 to 22
+    let trace: Vec<f64> = (0..40)
+        .map(|t| {
+            let event = if (20..23).contains(&t) { 50.0 } else { 0.0 };
+            100.0 - t as f64 + event
+        })
+        .collect();
+    let mut data = Array3::<f64>::zeros((3, 4, 40));
+    data.indexed_iter_mut()
+        .for_each(|((_, _, t), v)| *v = trace[t]);
+    let output = rolling_background(&data, 9, None, Some(2), THREADS)?;
+    let output_seq = rolling_background(&data, 9, None, Some(2), None)?;
+    assert_eq!(output, output_seq);
+    let (corr_par, bg_par) = (output.corrected, output.background);
+    assert_eq!(corr_par.shape(), data.shape());
+    // the window minimum of a decreasing trace is the last frame of the window
+    assert!(approx_equal(bg_par[[1, 2, 10]], 86.0, None));
+    assert!(approx_equal(bg_par[[1, 2, 39]], 61.0, None));
+    assert!(approx_equal(corr_par[[0, 0, 21]], 54.0, None));
+    assert!(approx_equal(corr_par[[0, 0, 5]], 4.0, None));
+    // the median of a linear window is the window center
+    let median = rolling_background(&data, 9, Some(50.0), Some(2), None)?;
+    let (corr_med, bg_med) = (median.corrected, median.background);
+    assert!(approx_equal(bg_med[[2, 3, 10]], 90.0, None));
+    assert!(approx_equal(corr_med[[2, 3, 10]], 0.0, None));
+    assert!(corr_med[[2, 3, 21]] > 40.0);
+    // the 0th percentile equals the minimum and percentiles interpolate
+    let bg_p0 = rolling_background(&data, 9, Some(0.0), Some(2), None)?.background;
+    assert_eq!(bg_p0, bg_par);
+    let bg_p25 = rolling_background(&arr1(&[4.0, 0.0, 2.0]), 3, Some(25.0), None, None)?.background;
+    assert!(approx_equal(bg_p25[1], 1.0, None));
+    assert!(rolling_background(&data, 0, None, None, None).is_err());
+    assert!(rolling_background(&data, 3, Some(101.0), None, None).is_err());
+    assert!(rolling_background(&data, 3, None, Some(3), None).is_err());
+    Ok(())
+}
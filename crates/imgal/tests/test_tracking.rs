@@ -0,0 +1,54 @@
+use ndarray::{arr2, s};
+
+use imgal::prelude::*;
+use imgal::simulation::trajectories::{MotionModel, simulate_tracks};
+use imgal::tracking::TrackTable;
+
+/// Tests that `TrackTable` sorts its rows, round trips the `simulate_tracks`
+/// layout and rejects invalid tables.
+#[test]
+fn table_track_table_expected_results() -> Result<(), ImgalError> {
+    let coords = arr2(&[[1.0, 1.0], [0.0, 0.0], [5.0, 5.0]]);
+    let table = TrackTable::new(vec![0, 0, 1], vec![1, 0, 0], &coords, Some(vec![3, 2, 7]))?;
+    assert_eq!(table.len(), 3);
+    assert_eq!(table.ndim(), 2);
+    assert_eq!(table.frame(), &[0, 1, 0]);
+    assert_eq!(table.label(), &[2, 3, 7]);
+    assert_eq!(table.coords(), arr2(&[[0.0, 0.0], [1.0, 1.0], [5.0, 5.0]]));
+    assert_eq!(table.track_ids(), vec![0, 1]);
+    let tracks = simulate_tracks(
+        4,
+        6,
+        &[32, 32],
+        MotionModel::Brownian { diffusion: 0.5 },
+        Some(7),
+    )?;
+    let sim = TrackTable::from_array(&tracks)?;
+    assert_eq!(sim.len(), 24);
+    assert_eq!(sim.to_array(), tracks);
+    // duplicate detections, mismatched lengths and invalid IDs
+    assert!(TrackTable::new(vec![0, 0], vec![1, 1], coords.slice(s![..2, ..]), None).is_err());
+    assert!(TrackTable::new(vec![0, 0], vec![0, 1], &coords, None).is_err());
+    assert!(TrackTable::from_array(&arr2(&[[-1.0, 0.0, 0.0]])).is_err());
+    Ok(())
+}
+
+/// Tests that `TrackTable` filters short tracks and merges tables without
+/// joining their tracks.
+#[test]
+fn table_filter_merge_expected_results() -> Result<(), ImgalError> {
+    let coords = arr2(&[[0.0], [1.0], [2.0], [9.0]]);
+    let table = TrackTable::new(vec![0, 0, 0, 3], vec![0, 1, 2, 0], &coords, None)?;
+    let long = table.filter_by_length(2);
+    assert_eq!(long.track_id(), &[0, 0, 0]);
+    let early = table.filter(|_, f, _, _| f < 1);
+    assert_eq!(early.track_id(), &[0, 3]);
+    let merged = table.merge(&long)?;
+    assert_eq!(merged.len(), 7);
+    assert_eq!(merged.track_ids(), vec![0, 3, 4]);
+    assert_eq!(merged.track_lengths()[&4], 3);
+    assert_eq!(table.merge(&early.filter(|_, _, _, _| false))?, table);
+    let other = TrackTable::new(vec![0], vec![0], &arr2(&[[0.0, 0.0]]), None)?;
+    assert!(table.merge(&other).is_err());
+    Ok(())
+}
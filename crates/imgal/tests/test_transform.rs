@@ -1,8 +1,17 @@
-use ndarray::arr2;
+use ndarray::{Array1, Array2, Array3, arr2, s};
 
 use imgal::prelude::*;
 use imgal::simulation::blob::gaussian_metaballs;
+use imgal::transform::blend::{BlendGradient, poisson_blend};
+use imgal::transform::downsample::{
+    BinMode, bin_axis, downsample_mean, downsample_sum, spatial_bin,
+};
 use imgal::transform::pad::{constant_pad, reflect_pad, zero_pad};
+use imgal::transform::prefetch::TilePrefetcher;
+use imgal::transform::pyramid::{ngff_multiscales_metadata, pyramid_gaussian};
+use imgal::transform::shift::circular_shift_decay;
+use imgal::transform::upsample::fourier_upsample;
+use imgal::transform::wells::{detect_well_grid, split_wells};
 
 const TOLERANCE: f64 = 1e-10;
 const CENTER_2D: [[f64; 2]; 1] = [[25.0, 25.0]];
@@ -21,6 +30,160 @@ fn approx_equal(a: f64, b: f64, tol: Option<f64>) -> bool {
     (a - b).abs() < tol.unwrap_or(TOLERANCE)
 }
 
+/// Tests that `poisson_blend` shifts a source patch to match the target at
+/// the mask boundary and that mixed gradients keep the target texture.
+#[test]
+fn blend_poisson_blend_expected_results() -> Result<(), ImgalError> {
+    let target = Array2::<f64>::from_elem((20, 24), 10.0);
+    let bump = |r: usize, c: usize| {
+        let d2 = (r as f64 - 10.0).powi(2) + (c as f64 - 12.0).powi(2);
+        if d2 < 9.0 { 9.0 - d2 } else { 0.0 }
+    };
+    let source = Array2::from_shape_fn((20, 24), |(r, c)| 50.0 + bump(r, c));
+    let mask = Array2::from_shape_fn((20, 24), |(r, c)| {
+        (4..17).contains(&r) && (5..20).contains(&c)
+    });
+    let par = poisson_blend(&target, &source, mask.view(), None, THREADS)?;
+    let seq = poisson_blend(&target, &source, mask.view(), None, None)?;
+    par.iter()
+        .zip(seq.iter())
+        .for_each(|(a, b)| assert!((a - b).abs() < 1e-8));
+    // the source details are kept with the offset of the target boundary
+    par.indexed_iter().for_each(|((r, c), &v)| {
+        let expected = if mask[[r, c]] {
+            10.0 + bump(r, c)
+        } else {
+            10.0
+        };
+        assert!((v - expected).abs() < 1e-6);
+    });
+    // a flat source with mixed gradients keeps the target ramp
+    let ramp = Array2::from_shape_fn((20, 24), |(r, c)| (r + 2 * c) as f64);
+    let flat = Array2::<f64>::zeros((20, 24));
+    let mixed = poisson_blend(
+        &ramp,
+        &flat,
+        mask.view(),
+        Some(BlendGradient::Mixed),
+        THREADS,
+    )?;
+    mixed
+        .iter()
+        .zip(ramp.iter())
+        .for_each(|(a, b)| assert!((a - b).abs() < 1e-6));
+    // a mask touching the image border uses the in-image neighbors only
+    let edge = Array2::from_shape_fn((20, 24), |(r, _)| r < 5);
+    let edged = poisson_blend(&target, &source, edge.view(), None, THREADS)?;
+    assert!((edged[[0, 0]] - 10.0).abs() < 1e-6);
+    assert!(
+        poisson_blend(
+            &target,
+            &source,
+            Array2::from_elem((20, 24), true).view(),
+            None,
+            None
+        )
+        .is_err()
+    );
+    assert!(
+        poisson_blend(
+            &target,
+            &source,
+            Array2::from_elem((20, 23), false).view(),
+            None,
+            None
+        )
+        .is_err()
+    );
+    // a non-finite source does not converge
+    let mut broken = source.clone();
+    broken[[10, 12]] = f64::NAN;
+    assert!(poisson_blend(&target, &broken, mask.view(), None, None).is_err());
+    Ok(())
+}
+
+/// Tests that `bin_axis` sums or averages consecutive samples along one axis
+/// and leaves the other axes unchanged.
+#[test]
+fn downsample_bin_axis_expected_results() -> Result<(), ImgalError> {
+    let data = Array3::from_shape_fn((3, 4, 10), |(r, c, t)| ((r * 4 + c) * 100 + t) as u16);
+    let par = bin_axis(&data, 4, None, None, THREADS)?;
+    let seq = bin_axis(&data, 4, None, Some(BinMode::Sum), None)?;
+    assert_eq!(par, seq);
+    assert_eq!(par.dim(), (3, 4, 3));
+    assert_eq!(par, downsample_sum(&data, &[1, 1, 4], None)?);
+    assert_eq!(par.sum(), data.iter().map(|&v| v as f64).sum::<f64>());
+    // a full bin of 4 samples and the partial bin of 2 samples
+    assert_eq!(par[[1, 2, 0]], (4 * 600 + 6) as f64);
+    assert_eq!(par[[1, 2, 2]], (2 * 600 + 17) as f64);
+    let mean = bin_axis(&data, 4, Some(2), Some(BinMode::Mean), THREADS)?;
+    assert!(approx_equal(mean[[1, 2, 0]], 601.5, None));
+    assert!(approx_equal(mean[[1, 2, 2]], 608.5, None));
+    let rows = bin_axis(&data, 2, Some(0), None, THREADS)?;
+    assert_eq!(rows.dim(), (2, 4, 10));
+    assert_eq!(rows[[1, 0, 0]], 800.0);
+    assert!(bin_axis(&data, 2, Some(3), None, THREADS).is_err());
+    assert!(bin_axis(&data, 0, None, None, THREADS).is_err());
+    Ok(())
+}
+
+/// Tests that `downsample_sum` conserves the total counts and that
+/// `downsample_mean` averages full and partial blocks.
+#[test]
+fn downsample_downsample_sum_mean_expected_results() -> Result<(), ImgalError> {
+    let data = Array3::from_shape_fn((5, 6, 7), |(p, r, c)| (p * 42 + r * 7 + c) as u16);
+    let total: f64 = data.iter().map(|&v| v as f64).sum();
+    let par = downsample_sum(&data, &[2, 3, 1], THREADS)?;
+    let seq = downsample_sum(&data, &[2, 3, 1], None)?;
+    assert_eq!(par, seq);
+    assert_eq!(par.dim(), (3, 2, 7));
+    assert_eq!(par.sum(), total);
+    // a full block of 2 × 3 pixels and the partial block of 1 × 3 pixels
+    assert_eq!(par[[0, 0, 0]], (7 + 14 + 42 + 49 + 56) as f64);
+    assert_eq!(
+        par[[2, 1, 6]],
+        (4 * 42 + 3 * 7 + 6 + 4 * 42 + 4 * 7 + 6 + 4 * 42 + 5 * 7 + 6) as f64
+    );
+    let mean = downsample_mean(&data, &[2, 3, 1], THREADS)?;
+    assert!(approx_equal(mean[[0, 0, 0]], par[[0, 0, 0]] / 6.0, None));
+    assert!(approx_equal(mean[[2, 1, 6]], par[[2, 1, 6]] / 3.0, None));
+    // unit factors are the identity
+    let same = downsample_mean(&data, &[1, 1, 1], THREADS)?;
+    assert_eq!(same, data.mapv(|v| v as f64));
+    assert!(downsample_sum(&data, &[2, 2], THREADS).is_err());
+    assert!(downsample_sum(&data, &[2, 0, 1], THREADS).is_err());
+    Ok(())
+}
+
+/// Tests that `spatial_bin` bins blocks of the spatial axes and leaves the
+/// decay axis unchanged.
+#[test]
+fn downsample_spatial_bin_expected_results() -> Result<(), ImgalError> {
+    let data = Array3::from_shape_fn((5, 4, 6), |(r, c, t)| (r * 24 + c * 6 + t) as u16);
+    let par = spatial_bin(&data, 2, Some(&[0, 1]), None, THREADS)?;
+    let seq = spatial_bin(&data, 2, Some(&[1, 0]), Some(BinMode::Sum), None)?;
+    assert_eq!(par, seq);
+    assert_eq!(par.dim(), (3, 2, 6));
+    assert_eq!(par, downsample_sum(&data, &[2, 2, 1], None)?);
+    assert_eq!(par.sum(), data.iter().map(|&v| v as f64).sum::<f64>());
+    // each decay sample of a binned pixel is the sum of the 2 × 2 block
+    assert_eq!(par[[0, 1, 3]], (12 + 18 + 36 + 42 + 4 * 3) as f64);
+    // the partial block of the last row
+    assert_eq!(par[[2, 0, 0]], (96 + 102) as f64);
+    let mean = spatial_bin(&data, 2, Some(&[0, 1]), Some(BinMode::Mean), THREADS)?;
+    assert!(approx_equal(mean[[0, 1, 3]], par[[0, 1, 3]] / 4.0, None));
+    assert!(approx_equal(mean[[2, 0, 0]], 99.0, None));
+    // the default bins all axes except the decay axis of a 3D stack
+    let default = spatial_bin(&data, 2, None, None, THREADS)?;
+    assert_eq!(default, par);
+    let image = Array2::from_shape_fn((5, 4), |(r, c)| (r * 4 + c) as u16);
+    assert_eq!(spatial_bin(&image, 2, None, None, None)?.dim(), (3, 2));
+    assert!(spatial_bin(&data, 2, Some(&[0, 3]), None, THREADS).is_err());
+    assert!(spatial_bin(&data, 2, Some(&[0, 0]), None, THREADS).is_err());
+    assert!(spatial_bin(&data, 0, Some(&[]), None, THREADS).is_err());
+    Ok(())
+}
+
 /// Tests that `constant_pad` returns the expected constant value padded array
 /// (2D and 3D) by checking the center for the maximum value and padded regions
 /// for the constant value.
@@ -282,3 +445,238 @@ fn pad_zero_pad_expected_results() -> Result<(), ImgalError> {
     assert_eq!(pad_3d_sym_seq[[7, 10, 58]], 0.0);
     Ok(())
 }
+
+/// Tests that `fourier_upsample` exactly interpolates band-limited signals and
+/// passes through the original samples.
+#[test]
+fn upsample_fourier_upsample_expected_results() -> Result<(), ImgalError> {
+    let tau = 2.0 * std::f64::consts::PI;
+    let wave = Array1::from_shape_fn(16, |k| (tau * 3.0 * k as f64 / 16.0).cos());
+    let up = fourier_upsample(&wave, &[4], THREADS)?;
+    assert_eq!(up.len(), 64);
+    up.iter().enumerate().for_each(|(j, &v)| {
+        assert!(approx_equal(
+            v,
+            (tau * 3.0 * j as f64 / 64.0).cos(),
+            Some(1e-12)
+        ));
+    });
+    // odd and even axis lengths pass through the samples and keep the mean
+    let data = Array2::from_shape_fn((7, 8), |(r, c)| ((r * 8 + c) % 5) as f64);
+    let par = fourier_upsample(&data, &[3, 2], THREADS)?;
+    let seq = fourier_upsample(&data, &[3, 2], None)?;
+    assert_eq!(par.dim(), (21, 16));
+    par.iter()
+        .zip(seq.iter())
+        .for_each(|(a, b)| assert!(approx_equal(*a, *b, Some(1e-12))));
+    par.slice(s![..;3, ..;2])
+        .iter()
+        .zip(data.iter())
+        .for_each(|(a, b)| assert!(approx_equal(*a, *b, Some(1e-12))));
+    assert!(approx_equal(
+        par.mean().unwrap(),
+        data.mean().unwrap(),
+        Some(1e-12)
+    ));
+    assert!(fourier_upsample(&data, &[2], THREADS).is_err());
+    assert!(fourier_upsample(&data, &[0, 2], THREADS).is_err());
+    Ok(())
+}
+
+/// Tests that `detect_well_grid` and `split_wells` recover the well grid of a
+/// simulated 2 x 3 plate and split it into wells in row-major order.
+#[test]
+fn wells_split_wells_expected_results() -> Result<(), ImgalError> {
+    // Gaussian wells with an amplitude encoding the well index
+    let offset = (25.0, 20.0);
+    let spacing = (40.0, 30.0);
+    let plate = Array2::from_shape_fn((110, 100), |(r, c)| {
+        let mut v = 0.0;
+        for i in 0..2 {
+            for j in 0..3 {
+                let dr = r as f64 - (offset.0 + i as f64 * spacing.0);
+                let dc = c as f64 - (offset.1 + j as f64 * spacing.1);
+                v += (1.0 + (i * 3 + j) as f64) * (-(dr * dr + dc * dc) / 72.0).exp();
+            }
+        }
+        v
+    });
+    let detected = detect_well_grid(&plate, (2, 3), THREADS)?;
+    assert!(approx_equal(detected.offset.0, offset.0, Some(1.01)));
+    assert!(approx_equal(detected.offset.1, offset.1, Some(1.01)));
+    assert!(approx_equal(detected.spacing.0, spacing.0, Some(1.01)));
+    assert!(approx_equal(detected.spacing.1, spacing.1, Some(1.01)));
+    let wells = split_wells(
+        &plate,
+        (2, 3),
+        Some(spacing),
+        Some(offset),
+        Some((21, 21)),
+        None,
+    )?;
+    let wells_auto = split_wells(&plate, (2, 3), None, None, Some((21, 21)), THREADS)?;
+    assert_eq!(wells.len(), 6);
+    assert_eq!(wells_auto.len(), 6);
+    for (k, (w, w_auto)) in wells.iter().zip(wells_auto.iter()).enumerate() {
+        assert_eq!(w.dim(), (21, 21));
+        assert!(approx_equal(w[[10, 10]], 1.0 + k as f64, Some(1e-3)));
+        let max = w_auto.iter().cloned().fold(f64::MIN, f64::max);
+        assert!(approx_equal(max, 1.0 + k as f64, Some(1e-3)));
+    }
+    // the default well shape is the spacing
+    let wells = split_wells(&plate, (2, 3), Some(spacing), Some(offset), None, None)?;
+    assert_eq!(wells[0].dim(), (40, 30));
+    assert!(split_wells(&plate, (2, 3), Some(spacing), Some((5.0, 5.0)), None, None).is_err());
+    assert!(split_wells(&plate, (0, 3), Some(spacing), Some(offset), None, None).is_err());
+    assert!(detect_well_grid(&plate, (60, 3), None).is_err());
+    Ok(())
+}
+
+/// Tests that `pyramid_gaussian` downsamples the selected axes, preserves the
+/// mean intensity and that `ngff_multiscales_metadata` scales each level.
+#[test]
+fn pyramid_pyramid_gaussian_expected_results() -> Result<(), ImgalError> {
+    let data = Array3::from_shape_fn((2, 16, 12), |(ch, r, c)| {
+        (ch + 1) as f64 * (10.0 + ((r * 12 + c) as f64 * 0.3).sin())
+    });
+    let pyramid = pyramid_gaussian(&data, 3, None, None, &[1, 2], THREADS)?;
+    let pyramid_seq = pyramid_gaussian(&data, 3, None, None, &[1, 2], None)?;
+    assert_eq!(pyramid.len(), 3);
+    assert_eq!(pyramid[0].shape(), &[2, 16, 12]);
+    assert_eq!(pyramid[1].shape(), &[2, 8, 6]);
+    assert_eq!(pyramid[2].shape(), &[2, 4, 3]);
+    assert_eq!(pyramid, pyramid_seq);
+    // the smoothing removes the high frequency pattern around the mean
+    let mean = pyramid[2].mean().unwrap();
+    assert!(approx_equal(mean, 15.0, Some(0.5)));
+    // a constant image stays constant at all levels
+    let flat = Array2::<f64>::from_elem((9, 7), 3.0);
+    let flat_pyramid = pyramid_gaussian(&flat, 3, Some(3), None, &[0, 1], None)?;
+    assert_eq!(flat_pyramid[2].shape(), &[1, 1]);
+    flat_pyramid
+        .iter()
+        .flat_map(|l| l.iter())
+        .for_each(|&v| assert!(approx_equal(v, 3.0, None)));
+    let meta = ngff_multiscales_metadata(
+        "img",
+        "czyx",
+        &[1.0, 2.0, 0.5, 0.5],
+        None,
+        2,
+        2,
+        Some(&[2, 3]),
+    )?;
+    assert!(meta.contains("{\"name\": \"c\", \"type\": \"channel\"}"));
+    assert!(meta.contains("{\"name\": \"x\", \"type\": \"space\", \"unit\": \"micrometer\"}"));
+    assert!(meta.contains("\"scale\": [1.0, 2.0, 0.5, 0.5]"));
+    assert!(meta.contains("\"path\": \"1\", \"coordinateTransformations\": [{\"type\": \"scale\", \"scale\": [1.0, 2.0, 1.0, 1.0]}]"));
+    assert!(pyramid_gaussian(&data, 0, None, None, &[1, 2], None).is_err());
+    assert!(pyramid_gaussian(&data, 2, Some(1), None, &[1, 2], None).is_err());
+    assert!(pyramid_gaussian(&data, 2, None, None, &[3], None).is_err());
+    assert!(ngff_multiscales_metadata("img", "yx", &[1.0], None, 2, 2, None).is_err());
+    Ok(())
+}
+
+/// Tests that `ngff_multiscales_metadata` produces valid JSON with escaped
+/// strings, only downsamples the space axes by default and rejects invalid
+/// parameters.
+#[test]
+fn pyramid_ngff_multiscales_metadata_valid_json() -> Result<(), ImgalError> {
+    let name = "cells \"A1\" C:\\data\n";
+    let meta = ngff_multiscales_metadata(
+        name,
+        "tzyx",
+        &[5.0, 2.0, 0.5, 0.5],
+        Some("µm\\"),
+        3,
+        2,
+        None,
+    )?;
+    let json: serde_json::Value = serde_json::from_str(&meta).expect("invalid metadata JSON");
+    let ms = &json["multiscales"][0];
+    assert_eq!(ms["name"], name);
+    assert_eq!(ms["axes"][0]["type"], "time");
+    assert_eq!(ms["axes"][1]["name"], "z");
+    assert_eq!(ms["axes"][3]["unit"], "µm\\");
+    assert_eq!(ms["datasets"].as_array().map(|d| d.len()), Some(3));
+    assert_eq!(ms["datasets"][2]["path"], "2");
+    assert_eq!(
+        ms["datasets"][2]["coordinateTransformations"][0]["scale"],
+        serde_json::json!([5.0, 8.0, 2.0, 2.0])
+    );
+    assert!(ngff_multiscales_metadata("img", "zyy", &[1.0; 3], None, 2, 2, None).is_err());
+    assert!(ngff_multiscales_metadata("img", "yx", &[f64::NAN, 1.0], None, 2, 2, None).is_err());
+    assert!(ngff_multiscales_metadata("img", "yx", &[0.0, 1.0], None, 2, 2, None).is_err());
+    assert!(ngff_multiscales_metadata("img", "yx", &[1.0, 1.0], None, 2, 1, None).is_err());
+    Ok(())
+}
+
+/// Tests that `TilePrefetcher` yields all tiles in order and stops loading
+/// when dropped early.
+#[test]
+fn prefetch_tile_prefetcher_expected_results() -> Result<(), ImgalError> {
+    let data = Array2::from_shape_fn((20, 30), |(r, c)| (r * 30 + c) as f64);
+    let expected: Vec<f64> = (0..4)
+        .map(|i| data.slice(s![i * 5..(i + 1) * 5, ..]).sum())
+        .collect();
+    let source = std::sync::Arc::new(data);
+    let prefetcher = TilePrefetcher::new(4, None, move |i| {
+        source.slice(s![i * 5..(i + 1) * 5, ..]).to_owned()
+    })?;
+    let sums: Vec<f64> = prefetcher.map(|tile| tile.sum()).collect();
+    assert_eq!(sums, expected);
+    // dropping a partially consumed prefetcher does not block
+    let mut prefetcher = TilePrefetcher::new(1000, Some(1), |i| i)?;
+    assert_eq!(prefetcher.next(), Some(0));
+    assert_eq!(prefetcher.next(), Some(1));
+    drop(prefetcher);
+    assert!(TilePrefetcher::new(4, Some(0), |i| i).is_err());
+    Ok(())
+}
+
+/// Tests that a panicking loader propagates its panic to the consumer instead
+/// of ending the tile stream early.
+#[test]
+#[should_panic(expected = "failed to load tile 2")]
+fn prefetch_tile_prefetcher_loader_panic() {
+    let prefetcher = TilePrefetcher::new(4, None, |i| {
+        if i == 2 {
+            panic!("failed to load tile {i}");
+        }
+        i
+    })
+    .unwrap();
+    let _tiles: Vec<usize> = prefetcher.collect();
+}
+
+/// Tests that `circular_shift_decay` rolls decays by integer shifts, preserves
+/// the photon count of fractional shifts and is inverted by the opposite shift.
+#[test]
+fn shift_circular_shift_decay_expected_results() -> Result<(), ImgalError> {
+    let data = Array3::from_shape_fn((2, 3, 15), |(i, j, k)| {
+        (i + j + 1) as f64 * (-((k as f64 - 4.0).powi(2)) / 4.0).exp()
+    });
+    let roll_par = circular_shift_decay(&data, 3.0, None, THREADS)?;
+    let roll_seq = circular_shift_decay(&data, 3.0, Some(2), None)?;
+    for k in 0..15 {
+        let src = data[[1, 2, (k + 12) % 15]];
+        assert!(approx_equal(roll_par[[1, 2, k]], src, None));
+        assert!(approx_equal(roll_seq[[1, 2, k]], src, None));
+    }
+    let frac: Array3<f64> = circular_shift_decay(&data, 2.5, None, THREADS)?;
+    let back = circular_shift_decay(&frac, -2.5, None, None)?;
+    assert!(approx_equal(frac.sum(), data.sum(), None));
+    assert!(
+        back.iter()
+            .zip(data.iter())
+            .all(|(a, b)| approx_equal(*a, *b, None))
+    );
+    // the fractional shift moves the peak between bins 6 and 7
+    assert!(approx_equal(frac[[0, 0, 6]], frac[[0, 0, 7]], Some(1e-3)));
+    assert!(frac[[0, 0, 6]] > frac[[0, 0, 5]] && frac[[0, 0, 7]] > frac[[0, 0, 8]]);
+    // the first axis, of length 2, shifted by a full period is unchanged
+    let period = circular_shift_decay(&data, 2.0, Some(0), None)?;
+    assert!(approx_equal(period[[0, 1, 4]], data[[0, 1, 4]], None));
+    assert!(circular_shift_decay(&data, 1.0, Some(3), None).is_err());
+    Ok(())
+}
@@ -5,8 +5,14 @@ use ndarray::{ArrayViewD, IxDyn};
 use imgal::prelude::*;
 use imgal::statistics;
 
+/// Compute the maximum value of an `f64` buffer.
+///
+/// # Safety
+///
+/// `ptr` must either be null or point to `len` initialized and properly
+/// aligned `f64` values that remain valid for the duration of the call.
 #[unsafe(no_mangle)]
-pub extern "C" fn max(ptr: *const f64, len: usize, threads: usize) -> f64 {
+pub unsafe extern "C" fn max(ptr: *const f64, len: usize, threads: usize) -> f64 {
     // validate the pointer and array length
     if ptr.is_null() || len == 0 {
         return 0.0;
@@ -22,49 +28,186 @@ pub extern "C" fn max(ptr: *const f64, len: usize, threads: usize) -> f64 {
     statistics::max(arr.unwrap(), Some(threads)).unwrap()
 }
 
+/// Compute the sum of a `u8` buffer.
+///
+/// The sum is accumulated exactly in 128-bit precision and written to `sum` as
+/// a `u64`. A status code is returned: `0` on success, `-1` if `data_ptr` or
+/// `sum` is null or `data_len == 0` and `-2` if the sum overflows the range of
+/// `u64`. On failure `sum` is left unchanged.
+///
+/// # Safety
+///
+/// `data_ptr` must either be null or point to `data_len` initialized and
+/// properly aligned `u8` values that remain valid for the duration of the
+/// call. `sum` must either be null or point to a writable and properly aligned
+/// `u64`.
 #[unsafe(no_mangle)]
-pub extern "C" fn sum_u8(data_ptr: *const u8, data_len: usize, threads: usize) -> u8 {
-    sum_generic(data_ptr, data_len, threads)
+pub unsafe extern "C" fn sum_u8(
+    data_ptr: *const u8,
+    data_len: usize,
+    threads: usize,
+    sum: *mut u64,
+) -> i32 {
+    unsafe { sum_generic(data_ptr, data_len, threads, sum) }
 }
 
+/// Compute the sum of a `u16` buffer.
+///
+/// The sum is accumulated exactly in 128-bit precision and written to `sum` as
+/// a `u64`. A status code is returned: `0` on success, `-1` if `data_ptr` or
+/// `sum` is null or `data_len == 0` and `-2` if the sum overflows the range of
+/// `u64`. On failure `sum` is left unchanged.
+///
+/// # Safety
+///
+/// `data_ptr` must either be null or point to `data_len` initialized and
+/// properly aligned `u16` values that remain valid for the duration of the
+/// call. `sum` must either be null or point to a writable and properly aligned
+/// `u64`.
 #[unsafe(no_mangle)]
-pub extern "C" fn sum_u16(data_ptr: *const u16, data_len: usize, threads: usize) -> u16 {
-    sum_generic(data_ptr, data_len, threads)
+pub unsafe extern "C" fn sum_u16(
+    data_ptr: *const u16,
+    data_len: usize,
+    threads: usize,
+    sum: *mut u64,
+) -> i32 {
+    unsafe { sum_generic(data_ptr, data_len, threads, sum) }
 }
 
+/// Compute the sum of a `u64` buffer.
+///
+/// The sum is accumulated exactly in 128-bit precision and written to `sum` as
+/// a `u64`. A status code is returned: `0` on success, `-1` if `data_ptr` or
+/// `sum` is null or `data_len == 0` and `-2` if the sum overflows the range of
+/// `u64`. On failure `sum` is left unchanged.
+///
+/// # Safety
+///
+/// `data_ptr` must either be null or point to `data_len` initialized and
+/// properly aligned `u64` values that remain valid for the duration of the
+/// call. `sum` must either be null or point to a writable and properly aligned
+/// `u64`.
 #[unsafe(no_mangle)]
-pub extern "C" fn sum_u64(data_ptr: *const u64, data_len: usize, threads: usize) -> u64 {
-    sum_generic(data_ptr, data_len, threads)
+pub unsafe extern "C" fn sum_u64(
+    data_ptr: *const u64,
+    data_len: usize,
+    threads: usize,
+    sum: *mut u64,
+) -> i32 {
+    unsafe { sum_generic(data_ptr, data_len, threads, sum) }
 }
 
+/// Compute the sum of a `i32` buffer.
+///
+/// The sum is accumulated exactly in 128-bit precision and written to `sum` as
+/// a `i64`. A status code is returned: `0` on success, `-1` if `data_ptr` or
+/// `sum` is null or `data_len == 0` and `-2` if the sum overflows the range of
+/// `i64`. On failure `sum` is left unchanged.
+///
+/// # Safety
+///
+/// `data_ptr` must either be null or point to `data_len` initialized and
+/// properly aligned `i32` values that remain valid for the duration of the
+/// call. `sum` must either be null or point to a writable and properly aligned
+/// `i64`.
 #[unsafe(no_mangle)]
-pub extern "C" fn sum_i32(data_ptr: *const i32, data_len: usize, threads: usize) -> i32 {
-    sum_generic(data_ptr, data_len, threads)
+pub unsafe extern "C" fn sum_i32(
+    data_ptr: *const i32,
+    data_len: usize,
+    threads: usize,
+    sum: *mut i64,
+) -> i32 {
+    unsafe { sum_generic(data_ptr, data_len, threads, sum) }
 }
 
+/// Compute the sum of a `i64` buffer.
+///
+/// The sum is accumulated exactly in 128-bit precision and written to `sum` as
+/// a `i64`. A status code is returned: `0` on success, `-1` if `data_ptr` or
+/// `sum` is null or `data_len == 0` and `-2` if the sum overflows the range of
+/// `i64`. On failure `sum` is left unchanged.
+///
+/// # Safety
+///
+/// `data_ptr` must either be null or point to `data_len` initialized and
+/// properly aligned `i64` values that remain valid for the duration of the
+/// call. `sum` must either be null or point to a writable and properly aligned
+/// `i64`.
 #[unsafe(no_mangle)]
-pub extern "C" fn sum_i64(data_ptr: *const i64, data_len: usize, threads: usize) -> i64 {
-    sum_generic(data_ptr, data_len, threads)
+pub unsafe extern "C" fn sum_i64(
+    data_ptr: *const i64,
+    data_len: usize,
+    threads: usize,
+    sum: *mut i64,
+) -> i32 {
+    unsafe { sum_generic(data_ptr, data_len, threads, sum) }
 }
 
+/// Compute the sum of a `f32` buffer.
+///
+/// The sum is accumulated in `f64` precision and written to `sum`. A status
+/// code is returned: `0` on success and `-1` if `data_ptr` or `sum` is null or
+/// `data_len == 0`. On failure `sum` is left unchanged.
+///
+/// # Safety
+///
+/// `data_ptr` must either be null or point to `data_len` initialized and
+/// properly aligned `f32` values that remain valid for the duration of the
+/// call. `sum` must either be null or point to a writable and properly aligned
+/// `f64`.
 #[unsafe(no_mangle)]
-pub extern "C" fn sum_f32(data_ptr: *const f32, data_len: usize, threads: usize) -> f32 {
-    sum_generic(data_ptr, data_len, threads)
+pub unsafe extern "C" fn sum_f32(
+    data_ptr: *const f32,
+    data_len: usize,
+    threads: usize,
+    sum: *mut f64,
+) -> i32 {
+    unsafe { sum_generic(data_ptr, data_len, threads, sum) }
 }
 
+/// Compute the sum of a `f64` buffer.
+///
+/// The sum is accumulated in `f64` precision and written to `sum`. A status
+/// code is returned: `0` on success and `-1` if `data_ptr` or `sum` is null or
+/// `data_len == 0`. On failure `sum` is left unchanged.
+///
+/// # Safety
+///
+/// `data_ptr` must either be null or point to `data_len` initialized and
+/// properly aligned `f64` values that remain valid for the duration of the
+/// call. `sum` must either be null or point to a writable and properly aligned
+/// `f64`.
 #[unsafe(no_mangle)]
-pub extern "C" fn sum_f64(data_ptr: *const f64, data_len: usize, threads: usize) -> f64 {
-    sum_generic(data_ptr, data_len, threads)
+pub unsafe extern "C" fn sum_f64(
+    data_ptr: *const f64,
+    data_len: usize,
+    threads: usize,
+    sum: *mut f64,
+) -> i32 {
+    unsafe { sum_generic(data_ptr, data_len, threads, sum) }
 }
 
-/// TODO
-fn sum_generic<T>(data_ptr: *const T, data_len: usize, threads: usize) -> T
+/// Compute the sum of a buffer and write it to `sum`, returning a status code.
+///
+/// # Safety
+///
+/// `data_ptr` must either be null or point to `data_len` initialized and
+/// properly aligned values of type `T`. `sum` must either be null or point to
+/// a writable and properly aligned `S`.
+unsafe fn sum_generic<T, S>(data_ptr: *const T, data_len: usize, threads: usize, sum: *mut S) -> i32
 where
     T: AsNumeric,
+    S: TryFrom<T::Acc>,
 {
-    if data_ptr.is_null() || data_len == 0 {
-        return T::from_i32(-1);
+    if data_ptr.is_null() || sum.is_null() || data_len == 0 {
+        return -1;
     }
     let s = unsafe { slice::from_raw_parts(data_ptr, data_len) };
-    statistics::sum(&s, Some(threads))
+    match S::try_from(statistics::sum(&s, Some(threads))) {
+        Ok(total) => {
+            unsafe { sum.write(total) };
+            0
+        }
+        Err(_) => -2,
+    }
 }
@@ -35,6 +35,14 @@ pub fn register_statistics_module(parent_module: &Bound<'_, PyModule>) -> PyResu
         statistics_functions::statistics_linear_percentile,
         &statistics_module
     )?)?;
+    statistics_module.add_function(wrap_pyfunction!(
+        statistics_functions::statistics_pearson,
+        &statistics_module
+    )?)?;
+    statistics_module.add_function(wrap_pyfunction!(
+        statistics_functions::statistics_spearman_correlation,
+        &statistics_module
+    )?)?;
     statistics_module.add_function(wrap_pyfunction!(
         statistics_functions::statistics_sum,
         &statistics_module
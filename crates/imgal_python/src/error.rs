@@ -1,5 +1,5 @@
 use pyo3::PyErr;
-use pyo3::exceptions::{PyException, PyIndexError, PyValueError};
+use pyo3::exceptions::{PyException, PyIndexError, PyOverflowError, PyValueError};
 
 use imgal::ImgalError;
 
@@ -59,7 +59,15 @@ pub fn map_imgal_error(err: ImgalError) -> PyErr {
             "Invalid axis value, axis {} of \"{}\" is not a multiple of {}.",
             axis_idx, arr_name, multiple
         )),
-        ImgalError::InvalidGeneric { msg } => PyException::new_err(format!("{}", msg)),
+        ImgalError::InvalidDecayIntegral { n_pixels } => PyValueError::new_err(format!(
+            "Invalid decay integral, {} pixels have a decay integral of zero.",
+            n_pixels
+        )),
+        ImgalError::InvalidGeneric { msg } => PyException::new_err(msg.to_string()),
+        ImgalError::InvalidNumericOverflow { op, type_name } => PyOverflowError::new_err(format!(
+            "Invalid numeric result, the {} overflows the range of type \"{}\".",
+            op, type_name
+        )),
         ImgalError::InvalidParameterEmptyArray { param_name } => PyException::new_err(format!(
             "Invalid array parameter, the array \"{}\" can not be empty.",
             param_name
@@ -102,6 +110,14 @@ pub fn map_imgal_error(err: ImgalError) -> PyErr {
             "Invalid positive range, the range start value {} is larger than the end value {}.",
             start, end
         )),
+        ImgalError::InvalidRoiCoordinates {
+            roi_label,
+            coords,
+            shape,
+        } => PyIndexError::new_err(format!(
+            "Invalid ROI coordinates, point {:?} of ROI {} is out of bounds for array shape {:?}.",
+            coords, roi_label, shape
+        )),
         ImgalError::InvalidSum { expected, got } => PyValueError::new_err(format!(
             "Invalid sum, expected {} but got {}.",
             expected, got
@@ -1,5 +1,6 @@
 use std::collections::HashMap;
 
+use half::{bf16, f16};
 use numpy::ndarray::Array2;
 use numpy::{
     IntoPyArray, PyArray2, PyArray3, PyArrayDyn, PyArrayMethods, PyReadonlyArray2,
@@ -9,6 +10,7 @@ use pyo3::exceptions::PyTypeError;
 use pyo3::prelude::*;
 
 use crate::error::map_imgal_error;
+use crate::utils::is_bfloat16;
 use imgal::colocalization;
 
 /// Compute the Pearson correlation coefficient between two n-dimensional images
@@ -60,36 +62,40 @@ pub fn colocalization_pearson_roi_coloc<'py>(
     if let Ok(arr_a) = data_a.extract::<PyReadonlyArrayDyn<u8>>() {
         let arr_b = data_b.extract::<PyReadonlyArrayDyn<u8>>()?;
         colocalization::pearson_roi_coloc(arr_a.as_array(), arr_b.as_array(), &rois, threads)
-            .map(|output| output)
             .map_err(map_imgal_error)
     } else if let Ok(arr_a) = data_a.extract::<PyReadonlyArrayDyn<u16>>() {
         let arr_b = data_b.extract::<PyReadonlyArrayDyn<u16>>()?;
         colocalization::pearson_roi_coloc(arr_a.as_array(), arr_b.as_array(), &rois, threads)
-            .map(|output| output)
             .map_err(map_imgal_error)
     } else if let Ok(arr_a) = data_a.extract::<PyReadonlyArrayDyn<u64>>() {
         let arr_b = data_b.extract::<PyReadonlyArrayDyn<u64>>()?;
         colocalization::pearson_roi_coloc(arr_a.as_array(), arr_b.as_array(), &rois, threads)
-            .map(|output| output)
             .map_err(map_imgal_error)
     } else if let Ok(arr_a) = data_a.extract::<PyReadonlyArrayDyn<i64>>() {
         let arr_b = data_b.extract::<PyReadonlyArrayDyn<i64>>()?;
         colocalization::pearson_roi_coloc(arr_a.as_array(), arr_b.as_array(), &rois, threads)
-            .map(|output| output)
             .map_err(map_imgal_error)
     } else if let Ok(arr_a) = data_a.extract::<PyReadonlyArrayDyn<f32>>() {
         let arr_b = data_b.extract::<PyReadonlyArrayDyn<f32>>()?;
         colocalization::pearson_roi_coloc(arr_a.as_array(), arr_b.as_array(), &rois, threads)
-            .map(|output| output)
+            .map_err(map_imgal_error)
+    } else if let Ok(arr_a) = data_a.extract::<PyReadonlyArrayDyn<f16>>() {
+        let arr_b = data_b.extract::<PyReadonlyArrayDyn<f16>>()?;
+        colocalization::pearson_roi_coloc(arr_a.as_array(), arr_b.as_array(), &rois, threads)
+            .map_err(map_imgal_error)
+    } else if is_bfloat16(&data_a)
+        && let Ok(arr_a) = data_a.extract::<PyReadonlyArrayDyn<bf16>>()
+    {
+        let arr_b = data_b.extract::<PyReadonlyArrayDyn<bf16>>()?;
+        colocalization::pearson_roi_coloc(arr_a.as_array(), arr_b.as_array(), &rois, threads)
             .map_err(map_imgal_error)
     } else if let Ok(arr_a) = data_a.extract::<PyReadonlyArrayDyn<f64>>() {
         let arr_b = data_b.extract::<PyReadonlyArrayDyn<f64>>()?;
         colocalization::pearson_roi_coloc(arr_a.as_array(), arr_b.as_array(), &rois, threads)
-            .map(|output| output)
             .map_err(map_imgal_error)
     } else {
         Err(PyErr::new::<PyTypeError, _>(
-            "Unsupported array dtype, supported array dtypes are u8, u16, u64, i64, f32, and f64.",
+            "Unsupported array dtype, supported array dtypes are u8, u16, u64, i64, f16, bf16, f32, and f64.",
         ))
     }
 }
@@ -115,6 +121,10 @@ pub fn colocalization_pearson_roi_coloc<'py>(
 ///     threshold_b: Pixel intensity threshold value for `data_b`. Pixels below
 ///         this value are given a weight of `0.0` if the pixel is in the
 ///         circular neighborhood.
+///     mask: An optional boolean mask (*e.g.* of a cell or ROI) with the same
+///         shape as the input images. If given, the *z-score* is only computed
+///         for pixels where the mask is `True`, and pixels where the mask is
+///         `False` are excluded from the adaptive neighborhoods.
 ///     threads: The requested number of threads to use for parallel execution.
 ///         If `None` or `1` sequential execution is used. If `0`, then the
 ///         maximum available parallelism is used. Thread counts are clamped to
@@ -123,24 +133,27 @@ pub fn colocalization_pearson_roi_coloc<'py>(
 /// Returns:
 ///     The pixel-wise *z-score* indicating colocalization or
 ///     anti-colocalization by its sign and the degree or strength of the
-///     relationship through its absolute values.
+///     relationship through its absolute values. Pixels outside of the mask
+///     are `NaN`.
 ///
 /// Errors:
-///     If `data_a.shape != data_b.shape`.
+///     If `data_a.shape != data_b.shape`. If `mask.shape != data_a.shape`.
 ///
 /// Reference:
 ///     <https://doi.org/10.1109/TIP.2019.2909194>
 #[pyfunction]
 #[pyo3(name = "saca_2d")]
-#[pyo3(signature = (data_a, data_b, threshold_a, threshold_b, threads=None))]
+#[pyo3(signature = (data_a, data_b, threshold_a, threshold_b, mask=None, threads=None))]
 pub fn colocalization_saca_2d<'py>(
     py: Python<'py>,
     data_a: Bound<'py, PyAny>,
     data_b: Bound<'py, PyAny>,
     threshold_a: f64,
     threshold_b: f64,
+    mask: Option<PyReadonlyArray2<bool>>,
     threads: Option<usize>,
 ) -> PyResult<Bound<'py, PyArray2<f64>>> {
+    let mask = mask.as_ref().map(|m| m.as_array());
     if let Ok(arr_a) = data_a.extract::<PyReadonlyArray2<u8>>() {
         let arr_b = data_b.extract::<PyReadonlyArray2<u8>>()?;
         colocalization::saca_2d(
@@ -148,6 +161,7 @@ pub fn colocalization_saca_2d<'py>(
             arr_b.as_array(),
             threshold_a as u8,
             threshold_b as u8,
+            mask,
             threads,
         )
         .map(|output| output.into_pyarray(py))
@@ -159,6 +173,7 @@ pub fn colocalization_saca_2d<'py>(
             arr_b.as_array(),
             threshold_a as u16,
             threshold_b as u16,
+            mask,
             threads,
         )
         .map(|output| output.into_pyarray(py))
@@ -170,6 +185,7 @@ pub fn colocalization_saca_2d<'py>(
             arr_b.as_array(),
             threshold_a as u64,
             threshold_b as u64,
+            mask,
             threads,
         )
         .map(|output| output.into_pyarray(py))
@@ -181,6 +197,7 @@ pub fn colocalization_saca_2d<'py>(
             arr_b.as_array(),
             threshold_a as i64,
             threshold_b as i64,
+            mask,
             threads,
         )
         .map(|output| output.into_pyarray(py))
@@ -192,6 +209,33 @@ pub fn colocalization_saca_2d<'py>(
             arr_b.as_array(),
             threshold_a as f32,
             threshold_b as f32,
+            mask,
+            threads,
+        )
+        .map(|output| output.into_pyarray(py))
+        .map_err(map_imgal_error)
+    } else if let Ok(arr_a) = data_a.extract::<PyReadonlyArray2<f16>>() {
+        let arr_b = data_b.extract::<PyReadonlyArray2<f16>>()?;
+        colocalization::saca_2d(
+            arr_a.as_array(),
+            arr_b.as_array(),
+            f16::from_f64(threshold_a),
+            f16::from_f64(threshold_b),
+            mask,
+            threads,
+        )
+        .map(|output| output.into_pyarray(py))
+        .map_err(map_imgal_error)
+    } else if is_bfloat16(&data_a)
+        && let Ok(arr_a) = data_a.extract::<PyReadonlyArray2<bf16>>()
+    {
+        let arr_b = data_b.extract::<PyReadonlyArray2<bf16>>()?;
+        colocalization::saca_2d(
+            arr_a.as_array(),
+            arr_b.as_array(),
+            bf16::from_f64(threshold_a),
+            bf16::from_f64(threshold_b),
+            mask,
             threads,
         )
         .map(|output| output.into_pyarray(py))
@@ -203,13 +247,14 @@ pub fn colocalization_saca_2d<'py>(
             arr_b.as_array(),
             threshold_a,
             threshold_b,
+            mask,
             threads,
         )
         .map(|output| output.into_pyarray(py))
         .map_err(map_imgal_error)
     } else {
         Err(PyErr::new::<PyTypeError, _>(
-            "Unsupported array dtype, supported array dtypes are u8, u16, u64, i64, f32, and f64.",
+            "Unsupported array dtype, supported array dtypes are u8, u16, u64, i64, f16, bf16, f32, and f64.",
         ))
     }
 }
@@ -236,6 +281,10 @@ pub fn colocalization_saca_2d<'py>(
 ///     threshold_b: Pixel intensity threshold value for `data_b`. Pixels below
 ///         this value are given a weight of `0.0` if the pixel is in the
 ///         circular neighborhood.
+///     mask: An optional boolean mask (*e.g.* of a cell or ROI) with the same
+///         shape as the input images. If given, the *z-score* is only computed
+///         for pixels where the mask is `True`, and pixels where the mask is
+///         `False` are excluded from the adaptive neighborhoods.
 ///     threads: The requested number of threads to use for parallel execution.
 ///         If `None` or `1` sequential execution is used. If `0`, then the
 ///         maximum available parallelism is used. Thread counts are clamped to
@@ -244,24 +293,27 @@ pub fn colocalization_saca_2d<'py>(
 /// Returns:
 ///     The pixel-wise *z-score* indicating colocalization or
 ///     anti-colocalization by its sign and the degree or strength of the
-///     relationship through its absolute values.
+///     relationship through its absolute values. Pixels outside of the mask
+///     are `NaN`.
 ///
 /// Errors:
-///     If `data_a.shape != data_b.shape`.
+///     If `data_a.shape != data_b.shape`. If `mask.shape != data_a.shape`.
 ///
 /// Reference:
 ///     <https://doi.org/10.1109/TIP.2019.2909194>
 #[pyfunction]
 #[pyo3(name = "saca_3d")]
-#[pyo3(signature = (data_a, data_b, threshold_a, threshold_b, threads=None))]
+#[pyo3(signature = (data_a, data_b, threshold_a, threshold_b, mask=None, threads=None))]
 pub fn colocalization_saca_3d<'py>(
     py: Python<'py>,
     data_a: Bound<'py, PyAny>,
     data_b: Bound<'py, PyAny>,
     threshold_a: f64,
     threshold_b: f64,
+    mask: Option<PyReadonlyArray3<bool>>,
     threads: Option<usize>,
 ) -> PyResult<Bound<'py, PyArray3<f64>>> {
+    let mask = mask.as_ref().map(|m| m.as_array());
     if let Ok(arr_a) = data_a.extract::<PyReadonlyArray3<u8>>() {
         let arr_b = data_b.extract::<PyReadonlyArray3<u8>>()?;
         colocalization::saca_3d(
@@ -269,6 +321,7 @@ pub fn colocalization_saca_3d<'py>(
             arr_b.as_array(),
             threshold_a as u8,
             threshold_b as u8,
+            mask,
             threads,
         )
         .map(|output| output.into_pyarray(py))
@@ -280,6 +333,7 @@ pub fn colocalization_saca_3d<'py>(
             arr_b.as_array(),
             threshold_a as u16,
             threshold_b as u16,
+            mask,
             threads,
         )
         .map(|output| output.into_pyarray(py))
@@ -291,6 +345,7 @@ pub fn colocalization_saca_3d<'py>(
             arr_b.as_array(),
             threshold_a as u64,
             threshold_b as u64,
+            mask,
             threads,
         )
         .map(|output| output.into_pyarray(py))
@@ -302,6 +357,7 @@ pub fn colocalization_saca_3d<'py>(
             arr_b.as_array(),
             threshold_a as i64,
             threshold_b as i64,
+            mask,
             threads,
         )
         .map(|output| output.into_pyarray(py))
@@ -313,6 +369,33 @@ pub fn colocalization_saca_3d<'py>(
             arr_b.as_array(),
             threshold_a as f32,
             threshold_b as f32,
+            mask,
+            threads,
+        )
+        .map(|output| output.into_pyarray(py))
+        .map_err(map_imgal_error)
+    } else if let Ok(arr_a) = data_a.extract::<PyReadonlyArray3<f16>>() {
+        let arr_b = data_b.extract::<PyReadonlyArray3<f16>>()?;
+        colocalization::saca_3d(
+            arr_a.as_array(),
+            arr_b.as_array(),
+            f16::from_f64(threshold_a),
+            f16::from_f64(threshold_b),
+            mask,
+            threads,
+        )
+        .map(|output| output.into_pyarray(py))
+        .map_err(map_imgal_error)
+    } else if is_bfloat16(&data_a)
+        && let Ok(arr_a) = data_a.extract::<PyReadonlyArray3<bf16>>()
+    {
+        let arr_b = data_b.extract::<PyReadonlyArray3<bf16>>()?;
+        colocalization::saca_3d(
+            arr_a.as_array(),
+            arr_b.as_array(),
+            bf16::from_f64(threshold_a),
+            bf16::from_f64(threshold_b),
+            mask,
             threads,
         )
         .map(|output| output.into_pyarray(py))
@@ -324,13 +407,14 @@ pub fn colocalization_saca_3d<'py>(
             arr_b.as_array(),
             threshold_a,
             threshold_b,
+            mask,
             threads,
         )
         .map(|output| output.into_pyarray(py))
         .map_err(map_imgal_error)
     } else {
         Err(PyErr::new::<PyTypeError, _>(
-            "Unsupported array dtype, supported array dtypes are u8, u16, u64, i64, f32, and f64.",
+            "Unsupported array dtype, supported array dtypes are u8, u16, u64, i64, f16, bf16, f32, and f64.",
         ))
     }
 }
@@ -25,9 +25,7 @@ use imgal::distribution;
 #[pyfunction]
 #[pyo3(name = "inverse_normal_cdf")]
 pub fn distribution_inverse_normal_cdf(p: f64) -> PyResult<f64> {
-    distribution::inverse_normal_cdf(p)
-        .map(|output| output)
-        .map_err(map_imgal_error)
+    distribution::inverse_normal_cdf(p).map_err(map_imgal_error)
 }
 
 /// Create a normalized Gaussian distribution over a specified range.
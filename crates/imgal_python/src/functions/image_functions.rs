@@ -1,8 +1,10 @@
+use half::{bf16, f16};
 use numpy::{IntoPyArray, PyArray1, PyArrayDyn, PyReadonlyArrayDyn};
 use pyo3::exceptions::PyTypeError;
 use pyo3::prelude::*;
 
 use crate::error::map_imgal_error;
+use crate::utils::is_bfloat16;
 use imgal::image;
 
 /// Create an image histogram from an n-dimensional image.
@@ -54,13 +56,23 @@ pub fn image_histogram<'py>(
         image::histogram(arr.as_array(), bins, threads)
             .map(|output| output.into_pyarray(py))
             .map_err(map_imgal_error)
+    } else if let Ok(arr) = data.extract::<PyReadonlyArrayDyn<f16>>() {
+        image::histogram(arr.as_array(), bins, threads)
+            .map(|output| output.into_pyarray(py))
+            .map_err(map_imgal_error)
+    } else if is_bfloat16(&data)
+        && let Ok(arr) = data.extract::<PyReadonlyArrayDyn<bf16>>()
+    {
+        image::histogram(arr.as_array(), bins, threads)
+            .map(|output| output.into_pyarray(py))
+            .map_err(map_imgal_error)
     } else if let Ok(arr) = data.extract::<PyReadonlyArrayDyn<f64>>() {
         image::histogram(arr.as_array(), bins, threads)
             .map(|output| output.into_pyarray(py))
             .map_err(map_imgal_error)
     } else {
         Err(PyErr::new::<PyTypeError, _>(
-            "Unsupported array dtype, supported array dtypes are u8, u16, u64, i64, f32, and f64.",
+            "Unsupported array dtype, supported array dtypes are u8, u16, u64, i64, f16, bf16, f32, and f64.",
         ))
     }
 }
@@ -91,9 +103,7 @@ pub fn image_histogram_bin_midpoint(
     max: f64,
     bins: usize,
 ) -> PyResult<f64> {
-    image::histogram_bin_midpoint(index, min, max, bins)
-        .map(|output| output)
-        .map_err(map_imgal_error)
+    image::histogram_bin_midpoint(index, min, max, bins).map_err(map_imgal_error)
 }
 
 /// Compute the histogram bin value range from a bin index.
@@ -123,9 +133,7 @@ pub fn image_histogram_bin_range(
     max: f64,
     bins: usize,
 ) -> PyResult<(f64, f64)> {
-    image::histogram_bin_range(index, min, max, bins)
-        .map(|output| output)
-        .map_err(map_imgal_error)
+    image::histogram_bin_range(index, min, max, bins).map_err(map_imgal_error)
 }
 
 /// Normalize an n-dimensional image using percentile-based minimum and maximum.
@@ -157,6 +165,7 @@ pub fn image_histogram_bin_range(
 #[pyfunction]
 #[pyo3(name = "percentile_normalize")]
 #[pyo3(signature = (data, min, max, clip=None, axis=None, epsilon=None, threads=None))]
+#[allow(clippy::too_many_arguments)]
 pub fn normalize_percentile_normalize<'py>(
     py: Python<'py>,
     data: Bound<'py, PyAny>,
@@ -188,13 +197,23 @@ pub fn normalize_percentile_normalize<'py>(
         image::percentile_normalize(arr.as_array(), min, max, clip, axis, epsilon, threads)
             .map(|output| output.into_pyarray(py))
             .map_err(map_imgal_error)
+    } else if let Ok(arr) = data.extract::<PyReadonlyArrayDyn<f16>>() {
+        image::percentile_normalize(arr.as_array(), min, max, clip, axis, epsilon, threads)
+            .map(|output| output.into_pyarray(py))
+            .map_err(map_imgal_error)
+    } else if is_bfloat16(&data)
+        && let Ok(arr) = data.extract::<PyReadonlyArrayDyn<bf16>>()
+    {
+        image::percentile_normalize(arr.as_array(), min, max, clip, axis, epsilon, threads)
+            .map(|output| output.into_pyarray(py))
+            .map_err(map_imgal_error)
     } else if let Ok(arr) = data.extract::<PyReadonlyArrayDyn<f64>>() {
         image::percentile_normalize(arr.as_array(), min, max, clip, axis, epsilon, threads)
             .map(|output| output.into_pyarray(py))
             .map_err(map_imgal_error)
     } else {
         Err(PyErr::new::<PyTypeError, _>(
-            "Unsupported array dtype, supported array dtypes are u8, u16, u64, i64, f32, and f64.",
+            "Unsupported array dtype, supported array dtypes are u8, u16, u64, i64, f16, bf16, f32, and f64.",
         ))
     }
 }
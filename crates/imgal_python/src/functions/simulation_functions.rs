@@ -43,6 +43,7 @@ use imgal::simulation;
 #[pyfunction]
 #[pyo3(name = "gaussian_metaballs")]
 #[pyo3(signature = (centers, radii, intensities, falloffs, background, shape, threads=None))]
+#[allow(clippy::too_many_arguments)]
 pub fn blob_gaussian_metaballs<'py>(
     py: Python<'py>,
     centers: Bound<'py, PyAny>,
@@ -170,6 +171,7 @@ pub fn blob_gaussian_metaballs<'py>(
 #[pyfunction]
 #[pyo3(name = "logistic_metaballs")]
 #[pyo3(signature = (centers, radii, intensities, falloffs, background, shape, threads=None))]
+#[allow(clippy::too_many_arguments)]
 pub fn blob_logistic_metaballs<'py>(
     py: Python<'py>,
     centers: Bound<'py, PyAny>,
@@ -303,6 +305,7 @@ pub fn blob_logistic_metaballs<'py>(
 #[pyfunction]
 #[pyo3(name = "gaussian_exponential_decay_1d")]
 #[pyo3(signature = (samples, period, taus, fractions, total_counts, irf_center, irf_width, threads=None))]
+#[allow(clippy::too_many_arguments)]
 pub fn decay_gaussian_exponential_decay_1d(
     py: Python,
     samples: usize,
@@ -373,6 +376,7 @@ pub fn decay_gaussian_exponential_decay_1d(
 #[pyfunction]
 #[pyo3(name = "gaussian_exponential_decay_3d")]
 #[pyo3(signature = (samples, period, taus, fractions, total_counts, irf_center, irf_width, shape, threads=None))]
+#[allow(clippy::too_many_arguments)]
 pub fn decay_gaussian_exponential_decay_3d(
     py: Python,
     samples: usize,
@@ -513,6 +517,7 @@ pub fn decay_ideal_exponential_decay_1d(
 #[pyfunction]
 #[pyo3(name = "ideal_exponential_decay_3d")]
 #[pyo3(signature = (samples, period, taus, fractions, total_counts, shape, threads=None))]
+#[allow(clippy::too_many_arguments)]
 pub fn decay_ideal_exponential_decay_3d(
     py: Python,
     samples: usize,
@@ -577,6 +582,7 @@ pub fn decay_ideal_exponential_decay_3d(
 #[pyfunction]
 #[pyo3(name = "irf_exponential_decay_1d")]
 #[pyo3(signature = (irf, samples, period, taus, fractions, total_counts, threads=None))]
+#[allow(clippy::too_many_arguments)]
 pub fn decay_irf_exponential_decay_1d(
     py: Python,
     irf: Vec<f64>,
@@ -643,6 +649,7 @@ pub fn decay_irf_exponential_decay_1d(
 #[pyfunction]
 #[pyo3(name = "irf_exponential_decay_3d")]
 #[pyo3(signature = (irf, samples, period, taus, fractions, total_counts, shape, threads=None))]
+#[allow(clippy::too_many_arguments)]
 pub fn decay_irf_exponential_decay_3d(
     py: Python,
     irf: Vec<f64>,
@@ -16,6 +16,9 @@ use imgal::spatial::halfspace::{
 };
 use imgal::spatial::{convex_hull, roi};
 
+/// Intersection vertices and the halfspace indices that define each vertex.
+type PyHalfspaceIntersection<'py> = (Bound<'py, PyArray2<f64>>, Bound<'py, PyArray2<usize>>);
+
 /// Create a convex hull from a 2D point cloud using Timothy Chan's algorithm.
 ///
 /// Constructs a 2D convex hull from a 2D point cloud using Timothy Chan's
@@ -407,7 +410,6 @@ pub fn geometry_inside_polyhedron<'py>(
             arr_q.as_array(),
             threads,
         )
-        .map(|output| output)
         .map_err(map_imgal_error)
     } else if let Ok(arr_v) = vertices.extract::<PyReadonlyArray2<u16>>() {
         let arr_c = center.extract::<PyReadonlyArray1<u16>>()?;
@@ -419,7 +421,6 @@ pub fn geometry_inside_polyhedron<'py>(
             arr_q.as_array(),
             threads,
         )
-        .map(|output| output)
         .map_err(map_imgal_error)
     } else if let Ok(arr_v) = vertices.extract::<PyReadonlyArray2<u64>>() {
         let arr_c = center.extract::<PyReadonlyArray1<u64>>()?;
@@ -431,7 +432,6 @@ pub fn geometry_inside_polyhedron<'py>(
             arr_q.as_array(),
             threads,
         )
-        .map(|output| output)
         .map_err(map_imgal_error)
     } else if let Ok(arr_v) = vertices.extract::<PyReadonlyArray2<i64>>() {
         let arr_c = center.extract::<PyReadonlyArray1<i64>>()?;
@@ -443,7 +443,6 @@ pub fn geometry_inside_polyhedron<'py>(
             arr_q.as_array(),
             threads,
         )
-        .map(|output| output)
         .map_err(map_imgal_error)
     } else if let Ok(arr_v) = vertices.extract::<PyReadonlyArray2<f32>>() {
         let arr_c = center.extract::<PyReadonlyArray1<f32>>()?;
@@ -455,7 +454,6 @@ pub fn geometry_inside_polyhedron<'py>(
             arr_q.as_array(),
             threads,
         )
-        .map(|output| output)
         .map_err(map_imgal_error)
     } else if let Ok(arr_v) = vertices.extract::<PyReadonlyArray2<i64>>() {
         let arr_c = center.extract::<PyReadonlyArray1<i64>>()?;
@@ -467,7 +465,6 @@ pub fn geometry_inside_polyhedron<'py>(
             arr_q.as_array(),
             threads,
         )
-        .map(|output| output)
         .map_err(map_imgal_error)
     } else if let Ok(arr_v) = vertices.extract::<PyReadonlyArray2<f64>>() {
         let arr_c = center.extract::<PyReadonlyArray1<f64>>()?;
@@ -479,7 +476,6 @@ pub fn geometry_inside_polyhedron<'py>(
             arr_q.as_array(),
             threads,
         )
-        .map(|output| output)
         .map_err(map_imgal_error)
     } else {
         Err(PyErr::new::<PyTypeError, _>(
@@ -530,7 +526,6 @@ pub fn geometry_inside_tetrahedron<'py>(
             arr_d.as_array(),
             arr_q.as_array(),
         )
-        .map(|output| output)
         .map_err(map_imgal_error)
     } else if let Ok(arr_a) = a.extract::<PyReadonlyArray1<u16>>() {
         let arr_b = b.extract::<PyReadonlyArray1<u16>>()?;
@@ -544,7 +539,6 @@ pub fn geometry_inside_tetrahedron<'py>(
             arr_d.as_array(),
             arr_q.as_array(),
         )
-        .map(|output| output)
         .map_err(map_imgal_error)
     } else if let Ok(arr_a) = a.extract::<PyReadonlyArray1<u64>>() {
         let arr_b = b.extract::<PyReadonlyArray1<u64>>()?;
@@ -558,7 +552,6 @@ pub fn geometry_inside_tetrahedron<'py>(
             arr_d.as_array(),
             arr_q.as_array(),
         )
-        .map(|output| output)
         .map_err(map_imgal_error)
     } else if let Ok(arr_a) = a.extract::<PyReadonlyArray1<i64>>() {
         let arr_b = b.extract::<PyReadonlyArray1<i64>>()?;
@@ -572,7 +565,6 @@ pub fn geometry_inside_tetrahedron<'py>(
             arr_d.as_array(),
             arr_q.as_array(),
         )
-        .map(|output| output)
         .map_err(map_imgal_error)
     } else if let Ok(arr_a) = a.extract::<PyReadonlyArray1<f32>>() {
         let arr_b = b.extract::<PyReadonlyArray1<f32>>()?;
@@ -586,7 +578,6 @@ pub fn geometry_inside_tetrahedron<'py>(
             arr_d.as_array(),
             arr_q.as_array(),
         )
-        .map(|output| output)
         .map_err(map_imgal_error)
     } else if let Ok(arr_a) = a.extract::<PyReadonlyArray1<f64>>() {
         let arr_b = b.extract::<PyReadonlyArray1<f64>>()?;
@@ -600,7 +591,6 @@ pub fn geometry_inside_tetrahedron<'py>(
             arr_d.as_array(),
             arr_q.as_array(),
         )
-        .map(|output| output)
         .map_err(map_imgal_error)
     } else {
         Err(PyErr::new::<PyTypeError, _>(
@@ -646,37 +636,31 @@ pub fn geometry_orient_pred_2d<'py>(
         let arr_a = a.extract::<PyReadonlyArray1<u8>>()?;
         let arr_b = b.extract::<PyReadonlyArray1<u8>>()?;
         orient_pred_2d(arr_o.as_array(), arr_a.as_array(), arr_b.as_array())
-            .map(|output| output)
             .map_err(map_imgal_error)
     } else if let Ok(arr_o) = o.extract::<PyReadonlyArray1<u16>>() {
         let arr_a = a.extract::<PyReadonlyArray1<u16>>()?;
         let arr_b = b.extract::<PyReadonlyArray1<u16>>()?;
         orient_pred_2d(arr_o.as_array(), arr_a.as_array(), arr_b.as_array())
-            .map(|output| output)
             .map_err(map_imgal_error)
     } else if let Ok(arr_o) = o.extract::<PyReadonlyArray1<u64>>() {
         let arr_a = a.extract::<PyReadonlyArray1<u64>>()?;
         let arr_b = b.extract::<PyReadonlyArray1<u64>>()?;
         orient_pred_2d(arr_o.as_array(), arr_a.as_array(), arr_b.as_array())
-            .map(|output| output)
             .map_err(map_imgal_error)
     } else if let Ok(arr_o) = o.extract::<PyReadonlyArray1<i64>>() {
         let arr_a = a.extract::<PyReadonlyArray1<i64>>()?;
         let arr_b = b.extract::<PyReadonlyArray1<i64>>()?;
         orient_pred_2d(arr_o.as_array(), arr_a.as_array(), arr_b.as_array())
-            .map(|output| output)
             .map_err(map_imgal_error)
     } else if let Ok(arr_o) = o.extract::<PyReadonlyArray1<f32>>() {
         let arr_a = a.extract::<PyReadonlyArray1<f32>>()?;
         let arr_b = b.extract::<PyReadonlyArray1<f32>>()?;
         orient_pred_2d(arr_o.as_array(), arr_a.as_array(), arr_b.as_array())
-            .map(|output| output)
             .map_err(map_imgal_error)
     } else if let Ok(arr_o) = o.extract::<PyReadonlyArray1<f64>>() {
         let arr_a = a.extract::<PyReadonlyArray1<f64>>()?;
         let arr_b = b.extract::<PyReadonlyArray1<f64>>()?;
         orient_pred_2d(arr_o.as_array(), arr_a.as_array(), arr_b.as_array())
-            .map(|output| output)
             .map_err(map_imgal_error)
     } else {
         Err(PyErr::new::<PyTypeError, _>(
@@ -731,7 +715,6 @@ pub fn geometry_orient_pred_3d<'py>(
             arr_c.as_array(),
             arr_d.as_array(),
         )
-        .map(|output| output)
         .map_err(map_imgal_error)
     } else if let Ok(arr_a) = a.extract::<PyReadonlyArray1<u16>>() {
         let arr_b = b.extract::<PyReadonlyArray1<u16>>()?;
@@ -743,7 +726,6 @@ pub fn geometry_orient_pred_3d<'py>(
             arr_c.as_array(),
             arr_d.as_array(),
         )
-        .map(|output| output)
         .map_err(map_imgal_error)
     } else if let Ok(arr_a) = a.extract::<PyReadonlyArray1<u64>>() {
         let arr_b = b.extract::<PyReadonlyArray1<u64>>()?;
@@ -755,7 +737,6 @@ pub fn geometry_orient_pred_3d<'py>(
             arr_c.as_array(),
             arr_d.as_array(),
         )
-        .map(|output| output)
         .map_err(map_imgal_error)
     } else if let Ok(arr_a) = a.extract::<PyReadonlyArray1<i64>>() {
         let arr_b = b.extract::<PyReadonlyArray1<i64>>()?;
@@ -767,7 +748,6 @@ pub fn geometry_orient_pred_3d<'py>(
             arr_c.as_array(),
             arr_d.as_array(),
         )
-        .map(|output| output)
         .map_err(map_imgal_error)
     } else if let Ok(arr_a) = a.extract::<PyReadonlyArray1<f32>>() {
         let arr_b = b.extract::<PyReadonlyArray1<f32>>()?;
@@ -779,7 +759,6 @@ pub fn geometry_orient_pred_3d<'py>(
             arr_c.as_array(),
             arr_d.as_array(),
         )
-        .map(|output| output)
         .map_err(map_imgal_error)
     } else if let Ok(arr_a) = a.extract::<PyReadonlyArray1<f64>>() {
         let arr_b = b.extract::<PyReadonlyArray1<f64>>()?;
@@ -791,7 +770,6 @@ pub fn geometry_orient_pred_3d<'py>(
             arr_c.as_array(),
             arr_d.as_array(),
         )
-        .map(|output| output)
         .map_err(map_imgal_error)
     } else {
         Err(PyErr::new::<PyTypeError, _>(
@@ -838,32 +816,25 @@ pub fn geometry_polyhedron_volume<'py>(
     if let Ok(arr_v) = vertices.extract::<PyReadonlyArray2<u8>>() {
         let apex = apex.map(|v| v.into_iter().map(|e| e as u8).collect::<Vec<u8>>());
         polyhedron_volume(arr_v.as_array(), arr_f.as_array(), apex.as_ref(), threads)
-            .map(|output| output)
             .map_err(map_imgal_error)
     } else if let Ok(arr_v) = vertices.extract::<PyReadonlyArray2<u16>>() {
         let apex = apex.map(|v| v.into_iter().map(|e| e as u16).collect::<Vec<u16>>());
         polyhedron_volume(arr_v.as_array(), arr_f.as_array(), apex.as_ref(), threads)
-            .map(|output| output)
             .map_err(map_imgal_error)
     } else if let Ok(arr_v) = vertices.extract::<PyReadonlyArray2<u64>>() {
         let apex = apex.map(|v| v.into_iter().map(|e| e as u64).collect::<Vec<u64>>());
         polyhedron_volume(arr_v.as_array(), arr_f.as_array(), apex.as_ref(), threads)
-            .map(|output| output)
             .map_err(map_imgal_error)
     } else if let Ok(arr_v) = vertices.extract::<PyReadonlyArray2<i64>>() {
         let apex = apex.map(|v| v.into_iter().map(|e| e as i64).collect::<Vec<i64>>());
         polyhedron_volume(arr_v.as_array(), arr_f.as_array(), apex.as_ref(), threads)
-            .map(|output| output)
             .map_err(map_imgal_error)
     } else if let Ok(arr_v) = vertices.extract::<PyReadonlyArray2<f32>>() {
         let apex = apex.map(|v| v.into_iter().map(|e| e as f32).collect::<Vec<f32>>());
         polyhedron_volume(arr_v.as_array(), arr_f.as_array(), apex.as_ref(), threads)
-            .map(|output| output)
             .map_err(map_imgal_error)
     } else if let Ok(arr_v) = vertices.extract::<PyReadonlyArray2<f64>>() {
-        let apex = apex.map(|v| v.into_iter().map(|e| e as f64).collect::<Vec<f64>>());
         polyhedron_volume(arr_v.as_array(), arr_f.as_array(), apex.as_ref(), threads)
-            .map(|output| output)
             .map_err(map_imgal_error)
     } else {
         Err(PyErr::new::<PyTypeError, _>(
@@ -919,7 +890,6 @@ pub fn geometry_tetrahedron_volume<'py>(
             arr_c.as_array(),
             arr_d.as_array(),
         )
-        .map(|output| output)
         .map_err(map_imgal_error)
     } else if let Ok(arr_a) = a.extract::<PyReadonlyArray1<u16>>() {
         let arr_b = b.extract::<PyReadonlyArray1<u16>>()?;
@@ -931,7 +901,6 @@ pub fn geometry_tetrahedron_volume<'py>(
             arr_c.as_array(),
             arr_d.as_array(),
         )
-        .map(|output| output)
         .map_err(map_imgal_error)
     } else if let Ok(arr_a) = a.extract::<PyReadonlyArray1<u64>>() {
         let arr_b = b.extract::<PyReadonlyArray1<u64>>()?;
@@ -943,7 +912,6 @@ pub fn geometry_tetrahedron_volume<'py>(
             arr_c.as_array(),
             arr_d.as_array(),
         )
-        .map(|output| output)
         .map_err(map_imgal_error)
     } else if let Ok(arr_a) = a.extract::<PyReadonlyArray1<i64>>() {
         let arr_b = b.extract::<PyReadonlyArray1<i64>>()?;
@@ -955,7 +923,6 @@ pub fn geometry_tetrahedron_volume<'py>(
             arr_c.as_array(),
             arr_d.as_array(),
         )
-        .map(|output| output)
         .map_err(map_imgal_error)
     } else if let Ok(arr_a) = a.extract::<PyReadonlyArray1<f32>>() {
         let arr_b = b.extract::<PyReadonlyArray1<f32>>()?;
@@ -967,7 +934,6 @@ pub fn geometry_tetrahedron_volume<'py>(
             arr_c.as_array(),
             arr_d.as_array(),
         )
-        .map(|output| output)
         .map_err(map_imgal_error)
     } else if let Ok(arr_a) = a.extract::<PyReadonlyArray1<f64>>() {
         let arr_b = b.extract::<PyReadonlyArray1<f64>>()?;
@@ -979,7 +945,6 @@ pub fn geometry_tetrahedron_volume<'py>(
             arr_c.as_array(),
             arr_d.as_array(),
         )
-        .map(|output| output)
         .map_err(map_imgal_error)
     } else {
         Err(PyErr::new::<PyTypeError, _>(
@@ -1092,7 +1057,7 @@ pub fn halfspace_halfspace_intersection<'py>(
     halfspaces: PyReadonlyArray2<f64>,
     interior_point: Bound<'py, PyAny>,
     threads: Option<usize>,
-) -> PyResult<(Bound<'py, PyArray2<f64>>, Bound<'py, PyArray2<usize>>)> {
+) -> PyResult<PyHalfspaceIntersection<'py>> {
     if let Ok(arr_ip) = interior_point.extract::<PyReadonlyArray1<u8>>() {
         halfspace_intersection(halfspaces.as_array(), arr_ip.as_array(), threads)
             .map(|output| (output.0.into_pyarray(py), output.1.into_pyarray(py)))
@@ -1233,7 +1198,6 @@ pub fn halfspace_inside_halfspace_inerior<'py>(
             include_boundary,
             threads,
         )
-        .map(|output| output)
         .map_err(map_imgal_error)
     } else if let Ok(arr_q) = query.extract::<PyReadonlyArray1<u16>>() {
         inside_halfspace_interior(
@@ -1242,7 +1206,6 @@ pub fn halfspace_inside_halfspace_inerior<'py>(
             include_boundary,
             threads,
         )
-        .map(|output| output)
         .map_err(map_imgal_error)
     } else if let Ok(arr_q) = query.extract::<PyReadonlyArray1<u64>>() {
         inside_halfspace_interior(
@@ -1251,7 +1214,6 @@ pub fn halfspace_inside_halfspace_inerior<'py>(
             include_boundary,
             threads,
         )
-        .map(|output| output)
         .map_err(map_imgal_error)
     } else if let Ok(arr_q) = query.extract::<PyReadonlyArray1<i64>>() {
         inside_halfspace_interior(
@@ -1260,7 +1222,6 @@ pub fn halfspace_inside_halfspace_inerior<'py>(
             include_boundary,
             threads,
         )
-        .map(|output| output)
         .map_err(map_imgal_error)
     } else if let Ok(arr_q) = query.extract::<PyReadonlyArray1<f32>>() {
         inside_halfspace_interior(
@@ -1269,7 +1230,6 @@ pub fn halfspace_inside_halfspace_inerior<'py>(
             include_boundary,
             threads,
         )
-        .map(|output| output)
         .map_err(map_imgal_error)
     } else if let Ok(arr_q) = query.extract::<PyReadonlyArray1<f64>>() {
         inside_halfspace_interior(
@@ -1278,7 +1238,6 @@ pub fn halfspace_inside_halfspace_inerior<'py>(
             include_boundary,
             threads,
         )
-        .map(|output| output)
         .map_err(map_imgal_error)
     } else {
         Err(PyErr::new::<PyTypeError, _>(
@@ -1,8 +1,10 @@
+use half::{bf16, f16};
 use numpy::{IntoPyArray, PyArrayDyn, PyReadonlyArrayDyn, PyReadwriteArray1};
 use pyo3::exceptions::PyTypeError;
 use pyo3::prelude::*;
 
 use crate::error::map_imgal_error;
+use crate::utils::is_bfloat16;
 use imgal::statistics;
 
 /// Compute the effective sample size (ESS) of a weighted sample set.
@@ -30,12 +32,16 @@ pub fn statistics_effective_sample_size(weights: Vec<f64>) -> f64 {
 ///
 /// Computes the Kahan sum of an n-dimensional image. The Kahan compensated
 /// summation algorithm corrects for floating-point rounding errors and
-/// precision loss at each step of the summation. To compensate for
-/// floating-point error an error residual is subtracted from each value per
-/// iteration.
+/// precision loss at each step of the summation. The Neumaier variant is used
+/// and with parallel execution the per-thread compensated sums are merged with
+/// the same compensated addition.
 ///
 /// Args:
 ///     data: The input n-dimensional image.
+///     threads: The requested number of threads to use for parallel execution.
+///         If `None` or `1` sequential execution is used. If `0`, then the
+///         maximum available parallelism is used. Thread counts are clamped to
+///         the systems maximum.
 ///
 /// Returns:
 ///     The Kahan sum.
@@ -44,31 +50,28 @@ pub fn statistics_effective_sample_size(weights: Vec<f64>) -> f64 {
 ///     If `data` is empty.
 #[pyfunction]
 #[pyo3(name = "kahan_sum")]
-pub fn statistics_kahan_sum<'py>(data: Bound<'py, PyAny>) -> PyResult<f64> {
+#[pyo3(signature = (data, threads=None))]
+pub fn statistics_kahan_sum<'py>(data: Bound<'py, PyAny>, threads: Option<usize>) -> PyResult<f64> {
     if let Ok(arr) = data.extract::<PyReadonlyArrayDyn<u8>>() {
-        statistics::kahan_sum(arr.as_array())
+        statistics::kahan_sum(arr.as_array(), threads)
             .map(|output| output as f64)
             .map_err(map_imgal_error)
     } else if let Ok(arr) = data.extract::<PyReadonlyArrayDyn<u16>>() {
-        statistics::kahan_sum(arr.as_array())
+        statistics::kahan_sum(arr.as_array(), threads)
             .map(|output| output as f64)
             .map_err(map_imgal_error)
     } else if let Ok(arr) = data.extract::<PyReadonlyArrayDyn<u64>>() {
-        statistics::kahan_sum(arr.as_array())
+        statistics::kahan_sum(arr.as_array(), threads)
             .map(|output| output as f64)
             .map_err(map_imgal_error)
     } else if let Ok(arr) = data.extract::<PyReadonlyArrayDyn<i64>>() {
-        statistics::kahan_sum(arr.as_array())
+        statistics::kahan_sum(arr.as_array(), threads)
             .map(|output| output as f64)
             .map_err(map_imgal_error)
     } else if let Ok(arr) = data.extract::<PyReadonlyArrayDyn<f32>>() {
-        statistics::kahan_sum(arr.as_array())
-            .map(|output| output as f64)
-            .map_err(map_imgal_error)
+        statistics::kahan_sum(arr.as_array(), threads).map_err(map_imgal_error)
     } else if let Ok(arr) = data.extract::<PyReadonlyArrayDyn<f64>>() {
-        statistics::kahan_sum(arr.as_array())
-            .map(|output| output)
-            .map_err(map_imgal_error)
+        statistics::kahan_sum(arr.as_array(), threads).map_err(map_imgal_error)
     } else {
         Err(PyErr::new::<PyTypeError, _>(
             "Unsupported array dtype, supported array dtypes are u8, u16, u64, i64, f32, and f64.",
@@ -151,13 +154,23 @@ pub fn statistics_linear_percentile<'py>(
         statistics::linear_percentile(arr.as_array(), p, axis, epsilon, threads)
             .map(|output| output.into_pyarray(py))
             .map_err(map_imgal_error)
+    } else if let Ok(arr) = data.extract::<PyReadonlyArrayDyn<f16>>() {
+        statistics::linear_percentile(arr.as_array(), p, axis, epsilon, threads)
+            .map(|output| output.into_pyarray(py))
+            .map_err(map_imgal_error)
+    } else if is_bfloat16(&data)
+        && let Ok(arr) = data.extract::<PyReadonlyArrayDyn<bf16>>()
+    {
+        statistics::linear_percentile(arr.as_array(), p, axis, epsilon, threads)
+            .map(|output| output.into_pyarray(py))
+            .map_err(map_imgal_error)
     } else if let Ok(arr) = data.extract::<PyReadonlyArrayDyn<f64>>() {
         statistics::linear_percentile(arr.as_array(), p, axis, epsilon, threads)
             .map(|output| output.into_pyarray(py))
             .map_err(map_imgal_error)
     } else {
         Err(PyErr::new::<PyTypeError, _>(
-            "Unsupported array dtype, supported array dtypes are u8, u16, u64, i64, f32, and f64.",
+            "Unsupported array dtype, supported array dtypes are u8, u16, u64, i64, f16, bf16, f32, and f64.",
         ))
     }
 }
@@ -203,13 +216,21 @@ pub fn statistics_max<'py>(data: Bound<'py, PyAny>, threads: Option<usize>) -> P
         statistics::max(arr.as_array(), threads)
             .map(|output| output as f64)
             .map_err(map_imgal_error)
-    } else if let Ok(arr) = data.extract::<PyReadonlyArrayDyn<f64>>() {
+    } else if let Ok(arr) = data.extract::<PyReadonlyArrayDyn<f16>>() {
+        statistics::max(arr.as_array(), threads)
+            .map(f64::from)
+            .map_err(map_imgal_error)
+    } else if is_bfloat16(&data)
+        && let Ok(arr) = data.extract::<PyReadonlyArrayDyn<bf16>>()
+    {
         statistics::max(arr.as_array(), threads)
-            .map(|output| output)
+            .map(f64::from)
             .map_err(map_imgal_error)
+    } else if let Ok(arr) = data.extract::<PyReadonlyArrayDyn<f64>>() {
+        statistics::max(arr.as_array(), threads).map_err(map_imgal_error)
     } else {
         Err(PyErr::new::<PyTypeError, _>(
-            "Unsupported array dtype, supported array dtypes are u8, u16, u64, i64, f32, and f64.",
+            "Unsupported array dtype, supported array dtypes are u8, u16, u64, i64, f16, bf16, f32, and f64.",
         ))
     }
 }
@@ -255,13 +276,21 @@ pub fn statistics_min<'py>(data: Bound<'py, PyAny>, threads: Option<usize>) -> P
         statistics::min(arr.as_array(), threads)
             .map(|output| output as f64)
             .map_err(map_imgal_error)
-    } else if let Ok(arr) = data.extract::<PyReadonlyArrayDyn<f64>>() {
+    } else if let Ok(arr) = data.extract::<PyReadonlyArrayDyn<f16>>() {
+        statistics::min(arr.as_array(), threads)
+            .map(f64::from)
+            .map_err(map_imgal_error)
+    } else if is_bfloat16(&data)
+        && let Ok(arr) = data.extract::<PyReadonlyArrayDyn<bf16>>()
+    {
         statistics::min(arr.as_array(), threads)
-            .map(|output| output)
+            .map(f64::from)
             .map_err(map_imgal_error)
+    } else if let Ok(arr) = data.extract::<PyReadonlyArrayDyn<f64>>() {
+        statistics::min(arr.as_array(), threads).map_err(map_imgal_error)
     } else {
         Err(PyErr::new::<PyTypeError, _>(
-            "Unsupported array dtype, supported array dtypes are u8, u16, u64, i64, f32, and f64.",
+            "Unsupported array dtype, supported array dtypes are u8, u16, u64, i64, f16, bf16, f32, and f64.",
         ))
     }
 }
@@ -311,13 +340,21 @@ pub fn statistics_min_max<'py>(
         statistics::min_max(arr.as_array(), threads)
             .map(|output| (output.0 as f64, output.1 as f64))
             .map_err(map_imgal_error)
-    } else if let Ok(arr) = data.extract::<PyReadonlyArrayDyn<f64>>() {
+    } else if let Ok(arr) = data.extract::<PyReadonlyArrayDyn<f16>>() {
         statistics::min_max(arr.as_array(), threads)
-            .map(|output| output)
+            .map(|output| (f64::from(output.0), f64::from(output.1)))
             .map_err(map_imgal_error)
+    } else if is_bfloat16(&data)
+        && let Ok(arr) = data.extract::<PyReadonlyArrayDyn<bf16>>()
+    {
+        statistics::min_max(arr.as_array(), threads)
+            .map(|output| (f64::from(output.0), f64::from(output.1)))
+            .map_err(map_imgal_error)
+    } else if let Ok(arr) = data.extract::<PyReadonlyArrayDyn<f64>>() {
+        statistics::min_max(arr.as_array(), threads).map_err(map_imgal_error)
     } else {
         Err(PyErr::new::<PyTypeError, _>(
-            "Unsupported array dtype, supported array dtypes are u8, u16, u64, i64, f32, and f64.",
+            "Unsupported array dtype, supported array dtypes are u8, u16, u64, i64, f16, bf16, f32, and f64.",
         ))
     }
 }
@@ -357,14 +394,54 @@ pub fn statistics_pearson(
     data_b: Vec<f64>,
     threads: Option<usize>,
 ) -> PyResult<f64> {
-    statistics::pearson(&data_a, &data_b, threads)
-        .map(|output| output)
-        .map_err(map_imgal_error)
+    statistics::pearson(&data_a, &data_b, threads).map_err(map_imgal_error)
+}
+
+/// Compute the Spearman rank correlation coefficient between two 1D arrays.
+///
+/// Computes the Spearman rank correlation coefficient, a measure of the
+/// monotonic (not necessarily linear) correlation between two sets of 1D
+/// data. The values of each array are rank transformed, where tied values are
+/// assigned the average of their ranks, and the Pearson correlation
+/// coefficient of the ranks is computed:
+///
+/// ```text
+/// ρ = pearson(rank(a), rank(b))
+/// ```
+///
+/// Args:
+///     data_a: The first array for correlation analysis.
+///     data_b: The second array for correlation analysis.
+///     threads: The requested number of threads to use for parallel execution.
+///         If `None` or `1` sequential execution is used. If `0`, then the
+///         maximum available parallelism is used. Thread counts are clamped to
+///         the systems maximum.
+///
+/// Returns:
+///     Spearman's rank correlation coefficient ranging between `-1.0` (perfect
+///     negative monotonic correlation), `0.0` (no correlation), and `1.0`
+///     (perfect positive monotonic correlation).
+///
+/// Errors:
+///     If `len(data_a) != len(data_b)`. If `len(data_a)` or `len(data_b)` is <=
+///     2. If all values of an array are tied.
+#[pyfunction]
+#[pyo3(name = "spearman_correlation")]
+#[pyo3(signature = (data_a, data_b, threads=None))]
+pub fn statistics_spearman_correlation(
+    data_a: Vec<f64>,
+    data_b: Vec<f64>,
+    threads: Option<usize>,
+) -> PyResult<f64> {
+    statistics::spearman_correlation(&data_a, &data_b, threads).map_err(map_imgal_error)
 }
 
 /// Compute the sum of an n-dimensional image.
 ///
-/// Computes the sum of numerical values in an n-dimensional image.
+/// Computes the sum of numerical values in an n-dimensional image. Integer
+/// values are accumulated exactly in 128-bit precision and floating point
+/// values in `f64` precision, so that the sum can not overflow the range of the
+/// image dtype.
 ///
 /// Args:
 ///     data: The input n-dimensional image.
@@ -388,7 +465,7 @@ pub fn statistics_sum<'py>(data: Bound<'py, PyAny>, threads: Option<usize>) -> P
     } else if let Ok(arr) = data.extract::<PyReadonlyArrayDyn<i64>>() {
         Ok(statistics::sum(arr.as_array(), threads) as f64)
     } else if let Ok(arr) = data.extract::<PyReadonlyArrayDyn<f32>>() {
-        Ok(statistics::sum(arr.as_array(), threads) as f64)
+        Ok(statistics::sum(arr.as_array(), threads))
     } else if let Ok(arr) = data.extract::<PyReadonlyArrayDyn<f64>>() {
         Ok(statistics::sum(arr.as_array(), threads))
     } else {
@@ -439,9 +516,7 @@ pub fn statistics_weighted_kendall_tau_b(
     data_b: Vec<f64>,
     weights: Vec<f64>,
 ) -> PyResult<f64> {
-    statistics::weighted_kendall_tau_b(&data_a, &data_b, &weights)
-        .map(|output| output)
-        .map_err(map_imgal_error)
+    statistics::weighted_kendall_tau_b(&data_a, &data_b, &weights).map_err(map_imgal_error)
 }
 
 /// Sort 1D arrays of values and their associated weights.
@@ -474,42 +549,36 @@ pub fn statistics_weighted_merge_sort_mut<'py>(
             d.as_slice_mut().unwrap(),
             weights.as_slice_mut().unwrap(),
         )
-        .map(|output| output)
         .map_err(map_imgal_error)
     } else if let Ok(mut d) = data.extract::<PyReadwriteArray1<u16>>() {
         statistics::weighted_merge_sort_mut(
             d.as_slice_mut().unwrap(),
             weights.as_slice_mut().unwrap(),
         )
-        .map(|output| output)
         .map_err(map_imgal_error)
     } else if let Ok(mut d) = data.extract::<PyReadwriteArray1<u64>>() {
         statistics::weighted_merge_sort_mut(
             d.as_slice_mut().unwrap(),
             weights.as_slice_mut().unwrap(),
         )
-        .map(|output| output)
         .map_err(map_imgal_error)
     } else if let Ok(mut d) = data.extract::<PyReadwriteArray1<i64>>() {
         statistics::weighted_merge_sort_mut(
             d.as_slice_mut().unwrap(),
             weights.as_slice_mut().unwrap(),
         )
-        .map(|output| output)
         .map_err(map_imgal_error)
     } else if let Ok(mut d) = data.extract::<PyReadwriteArray1<f32>>() {
         statistics::weighted_merge_sort_mut(
             d.as_slice_mut().unwrap(),
             weights.as_slice_mut().unwrap(),
         )
-        .map(|output| output)
         .map_err(map_imgal_error)
     } else if let Ok(mut d) = data.extract::<PyReadwriteArray1<f64>>() {
         statistics::weighted_merge_sort_mut(
             d.as_slice_mut().unwrap(),
             weights.as_slice_mut().unwrap(),
         )
-        .map(|output| output)
         .map_err(map_imgal_error)
     } else {
         Err(PyErr::new::<pyo3::exceptions::PyTypeError, _>(
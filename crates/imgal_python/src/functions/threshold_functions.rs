@@ -1,8 +1,10 @@
+use half::{bf16, f16};
 use numpy::{IntoPyArray, PyArrayDyn, PyReadonlyArrayDyn};
 use pyo3::exceptions::PyTypeError;
 use pyo3::prelude::*;
 
 use crate::error::map_imgal_error;
+use crate::utils::is_bfloat16;
 use imgal::threshold::{global, manual};
 
 /// Create a boolean mask using Otsu's method.
@@ -60,13 +62,23 @@ pub fn global_otsu_mask<'py>(
         global::otsu_mask(arr.as_array(), bins, threads)
             .map(|output| output.into_pyarray(py))
             .map_err(map_imgal_error)
+    } else if let Ok(arr) = data.extract::<PyReadonlyArrayDyn<f16>>() {
+        global::otsu_mask(arr.as_array(), bins, threads)
+            .map(|output| output.into_pyarray(py))
+            .map_err(map_imgal_error)
+    } else if is_bfloat16(&data)
+        && let Ok(arr) = data.extract::<PyReadonlyArrayDyn<bf16>>()
+    {
+        global::otsu_mask(arr.as_array(), bins, threads)
+            .map(|output| output.into_pyarray(py))
+            .map_err(map_imgal_error)
     } else if let Ok(arr) = data.extract::<PyReadonlyArrayDyn<f64>>() {
         global::otsu_mask(arr.as_array(), bins, threads)
             .map(|output| output.into_pyarray(py))
             .map_err(map_imgal_error)
     } else {
         Err(PyErr::new::<PyTypeError, _>(
-            "Unsupported array dtype, supported array dtypes are u8, u16, u64, i64, f32, and f64.",
+            "Unsupported array dtype, supported array dtypes are u8, u16, u64, i64, f16, bf16, f32, and f64.",
         ))
     }
 }
@@ -123,13 +135,21 @@ pub fn global_otsu_value<'py>(
         global::otsu_value(arr.as_array(), bins, threads)
             .map(|output| output as f64)
             .map_err(map_imgal_error)
-    } else if let Ok(arr) = data.extract::<PyReadonlyArrayDyn<f64>>() {
+    } else if let Ok(arr) = data.extract::<PyReadonlyArrayDyn<f16>>() {
         global::otsu_value(arr.as_array(), bins, threads)
-            .map(|output| output as f64)
+            .map(f64::from)
             .map_err(map_imgal_error)
+    } else if is_bfloat16(&data)
+        && let Ok(arr) = data.extract::<PyReadonlyArrayDyn<bf16>>()
+    {
+        global::otsu_value(arr.as_array(), bins, threads)
+            .map(f64::from)
+            .map_err(map_imgal_error)
+    } else if let Ok(arr) = data.extract::<PyReadonlyArrayDyn<f64>>() {
+        global::otsu_value(arr.as_array(), bins, threads).map_err(map_imgal_error)
     } else {
         Err(PyErr::new::<PyTypeError, _>(
-            "Unsupported array dtype, supported array dtypes are u8, u16, u64, i64, f32, and f64.",
+            "Unsupported array dtype, supported array dtypes are u8, u16, u64, i64, f16, bf16, f32, and f64.",
         ))
     }
 }
@@ -170,11 +190,20 @@ pub fn manual_manual_mask<'py>(
         Ok(manual::manual_mask(arr.as_array(), threshold as i64, threads).into_pyarray(py))
     } else if let Ok(arr) = data.extract::<PyReadonlyArrayDyn<f32>>() {
         Ok(manual::manual_mask(arr.as_array(), threshold as f32, threads).into_pyarray(py))
+    } else if let Ok(arr) = data.extract::<PyReadonlyArrayDyn<f16>>() {
+        Ok(manual::manual_mask(arr.as_array(), f16::from_f64(threshold), threads).into_pyarray(py))
+    } else if is_bfloat16(&data)
+        && let Ok(arr) = data.extract::<PyReadonlyArrayDyn<bf16>>()
+    {
+        Ok(
+            manual::manual_mask(arr.as_array(), bf16::from_f64(threshold), threads)
+                .into_pyarray(py),
+        )
     } else if let Ok(arr) = data.extract::<PyReadonlyArrayDyn<f64>>() {
         Ok(manual::manual_mask(arr.as_array(), threshold, threads).into_pyarray(py))
     } else {
         Err(PyErr::new::<PyTypeError, _>(
-            "Unsupported array dtype, supported array dtypes are u8, u16, u64, i64, f32, and f64.",
+            "Unsupported array dtype, supported array dtypes are u8, u16, u64, i64, f16, bf16, f32, and f64.",
         ))
     }
 }
@@ -23,3 +23,22 @@ pub fn py_import_module(module_name: &str) {
         py.run(c_str_cmd.as_c_str(), None, None).unwrap();
     });
 }
+
+/// Check if a Python object is a NumPy array with the `bfloat16` dtype.
+///
+/// # Description
+///
+/// NumPy has no native `bfloat16` dtype, it is registered by third party
+/// packages such as `ml_dtypes`. Extracting a `half::bf16` array looks up the
+/// registered dtype and panics if no package provides it, so the dtype name of
+/// the input is checked before attempting the extraction.
+///
+/// # Arguments
+///
+/// * `data` - The Python object to check.
+pub fn is_bfloat16(data: &Bound<'_, PyAny>) -> bool {
+    data.getattr("dtype")
+        .and_then(|dt| dt.getattr("name"))
+        .and_then(|name| name.extract::<String>())
+        .is_ok_and(|name| name == "bfloat16")
+}